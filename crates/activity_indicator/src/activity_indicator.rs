@@ -12,6 +12,7 @@ use lsp::LanguageServerName;
 use project::{EnvironmentErrorMessage, LanguageServerProgress, Project, WorktreeId};
 use smallvec::SmallVec;
 use std::{cmp::Reverse, fmt::Write, sync::Arc, time::Duration};
+use terminal::BackgroundTaskStatus;
 use ui::{prelude::*, ButtonLike, ContextMenu, PopoverMenu, PopoverMenuHandle, Tooltip};
 use util::truncate_and_trailoff;
 use workspace::{item::ItemHandle, StatusItemView, Workspace};
@@ -254,6 +255,36 @@ impl ActivityIndicator {
             });
         }
 
+        // Show any background task that is not ready.
+        let mut pending_background_tasks = self.project.read(cx).pending_background_tasks(cx);
+        if let Some((label, status)) = pending_background_tasks.next() {
+            let mut message = match status {
+                BackgroundTaskStatus::Rebuilding => format!("Rebuilding {label}..."),
+                BackgroundTaskStatus::Starting | BackgroundTaskStatus::Ready => {
+                    format!("Starting {label}...")
+                }
+            };
+            let additional_task_count = pending_background_tasks.count();
+            if additional_task_count > 0 {
+                write!(&mut message, " + {} more", additional_task_count).unwrap();
+            }
+
+            return Some(Content {
+                icon: Some(
+                    Icon::new(IconName::ArrowCircle)
+                        .size(IconSize::Small)
+                        .with_animation(
+                            "arrow-circle",
+                            Animation::new(Duration::from_secs(2)).repeat(),
+                            |icon, delta| icon.transform(Transformation::rotate(percentage(delta))),
+                        )
+                        .into_any_element(),
+                ),
+                message,
+                on_click: None,
+            });
+        }
+
         // Show any language server installation info.
         let mut downloading = SmallVec::<[_; 3]>::new();
         let mut checking_for_update = SmallVec::<[_; 3]>::new();
@@ -0,0 +1,459 @@
+use editor::{actions::SetBookmark, scroll::Autoscroll, Editor, EditorEvent, EditorMode};
+use fuzzy::{StringMatch, StringMatchCandidate};
+use gpui::{
+    actions, rems, App, Context, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable,
+    Render, Subscription, Task, WeakEntity, Window,
+};
+use language::{Anchor, Buffer, Point, ToPoint as _};
+use picker::{Picker, PickerDelegate};
+use project::Project;
+use std::sync::Arc;
+use ui::{prelude::*, HighlightedLabel, ListItem, ListItemSpacing};
+use util::ResultExt;
+use workspace::{ModalView, Workspace};
+
+actions!(bookmarks, [Toggle, NameBookmark]);
+
+pub fn init(cx: &mut App) {
+    cx.observe_new(
+        |workspace: &mut Workspace, _window, _: &mut Context<Workspace>| {
+            workspace.register_action(|workspace, _: &Toggle, window, cx| {
+                let project = workspace.project().clone();
+                // The bookmarks of the files that aren't open are only anchored once their buffers are opened.
+                let open_closed_files = project
+                    .read(cx)
+                    .bookmark_store()
+                    .update(cx, |store, cx| store.open_closed_files(cx));
+                cx.spawn_in(window, |workspace, mut cx| async move {
+                    let _buffers = open_closed_files.await;
+                    workspace.update_in(&mut cx, |workspace, window, cx| {
+                        let handle = cx.entity().downgrade();
+                        workspace.toggle_modal(window, cx, move |window, cx| {
+                            let delegate = BookmarksDelegate::new(handle, &project, cx);
+                            Picker::uniform_list(delegate, window, cx).width(rems(34.))
+                        })
+                    })
+                })
+                .detach_and_log_err(cx);
+            });
+        },
+    )
+    .detach();
+    cx.observe_new(NameBookmarkModal::register).detach();
+}
+
+pub type Bookmarks = Entity<Picker<BookmarksDelegate>>;
+
+struct BookmarkEntry {
+    buffer: Entity<Buffer>,
+    position: Anchor,
+    /// The bookmark's label, or the text of its line for anonymous bookmarks.
+    label: SharedString,
+    /// The bookmark's path and line number.
+    location: SharedString,
+}
+
+pub struct BookmarksDelegate {
+    workspace: WeakEntity<Workspace>,
+    entries: Vec<BookmarkEntry>,
+    candidates: Vec<StringMatchCandidate>,
+    matches: Vec<StringMatch>,
+    selected_match_index: usize,
+}
+
+impl BookmarksDelegate {
+    fn new(workspace: WeakEntity<Workspace>, project: &Entity<Project>, cx: &App) -> Self {
+        let entries = project
+            .read(cx)
+            .bookmark_store()
+            .read(cx)
+            .all_bookmarks()
+            .map(|(buffer, bookmark)| {
+                let snapshot = buffer.read(cx).snapshot();
+                let row = bookmark.row(&snapshot);
+                let label = bookmark.label.clone().unwrap_or_else(|| {
+                    snapshot
+                        .text_for_range(Point::new(row, 0)..Point::new(row, snapshot.line_len(row)))
+                        .collect::<String>()
+                        .trim()
+                        .to_string()
+                        .into()
+                });
+                let path = buffer.read(cx).file().map_or_else(
+                    || "untitled".to_string(),
+                    |file| file.full_path(cx).to_string_lossy().into_owned(),
+                );
+                BookmarkEntry {
+                    buffer,
+                    position: bookmark.position,
+                    label,
+                    location: format!("{path}:{}", row + 1).into(),
+                }
+            })
+            .collect::<Vec<_>>();
+        let candidates = entries
+            .iter()
+            .enumerate()
+            .map(|(id, entry)| {
+                StringMatchCandidate::new(id, &format!("{} {}", entry.label, entry.location))
+            })
+            .collect();
+
+        Self {
+            workspace,
+            entries,
+            candidates,
+            matches: Vec::new(),
+            selected_match_index: 0,
+        }
+    }
+}
+
+impl PickerDelegate for BookmarksDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _window: &mut Window, _cx: &mut App) -> Arc<str> {
+        "Search bookmarks...".into()
+    }
+
+    fn no_matches_text(&self, _window: &mut Window, _cx: &mut App) -> SharedString {
+        if self.entries.is_empty() {
+            "No bookmarks in this project".into()
+        } else {
+            "No matches".into()
+        }
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_match_index
+    }
+
+    fn set_selected_index(
+        &mut self,
+        ix: usize,
+        _window: &mut Window,
+        _cx: &mut Context<Picker<Self>>,
+    ) {
+        self.selected_match_index = ix;
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        _window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Task<()> {
+        self.matches = if query.is_empty() {
+            self.candidates
+                .iter()
+                .map(|candidate| StringMatch {
+                    candidate_id: candidate.id,
+                    score: 0.,
+                    positions: Vec::new(),
+                    string: candidate.string.clone(),
+                })
+                .collect()
+        } else {
+            cx.background_executor().block(fuzzy::match_strings(
+                &self.candidates,
+                &query,
+                false,
+                100,
+                &Default::default(),
+                cx.background_executor().clone(),
+            ))
+        };
+        self.selected_match_index = 0;
+        Task::ready(())
+    }
+
+    fn confirm(&mut self, secondary: bool, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let Some(entry) = self
+            .matches
+            .get(self.selected_match_index)
+            .map(|mat| &self.entries[mat.candidate_id])
+        else {
+            return;
+        };
+        let buffer = entry.buffer.clone();
+        let position = entry.position;
+        self.workspace
+            .update(cx, |workspace, cx| {
+                let pane = if secondary {
+                    workspace.adjacent_pane(window, cx)
+                } else {
+                    workspace.active_pane().clone()
+                };
+                let point = position.to_point(&buffer.read(cx).snapshot());
+                let editor =
+                    workspace.open_project_item::<Editor>(pane, buffer, true, true, window, cx);
+                editor.update(cx, |editor, cx| {
+                    editor.change_selections(Some(Autoscroll::center()), window, cx, |s| {
+                        s.select_ranges([point..point])
+                    });
+                });
+            })
+            .log_err();
+        cx.emit(DismissEvent);
+    }
+
+    fn dismissed(&mut self, _window: &mut Window, _cx: &mut Context<Picker<Self>>) {}
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _window: &mut Window,
+        _cx: &mut Context<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let mat = self.matches.get(ix)?;
+        let entry = self.entries.get(mat.candidate_id)?;
+
+        // Candidates are the label and the location separated by a space.
+        let location_start = entry.label.len() + 1;
+        let (label_positions, location_positions): (Vec<_>, Vec<_>) = mat
+            .positions
+            .iter()
+            .copied()
+            .partition(|position| *position < location_start);
+        let location_positions = location_positions
+            .into_iter()
+            .map(|position| position - location_start)
+            .collect();
+
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .toggle_state(selected)
+                .start_slot(
+                    Icon::new(IconName::Bookmark)
+                        .size(IconSize::Small)
+                        .color(Color::Muted),
+                )
+                .child(
+                    v_flex()
+                        .child(HighlightedLabel::new(entry.label.clone(), label_positions))
+                        .child(
+                            HighlightedLabel::new(entry.location.clone(), location_positions)
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                        ),
+                ),
+        )
+    }
+}
+
+/// Prompts for a name for the bookmark on the current line.
+pub struct NameBookmarkModal {
+    name_editor: Entity<Editor>,
+    active_editor: Entity<Editor>,
+    _subscription: Subscription,
+}
+
+impl ModalView for NameBookmarkModal {}
+
+impl EventEmitter<DismissEvent> for NameBookmarkModal {}
+
+impl Focusable for NameBookmarkModal {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.name_editor.focus_handle(cx)
+    }
+}
+
+impl NameBookmarkModal {
+    fn register(editor: &mut Editor, _: Option<&mut Window>, cx: &mut Context<Editor>) {
+        if editor.mode() != EditorMode::Full {
+            return;
+        }
+        let handle = cx.entity().downgrade();
+        editor
+            .register_action(move |_: &NameBookmark, window, cx| {
+                let Some(active_editor) = handle.upgrade() else {
+                    return;
+                };
+                let Some(workspace) = active_editor.read(cx).workspace() else {
+                    return;
+                };
+                workspace.update(cx, |workspace, cx| {
+                    workspace.toggle_modal(window, cx, move |window, cx| {
+                        NameBookmarkModal::new(active_editor, window, cx)
+                    });
+                })
+            })
+            .detach();
+    }
+
+    fn new(active_editor: Entity<Editor>, window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let name_editor = cx.new(|cx| {
+            let mut editor = Editor::single_line(window, cx);
+            editor.set_placeholder_text("Bookmark name", cx);
+            editor
+        });
+        let _subscription =
+            cx.subscribe_in(&name_editor, window, |_, _, event: &EditorEvent, _, cx| {
+                if let EditorEvent::Blurred = event {
+                    cx.emit(DismissEvent);
+                }
+            });
+        Self {
+            name_editor,
+            active_editor,
+            _subscription,
+        }
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, _: &mut Window, cx: &mut Context<Self>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, window: &mut Window, cx: &mut Context<Self>) {
+        let label = self.name_editor.read(cx).text(cx);
+        self.active_editor.update(cx, |editor, cx| {
+            editor.set_bookmark(&SetBookmark { label: Some(label) }, window, cx);
+            editor.focus_handle(cx).focus(window);
+        });
+        cx.emit(DismissEvent);
+    }
+}
+
+impl Render for NameBookmarkModal {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .w(rems(24.))
+            .elevation_2(cx)
+            .key_context("NameBookmark")
+            .on_action(cx.listener(Self::cancel))
+            .on_action(cx.listener(Self::confirm))
+            .child(
+                div()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .px_2()
+                    .py_1()
+                    .child(self.name_editor.clone()),
+            )
+            .child(
+                h_flex()
+                    .px_2()
+                    .py_1()
+                    .child(Label::new("Name the bookmark on the current line").color(Color::Muted)),
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{SemanticVersion, TestAppContext, VisualTestContext};
+    use project::FakeFs;
+    use serde_json::json;
+    use settings::SettingsStore;
+    use std::path::Path;
+    use util::path;
+
+    #[gpui::test]
+    async fn test_bookmarks_picker(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/dir"),
+            json!({
+                "a.rs": "fn main() {\n    println!(\"hello\");\n}\n",
+                "b.rs": "struct Foo;\nstruct Bar;\n",
+            }),
+        )
+        .await;
+        let project = Project::test(fs, [path!("/dir").as_ref()], cx).await;
+        let (workspace, cx) =
+            cx.add_window_view(|window, cx| Workspace::test_new(project.clone(), window, cx));
+
+        for (file, row, label) in [("a.rs", 1, None), ("b.rs", 1, Some("the bar"))] {
+            let buffer = project
+                .update(cx, |project, cx| {
+                    project.open_local_buffer(Path::new(path!("/dir")).join(file), cx)
+                })
+                .await
+                .unwrap();
+            project.update(cx, |project, cx| {
+                project.bookmark_store().update(cx, |store, cx| {
+                    store.set(&buffer, row, label.map(SharedString::from), cx)
+                })
+            });
+        }
+
+        let picker = open_bookmarks(&workspace, cx);
+        picker.update(cx, |picker, _| {
+            let entries = picker
+                .delegate
+                .matches
+                .iter()
+                .map(|mat| {
+                    let entry = &picker.delegate.entries[mat.candidate_id];
+                    (entry.label.to_string(), entry.location.to_string())
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(
+                entries,
+                [
+                    (
+                        "println!(\"hello\");".to_string(),
+                        format!("dir{}a.rs:2", std::path::MAIN_SEPARATOR)
+                    ),
+                    (
+                        "the bar".to_string(),
+                        format!("dir{}b.rs:2", std::path::MAIN_SEPARATOR)
+                    ),
+                ]
+            );
+        });
+
+        cx.simulate_input("bar");
+        picker.update(cx, |picker, _| assert_eq!(picker.delegate.matches.len(), 1));
+        cx.dispatch_action(menu::Confirm);
+
+        let editor = workspace.update(cx, |workspace, cx| {
+            workspace
+                .active_item_as::<Editor>(cx)
+                .expect("bookmark was not opened")
+        });
+        editor.update(cx, |editor, cx| {
+            assert_eq!(editor.text(cx), "struct Foo;\nstruct Bar;\n");
+            assert_eq!(
+                editor.selections.newest::<Point>(cx).head(),
+                Point::new(1, 0)
+            );
+        });
+    }
+
+    fn open_bookmarks(
+        workspace: &Entity<Workspace>,
+        cx: &mut VisualTestContext,
+    ) -> Entity<Picker<BookmarksDelegate>> {
+        cx.dispatch_action(Toggle);
+        cx.run_until_parked();
+        workspace.update(cx, |workspace, cx| {
+            workspace
+                .active_modal::<Picker<BookmarksDelegate>>(cx)
+                .expect("bookmarks picker is not open")
+        })
+    }
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let store = SettingsStore::test(cx);
+            cx.set_global(store);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            release_channel::init(SemanticVersion::default(), cx);
+            language::init(cx);
+            Project::init_settings(cx);
+            workspace::init_settings(cx);
+            editor::init(cx);
+            init(cx);
+        });
+    }
+}
@@ -179,6 +179,42 @@ pub enum UuidVersion {
     V7,
 }
 
+#[derive(PartialEq, Clone, Deserialize, Default, JsonSchema)]
+pub struct SetBookmark {
+    /// The name of the bookmark. Bookmarks without a name are listed by their line's text.
+    #[serde(default)]
+    pub label: Option<String>,
+}
+
+#[derive(PartialEq, Clone, Deserialize, Default, JsonSchema)]
+pub struct PlayLastMacro {
+    /// How many times to play the macro in a row.
+    #[serde(default)]
+    pub times: Option<usize>,
+    /// Whether to play the macro once for every cursor, starting from that cursor alone.
+    #[serde(default)]
+    pub per_cursor: bool,
+}
+
+#[derive(PartialEq, Clone, Deserialize, Default, JsonSchema)]
+pub struct PlayMacro {
+    /// The name of the macro in the `macros` setting.
+    pub name: String,
+    /// How many times to play the macro in a row.
+    #[serde(default)]
+    pub times: Option<usize>,
+    /// Whether to play the macro once for every cursor, starting from that cursor alone.
+    #[serde(default)]
+    pub per_cursor: bool,
+}
+
+#[derive(PartialEq, Clone, Deserialize, Default, JsonSchema)]
+pub struct SaveLastMacro {
+    /// The name to save the macro under. Defaults to the first free `macro_<n>` name.
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
 impl_actions!(
     editor,
     [
@@ -198,12 +234,16 @@ impl_actions!(
         MoveToBeginningOfLine,
         MoveToEndOfLine,
         MoveUpByLines,
+        PlayLastMacro,
+        PlayMacro,
+        SaveLastMacro,
         SelectDownByLines,
         SelectNext,
         SelectPrevious,
         SelectToBeginningOfLine,
         SelectToEndOfLine,
         SelectUpByLines,
+        SetBookmark,
         SpawnNearestTask,
         ShowCompletions,
         ToggleCodeActions,
@@ -226,6 +266,7 @@ gpui::actions!(
         Backspace,
         Cancel,
         CancelLanguageServerWork,
+        ClearBookmarks,
         ConfirmRename,
         ContextMenuFirst,
         ContextMenuLast,
@@ -277,8 +318,10 @@ gpui::actions!(
         GoToHunk,
         GoToImplementation,
         GoToImplementationSplit,
+        GoToNextBookmark,
         GoToPrevDiagnostic,
         GoToPrevHunk,
+        GoToPreviousBookmark,
         GoToTypeDefinition,
         GoToTypeDefinitionSplit,
         HalfPageDown,
@@ -312,6 +355,7 @@ gpui::actions!(
         NewlineBelow,
         NextInlineCompletion,
         NextScreen,
+        OpenBookmarksInMultibuffer,
         OpenContextMenu,
         OpenExcerpts,
         OpenExcerptsSplit,
@@ -342,11 +386,17 @@ gpui::actions!(
         ScrollCursorTop,
         SelectAll,
         SelectAllMatches,
+        SelectAllSymbolOccurrences,
+        SelectColumnDown,
+        SelectColumnLeft,
+        SelectColumnRight,
+        SelectColumnUp,
         SelectDown,
         SelectEnclosingSymbol,
         SelectLargerSyntaxNode,
         SelectLeft,
         SelectLine,
+        SelectNextSyntaxSibling,
         SelectPageDown,
         SelectPageUp,
         SelectRight,
@@ -364,13 +414,17 @@ gpui::actions!(
         ShowInlineCompletion,
         ShowSignatureHelp,
         ShuffleLines,
+        SkipAndSelectNextOccurrence,
         SortLinesCaseInsensitive,
         SortLinesCaseSensitive,
         SplitSelectionIntoLines,
+        StartRecordingMacro,
+        StopRecordingMacro,
         SwitchSourceHeader,
         Tab,
         TabPrev,
         ToggleAutoSignatureHelp,
+        ToggleBookmark,
         ToggleGitBlame,
         ToggleGitBlameInline,
         ToggleIndentGuides,
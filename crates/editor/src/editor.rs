@@ -30,6 +30,7 @@ mod inlay_hint_cache;
 pub mod items;
 mod linked_editing_ranges;
 mod lsp_ext;
+mod macros;
 mod mouse_context_menu;
 pub mod movement;
 mod persistence;
@@ -69,6 +70,7 @@ pub use element::{
 use element::{LineWithInvisibles, PositionMap};
 use futures::{future, FutureExt};
 use fuzzy::StringMatchCandidate;
+pub use macros::{MacroSettings, MacroStep};
 
 use code_context_menus::{
     AvailableCodeAction, CodeActionContents, CodeActionsItem, CodeActionsMenu, CodeContextMenu,
@@ -96,9 +98,9 @@ use itertools::Itertools;
 use language::{
     language_settings::{self, all_language_settings, language_settings, InlayHintSettings},
     markdown, point_from_lsp, AutoindentMode, BracketPair, Buffer, Capability, CharKind, CodeLabel,
-    CompletionDocumentation, CursorShape, Diagnostic, EditPreview, HighlightedText, IndentKind,
-    IndentSize, Language, OffsetRangeExt, Point, Selection, SelectionGoal, TextObject,
-    TransactionId, TreeSitterOptions,
+    CompletionDocumentation, CursorShape, Diagnostic, EditPreview, EmmetSyntax, File as _,
+    HighlightedText, IndentKind, IndentSize, Language, OffsetRangeExt, Point, Selection,
+    SelectionGoal, TextObject, TransactionId, TreeSitterOptions,
 };
 use language::{point_to_lsp, BufferRow, CharClassifier, Runnable, RunnableRange};
 use linked_editing_ranges::refresh_linked_ranges;
@@ -127,6 +129,7 @@ use multi_buffer::{
     ExpandExcerptDirection, MultiBufferDiffHunk, MultiBufferPoint, MultiBufferRow, ToOffsetUtf16,
 };
 use project::{
+    bookmark_store::{BookmarkStore, BookmarkStoreEvent},
     lsp_store::{FormatTrigger, LspFormatTarget, OpenLspBufferHandle},
     project_settings::{GitGutterSetting, ProjectSettings},
     CodeAction, Completion, CompletionIntent, DocumentHighlight, InlayHint, Location, LocationLink,
@@ -141,7 +144,7 @@ use selections_collection::{
 use serde::{Deserialize, Serialize};
 use settings::{update_settings_file, Settings, SettingsLocation, SettingsStore};
 use smallvec::SmallVec;
-use snippet::Snippet;
+use snippet::{Snippet, Transformation};
 use std::{
     any::TypeId,
     borrow::Cow,
@@ -292,10 +295,12 @@ impl Navigated {
 
 pub fn init_settings(cx: &mut App) {
     EditorSettings::register(cx);
+    MacroSettings::register(cx);
 }
 
 pub fn init(cx: &mut App) {
     init_settings(cx);
+    macros::init(cx);
 
     workspace::register_project_item::<Editor>(cx);
     workspace::FollowableViewRegistry::register::<Editor>(cx);
@@ -569,6 +574,8 @@ impl RunnableTasks {
 #[derive(Clone)]
 struct ResolvedTasks {
     templates: SmallVec<[(TaskSourceKind, ResolvedTask); 1]>,
+    /// The context the templates were resolved with, to resolve them again with their inputs' values.
+    context: task::TaskContext,
     position: Anchor,
 }
 #[derive(Copy, Clone, Debug)]
@@ -606,6 +613,7 @@ pub struct Editor {
     /// typing enters text into each of them, even the ones that aren't focused.
     pub(crate) show_cursor_when_unfocused: bool,
     columnar_selection_tail: Option<Anchor>,
+    columnar_selection_state: Option<ColumnarSelectionState>,
     add_selections_state: Option<AddSelectionsState>,
     select_next_state: Option<SelectNextState>,
     select_prev_state: Option<SelectNextState>,
@@ -913,6 +921,16 @@ struct RowHighlight {
     should_autoscroll: bool,
 }
 
+/// The corners of the most recent column selection. Their columns may lie past the end of
+/// their lines, so that the rectangle keeps its width while it's extended across short lines.
+/// Lines ending before the rectangle get an empty selection at their end, so that every row
+/// of the rectangle is copied.
+#[derive(Clone, Copy, Debug)]
+struct ColumnarSelectionState {
+    tail: DisplayPoint,
+    head: DisplayPoint,
+}
+
 #[derive(Clone, Debug)]
 struct AddSelectionsState {
     above: bool,
@@ -947,6 +965,8 @@ struct SnippetState {
     ranges: Vec<Vec<Range<Anchor>>>,
     active_index: usize,
     choices: Vec<Option<Vec<String>>>,
+    /// The ranges mirroring each tabstop through a transformation, updated when leaving the tabstop.
+    transformed_mirrors: Vec<Vec<(Range<Anchor>, Transformation)>>,
 }
 
 #[doc(hidden)]
@@ -978,6 +998,10 @@ pub struct ClipboardSelection {
     pub len: usize,
     pub is_entire_line: bool,
     pub first_line_indent: u32,
+    /// Whether this selection was one row of a column selection, in which case pasting
+    /// the rows into a single cursor inserts them as a rectangle.
+    #[serde(default)]
+    pub is_rectangular: bool,
 }
 
 #[derive(Debug)]
@@ -1245,6 +1269,10 @@ impl Editor {
                         }
                     },
                 ));
+                project_subscriptions.push(cx.subscribe(
+                    project.read(cx).bookmark_store(),
+                    |_, _, _: &BookmarkStoreEvent, cx| cx.notify(),
+                ));
                 if let Some(task_inventory) = project
                     .read(cx)
                     .task_store()
@@ -1303,6 +1331,7 @@ impl Editor {
             selections,
             scroll_manager: ScrollManager::new(cx),
             columnar_selection_tail: None,
+            columnar_selection_state: None,
             add_selections_state: None,
             select_next_state: None,
             select_prev_state: None,
@@ -1967,6 +1996,7 @@ impl Editor {
             .display_map
             .update(cx, |display_map, cx| display_map.snapshot(cx));
         let buffer = &display_map.buffer_snapshot;
+        self.columnar_selection_state = None;
         self.add_selections_state = None;
         self.select_next_state = None;
         self.select_prev_state = None;
@@ -2368,6 +2398,31 @@ impl Editor {
         }
     }
 
+    /// Turns the pending selection into a column selection once it's dragged to another line.
+    fn drag_pending_selection_as_columns(
+        &mut self,
+        position: DisplayPoint,
+        goal_column: u32,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.columnar_selection_tail.is_some()
+            || !matches!(self.selections.pending_mode(), Some(SelectMode::Character))
+        {
+            return;
+        }
+        let Some(pending) = self.selections.pending_anchor() else {
+            return;
+        };
+
+        let display_map = self.display_map.update(cx, |map, cx| map.snapshot(cx));
+        let tail = pending.tail().to_display_point(&display_map);
+        if tail.row() != position.row() {
+            self.columnar_selection_tail = Some(pending.tail());
+            self.select_columns(tail, position, goal_column, &display_map, window, cx);
+        }
+    }
+
     fn update_selection(
         &mut self,
         position: DisplayPoint,
@@ -2495,7 +2550,7 @@ impl Editor {
         let selection_ranges = (start_row.0..=end_row.0)
             .map(DisplayRow)
             .filter_map(|row| {
-                if start_column <= display_map.line_len(row) && !display_map.is_block_line(row) {
+                if !display_map.is_block_line(row) {
                     let start = display_map
                         .clip_point(DisplayPoint::new(row, start_column), Bias::Left)
                         .to_point(display_map);
@@ -2516,17 +2571,102 @@ impl Editor {
         self.change_selections(None, window, cx, |s| {
             s.select_ranges(selection_ranges);
         });
+        self.columnar_selection_state = Some(ColumnarSelectionState {
+            tail,
+            head: DisplayPoint::new(head.row(), goal_column),
+        });
         cx.notify();
     }
 
+    pub fn select_column_up(
+        &mut self,
+        _: &SelectColumnUp,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.extend_column_selection(
+            |head, _| DisplayPoint::new(DisplayRow(head.row().0.saturating_sub(1)), head.column()),
+            window,
+            cx,
+        );
+    }
+
+    pub fn select_column_down(
+        &mut self,
+        _: &SelectColumnDown,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.extend_column_selection(
+            |head, display_map| {
+                let row = cmp::min(head.row().next_row(), display_map.max_point().row());
+                DisplayPoint::new(row, head.column())
+            },
+            window,
+            cx,
+        );
+    }
+
+    pub fn select_column_left(
+        &mut self,
+        _: &SelectColumnLeft,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.extend_column_selection(
+            |head, _| DisplayPoint::new(head.row(), head.column().saturating_sub(1)),
+            window,
+            cx,
+        );
+    }
+
+    pub fn select_column_right(
+        &mut self,
+        _: &SelectColumnRight,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.extend_column_selection(
+            |head, _| DisplayPoint::new(head.row(), head.column() + 1),
+            window,
+            cx,
+        );
+    }
+
+    fn extend_column_selection(
+        &mut self,
+        move_head: impl FnOnce(DisplayPoint, &DisplaySnapshot) -> DisplayPoint,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let display_map = self.display_map.update(cx, |map, cx| map.snapshot(cx));
+        let state = self.columnar_selection_state.unwrap_or_else(|| {
+            let newest_selection = self.selections.newest_display(cx);
+            ColumnarSelectionState {
+                tail: newest_selection.tail(),
+                head: newest_selection.head(),
+            }
+        });
+        let head = move_head(state.head, &display_map);
+        self.select_columns(state.tail, head, head.column(), &display_map, window, cx);
+        self.request_autoscroll(Autoscroll::fit(), cx);
+    }
+
     pub fn has_pending_nonempty_selection(&self) -> bool {
         let pending_nonempty_selection = match self.selections.pending_anchor() {
             Some(Selection { start, end, .. }) => start != end,
             None => false,
         };
 
-        pending_nonempty_selection
-            || (self.columnar_selection_tail.is_some() && self.selections.disjoint.len() > 1)
+        // A column dragged within a line is a single selection, which is empty when it's just a click.
+        let columnar_selection = self.columnar_selection_tail.is_some()
+            && (self.selections.disjoint.len() > 1
+                || self
+                    .selections
+                    .disjoint
+                    .iter()
+                    .any(|selection| selection.start != selection.end));
+        pending_nonempty_selection || columnar_selection
     }
 
     pub fn has_pending_selection(&self) -> bool {
@@ -3949,7 +4089,10 @@ impl Editor {
         let text;
 
         if completion.is_snippet() {
-            snippet = Some(Snippet::parse(&completion.new_text).log_err()?);
+            let names = Snippet::variable_names(&completion.new_text).log_err()?;
+            let variables = self.snippet_variables(&names, cx);
+            snippet =
+                Some(Snippet::parse_with_variables(&completion.new_text, &variables).log_err()?);
             text = snippet.as_ref().unwrap().text.clone();
         } else {
             snippet = None;
@@ -4196,6 +4339,7 @@ impl Editor {
                             tasks.zip(task_context).map(|(tasks, task_context)| {
                                 Rc::new(ResolvedTasks {
                                     templates: tasks.resolve(&task_context).collect(),
+                                    context: task_context,
                                     position: snapshot.buffer_snapshot.anchor_before(Point::new(
                                         multibuffer_point.row,
                                         tasks.column,
@@ -4271,12 +4415,15 @@ impl Editor {
 
         match action {
             CodeActionsItem::Task(task_source_kind, resolved_task) => {
+                let task_context = actions_menu.actions.tasks.as_ref()?.context.clone();
                 workspace.update(cx, |workspace, cx| {
-                    workspace::tasks::schedule_resolved_task(
+                    workspace::tasks::schedule_task_with_inputs(
                         workspace,
                         task_source_kind,
-                        resolved_task,
+                        resolved_task.original_task().clone(),
+                        task_context,
                         false,
+                        window,
                         cx,
                     );
 
@@ -4540,7 +4687,6 @@ impl Editor {
                         return;
                     }
 
-                    let buffer_id = cursor_position.buffer_id;
                     let buffer = this.buffer.read(cx);
                     if !buffer
                         .text_anchor_for_position(cursor_position, cx)
@@ -4549,41 +4695,15 @@ impl Editor {
                         return;
                     }
 
-                    let cursor_buffer_snapshot = cursor_buffer.read(cx);
                     let mut write_ranges = Vec::new();
                     let mut read_ranges = Vec::new();
-                    for highlight in highlights {
-                        for (excerpt_id, excerpt_range) in
-                            buffer.excerpts_for_buffer(cursor_buffer.read(cx).remote_id(), cx)
-                        {
-                            let start = highlight
-                                .range
-                                .start
-                                .max(&excerpt_range.context.start, cursor_buffer_snapshot);
-                            let end = highlight
-                                .range
-                                .end
-                                .min(&excerpt_range.context.end, cursor_buffer_snapshot);
-                            if start.cmp(&end, cursor_buffer_snapshot).is_ge() {
-                                continue;
-                            }
-
-                            let range = Anchor {
-                                buffer_id,
-                                excerpt_id,
-                                text_anchor: start,
-                                diff_base_anchor: None,
-                            }..Anchor {
-                                buffer_id,
-                                excerpt_id,
-                                text_anchor: end,
-                                diff_base_anchor: None,
-                            };
-                            if highlight.kind == lsp::DocumentHighlightKind::WRITE {
-                                write_ranges.push(range);
-                            } else {
-                                read_ranges.push(range);
-                            }
+                    for (range, kind) in
+                        document_highlight_ranges(buffer, &cursor_buffer, highlights, cx)
+                    {
+                        if kind == lsp::DocumentHighlightKind::WRITE {
+                            write_ranges.push(range);
+                        } else {
+                            read_ranges.push(range);
                         }
                     }
 
@@ -5332,18 +5452,20 @@ impl Editor {
         let task_context = Self::build_tasks_context(&project, &buffer, buffer_row, &tasks, cx);
         cx.spawn_in(window, |_, mut cx| async move {
             let context = task_context.await?;
-            let (task_source_kind, mut resolved_task) = tasks.resolve(&context).next()?;
+            let (task_source_kind, resolved_task) = tasks.resolve(&context).next()?;
 
-            let resolved = resolved_task.resolved.as_mut()?;
-            resolved.reveal = reveal_strategy;
+            let mut template = resolved_task.original_task().clone();
+            template.reveal = reveal_strategy;
 
             workspace
-                .update(&mut cx, |workspace, cx| {
-                    workspace::tasks::schedule_resolved_task(
+                .update_in(&mut cx, |workspace, window, cx| {
+                    workspace::tasks::schedule_task_with_inputs(
                         workspace,
                         task_source_kind,
-                        resolved_task,
+                        template,
+                        context,
                         false,
+                        window,
                         cx,
                     );
                 })
@@ -5432,6 +5554,19 @@ impl Editor {
             }))
     }
 
+    fn render_bookmark_indicator(&self, row: DisplayRow, cx: &mut Context<Self>) -> IconButton {
+        IconButton::new(
+            ("bookmark_indicator", row.0 as usize),
+            ui::IconName::Bookmark,
+        )
+        .shape(ui::IconButtonShape::Square)
+        .icon_size(IconSize::XSmall)
+        .icon_color(Color::Accent)
+        .on_click(cx.listener(move |editor, _e, _window, cx| {
+            editor.toggle_bookmark_at_row(row, cx);
+        }))
+    }
+
     pub fn context_menu_visible(&self) -> bool {
         self.context_menu
             .borrow()
@@ -5875,6 +6010,7 @@ impl Editor {
             is_end_tabstop: bool,
             ranges: Vec<Range<T>>,
             choices: Option<Vec<String>>,
+            transformed_mirrors: Vec<(Range<T>, Transformation)>,
         }
 
         let tabstops = self.buffer.update(cx, |buffer, cx| {
@@ -5897,30 +6033,40 @@ impl Editor {
                     let is_end_tabstop = tabstop.ranges.first().map_or(false, |tabstop| {
                         tabstop.is_empty() && tabstop.start == snippet.text.len() as isize
                     });
+                    let anchor_ranges = move |tabstop_range: &Range<isize>| {
+                        let tabstop_range = tabstop_range.clone();
+                        let mut delta = 0_isize;
+                        insertion_ranges.iter().map(move |insertion_range| {
+                            let insertion_start = insertion_range.start as isize + delta;
+                            delta += snippet.text.len() as isize - insertion_range.len() as isize;
+
+                            let start = ((insertion_start + tabstop_range.start) as usize)
+                                .min(snapshot.len());
+                            let end = ((insertion_start + tabstop_range.end) as usize)
+                                .min(snapshot.len());
+                            snapshot.anchor_before(start)..snapshot.anchor_after(end)
+                        })
+                    };
                     let mut tabstop_ranges = tabstop
                         .ranges
                         .iter()
-                        .flat_map(|tabstop_range| {
-                            let mut delta = 0_isize;
-                            insertion_ranges.iter().map(move |insertion_range| {
-                                let insertion_start = insertion_range.start as isize + delta;
-                                delta +=
-                                    snippet.text.len() as isize - insertion_range.len() as isize;
-
-                                let start = ((insertion_start + tabstop_range.start) as usize)
-                                    .min(snapshot.len());
-                                let end = ((insertion_start + tabstop_range.end) as usize)
-                                    .min(snapshot.len());
-                                snapshot.anchor_before(start)..snapshot.anchor_after(end)
-                            })
-                        })
+                        .flat_map(anchor_ranges)
                         .collect::<Vec<_>>();
                     tabstop_ranges.sort_unstable_by(|a, b| a.start.cmp(&b.start, snapshot));
+                    let transformed_mirrors = tabstop
+                        .transformed_mirrors
+                        .iter()
+                        .flat_map(|mirror| {
+                            anchor_ranges(&mirror.range)
+                                .map(|range| (range, mirror.transformation.clone()))
+                        })
+                        .collect();
 
                     Tabstop {
                         is_end_tabstop,
                         ranges: tabstop_ranges,
                         choices: tabstop.choices.clone(),
+                        transformed_mirrors,
                     }
                 })
                 .collect::<Vec<_>>()
@@ -5944,15 +6090,16 @@ impl Editor {
                     .map(|tabstop| tabstop.choices.clone())
                     .collect();
 
-                let ranges = tabstops
+                let (ranges, transformed_mirrors) = tabstops
                     .into_iter()
-                    .map(|tabstop| tabstop.ranges)
-                    .collect::<Vec<_>>();
+                    .map(|tabstop| (tabstop.ranges, tabstop.transformed_mirrors))
+                    .unzip();
 
                 self.snippet_stack.push(SnippetState {
                     active_index: 0,
                     ranges,
                     choices,
+                    transformed_mirrors,
                 });
             }
 
@@ -6018,6 +6165,7 @@ impl Editor {
         cx: &mut Context<Self>,
     ) -> bool {
         if let Some(mut snippet) = self.snippet_stack.pop() {
+            self.update_snippet_transformed_mirrors(&snippet, cx);
             match bias {
                 Bias::Left => {
                     if snippet.active_index > 0 {
@@ -6058,6 +6206,161 @@ impl Editor {
         false
     }
 
+    /// Updates the ranges mirroring the active tabstop through a transformation, with the text of the tabstop.
+    fn update_snippet_transformed_mirrors(
+        &mut self,
+        snippet: &SnippetState,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(mirrors) = snippet
+            .transformed_mirrors
+            .get(snippet.active_index)
+            .filter(|mirrors| !mirrors.is_empty())
+        else {
+            return;
+        };
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let Some(value) = snippet.ranges[snippet.active_index]
+            .iter()
+            .map(|range| snapshot.text_for_range(range.clone()).collect::<String>())
+            .find(|value| !value.is_empty())
+        else {
+            return;
+        };
+        let edits = mirrors
+            .iter()
+            .map(|(range, transformation)| {
+                (range.to_offset(&snapshot), transformation.apply(&value))
+            })
+            .collect::<Vec<_>>();
+        self.buffer
+            .update(cx, |buffer, cx| buffer.edit(edits, None, cx));
+    }
+
+    /// The values of the given variables snippets can use, like `$TM_FILENAME` or `$CLIPBOARD`,
+    /// resolved at the newest selection. Only the variables in `names` are resolved, so that
+    /// e.g. the clipboard is only read by the snippets using it.
+    pub fn snippet_variables(
+        &self,
+        names: &HashSet<String>,
+        cx: &mut Context<Self>,
+    ) -> HashMap<String, String> {
+        let mut variables = HashMap::default();
+        if names.is_empty() {
+            return variables;
+        }
+        let wants = |name: &str| names.contains(name);
+        let mut insert = |name: &str, value: String| {
+            if wants(name) {
+                variables.insert(name.to_string(), value);
+            }
+        };
+
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let selection = self.selections.newest::<Point>(cx);
+        let row = selection.head().row;
+        insert(
+            "TM_SELECTED_TEXT",
+            snapshot.text_for_range(selection.range()).collect(),
+        );
+        insert(
+            "TM_CURRENT_LINE",
+            snapshot
+                .text_for_range(
+                    Point::new(row, 0)..Point::new(row, snapshot.line_len(MultiBufferRow(row))),
+                )
+                .collect(),
+        );
+        let (word_range, _) = snapshot.surrounding_word(selection.head(), false);
+        insert(
+            "TM_CURRENT_WORD",
+            snapshot.text_for_range(word_range).collect(),
+        );
+        insert("TM_LINE_INDEX", row.to_string());
+        insert("TM_LINE_NUMBER", (row + 1).to_string());
+
+        if let Some(scope) = snapshot.language_scope_at(selection.head()) {
+            if let Some(prefix) = scope.line_comment_prefixes().first() {
+                insert("LINE_COMMENT", prefix.trim_end().to_string());
+            }
+            if let Some((start, end)) = scope.block_comment_delimiters() {
+                insert("BLOCK_COMMENT_START", start.trim_end().to_string());
+                insert("BLOCK_COMMENT_END", end.trim_start().to_string());
+            }
+        }
+
+        if let Some(file) = self.target_file(cx) {
+            let abs_path = file.abs_path(cx);
+            insert("TM_FILEPATH", abs_path.to_string_lossy().into_owned());
+            if let Some(directory) = abs_path.parent() {
+                insert("TM_DIRECTORY", directory.to_string_lossy().into_owned());
+            }
+            insert(
+                "TM_FILENAME",
+                file.file_name(cx).to_string_lossy().into_owned(),
+            );
+            if let Some(file_stem) = abs_path.file_stem() {
+                insert("TM_FILENAME_BASE", file_stem.to_string_lossy().into_owned());
+            }
+            insert(
+                "RELATIVE_FILEPATH",
+                file.path().to_string_lossy().into_owned(),
+            );
+            let worktree = self
+                .project
+                .as_ref()
+                .and_then(|project| project.read(cx).worktree_for_id(file.worktree_id(cx), cx));
+            if let Some(worktree) = worktree {
+                let worktree = worktree.read(cx);
+                insert("WORKSPACE_NAME", worktree.root_name().to_string());
+                insert(
+                    "WORKSPACE_FOLDER",
+                    worktree.abs_path().to_string_lossy().into_owned(),
+                );
+            }
+        }
+
+        if wants("CLIPBOARD") {
+            if let Some(clipboard) = cx.read_from_clipboard().and_then(|item| item.text()) {
+                insert("CLIPBOARD", clipboard);
+            }
+        }
+
+        let now = chrono::Local::now();
+        for (name, format) in [
+            ("CURRENT_YEAR", "%Y"),
+            ("CURRENT_YEAR_SHORT", "%y"),
+            ("CURRENT_MONTH", "%m"),
+            ("CURRENT_MONTH_NAME", "%B"),
+            ("CURRENT_MONTH_NAME_SHORT", "%b"),
+            ("CURRENT_DATE", "%d"),
+            ("CURRENT_DAY_NAME", "%A"),
+            ("CURRENT_DAY_NAME_SHORT", "%a"),
+            ("CURRENT_HOUR", "%H"),
+            ("CURRENT_MINUTE", "%M"),
+            ("CURRENT_SECOND", "%S"),
+            ("CURRENT_SECONDS_UNIX", "%s"),
+            ("CURRENT_TIMEZONE_OFFSET", "%:z"),
+        ] {
+            if wants(name) {
+                insert(name, now.format(format).to_string());
+            }
+        }
+
+        let mut rng = thread_rng();
+        if wants("RANDOM") {
+            insert("RANDOM", format!("{:06}", rng.gen_range(0..1_000_000)));
+        }
+        if wants("RANDOM_HEX") {
+            insert("RANDOM_HEX", format!("{:06x}", rng.gen_range(0..0x1000000)));
+        }
+        if wants("UUID") {
+            insert("UUID", uuid::Uuid::new_v4().to_string());
+        }
+
+        variables
+    }
+
     pub fn clear(&mut self, window: &mut Window, cx: &mut Context<Self>) {
         self.transact(window, cx, |this, window, cx| {
             this.select_all(&SelectAll, window, cx);
@@ -7550,11 +7853,14 @@ impl Editor {
         let buffer = self.buffer.read(cx).snapshot(cx);
         let mut selections = self.selections.all::<Point>(cx);
         let mut clipboard_selections = Vec::with_capacity(selections.len());
+        let is_rectangular = self.columnar_selection_state.is_some() && selections.len() > 1;
         {
             let max_point = buffer.max_point();
             let mut is_first = true;
             for selection in &mut selections {
-                let is_entire_line = selection.is_empty() || self.selections.line_mode;
+                // The empty rows of a rectangle are lines ending before it, not whole lines.
+                let is_entire_line =
+                    !is_rectangular && (selection.is_empty() || self.selections.line_mode);
                 if is_entire_line {
                     selection.start = Point::new(selection.start.row, 0);
                     if !selection.is_empty() && selection.end.column == 0 {
@@ -7580,6 +7886,7 @@ impl Editor {
                     first_line_indent: buffer
                         .indent_size_for_line(MultiBufferRow(selection.start.row))
                         .len,
+                    is_rectangular,
                 });
             }
         }
@@ -7634,13 +7941,15 @@ impl Editor {
         let mut text = String::new();
 
         let mut clipboard_selections = Vec::with_capacity(selections.len());
+        let is_rectangular = self.columnar_selection_state.is_some() && selections.len() > 1;
         {
             let max_point = buffer.max_point();
             let mut is_first = true;
             for selection in selections.iter() {
                 let mut start = selection.start;
                 let mut end = selection.end;
-                let is_entire_line = selection.is_empty() || self.selections.line_mode;
+                let is_entire_line =
+                    !is_rectangular && (selection.is_empty() || self.selections.line_mode);
                 if is_entire_line {
                     start = Point::new(start.row, 0);
                     end = cmp::min(max_point, Point::new(end.row + 1, 0));
@@ -7659,6 +7968,7 @@ impl Editor {
                     len,
                     is_entire_line,
                     first_line_indent: buffer.indent_size_for_line(MultiBufferRow(start.row)).len,
+                    is_rectangular,
                 });
             }
         }
@@ -7690,6 +8000,22 @@ impl Editor {
                     clipboard_selections.iter().all(|s| s.is_entire_line);
                 let first_selection_indent_column =
                     clipboard_selections.first().map(|s| s.first_line_indent);
+                if old_selections.len() == 1
+                    && clipboard_selections.len() > 1
+                    && clipboard_selections.iter().all(|s| s.is_rectangular)
+                {
+                    let mut start_offset = 0;
+                    let rows = clipboard_selections
+                        .iter()
+                        .map(|selection| {
+                            let row = &clipboard_text[start_offset..start_offset + selection.len];
+                            start_offset += selection.len + 1;
+                            row
+                        })
+                        .collect::<Vec<_>>();
+                    this.insert_rectangle(&rows, window, cx);
+                    return;
+                }
                 if clipboard_selections.len() != old_selections.len() {
                     clipboard_selections.drain(..);
                 }
@@ -7760,6 +8086,82 @@ impl Editor {
         });
     }
 
+    /// Inserts the rows below one another, starting at the newest cursor and aligned to its
+    /// display column. Lines that are too short are padded with spaces, as are the rows
+    /// inserted before the rest of a line, so that the rectangle keeps its shape.
+    fn insert_rectangle(&mut self, rows: &[&str], window: &mut Window, cx: &mut Context<Self>) {
+        if !self.selections.newest::<usize>(cx).is_empty() {
+            self.insert("", window, cx);
+        }
+
+        let display_map = self.display_map.update(cx, |map, cx| map.snapshot(cx));
+        let buffer = &display_map.buffer_snapshot;
+        let cursor = self.selections.newest::<Point>(cx).head();
+        let column = cursor.to_display_point(&display_map).column();
+        let max_point = buffer.max_point();
+        let width = rows
+            .iter()
+            .map(|row| row.chars().count())
+            .max()
+            .unwrap_or(0);
+
+        let mut edits = Vec::new();
+        let mut appended_rows = String::new();
+        for (ix, row) in rows.iter().enumerate() {
+            let buffer_row = cursor.row + ix as u32;
+            if buffer_row > max_point.row {
+                appended_rows.push('\n');
+                if !row.is_empty() {
+                    appended_rows.push_str(&" ".repeat(column as usize));
+                    appended_rows.push_str(row);
+                }
+                continue;
+            }
+
+            let display_row = Point::new(buffer_row, 0)
+                .to_display_point(&display_map)
+                .row();
+            let line_len = display_map.line_len(display_row);
+            if column <= line_len {
+                let position = display_map
+                    .clip_point(DisplayPoint::new(display_row, column), Bias::Left)
+                    .to_point(&display_map);
+                let mut text = row.to_string();
+                if column < line_len {
+                    text.push_str(&" ".repeat(width - row.chars().count()));
+                }
+                edits.push((position..position, text));
+            } else if row.is_empty() {
+                let position = Point::new(buffer_row, buffer.line_len(MultiBufferRow(buffer_row)));
+                edits.push((position..position, String::new()));
+            } else {
+                let position = Point::new(buffer_row, buffer.line_len(MultiBufferRow(buffer_row)));
+                let padding = " ".repeat((column - line_len) as usize);
+                edits.push((position..position, padding + row));
+            }
+        }
+        if !appended_rows.is_empty() {
+            match edits.last_mut() {
+                Some((range, text)) if range.start == max_point => text.push_str(&appended_rows),
+                _ => edits.push((max_point..max_point, appended_rows)),
+            }
+        }
+
+        let last_row = edits
+            .last()
+            .map(|(range, text)| (buffer.anchor_before(range.start), text.len()));
+        self.buffer
+            .update(cx, |buffer, cx| buffer.edit(edits, None, cx));
+
+        if let Some((position, len)) = last_row {
+            let buffer = self.buffer.read(cx).snapshot(cx);
+            let cursor = position.to_offset(&buffer) + len;
+            self.change_selections(Some(Autoscroll::fit()), window, cx, |s| {
+                s.select_ranges([cursor..cursor])
+            });
+        }
+    }
+
     pub fn paste(&mut self, _: &Paste, window: &mut Window, cx: &mut Context<Self>) {
         if let Some(item) = cx.read_from_clipboard() {
             let entries = item.entries();
@@ -9345,6 +9747,128 @@ impl Editor {
         Ok(())
     }
 
+    /// Requests the occurrences of the symbol under the newest cursor from the language
+    /// server. Resolves to `None` when there are none, so that callers can fall back to
+    /// matching text.
+    fn symbol_occurrences(&self, cx: &mut Context<Self>) -> Task<Option<Vec<Range<Anchor>>>> {
+        let Some(provider) = self.semantics_provider.clone() else {
+            return Task::ready(None);
+        };
+        let position = self.selections.newest_anchor().head();
+        let Some((buffer, buffer_position)) =
+            self.buffer.read(cx).text_anchor_for_position(position, cx)
+        else {
+            return Task::ready(None);
+        };
+        let Some(highlights) = provider.document_highlights(&buffer, buffer_position, cx) else {
+            return Task::ready(None);
+        };
+        cx.spawn(|this, mut cx| async move {
+            let highlights = highlights.await.log_err()?;
+            let mut ranges = this
+                .update(&mut cx, |this, cx| {
+                    document_highlight_ranges(this.buffer.read(cx), &buffer, highlights, cx)
+                })
+                .ok()?
+                .into_iter()
+                .map(|(range, _)| range)
+                .collect::<Vec<_>>();
+            if ranges.is_empty() {
+                return None;
+            }
+            let snapshot = this
+                .update(&mut cx, |this, cx| this.buffer.read(cx).snapshot(cx))
+                .ok()?;
+            ranges.sort_by(|a, b| a.start.cmp(&b.start, &snapshot));
+            Some(ranges)
+        })
+    }
+
+    /// Replaces the newest selection with the next occurrence of the symbol under it, or of
+    /// its text if the language server can't find the symbol's occurrences.
+    pub fn skip_and_select_next_occurrence(
+        &mut self,
+        _: &SkipAndSelectNextOccurrence,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let occurrences = self.symbol_occurrences(cx);
+        cx.spawn_in(window, |this, mut cx| async move {
+            let occurrences = occurrences.await;
+            this.update_in(&mut cx, |this, window, cx| {
+                this.push_to_selection_history();
+                let Some(occurrences) = occurrences else {
+                    let display_map = this.display_map.update(cx, |map, cx| map.snapshot(cx));
+                    return this.select_next_match_internal(
+                        &display_map,
+                        true,
+                        Some(Autoscroll::newest()),
+                        window,
+                        cx,
+                    );
+                };
+
+                let snapshot = this.buffer.read(cx).snapshot(cx);
+                let newest_selection = this.selections.newest::<usize>(cx);
+                let selections = this.selections.all::<usize>(cx);
+                let occurrences = occurrences
+                    .iter()
+                    .map(|range| range.to_offset(&snapshot))
+                    .collect::<Vec<_>>();
+                let is_free = |range: &&Range<usize>| {
+                    !selections.iter().any(|selection| {
+                        selection.id != newest_selection.id && selection.range().overlaps(*range)
+                    })
+                };
+                let next_occurrence = occurrences
+                    .iter()
+                    .filter(|range| range.start >= newest_selection.end)
+                    .chain(
+                        occurrences
+                            .iter()
+                            .filter(|range| range.end <= newest_selection.start),
+                    )
+                    .find(is_free);
+                if let Some(next_occurrence) = next_occurrence.cloned() {
+                    this.unfold_ranges(&[next_occurrence.clone()], false, true, cx);
+                    this.change_selections(Some(Autoscroll::newest()), window, cx, |s| {
+                        s.delete(s.newest_anchor().id);
+                        s.insert_range(next_occurrence);
+                    });
+                }
+                Ok(())
+            })?
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// Selects all occurrences of the symbol under the newest cursor, or of its text if the
+    /// language server can't find the symbol's occurrences.
+    pub fn select_all_symbol_occurrences(
+        &mut self,
+        _: &SelectAllSymbolOccurrences,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let occurrences = self.symbol_occurrences(cx);
+        cx.spawn_in(window, |this, mut cx| async move {
+            let occurrences = occurrences.await;
+            this.update_in(&mut cx, |this, window, cx| {
+                let Some(occurrences) = occurrences else {
+                    return this.select_all_matches(&SelectAllMatches, window, cx);
+                };
+
+                this.push_to_selection_history();
+                this.unfold_ranges(&occurrences, false, false, cx);
+                this.change_selections(Some(Autoscroll::fit()), window, cx, |s| {
+                    s.select_anchor_ranges(occurrences);
+                });
+                Ok(())
+            })?
+        })
+        .detach_and_log_err(cx);
+    }
+
     pub fn toggle_comments(
         &mut self,
         action: &ToggleComments,
@@ -9775,6 +10299,34 @@ impl Editor {
         self.select_larger_syntax_node_stack = stack;
     }
 
+    /// Adds a selection at the next syntax node of the same kind as the one at the newest
+    /// selection, like the next argument of a call or the next item of a list.
+    pub fn select_next_syntax_sibling(
+        &mut self,
+        _: &SelectNextSyntaxSibling,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let buffer = self.buffer.read(cx).snapshot(cx);
+        let newest_selection = self.selections.newest::<usize>(cx);
+        let Some((node_range, sibling_range)) =
+            buffer.next_sibling_of_same_kind(newest_selection.range())
+        else {
+            return;
+        };
+
+        self.push_to_selection_history();
+        self.unfold_ranges(&[sibling_range.clone()], false, true, cx);
+        self.change_selections(Some(Autoscroll::newest()), window, cx, |s| {
+            // The newest selection is expanded to its node, so that it matches the new one.
+            if newest_selection.range() != node_range {
+                s.delete(newest_selection.id);
+                s.insert_range(node_range);
+            }
+            s.insert_range(sibling_range);
+        });
+    }
+
     fn refresh_runnables(&mut self, window: &mut Window, cx: &mut Context<Self>) -> Task<()> {
         if !EditorSettings::get_global(cx).gutter.runnables {
             self.clear_tasks();
@@ -13061,6 +13613,191 @@ impl Editor {
         .detach();
     }
 
+    fn bookmark_store(&self, cx: &App) -> Option<Entity<BookmarkStore>> {
+        Some(self.project.as_ref()?.read(cx).bookmark_store().clone())
+    }
+
+    /// Returns the positions of the bookmarks within this editor's excerpts, in order.
+    pub fn bookmark_anchors(&self, snapshot: &MultiBufferSnapshot, cx: &App) -> Vec<Anchor> {
+        let Some(bookmark_store) = self.bookmark_store(cx) else {
+            return Vec::new();
+        };
+        let bookmark_store = bookmark_store.read(cx);
+        if bookmark_store.is_empty() {
+            return Vec::new();
+        }
+
+        snapshot
+            .excerpts()
+            .flat_map(|(excerpt_id, buffer, range)| {
+                bookmark_store
+                    .bookmarks_for_buffer(buffer.remote_id())
+                    .iter()
+                    .filter(move |bookmark| {
+                        bookmark.position.cmp(&range.context.start, buffer).is_ge()
+                            && bookmark.position.cmp(&range.context.end, buffer).is_le()
+                    })
+                    .filter_map(move |bookmark| {
+                        snapshot.anchor_in_excerpt(excerpt_id, bookmark.position)
+                    })
+            })
+            .collect()
+    }
+
+    /// Returns the buffer lines that contain the heads of the selections.
+    fn selected_buffer_lines(&self, cx: &mut Context<Self>) -> Vec<(Entity<Buffer>, u32)> {
+        let selections = self.selections.all::<Point>(cx);
+        let multibuffer = self.buffer.read(cx);
+        let mut lines = Vec::new();
+        for selection in selections {
+            if let Some((buffer, point, _)) =
+                multibuffer.point_to_buffer_point(selection.head(), cx)
+            {
+                if !lines.contains(&(buffer.clone(), point.row)) {
+                    lines.push((buffer, point.row));
+                }
+            }
+        }
+        lines
+    }
+
+    pub fn toggle_bookmark(&mut self, _: &ToggleBookmark, _: &mut Window, cx: &mut Context<Self>) {
+        let Some(bookmark_store) = self.bookmark_store(cx) else {
+            return;
+        };
+        for (buffer, row) in self.selected_buffer_lines(cx) {
+            bookmark_store.update(cx, |store, cx| store.toggle(&buffer, row, cx));
+        }
+    }
+
+    pub fn set_bookmark(&mut self, action: &SetBookmark, _: &mut Window, cx: &mut Context<Self>) {
+        let Some(bookmark_store) = self.bookmark_store(cx) else {
+            return;
+        };
+        let label = action
+            .label
+            .as_ref()
+            .map(|label| label.trim())
+            .filter(|label| !label.is_empty())
+            .map(|label| SharedString::from(label.to_string()));
+        for (buffer, row) in self.selected_buffer_lines(cx) {
+            bookmark_store.update(cx, |store, cx| store.set(&buffer, row, label.clone(), cx));
+        }
+    }
+
+    fn toggle_bookmark_at_row(&mut self, row: DisplayRow, cx: &mut Context<Self>) {
+        let Some(bookmark_store) = self.bookmark_store(cx) else {
+            return;
+        };
+        let point = self
+            .display_map
+            .update(cx, |map, cx| map.snapshot(cx))
+            .display_point_to_point(DisplayPoint::new(row, 0), Bias::Left);
+        if let Some((buffer, point, _)) = self.buffer.read(cx).point_to_buffer_point(point, cx) {
+            bookmark_store.update(cx, |store, cx| store.toggle(&buffer, point.row, cx));
+        }
+    }
+
+    pub fn clear_bookmarks(&mut self, _: &ClearBookmarks, _: &mut Window, cx: &mut Context<Self>) {
+        if let Some(bookmark_store) = self.bookmark_store(cx) {
+            bookmark_store.update(cx, |store, cx| store.clear(cx));
+        }
+    }
+
+    pub fn go_to_next_bookmark(
+        &mut self,
+        _: &GoToNextBookmark,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.go_to_bookmark(Direction::Next, window, cx);
+    }
+
+    pub fn go_to_previous_bookmark(
+        &mut self,
+        _: &GoToPreviousBookmark,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.go_to_bookmark(Direction::Prev, window, cx);
+    }
+
+    fn go_to_bookmark(
+        &mut self,
+        direction: Direction,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let rows = self
+            .bookmark_anchors(&snapshot, cx)
+            .into_iter()
+            .map(|anchor| anchor.to_point(&snapshot).row)
+            .collect::<Vec<_>>();
+        let head_row = self.selections.newest::<Point>(cx).head().row;
+        let row = match direction {
+            Direction::Next => rows.iter().find(|row| **row > head_row).or(rows.first()),
+            Direction::Prev => rows
+                .iter()
+                .rev()
+                .find(|row| **row < head_row)
+                .or(rows.last()),
+        };
+        if let Some(row) = row {
+            let destination = Point::new(*row, 0);
+            self.unfold_ranges(&[destination..destination], false, false, cx);
+            self.change_selections(Some(Autoscroll::center()), window, cx, |s| {
+                s.select_ranges([destination..destination]);
+            });
+        }
+    }
+
+    pub fn open_bookmarks_in_multibuffer(
+        &mut self,
+        _: &OpenBookmarksInMultibuffer,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(workspace) = self.workspace() else {
+            return;
+        };
+        let Some(bookmark_store) = self.bookmark_store(cx) else {
+            return;
+        };
+
+        if bookmark_store.read(cx).is_empty() {
+            return;
+        }
+
+        let open_closed_files = bookmark_store.update(cx, |store, cx| store.open_closed_files(cx));
+        cx.spawn_in(window, |_, mut cx| async move {
+            let _buffers = open_closed_files.await;
+            workspace.update_in(&mut cx, |workspace, window, cx| {
+                let locations = bookmark_store
+                    .read(cx)
+                    .all_bookmarks()
+                    .map(|(buffer, bookmark)| Location {
+                        buffer,
+                        range: bookmark.position..bookmark.position,
+                    })
+                    .collect::<Vec<_>>();
+                if locations.is_empty() {
+                    return;
+                }
+                Self::open_locations_in_multibuffer(
+                    workspace,
+                    locations,
+                    "Bookmarks".to_string(),
+                    false,
+                    MultibufferSelectionMode::All,
+                    window,
+                    cx,
+                );
+            })
+        })
+        .detach();
+    }
+
     /// Adds a row highlight for the given range. If a row has multiple highlights, the
     /// last highlight added will be used.
     ///
@@ -13385,31 +14122,10 @@ impl Editor {
         let mut results = Vec::new();
         for (color_fetcher, ranges) in self.background_highlights.values() {
             let color = color_fetcher(theme);
-            let start_ix = match ranges.binary_search_by(|probe| {
-                let cmp = probe
-                    .end
-                    .cmp(&search_range.start, &display_snapshot.buffer_snapshot);
-                if cmp.is_gt() {
-                    Ordering::Greater
-                } else {
-                    Ordering::Less
-                }
-            }) {
-                Ok(i) | Err(i) => i,
-            };
-            for range in &ranges[start_ix..] {
-                if range
-                    .start
-                    .cmp(&search_range.end, &display_snapshot.buffer_snapshot)
-                    .is_ge()
-                {
-                    break;
-                }
-
-                let start = range.start.to_display_point(display_snapshot);
-                let end = range.end.to_display_point(display_snapshot);
-                results.push((start..end, color))
-            }
+            results.extend(
+                highlight_ranges_in_range(ranges, &search_range, display_snapshot)
+                    .map(|range| (range, color)),
+            );
         }
         results
     }
@@ -14947,6 +15663,49 @@ impl CodeActionProvider for Entity<Project> {
     }
 }
 
+/// Converts the document highlights of a buffer into ranges in each of its excerpts.
+fn document_highlight_ranges(
+    buffer: &MultiBuffer,
+    highlight_buffer: &Entity<Buffer>,
+    highlights: Vec<DocumentHighlight>,
+    cx: &App,
+) -> Vec<(Range<Anchor>, lsp::DocumentHighlightKind)> {
+    let highlight_buffer_snapshot = highlight_buffer.read(cx);
+    let buffer_id = Some(highlight_buffer_snapshot.remote_id());
+    let mut ranges = Vec::new();
+    for highlight in highlights {
+        for (excerpt_id, excerpt_range) in
+            buffer.excerpts_for_buffer(highlight_buffer_snapshot.remote_id(), cx)
+        {
+            let start = highlight
+                .range
+                .start
+                .max(&excerpt_range.context.start, highlight_buffer_snapshot);
+            let end = highlight
+                .range
+                .end
+                .min(&excerpt_range.context.end, highlight_buffer_snapshot);
+            if start.cmp(&end, highlight_buffer_snapshot).is_ge() {
+                continue;
+            }
+
+            let range = Anchor {
+                buffer_id,
+                excerpt_id,
+                text_anchor: start,
+                diff_base_anchor: None,
+            }..Anchor {
+                buffer_id,
+                excerpt_id,
+                text_anchor: end,
+                diff_base_anchor: None,
+            };
+            ranges.push((range, highlight.kind));
+        }
+    }
+    ranges
+}
+
 fn snippet_completions(
     project: &Project,
     buffer: &Entity<Buffer>,
@@ -15087,6 +15846,83 @@ fn snippet_completions(
     })
 }
 
+/// Offers the Emmet abbreviation before the cursor as a completion, in the language scopes
+/// configured to expand them.
+fn emmet_completions(
+    buffer: &Entity<Buffer>,
+    buffer_position: text::Anchor,
+    cx: &App,
+) -> Vec<Completion> {
+    let snapshot = buffer.read(cx).snapshot();
+    let Some(syntax) = snapshot
+        .language_scope_at(buffer_position)
+        .and_then(|scope| scope.emmet_syntax())
+    else {
+        return Vec::new();
+    };
+
+    let position = text::ToPoint::to_point(&buffer_position, &snapshot);
+    let line_start = Point::new(position.row, 0);
+    let line_prefix = snapshot
+        .text_for_range(line_start..position)
+        .collect::<String>();
+    let Some(abbreviation_start) = emmet::extract_abbreviation(&line_prefix, syntax) else {
+        return Vec::new();
+    };
+    let abbreviation = &line_prefix[abbreviation_start..];
+    let indent = snapshot
+        .language_indent_size_at(position, cx)
+        .chars()
+        .collect::<String>();
+    let Some(expansion) = emmet::expand(abbreviation, syntax, &indent) else {
+        return Vec::new();
+    };
+    let Some(preview) = Snippet::parse(&expansion).log_err() else {
+        return Vec::new();
+    };
+
+    let start = snapshot.anchor_before(line_start + Point::new(0, abbreviation_start as u32));
+    let lsp_range = lsp::Range {
+        start: point_to_lsp(text::ToPointUtf16::to_point_utf16(&start, &snapshot)),
+        end: point_to_lsp(text::ToPointUtf16::to_point_utf16(
+            &buffer_position,
+            &snapshot,
+        )),
+    };
+    vec![Completion {
+        old_range: start..buffer_position,
+        new_text: expansion.clone(),
+        resolved: true,
+        label: CodeLabel {
+            text: abbreviation.to_string(),
+            runs: vec![],
+            filter_range: 0..abbreviation.len(),
+        },
+        server_id: LanguageServerId(usize::MAX),
+        documentation: Some(CompletionDocumentation::MultiLinePlainText(preview.text)),
+        lsp_completion: lsp::CompletionItem {
+            label: abbreviation.to_string(),
+            kind: Some(CompletionItemKind::SNIPPET),
+            label_details: Some(lsp::CompletionItemLabelDetails {
+                detail: None,
+                description: Some("Emmet Abbreviation".to_string()),
+            }),
+            insert_text_format: Some(InsertTextFormat::SNIPPET),
+            text_edit: Some(lsp::CompletionTextEdit::InsertAndReplace(
+                lsp::InsertReplaceEdit {
+                    new_text: expansion,
+                    insert: lsp_range,
+                    replace: lsp_range,
+                },
+            )),
+            filter_text: Some(abbreviation.to_string()),
+            sort_text: Some(char::MAX.to_string()),
+            ..Default::default()
+        },
+        confirm: None,
+    }]
+}
+
 impl CompletionProvider for Entity<Project> {
     fn completions(
         &self,
@@ -15098,11 +15934,13 @@ impl CompletionProvider for Entity<Project> {
     ) -> Task<Result<Vec<Completion>>> {
         self.update(cx, |project, cx| {
             let snippets = snippet_completions(project, buffer, buffer_position, cx);
+            let emmet_completions = emmet_completions(buffer, buffer_position, cx);
             let project_completions = project.completions(buffer, buffer_position, options, cx);
             cx.background_executor().spawn(async move {
                 let mut completions = project_completions.await?;
                 let snippets_completions = snippets.await?;
                 completions.extend(snippets_completions);
+                completions.extend(emmet_completions);
                 Ok(completions)
             })
         })
@@ -15171,6 +16009,16 @@ impl CompletionProvider for Entity<Project> {
             return true;
         }
 
+        // Closing an attribute list completes an Emmet abbreviation like `a[href]`.
+        if char == ']'
+            && snapshot
+                .language_scope_at(position)
+                .and_then(|scope| scope.emmet_syntax())
+                .map_or(false, |syntax| syntax != EmmetSyntax::Css)
+        {
+            return true;
+        }
+
         buffer.completion_triggers().contains(text)
     }
 }
@@ -15302,6 +16150,31 @@ fn inlay_hint_settings(
     language_settings(language, file, cx).inlay_hints
 }
 
+/// Returns the display ranges of the sorted highlights that intersect the search range.
+pub(crate) fn highlight_ranges_in_range<'a>(
+    ranges: &'a [Range<Anchor>],
+    search_range: &'a Range<Anchor>,
+    display_snapshot: &'a DisplaySnapshot,
+) -> impl Iterator<Item = Range<DisplayPoint>> + 'a {
+    let buffer = &display_snapshot.buffer_snapshot;
+    let start_ix = match ranges.binary_search_by(|probe| {
+        if probe.end.cmp(&search_range.start, buffer).is_gt() {
+            Ordering::Greater
+        } else {
+            Ordering::Less
+        }
+    }) {
+        Ok(i) | Err(i) => i,
+    };
+    ranges[start_ix..]
+        .iter()
+        .take_while(move |range| range.start.cmp(&search_range.end, buffer).is_lt())
+        .map(move |range| {
+            range.start.to_display_point(display_snapshot)
+                ..range.end.to_display_point(display_snapshot)
+        })
+}
+
 fn consume_contiguous_rows(
     contiguous_row_selections: &mut Vec<Selection<Point>>,
     selection: &Selection<Point>,
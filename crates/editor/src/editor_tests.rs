@@ -4949,6 +4949,206 @@ fn test_split_selection_into_lines(cx: &mut TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_select_columns(cx: &mut TestAppContext) {
+    init_test(cx, |_| {});
+
+    let mut cx = EditorTestContext::new(cx).await;
+    cx.set_state(indoc! {"
+        abcˇdef
+        gh
+        ijklmn
+        opqrst"});
+
+    // Short lines get an empty selection at their end, and the column is kept for the lines
+    // after them.
+    cx.update_editor(|editor, window, cx| {
+        editor.select_column_down(&SelectColumnDown, window, cx);
+    });
+    cx.assert_editor_state(indoc! {"
+        abcˇdef
+        ghˇ
+        ijklmn
+        opqrst"});
+    cx.update_editor(|editor, window, cx| {
+        editor.select_column_down(&SelectColumnDown, window, cx);
+        editor.select_column_right(&SelectColumnRight, window, cx);
+        editor.select_column_right(&SelectColumnRight, window, cx);
+    });
+    cx.assert_editor_state(indoc! {"
+        abc«deˇ»f
+        ghˇ
+        ijk«lmˇ»n
+        opqrst"});
+
+    cx.update_editor(|editor, window, cx| {
+        editor.select_column_up(&SelectColumnUp, window, cx);
+        editor.select_column_left(&SelectColumnLeft, window, cx);
+    });
+    cx.assert_editor_state(indoc! {"
+        abc«dˇ»ef
+        ghˇ
+        ijklmn
+        opqrst"});
+    cx.update_editor(|editor, window, cx| {
+        editor.select_column_down(&SelectColumnDown, window, cx);
+        editor.select_column_right(&SelectColumnRight, window, cx);
+        editor.copy(&Copy, window, cx);
+    });
+    cx.assert_editor_state(indoc! {"
+        abc«deˇ»f
+        ghˇ
+        ijk«lmˇ»n
+        opqrst"});
+
+    // Pasting the column into a single cursor inserts it as a rectangle, keeping the empty
+    // row and padding short lines.
+    cx.set_state(indoc! {"
+        12ˇ
+        x"});
+    cx.update_editor(|editor, window, cx| editor.paste(&Paste, window, cx));
+    cx.assert_editor_state(indoc! {"
+        12de
+        x
+          lmˇ"});
+
+    // Rows inserted before the rest of a line are padded to the width of the rectangle.
+    cx.set_state(indoc! {"
+        12ˇ345
+        12345
+        12345"});
+    cx.update_editor(|editor, window, cx| editor.paste(&Paste, window, cx));
+    cx.assert_editor_state(indoc! {"
+        12de345
+        12  345
+        12lmˇ345"});
+
+    // Rows past the end of the buffer are appended.
+    cx.set_state("1ˇ");
+    cx.update_editor(|editor, window, cx| editor.paste(&Paste, window, cx));
+    cx.assert_editor_state(indoc! {"
+        1de
+
+         lmˇ"});
+
+    // With as many cursors as rows, each cursor pastes its own row.
+    cx.set_state(indoc! {"
+        ˇ1
+        ˇ2
+        ˇ3"});
+    cx.update_editor(|editor, window, cx| editor.paste(&Paste, window, cx));
+    cx.assert_editor_state(indoc! {"
+        deˇ1
+        ˇ2
+        lmˇ3"});
+
+    // Cutting a column doesn't remove the lines ending before it.
+    cx.set_state(indoc! {"
+        abcˇdef
+        gh
+        ijklmn"});
+    cx.update_editor(|editor, window, cx| {
+        editor.select_column_down(&SelectColumnDown, window, cx);
+        editor.select_column_down(&SelectColumnDown, window, cx);
+        editor.select_column_right(&SelectColumnRight, window, cx);
+        editor.select_column_right(&SelectColumnRight, window, cx);
+        editor.cut(&Cut, window, cx);
+    });
+    cx.assert_editor_state(indoc! {"
+        abcˇf
+        ghˇ
+        ijkˇn"});
+}
+
+#[gpui::test]
+fn test_select_columns_with_mouse(cx: &mut TestAppContext) {
+    init_test(cx, |_| {});
+
+    let editor = cx.add_window(|window, cx| {
+        let buffer = MultiBuffer::build_simple("aaaaaa\n\ncccccc\n", cx);
+        build_editor(buffer, window, cx)
+    });
+
+    // Dragging a cursor being added within its line selects text as usual.
+    _ = editor.update(cx, |editor, window, cx| {
+        editor.begin_selection(DisplayPoint::new(DisplayRow(0), 1), true, 1, window, cx);
+        editor.drag_pending_selection_as_columns(
+            DisplayPoint::new(DisplayRow(0), 3),
+            3,
+            window,
+            cx,
+        );
+        editor.update_selection(
+            DisplayPoint::new(DisplayRow(0), 3),
+            3,
+            gpui::Point::<f32>::default(),
+            window,
+            cx,
+        );
+    });
+    assert_eq!(
+        editor
+            .update(cx, |editor, _, cx| editor.selections.display_ranges(cx))
+            .unwrap(),
+        [
+            DisplayPoint::new(DisplayRow(0), 0)..DisplayPoint::new(DisplayRow(0), 0),
+            DisplayPoint::new(DisplayRow(0), 1)..DisplayPoint::new(DisplayRow(0), 3),
+        ]
+    );
+
+    // Dragging it to other lines selects a column instead, with an empty selection on the
+    // empty line.
+    _ = editor.update(cx, |editor, window, cx| {
+        editor.drag_pending_selection_as_columns(
+            DisplayPoint::new(DisplayRow(2), 4),
+            4,
+            window,
+            cx,
+        );
+        editor.update_selection(
+            DisplayPoint::new(DisplayRow(2), 4),
+            4,
+            gpui::Point::<f32>::default(),
+            window,
+            cx,
+        );
+        editor.end_selection(window, cx);
+    });
+    assert_eq!(
+        editor
+            .update(cx, |editor, _, cx| editor.selections.display_ranges(cx))
+            .unwrap(),
+        [
+            DisplayPoint::new(DisplayRow(0), 1)..DisplayPoint::new(DisplayRow(0), 4),
+            DisplayPoint::new(DisplayRow(1), 0)..DisplayPoint::new(DisplayRow(1), 0),
+            DisplayPoint::new(DisplayRow(2), 1)..DisplayPoint::new(DisplayRow(2), 4),
+        ]
+    );
+
+    // A middle click without dragging doesn't select anything, so that it can paste instead.
+    _ = editor.update(cx, |editor, window, cx| {
+        editor.begin_columnar_selection(DisplayPoint::new(DisplayRow(2), 2), 2, true, window, cx);
+        assert!(!editor.has_pending_nonempty_selection());
+
+        // Dragging within the line selects a column of one line.
+        editor.update_selection(
+            DisplayPoint::new(DisplayRow(2), 5),
+            5,
+            gpui::Point::<f32>::default(),
+            window,
+            cx,
+        );
+        assert!(editor.has_pending_nonempty_selection());
+        editor.end_selection(window, cx);
+    });
+    assert_eq!(
+        editor
+            .update(cx, |editor, _, cx| editor.selections.display_ranges(cx))
+            .unwrap(),
+        [DisplayPoint::new(DisplayRow(2), 2)..DisplayPoint::new(DisplayRow(2), 5)]
+    );
+}
+
 #[gpui::test]
 async fn test_add_selection_above_below(cx: &mut TestAppContext) {
     init_test(cx, |_| {});
@@ -5426,6 +5626,101 @@ async fn test_select_previous_with_single_selection(cx: &mut gpui::TestAppContex
     cx.assert_editor_state("«abcˇ»\n«ˇabc» «abcˇ»\ndef«abcˇ»\n«abcˇ»");
 }
 
+#[gpui::test]
+async fn test_select_symbol_occurrences(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let mut cx = EditorLspTestContext::new_rust(
+        lsp::ServerCapabilities {
+            document_highlight_provider: Some(lsp::OneOf::Left(true)),
+            ..Default::default()
+        },
+        cx,
+    )
+    .await;
+
+    // The server only reports the occurrences of the parameter in the first function.
+    let highlight = |start, end| lsp::DocumentHighlight {
+        range: lsp::Range::new(lsp::Position::new(0, start), lsp::Position::new(0, end)),
+        kind: Some(lsp::DocumentHighlightKind::READ),
+    };
+    cx.handle_request::<lsp::request::DocumentHighlightRequest, _, _>(move |_, _, _| async move {
+        Ok(Some(vec![highlight(5, 8), highlight(22, 25)]))
+    });
+
+    cx.set_state(indoc! {"
+        fn a(fˇoo: u8) -> u8 { foo }
+        fn b(foo: u8) -> u8 { foo }
+    "});
+    cx.update_editor(|editor, window, cx| {
+        editor.select_all_symbol_occurrences(&SelectAllSymbolOccurrences, window, cx)
+    });
+    cx.run_until_parked();
+    cx.assert_editor_state(indoc! {"
+        fn a(«fooˇ»: u8) -> u8 { «fooˇ» }
+        fn b(foo: u8) -> u8 { foo }
+    "});
+
+    cx.set_state(indoc! {"
+        fn a(«fooˇ»: u8) -> u8 { foo }
+        fn b(foo: u8) -> u8 { foo }
+    "});
+    cx.update_editor(|editor, window, cx| {
+        editor.skip_and_select_next_occurrence(&SkipAndSelectNextOccurrence, window, cx)
+    });
+    cx.run_until_parked();
+    cx.assert_editor_state(indoc! {"
+        fn a(foo: u8) -> u8 { «fooˇ» }
+        fn b(foo: u8) -> u8 { foo }
+    "});
+
+    // Without any occurrences from the server, whole words are matched instead.
+    cx.handle_request::<lsp::request::DocumentHighlightRequest, _, _>(move |_, _, _| async move {
+        Ok(None)
+    });
+    cx.set_state(indoc! {"
+        fn a(fˇoo: u8) -> u8 { foo }
+        fn b(foo: u8) -> u8 { food }
+    "});
+    cx.update_editor(|editor, window, cx| {
+        editor.select_all_symbol_occurrences(&SelectAllSymbolOccurrences, window, cx)
+    });
+    cx.run_until_parked();
+    cx.assert_editor_state(indoc! {"
+        fn a(«fooˇ»: u8) -> u8 { «fooˇ» }
+        fn b(«fooˇ»: u8) -> u8 { food }
+    "});
+}
+
+#[gpui::test]
+async fn test_select_next_syntax_sibling(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let language = Arc::new(Language::new(
+        LanguageConfig::default(),
+        Some(tree_sitter_rust::LANGUAGE.into()),
+    ));
+    let mut cx = EditorTestContext::new(cx).await;
+    cx.update_buffer(|buffer, cx| buffer.set_language(Some(language), cx));
+    cx.set_state("fn a() { b(oˇne, two(), three, four); }");
+    cx.run_until_parked();
+
+    cx.update_editor(|editor, window, cx| {
+        editor.select_next_syntax_sibling(&SelectNextSyntaxSibling, window, cx)
+    });
+    cx.assert_editor_state("fn a() { b(«oneˇ», two(), «threeˇ», four); }");
+
+    cx.update_editor(|editor, window, cx| {
+        editor.select_next_syntax_sibling(&SelectNextSyntaxSibling, window, cx)
+    });
+    cx.assert_editor_state("fn a() { b(«oneˇ», two(), «threeˇ», «fourˇ»); }");
+
+    cx.update_editor(|editor, window, cx| {
+        editor.select_next_syntax_sibling(&SelectNextSyntaxSibling, window, cx)
+    });
+    cx.assert_editor_state("fn a() { b(«oneˇ», two(), «threeˇ», «fourˇ»); }");
+}
+
 #[gpui::test]
 async fn test_select_larger_smaller_syntax_node(cx: &mut gpui::TestAppContext) {
     init_test(cx, |_| {});
@@ -7070,6 +7365,33 @@ async fn test_snippets(cx: &mut gpui::TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_snippet_transformed_mirrors(cx: &mut TestAppContext) {
+    init_test(cx, |_| {});
+
+    let (text, insertion_ranges) = marked_text_ranges("let ˇ;", false);
+    let buffer = cx.update(|cx| MultiBuffer::build_simple(&text, cx));
+    let (editor, cx) = cx.add_window_view(|window, cx| build_editor(buffer, window, cx));
+
+    editor.update_in(cx, |editor, window, cx| {
+        let snippet = Snippet::parse("${1:name}: ${1/(.*)/${1:/pascalcase}/}$0").unwrap();
+        editor
+            .insert_snippet(&insertion_ranges, snippet, window, cx)
+            .unwrap();
+        let (expected_text, selection_ranges) = marked_text_ranges("let «name»: Name;", false);
+        assert_eq!(editor.text(cx), expected_text);
+        assert_eq!(editor.selections.ranges::<usize>(cx), selection_ranges);
+
+        // Mirrors are transformed when leaving the tabstop.
+        editor.insert("user_id", window, cx);
+        assert_eq!(editor.text(cx), "let user_id: Name;");
+        assert!(editor.move_to_next_snippet_tabstop(window, cx));
+        let (expected_text, selection_ranges) = marked_text_ranges("let user_id: UserIdˇ;", false);
+        assert_eq!(editor.text(cx), expected_text);
+        assert_eq!(editor.selections.ranges::<usize>(cx), selection_ranges);
+    });
+}
+
 #[gpui::test]
 async fn test_document_format_during_save(cx: &mut gpui::TestAppContext) {
     init_test(cx, |_| {});
@@ -10692,6 +11014,97 @@ async fn test_diagnostics_with_links(cx: &mut TestAppContext) {
     cx.update_editor(|editor, _, _| assert!(editor.hover_state.diagnostic_popover.is_some()))
 }
 
+#[gpui::test]
+async fn test_bookmarks(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+    let mut cx = EditorTestContext::new(cx).await;
+
+    cx.set_state(indoc! {"
+        oneˇ
+        two
+        three
+        four
+        five
+    "});
+    cx.update_editor(|editor, window, cx| {
+        editor.toggle_bookmark(&ToggleBookmark, window, cx);
+        editor.change_selections(None, window, cx, |s| {
+            s.select_ranges([Point::new(3, 0)..Point::new(3, 0)])
+        });
+        editor.set_bookmark(
+            &SetBookmark {
+                label: Some("four".into()),
+            },
+            window,
+            cx,
+        );
+    });
+
+    // Bookmarks follow edits.
+    cx.update_editor(|editor, window, cx| {
+        editor.change_selections(None, window, cx, |s| {
+            s.select_ranges([Point::new(1, 0)..Point::new(1, 0)])
+        });
+        editor.handle_input("inserted\n", window, cx);
+    });
+    let bookmarked_rows = |cx: &mut EditorTestContext| {
+        cx.update_editor(|editor, _, cx| {
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            editor
+                .bookmark_anchors(&snapshot, cx)
+                .into_iter()
+                .map(|anchor| anchor.to_point(&snapshot).row)
+                .collect::<Vec<_>>()
+        })
+    };
+    assert_eq!(bookmarked_rows(&mut cx), [0, 4]);
+
+    cx.update_editor(|editor, window, cx| {
+        editor.go_to_next_bookmark(&GoToNextBookmark, window, cx);
+    });
+    cx.assert_editor_state(indoc! {"
+        one
+        inserted
+        two
+        three
+        ˇfour
+        five
+    "});
+    cx.update_editor(|editor, window, cx| {
+        editor.go_to_next_bookmark(&GoToNextBookmark, window, cx);
+    });
+    cx.assert_editor_state(indoc! {"
+        ˇone
+        inserted
+        two
+        three
+        four
+        five
+    "});
+    cx.update_editor(|editor, window, cx| {
+        editor.go_to_previous_bookmark(&GoToPreviousBookmark, window, cx);
+    });
+    cx.assert_editor_state(indoc! {"
+        one
+        inserted
+        two
+        three
+        ˇfour
+        five
+    "});
+
+    // Toggling removes the bookmark under the cursor.
+    cx.update_editor(|editor, window, cx| {
+        editor.toggle_bookmark(&ToggleBookmark, window, cx);
+    });
+    assert_eq!(bookmarked_rows(&mut cx), [0]);
+
+    cx.update_editor(|editor, window, cx| {
+        editor.clear_bookmarks(&ClearBookmarks, window, cx);
+    });
+    assert_eq!(bookmarked_rows(&mut cx), Vec::<u32>::new());
+}
+
 #[gpui::test]
 async fn test_go_to_hunk(executor: BackgroundExecutor, cx: &mut gpui::TestAppContext) {
     init_test(cx, |_| {});
@@ -11854,6 +12267,78 @@ async fn test_completions_in_languages_with_extra_word_characters(cx: &mut gpui:
     });
 }
 
+#[gpui::test]
+async fn test_emmet_completions(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let mut cx = EditorLspTestContext::new(
+        Language::new(
+            LanguageConfig {
+                matcher: LanguageMatcher {
+                    path_suffixes: vec!["jsx".into()],
+                    ..Default::default()
+                },
+                overrides: [(
+                    "element".into(),
+                    LanguageConfigOverride {
+                        emmet: Override::Set(EmmetSyntax::Jsx),
+                        ..Default::default()
+                    },
+                )]
+                .into_iter()
+                .collect(),
+                ..Default::default()
+            },
+            Some(tree_sitter_typescript::LANGUAGE_TSX.into()),
+        )
+        .with_override_query("(jsx_element) @element")
+        .unwrap(),
+        lsp::ServerCapabilities {
+            completion_provider: Some(lsp::CompletionOptions::default()),
+            ..Default::default()
+        },
+        cx,
+    )
+    .await;
+    cx.lsp
+        .handle_request::<lsp::request::Completion, _, _>(move |_, _| async move {
+            Ok(Some(lsp::CompletionResponse::Array(Vec::new())))
+        });
+
+    // Abbreviations are only offered in the scopes that enable Emmet.
+    cx.set_state("const x = span.a+em.ˇ;");
+    cx.simulate_keystroke("b");
+    cx.executor().run_until_parked();
+    cx.update_editor(|editor, _, _| {
+        assert!(editor.context_menu.borrow().is_none());
+    });
+
+    cx.set_state("<div>span.a+em.ˇ</div>");
+    cx.simulate_keystroke("b");
+    cx.executor().run_until_parked();
+    cx.update_editor(|editor, _, _| {
+        if let Some(CodeContextMenu::Completions(menu)) = editor.context_menu.borrow_mut().as_ref()
+        {
+            assert_eq!(completion_menu_entries(&menu), &["span.a+em.b"]);
+        } else {
+            panic!("expected completion menu to be open");
+        }
+    });
+
+    let apply_additional_edits = cx.update_editor(|editor, window, cx| {
+        editor
+            .confirm_completion(&ConfirmCompletion::default(), window, cx)
+            .unwrap()
+    });
+    apply_additional_edits.await.unwrap();
+    cx.assert_editor_state(r#"<div><span className="a">ˇ</span><em className="b"></em></div>"#);
+
+    cx.update_editor(|editor, window, cx| {
+        assert!(editor.move_to_next_snippet_tabstop(window, cx));
+    });
+    cx.assert_editor_state(r#"<div><span className="a"></span><em className="b">ˇ</em></div>"#);
+}
+
 fn completion_menu_entries(menu: &CompletionsMenu) -> Vec<String> {
     let entries = menu.entries.borrow();
     entries.iter().map(|mat| mat.string.clone()).collect()
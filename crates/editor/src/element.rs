@@ -5,16 +5,17 @@ use crate::{
         Block, BlockContext, BlockStyle, DisplaySnapshot, HighlightedChunk, ToDisplayPoint,
     },
     editor_settings::{
-        CurrentLineHighlight, DoubleClickInMultibuffer, MultiCursorModifier, ScrollBeyondLastLine,
-        ScrollbarDiagnostics, ShowScrollbar,
+        CurrentLineHighlight, DoubleClickInMultibuffer, MinimapSide, MultiCursorModifier,
+        ScrollBeyondLastLine, ScrollbarDiagnostics, ShowMinimap, ShowMinimapSlider, ShowScrollbar,
     },
     git::blame::{CommitDetails, GitBlame},
+    highlight_ranges_in_range,
     hover_popover::{
         self, hover_at, HOVER_POPOVER_GAP, MIN_POPOVER_CHARACTER_WIDTH, MIN_POPOVER_LINE_HEIGHT,
     },
     items::BufferSearchHighlights,
     mouse_context_menu::{self, MenuPosition, MouseContextMenu},
-    scroll::{axis_pair, scroll_amount::ScrollAmount, AxisPair},
+    scroll::{axis_pair, scroll_amount::ScrollAmount, Autoscroll, AxisPair},
     BlockId, ChunkReplacement, CursorShape, CustomBlockId, DisplayPoint, DisplayRow,
     DocumentHighlightRead, DocumentHighlightWrite, EditDisplayMode, Editor, EditorMode,
     EditorSettings, EditorSnapshot, EditorStyle, ExpandExcerpts, FocusedBlock, GoToHunk,
@@ -36,8 +37,8 @@ use gpui::{
     GlobalElementId, Hitbox, Hsla, InteractiveElement, IntoElement, Keystroke, Length,
     ModifiersChangedEvent, MouseButton, MouseDownEvent, MouseMoveEvent, MouseUpEvent, PaintQuad,
     ParentElement, Pixels, ScrollDelta, ScrollWheelEvent, ShapedLine, SharedString, Size,
-    StatefulInteractiveElement, Style, Styled, Subscription, TextRun, TextStyleRefinement,
-    WeakEntity, Window,
+    StatefulInteractiveElement, Style, Styled, StyledText, Subscription, TextRun,
+    TextStyleRefinement, WeakEntity, Window,
 };
 use itertools::Itertools;
 use language::{
@@ -67,7 +68,7 @@ use std::{
 };
 use sum_tree::Bias;
 use text::BufferId;
-use theme::{ActiveTheme, Appearance, PlayerColor};
+use theme::{ActiveTheme, Appearance, PlayerColor, Theme};
 use ui::{
     h_flex, prelude::*, ButtonLike, ButtonStyle, ContextMenu, IconButtonShape, KeyBinding, Tooltip,
     POPOVER_Y_PADDING,
@@ -312,15 +313,22 @@ impl EditorElement {
         register_action(editor, window, Editor::split_selection_into_lines);
         register_action(editor, window, Editor::add_selection_above);
         register_action(editor, window, Editor::add_selection_below);
+        register_action(editor, window, Editor::select_column_up);
+        register_action(editor, window, Editor::select_column_down);
+        register_action(editor, window, Editor::select_column_left);
+        register_action(editor, window, Editor::select_column_right);
         register_action(editor, window, |editor, action, window, cx| {
             editor.select_next(action, window, cx).log_err();
         });
         register_action(editor, window, |editor, action, window, cx| {
             editor.select_previous(action, window, cx).log_err();
         });
+        register_action(editor, window, Editor::skip_and_select_next_occurrence);
+        register_action(editor, window, Editor::select_all_symbol_occurrences);
         register_action(editor, window, Editor::toggle_comments);
         register_action(editor, window, Editor::select_larger_syntax_node);
         register_action(editor, window, Editor::select_smaller_syntax_node);
+        register_action(editor, window, Editor::select_next_syntax_sibling);
         register_action(editor, window, Editor::select_enclosing_symbol);
         register_action(editor, window, Editor::move_to_enclosing_bracket);
         register_action(editor, window, Editor::undo_selection);
@@ -496,6 +504,17 @@ impl EditorElement {
         register_action(editor, window, Editor::insert_uuid_v4);
         register_action(editor, window, Editor::insert_uuid_v7);
         register_action(editor, window, Editor::open_selections_in_multibuffer);
+        register_action(editor, window, Editor::toggle_bookmark);
+        register_action(editor, window, Editor::set_bookmark);
+        register_action(editor, window, Editor::clear_bookmarks);
+        register_action(editor, window, Editor::go_to_next_bookmark);
+        register_action(editor, window, Editor::go_to_previous_bookmark);
+        register_action(editor, window, Editor::open_bookmarks_in_multibuffer);
+        register_action(editor, window, Editor::start_recording_macro);
+        register_action(editor, window, Editor::stop_recording_macro);
+        register_action(editor, window, Editor::play_last_macro);
+        register_action(editor, window, Editor::play_macro);
+        register_action(editor, window, Editor::save_last_macro);
     }
 
     fn register_key_listeners(&self, window: &mut Window, _: &mut App, layout: &EditorLayout) {
@@ -612,6 +631,7 @@ impl EditorElement {
 
         let point_for_position = position_map.point_for_position(event.position);
         let position = point_for_position.previous_valid;
+        let multi_cursor_setting = EditorSettings::get_global(cx).multi_cursor_modifier;
         if modifiers.shift && modifiers.alt {
             editor.select(
                 SelectPhase::BeginColumnar {
@@ -622,6 +642,21 @@ impl EditorElement {
                 window,
                 cx,
             );
+        } else if modifiers.alt
+            && !modifiers.shift
+            && !modifiers.secondary()
+            && multi_cursor_setting == MultiCursorModifier::CmdOrCtrl
+        {
+            // When alt isn't needed for adding cursors, alt-dragging selects a column.
+            editor.select(
+                SelectPhase::BeginColumnar {
+                    position,
+                    reset: true,
+                    goal_column: point_for_position.exact_unclipped.column(),
+                },
+                window,
+                cx,
+            );
         } else if modifiers.shift && !modifiers.control && !modifiers.alt && !modifiers.secondary()
         {
             editor.select(
@@ -633,7 +668,6 @@ impl EditorElement {
                 cx,
             );
         } else {
-            let multi_cursor_setting = EditorSettings::get_global(cx).multi_cursor_modifier;
             let multi_cursor_modifier = match multi_cursor_setting {
                 MultiCursorModifier::Alt => modifiers.alt,
                 MultiCursorModifier::CmdOrCtrl => modifiers.secondary(),
@@ -841,6 +875,19 @@ impl EditorElement {
             scroll_delta.x = scale_horizontal_mouse_autoscroll_delta(event.position.x - right);
         }
 
+        // Alt-clicking adds a cursor, and alt-dragging it to other lines selects a column.
+        if event.modifiers.alt
+            && !event.modifiers.shift
+            && EditorSettings::get_global(cx).multi_cursor_modifier == MultiCursorModifier::Alt
+        {
+            editor.drag_pending_selection_as_columns(
+                point_for_position.previous_valid,
+                point_for_position.exact_unclipped.column(),
+                window,
+                cx,
+            );
+        }
+
         editor.select(
             SelectPhase::Update {
                 position: point_for_position.previous_valid,
@@ -1463,6 +1510,216 @@ impl EditorElement {
         axis_pair(horizontal_scrollbar, vertical_scrollbar)
     }
 
+    fn minimap_width(&self, available_width: Pixels, cx: &App) -> Pixels {
+        let settings = EditorSettings::get_global(cx).minimap;
+        if settings.show == ShowMinimap::Never
+            || !matches!(self.editor.read(cx).mode, EditorMode::Full)
+        {
+            return Pixels::ZERO;
+        }
+        // Keep most of a narrow editor for the text.
+        px(settings.width.max(0.)).min(available_width / 3.).floor()
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn layout_minimap(
+        &self,
+        snapshot: &EditorSnapshot,
+        bounds: Bounds<Pixels>,
+        scroll_position: gpui::Point<f32>,
+        max_scroll_top: f32,
+        height_in_lines: f32,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> MinimapLayout {
+        let max_row = snapshot.max_point().row();
+        let minimap_rows = bounds.size.height / MinimapLayout::ROW_HEIGHT;
+        // When the buffer does not fit, the minimap scrolls along with the editor,
+        // so that both reach their end together.
+        let scrollable_rows = (max_row.next_row().as_f32() - minimap_rows).max(0.);
+        let scroll_ratio = if max_scroll_top > 0. {
+            (scrollable_rows / max_scroll_top).min(MinimapLayout::MAX_SCROLL_RATIO)
+        } else {
+            0.
+        };
+        let first_row = (scroll_position.y * scroll_ratio).min(scrollable_rows);
+        let start_row = DisplayRow(first_row as u32);
+        let end_row =
+            DisplayRow(((first_row + minimap_rows).ceil() as u32).min(max_row.next_row().0));
+        let column_count = (bounds.size.width / MinimapLayout::COLUMN_WIDTH) as u32;
+        let row_top =
+            |row: DisplayRow| bounds.top() + (row.as_f32() - first_row) * MinimapLayout::ROW_HEIGHT;
+        let columns_bounds = |row: DisplayRow, columns: Range<u32>| {
+            Bounds::new(
+                point(
+                    bounds.left() + MinimapLayout::COLUMN_WIDTH * columns.start as f32,
+                    row_top(row),
+                ),
+                size(
+                    MinimapLayout::COLUMN_WIDTH * columns.len() as f32,
+                    MinimapLayout::ROW_HEIGHT,
+                ),
+            )
+        };
+
+        // Draw every run of non-whitespace characters of the same color as a block.
+        let mut text_quads = Vec::new();
+        let mut row = start_row;
+        let mut column = 0;
+        let mut run: Option<(u32, Hsla)> = None;
+        let mut finish_run = |run: &mut Option<(u32, Hsla)>, row: DisplayRow, column: u32| {
+            if let Some((start_column, color)) = run.take() {
+                text_quads.push(fill(columns_bounds(row, start_column..column), color));
+            }
+        };
+        for chunk in snapshot.highlighted_chunks(start_row..end_row, true, &self.style) {
+            let color = chunk
+                .style
+                .and_then(|style| style.color)
+                .unwrap_or(self.style.text.color);
+            for character in chunk.text.chars() {
+                if character == '\n' {
+                    finish_run(&mut run, row, column);
+                    row.0 += 1;
+                    column = 0;
+                    continue;
+                }
+                if column == column_count {
+                    continue;
+                }
+                if character.is_whitespace() {
+                    finish_run(&mut run, row, column);
+                } else if run.map_or(true, |(_, run_color)| run_color != color) {
+                    finish_run(&mut run, row, column);
+                    run = Some((column, color));
+                }
+                column += 1;
+            }
+        }
+        finish_run(&mut run, row, column);
+
+        let start_point = DisplayPoint::new(start_row, 0);
+        let end_point = if end_row > max_row {
+            snapshot.max_point()
+        } else {
+            DisplayPoint::new(end_row, 0)
+        };
+        let buffer_range = start_point.to_point(snapshot)..end_point.to_point(snapshot);
+        let anchor_range = snapshot.buffer_snapshot.anchor_before(buffer_range.start)
+            ..snapshot.buffer_snapshot.anchor_after(buffer_range.end);
+        let scrollbar_settings = EditorSettings::get_global(cx).scrollbar;
+        let theme = cx.theme();
+
+        // Search results and symbol occurrences are highlighted in place.
+        let mut highlight_quads = Vec::new();
+        for (highlight_id, (_, ranges)) in &self.editor.read(cx).background_highlights {
+            let is_search_highlights = *highlight_id == TypeId::of::<BufferSearchHighlights>();
+            let is_symbol_occurrences = *highlight_id == TypeId::of::<DocumentHighlightRead>()
+                || *highlight_id == TypeId::of::<DocumentHighlightWrite>();
+            if !(is_search_highlights && scrollbar_settings.search_results)
+                && !(is_symbol_occurrences && scrollbar_settings.selected_symbol)
+            {
+                continue;
+            }
+            let color = highlight_marker_color(is_symbol_occurrences, theme);
+            for range in highlight_ranges_in_range(ranges, &anchor_range, snapshot) {
+                for row in range.start.row().0.max(start_row.0)..=range.end.row().0 {
+                    let row = DisplayRow(row);
+                    if row >= end_row {
+                        break;
+                    }
+                    let start_column = if row == range.start.row() {
+                        range.start.column().min(column_count)
+                    } else {
+                        0
+                    };
+                    let end_column = if row == range.end.row() {
+                        range.end.column().max(start_column + 1).min(column_count)
+                    } else {
+                        column_count
+                    };
+                    highlight_quads
+                        .push(fill(columns_bounds(row, start_column..end_column), color));
+                }
+            }
+        }
+
+        // Git diff hunks and diagnostics are marked on the edges.
+        let mut marker_quads = Vec::new();
+        let mut push_marker = |rows: Range<DisplayRow>, left: Pixels, color: Hsla| {
+            marker_quads.push(fill(
+                Bounds::new(
+                    point(left, row_top(rows.start)),
+                    size(
+                        MinimapLayout::MARKER_WIDTH,
+                        MinimapLayout::ROW_HEIGHT * (rows.end.0 - rows.start.0).max(1) as f32,
+                    ),
+                ),
+                color,
+            ));
+        };
+        if scrollbar_settings.git_diff {
+            for hunk in snapshot
+                .buffer_snapshot
+                .diff_hunks_in_range(buffer_range.clone())
+            {
+                let start_row = MultiBufferPoint::new(hunk.row_range.start.0, 0)
+                    .to_display_point(snapshot)
+                    .row();
+                let end_row = MultiBufferPoint::new(hunk.row_range.end.0, 0)
+                    .to_display_point(snapshot)
+                    .row();
+                push_marker(
+                    start_row..end_row,
+                    bounds.left(),
+                    diff_hunk_marker_color(&hunk.status(), theme),
+                );
+            }
+        }
+        if scrollbar_settings.diagnostics != ScrollbarDiagnostics::None {
+            let diagnostics = snapshot
+                .buffer_snapshot
+                .diagnostics_in_range::<Point>(buffer_range)
+                .filter(|diagnostic| {
+                    is_diagnostic_marker_shown(
+                        scrollbar_settings.diagnostics,
+                        diagnostic.diagnostic.severity,
+                    )
+                })
+                // Paint the most severe diagnostics last.
+                .sorted_by_key(|diagnostic| cmp::Reverse(diagnostic.diagnostic.severity));
+            for diagnostic in diagnostics {
+                let start_row = diagnostic.range.start.to_display_point(snapshot).row();
+                let end_row = diagnostic.range.end.to_display_point(snapshot).row();
+                push_marker(
+                    start_row..end_row.next_row(),
+                    bounds.right() - MinimapLayout::MARKER_WIDTH,
+                    diagnostic_marker_color(diagnostic.diagnostic.severity, theme),
+                );
+            }
+        }
+
+        MinimapLayout {
+            hitbox: window.insert_hitbox(bounds, false),
+            first_row,
+            scroll_ratio,
+            visible_line_count: height_in_lines,
+            slider_bounds: Bounds::new(
+                point(
+                    bounds.left(),
+                    row_top(DisplayRow(0)) + scroll_position.y * MinimapLayout::ROW_HEIGHT,
+                ),
+                size(
+                    bounds.size.width,
+                    height_in_lines * MinimapLayout::ROW_HEIGHT,
+                ),
+            ),
+            text_quads,
+            highlight_quads,
+            marker_quads,
+        }
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn prepaint_crease_toggles(
         &self,
@@ -1981,6 +2238,63 @@ impl EditorElement {
         })
     }
 
+    #[allow(clippy::too_many_arguments)]
+    fn layout_bookmark_indicators(
+        &self,
+        line_height: Pixels,
+        range: Range<DisplayRow>,
+        scroll_pixel_position: gpui::Point<Pixels>,
+        gutter_dimensions: &GutterDimensions,
+        gutter_hitbox: &Hitbox,
+        rows_with_hunk_bounds: &HashMap<DisplayRow, Bounds<Pixels>>,
+        snapshot: &EditorSnapshot,
+        show_runnables: bool,
+        code_actions_row: Option<DisplayRow>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Vec<AnyElement> {
+        self.editor.update(cx, |editor, cx| {
+            let mut rows = editor
+                .bookmark_anchors(&snapshot.buffer_snapshot, cx)
+                .into_iter()
+                .filter_map(|anchor| {
+                    let point = anchor.to_point(&snapshot.buffer_snapshot);
+                    let (buffer, buffer_range) = snapshot
+                        .buffer_snapshot
+                        .buffer_line_for_row(MultiBufferRow(point.row))?;
+                    // Run indicators take precedence over bookmarks.
+                    if show_runnables
+                        && editor
+                            .tasks
+                            .contains_key(&(buffer.remote_id(), buffer_range.start.row))
+                    {
+                        return None;
+                    }
+                    let row = point.to_display_point(snapshot).row();
+                    (range.contains(&row) && Some(row) != code_actions_row).then_some(row)
+                })
+                .collect::<Vec<_>>();
+            rows.dedup();
+
+            rows.into_iter()
+                .map(|row| {
+                    let button = editor.render_bookmark_indicator(row, cx);
+                    prepaint_gutter_button(
+                        button,
+                        row,
+                        line_height,
+                        gutter_dimensions,
+                        scroll_pixel_position,
+                        gutter_hitbox,
+                        rows_with_hunk_bounds,
+                        window,
+                        cx,
+                    )
+                })
+                .collect()
+        })
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn layout_code_actions_indicator(
         &self,
@@ -3075,6 +3389,120 @@ impl EditorElement {
         header
     }
 
+    /// Lays out the headers of the syntactic scopes (functions, impls, modules, …)
+    /// that enclose the first visible line, so that they stay pinned at the top of
+    /// the editor while their bodies are scrolled through.
+    #[allow(clippy::too_many_arguments)]
+    fn layout_sticky_scopes(
+        &self,
+        snapshot: &EditorSnapshot,
+        scroll_position: gpui::Point<f32>,
+        header_lines: u32,
+        line_height: Pixels,
+        em_advance: Pixels,
+        gutter_hitbox: &Hitbox,
+        text_hitbox: &Hitbox,
+        content_origin: gpui::Point<Pixels>,
+        scroll_pixel_position: gpui::Point<Pixels>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Vec<AnyElement> {
+        let settings = EditorSettings::get_global(cx).sticky_scroll;
+        if !settings.enabled || settings.max_depth == 0 || snapshot.mode != EditorMode::Full {
+            return Vec::new();
+        }
+
+        let top = scroll_position.y + header_lines as f32;
+        let top_row = DisplayRow(top.floor() as u32).min(snapshot.max_point().row());
+        let top_offset = DisplayPoint::new(top_row, 0).to_offset(snapshot, Bias::Left);
+        let Some((_, items)) = snapshot
+            .buffer_snapshot
+            .symbols_containing(top_offset, Some(&self.style.syntax))
+        else {
+            return Vec::new();
+        };
+
+        let mut scopes = Vec::new();
+        for item in items.into_iter().take(settings.max_depth) {
+            let ix = scopes.len();
+            let start = item.range.start.to_display_point(snapshot);
+            let end = item.range.end.to_display_point(snapshot);
+            // Only pin scopes whose header has already scrolled underneath the
+            // lines pinned above it.
+            if start.row().as_f32() >= top + ix as f32 {
+                break;
+            }
+            // Push the scope up as its last line approaches the pinned area.
+            let line = (ix as f32).min(end.row().as_f32() - top);
+            scopes.push((ix, line, start, item));
+        }
+
+        let colors = cx.theme().colors();
+        let gutter_background = colors.editor_gutter_background;
+        let background = colors.editor_background;
+        let border_color = colors.border_variant;
+        let width = text_hitbox.right() - gutter_hitbox.left();
+        let scope_count = scopes.len();
+
+        scopes
+            .into_iter()
+            .map(|(ix, line, start, item)| {
+                let jump_to = item.range.start;
+                let text_left = content_origin.x - text_hitbox.left()
+                    + em_advance * start.column() as f32
+                    - scroll_pixel_position.x;
+                let editor = self.editor.clone();
+                let mut element = h_flex()
+                    .id(("sticky-scope", ix))
+                    .w(width)
+                    .h(line_height)
+                    .when(ix + 1 == scope_count, |div| {
+                        div.border_b_1().border_color(border_color)
+                    })
+                    .cursor_pointer()
+                    .child(
+                        div()
+                            .w(gutter_hitbox.size.width)
+                            .h_full()
+                            .bg(gutter_background),
+                    )
+                    .child(
+                        div()
+                            .relative()
+                            .flex_1()
+                            .h_full()
+                            .overflow_hidden()
+                            .bg(background)
+                            .child(
+                                div().absolute().left(text_left).child(
+                                    StyledText::new(item.text)
+                                        .with_highlights(&self.style.text, item.highlight_ranges),
+                                ),
+                            ),
+                    )
+                    .on_mouse_down(MouseButton::Left, move |_, window, cx| {
+                        cx.stop_propagation();
+                        editor.update(cx, |editor, cx| {
+                            editor.change_selections(
+                                Some(Autoscroll::top_relative(header_lines as usize + ix)),
+                                window,
+                                cx,
+                                |selections| selections.select_anchor_ranges([jump_to..jump_to]),
+                            );
+                        });
+                    })
+                    .into_any_element();
+
+                let origin = point(
+                    gutter_hitbox.left(),
+                    text_hitbox.top() + (header_lines as f32 + line) * line_height,
+                );
+                element.prepaint_as_root(origin, AvailableSpace::min_size(), window, cx);
+                element
+            })
+            .collect()
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn layout_cursor_popovers(
         &self,
@@ -4588,6 +5016,10 @@ impl EditorElement {
                 }
             });
 
+            for bookmark_indicator in layout.bookmark_indicators.iter_mut() {
+                bookmark_indicator.paint(window, cx);
+            }
+
             for test_indicator in layout.test_indicators.iter_mut() {
                 test_indicator.paint(window, cx);
             }
@@ -5112,6 +5544,124 @@ impl EditorElement {
         }
     }
 
+    fn paint_minimap(&self, layout: &mut EditorLayout, window: &mut Window, cx: &mut App) {
+        let Some(minimap) = layout.minimap.take() else {
+            return;
+        };
+        let hitbox = minimap.hitbox.clone();
+        let slider_bounds = minimap.slider_bounds;
+        let is_hovered = hitbox.is_hovered(window);
+        let is_dragging = self.editor.read(cx).scroll_manager.is_dragging_minimap();
+        let show_slider = match EditorSettings::get_global(cx).minimap.show_slider {
+            ShowMinimapSlider::Always => true,
+            ShowMinimapSlider::Hover => is_hovered || is_dragging,
+        };
+
+        window.paint_layer(hitbox.bounds, |window| {
+            window.with_content_mask(
+                Some(ContentMask {
+                    bounds: hitbox.bounds,
+                }),
+                |window| {
+                    window.paint_quad(fill(hitbox.bounds, self.style.background));
+                    for quad in minimap
+                        .highlight_quads
+                        .into_iter()
+                        .chain(minimap.text_quads)
+                        .chain(minimap.marker_quads)
+                    {
+                        window.paint_quad(quad);
+                    }
+                    if show_slider {
+                        window.paint_quad(fill(
+                            slider_bounds,
+                            cx.theme().colors().scrollbar_thumb_background,
+                        ));
+                    }
+                },
+            );
+        });
+        window.set_cursor_style(CursorStyle::Arrow, &hitbox);
+
+        // Dragging the slider by one pixel scrolls the editor by this many rows.
+        let rows_per_pixel = 1. / (MinimapLayout::ROW_HEIGHT * (1. - minimap.scroll_ratio)).0;
+        window.on_mouse_event({
+            let editor = self.editor.clone();
+            let hitbox = hitbox.clone();
+            let mut mouse_position = window.mouse_position();
+            move |event: &MouseMoveEvent, phase, window, cx| {
+                if phase == DispatchPhase::Capture {
+                    return;
+                }
+
+                editor.update(cx, |editor, cx| {
+                    if event.pressed_button == Some(MouseButton::Left)
+                        && editor.scroll_manager.is_dragging_minimap()
+                    {
+                        let mut position = editor.scroll_position(cx);
+                        position.y += (event.position.y - mouse_position.y).0 * rows_per_pixel;
+                        position.y = position.y.max(0.);
+                        editor.set_scroll_position(position, window, cx);
+                        cx.stop_propagation();
+                    } else {
+                        if editor.scroll_manager.is_dragging_minimap() {
+                            editor.scroll_manager.set_is_dragging_minimap(false, cx);
+                        }
+                        if hitbox.is_hovered(window) != is_hovered {
+                            cx.notify();
+                        }
+                    }
+                    mouse_position = event.position;
+                })
+            }
+        });
+
+        if is_dragging {
+            window.on_mouse_event({
+                let editor = self.editor.clone();
+                move |_: &MouseUpEvent, phase, _, cx| {
+                    if phase == DispatchPhase::Capture {
+                        return;
+                    }
+
+                    editor.update(cx, |editor, cx| {
+                        editor.scroll_manager.set_is_dragging_minimap(false, cx);
+                        cx.stop_propagation();
+                    });
+                }
+            });
+        } else {
+            window.on_mouse_event({
+                let editor = self.editor.clone();
+                let first_row = minimap.first_row;
+                let visible_line_count = minimap.visible_line_count;
+                move |event: &MouseDownEvent, phase, window, cx| {
+                    if phase == DispatchPhase::Capture
+                        || event.button != MouseButton::Left
+                        || !hitbox.is_hovered(window)
+                    {
+                        return;
+                    }
+
+                    editor.update(cx, |editor, cx| {
+                        editor.scroll_manager.set_is_dragging_minimap(true, cx);
+
+                        // Clicking outside of the slider centers the clicked row in the editor.
+                        let y = event.position.y;
+                        if y < slider_bounds.top() || slider_bounds.bottom() < y {
+                            let row = first_row + (y - hitbox.top()) / MinimapLayout::ROW_HEIGHT;
+                            let mut position = editor.scroll_position(cx);
+                            position.y = (row - visible_line_count / 2.).max(0.);
+                            editor.set_scroll_position(position, window, cx);
+                        }
+
+                        cx.stop_propagation();
+                    });
+                }
+            });
+        }
+    }
+
     fn collect_fast_scrollbar_markers(
         &self,
         layout: &EditorLayout,
@@ -5179,15 +5729,10 @@ impl EditorElement {
                                         if end_display_row != start_display_row {
                                             end_display_row.0 -= 1;
                                         }
-                                        let color = match &hunk.status() {
-                                            DiffHunkStatus::Added => theme.status().created,
-                                            DiffHunkStatus::Modified => theme.status().modified,
-                                            DiffHunkStatus::Removed => theme.status().deleted,
-                                        };
                                         ColoredRange {
                                             start: start_display_row,
                                             end: end_display_row,
-                                            color,
+                                            color: diff_hunk_marker_color(&hunk.status(), &theme),
                                         }
                                     });
 
@@ -5209,10 +5754,8 @@ impl EditorElement {
                                 if (is_search_highlights && scrollbar_settings.search_results)
                                     || (is_symbol_occurrences && scrollbar_settings.selected_symbol)
                                 {
-                                    let mut color = theme.status().info;
-                                    if is_symbol_occurrences {
-                                        color.fade_out(0.5);
-                                    }
+                                    let color =
+                                        highlight_marker_color(is_symbol_occurrences, &theme);
                                     let marker_row_ranges = background_ranges.iter().map(|range| {
                                         let display_start = range
                                             .start
@@ -5238,28 +5781,10 @@ impl EditorElement {
                                     .diagnostics_in_range::<Point>(Point::zero()..max_point)
                                     // Don't show diagnostics the user doesn't care about
                                     .filter(|diagnostic| {
-                                        match (
+                                        is_diagnostic_marker_shown(
                                             scrollbar_settings.diagnostics,
                                             diagnostic.diagnostic.severity,
-                                        ) {
-                                            (ScrollbarDiagnostics::All, _) => true,
-                                            (
-                                                ScrollbarDiagnostics::Error,
-                                                DiagnosticSeverity::ERROR,
-                                            ) => true,
-                                            (
-                                                ScrollbarDiagnostics::Warning,
-                                                DiagnosticSeverity::ERROR
-                                                | DiagnosticSeverity::WARNING,
-                                            ) => true,
-                                            (
-                                                ScrollbarDiagnostics::Information,
-                                                DiagnosticSeverity::ERROR
-                                                | DiagnosticSeverity::WARNING
-                                                | DiagnosticSeverity::INFORMATION,
-                                            ) => true,
-                                            (_, _) => false,
-                                        }
+                                        )
                                     })
                                     // We want to sort by severity, in order to paint the most severe diagnostics last.
                                     .sorted_by_key(|diagnostic| {
@@ -5275,16 +5800,13 @@ impl EditorElement {
                                         .range
                                         .end
                                         .to_display_point(&snapshot.display_snapshot);
-                                    let color = match diagnostic.diagnostic.severity {
-                                        DiagnosticSeverity::ERROR => theme.status().error,
-                                        DiagnosticSeverity::WARNING => theme.status().warning,
-                                        DiagnosticSeverity::INFORMATION => theme.status().info,
-                                        _ => theme.status().hint,
-                                    };
                                     ColoredRange {
                                         start: start_display.row(),
                                         end: end_display.row(),
-                                        color,
+                                        color: diagnostic_marker_color(
+                                            diagnostic.diagnostic.severity,
+                                            &theme,
+                                        ),
                                     }
                                 });
                                 marker_quads.extend(
@@ -6688,7 +7210,21 @@ impl Element for EditorElement {
                             cx,
                         )
                         .unwrap_or_default();
-                    let text_width = bounds.size.width - gutter_dimensions.width;
+                    let minimap_settings = EditorSettings::get_global(cx).minimap;
+                    let minimap_width =
+                        self.minimap_width(bounds.size.width - gutter_dimensions.width, cx);
+                    // On the right, the minimap sits between the text and the vertical scrollbar.
+                    let (left_minimap_width, right_minimap_width) = match minimap_settings.side {
+                        _ if minimap_width == Pixels::ZERO => (Pixels::ZERO, Pixels::ZERO),
+                        MinimapSide::Left => (minimap_width, Pixels::ZERO),
+                        MinimapSide::Right => {
+                            (Pixels::ZERO, minimap_width + self.style.scrollbar_width)
+                        }
+                    };
+                    let text_width = bounds.size.width
+                        - gutter_dimensions.width
+                        - left_minimap_width
+                        - right_minimap_width;
 
                     let editor_width = text_width - gutter_dimensions.margin - em_width;
 
@@ -6727,8 +7263,16 @@ impl Element for EditorElement {
                         .collect::<SmallVec<[_; 2]>>();
 
                     let hitbox = window.insert_hitbox(bounds, false);
-                    let gutter_hitbox =
-                        window.insert_hitbox(gutter_bounds(bounds, gutter_dimensions), false);
+                    let gutter_hitbox = window.insert_hitbox(
+                        gutter_bounds(
+                            Bounds::from_corners(
+                                bounds.origin + point(left_minimap_width, Pixels::ZERO),
+                                bounds.bottom_right(),
+                            ),
+                            gutter_dimensions,
+                        ),
+                        false,
+                    );
                     let text_hitbox = window.insert_hitbox(
                         Bounds {
                             origin: gutter_hitbox.top_right(),
@@ -7205,6 +7749,24 @@ impl Element for EditorElement {
                         );
                     });
 
+                    let sticky_scopes = self.layout_sticky_scopes(
+                        &snapshot,
+                        scroll_position,
+                        if sticky_buffer_header.is_some() {
+                            FILE_HEADER_HEIGHT
+                        } else {
+                            0
+                        },
+                        line_height,
+                        em_advance,
+                        &gutter_hitbox,
+                        &text_hitbox,
+                        content_origin,
+                        scroll_pixel_position,
+                        window,
+                        cx,
+                    );
+
                     let cursors = self.collect_cursors(&snapshot, cx);
                     let visible_row_range = start_row..end_row;
                     let non_visible_cursors = cursors
@@ -7238,6 +7800,25 @@ impl Element for EditorElement {
                         cx,
                     );
 
+                    let minimap = (minimap_width > Pixels::ZERO).then(|| {
+                        let minimap_origin = match minimap_settings.side {
+                            MinimapSide::Left => bounds.origin,
+                            MinimapSide::Right => point(
+                                bounds.right() - self.style.scrollbar_width - minimap_width,
+                                bounds.top(),
+                            ),
+                        };
+                        self.layout_minimap(
+                            &snapshot,
+                            Bounds::new(minimap_origin, size(minimap_width, bounds.size.height)),
+                            scroll_position,
+                            max_scroll_top,
+                            height_in_lines,
+                            window,
+                            cx,
+                        )
+                    });
+
                     let gutter_settings = EditorSettings::get_global(cx).gutter;
 
                     let rows_with_hunk_bounds = display_hunks
@@ -7347,6 +7928,22 @@ impl Element for EditorElement {
                         Vec::new()
                     };
 
+                    let bookmark_indicators = self.layout_bookmark_indicators(
+                        line_height,
+                        start_row..end_row,
+                        scroll_pixel_position,
+                        &gutter_dimensions,
+                        &gutter_hitbox,
+                        &rows_with_hunk_bounds,
+                        &snapshot,
+                        gutter_settings.runnables,
+                        newest_selection_head
+                            .filter(|_| code_actions_indicator.is_some())
+                            .map(|head| head.row()),
+                        window,
+                        cx,
+                    );
+
                     self.layout_signature_help(
                         &hitbox,
                         content_origin,
@@ -7489,6 +8086,7 @@ impl Element for EditorElement {
                         display_hunks,
                         content_origin,
                         scrollbars_layout,
+                        minimap,
                         active_rows,
                         highlighted_rows,
                         highlighted_ranges,
@@ -7506,12 +8104,14 @@ impl Element for EditorElement {
                         diff_hunk_controls: hunk_controls,
                         mouse_context_menu,
                         test_indicators,
+                        bookmark_indicators,
                         code_actions_indicator,
                         crease_toggles,
                         crease_trailers,
                         tab_invisible,
                         space_invisible,
                         sticky_buffer_header,
+                        sticky_scopes,
                     }
                 })
             })
@@ -7572,12 +8172,19 @@ impl Element for EditorElement {
                         });
                     }
 
+                    // Paint inner scopes first so that the ones being pushed up slide
+                    // underneath their parents.
+                    for sticky_scope in layout.sticky_scopes.iter_mut().rev() {
+                        sticky_scope.paint(window, cx);
+                    }
+
                     window.with_element_namespace("blocks", |window| {
                         if let Some(mut sticky_header) = layout.sticky_buffer_header.take() {
                             sticky_header.paint(window, cx)
                         }
                     });
 
+                    self.paint_minimap(layout, window, cx);
                     self.paint_scrollbars(layout, window, cx);
                     self.paint_inline_completion_popover(layout, window, cx);
                     self.paint_mouse_context_menu(layout, window, cx);
@@ -7659,6 +8266,7 @@ pub struct EditorLayout {
     gutter_hitbox: Hitbox,
     content_origin: gpui::Point<Pixels>,
     scrollbars_layout: AxisPair<Option<ScrollbarLayout>>,
+    minimap: Option<MinimapLayout>,
     mode: EditorMode,
     wrap_guides: SmallVec<[(Pixels, bool); 2]>,
     indent_guides: Option<Vec<IndentGuideLayout>>,
@@ -7679,6 +8287,7 @@ pub struct EditorLayout {
     selections: Vec<(PlayerColor, Vec<SelectionLayout>)>,
     code_actions_indicator: Option<AnyElement>,
     test_indicators: Vec<AnyElement>,
+    bookmark_indicators: Vec<AnyElement>,
     crease_toggles: Vec<Option<AnyElement>>,
     diff_hunk_controls: Vec<AnyElement>,
     crease_trailers: Vec<Option<CreaseTrailerLayout>>,
@@ -7687,6 +8296,7 @@ pub struct EditorLayout {
     tab_invisible: ShapedLine,
     space_invisible: ShapedLine,
     sticky_buffer_header: Option<AnyElement>,
+    sticky_scopes: Vec<AnyElement>,
 }
 
 impl EditorLayout {
@@ -7707,6 +8317,71 @@ struct ColoredRange<T> {
     color: Hsla,
 }
 
+fn diff_hunk_marker_color(status: &DiffHunkStatus, theme: &Theme) -> Hsla {
+    match status {
+        DiffHunkStatus::Added => theme.status().created,
+        DiffHunkStatus::Modified => theme.status().modified,
+        DiffHunkStatus::Removed => theme.status().deleted,
+    }
+}
+
+fn diagnostic_marker_color(severity: DiagnosticSeverity, theme: &Theme) -> Hsla {
+    match severity {
+        DiagnosticSeverity::ERROR => theme.status().error,
+        DiagnosticSeverity::WARNING => theme.status().warning,
+        DiagnosticSeverity::INFORMATION => theme.status().info,
+        _ => theme.status().hint,
+    }
+}
+
+/// The color of the buffer search result markers, or of the selected symbol occurrence ones.
+fn highlight_marker_color(is_symbol_occurrence: bool, theme: &Theme) -> Hsla {
+    let mut color = theme.status().info;
+    if is_symbol_occurrence {
+        color.fade_out(0.5);
+    }
+    color
+}
+
+fn is_diagnostic_marker_shown(setting: ScrollbarDiagnostics, severity: DiagnosticSeverity) -> bool {
+    match (setting, severity) {
+        (ScrollbarDiagnostics::All, _) => true,
+        (ScrollbarDiagnostics::Error, DiagnosticSeverity::ERROR) => true,
+        (
+            ScrollbarDiagnostics::Warning,
+            DiagnosticSeverity::ERROR | DiagnosticSeverity::WARNING,
+        ) => true,
+        (
+            ScrollbarDiagnostics::Information,
+            DiagnosticSeverity::ERROR
+            | DiagnosticSeverity::WARNING
+            | DiagnosticSeverity::INFORMATION,
+        ) => true,
+        (_, _) => false,
+    }
+}
+
+struct MinimapLayout {
+    hitbox: Hitbox,
+    /// The fractional display row shown at the top of the minimap.
+    first_row: f32,
+    /// How far the minimap scrolls for every row the editor scrolls.
+    scroll_ratio: f32,
+    visible_line_count: f32,
+    slider_bounds: Bounds<Pixels>,
+    text_quads: Vec<PaintQuad>,
+    highlight_quads: Vec<PaintQuad>,
+    marker_quads: Vec<PaintQuad>,
+}
+
+impl MinimapLayout {
+    const ROW_HEIGHT: Pixels = px(2.);
+    const COLUMN_WIDTH: Pixels = px(1.);
+    const MARKER_WIDTH: Pixels = px(3.);
+    /// Keeps the slider moving when the editor scrolls, however long the buffer.
+    const MAX_SCROLL_RATIO: f32 = 0.95;
+}
+
 #[derive(Clone)]
 struct ScrollbarLayout {
     hitbox: Hitbox,
@@ -8295,12 +8970,14 @@ mod tests {
     use super::*;
     use crate::{
         display_map::{BlockPlacement, BlockProperties},
+        editor_settings::{MinimapContent, StickyScrollContent},
         editor_tests::{init_test, update_test_language_settings},
         Editor, MultiBuffer,
     };
     use gpui::{TestAppContext, VisualTestContext};
-    use language::language_settings;
+    use language::{language_settings, Buffer, Language, LanguageConfig};
     use log::info;
+    use settings::SettingsStore;
     use similar::DiffableStr;
     use std::num::NonZeroU32;
     use util::test::sample_text;
@@ -8606,6 +9283,134 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    fn test_minimap_layout(cx: &mut TestAppContext) {
+        init_test(cx, |_| {});
+        cx.update(|cx| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.minimap = Some(MinimapContent {
+                        show: Some(ShowMinimap::Always),
+                        width: Some(60.),
+                        ..Default::default()
+                    });
+                });
+            });
+        });
+
+        let window = cx.add_window(|window, cx| {
+            let buffer = MultiBuffer::build_simple("fn  main() {}\n\nlet x = 1;", cx);
+            Editor::new(EditorMode::Full, buffer, None, true, window, cx)
+        });
+        let cx = &mut VisualTestContext::from_window(*window, cx);
+        let editor = window.root(cx).unwrap();
+        let style = cx.update(|_, cx| editor.read(cx).style().unwrap().clone());
+        let scrollbar_width = style.scrollbar_width;
+        let (_, state) = cx.draw(
+            point(px(500.), px(500.)),
+            size(px(500.), px(500.)),
+            |_, _| EditorElement::new(&editor, style),
+        );
+
+        // The minimap sits between the text and the vertical scrollbar.
+        let minimap = state.minimap.as_ref().unwrap();
+        assert_eq!(minimap.hitbox.size.width, px(60.));
+        assert_eq!(
+            minimap.hitbox.right(),
+            state.hitbox.right() - scrollbar_width
+        );
+        assert!((state.position_map.text_hitbox.right() - minimap.hitbox.left()).abs() < px(0.01));
+
+        // One block per word.
+        assert_eq!(minimap.text_quads.len(), 7);
+        assert_eq!(
+            minimap.text_quads[1].bounds.origin.x,
+            minimap.hitbox.left() + px(4.)
+        );
+        assert_eq!(minimap.text_quads[1].bounds.size.width, px(6.));
+        assert_eq!(
+            minimap.text_quads[3].bounds.origin.y,
+            minimap.hitbox.top() + px(4.)
+        );
+        assert!(minimap.highlight_quads.is_empty() && minimap.marker_quads.is_empty());
+    }
+
+    #[gpui::test]
+    fn test_sticky_scopes_layout(cx: &mut TestAppContext) {
+        init_test(cx, |_| {});
+        let set_sticky_scroll = |max_depth: usize, cx: &mut App| {
+            cx.update_global::<SettingsStore, _>(|settings, cx| {
+                settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                    settings.sticky_scroll = Some(StickyScrollContent {
+                        enabled: Some(true),
+                        max_depth: Some(max_depth),
+                    });
+                });
+            });
+        };
+        cx.update(|cx| set_sticky_scroll(5, cx));
+
+        let language = Arc::new(
+            Language::new(
+                LanguageConfig::default(),
+                Some(tree_sitter_rust::LANGUAGE.into()),
+            )
+            .with_outline_query(
+                r#"
+                (mod_item
+                    "mod" @context
+                    name: (_) @name) @item
+                (function_item
+                    "fn" @context
+                    name: (_) @name) @item
+                "#,
+            )
+            .unwrap(),
+        );
+        let text = indoc::indoc! {"
+            mod outer {
+                fn inner() {
+                    let a = 1;
+                    let b = 2;
+                    let c = 3;
+                    let d = 4;
+                }
+            }
+        "};
+        let window = cx.add_window(|window, cx| {
+            let buffer = cx.new(|cx| Buffer::local(text, cx).with_language(language, cx));
+            let buffer = cx.new(|cx| MultiBuffer::singleton(buffer, cx));
+            Editor::new(EditorMode::Full, buffer, None, true, window, cx)
+        });
+        cx.executor().run_until_parked();
+        let cx = &mut VisualTestContext::from_window(*window, cx);
+        let editor = window.root(cx).unwrap();
+        let style = cx.update(|_, cx| editor.read(cx).style().unwrap().clone());
+        let draw = |scroll_top: f32, cx: &mut VisualTestContext| {
+            editor.update_in(cx, |editor, window, cx| {
+                editor.set_scroll_position(point(0., scroll_top), window, cx)
+            });
+            let (_, state) = cx.draw(
+                point(px(500.), px(500.)),
+                size(px(500.), px(500.)),
+                |_, _| EditorElement::new(&editor, style.clone()),
+            );
+            state.sticky_scopes.len()
+        };
+
+        // Nothing is pinned while the scope headers are visible.
+        assert_eq!(draw(0., cx), 0);
+        // Once the `mod` header scrolls off, it gets pinned, followed by
+        // the `fn` header once that one is covered as well.
+        assert_eq!(draw(1., cx), 1);
+        assert_eq!(draw(3., cx), 2);
+        // Scopes are unpinned after their last line scrolls by.
+        assert_eq!(draw(8., cx), 0);
+
+        cx.update(|_, cx| set_sticky_scroll(1, cx));
+        assert_eq!(draw(3., cx), 1);
+    }
+
     #[gpui::test]
     fn test_all_invisibles_drawing(cx: &mut TestAppContext) {
         const TAB_SIZE: u32 = 4;
@@ -0,0 +1,662 @@
+//! Keyboard macros for the non-vim keymaps.
+//!
+//! While a macro is being recorded, the actions dispatched from the keyboard and the text typed
+//! into the editor the recording was started in are captured. Raw keystrokes are not recorded, so a macro replays the same way
+//! regardless of the keymap it's played with.
+
+use crate::{
+    actions::{PlayLastMacro, PlayMacro, SaveLastMacro, StartRecordingMacro, StopRecordingMacro},
+    scroll::Autoscroll,
+    Anchor, Editor, EditorEvent,
+};
+use anyhow::{anyhow, Result};
+use collections::{BTreeMap, VecDeque};
+use gpui::{Action, App, AppContext as _, Context, EntityId, Global, WeakEntity, Window};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{update_settings_file, Settings, SettingsSources};
+use std::{
+    ops::Range,
+    rc::{Rc, Weak},
+    sync::Arc,
+};
+use workspace::{notifications::NotificationId, Toast};
+
+/// Guards against macros that never finish, e.g. ones that were saved playing themselves.
+const MAX_REPLAYED_STEPS: usize = 10_000;
+
+/// A step of a macro saved in the settings.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum MacroStep {
+    /// Dispatches an action, e.g. `{"action": "editor::MoveDown"}`.
+    Action {
+        action: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        arguments: Option<serde_json::Value>,
+    },
+    /// Inserts text as if it was typed, e.g. `{"text": "// TODO: "}`.
+    Text { text: String },
+}
+
+/// Named keyboard macros, which can be played with `editor::PlayMacro`.
+///
+/// Default: {}
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(transparent)]
+pub struct MacroSettings(pub BTreeMap<String, Vec<MacroStep>>);
+
+impl Settings for MacroSettings {
+    const KEY: Option<&'static str> = Some("macros");
+
+    type FileContent = Self;
+
+    fn load(sources: SettingsSources<Self::FileContent>, _: &mut App) -> Result<Self> {
+        sources.json_merge()
+    }
+}
+
+#[derive(Debug)]
+enum RecordedStep {
+    Action(Box<dyn Action>),
+    Insertion {
+        text: Arc<str>,
+        utf16_range_to_replace: Option<Range<isize>>,
+    },
+}
+
+impl Clone for RecordedStep {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Action(action) => Self::Action(action.boxed_clone()),
+            Self::Insertion {
+                text,
+                utf16_range_to_replace,
+            } => Self::Insertion {
+                text: text.clone(),
+                utf16_range_to_replace: utf16_range_to_replace.clone(),
+            },
+        }
+    }
+}
+
+impl RecordedStep {
+    fn from_macro_step(step: &MacroStep, cx: &App) -> Result<Self> {
+        match step {
+            MacroStep::Action { action, arguments } => {
+                Ok(Self::Action(cx.build_action(action, arguments.clone())?))
+            }
+            MacroStep::Text { text } => Ok(Self::Insertion {
+                text: text.as_str().into(),
+                utf16_range_to_replace: None,
+            }),
+        }
+    }
+
+    fn to_macro_step(&self, cx: &App) -> Result<MacroStep> {
+        match self {
+            Self::Action(action) => {
+                // Actions can't be serialized, so only the ones that are equal to their default
+                // value can be written back to the settings.
+                let name = action.name();
+                let is_default = cx
+                    .build_action(name, None)
+                    .map_or(false, |default| default.partial_eq(action.as_ref()));
+                if is_default {
+                    Ok(MacroStep::Action {
+                        action: name.to_string(),
+                        arguments: None,
+                    })
+                } else {
+                    Err(anyhow!(
+                        "`{name}` was played with arguments, which can't be saved"
+                    ))
+                }
+            }
+            Self::Insertion {
+                text,
+                utf16_range_to_replace,
+            } => {
+                if utf16_range_to_replace.is_some() {
+                    Err(anyhow!(
+                        "the macro contains input method edits, which can't be saved"
+                    ))
+                } else {
+                    Ok(MacroStep::Text {
+                        text: text.to_string(),
+                    })
+                }
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct MacroRecorder {
+    recording: Option<Vec<RecordedStep>>,
+    /// Text typed into other editors, e.g. the search bar or the command palette, would be
+    /// replayed into the buffer, so only insertions into this editor are recorded.
+    recording_editor: Option<EntityId>,
+    last_macro: Option<Vec<RecordedStep>>,
+    /// Held by the macro being played. It's dropped with the replay, even when the replay
+    /// doesn't get to finish because its window was closed.
+    active_replay: Weak<()>,
+}
+
+impl Global for MacroRecorder {}
+
+impl MacroRecorder {
+    fn is_replaying(&self) -> bool {
+        self.active_replay.strong_count() > 0
+    }
+
+    fn observe_action(&mut self, action: &dyn Action) {
+        if self.is_replaying() || is_macro_action(action) {
+            return;
+        }
+        if let Some(recording) = &mut self.recording {
+            recording.push(RecordedStep::Action(action.boxed_clone()));
+        }
+    }
+
+    fn observe_insertion(
+        &mut self,
+        editor: EntityId,
+        text: &Arc<str>,
+        utf16_range_to_replace: Option<Range<isize>>,
+    ) {
+        if self.is_replaying() || self.recording_editor != Some(editor) {
+            return;
+        }
+        if let Some(recording) = &mut self.recording {
+            recording.push(RecordedStep::Insertion {
+                text: text.clone(),
+                utf16_range_to_replace,
+            });
+        }
+    }
+
+    fn finish_recording(&mut self) {
+        self.recording_editor = None;
+        if let Some(recording) = self.recording.take() {
+            self.last_macro = Some(recording);
+        }
+    }
+}
+
+fn is_macro_action(action: &dyn Action) -> bool {
+    let action = action.as_any();
+    action.is::<StartRecordingMacro>()
+        || action.is::<StopRecordingMacro>()
+        || action.is::<PlayLastMacro>()
+        || action.is::<PlayMacro>()
+        || action.is::<SaveLastMacro>()
+}
+
+pub(crate) fn init(cx: &mut App) {
+    cx.observe_keystrokes(|event, _, cx| {
+        if let Some(action) = &event.action {
+            cx.default_global::<MacroRecorder>()
+                .observe_action(action.as_ref());
+        }
+    })
+    .detach();
+
+    cx.observe_new(|_: &mut Editor, _, cx: &mut Context<Editor>| {
+        let editor = cx.entity();
+        cx.subscribe(&editor, |_, editor, event: &EditorEvent, cx| {
+            if let EditorEvent::InputHandled {
+                utf16_range_to_replace,
+                text,
+            } = event
+            {
+                cx.default_global::<MacroRecorder>().observe_insertion(
+                    editor.entity_id(),
+                    text,
+                    utf16_range_to_replace.clone(),
+                );
+            }
+        })
+        .detach();
+    })
+    .detach();
+}
+
+enum ReplayStep {
+    Step(RecordedStep),
+    SelectCursor(Range<Anchor>),
+    FinishCursor,
+    RestoreCursors,
+}
+
+struct Replay {
+    editor: WeakEntity<Editor>,
+    steps: VecDeque<ReplayStep>,
+    replayed_steps: usize,
+    finished_cursors: Vec<Range<Anchor>>,
+    _active: Rc<()>,
+}
+
+impl Replay {
+    /// Replays one step at a time, so that the effects of every action are flushed before the
+    /// next one is dispatched.
+    fn next(mut self, window: &mut Window, cx: &mut App) {
+        let Some(step) = self.steps.pop_front() else {
+            return;
+        };
+        if self.replayed_steps == MAX_REPLAYED_STEPS {
+            log::error!("Aborting macro after {MAX_REPLAYED_STEPS} steps");
+            return;
+        }
+        self.replayed_steps += 1;
+
+        let Some(editor) = self.editor.upgrade() else {
+            return;
+        };
+        match step {
+            ReplayStep::Step(RecordedStep::Action(action)) => {
+                window.dispatch_action(action, cx);
+            }
+            ReplayStep::Step(RecordedStep::Insertion {
+                text,
+                utf16_range_to_replace,
+            }) => editor.update(cx, |editor, cx| {
+                editor.replay_insert_event(&text, utf16_range_to_replace, window, cx)
+            }),
+            ReplayStep::SelectCursor(range) => editor.update(cx, |editor, cx| {
+                editor.change_selections(None, window, cx, |s| s.select_anchor_ranges([range]))
+            }),
+            ReplayStep::FinishCursor => {
+                let selections = editor.read(cx).selections.disjoint_anchor_ranges();
+                self.finished_cursors.extend(selections);
+            }
+            ReplayStep::RestoreCursors => {
+                let cursors = std::mem::take(&mut self.finished_cursors);
+                editor.update(cx, |editor, cx| {
+                    editor.change_selections(Some(Autoscroll::fit()), window, cx, |s| {
+                        s.select_anchor_ranges(cursors)
+                    })
+                });
+            }
+        }
+        window.defer(cx, move |window, cx| self.next(window, cx));
+    }
+}
+
+impl Editor {
+    pub fn is_recording_macro(cx: &App) -> bool {
+        cx.try_global::<MacroRecorder>()
+            .map_or(false, |recorder| recorder.recording.is_some())
+    }
+
+    pub fn start_recording_macro(
+        &mut self,
+        _: &StartRecordingMacro,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let editor = cx.entity_id();
+        let recorder = cx.default_global::<MacroRecorder>();
+        recorder.recording = Some(Vec::new());
+        recorder.recording_editor = Some(editor);
+    }
+
+    pub fn stop_recording_macro(
+        &mut self,
+        _: &StopRecordingMacro,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        cx.default_global::<MacroRecorder>().finish_recording();
+    }
+
+    pub fn play_last_macro(
+        &mut self,
+        action: &PlayLastMacro,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        // Playing a macro while recording one ends the recording, so that it can't play itself.
+        let recorder = cx.default_global::<MacroRecorder>();
+        recorder.finish_recording();
+        let Some(steps) = recorder.last_macro.clone() else {
+            return;
+        };
+        self.play_macro_steps(steps, action.times, action.per_cursor, window, cx);
+    }
+
+    pub fn play_macro(&mut self, action: &PlayMacro, window: &mut Window, cx: &mut Context<Self>) {
+        let steps = MacroSettings::get_global(cx)
+            .0
+            .get(&action.name)
+            .ok_or_else(|| anyhow!("There is no macro named `{}`", action.name))
+            .and_then(|steps| {
+                steps
+                    .iter()
+                    .map(|step| RecordedStep::from_macro_step(step, cx))
+                    .collect::<Result<Vec<_>>>()
+            });
+        match steps {
+            Ok(steps) => self.play_macro_steps(steps, action.times, action.per_cursor, window, cx),
+            Err(error) => self.show_macro_toast(format!("Failed to play macro: {error}"), cx),
+        }
+    }
+
+    pub fn save_last_macro(
+        &mut self,
+        action: &SaveLastMacro,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(workspace) = self.workspace() else {
+            return;
+        };
+        let recorder = cx.default_global::<MacroRecorder>();
+        recorder.finish_recording();
+        let Some(steps) = recorder.last_macro.clone() else {
+            self.show_macro_toast("No macro has been recorded".to_string(), cx);
+            return;
+        };
+        let steps = match steps
+            .iter()
+            .map(|step| step.to_macro_step(cx))
+            .collect::<Result<Vec<_>>>()
+        {
+            Ok(steps) => steps,
+            Err(error) => {
+                self.show_macro_toast(format!("Failed to save macro: {error}"), cx);
+                return;
+            }
+        };
+
+        let name = action.name.clone().unwrap_or_else(|| {
+            let macros = &MacroSettings::get_global(cx).0;
+            (1..)
+                .map(|ix| format!("macro_{ix}"))
+                .find(|name| !macros.contains_key(name))
+                .unwrap()
+        });
+        let fs = workspace.read(cx).app_state().fs.clone();
+        update_settings_file::<MacroSettings>(fs, cx, {
+            let name = name.clone();
+            move |macros, _| {
+                macros.0.insert(name, steps);
+            }
+        });
+        self.show_macro_toast(
+            format!(
+                "Saved macro `{name}`. Bind it with `[\"editor::PlayMacro\", {{ \"name\": \"{name}\" }}]`"
+            ),
+            cx,
+        );
+    }
+
+    fn play_macro_steps(
+        &mut self,
+        steps: Vec<RecordedStep>,
+        times: Option<usize>,
+        per_cursor: bool,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let recorder = cx.default_global::<MacroRecorder>();
+        if recorder.is_replaying() || steps.is_empty() {
+            return;
+        }
+        let active = Rc::new(());
+        recorder.active_replay = Rc::downgrade(&active);
+
+        // Steps past the limit are never replayed, so they aren't queued either.
+        let times = times
+            .unwrap_or(1)
+            .clamp(1, (MAX_REPLAYED_STEPS / steps.len()).max(1));
+        let repeated_steps = || {
+            (0..times)
+                .flat_map(|_| steps.iter().cloned())
+                .map(ReplayStep::Step)
+        };
+        let mut replay_steps = VecDeque::new();
+        if per_cursor {
+            for range in self.selections.disjoint_anchor_ranges() {
+                if replay_steps.len() >= MAX_REPLAYED_STEPS {
+                    break;
+                }
+                replay_steps.push_back(ReplayStep::SelectCursor(range));
+                replay_steps.extend(repeated_steps());
+                replay_steps.push_back(ReplayStep::FinishCursor);
+            }
+            replay_steps.push_back(ReplayStep::RestoreCursors);
+        } else {
+            replay_steps.extend(repeated_steps());
+        }
+
+        let replay = Replay {
+            editor: cx.entity().downgrade(),
+            steps: replay_steps,
+            replayed_steps: 0,
+            finished_cursors: Vec::new(),
+            _active: active,
+        };
+        window.defer(cx, move |window, cx| replay.next(window, cx));
+    }
+
+    fn show_macro_toast(&self, message: String, cx: &mut Context<Self>) {
+        struct MacroToast;
+
+        if let Some(workspace) = self.workspace() {
+            workspace.update(cx, |workspace, cx| {
+                workspace.show_toast(
+                    Toast::new(NotificationId::unique::<MacroToast>(), message),
+                    cx,
+                )
+            });
+        } else {
+            log::error!("{message}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        editor_tests::init_test, test::editor_test_context::EditorTestContext, MoveDown,
+        MoveToBeginningOfLine, SelectNext,
+    };
+    use gpui::{AppContext as _, EntityInputHandler, KeyBinding, TestAppContext};
+    use serde_json::json;
+
+    #[gpui::test]
+    async fn test_record_and_play_macro(cx: &mut TestAppContext) {
+        init_test(cx, |_| {});
+        let mut cx = EditorTestContext::new(cx).await;
+        cx.update(|_, cx| {
+            cx.bind_keys([
+                KeyBinding::new("down", MoveDown, Some("Editor")),
+                KeyBinding::new(
+                    "home",
+                    MoveToBeginningOfLine {
+                        stop_at_soft_wraps: true,
+                    },
+                    Some("Editor"),
+                ),
+            ])
+        });
+        cx.set_state("ˇone\ntwo\nthree\nfour\n");
+
+        cx.update_editor(|editor, window, cx| {
+            editor.start_recording_macro(&StartRecordingMacro, window, cx);
+        });
+        assert!(cx.update(|_, cx| Editor::is_recording_macro(cx)));
+        cx.simulate_keystrokes("t o space down home");
+        cx.update_editor(|editor, window, cx| {
+            editor.stop_recording_macro(&StopRecordingMacro, window, cx);
+        });
+        assert!(!cx.update(|_, cx| Editor::is_recording_macro(cx)));
+        cx.assert_editor_state("to one\nˇtwo\nthree\nfour\n");
+
+        cx.update_editor(|editor, window, cx| {
+            editor.play_last_macro(
+                &PlayLastMacro {
+                    times: Some(2),
+                    per_cursor: false,
+                },
+                window,
+                cx,
+            );
+        });
+        cx.run_until_parked();
+        cx.assert_editor_state("to one\nto two\nto three\nˇfour\n");
+
+        cx.set_state("ˇone\ntwo\nˇthree\n");
+        cx.update_editor(|editor, window, cx| {
+            editor.play_last_macro(
+                &PlayLastMacro {
+                    times: None,
+                    per_cursor: true,
+                },
+                window,
+                cx,
+            );
+        });
+        cx.run_until_parked();
+        cx.assert_editor_state("to one\nˇtwo\nto three\nˇ");
+    }
+
+    #[gpui::test]
+    async fn test_macro_ignores_input_into_other_editors(cx: &mut TestAppContext) {
+        init_test(cx, |_| {});
+        let mut cx = EditorTestContext::new(cx).await;
+        cx.set_state("ˇone\n");
+        let search_editor = cx.update(|window, cx| cx.new(|cx| Editor::single_line(window, cx)));
+
+        cx.update_editor(|editor, window, cx| {
+            editor.start_recording_macro(&StartRecordingMacro, window, cx);
+        });
+        cx.simulate_keystrokes("a");
+        search_editor.update_in(&mut *cx, |editor, window, cx| {
+            editor.replace_text_in_range(None, "query", window, cx);
+        });
+        cx.simulate_keystrokes("b");
+        cx.update_editor(|editor, window, cx| {
+            editor.stop_recording_macro(&StopRecordingMacro, window, cx);
+        });
+        cx.assert_editor_state("abˇone\n");
+
+        cx.update_editor(|editor, window, cx| {
+            editor.play_last_macro(
+                &PlayLastMacro {
+                    times: None,
+                    per_cursor: false,
+                },
+                window,
+                cx,
+            );
+        });
+        cx.run_until_parked();
+        cx.assert_editor_state("ababˇone\n");
+        search_editor.update(&mut *cx, |editor, cx| {
+            assert_eq!(editor.text(cx), "query");
+        });
+    }
+
+    #[gpui::test]
+    async fn test_play_macro_many_times(cx: &mut TestAppContext) {
+        init_test(cx, |_| {});
+        let mut cx = EditorTestContext::new(cx).await;
+        cx.set_state("ˇ");
+        cx.update_editor(|editor, window, cx| {
+            editor.start_recording_macro(&StartRecordingMacro, window, cx);
+        });
+        cx.simulate_keystrokes("a");
+        cx.update_editor(|editor, window, cx| {
+            editor.stop_recording_macro(&StopRecordingMacro, window, cx);
+        });
+
+        // The repetitions are limited before they're queued.
+        cx.update_editor(|editor, window, cx| {
+            editor.play_last_macro(
+                &PlayLastMacro {
+                    times: Some(usize::MAX),
+                    per_cursor: false,
+                },
+                window,
+                cx,
+            );
+        });
+        cx.run_until_parked();
+        cx.update_editor(|editor, _, cx| {
+            assert_eq!(editor.text(cx).len(), 1 + MAX_REPLAYED_STEPS);
+        });
+
+        // Once the replay is done, macros can be played again.
+        assert!(!cx.update(|_, cx| cx.global::<MacroRecorder>().is_replaying()));
+        cx.set_state("ˇ");
+        cx.update_editor(|editor, window, cx| {
+            editor.play_last_macro(
+                &PlayLastMacro {
+                    times: None,
+                    per_cursor: false,
+                },
+                window,
+                cx,
+            );
+        });
+        cx.run_until_parked();
+        cx.assert_editor_state("aˇ");
+    }
+
+    #[gpui::test]
+    fn test_saving_macro_steps(cx: &mut App) {
+        let recorded = [
+            RecordedStep::Action(MoveDown.boxed_clone()),
+            RecordedStep::Action(
+                MoveToBeginningOfLine {
+                    stop_at_soft_wraps: true,
+                }
+                .boxed_clone(),
+            ),
+            RecordedStep::Insertion {
+                text: "to ".into(),
+                utf16_range_to_replace: None,
+            },
+        ];
+        let saved = recorded
+            .iter()
+            .map(|step| step.to_macro_step(cx))
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(
+            serde_json::to_value(&saved).unwrap(),
+            json!([
+                { "action": "editor::MoveDown" },
+                { "action": "editor::MoveToBeginningOfLine" },
+                { "text": "to " },
+            ])
+        );
+
+        let loaded = saved
+            .iter()
+            .map(|step| RecordedStep::from_macro_step(step, cx))
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert!(matches!(&loaded[0], RecordedStep::Action(action) if action.partial_eq(&MoveDown)));
+        assert!(matches!(
+            &loaded[2],
+            RecordedStep::Insertion { text, .. } if text.as_ref() == "to "
+        ));
+
+        let step: MacroStep = serde_json::from_value(json!({
+            "action": "editor::SelectNext",
+            "arguments": { "replace_newest": true }
+        }))
+        .unwrap();
+        let RecordedStep::Action(action) = RecordedStep::from_macro_step(&step, cx).unwrap() else {
+            panic!("expected an action");
+        };
+        assert!(action.partial_eq(&SelectNext {
+            replace_newest: true
+        }));
+        assert!(RecordedStep::Action(action).to_macro_step(cx).is_err());
+    }
+}
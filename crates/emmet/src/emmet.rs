@@ -0,0 +1,44 @@
+//! Expansion of [Emmet](https://docs.emmet.io/abbreviations/) abbreviations into snippets.
+//!
+//! Markup abbreviations like `ul>li.item*3>a[href]` expand into HTML or JSX elements, and
+//! stylesheet abbreviations like `m10-20` or `d:f` expand into CSS declarations. Expansions
+//! are returned in the snippet syntax understood by the `snippet` crate, with a tabstop at
+//! every empty attribute value and element.
+
+mod markup;
+mod stylesheet;
+
+use language::EmmetSyntax;
+
+/// Returns the offset in the given text, which ends at the cursor, where the abbreviation
+/// before the cursor starts, if there is one.
+pub fn extract_abbreviation(text: &str, syntax: EmmetSyntax) -> Option<usize> {
+    let start = if syntax.is_markup() {
+        markup::extract_abbreviation(text)
+    } else {
+        stylesheet::extract_abbreviation(text)
+    };
+    (start < text.len()).then_some(start)
+}
+
+/// Expands the abbreviation into a snippet, indenting nested lines with the given indent.
+///
+/// Returns `None` if the abbreviation is invalid, or if it's a plain word that isn't known
+/// to Emmet, as those are much more likely to be ordinary text than abbreviations.
+pub fn expand(abbreviation: &str, syntax: EmmetSyntax, indent: &str) -> Option<String> {
+    if syntax.is_markup() {
+        markup::expand(abbreviation, syntax == EmmetSyntax::Jsx, indent)
+    } else {
+        stylesheet::expand(abbreviation)
+    }
+}
+
+/// Escapes text from an abbreviation so that it's inserted literally by a snippet.
+fn escape_snippet_text(text: &str, output: &mut String) {
+    for c in text.chars() {
+        if matches!(c, '$' | '}' | '\\') {
+            output.push('\\');
+        }
+        output.push(c);
+    }
+}
@@ -0,0 +1,765 @@
+//! Parsing and expansion of HTML and JSX abbreviations.
+
+use crate::escape_snippet_text;
+
+/// The characters that make a word an abbreviation, rather than an element name on its own.
+const OPERATORS: &[char] = &['>', '+', '^', '*', '.', '#', '[', '{', '('];
+
+const KNOWN_ELEMENTS: &[&str] = &[
+    "a",
+    "abbr",
+    "address",
+    "area",
+    "article",
+    "aside",
+    "audio",
+    "b",
+    "base",
+    "bdi",
+    "bdo",
+    "blockquote",
+    "body",
+    "br",
+    "button",
+    "canvas",
+    "caption",
+    "cite",
+    "code",
+    "col",
+    "colgroup",
+    "data",
+    "datalist",
+    "dd",
+    "del",
+    "details",
+    "dfn",
+    "dialog",
+    "div",
+    "dl",
+    "dt",
+    "em",
+    "embed",
+    "fieldset",
+    "figcaption",
+    "figure",
+    "footer",
+    "form",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "head",
+    "header",
+    "hgroup",
+    "hr",
+    "html",
+    "i",
+    "iframe",
+    "img",
+    "input",
+    "ins",
+    "kbd",
+    "label",
+    "legend",
+    "li",
+    "link",
+    "main",
+    "map",
+    "mark",
+    "menu",
+    "meta",
+    "meter",
+    "nav",
+    "noscript",
+    "object",
+    "ol",
+    "optgroup",
+    "option",
+    "output",
+    "p",
+    "picture",
+    "pre",
+    "progress",
+    "q",
+    "rp",
+    "rt",
+    "ruby",
+    "s",
+    "samp",
+    "script",
+    "search",
+    "section",
+    "select",
+    "slot",
+    "small",
+    "source",
+    "span",
+    "strong",
+    "style",
+    "sub",
+    "summary",
+    "sup",
+    "table",
+    "tbody",
+    "td",
+    "template",
+    "textarea",
+    "tfoot",
+    "th",
+    "thead",
+    "time",
+    "title",
+    "tr",
+    "track",
+    "u",
+    "ul",
+    "var",
+    "video",
+    "wbr",
+];
+
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+
+const INLINE_ELEMENTS: &[&str] = &[
+    "a", "abbr", "b", "bdi", "bdo", "br", "button", "cite", "code", "data", "dfn", "em", "i",
+    "img", "input", "kbd", "label", "mark", "q", "s", "samp", "select", "small", "span", "strong",
+    "sub", "sup", "textarea", "time", "u", "var",
+];
+
+/// Attributes added to elements that don't set them, with empty values becoming tabstops.
+const DEFAULT_ATTRIBUTES: &[(&str, &[(&str, &str)])] = &[
+    ("a", &[("href", "")]),
+    ("form", &[("action", "")]),
+    ("iframe", &[("src", "")]),
+    ("img", &[("src", ""), ("alt", "")]),
+    ("input", &[("type", "text")]),
+    ("label", &[("for", "")]),
+    ("link", &[("rel", "stylesheet"), ("href", "")]),
+];
+
+/// Inline elements can only contain more than this many elements if they're on their own lines.
+const MAX_INLINE_CHILDREN: usize = 2;
+
+pub fn extract_abbreviation(text: &str) -> usize {
+    let mut start = text.len();
+    let mut bracket_depth = 0;
+    let mut brace_depth = 0;
+    for (ix, c) in text.char_indices().rev() {
+        if brace_depth > 0 {
+            match c {
+                '}' => brace_depth += 1,
+                '{' => brace_depth -= 1,
+                _ => {}
+            }
+        } else if bracket_depth > 0 {
+            match c {
+                ']' => bracket_depth += 1,
+                '[' => bracket_depth -= 1,
+                _ => {}
+            }
+        } else {
+            match c {
+                '}' => brace_depth += 1,
+                ']' => bracket_depth += 1,
+                '<' => {
+                    // The abbreviation follows a tag, like `<div>ul>li`.
+                    return text[ix..]
+                        .find('>')
+                        .map_or(text.len(), |tag_end| ix + tag_end + 1);
+                }
+                c if c.is_alphanumeric() || "-_:.#>+^*$@!()/".contains(c) => {}
+                _ => break,
+            }
+        }
+        start = ix;
+    }
+    start
+}
+
+pub fn expand(abbreviation: &str, jsx: bool, indent: &str) -> Option<String> {
+    if !abbreviation.contains(OPERATORS) {
+        let name = abbreviation.split(':').next()?;
+        if !KNOWN_ELEMENTS.contains(&name) {
+            return None;
+        }
+    }
+
+    let mut parser = Parser {
+        source: abbreviation,
+        offset: 0,
+    };
+    let nodes = parser.parse_siblings(true)?.0;
+    if parser.offset < abbreviation.len() {
+        return None;
+    }
+
+    let mut instances = Vec::new();
+    collect_instances(&nodes, Numbering::default(), &mut instances);
+    if instances.iter().all(|(node, _)| node.is_text()) {
+        return None;
+    }
+
+    let mut renderer = Renderer {
+        jsx,
+        indent,
+        output: String::new(),
+        next_tabstop: 1,
+    };
+    renderer.render_instances(&instances, None, 0);
+    Some(renderer.output)
+}
+
+#[derive(Debug, Default)]
+struct Node {
+    name: Option<String>,
+    id: Option<String>,
+    classes: Vec<String>,
+    attributes: Vec<(String, String)>,
+    text: Option<String>,
+    repeat: Option<usize>,
+    children: Vec<Node>,
+    is_group: bool,
+}
+
+impl Node {
+    /// Whether this is plain text, like `{Click}` in `a>{Click}`, rather than an element.
+    fn is_text(&self) -> bool {
+        !self.is_group
+            && self.name.is_none()
+            && self.id.is_none()
+            && self.classes.is_empty()
+            && self.attributes.is_empty()
+            && self.text.is_some()
+    }
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    offset: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<char> {
+        self.source[self.offset..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.offset += c.len_utf8();
+        Some(c)
+    }
+
+    fn take_while(&mut self, predicate: impl Fn(char) -> bool) -> &str {
+        let start = self.offset;
+        while self.peek().map_or(false, &predicate) {
+            self.bump();
+        }
+        &self.source[start..self.offset]
+    }
+
+    /// Parses a list of siblings, along with their children. Also returns the number of
+    /// levels that the abbreviation climbs up with `^` after the list.
+    fn parse_siblings(&mut self, is_root: bool) -> Option<(Vec<Node>, usize)> {
+        let mut nodes = Vec::new();
+        loop {
+            let mut node = self.parse_item()?;
+            match self.peek() {
+                Some('>') => {
+                    self.bump();
+                    let (children, climb) = self.parse_siblings(false)?;
+                    node.children.extend(children);
+                    nodes.push(node);
+                    if climb == 0 {
+                        return Some((nodes, 0));
+                    } else if climb > 1 && !is_root {
+                        return Some((nodes, climb - 1));
+                    }
+                }
+                Some('+') => {
+                    self.bump();
+                    nodes.push(node);
+                }
+                Some('^') => {
+                    let climb = self.take_while(|c| c == '^').len();
+                    nodes.push(node);
+                    if !is_root {
+                        return Some((nodes, climb));
+                    }
+                }
+                _ => {
+                    nodes.push(node);
+                    return Some((nodes, 0));
+                }
+            }
+        }
+    }
+
+    fn parse_item(&mut self) -> Option<Node> {
+        let mut node = if self.peek() == Some('(') {
+            self.bump();
+            let children = self.parse_siblings(true)?.0;
+            if self.bump() != Some(')') {
+                return None;
+            }
+            Node {
+                children,
+                is_group: true,
+                ..Default::default()
+            }
+        } else {
+            self.parse_element()?
+        };
+
+        if self.peek() == Some('*') {
+            self.bump();
+            node.repeat = Some(self.take_while(|c| c.is_ascii_digit()).parse().ok()?);
+        }
+        Some(node)
+    }
+
+    fn parse_element(&mut self) -> Option<Node> {
+        let is_name_char = |c: char| c.is_alphanumeric() || matches!(c, '-' | '_' | ':' | '$');
+        let is_value_char = |c: char| {
+            !c.is_whitespace()
+                && !matches!(c, '.' | '#' | '[' | '{' | '>' | '+' | '^' | '*' | '(' | ')')
+        };
+
+        let mut node = Node::default();
+        let name = self.take_while(is_name_char);
+        if !name.is_empty() {
+            node.name = Some(name.to_string());
+        }
+        loop {
+            match self.peek() {
+                Some('#') => {
+                    self.bump();
+                    node.id = Some(self.take_while(is_value_char).to_string());
+                }
+                Some('.') => {
+                    self.bump();
+                    let class = self.take_while(is_value_char);
+                    if class.is_empty() {
+                        return None;
+                    }
+                    node.classes.push(class.to_string());
+                }
+                Some('[') => {
+                    self.bump();
+                    self.parse_attributes(&mut node)?;
+                }
+                Some('{') => {
+                    self.bump();
+                    let text = self.take_while(|c| c != '}').to_string();
+                    self.bump()?;
+                    node.text = Some(text);
+                }
+                _ => break,
+            }
+        }
+
+        let is_empty = node.name.is_none()
+            && node.id.is_none()
+            && node.classes.is_empty()
+            && node.attributes.is_empty()
+            && node.text.is_none();
+        (!is_empty).then_some(node)
+    }
+
+    fn parse_attributes(&mut self, node: &mut Node) -> Option<()> {
+        loop {
+            self.take_while(char::is_whitespace);
+            if self.peek()? == ']' {
+                self.bump();
+                return Some(());
+            }
+            let name = self
+                .take_while(|c| !c.is_whitespace() && c != '=' && c != ']')
+                .to_string();
+            if name.is_empty() {
+                return None;
+            }
+            let mut value = String::new();
+            if self.peek() == Some('=') {
+                self.bump();
+                match self.peek()? {
+                    quote @ ('"' | '\'') => {
+                        self.bump();
+                        value = self.take_while(|c| c != quote).to_string();
+                        self.bump()?;
+                    }
+                    _ => {
+                        value = self
+                            .take_while(|c| !c.is_whitespace() && c != ']')
+                            .to_string()
+                    }
+                }
+            }
+            node.attributes.push((name, value));
+        }
+    }
+}
+
+/// The position of an element among the copies made by the nearest multiplication, which
+/// replaces `$` in its names and values.
+#[derive(Clone, Copy, Debug)]
+struct Numbering {
+    index: usize,
+    count: usize,
+}
+
+impl Default for Numbering {
+    fn default() -> Self {
+        Self { index: 0, count: 1 }
+    }
+}
+
+impl Numbering {
+    /// Replaces runs of `$` with the element's number, padded with zeros to the length of the
+    /// run. A run can be followed by `@-` to count down, and by `@N` to start counting at N.
+    fn apply(&self, text: &str) -> String {
+        let mut result = String::new();
+        let mut rest = text;
+        while let Some(start) = rest.find('$') {
+            result.push_str(&rest[..start]);
+            rest = &rest[start..];
+            let width = rest.len() - rest.trim_start_matches('$').len();
+            rest = &rest[width..];
+
+            let mut reversed = false;
+            let mut base = 1;
+            if let Some(modifier) = rest.strip_prefix('@') {
+                rest = modifier;
+                if let Some(modifier) = rest.strip_prefix('-') {
+                    reversed = true;
+                    rest = modifier;
+                }
+                let digits =
+                    rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+                if let Ok(start) = rest[..digits].parse() {
+                    base = start;
+                }
+                rest = &rest[digits..];
+            }
+
+            let number = if reversed {
+                base + self.count - 1 - self.index
+            } else {
+                base + self.index
+            };
+            result.push_str(&format!("{number:0width$}"));
+        }
+        result.push_str(rest);
+        result
+    }
+}
+
+/// Flattens groups and multiplications into the elements they produce.
+fn collect_instances<'a>(
+    nodes: &'a [Node],
+    numbering: Numbering,
+    instances: &mut Vec<(&'a Node, Numbering)>,
+) {
+    for node in nodes {
+        let count = node.repeat.unwrap_or(1);
+        for index in 0..count {
+            let numbering = if node.repeat.is_some() {
+                Numbering { index, count }
+            } else {
+                numbering
+            };
+            if node.is_group {
+                collect_instances(&node.children, numbering, instances);
+            } else {
+                instances.push((node, numbering));
+            }
+        }
+    }
+}
+
+fn element_name(node: &Node, numbering: Numbering, parent: Option<&str>) -> String {
+    if let Some(name) = &node.name {
+        return numbering.apply(name);
+    }
+    let implicit_name = match parent.map(|parent| parent.to_lowercase()).as_deref() {
+        Some("ul" | "ol" | "menu") => "li",
+        Some("table" | "thead" | "tbody" | "tfoot") => "tr",
+        Some("tr") => "td",
+        Some("select" | "optgroup" | "datalist") => "option",
+        Some(parent) if INLINE_ELEMENTS.contains(&parent) || parent == "p" => "span",
+        _ => "div",
+    };
+    implicit_name.to_string()
+}
+
+fn is_inline(node: &Node, numbering: Numbering, parent: Option<&str>) -> bool {
+    if node.is_text() {
+        return true;
+    }
+    let name = element_name(node, numbering, parent);
+    if !INLINE_ELEMENTS.contains(&name.as_str()) {
+        return false;
+    }
+    let mut children = Vec::new();
+    collect_instances(&node.children, numbering, &mut children);
+    is_inline_content(&children, Some(&name))
+}
+
+fn is_inline_content(instances: &[(&Node, Numbering)], parent: Option<&str>) -> bool {
+    instances.iter().filter(|(node, _)| !node.is_text()).count() <= MAX_INLINE_CHILDREN
+        && instances
+            .iter()
+            .all(|(node, numbering)| is_inline(node, *numbering, parent))
+}
+
+struct Renderer<'a> {
+    jsx: bool,
+    indent: &'a str,
+    output: String,
+    next_tabstop: usize,
+}
+
+impl Renderer<'_> {
+    fn render_instances(
+        &mut self,
+        instances: &[(&Node, Numbering)],
+        parent: Option<&str>,
+        depth: usize,
+    ) {
+        let inline = is_inline_content(instances, parent);
+        for (ix, (node, numbering)) in instances.iter().enumerate() {
+            if !inline && ix > 0 {
+                self.newline(depth);
+            }
+            self.render_node(node, *numbering, parent, depth);
+        }
+    }
+
+    fn render_node(
+        &mut self,
+        node: &Node,
+        numbering: Numbering,
+        parent: Option<&str>,
+        depth: usize,
+    ) {
+        if node.is_text() {
+            self.push_text(&numbering.apply(node.text.as_deref().unwrap_or_default()));
+            return;
+        }
+
+        let mut name = element_name(node, numbering, parent);
+        let mut attributes = Vec::new();
+        // Aliases like `input:checkbox` set the type of the element.
+        if let Some(colon) = name.find(':') {
+            attributes.push(("type".to_string(), name[colon + 1..].to_string()));
+            name.truncate(colon);
+        }
+        if let Some((_, defaults)) = DEFAULT_ATTRIBUTES
+            .iter()
+            .find(|(element, _)| *element == name)
+        {
+            for (attribute, value) in defaults.iter() {
+                if !attributes.iter().any(|(name, _)| name == attribute) {
+                    attributes.push((attribute.to_string(), value.to_string()));
+                }
+            }
+        }
+        if let Some(id) = &node.id {
+            attributes.push(("id".to_string(), numbering.apply(id)));
+        }
+        if !node.classes.is_empty() {
+            let classes = node
+                .classes
+                .iter()
+                .map(|class| numbering.apply(class))
+                .collect::<Vec<_>>();
+            attributes.push(("class".to_string(), classes.join(" ")));
+        }
+        for (attribute, value) in &node.attributes {
+            let value = numbering.apply(value);
+            if let Some(existing) = attributes.iter_mut().find(|(name, _)| name == attribute) {
+                existing.1 = value;
+            } else {
+                attributes.push((attribute.clone(), value));
+            }
+        }
+
+        self.output.push('<');
+        self.push_text(&name);
+        for (attribute, value) in attributes {
+            let attribute = match attribute.as_str() {
+                "class" if self.jsx => "className",
+                "for" if self.jsx => "htmlFor",
+                attribute => attribute,
+            };
+            self.output.push(' ');
+            self.push_text(attribute);
+            self.output.push_str("=\"");
+            if value.is_empty() {
+                self.push_tabstop();
+            } else {
+                self.push_text(&value);
+            }
+            self.output.push('"');
+        }
+
+        let mut children = Vec::new();
+        collect_instances(&node.children, numbering, &mut children);
+        if VOID_ELEMENTS.contains(&name.as_str()) && children.is_empty() && node.text.is_none() {
+            self.output.push_str(if self.jsx { " />" } else { ">" });
+            return;
+        }
+        self.output.push('>');
+
+        if let Some(text) = &node.text {
+            self.push_text(&numbering.apply(text));
+        }
+        if children.is_empty() {
+            if node.text.is_none() {
+                self.push_tabstop();
+            }
+        } else if is_inline_content(&children, Some(&name)) {
+            self.render_instances(&children, Some(&name), depth + 1);
+        } else {
+            self.newline(depth + 1);
+            self.render_instances(&children, Some(&name), depth + 1);
+            self.newline(depth);
+        }
+
+        self.output.push_str("</");
+        self.push_text(&name);
+        self.output.push('>');
+    }
+
+    fn push_text(&mut self, text: &str) {
+        escape_snippet_text(text, &mut self.output);
+    }
+
+    fn push_tabstop(&mut self) {
+        self.output.push_str(&format!("${}", self.next_tabstop));
+        self.next_tabstop += 1;
+    }
+
+    fn newline(&mut self, depth: usize) {
+        self.output.push('\n');
+        for _ in 0..depth {
+            self.output.push_str(self.indent);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    fn expand_html(abbreviation: &str) -> Option<String> {
+        expand(abbreviation, false, "  ")
+    }
+
+    #[test]
+    fn test_extract_abbreviation() {
+        let extract = |text: &str| &text[extract_abbreviation(text)..];
+        assert_eq!(extract("  ul>li.item*3>a[href]"), "ul>li.item*3>a[href]");
+        assert_eq!(extract("<div>ul>li"), "ul>li");
+        assert_eq!(extract("</p>a{Click here}"), "a{Click here}");
+        assert_eq!(
+            extract("input[type=\"text\" name]"),
+            "input[type=\"text\" name]"
+        );
+        assert_eq!(extract("<div"), "");
+        assert_eq!(extract("foo = "), "");
+    }
+
+    #[test]
+    fn test_expand_elements() {
+        assert_eq!(
+            expand_html("ul>li.item*3>a[href]").unwrap(),
+            indoc! {r#"
+                <ul>
+                  <li class="item"><a href="$1">$2</a></li>
+                  <li class="item"><a href="$3">$4</a></li>
+                  <li class="item"><a href="$5">$6</a></li>
+                </ul>"#}
+        );
+        assert_eq!(
+            expand_html("div#main.a.b>p{Hello}+img").unwrap(),
+            indoc! {r#"
+                <div id="main" class="a b">
+                  <p>Hello</p>
+                  <img src="$1" alt="$2">
+                </div>"#}
+        );
+        assert_eq!(
+            expand_html("a[title='Go home' target=_blank]{Home}").unwrap(),
+            r#"<a href="$1" title="Go home" target="_blank">Home</a>"#
+        );
+        assert_eq!(
+            expand_html("input:checkbox").unwrap(),
+            r#"<input type="checkbox">"#
+        );
+    }
+
+    #[test]
+    fn test_expand_operators() {
+        assert_eq!(
+            expand_html("header>h1^main+footer").unwrap(),
+            indoc! {r#"
+                <header>
+                  <h1>$1</h1>
+                </header>
+                <main>$2</main>
+                <footer>$3</footer>"#}
+        );
+        assert_eq!(
+            expand_html("(dt+dd)*2").unwrap(),
+            indoc! {r#"
+                <dt>$1</dt>
+                <dd>$2</dd>
+                <dt>$3</dt>
+                <dd>$4</dd>"#}
+        );
+        assert_eq!(
+            expand_html("table>.row$*2>.cell$$@-").unwrap(),
+            indoc! {r#"
+                <table>
+                  <tr class="row1">
+                    <td class="cell02">$1</td>
+                  </tr>
+                  <tr class="row2">
+                    <td class="cell01">$2</td>
+                  </tr>
+                </table>"#}
+        );
+        assert_eq!(
+            expand_html("p>span+em").unwrap(),
+            "<p><span>$1</span><em>$2</em></p>"
+        );
+    }
+
+    #[test]
+    fn test_expand_jsx() {
+        assert_eq!(
+            expand("label.name[for=id]+input.field", true, "\t").unwrap(),
+            r#"<label htmlFor="id" className="name">$1</label><input type="text" className="field" />"#
+        );
+        assert_eq!(
+            expand("Card>.body", true, "\t").unwrap(),
+            "<Card>\n\t<div className=\"body\">$1</div>\n</Card>"
+        );
+    }
+
+    #[test]
+    fn test_not_abbreviations() {
+        assert_eq!(expand_html("hello"), None);
+        assert_eq!(expand_html("{just text}"), None);
+        assert_eq!(expand_html("ul>"), None);
+        assert_eq!(expand_html("(li"), None);
+        assert_eq!(expand_html("li*"), None);
+        assert!(expand_html("section").is_some());
+    }
+}
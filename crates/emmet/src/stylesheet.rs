@@ -0,0 +1,381 @@
+//! Expansion of CSS abbreviations like `m10-20`, `w100p`, `c#f` and `d:f`.
+
+use crate::escape_snippet_text;
+
+/// Property abbreviations, along with the keyword abbreviations of their values.
+const PROPERTIES: &[(&str, &str, &[(&str, &str)])] = &[
+    ("ac", "align-content", ALIGNMENT),
+    ("ai", "align-items", ALIGNMENT),
+    ("as", "align-self", ALIGNMENT),
+    ("b", "bottom", &[]),
+    ("bd", "border", &[("n", "none")]),
+    ("bdb", "border-bottom", &[("n", "none")]),
+    ("bdc", "border-color", &[]),
+    ("bdl", "border-left", &[("n", "none")]),
+    ("bdr", "border-right", &[("n", "none")]),
+    ("bdrs", "border-radius", &[]),
+    ("bdt", "border-top", &[("n", "none")]),
+    ("bg", "background", &[("n", "none")]),
+    ("bgc", "background-color", &[("t", "transparent")]),
+    ("bgi", "background-image", &[("n", "none")]),
+    ("bxsh", "box-shadow", &[("n", "none")]),
+    (
+        "bxz",
+        "box-sizing",
+        &[("bb", "border-box"), ("cb", "content-box")],
+    ),
+    ("c", "color", &[]),
+    (
+        "cl",
+        "clear",
+        &[("b", "both"), ("l", "left"), ("r", "right"), ("n", "none")],
+    ),
+    ("ct", "content", &[("n", "normal")]),
+    (
+        "cur",
+        "cursor",
+        &[
+            ("a", "auto"),
+            ("d", "default"),
+            ("m", "move"),
+            ("p", "pointer"),
+            ("t", "text"),
+        ],
+    ),
+    (
+        "d",
+        "display",
+        &[
+            ("b", "block"),
+            ("c", "contents"),
+            ("f", "flex"),
+            ("g", "grid"),
+            ("i", "inline"),
+            ("ib", "inline-block"),
+            ("if", "inline-flex"),
+            ("ig", "inline-grid"),
+            ("n", "none"),
+            ("t", "table"),
+            ("tc", "table-cell"),
+        ],
+    ),
+    (
+        "ff",
+        "font-family",
+        &[("m", "monospace"), ("s", "serif"), ("ss", "sans-serif")],
+    ),
+    (
+        "fl",
+        "float",
+        &[("l", "left"), ("r", "right"), ("n", "none")],
+    ),
+    ("fs", "font-style", &[("i", "italic"), ("n", "normal")]),
+    (
+        "fw",
+        "font-weight",
+        &[
+            ("b", "bold"),
+            ("br", "bolder"),
+            ("l", "lighter"),
+            ("n", "normal"),
+        ],
+    ),
+    ("fx", "flex", &[("n", "none")]),
+    ("fxb", "flex-basis", &[]),
+    (
+        "fxd",
+        "flex-direction",
+        &[
+            ("c", "column"),
+            ("cr", "column-reverse"),
+            ("r", "row"),
+            ("rr", "row-reverse"),
+        ],
+    ),
+    ("fxg", "flex-grow", &[]),
+    ("fxsh", "flex-shrink", &[]),
+    (
+        "fxw",
+        "flex-wrap",
+        &[("n", "nowrap"), ("w", "wrap"), ("wr", "wrap-reverse")],
+    ),
+    ("fz", "font-size", &[]),
+    ("g", "gap", &[]),
+    ("gtc", "grid-template-columns", &[]),
+    ("gtr", "grid-template-rows", &[]),
+    ("h", "height", &[]),
+    ("jc", "justify-content", ALIGNMENT),
+    ("l", "left", &[]),
+    ("lh", "line-height", &[]),
+    ("lis", "list-style", &[("n", "none")]),
+    ("ls", "letter-spacing", &[]),
+    ("m", "margin", &[]),
+    ("mah", "max-height", &[("n", "none")]),
+    ("maw", "max-width", &[("n", "none")]),
+    ("mb", "margin-bottom", &[]),
+    ("mih", "min-height", &[]),
+    ("miw", "min-width", &[]),
+    ("ml", "margin-left", &[]),
+    ("mr", "margin-right", &[]),
+    ("mt", "margin-top", &[]),
+    ("ol", "outline", &[("n", "none")]),
+    ("op", "opacity", &[]),
+    ("ord", "order", &[]),
+    ("ov", "overflow", OVERFLOW),
+    ("ovx", "overflow-x", OVERFLOW),
+    ("ovy", "overflow-y", OVERFLOW),
+    ("p", "padding", &[]),
+    ("pb", "padding-bottom", &[]),
+    ("pe", "pointer-events", &[("a", "auto"), ("n", "none")]),
+    ("pl", "padding-left", &[]),
+    (
+        "pos",
+        "position",
+        &[
+            ("a", "absolute"),
+            ("f", "fixed"),
+            ("r", "relative"),
+            ("s", "static"),
+            ("st", "sticky"),
+        ],
+    ),
+    ("pr", "padding-right", &[]),
+    ("pt", "padding-top", &[]),
+    ("r", "right", &[]),
+    ("t", "top", &[]),
+    (
+        "ta",
+        "text-align",
+        &[
+            ("c", "center"),
+            ("j", "justify"),
+            ("l", "left"),
+            ("r", "right"),
+        ],
+    ),
+    (
+        "td",
+        "text-decoration",
+        &[("l", "line-through"), ("n", "none"), ("u", "underline")],
+    ),
+    ("ti", "text-indent", &[]),
+    ("trf", "transform", &[("n", "none")]),
+    ("trs", "transition", &[("n", "none")]),
+    (
+        "tt",
+        "text-transform",
+        &[
+            ("c", "capitalize"),
+            ("l", "lowercase"),
+            ("n", "none"),
+            ("u", "uppercase"),
+        ],
+    ),
+    ("us", "user-select", &[("a", "auto"), ("n", "none")]),
+    ("v", "visibility", &[("h", "hidden"), ("v", "visible")]),
+    (
+        "va",
+        "vertical-align",
+        &[("b", "bottom"), ("m", "middle"), ("t", "top")],
+    ),
+    ("w", "width", &[]),
+    (
+        "whs",
+        "white-space",
+        &[("n", "normal"), ("nw", "nowrap"), ("p", "pre")],
+    ),
+    ("z", "z-index", &[]),
+];
+
+const ALIGNMENT: &[(&str, &str)] = &[
+    ("b", "baseline"),
+    ("c", "center"),
+    ("fe", "flex-end"),
+    ("fs", "flex-start"),
+    ("s", "stretch"),
+    ("sa", "space-around"),
+    ("sb", "space-between"),
+    ("se", "space-evenly"),
+];
+
+const OVERFLOW: &[(&str, &str)] = &[
+    ("a", "auto"),
+    ("h", "hidden"),
+    ("s", "scroll"),
+    ("v", "visible"),
+];
+
+/// Keyword abbreviations that apply to every property.
+const GLOBAL_KEYWORDS: &[(&str, &str)] = &[
+    ("a", "auto"),
+    ("inh", "inherit"),
+    ("ini", "initial"),
+    ("n", "none"),
+];
+
+/// Properties whose numbers don't get a default unit.
+const UNITLESS_PROPERTIES: &[&str] = &[
+    "flex",
+    "flex-grow",
+    "flex-shrink",
+    "font-weight",
+    "line-height",
+    "opacity",
+    "order",
+    "z-index",
+];
+
+const UNIT_ALIASES: &[(&str, &str)] = &[("e", "em"), ("p", "%"), ("r", "rem"), ("x", "ex")];
+
+pub fn extract_abbreviation(text: &str) -> usize {
+    text.rfind(|c: char| !(c.is_alphanumeric() || matches!(c, '-' | ':' | '#' | '.' | '!' | '%')))
+        .map_or(0, |ix| {
+            ix + text[ix..].chars().next().map_or(0, char::len_utf8)
+        })
+}
+
+pub fn expand(abbreviation: &str) -> Option<String> {
+    let (abbreviation, important) = match abbreviation.strip_suffix('!') {
+        Some(abbreviation) => (abbreviation, true),
+        None => (abbreviation, false),
+    };
+
+    let (name, keywords, value) = if let Some((name, value)) = abbreviation.split_once(':') {
+        let (_, property, keywords) = PROPERTIES.iter().find(|(abbr, _, _)| *abbr == name)?;
+        (*property, *keywords, value)
+    } else {
+        // Without a colon, the property abbreviation is made of the leading lowercase
+        // letters, like `bdrs` in `bdrs4`, and the value starts after them.
+        let name_len = abbreviation
+            .find(|c: char| !c.is_ascii_lowercase())
+            .unwrap_or(abbreviation.len());
+        let (_, property, keywords) = PROPERTIES
+            .iter()
+            .find(|(abbr, _, _)| *abbr == &abbreviation[..name_len])?;
+        (*property, *keywords, &abbreviation[name_len..])
+    };
+
+    let mut snippet = String::new();
+    snippet.push_str(name);
+    snippet.push_str(": ");
+    if value.is_empty() {
+        snippet.push_str("$1");
+    } else {
+        let value = expand_value(name, keywords, value)?;
+        escape_snippet_text(&value, &mut snippet);
+    }
+    if important {
+        snippet.push_str(" !important");
+    }
+    snippet.push(';');
+    Some(snippet)
+}
+
+fn expand_value(property: &str, keywords: &[(&str, &str)], value: &str) -> Option<String> {
+    if let Some(color) = value.strip_prefix('#') {
+        return expand_color(color);
+    }
+    if value.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        let keyword = keywords
+            .iter()
+            .chain(GLOBAL_KEYWORDS)
+            .find(|(abbr, _)| *abbr == value)
+            .map_or(value, |(_, keyword)| *keyword);
+        return Some(keyword.to_string());
+    }
+
+    // Numbers are separated by dashes, and a leading or doubled dash makes a number negative.
+    let mut numbers = Vec::new();
+    let mut rest = value;
+    while !rest.is_empty() {
+        let negative = rest.starts_with('-');
+        if negative {
+            rest = &rest[1..];
+        }
+        let number_len = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(rest.len());
+        let unit_len = rest[number_len..]
+            .find(|c: char| !(c.is_ascii_alphabetic() || c == '%'))
+            .unwrap_or(rest.len() - number_len);
+        let number = &rest[..number_len];
+        let unit = &rest[number_len..number_len + unit_len];
+        if number.is_empty() || number.parse::<f64>().is_err() {
+            return None;
+        }
+        rest = &rest[number_len + unit_len..];
+        rest = rest
+            .strip_prefix('-')
+            .filter(|rest| !rest.is_empty())
+            .unwrap_or(rest);
+
+        let unit = if unit.is_empty() {
+            if UNITLESS_PROPERTIES.contains(&property) || number.parse::<f64>() == Ok(0.) {
+                ""
+            } else {
+                "px"
+            }
+        } else {
+            UNIT_ALIASES
+                .iter()
+                .find(|(alias, _)| *alias == unit)
+                .map_or(unit, |(_, unit)| *unit)
+        };
+        let sign = if negative { "-" } else { "" };
+        numbers.push(format!("{sign}{number}{unit}"));
+    }
+    Some(numbers.join(" "))
+}
+
+fn expand_color(color: &str) -> Option<String> {
+    if !color.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let color = match color.len() {
+        1 => color.repeat(6),
+        2 => color.repeat(3),
+        3 | 6 | 8 => color.to_string(),
+        _ => return None,
+    };
+    Some(format!("#{color}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand() {
+        assert_eq!(expand("m10").unwrap(), "margin: 10px;");
+        assert_eq!(expand("m10-20").unwrap(), "margin: 10px 20px;");
+        assert_eq!(expand("m-10--5e").unwrap(), "margin: -10px -5em;");
+        assert_eq!(expand("p0-1.5r").unwrap(), "padding: 0 1.5rem;");
+        assert_eq!(expand("w100p").unwrap(), "width: 100%;");
+        assert_eq!(expand("lh1.5").unwrap(), "line-height: 1.5;");
+        assert_eq!(expand("z10!").unwrap(), "z-index: 10 !important;");
+        assert_eq!(expand("c#f").unwrap(), "color: #ffffff;");
+        assert_eq!(expand("bgc#e0").unwrap(), "background-color: #e0e0e0;");
+        assert_eq!(expand("d:f").unwrap(), "display: flex;");
+        assert_eq!(expand("jc:sb").unwrap(), "justify-content: space-between;");
+        assert_eq!(expand("pos:a").unwrap(), "position: absolute;");
+        assert_eq!(expand("ov:a").unwrap(), "overflow: auto;");
+        assert_eq!(expand("bdrs4").unwrap(), "border-radius: 4px;");
+        assert_eq!(expand("fxd").unwrap(), "flex-direction: $1;");
+    }
+
+    #[test]
+    fn test_not_abbreviations() {
+        assert_eq!(expand("color"), None);
+        assert_eq!(expand("m1..2"), None);
+        assert_eq!(expand("c#ggg"), None);
+        assert_eq!(expand("foo:bar"), None);
+    }
+
+    #[test]
+    fn test_extract_abbreviation() {
+        let extract = |text: &str| &text[extract_abbreviation(text)..];
+        assert_eq!(extract("  m10-20"), "m10-20");
+        assert_eq!(extract("a { d:f"), "d:f");
+        assert_eq!(extract("color: red;"), "");
+    }
+}
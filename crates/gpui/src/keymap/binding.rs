@@ -2,7 +2,7 @@ use std::rc::Rc;
 
 use collections::HashMap;
 
-use crate::{Action, InvalidKeystrokeError, KeyBindingContextPredicate, Keystroke};
+use crate::{Action, InvalidKeystrokeError, KeyBindingContextPredicate, Keystroke, SharedString};
 use smallvec::SmallVec;
 
 /// A keybinding and its associated metadata, from the keymap.
@@ -10,14 +10,23 @@ pub struct KeyBinding {
     pub(crate) action: Box<dyn Action>,
     pub(crate) keystrokes: SmallVec<[Keystroke; 2]>,
     pub(crate) context_predicate: Option<Rc<KeyBindingContextPredicate>>,
+    pub(crate) meta: Option<KeyBindingMetaIndex>,
+    pub(crate) action_input: Option<SharedString>,
 }
 
+/// An opaque index into metadata kept by whoever loaded a binding, such as the keymap file it
+/// comes from. It isn't interpreted by the keymap.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct KeyBindingMetaIndex(pub u32);
+
 impl Clone for KeyBinding {
     fn clone(&self) -> Self {
         KeyBinding {
             action: self.action.boxed_clone(),
             keystrokes: self.keystrokes.clone(),
             context_predicate: self.context_predicate.clone(),
+            meta: self.meta,
+            action_input: self.action_input.clone(),
         }
     }
 }
@@ -30,15 +39,17 @@ impl KeyBinding {
         } else {
             None
         };
-        Self::load(keystrokes, Box::new(action), context_predicate, None).unwrap()
+        Self::load(keystrokes, Box::new(action), context_predicate, None, None).unwrap()
     }
 
-    /// Load a keybinding from the given raw data.
+    /// Load a keybinding from the given raw data. The action input is the JSON the action was
+    /// built from, if any.
     pub fn load(
         keystrokes: &str,
         action: Box<dyn Action>,
         context_predicate: Option<Rc<KeyBindingContextPredicate>>,
         key_equivalents: Option<&HashMap<char, char>>,
+        action_input: Option<SharedString>,
     ) -> std::result::Result<Self, InvalidKeystrokeError> {
         let mut keystrokes: SmallVec<[Keystroke; 2]> = keystrokes
             .split_whitespace()
@@ -59,9 +70,17 @@ impl KeyBinding {
             keystrokes,
             action,
             context_predicate,
+            meta: None,
+            action_input,
         })
     }
 
+    /// Set the metadata of this binding.
+    pub fn with_meta(mut self, meta: KeyBindingMetaIndex) -> Self {
+        self.meta = Some(meta);
+        self
+    }
+
     /// Check if the given keystrokes match this binding.
     pub fn match_keystrokes(&self, typed: &[Keystroke]) -> Option<bool> {
         if self.keystrokes.len() < typed.len() {
@@ -91,6 +110,16 @@ impl KeyBinding {
     pub fn predicate(&self) -> Option<Rc<KeyBindingContextPredicate>> {
         self.context_predicate.as_ref().map(|rc| rc.clone())
     }
+
+    /// Get the metadata of this binding, see [`KeyBindingMetaIndex`].
+    pub fn meta(&self) -> Option<KeyBindingMetaIndex> {
+        self.meta
+    }
+
+    /// Get the JSON input the action was built from, if any
+    pub fn action_input(&self) -> Option<SharedString> {
+        self.action_input.clone()
+    }
 }
 
 impl std::fmt::Debug for KeyBinding {
@@ -99,6 +128,8 @@ impl std::fmt::Debug for KeyBinding {
             .field("keystrokes", &self.keystrokes)
             .field("context_predicate", &self.context_predicate)
             .field("action", &self.action.name())
+            .field("action_input", &self.action_input)
+            .field("meta", &self.meta)
             .finish()
     }
 }
@@ -1165,6 +1165,21 @@ impl Window {
         });
     }
 
+    fn dispatch_keystroke_interceptors(&mut self, event: &KeyDownEvent, cx: &mut App) {
+        cx.keystroke_interceptors
+            .clone()
+            .retain(&(), move |callback| {
+                (callback)(
+                    &KeystrokeEvent {
+                        keystroke: event.keystroke.clone(),
+                        action: None,
+                    },
+                    self,
+                    cx,
+                )
+            });
+    }
+
     /// Schedules the given function to be run at the end of the current effect cycle, allowing entities
     /// that are currently on the stack to be returned to the app.
     pub fn defer(&self, cx: &mut App, f: impl FnOnce(&mut Window, &mut App) + 'static) {
@@ -3207,6 +3222,10 @@ impl Window {
             }
             self.pending_modifier.modifiers = event.modifiers
         } else if let Some(key_down_event) = event.downcast_ref::<KeyDownEvent>() {
+            self.dispatch_keystroke_interceptors(key_down_event, cx);
+            if !cx.propagate_event {
+                return;
+            }
             self.pending_modifier.saw_keystroke = true;
             keystroke = Some(key_down_event.keystroke.clone());
         }
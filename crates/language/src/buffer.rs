@@ -3109,6 +3109,24 @@ impl BufferSnapshot {
             .layers_for_range(0..self.len(), &self.text, true)
     }
 
+    /// Returns the ranges of text that are written in other languages and injected into the
+    /// buffer's language, like code blocks in Markdown, along with the language of each range.
+    ///
+    /// Only visible languages injected directly into the buffer's language are returned, and
+    /// injections that combine several disjoint ranges of text are skipped.
+    pub fn injection_ranges(&self) -> Vec<(Range<usize>, Arc<Language>)> {
+        let mut ranges = self
+            .syntax_layers()
+            .filter(|layer| layer.depth() == 1 && !layer.language.config.hidden)
+            .filter_map(|layer| {
+                let [range] = <[_; 1]>::try_from(layer.included_ranges()).ok()?;
+                (!range.is_empty()).then(|| (range, layer.language.clone()))
+            })
+            .collect::<Vec<_>>();
+        ranges.sort_by_key(|(range, _)| range.start);
+        ranges
+    }
+
     pub fn syntax_layer_at<D: ToOffset>(&self, position: D) -> Option<SyntaxLayer> {
         let offset = position.to_offset(self);
         self.syntax
@@ -3302,6 +3320,50 @@ impl BufferSnapshot {
         result
     }
 
+    /// Returns the range of the syntax node at the given range, along with the range of its next
+    /// sibling of the same kind. That node is the innermost one spanning the range, or one of its
+    /// ancestors with the same extent, if the innermost node has no such sibling.
+    pub fn next_sibling_of_same_kind<T: ToOffset>(
+        &self,
+        range: Range<T>,
+    ) -> Option<(Range<usize>, Range<usize>)> {
+        let range = range.start.to_offset(self)..range.end.to_offset(self);
+        let mut result = None;
+        for layer in self
+            .syntax
+            .layers_for_range(range.clone(), &self.text, true)
+        {
+            let Some(mut node) = layer
+                .node()
+                .descendant_for_byte_range(range.start, range.end)
+            else {
+                continue;
+            };
+            let layer_result = loop {
+                let mut sibling = node.next_sibling();
+                while let Some(candidate) = sibling {
+                    if candidate.kind_id() == node.kind_id() {
+                        break;
+                    }
+                    sibling = candidate.next_sibling();
+                }
+                if let Some(sibling) = sibling {
+                    break Some((node.byte_range(), sibling.byte_range()));
+                }
+                match node.parent() {
+                    Some(parent) if parent.byte_range() == node.byte_range() => node = parent,
+                    _ => break None,
+                }
+            };
+
+            // Prefer the innermost layer, e.g. a language injected into a string.
+            if layer_result.is_some() {
+                result = layer_result;
+            }
+        }
+        result
+    }
+
     /// Returns the outline for the buffer.
     ///
     /// This method allows passing an optional [`SyntaxTheme`] to
@@ -3812,6 +3874,42 @@ impl BufferSnapshot {
         })
     }
 
+    /// Returns the ranges of the scopes captured by the override query (e.g. `comment` or
+    /// `string`) that intersect the given range, along with the names of those scopes.
+    /// The buffer can be associated with multiple languages, and the override query associated
+    /// with each will be run on the relevant section of the buffer.
+    pub fn override_ranges<T: ToOffset>(
+        &self,
+        range: Range<T>,
+    ) -> impl Iterator<Item = (Range<usize>, &str)> + '_ {
+        let offset_range = range.start.to_offset(self)..range.end.to_offset(self);
+        let mut syntax_matches = self.syntax.matches(offset_range, self, |grammar| {
+            grammar.override_config.as_ref().map(|config| &config.query)
+        });
+
+        let configs = syntax_matches
+            .grammars()
+            .iter()
+            .map(|grammar| grammar.override_config.as_ref())
+            .collect::<Vec<_>>();
+
+        iter::from_fn(move || {
+            while let Some(mat) = syntax_matches.peek() {
+                let scope = configs[mat.grammar_index].and_then(|config| {
+                    mat.captures.iter().find_map(|capture| {
+                        let entry = config.values.get(&capture.index)?;
+                        Some((capture.node.byte_range(), entry.name.as_str()))
+                    })
+                });
+                syntax_matches.advance();
+                if scope.is_some() {
+                    return scope;
+                }
+            }
+            None
+        })
+    }
+
     pub fn injections_intersecting_range<T: ToOffset>(
         &self,
         range: Range<T>,
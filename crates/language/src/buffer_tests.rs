@@ -1142,6 +1142,36 @@ fn test_range_for_syntax_ancestor(cx: &mut App) {
     }
 }
 
+#[gpui::test]
+fn test_next_sibling_of_same_kind(cx: &mut App) {
+    cx.new(|cx| {
+        let text = "fn a() { let b = [one, two(), three, four]; c(); d(); }";
+        let buffer = Buffer::local(text, cx).with_language(Arc::new(rust_lang()), cx);
+        let snapshot = buffer.snapshot();
+        let range_of = |part: &str| {
+            let start = text.find(part).unwrap();
+            start..start + part.len()
+        };
+
+        let caret = text.find("ne,").unwrap();
+        assert_eq!(
+            snapshot.next_sibling_of_same_kind(caret..caret),
+            Some((range_of("one"), range_of("three")))
+        );
+        assert_eq!(
+            snapshot.next_sibling_of_same_kind(range_of("three")),
+            Some((range_of("three"), range_of("four")))
+        );
+        assert_eq!(snapshot.next_sibling_of_same_kind(range_of("four")), None);
+        assert_eq!(
+            snapshot.next_sibling_of_same_kind(range_of("c();")),
+            Some((range_of("c();"), range_of("d();")))
+        );
+
+        buffer
+    });
+}
+
 #[gpui::test]
 fn test_autoindent_with_soft_tabs(cx: &mut App) {
     init_settings(cx, |_| {});
@@ -2345,6 +2375,44 @@ fn test_language_scope_at_with_rust(cx: &mut App) {
     });
 }
 
+#[gpui::test]
+fn test_override_ranges(cx: &mut App) {
+    init_settings(cx, |_| {});
+
+    cx.new(|cx| {
+        let language = Language::new(
+            LanguageConfig {
+                name: "Rust".into(),
+                ..Default::default()
+            },
+            Some(tree_sitter_rust::LANGUAGE.into()),
+        )
+        .with_override_query(
+            r#"
+                (string_literal) @string
+                (line_comment) @comment.inclusive
+            "#,
+        )
+        .unwrap();
+
+        let text = r#"
+            // greet
+            const S: &str = "hello";
+        "#
+        .unindent();
+
+        let buffer = Buffer::local(text.clone(), cx).with_language(Arc::new(language), cx);
+        let snapshot = buffer.snapshot();
+        let scopes = snapshot
+            .override_ranges(0..text.len())
+            .map(|(range, name)| (text[range].trim_end(), name))
+            .collect::<Vec<_>>();
+        assert_eq!(scopes, [("// greet", "comment"), ("\"hello\"", "string")]);
+
+        buffer
+    });
+}
+
 #[gpui::test]
 fn test_language_scope_at_with_combined_injections(cx: &mut App) {
     init_settings(cx, |_| {});
@@ -2393,6 +2461,115 @@ fn test_language_scope_at_with_combined_injections(cx: &mut App) {
     });
 }
 
+#[gpui::test]
+fn test_injection_ranges(cx: &mut App) {
+    init_settings(cx, |_| {});
+
+    cx.new(|cx| {
+        let text = r#"
+            Some *emphasized* text.
+
+            ```rust
+            fn main() {}
+            ```
+        "#
+        .unindent();
+
+        let language_registry = Arc::new(LanguageRegistry::test(cx.background_executor().clone()));
+        language_registry.add(Arc::new(markdown_lang()));
+        language_registry.add(Arc::new(markdown_inline_lang()));
+        language_registry.add(Arc::new(rust_lang()));
+
+        let mut buffer = Buffer::local(text, cx);
+        buffer.set_language_registry(language_registry.clone());
+        buffer.set_language(
+            language_registry
+                .language_for_name("Markdown")
+                .now_or_never()
+                .unwrap()
+                .ok(),
+            cx,
+        );
+
+        let snapshot = buffer.snapshot();
+        let injections = snapshot
+            .injection_ranges()
+            .into_iter()
+            .map(|(range, language)| {
+                (
+                    snapshot.text_for_range(range).collect::<String>(),
+                    language.name(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(injections, [("fn main() {}\n".to_string(), "Rust".into())]);
+
+        buffer
+    });
+}
+
+#[gpui::test]
+fn test_structural_pattern(cx: &mut App) {
+    init_settings(cx, |_| {});
+
+    cx.new(|cx| {
+        let text = r#"
+            fn main() {
+                let a = foo.unwrap();
+                let b = bar(1).unwrap() + foo.unwrap_or(2);
+                a.unwrap().unwrap();
+                let c = b + b;
+                let d = b + c;
+            }
+        "#
+        .unindent();
+
+        let buffer = Buffer::local(text, cx).with_language(Arc::new(rust_lang()), cx);
+        let snapshot = buffer.snapshot();
+
+        let pattern = StructuralPattern::new("$X.unwrap()").unwrap();
+        assert_eq!(pattern.literal_words().collect::<Vec<_>>(), ["unwrap"]);
+        let matches = pattern.find_matches(&snapshot, 0..snapshot.len());
+        assert_eq!(
+            matches
+                .iter()
+                .map(|mat| {
+                    (
+                        snapshot
+                            .text_for_range(mat.range.clone())
+                            .collect::<String>(),
+                        StructuralPattern::expand("$X?", &snapshot, &mat.captures).unwrap(),
+                    )
+                })
+                .collect::<Vec<_>>(),
+            [
+                ("foo.unwrap()".to_string(), "foo?".to_string()),
+                ("bar(1).unwrap()".to_string(), "bar(1)?".to_string()),
+                ("a.unwrap().unwrap()".to_string(), "a.unwrap()?".to_string()),
+            ]
+        );
+
+        // A metavariable that occurs several times must match the same code each time.
+        let pattern = StructuralPattern::new("$A + $A").unwrap();
+        let matches = pattern.find_matches(&snapshot, 0..snapshot.len());
+        assert_eq!(
+            matches
+                .iter()
+                .map(|mat| snapshot
+                    .text_for_range(mat.range.clone())
+                    .collect::<String>())
+                .collect::<Vec<_>>(),
+            ["b + b"]
+        );
+        assert!(StructuralPattern::expand("$B", &snapshot, &matches[0].captures).is_err());
+
+        assert!(StructuralPattern::new("$1.unwrap()").is_err());
+        assert!(StructuralPattern::new("  ").is_err());
+
+        buffer
+    });
+}
+
 #[gpui::test]
 fn test_language_at_with_hidden_languages(cx: &mut App) {
     init_settings(cx, |_| {});
@@ -13,6 +13,7 @@ mod language_registry;
 pub mod language_settings;
 mod outline;
 pub mod proto;
+mod structural_pattern;
 mod syntax_map;
 mod task_context;
 mod toolchain;
@@ -79,6 +80,7 @@ pub use language_registry::{
 };
 pub use lsp::LanguageServerId;
 pub use outline::*;
+pub use structural_pattern::*;
 pub use syntax_map::{OwnedSyntaxLayer, SyntaxLayer, ToTreeSitterPoint, TreeSitterOptions};
 pub use text::{AnchorRangeExt, LineEnding};
 pub use tree_sitter::{Node, Parser, Tree, TreeCursor};
@@ -633,6 +635,24 @@ pub struct LanguageConfig {
     /// languages, but should not appear to the user as a distinct language.
     #[serde(default)]
     pub hidden: bool,
+    /// The kind of Emmet abbreviations to offer as completions, if any.
+    #[serde(default)]
+    pub emmet: Option<EmmetSyntax>,
+}
+
+/// The kind of text Emmet abbreviations expand into.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum EmmetSyntax {
+    Html,
+    Jsx,
+    Css,
+}
+
+impl EmmetSyntax {
+    pub fn is_markup(self) -> bool {
+        matches!(self, EmmetSyntax::Html | EmmetSyntax::Jsx)
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, Default, JsonSchema)]
@@ -671,6 +691,8 @@ pub struct LanguageConfigOverride {
     pub word_characters: Override<HashSet<char>>,
     #[serde(default)]
     pub opt_into_language_servers: Vec<LanguageServerName>,
+    #[serde(default)]
+    pub emmet: Override<EmmetSyntax>,
 }
 
 #[derive(Clone, Deserialize, Debug, Serialize, JsonSchema)]
@@ -720,6 +742,7 @@ impl Default for LanguageConfig {
             soft_wrap: None,
             prettier_parser_name: None,
             hidden: false,
+            emmet: None,
         }
     }
 }
@@ -1614,6 +1637,15 @@ impl LanguageScope {
         c.is_whitespace() || self.language.config.autoclose_before.contains(c)
     }
 
+    /// Returns the kind of Emmet abbreviations to offer as completions in this scope.
+    pub fn emmet_syntax(&self) -> Option<EmmetSyntax> {
+        Override::as_option(
+            self.config_override().map(|o| &o.emmet),
+            self.language.config.emmet.as_ref(),
+        )
+        .copied()
+    }
+
     pub fn language_allowed(&self, name: &LanguageServerName) -> bool {
         let config = &self.language.config;
         let opt_in_servers = &config.scope_opt_in_language_servers;
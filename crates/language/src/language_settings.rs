@@ -98,6 +98,9 @@ pub struct LanguageSettings {
     pub ensure_final_newline_on_save: bool,
     /// How to perform a buffer format.
     pub formatter: SelectedFormatter,
+    /// Whether to also format regions of other languages embedded in the buffer,
+    /// like code blocks in Markdown, using the formatters of those languages.
+    pub format_injections: bool,
     /// Zed's Prettier integration settings.
     pub prettier: PrettierSettings,
     /// Whether to use language servers to provide code intelligence.
@@ -300,6 +303,12 @@ pub struct LanguageSettingsContent {
     /// Default: auto
     #[serde(default)]
     pub formatter: Option<SelectedFormatter>,
+    /// Whether to also format regions of other languages embedded in the buffer,
+    /// like code blocks in Markdown, using the formatters of those languages.
+    ///
+    /// Default: false
+    #[serde(default)]
+    pub format_injections: Option<bool>,
     /// Zed's Prettier integration settings.
     /// Allows to enable/disable formatting with Prettier
     /// and configure default Prettier, used when no project-level Prettier installation is found.
@@ -1163,6 +1172,7 @@ fn merge_settings(settings: &mut LanguageSettings, src: &LanguageSettingsContent
         src.preferred_line_length,
     );
     merge(&mut settings.formatter, src.formatter.clone());
+    merge(&mut settings.format_injections, src.format_injections);
     merge(&mut settings.prettier, src.prettier.clone());
     merge(&mut settings.format_on_save, src.format_on_save.clone());
     merge(
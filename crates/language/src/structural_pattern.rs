@@ -0,0 +1,320 @@
+use crate::{with_parser, BufferSnapshot, Language, LanguageId};
+use anyhow::{anyhow, Result};
+use collections::HashMap;
+use std::{fmt::Write as _, ops::Range, sync::Arc};
+use tree_sitter::{Node, Tree};
+
+/// The identifier that metavariables are replaced with before a pattern is parsed, followed
+/// by the index of the metavariable. It needs to be a valid identifier in most languages.
+const PLACEHOLDER_PREFIX: &str = "zedmetavariable";
+
+/// A code pattern that's matched against syntax trees rather than text, like `$X.unwrap()`.
+///
+/// Each `$NAME` metavariable stands for any single syntax node. A metavariable that occurs
+/// several times must match the same text each time, except for `$_`, which matches
+/// anything. `$$` stands for a literal `$`.
+#[derive(Clone, Debug)]
+pub struct StructuralPattern {
+    /// The pattern with its metavariables replaced by placeholder identifiers.
+    source: String,
+    metavariables: Vec<Arc<str>>,
+}
+
+/// Code that matches a [`StructuralPattern`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StructuralMatch {
+    pub range: Range<usize>,
+    /// The range of the code matched by each metavariable.
+    pub captures: HashMap<Arc<str>, Range<usize>>,
+}
+
+impl StructuralPattern {
+    pub fn new(pattern: &str) -> Result<Self> {
+        let mut source = String::new();
+        let mut metavariables = Vec::<Arc<str>>::new();
+        for segment in parse_template(pattern)? {
+            match segment {
+                TemplateSegment::Text(text) => source.push_str(text),
+                TemplateSegment::Metavariable(name) => {
+                    let index = metavariables
+                        .iter()
+                        .position(|existing| existing.as_ref() == name)
+                        .unwrap_or_else(|| {
+                            metavariables.push(name.into());
+                            metavariables.len() - 1
+                        });
+                    write!(source, "{PLACEHOLDER_PREFIX}{index}").unwrap();
+                }
+            }
+        }
+        if source.trim().is_empty() {
+            return Err(anyhow!("structural search pattern is empty"));
+        }
+        Ok(Self {
+            source,
+            metavariables,
+        })
+    }
+
+    /// Returns the words of the pattern outside of its metavariables, which are contained
+    /// in any code that matches it.
+    pub fn literal_words(&self) -> impl Iterator<Item = &str> {
+        self.source
+            .split(|c: char| !c.is_alphanumeric() && c != '_')
+            .filter(|word| !word.is_empty() && self.metavariable_index(word).is_none())
+    }
+
+    /// Finds the code in the given range of the buffer that matches the pattern, in every
+    /// language of the buffer. Matches nested within other matches aren't returned.
+    pub fn find_matches(
+        &self,
+        buffer: &BufferSnapshot,
+        range: Range<usize>,
+    ) -> Vec<StructuralMatch> {
+        let mut matches = Vec::new();
+        let mut pattern_trees = HashMap::<LanguageId, Option<Tree>>::default();
+        let mut has_layers = false;
+        for layer in buffer.syntax_layers() {
+            has_layers = true;
+            let root = layer.node();
+            if root.end_byte() <= range.start || root.start_byte() >= range.end {
+                continue;
+            }
+            let pattern_tree = pattern_trees
+                .entry(layer.language.id)
+                .or_insert_with(|| self.parse(layer.language));
+            if let Some(pattern_tree) = pattern_tree {
+                self.find_matches_in_tree(pattern_tree, root, buffer, &range, &mut matches);
+            }
+        }
+
+        // The buffer may not have been parsed yet, for example when it was just opened
+        // to be searched.
+        if !has_layers {
+            if let Some((pattern_tree, tree)) = buffer
+                .language()
+                .and_then(|language| Some((self.parse(language)?, parse_buffer(buffer, language)?)))
+            {
+                self.find_matches_in_tree(
+                    &pattern_tree,
+                    tree.root_node(),
+                    buffer,
+                    &range,
+                    &mut matches,
+                );
+            }
+        }
+
+        matches.sort_by_key(|mat| (mat.range.start, usize::MAX - mat.range.end));
+        let mut last_end = 0;
+        matches.retain(|mat| {
+            if mat.range.start < last_end {
+                false
+            } else {
+                last_end = mat.range.end;
+                true
+            }
+        });
+        matches
+    }
+
+    /// Expands the `$NAME` metavariables in a replacement template to the code they
+    /// captured in the given match.
+    pub fn expand(
+        template: &str,
+        buffer: &BufferSnapshot,
+        captures: &HashMap<Arc<str>, Range<usize>>,
+    ) -> Result<String> {
+        let mut expansion = String::new();
+        for segment in parse_template(template)? {
+            match segment {
+                TemplateSegment::Text(text) => expansion.push_str(text),
+                TemplateSegment::Metavariable(name) => {
+                    let range = captures
+                        .get(name)
+                        .ok_or_else(|| anyhow!("metavariable ${name} isn't in the pattern"))?;
+                    expansion.extend(buffer.text_for_range(range.clone()));
+                }
+            }
+        }
+        Ok(expansion)
+    }
+
+    fn parse(&self, language: &Arc<Language>) -> Option<Tree> {
+        let grammar = language.grammar()?;
+        with_parser(|parser| {
+            parser.set_language(&grammar.ts_language).ok()?;
+            parser.parse(&self.source, None)
+        })
+    }
+
+    fn metavariable_index(&self, text: &str) -> Option<usize> {
+        let index = text
+            .strip_prefix(PLACEHOLDER_PREFIX)?
+            .parse::<usize>()
+            .ok()?;
+        (index < self.metavariables.len()).then_some(index)
+    }
+
+    fn find_matches_in_tree(
+        &self,
+        pattern_tree: &Tree,
+        root: Node,
+        buffer: &BufferSnapshot,
+        range: &Range<usize>,
+        matches: &mut Vec<StructuralMatch>,
+    ) {
+        let pattern = pattern_root(pattern_tree, &self.source);
+        let mut cursor = root.walk();
+        loop {
+            let node = cursor.node();
+            let node_range = node.byte_range();
+            let mut descend = node_range.start < range.end && node_range.end > range.start;
+            if descend && node_range.start >= range.start && node_range.end <= range.end {
+                let mut captures = Vec::new();
+                if self.match_node(pattern, node, buffer, &mut captures) {
+                    matches.push(StructuralMatch {
+                        range: node_range,
+                        captures: captures
+                            .into_iter()
+                            .map(|(index, range)| (self.metavariables[index].clone(), range))
+                            .collect(),
+                    });
+                    descend = false;
+                }
+            }
+
+            if descend && cursor.goto_first_child() {
+                continue;
+            }
+            while !cursor.goto_next_sibling() {
+                if !cursor.goto_parent() {
+                    return;
+                }
+            }
+        }
+    }
+
+    fn match_node(
+        &self,
+        pattern: Node,
+        node: Node,
+        buffer: &BufferSnapshot,
+        captures: &mut Vec<(usize, Range<usize>)>,
+    ) -> bool {
+        let pattern_text = &self.source[pattern.byte_range()];
+        if let Some(index) = self.metavariable_index(pattern_text) {
+            if !node.is_named() {
+                return false;
+            }
+            if self.metavariables[index].as_ref() == "_" {
+                return true;
+            }
+            if let Some((_, captured)) = captures.iter().find(|(ix, _)| *ix == index) {
+                return buffer
+                    .text_for_range(captured.clone())
+                    .eq(buffer.text_for_range(node.byte_range()));
+            }
+            captures.push((index, node.byte_range()));
+            return true;
+        }
+
+        if pattern.kind_id() != node.kind_id() {
+            return false;
+        }
+        let pattern_children = significant_children(pattern);
+        let children = significant_children(node);
+        if pattern_children.is_empty() {
+            return children.is_empty()
+                && node.byte_range().len() == pattern_text.len()
+                && buffer.contains_str_at(node.start_byte(), pattern_text);
+        }
+        pattern_children.len() == children.len()
+            && pattern_children
+                .into_iter()
+                .zip(children)
+                .all(|(pattern_child, child)| {
+                    self.match_node(pattern_child, child, buffer, captures)
+                })
+    }
+}
+
+/// Returns the node that the pattern is made of, skipping the nodes that only wrap it,
+/// like the root of the tree or the statement containing an expression.
+fn pattern_root<'a>(tree: &'a Tree, source: &str) -> Node<'a> {
+    let start = source.len() - source.trim_start().len();
+    let end = source.trim_end().len();
+    let mut node = tree.root_node();
+    loop {
+        let mut children = significant_children(node)
+            .into_iter()
+            .filter(|child| child.byte_range().len() > 0);
+        match (children.next(), children.next()) {
+            (Some(child), None) if child.start_byte() <= start && child.end_byte() >= end => {
+                node = child
+            }
+            _ => return node,
+        }
+    }
+}
+
+/// Returns the children of the node, except for comments and nodes that the parser
+/// inserted to recover from errors.
+fn significant_children(node: Node) -> Vec<Node> {
+    let mut cursor = node.walk();
+    let children = node
+        .children(&mut cursor)
+        .filter(|child| !child.is_extra() && !child.is_missing())
+        .collect();
+    children
+}
+
+fn parse_buffer(buffer: &BufferSnapshot, language: &Arc<Language>) -> Option<Tree> {
+    let grammar = language.grammar()?;
+    let mut chunks = buffer.as_rope().chunks_in_range(0..buffer.len());
+    with_parser(|parser| {
+        parser.set_language(&grammar.ts_language).ok()?;
+        parser.parse_with(
+            &mut move |offset, _| {
+                chunks.seek(offset);
+                chunks.next().unwrap_or("").as_bytes()
+            },
+            None,
+        )
+    })
+}
+
+enum TemplateSegment<'a> {
+    Text(&'a str),
+    Metavariable(&'a str),
+}
+
+/// Splits a pattern or replacement template into literal text and `$NAME` metavariables.
+fn parse_template(template: &str) -> Result<Vec<TemplateSegment>> {
+    let mut segments = Vec::new();
+    let mut rest = template;
+    while let Some(dollar_ix) = rest.find('$') {
+        segments.push(TemplateSegment::Text(&rest[..dollar_ix]));
+        let after_dollar = &rest[dollar_ix + 1..];
+        if let Some(after_escape) = after_dollar.strip_prefix('$') {
+            segments.push(TemplateSegment::Text("$"));
+            rest = after_escape;
+            continue;
+        }
+
+        let name_len = after_dollar
+            .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+            .unwrap_or(after_dollar.len());
+        let name = &after_dollar[..name_len];
+        if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+            return Err(anyhow!(
+                "expected a metavariable name after `$` at offset {}",
+                template.len() - rest.len() + dollar_ix
+            ));
+        }
+        segments.push(TemplateSegment::Metavariable(name));
+        rest = &after_dollar[name_len..];
+    }
+    segments.push(TemplateSegment::Text(rest));
+    Ok(segments)
+}
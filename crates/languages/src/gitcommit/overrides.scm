@@ -0,0 +1,4 @@
+[
+  (comment)
+  (trailer)
+] @verbatim
@@ -133,7 +133,8 @@ impl JsonLspAdapter {
                                 paths::snippets_dir()
                                     .join("*.json")
                                     .as_path()
-                            )
+                            ),
+                            paths::local_snippets_folder_relative_path().join("*.json")
                         ],
                         "schema": snippets_schema,
                     }
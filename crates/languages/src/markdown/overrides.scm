@@ -0,0 +1,6 @@
+[
+  (fenced_code_block)
+  (indented_code_block)
+  (html_block)
+  (link_destination)
+] @verbatim
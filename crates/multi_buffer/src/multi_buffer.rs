@@ -5639,6 +5639,27 @@ impl MultiBufferSnapshot {
         Some((node, excerpt.map_range_from_buffer(node.byte_range())))
     }
 
+    /// Returns the range of the syntax node at the given range, along with the range of its
+    /// next sibling of the same kind, if that's in the same excerpt.
+    pub fn next_sibling_of_same_kind<T: ToOffset>(
+        &self,
+        range: Range<T>,
+    ) -> Option<(Range<usize>, Range<usize>)> {
+        let range = range.start.to_offset(self)..range.end.to_offset(self);
+        let mut excerpt = self.excerpt_containing(range.clone())?;
+        let (node_range, sibling_range) = excerpt
+            .buffer()
+            .next_sibling_of_same_kind(excerpt.map_range_to_buffer(range))?;
+        let excerpt_range = excerpt.buffer_range();
+        if sibling_range.start < excerpt_range.start || sibling_range.end > excerpt_range.end {
+            return None;
+        }
+        Some((
+            excerpt.map_range_from_buffer(node_range),
+            excerpt.map_range_from_buffer(sibling_range),
+        ))
+    }
+
     pub fn outline(&self, theme: Option<&SyntaxTheme>) -> Option<Outline<Anchor>> {
         let (excerpt_id, _, buffer) = self.as_singleton()?;
         let outline = buffer.outline(theme)?;
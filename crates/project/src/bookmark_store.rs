@@ -0,0 +1,488 @@
+//! Line bookmarks, stored as buffer anchors so that they follow edits.
+//!
+//! The bookmarks of files that aren't open are kept as rows, and anchored once their buffer gets opened.
+
+use crate::{
+    buffer_store::{BufferStore, BufferStoreEvent},
+    worktree_store::WorktreeStore,
+    ProjectPath,
+};
+use anyhow::{anyhow, Result};
+use collections::BTreeMap;
+use gpui::{App, Context, Entity, EventEmitter, SharedString, Subscription, Task, WeakEntity};
+use language::{Buffer, BufferEvent, BufferSnapshot, Point};
+use std::{path::Path, sync::Arc};
+use text::BufferId;
+use util::ResultExt as _;
+use worktree::File;
+
+#[derive(Clone, Debug)]
+pub struct Bookmark {
+    /// The start of the bookmarked line.
+    pub position: text::Anchor,
+    pub label: Option<SharedString>,
+}
+
+impl Bookmark {
+    pub fn row(&self, snapshot: &BufferSnapshot) -> u32 {
+        snapshot.summary_for_anchor::<Point>(&self.position).row
+    }
+}
+
+/// A bookmark as it is persisted between sessions.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SerializedBookmark {
+    pub abs_path: Arc<Path>,
+    pub row: u32,
+    pub label: Option<String>,
+}
+
+/// A bookmark of a file that isn't open.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct ClosedBookmark {
+    row: u32,
+    label: Option<SharedString>,
+}
+
+struct BufferBookmarks {
+    buffer: WeakEntity<Buffer>,
+    /// Sorted by position.
+    bookmarks: Vec<Bookmark>,
+    _subscriptions: [Subscription; 2],
+}
+
+pub enum BookmarkStoreEvent {
+    BookmarksChanged,
+}
+
+/// Keeps the bookmarks of all buffers in a project.
+pub struct BookmarkStore {
+    buffer_store: Entity<BufferStore>,
+    worktree_store: Entity<WorktreeStore>,
+    buffers: BTreeMap<BufferId, BufferBookmarks>,
+    /// The bookmarks of the files whose buffers aren't open, by absolute path,
+    /// as of when their buffer was closed or when they were restored.
+    closed_files: BTreeMap<Arc<Path>, Vec<ClosedBookmark>>,
+    /// Whether the bookmarks of the previous session are being loaded,
+    /// during which the bookmarks shouldn't be persisted.
+    restoring: bool,
+    _subscription: Subscription,
+}
+
+impl EventEmitter<BookmarkStoreEvent> for BookmarkStore {}
+
+impl BookmarkStore {
+    pub fn new(
+        buffer_store: Entity<BufferStore>,
+        worktree_store: Entity<WorktreeStore>,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let _subscription = cx.subscribe(&buffer_store, |this, _, event, cx| {
+            if let BufferStoreEvent::BufferAdded(buffer) = event {
+                this.anchor_closed_bookmarks(buffer, cx);
+            }
+        });
+        Self {
+            buffer_store,
+            worktree_store,
+            buffers: BTreeMap::default(),
+            closed_files: BTreeMap::default(),
+            restoring: false,
+            _subscription,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buffers.is_empty() && self.closed_files.is_empty()
+    }
+
+    pub fn bookmarks_for_buffer(&self, buffer_id: BufferId) -> &[Bookmark] {
+        self.buffers
+            .get(&buffer_id)
+            .map_or(&[], |buffer| buffer.bookmarks.as_slice())
+    }
+
+    /// Returns every bookmark of the open buffers, grouped by buffer.
+    ///
+    /// Use [`Self::open_closed_files`] first to include the bookmarks of the files that aren't open.
+    pub fn all_bookmarks(&self) -> impl Iterator<Item = (Entity<Buffer>, &Bookmark)> {
+        self.buffers
+            .values()
+            .filter_map(|buffer| Some((buffer.buffer.upgrade()?, &buffer.bookmarks)))
+            .flat_map(|(buffer, bookmarks)| {
+                bookmarks
+                    .iter()
+                    .map(move |bookmark| (buffer.clone(), bookmark))
+            })
+    }
+
+    /// Opens the buffers of the files with bookmarks that aren't open yet, which anchors their bookmarks.
+    ///
+    /// The buffers are only held weakly, so the returned ones have to be kept to keep them open.
+    pub fn open_closed_files(&mut self, cx: &mut Context<Self>) -> Task<Vec<Entity<Buffer>>> {
+        let open_buffers = self
+            .closed_files
+            .keys()
+            .cloned()
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|abs_path| self.open_buffer(&abs_path, cx))
+            .collect::<Vec<_>>();
+        cx.spawn(|_, _| async move {
+            futures::future::join_all(open_buffers)
+                .await
+                .into_iter()
+                .filter_map(|buffer| buffer.log_err())
+                .collect()
+        })
+    }
+
+    fn open_buffer(
+        &mut self,
+        abs_path: &Path,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Entity<Buffer>>> {
+        let Some((worktree, path)) = self.worktree_store.read(cx).find_worktree(abs_path, cx)
+        else {
+            return Task::ready(Err(anyhow!("no worktree for bookmarked file {abs_path:?}")));
+        };
+        let project_path = ProjectPath {
+            worktree_id: worktree.read(cx).id(),
+            path: path.into(),
+        };
+        let open_buffer = self.buffer_store.update(cx, |buffer_store, cx| {
+            buffer_store.open_buffer(project_path, cx)
+        });
+        cx.spawn(|this, mut cx| async move {
+            let buffer = open_buffer.await?;
+            this.update(&mut cx, |this, cx| {
+                this.anchor_closed_bookmarks(&buffer, cx)
+            })?;
+            Ok(buffer)
+        })
+    }
+
+    /// Adds an anonymous bookmark on the given row, or removes the bookmark that's already there.
+    pub fn toggle(&mut self, buffer: &Entity<Buffer>, row: u32, cx: &mut Context<Self>) {
+        let snapshot = buffer.read(cx).snapshot();
+        let buffer_id = snapshot.remote_id();
+        let existing = self.buffers.get(&buffer_id).and_then(|buffer| {
+            buffer
+                .bookmarks
+                .iter()
+                .position(|bookmark| bookmark.row(&snapshot) == row)
+        });
+        match existing {
+            Some(ix) => {
+                if let Some(buffer) = self.buffers.get_mut(&buffer_id) {
+                    buffer.bookmarks.remove(ix);
+                    if buffer.bookmarks.is_empty() {
+                        self.buffers.remove(&buffer_id);
+                    }
+                }
+            }
+            None => self.insert(buffer, &snapshot, row, None, cx),
+        }
+        cx.emit(BookmarkStoreEvent::BookmarksChanged);
+    }
+
+    /// Bookmarks the given row with a label, replacing the label of an existing bookmark.
+    pub fn set(
+        &mut self,
+        buffer: &Entity<Buffer>,
+        row: u32,
+        label: Option<SharedString>,
+        cx: &mut Context<Self>,
+    ) {
+        let snapshot = buffer.read(cx).snapshot();
+        self.insert(buffer, &snapshot, row, label, cx);
+        cx.emit(BookmarkStoreEvent::BookmarksChanged);
+    }
+
+    pub fn clear(&mut self, cx: &mut Context<Self>) {
+        if !self.is_empty() {
+            self.buffers.clear();
+            self.closed_files.clear();
+            cx.emit(BookmarkStoreEvent::BookmarksChanged);
+        }
+    }
+
+    /// Whether the bookmarks of the previous session are being loaded, see [`Self::start_restoring`].
+    pub fn is_restoring(&self) -> bool {
+        self.restoring
+    }
+
+    /// Marks the bookmarks of the previous session as being loaded, until [`Self::restore`] is called.
+    pub fn start_restoring(&mut self) {
+        self.restoring = true;
+    }
+
+    /// Restores the bookmarks of the previous session, keeping the ones added since.
+    /// Their buffers are only opened when navigating to them.
+    pub fn restore(&mut self, bookmarks: Vec<SerializedBookmark>, cx: &mut Context<Self>) {
+        self.restoring = false;
+        for bookmark in bookmarks {
+            let closed_bookmarks = self.closed_files.entry(bookmark.abs_path).or_default();
+            if !closed_bookmarks
+                .iter()
+                .any(|closed_bookmark| closed_bookmark.row == bookmark.row)
+            {
+                closed_bookmarks.push(ClosedBookmark {
+                    row: bookmark.row,
+                    label: bookmark.label.map(SharedString::from),
+                });
+            }
+        }
+        let open_buffers = self.buffer_store.read(cx).buffers().collect::<Vec<_>>();
+        for buffer in open_buffers {
+            self.anchor_closed_bookmarks(&buffer, cx);
+        }
+        cx.emit(BookmarkStoreEvent::BookmarksChanged);
+    }
+
+    pub fn serialize(&self, cx: &App) -> Vec<SerializedBookmark> {
+        let mut serialized = Vec::new();
+        for buffer in self.buffers.values() {
+            let Some(buffer_handle) = buffer.buffer.upgrade() else {
+                continue;
+            };
+            let buffer_ref = buffer_handle.read(cx);
+            let Some(abs_path) = abs_path(buffer_ref, cx) else {
+                continue;
+            };
+            let snapshot = buffer_ref.snapshot();
+            serialized.extend(buffer.bookmarks.iter().map(|bookmark| SerializedBookmark {
+                abs_path: abs_path.clone(),
+                row: bookmark.row(&snapshot),
+                label: bookmark.label.as_ref().map(|label| label.to_string()),
+            }));
+        }
+        for (abs_path, bookmarks) in &self.closed_files {
+            serialized.extend(bookmarks.iter().map(|bookmark| SerializedBookmark {
+                abs_path: abs_path.clone(),
+                row: bookmark.row,
+                label: bookmark.label.as_ref().map(|label| label.to_string()),
+            }));
+        }
+        serialized
+    }
+
+    /// Anchors the bookmarks of the buffer's file, if it was closed.
+    fn anchor_closed_bookmarks(&mut self, buffer: &Entity<Buffer>, cx: &mut Context<Self>) {
+        let Some(closed_bookmarks) =
+            abs_path(buffer.read(cx), cx).and_then(|abs_path| self.closed_files.remove(&abs_path))
+        else {
+            return;
+        };
+        let snapshot = buffer.read(cx).snapshot();
+        let max_row = snapshot.max_point().row;
+        for bookmark in closed_bookmarks {
+            let exists = self
+                .bookmarks_for_buffer(snapshot.remote_id())
+                .iter()
+                .any(|existing| existing.row(&snapshot) == bookmark.row);
+            if bookmark.row <= max_row && !exists {
+                self.insert(buffer, &snapshot, bookmark.row, bookmark.label, cx);
+            }
+        }
+        cx.emit(BookmarkStoreEvent::BookmarksChanged);
+    }
+
+    /// Keeps the bookmarks of a buffer that's being closed as rows.
+    fn buffer_released(&mut self, buffer: &mut Buffer, cx: &mut Context<Self>) {
+        let snapshot = buffer.snapshot();
+        let Some(buffer_bookmarks) = self.buffers.remove(&snapshot.remote_id()) else {
+            return;
+        };
+        if let Some(abs_path) = abs_path(buffer, cx) {
+            let closed_bookmarks = buffer_bookmarks
+                .bookmarks
+                .iter()
+                .map(|bookmark| ClosedBookmark {
+                    row: bookmark.row(&snapshot),
+                    label: bookmark.label.clone(),
+                })
+                .collect();
+            self.closed_files.insert(abs_path, closed_bookmarks);
+        }
+        cx.emit(BookmarkStoreEvent::BookmarksChanged);
+    }
+
+    fn insert(
+        &mut self,
+        buffer: &Entity<Buffer>,
+        snapshot: &BufferSnapshot,
+        row: u32,
+        label: Option<SharedString>,
+        cx: &mut Context<Self>,
+    ) {
+        let bookmarks = &mut self
+            .buffers
+            .entry(snapshot.remote_id())
+            .or_insert_with(|| BufferBookmarks {
+                buffer: buffer.downgrade(),
+                bookmarks: Vec::new(),
+                _subscriptions: [
+                    // Persist where the bookmarks moved to, along with the edits that moved them.
+                    cx.subscribe(buffer, |_, _, event, cx| {
+                        if let BufferEvent::Saved = event {
+                            cx.emit(BookmarkStoreEvent::BookmarksChanged);
+                        }
+                    }),
+                    cx.observe_release(buffer, Self::buffer_released),
+                ],
+            })
+            .bookmarks;
+        if let Some(bookmark) = bookmarks
+            .iter_mut()
+            .find(|bookmark| bookmark.row(snapshot) == row)
+        {
+            bookmark.label = label;
+            return;
+        }
+
+        let position = snapshot.anchor_after(Point::new(row, 0));
+        let ix = bookmarks
+            .binary_search_by(|bookmark| bookmark.position.cmp(&position, snapshot))
+            .unwrap_or_else(|ix| ix);
+        bookmarks.insert(ix, Bookmark { position, label });
+    }
+}
+
+fn abs_path(buffer: &Buffer, cx: &App) -> Option<Arc<Path>> {
+    let file = File::from_dyn(buffer.file())?;
+    let abs_path = file.worktree.read(cx).absolutize(&file.path).ok()?;
+    Some(abs_path.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{project_tests::init_test, FakeFs, Project};
+    use gpui::TestAppContext;
+    use serde_json::json;
+    use util::path;
+
+    #[gpui::test]
+    async fn test_bookmarks_follow_edits(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(path!("/dir"), json!({ "a.txt": "one\ntwo\nthree\nfour\n" }))
+            .await;
+        let project = Project::test(fs, [path!("/dir").as_ref()], cx).await;
+        let buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer(path!("/dir/a.txt"), cx)
+            })
+            .await
+            .unwrap();
+        let store = project.read_with(cx, |project, _| project.bookmark_store().clone());
+
+        store.update(cx, |store, cx| {
+            store.toggle(&buffer, 2, cx);
+            store.set(&buffer, 0, Some("first".into()), cx);
+        });
+        let rows = |cx: &mut TestAppContext| {
+            store.read_with(cx, |store, cx| {
+                let snapshot = buffer.read(cx).snapshot();
+                store
+                    .all_bookmarks()
+                    .map(|(_, bookmark)| (bookmark.row(&snapshot), bookmark.label.clone()))
+                    .collect::<Vec<_>>()
+            })
+        };
+        assert_eq!(rows(cx), [(0, Some("first".into())), (2, None)]);
+
+        buffer.update(cx, |buffer, cx| {
+            buffer.edit([(4..4, "one and a half\n")], None, cx);
+        });
+        assert_eq!(rows(cx), [(0, Some("first".into())), (3, None)]);
+
+        store.update(cx, |store, cx| store.toggle(&buffer, 3, cx));
+        assert_eq!(rows(cx), [(0, Some("first".into()))]);
+
+        store.update(cx, |store, cx| store.toggle(&buffer, 0, cx));
+        assert!(store.read_with(cx, |store, _| store.is_empty()));
+    }
+
+    #[gpui::test]
+    async fn test_bookmarks_of_closed_files(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/dir"),
+            json!({
+                "a.txt": "one\ntwo\nthree\n",
+                "b.txt": "one\ntwo\n",
+            }),
+        )
+        .await;
+        let project = Project::test(fs, [path!("/dir").as_ref()], cx).await;
+        let store = project.read_with(cx, |project, _| project.bookmark_store().clone());
+        let bookmark = |path: &str, row, label: Option<&str>| SerializedBookmark {
+            abs_path: Path::new(path).into(),
+            row,
+            label: label.map(ToString::to_string),
+        };
+
+        // Bookmarks are restored without opening their files.
+        store.update(cx, |store, cx| {
+            store.start_restoring();
+            store.restore(
+                vec![
+                    bookmark(path!("/dir/a.txt"), 1, Some("two")),
+                    bookmark(path!("/dir/b.txt"), 0, None),
+                ],
+                cx,
+            )
+        });
+        assert!(!store.read_with(cx, |store, _| store.is_restoring()));
+        assert_eq!(
+            project.read_with(cx, |project, cx| project
+                .buffer_store()
+                .read(cx)
+                .buffers()
+                .count()),
+            0
+        );
+        assert_eq!(
+            store.read_with(cx, |store, _| store.all_bookmarks().count()),
+            0
+        );
+
+        // Opening a file anchors its bookmarks, which then follow the edits.
+        let buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer(path!("/dir/a.txt"), cx)
+            })
+            .await
+            .unwrap();
+        buffer.update(cx, |buffer, cx| buffer.edit([(0..0, "zero\n")], None, cx));
+        store.read_with(cx, |store, cx| {
+            let snapshot = buffer.read(cx).snapshot();
+            let bookmarks = store.bookmarks_for_buffer(snapshot.remote_id());
+            assert_eq!(bookmarks.len(), 1);
+            assert_eq!(bookmarks[0].row(&snapshot), 2);
+        });
+
+        // Closing the buffer keeps the bookmarks where they moved to, without keeping the buffer alive.
+        drop(buffer);
+        cx.run_until_parked();
+        assert_eq!(
+            store.read_with(cx, |store, cx| store.serialize(cx)),
+            [
+                bookmark(path!("/dir/a.txt"), 2, Some("two")),
+                bookmark(path!("/dir/b.txt"), 0, None),
+            ]
+        );
+
+        let buffers = store
+            .update(cx, |store, cx| store.open_closed_files(cx))
+            .await;
+        assert_eq!(buffers.len(), 2);
+        assert_eq!(
+            store.read_with(cx, |store, _| store.all_bookmarks().count()),
+            2
+        );
+    }
+}
@@ -76,6 +76,7 @@ use std::{
     sync::Arc,
     time::{Duration, Instant},
 };
+use task::{ProblemSeverity, TaskId, TaskProblem};
 use text::{Anchor, BufferId, LineEnding, OffsetRangeExt};
 use util::{
     debug_panic, defer, maybe, merge_json_value_into, paths::SanitizedPath, post_inc, ResultExt,
@@ -128,6 +129,13 @@ impl FormatTrigger {
     }
 }
 
+/// The diagnostics produced by the problem matchers of a task, stored as if they came from a language server
+/// of their own, so that they're replaced independently of the other diagnostics of the same files.
+struct TaskDiagnostics {
+    server_id: LanguageServerId,
+    abs_paths: HashSet<PathBuf>,
+}
+
 pub struct LocalLspStore {
     worktree_store: Entity<WorktreeStore>,
     toolchain_store: Entity<ToolchainStore>,
@@ -161,6 +169,7 @@ pub struct LocalLspStore {
         >,
     >,
     buffer_snapshots: HashMap<BufferId, HashMap<LanguageServerId, Vec<LspBufferSnapshot>>>, // buffer_id -> server_id -> vec of snapshots
+    task_diagnostics: HashMap<TaskId, TaskDiagnostics>,
     _subscription: gpui::Subscription,
     registered_buffers: HashMap<BufferId, usize>,
 }
@@ -1377,7 +1386,7 @@ impl LocalLspStore {
                 }
             }
 
-            buffer.handle.update(&mut cx, |b, cx| {
+            let format_transaction_id = buffer.handle.update(&mut cx, |b, cx| {
                 // If the buffer had its whitespace formatted and was edited while the language-specific
                 // formatting was being computed, avoid applying the language-specific formatting, because
                 // it can't be grouped with the whitespace formatting in the undo history.
@@ -1391,6 +1400,7 @@ impl LocalLspStore {
 
                 // Apply any language-specific formatting, and group the two formatting operations
                 // in the buffer's undo history.
+                let has_format_operations = !format_operations.is_empty();
                 for operation in format_operations {
                     match operation {
                         FormatOperation::Lsp(edits) => {
@@ -1411,6 +1421,39 @@ impl LocalLspStore {
                     }
                 }
 
+                whitespace_transaction_id
+                    .or_else(|| project_transaction.0.get(&buffer.handle).map(|t| t.id))
+                    .or_else(|| {
+                        has_format_operations
+                            .then(|| b.peek_undo_stack().map(|entry| entry.transaction_id()))
+                            .flatten()
+                    })
+                    .filter(|transaction_id| {
+                        b.peek_undo_stack()
+                            .map_or(false, |entry| entry.transaction_id() == *transaction_id)
+                    })
+            })?;
+
+            // Then format any code of other languages embedded in the buffer, grouping the
+            // edits with the ones made by the buffer's own formatter.
+            let format_injections = settings.format_injections
+                && ranges.is_none()
+                && !(trigger == FormatTrigger::Save
+                    && settings.format_on_save == FormatOnSave::Off);
+            if format_injections {
+                match Self::format_injections(&lsp_store, buffer, &mut cx).await {
+                    Ok(Some(diff)) => buffer.handle.update(&mut cx, |b, cx| {
+                        b.apply_diff(diff, cx);
+                        if let Some(transaction_id) = format_transaction_id {
+                            b.group_until_transaction(transaction_id);
+                        }
+                    })?,
+                    Ok(None) => {}
+                    Err(error) => log::error!("failed to format injected code: {error:#}"),
+                }
+            }
+
+            buffer.handle.update(&mut cx, |b, cx| {
                 if let Some(transaction) = b.finalize_last_transaction().cloned() {
                     if !push_to_history {
                         b.forget_transaction(transaction.id);
@@ -1719,6 +1762,230 @@ impl LocalLspStore {
         ))
     }
 
+    /// Formats the code embedded in the buffer in other languages, like code blocks in Markdown,
+    /// using the formatters configured for those languages.
+    async fn format_injections(
+        lsp_store: &WeakEntity<LspStore>,
+        buffer: &FormattableBuffer,
+        cx: &mut AsyncApp,
+    ) -> Result<Option<Diff>> {
+        let snapshot = buffer.handle.read_with(cx, |buffer, _| buffer.snapshot())?;
+
+        let mut new_text = String::new();
+        let mut last_end = 0;
+        for (range, language) in snapshot.injection_ranges() {
+            let text = snapshot.text_for_range(range.clone()).collect::<String>();
+            let starts_line = snapshot.offset_to_point(range.start).column == 0;
+            let Some(code) = InjectedCode::new(&text, starts_line) else {
+                continue;
+            };
+
+            let formatted =
+                match Self::format_injection(lsp_store, buffer, &code.body, &language, cx).await {
+                    Ok(formatted) if formatted != code.body => formatted,
+                    Ok(_) => continue,
+                    Err(error) => {
+                        log::warn!("failed to format {} code: {error:#}", language.name());
+                        continue;
+                    }
+                };
+
+            new_text.extend(snapshot.text_for_range(last_end..range.start));
+            new_text.push_str(&code.splice(&formatted));
+            last_end = range.end;
+        }
+        if last_end == 0 {
+            return Ok(None);
+        }
+        new_text.extend(snapshot.text_for_range(last_end..snapshot.len()));
+
+        let diff = buffer.handle.update(cx, |buffer, cx| {
+            // The formatted text is based on the snapshot, so discard it if the buffer has
+            // been edited since.
+            (buffer.version() == *snapshot.version()).then(|| buffer.diff(new_text, cx))
+        })?;
+        Ok(match diff {
+            Some(diff) => Some(diff.await),
+            None => None,
+        })
+    }
+
+    /// Formats a piece of code with the formatters configured for its language, in the
+    /// context of the buffer it's embedded in.
+    async fn format_injection(
+        lsp_store: &WeakEntity<LspStore>,
+        buffer: &FormattableBuffer,
+        text: &str,
+        language: &Arc<Language>,
+        cx: &mut AsyncApp,
+    ) -> Result<String> {
+        let settings = buffer.handle.read_with(cx, |buffer, cx| {
+            language_settings(Some(language.name()), buffer.file(), cx).into_owned()
+        })?;
+        let formatters = match &settings.formatter {
+            SelectedFormatter::Auto if settings.prettier.allowed => vec![Formatter::Prettier],
+            SelectedFormatter::Auto => vec![Formatter::LanguageServer { name: None }],
+            SelectedFormatter::List(formatters) => formatters.as_ref().to_vec(),
+        };
+
+        let abs_path = buffer
+            .abs_path
+            .as_deref()
+            .map(|abs_path| injection_abs_path(abs_path, language));
+        let injection = cx.new(|cx| Buffer::local(text, cx).with_language(language.clone(), cx))?;
+        for formatter in &formatters {
+            match formatter {
+                Formatter::LanguageServer { name } => {
+                    Self::format_injection_via_lsp(
+                        lsp_store,
+                        buffer,
+                        &injection,
+                        abs_path.as_deref(),
+                        language,
+                        name.as_deref(),
+                        &settings,
+                        cx,
+                    )
+                    .await
+                    .context("failed to format via language server")?;
+                }
+                Formatter::Prettier => {
+                    let prettier = lsp_store.update(cx, |lsp_store, _cx| {
+                        lsp_store.prettier_store().unwrap().downgrade()
+                    })?;
+                    let diff = prettier_store::format_text_with_prettier(
+                        &prettier,
+                        &buffer.handle,
+                        &injection,
+                        abs_path.clone(),
+                        cx,
+                    )
+                    .await
+                    .transpose()?;
+                    if let Some(diff) = diff {
+                        injection.update(cx, |injection, cx| injection.apply_diff(diff, cx))?;
+                    }
+                }
+                Formatter::External { command, arguments } => {
+                    let formattable = FormattableBuffer {
+                        id: injection.read_with(cx, |injection, _| injection.remote_id())?,
+                        handle: injection.clone(),
+                        abs_path: abs_path.clone(),
+                        env: buffer.env.clone(),
+                    };
+                    let diff = Self::format_via_external_command(
+                        &formattable,
+                        command,
+                        arguments.as_deref(),
+                        cx,
+                    )
+                    .await
+                    .context(format!(
+                        "failed to format via external command {:?}",
+                        command
+                    ))?;
+                    if let Some(diff) = diff {
+                        injection.update(cx, |injection, cx| injection.apply_diff(diff, cx))?;
+                    }
+                }
+                // Code actions can only be requested for documents that are open in a
+                // language server.
+                Formatter::CodeActions(_) => {}
+            }
+        }
+
+        injection.read_with(cx, |injection, _| injection.text())
+    }
+
+    /// Formats embedded code by opening it in a language server for its language as a
+    /// document of its own, next to the buffer that contains it.
+    #[allow(clippy::too_many_arguments)]
+    async fn format_injection_via_lsp(
+        lsp_store: &WeakEntity<LspStore>,
+        buffer: &FormattableBuffer,
+        injection: &Entity<Buffer>,
+        abs_path: Option<&Path>,
+        language: &Arc<Language>,
+        name: Option<&str>,
+        settings: &LanguageSettings,
+        cx: &mut AsyncApp,
+    ) -> Result<()> {
+        let Some(abs_path) = abs_path else {
+            return Ok(());
+        };
+        let adapter_and_server = lsp_store.update(cx, |lsp_store, cx| {
+            let local = lsp_store.as_local()?;
+            let worktree_id = File::from_dyn(buffer.handle.read(cx).file())?.worktree_id(cx);
+            local
+                .languages
+                .lsp_adapters(&language.name())
+                .into_iter()
+                .filter(|adapter| name.map_or(true, |name| adapter.name.0.as_ref() == name))
+                .find_map(|adapter| {
+                    let server_id = local
+                        .language_server_ids
+                        .get(&(worktree_id, adapter.name.clone()))?;
+                    match local.language_servers.get(server_id)? {
+                        LanguageServerState::Running { server, .. } => {
+                            Some((adapter.clone(), server.clone()))
+                        }
+                        _ => None,
+                    }
+                })
+        })?;
+        let Some((adapter, language_server)) = adapter_and_server else {
+            return Ok(());
+        };
+        let capabilities = &language_server.capabilities();
+        let formatting_provider = capabilities.document_formatting_provider.as_ref();
+        if !matches!(formatting_provider, Some(p) if *p != OneOf::Left(false)) {
+            return Ok(());
+        }
+
+        let uri = lsp::Url::from_file_path(abs_path)
+            .map_err(|_| anyhow!("failed to convert abs path to uri"))?;
+        let text = injection.read_with(cx, |injection, _| injection.text())?;
+        language_server.notify::<lsp::notification::DidOpenTextDocument>(
+            &lsp::DidOpenTextDocumentParams {
+                text_document: lsp::TextDocumentItem::new(
+                    uri.clone(),
+                    adapter.language_id(&language.name()),
+                    0,
+                    text,
+                ),
+            },
+        )?;
+        let lsp_edits = language_server
+            .request::<lsp::request::Formatting>(lsp::DocumentFormattingParams {
+                text_document: lsp::TextDocumentIdentifier::new(uri.clone()),
+                options: lsp_command::lsp_formatting_options(settings),
+                work_done_progress_params: Default::default(),
+            })
+            .await;
+        language_server
+            .notify::<lsp::notification::DidCloseTextDocument>(&lsp::DidCloseTextDocumentParams {
+                text_document: lsp::TextDocumentIdentifier::new(uri),
+            })
+            .log_err();
+
+        let lsp_edits = lsp_edits?.unwrap_or_default();
+        injection.update(cx, |injection, cx| {
+            let snapshot = injection.snapshot();
+            let mut edits = lsp_edits
+                .into_iter()
+                .map(|edit| {
+                    let range = range_from_lsp(edit.range);
+                    let start = snapshot.clip_point_utf16(range.start, Bias::Left);
+                    let end = snapshot.clip_point_utf16(range.end, Bias::Left);
+                    (start..end, edit.new_text)
+                })
+                .collect::<Vec<_>>();
+            edits.sort_by_key(|(range, _)| range.start);
+            injection.edit(edits, None, cx);
+        })?;
+        Ok(())
+    }
+
     async fn try_resolve_code_action(
         lang_server: &LanguageServer,
         action: &mut CodeAction,
@@ -2994,6 +3261,7 @@ impl LspStore {
                 yarn,
                 next_diagnostic_group_id: Default::default(),
                 diagnostics: Default::default(),
+                task_diagnostics: Default::default(),
                 _subscription: cx.on_app_quit(|this, cx| {
                     this.as_local_mut().unwrap().shutdown_language_servers(cx)
                 }),
@@ -5413,6 +5681,78 @@ impl LspStore {
         Ok(())
     }
 
+    /// Replaces the diagnostics found in the output of the given task with the new problems,
+    /// clearing them if there are none.
+    pub fn update_task_diagnostics(
+        &mut self,
+        task_id: &TaskId,
+        problems: Vec<TaskProblem>,
+        cx: &mut Context<Self>,
+    ) -> Result<()> {
+        let local = self
+            .as_local_mut()
+            .context("called update_task_diagnostics on remote")?;
+        if problems.is_empty() && !local.task_diagnostics.contains_key(task_id) {
+            return Ok(());
+        }
+
+        let (server_id, old_abs_paths) = match local.task_diagnostics.remove(task_id) {
+            Some(task_diagnostics) => (task_diagnostics.server_id, task_diagnostics.abs_paths),
+            None => (
+                local.languages.next_language_server_id(),
+                HashSet::default(),
+            ),
+        };
+        let mut diagnostics_by_path = old_abs_paths
+            .into_iter()
+            .map(|abs_path| (abs_path, Vec::new()))
+            .collect::<HashMap<_, _>>();
+        for problem in problems {
+            let group_id = post_inc(&mut local.next_diagnostic_group_id);
+            let start = PointUtf16::new(problem.range.start.row, problem.range.start.column);
+            let end = PointUtf16::new(problem.range.end.row, problem.range.end.column);
+            diagnostics_by_path
+                .entry(problem.path)
+                .or_default()
+                .push(DiagnosticEntry {
+                    range: Unclipped(start)..Unclipped(end),
+                    diagnostic: Diagnostic {
+                        source: Some(problem.source),
+                        code: problem.code,
+                        severity: match problem.severity {
+                            ProblemSeverity::Error => DiagnosticSeverity::ERROR,
+                            ProblemSeverity::Warning => DiagnosticSeverity::WARNING,
+                            ProblemSeverity::Info => DiagnosticSeverity::INFORMATION,
+                            ProblemSeverity::Hint => DiagnosticSeverity::HINT,
+                        },
+                        message: problem.message,
+                        group_id,
+                        is_primary: true,
+                        // The problems were found by a task, in the files on disk.
+                        is_disk_based: true,
+                        ..Diagnostic::default()
+                    },
+                });
+        }
+
+        let abs_paths = diagnostics_by_path
+            .iter()
+            .filter(|(_, diagnostics)| !diagnostics.is_empty())
+            .map(|(abs_path, _)| abs_path.clone())
+            .collect();
+        local.task_diagnostics.insert(
+            task_id.clone(),
+            TaskDiagnostics {
+                server_id,
+                abs_paths,
+            },
+        );
+        for (abs_path, diagnostics) in diagnostics_by_path {
+            self.update_diagnostic_entries(server_id, abs_path, None, diagnostics, cx)?;
+        }
+        Ok(())
+    }
+
     fn update_worktree_diagnostics(
         &mut self,
         worktree_id: WorktreeId,
@@ -8857,6 +9197,120 @@ fn ensure_uniform_list_compatible_label(label: &mut CodeLabel) {
     label.text = new_text;
 }
 
+/// Returns the path that formatters are given for code embedded in the file at `abs_path`,
+/// which has the extension of the embedded language so that they can recognize it.
+fn injection_abs_path(abs_path: &Path, language: &Language) -> PathBuf {
+    let mut file_name = abs_path.file_name().unwrap_or_default().to_os_string();
+    if let Some(suffix) = language.path_suffixes().first() {
+        file_name.push(".");
+        file_name.push(suffix);
+    }
+    abs_path.with_file_name(file_name)
+}
+
+/// Code embedded in a buffer of another language, split into the part that's formatted and
+/// the surrounding whitespace and indentation, which are kept as they were.
+struct InjectedCode<'a> {
+    prefix: &'a str,
+    body: String,
+    suffix: &'a str,
+    indent: String,
+    indent_first_line: bool,
+}
+
+impl<'a> InjectedCode<'a> {
+    fn new(text: &'a str, starts_line: bool) -> Option<Self> {
+        let body_start = text.len() - text.trim_start().len();
+        let body_end = text.trim_end().len();
+        if body_start >= body_end {
+            return None;
+        }
+
+        // Blank lines before the code are kept, but the indentation of its first line is
+        // treated like the indentation of the following lines.
+        let line_start = text[..body_start].rfind('\n').map(|ix| ix + 1);
+        let indent_first_line = starts_line || line_start.is_some();
+        let prefix_end = if indent_first_line {
+            line_start.unwrap_or(0)
+        } else {
+            body_start
+        };
+        let lines = text[prefix_end..body_end].lines().collect::<Vec<_>>();
+
+        let mut indent: Option<&str> = None;
+        for line in lines.iter().skip(if indent_first_line { 0 } else { 1 }) {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let line_indent = &line[..line.len() - line.trim_start().len()];
+            indent = Some(match indent {
+                Some(indent) => {
+                    let common_len = indent
+                        .char_indices()
+                        .zip(line_indent.chars())
+                        .take_while(|((_, a), b)| a == b)
+                        .last()
+                        .map_or(0, |((ix, c), _)| ix + c.len_utf8());
+                    &indent[..common_len]
+                }
+                None => line_indent,
+            });
+        }
+        let indent = indent.unwrap_or_default();
+
+        let body = lines
+            .iter()
+            .map(|line| line.strip_prefix(indent).unwrap_or(line.trim_start()))
+            .collect::<Vec<_>>()
+            .join("\n");
+        Some(Self {
+            prefix: &text[..prefix_end],
+            body,
+            suffix: &text[body_end..],
+            indent: indent.to_string(),
+            indent_first_line,
+        })
+    }
+
+    /// Returns the original text with the code replaced by its formatted version.
+    fn splice(&self, formatted: &str) -> String {
+        let mut text = self.prefix.to_string();
+        for (ix, line) in formatted.trim_matches('\n').trim_end().lines().enumerate() {
+            if ix > 0 {
+                text.push('\n');
+            }
+            if !line.is_empty() && (ix > 0 || self.indent_first_line) {
+                text.push_str(&self.indent);
+            }
+            text.push_str(line);
+        }
+        text.push_str(self.suffix);
+        text
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_injected_code() {
+    let text = "\n    fn main() {\n        foo();\n\n    }\n  ";
+    let code = InjectedCode::new(text, false).unwrap();
+    assert_eq!(code.body, "fn main() {\n    foo();\n\n}");
+    assert_eq!(
+        code.splice("fn main() {\n    foo();\n}\n"),
+        "\n    fn main() {\n        foo();\n    }\n  "
+    );
+
+    let text = "  SELECT *\n    FROM t";
+    let code = InjectedCode::new(text, false).unwrap();
+    assert_eq!(code.body, "SELECT *\nFROM t");
+    assert_eq!(
+        code.splice("SELECT\n  *\nFROM\n  t\n"),
+        "  SELECT\n      *\n    FROM\n      t"
+    );
+
+    assert!(InjectedCode::new(" \n ", true).is_none());
+}
+
 #[cfg(test)]
 #[test]
 fn test_glob_literal_prefix() {
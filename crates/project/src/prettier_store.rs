@@ -15,7 +15,7 @@ use futures::{
 use gpui::{AsyncApp, Context, Entity, EventEmitter, Task, WeakEntity};
 use language::{
     language_settings::{Formatter, LanguageSettings, SelectedFormatter},
-    Buffer, LanguageRegistry, LocalFile,
+    Buffer, Diff, LanguageRegistry, LocalFile,
 };
 use lsp::{LanguageServer, LanguageServerId, LanguageServerName};
 use node_runtime::NodeRuntime;
@@ -709,16 +709,37 @@ pub(super) async fn format_with_prettier(
     buffer: &Entity<Buffer>,
     cx: &mut AsyncApp,
 ) -> Option<Result<crate::lsp_store::FormatOperation>> {
+    let buffer_path = buffer
+        .update(cx, |buffer, cx| {
+            File::from_dyn(buffer.file()).map(|file| file.abs_path(cx))
+        })
+        .ok()
+        .flatten();
+
+    format_text_with_prettier(prettier_store, buffer, buffer, buffer_path, cx)
+        .await
+        .map(|result| result.map(crate::lsp_store::FormatOperation::Prettier))
+}
+
+/// Formats the text of `buffer` with the prettier instance that would format `host_buffer`.
+/// The two are different when formatting code that's embedded in the host buffer.
+pub(super) async fn format_text_with_prettier(
+    prettier_store: &WeakEntity<PrettierStore>,
+    host_buffer: &Entity<Buffer>,
+    buffer: &Entity<Buffer>,
+    buffer_path: Option<PathBuf>,
+    cx: &mut AsyncApp,
+) -> Option<Result<Diff>> {
     let prettier_instance = prettier_store
         .update(cx, |prettier_store, cx| {
-            prettier_store.prettier_instance_for_buffer(buffer, cx)
+            prettier_store.prettier_instance_for_buffer(host_buffer, cx)
         })
         .ok()?
         .await;
 
     let ignore_dir = prettier_store
         .update(cx, |prettier_store, cx| {
-            prettier_store.prettier_ignore_for_buffer(buffer, cx)
+            prettier_store.prettier_ignore_for_buffer(host_buffer, cx)
         })
         .ok()?
         .await;
@@ -732,17 +753,9 @@ pub(super) async fn format_with_prettier(
 
     match prettier_task.await {
         Ok(prettier) => {
-            let buffer_path = buffer
-                .update(cx, |buffer, cx| {
-                    File::from_dyn(buffer.file()).map(|file| file.abs_path(cx))
-                })
-                .ok()
-                .flatten();
-
             let format_result = prettier
                 .format(buffer, buffer_path, ignore_dir, cx)
                 .await
-                .map(crate::lsp_store::FormatOperation::Prettier)
                 .with_context(|| format!("{} failed to format buffer", prettier_description));
 
             Some(format_result)
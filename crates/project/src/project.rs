@@ -1,3 +1,4 @@
+pub mod bookmark_store;
 pub mod buffer_store;
 mod color_extractor;
 pub mod connection_manager;
@@ -28,6 +29,7 @@ mod yarn;
 
 use crate::git::GitState;
 use anyhow::{anyhow, Context as _, Result};
+use bookmark_store::BookmarkStore;
 use buffer_store::{BufferChangeSet, BufferStore, BufferStoreEvent};
 use client::{
     proto, Client, Collaborator, PendingEntitySubscription, ProjectId, TypedEnvelope, UserStore,
@@ -163,6 +165,7 @@ pub struct Project {
     worktree_store: Entity<WorktreeStore>,
     buffer_store: Entity<BufferStore>,
     image_store: Entity<ImageStore>,
+    bookmark_store: Entity<BookmarkStore>,
     lsp_store: Entity<LspStore>,
     _subscriptions: Vec<gpui::Subscription>,
     buffers_needing_diff: HashSet<WeakEntity<Buffer>>,
@@ -704,12 +707,16 @@ impl Project {
 
             cx.subscribe(&lsp_store, Self::on_lsp_store_event).detach();
 
+            let bookmark_store =
+                cx.new(|cx| BookmarkStore::new(buffer_store.clone(), worktree_store.clone(), cx));
+
             Self {
                 buffer_ordered_messages_tx: tx,
                 collaborators: Default::default(),
                 worktree_store,
                 buffer_store,
                 image_store,
+                bookmark_store,
                 lsp_store,
                 join_project_response_message_id: 0,
                 client_state: ProjectClientState::Local,
@@ -729,6 +736,7 @@ impl Project {
                 git_diff_debouncer: DebouncedDelay::new(),
                 terminals: Terminals {
                     local_handles: Vec::new(),
+                    task_problem_terminals: HashMap::default(),
                 },
                 node: Some(node),
                 search_history: Self::new_search_history(),
@@ -832,12 +840,16 @@ impl Project {
             cx.subscribe(&ssh, Self::on_ssh_event).detach();
             cx.observe(&ssh, |_, _, cx| cx.notify()).detach();
 
+            let bookmark_store =
+                cx.new(|cx| BookmarkStore::new(buffer_store.clone(), worktree_store.clone(), cx));
+
             let this = Self {
                 buffer_ordered_messages_tx: tx,
                 collaborators: Default::default(),
                 worktree_store,
                 buffer_store,
                 image_store,
+                bookmark_store,
                 lsp_store,
                 join_project_response_message_id: 0,
                 client_state: ProjectClientState::Local,
@@ -872,6 +884,7 @@ impl Project {
                 git_diff_debouncer: DebouncedDelay::new(),
                 terminals: Terminals {
                     local_handles: Vec::new(),
+                    task_problem_terminals: HashMap::default(),
                 },
                 node: Some(node),
                 search_history: Self::new_search_history(),
@@ -1059,10 +1072,14 @@ impl Project {
             cx.subscribe(&settings_observer, Self::on_settings_observer_event)
                 .detach();
 
+            let bookmark_store =
+                cx.new(|cx| BookmarkStore::new(buffer_store.clone(), worktree_store.clone(), cx));
+
             let mut this = Self {
                 buffer_ordered_messages_tx: tx,
                 buffer_store: buffer_store.clone(),
                 image_store,
+                bookmark_store,
                 worktree_store: worktree_store.clone(),
                 lsp_store: lsp_store.clone(),
                 active_entry: None,
@@ -1089,6 +1106,7 @@ impl Project {
                 git_diff_debouncer: DebouncedDelay::new(),
                 terminals: Terminals {
                     local_handles: Vec::new(),
+                    task_problem_terminals: HashMap::default(),
                 },
                 node: None,
                 search_history: Self::new_search_history(),
@@ -2395,6 +2413,21 @@ impl Project {
             }
         }
         cx.observe(worktree, |_, _, cx| cx.notify()).detach();
+        if self.is_local() {
+            let snippets_dir = worktree
+                .read(cx)
+                .abs_path()
+                .join(paths::local_snippets_folder_relative_path());
+            self.snippets.update(cx, |snippets, cx| {
+                snippets.watch_directory(&snippets_dir, cx)
+            });
+            cx.observe_release(worktree, move |project, _, cx| {
+                project.snippets.update(cx, |snippets, cx| {
+                    snippets.unwatch_directory(&snippets_dir, cx)
+                });
+            })
+            .detach();
+        }
         cx.notify();
     }
 
@@ -4295,6 +4328,10 @@ impl Project {
         &self.buffer_store
     }
 
+    pub fn bookmark_store(&self) -> &Entity<BookmarkStore> {
+        &self.bookmark_store
+    }
+
     pub fn git_state(&self) -> &Entity<GitState> {
         &self.git_state
     }
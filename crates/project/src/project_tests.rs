@@ -22,7 +22,7 @@ use std::os;
 use std::{str::FromStr, sync::OnceLock};
 
 use std::{mem, num::NonZeroU32, ops::Range, task::Poll};
-use task::{ResolvedTask, TaskContext};
+use task::{ProblemPosition, ProblemSeverity, ResolvedTask, TaskContext, TaskId, TaskProblem};
 use unindent::Unindent as _;
 use util::{
     assert_set_eq, path, paths::PathMatcher, separator, test::TempTree, uri, TryFutureExt as _,
@@ -2184,6 +2184,94 @@ async fn test_diagnostics_from_multiple_language_servers(cx: &mut gpui::TestAppC
     });
 }
 
+#[gpui::test]
+async fn test_task_diagnostics(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({ "a.rs": "one two three", "b.rs": "four five" }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+    let lsp_store = project.read_with(cx, |project, _| project.lsp_store.clone());
+    let task_id = TaskId("build".to_string());
+    let problem = |path: &str, column: u32, severity: ProblemSeverity| TaskProblem {
+        path: Path::new(path).to_owned(),
+        range: ProblemPosition { row: 0, column }..ProblemPosition { row: 0, column },
+        severity,
+        message: format!("problem in {path}"),
+        code: None,
+        source: "task".to_string(),
+    };
+
+    lsp_store.update(cx, |lsp_store, cx| {
+        lsp_store
+            .update_diagnostic_entries(
+                LanguageServerId(0),
+                Path::new("/dir/a.rs").to_owned(),
+                None,
+                vec![DiagnosticEntry {
+                    range: Unclipped(PointUtf16::new(0, 0))..Unclipped(PointUtf16::new(0, 3)),
+                    diagnostic: Diagnostic {
+                        severity: DiagnosticSeverity::ERROR,
+                        is_primary: true,
+                        message: "syntax error".to_string(),
+                        ..Default::default()
+                    },
+                }],
+                cx,
+            )
+            .unwrap();
+        lsp_store
+            .update_task_diagnostics(
+                &task_id,
+                vec![
+                    problem("/dir/a.rs", 4, ProblemSeverity::Error),
+                    problem("/dir/b.rs", 0, ProblemSeverity::Warning),
+                ],
+                cx,
+            )
+            .unwrap();
+        assert_eq!(
+            lsp_store.diagnostic_summary(false, cx),
+            DiagnosticSummary {
+                error_count: 2,
+                warning_count: 1,
+            }
+        );
+
+        // Rerunning the task replaces its diagnostics, leaving the language server's ones alone.
+        lsp_store
+            .update_task_diagnostics(
+                &task_id,
+                vec![problem("/dir/b.rs", 5, ProblemSeverity::Error)],
+                cx,
+            )
+            .unwrap();
+        assert_eq!(
+            lsp_store.diagnostic_summary(false, cx),
+            DiagnosticSummary {
+                error_count: 2,
+                warning_count: 0,
+            }
+        );
+
+        lsp_store
+            .update_task_diagnostics(&task_id, Vec::new(), cx)
+            .unwrap();
+        assert_eq!(
+            lsp_store.diagnostic_summary(false, cx),
+            DiagnosticSummary {
+                error_count: 1,
+                warning_count: 0,
+            }
+        );
+    });
+}
+
 #[gpui::test]
 async fn test_edits_from_lsp2_with_past_version(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -4354,6 +4442,47 @@ async fn test_search(cx: &mut gpui::TestAppContext) {
     );
 }
 
+#[gpui::test]
+async fn test_search_structural(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        path!("/dir"),
+        json!({
+            "one.rs": "fn one() { a.unwrap(); }",
+            "two.rs": "fn two() { b.unwrap_or(c); }",
+            "three.rs": "fn three() { f(x).unwrap() }",
+            "four.txt": "a.unwrap()",
+        }),
+    )
+    .await;
+    let project = Project::test(fs.clone(), [path!("/dir").as_ref()], cx).await;
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+
+    assert_eq!(
+        search(
+            &project,
+            SearchQuery::structural(
+                "$X.unwrap()",
+                false,
+                Default::default(),
+                Default::default(),
+                None
+            )
+            .unwrap(),
+            cx
+        )
+        .await
+        .unwrap(),
+        HashMap::from_iter([
+            (separator!("dir/one.rs").to_string(), vec![11..21]),
+            (separator!("dir/three.rs").to_string(), vec![13..26])
+        ])
+    );
+}
+
 #[gpui::test]
 async fn test_search_with_inclusions(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -27,6 +27,7 @@ use crate::worktree_store::WorktreeStore;
 #[derive(Debug, Default)]
 pub struct Inventory {
     last_scheduled_tasks: VecDeque<(TaskSourceKind, ResolvedTask)>,
+    last_input_values: HashMap<String, String>,
     templates_from_settings: ParsedTemplates,
 }
 
@@ -182,11 +183,11 @@ impl Inventory {
         let new_resolved_tasks = worktree_tasks
             .filter_map(|(kind, task)| {
                 let id_base = kind.to_id_base();
-                Some((
-                    kind,
-                    task.resolve_task(&id_base, task_context)?,
-                    not_used_score,
-                ))
+                let mut resolved_task = task.resolve_task(&id_base, task_context)?;
+                // A task with unresolved dependencies is still listed, and reports why when spawned.
+                self.resolve_task_dependencies(&mut resolved_task, worktree, task_context)
+                    .log_err();
+                Some((kind, resolved_task, not_used_score))
             })
             .filter(|(_, resolved_task, _)| {
                 match task_labels_to_ids.entry(resolved_task.resolved_label.clone()) {
@@ -207,6 +208,25 @@ impl Inventory {
         (previously_spawned_tasks, new_resolved_tasks)
     }
 
+    /// Resolves the tasks that the resolved task given depends on, with the same [`TaskContext`].
+    /// Dependencies are looked up by their labels among the tasks from the worktree's and global task files.
+    pub fn resolve_task_dependencies(
+        &self,
+        resolved_task: &mut ResolvedTask,
+        worktree: Option<WorktreeId>,
+        task_context: &TaskContext,
+    ) -> Result<()> {
+        if resolved_task.original_task().depends_on.is_empty() {
+            return Ok(());
+        }
+        let templates = self
+            .worktree_templates_from_settings(worktree)
+            .chain(self.global_templates_from_settings())
+            .map(|(kind, template)| (kind.to_id_base(), template))
+            .collect::<Vec<_>>();
+        resolved_task.resolve_dependencies(&templates, task_context)
+    }
+
     /// Returns the last scheduled task by task_id if provided.
     /// Otherwise, returns the last scheduled task.
     pub fn last_scheduled_task(
@@ -236,6 +256,20 @@ impl Inventory {
         }
     }
 
+    /// Returns the value last entered for the task input with the id given.
+    pub fn last_input_value(&self, input_id: &str) -> Option<&str> {
+        self.last_input_values.get(input_id).map(String::as_str)
+    }
+
+    /// Remembers the values entered for task inputs, to suggest them the next time the inputs are asked for.
+    pub fn input_values_entered(&mut self, input_values: &HashMap<String, String>) {
+        self.last_input_values.extend(
+            input_values
+                .iter()
+                .map(|(id, value)| (id.clone(), value.clone())),
+        );
+    }
+
     /// Deletes a resolved task from history, using its id.
     /// A similar may still resurface in `used_and_current_resolved_tasks` when its [`TaskTemplate`] is resolved again.
     pub fn delete_previously_used(&mut self, id: &TaskId) {
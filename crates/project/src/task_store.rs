@@ -356,6 +356,7 @@ fn local_task_context_for_location(
             project_env: project_env.unwrap_or_default(),
             cwd: worktree_abs_path.map(|p| p.to_path_buf()),
             task_variables,
+            input_values: HashMap::default(),
         })
     })
 }
@@ -419,6 +420,7 @@ fn remote_task_context_for_location(
                 )
                 .collect(),
             project_env: task_context.project_env.into_iter().collect(),
+            input_values: HashMap::default(),
         })
     })
 }
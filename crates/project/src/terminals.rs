@@ -1,7 +1,7 @@
 use crate::Project;
 use anyhow::{Context as _, Result};
 use collections::HashMap;
-use gpui::{AnyWindowHandle, App, AppContext as _, Context, Entity, Task, WeakEntity};
+use gpui::{AnyWindowHandle, App, AppContext as _, Context, Entity, EntityId, Task, WeakEntity};
 use itertools::Itertools;
 use language::LanguageName;
 use settings::{Settings, SettingsLocation};
@@ -13,10 +13,10 @@ use std::{
     path::{Path, PathBuf},
     sync::Arc,
 };
-use task::{Shell, ShellBuilder, SpawnInTerminal};
+use task::{ProblemMatcher, Shell, ShellBuilder, SpawnInTerminal, TaskId};
 use terminal::{
     terminal_settings::{self, TerminalSettings, VenvSettings},
-    TaskState, TaskStatus, Terminal, TerminalBuilder,
+    BackgroundTaskState, BackgroundTaskStatus, TaskState, TaskStatus, Terminal, TerminalBuilder,
 };
 use util::ResultExt;
 
@@ -25,6 +25,8 @@ use util::ResultExt;
 
 pub struct Terminals {
     pub(crate) local_handles: Vec<WeakEntity<terminal::Terminal>>,
+    /// The terminal of the latest run of each task with problem matchers, which owns the task's diagnostics.
+    pub(crate) task_problem_terminals: HashMap<TaskId, EntityId>,
 }
 
 /// Terminals are opened either for the users shell, or to run a task.
@@ -83,6 +85,17 @@ impl Project {
         kind: TerminalKind,
         window: AnyWindowHandle,
         cx: &mut Context<Self>,
+    ) -> Task<Result<Entity<Terminal>>> {
+        self.create_terminal_with_scrollback(kind, None, window, cx)
+    }
+
+    /// Creates a terminal that starts with the scrollback of a previous session, as returned by [`Terminal::scrollback_text`].
+    pub fn create_terminal_with_scrollback(
+        &mut self,
+        kind: TerminalKind,
+        restored_scrollback: Option<String>,
+        window: AnyWindowHandle,
+        cx: &mut Context<Self>,
     ) -> Task<Result<Entity<Terminal>>> {
         let path: Option<Arc<Path>> = match &kind {
             TerminalKind::Shell(path) => path.as_ref().map(|path| Arc::from(path.as_ref())),
@@ -117,7 +130,13 @@ impl Project {
                 None
             };
             project.update(&mut cx, |project, cx| {
-                project.create_terminal_with_venv(kind, python_venv_directory, window, cx)
+                project.create_terminal_with_venv(
+                    kind,
+                    python_venv_directory,
+                    restored_scrollback,
+                    window,
+                    cx,
+                )
             })?
         })
     }
@@ -183,6 +202,7 @@ impl Project {
         &mut self,
         kind: TerminalKind,
         python_venv_directory: Option<PathBuf>,
+        restored_scrollback: Option<String>,
         window: AnyWindowHandle,
         cx: &mut Context<Self>,
     ) -> Result<Entity<Terminal>> {
@@ -229,6 +249,7 @@ impl Project {
         };
 
         let mut python_venv_activate_command = None;
+        let mut task_problem_matchers = None;
 
         let (spawn_task, shell) = match kind {
             TerminalKind::Shell(_) => {
@@ -264,6 +285,8 @@ impl Project {
                 }
             }
             TerminalKind::Task(spawn_task) => {
+                task_problem_matchers =
+                    Some((spawn_task.id.clone(), spawn_task.problem_matchers.clone()));
                 let task_state = Some(TaskState {
                     id: spawn_task.id,
                     full_label: spawn_task.full_label,
@@ -273,6 +296,12 @@ impl Project {
                     status: TaskStatus::Running,
                     show_summary: spawn_task.show_summary,
                     show_command: spawn_task.show_command,
+                    background: spawn_task
+                        .background
+                        .as_ref()
+                        .map(|background| background.matcher())
+                        .transpose()?
+                        .map(BackgroundTaskState::new),
                     completion_rx,
                 });
 
@@ -324,6 +353,7 @@ impl Project {
                 }
             }
         };
+        let task_cwd = local_path.as_ref().map(|path| path.to_path_buf());
         TerminalBuilder::new(
             local_path.map(|path| path.to_path_buf()),
             python_venv_directory,
@@ -333,6 +363,7 @@ impl Project {
             settings.cursor_shape.unwrap_or_default(),
             settings.alternate_scroll,
             settings.max_scroll_history_lines,
+            restored_scrollback,
             ssh_details.is_some(),
             window,
             completion_tx,
@@ -359,13 +390,151 @@ impl Project {
             })
             .detach();
 
+            if terminal_handle
+                .read(cx)
+                .task()
+                .is_some_and(|task| task.background.is_some())
+            {
+                // Let the project observers know when the background task gets ready or starts rebuilding.
+                cx.subscribe(&terminal_handle, |_, _, event, cx| {
+                    if matches!(event, terminal::Event::BackgroundTaskStatusChanged) {
+                        cx.notify();
+                    }
+                })
+                .detach();
+            }
+
             if let Some(activate_command) = python_venv_activate_command {
                 this.activate_python_virtual_environment(activate_command, &terminal_handle, cx);
             }
+            if let Some((task_id, problem_matchers)) = task_problem_matchers {
+                this.find_task_problems(task_id, problem_matchers, task_cwd, &terminal_handle, cx);
+            }
             terminal_handle
         })
     }
 
+    /// Clears the problems found by the previous run of the task, and finds the ones in the output
+    /// of its new terminal once it finishes, or each time it gets ready for a background task.
+    fn find_task_problems(
+        &mut self,
+        task_id: TaskId,
+        problem_matchers: Vec<ProblemMatcher>,
+        cwd: Option<PathBuf>,
+        terminal: &Entity<Terminal>,
+        cx: &mut Context<Project>,
+    ) {
+        if !self.is_local() {
+            return;
+        }
+        self.lsp_store
+            .update(cx, |lsp_store, cx| {
+                lsp_store.update_task_diagnostics(&task_id, Vec::new(), cx)
+            })
+            .log_err();
+        if problem_matchers.is_empty() {
+            self.terminals.task_problem_terminals.remove(&task_id);
+            return;
+        }
+
+        self.terminals
+            .task_problem_terminals
+            .insert(task_id.clone(), terminal.entity_id());
+        let problem_matchers = Arc::<[ProblemMatcher]>::from(problem_matchers);
+        if terminal
+            .read(cx)
+            .task()
+            .is_some_and(|task| task.background.is_some())
+        {
+            let task_id = task_id.clone();
+            let problem_matchers = problem_matchers.clone();
+            let cwd = cwd.clone();
+            cx.subscribe(terminal, move |project, terminal, event, cx| {
+                let is_ready = terminal
+                    .read(cx)
+                    .task()
+                    .filter(|task| task.status == TaskStatus::Running)
+                    .and_then(|task| task.background.as_ref())
+                    .is_some_and(|background| background.status() == BackgroundTaskStatus::Ready);
+                if matches!(event, terminal::Event::BackgroundTaskStatusChanged) && is_ready {
+                    project.report_task_problems(
+                        task_id.clone(),
+                        problem_matchers.clone(),
+                        cwd.clone(),
+                        terminal.downgrade(),
+                        cx,
+                    );
+                }
+            })
+            .detach();
+        }
+
+        let task_completed = terminal.read(cx).wait_for_completed_task(cx);
+        let terminal = terminal.downgrade();
+        cx.spawn(|project, mut cx| async move {
+            task_completed.await;
+            project.update(&mut cx, |project, cx| {
+                project.report_task_problems(task_id, problem_matchers, cwd, terminal, cx)
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// Replaces the problems of the task with the ones in the output of its terminal, which is
+    /// limited to the latest build for background tasks.
+    fn report_task_problems(
+        &mut self,
+        task_id: TaskId,
+        problem_matchers: Arc<[ProblemMatcher]>,
+        cwd: Option<PathBuf>,
+        terminal: WeakEntity<Terminal>,
+        cx: &mut Context<Project>,
+    ) {
+        let Some((output, background_matcher)) = terminal
+            .read_with(cx, |terminal, _| {
+                let background_matcher = terminal
+                    .task()
+                    .and_then(|task| task.background.as_ref())
+                    .map(|background| background.matcher().clone());
+                Some((terminal.scrollback_text()?, background_matcher))
+            })
+            .ok()
+            .flatten()
+        else {
+            return;
+        };
+        cx.spawn(|project, mut cx| async move {
+            let problems = cx
+                .background_executor()
+                .spawn(async move {
+                    let output = match &background_matcher {
+                        Some(matcher) => matcher.latest_build(&output),
+                        None => &output,
+                    };
+                    problem_matchers
+                        .iter()
+                        .filter_map(|matcher| {
+                            matcher.find_problems(output, cwd.as_deref()).log_err()
+                        })
+                        .flatten()
+                        .collect::<Vec<_>>()
+                })
+                .await;
+            project.update(&mut cx, |project, cx| {
+                // A newer run of the task has cleared the diagnostics, and will report its own.
+                if project.terminals.task_problem_terminals.get(&task_id)
+                    != Some(&terminal.entity_id())
+                {
+                    return Ok(());
+                }
+                project.lsp_store.update(cx, |lsp_store, cx| {
+                    lsp_store.update_task_diagnostics(&task_id, problems, cx)
+                })
+            })?
+        })
+        .detach_and_log_err(cx);
+    }
+
     fn python_venv_directory(
         &self,
         abs_path: Arc<Path>,
@@ -512,6 +681,23 @@ impl Project {
     pub fn local_terminal_handles(&self) -> &Vec<WeakEntity<terminal::Terminal>> {
         &self.terminals.local_handles
     }
+
+    /// Returns the labels of the running background tasks that are not ready, with their statuses.
+    pub fn pending_background_tasks<'a>(
+        &'a self,
+        cx: &'a App,
+    ) -> impl Iterator<Item = (String, BackgroundTaskStatus)> + 'a {
+        self.terminals
+            .local_handles
+            .iter()
+            .filter_map(move |terminal| {
+                let terminal = terminal.upgrade()?;
+                let task = terminal.read(cx).task()?;
+                let status = task.background.as_ref()?.status();
+                (task.status == TaskStatus::Running && status != BackgroundTaskStatus::Ready)
+                    .then(|| (task.label.clone(), status))
+            })
+    }
 }
 
 fn wrap_for_ssh(
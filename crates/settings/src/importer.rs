@@ -0,0 +1,362 @@
+//! Converts the settings and key bindings of other editors into Zed settings and keymaps.
+
+mod jetbrains;
+mod sublime;
+mod vim;
+mod vscode;
+
+use std::fmt::Write as _;
+use std::path::Path;
+
+use anyhow::Result;
+use gpui::Keystroke;
+use serde_json::Value;
+
+/// A config file of another editor that can be imported.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImportSource {
+    /// VS Code's `settings.json`.
+    VsCodeSettings,
+    /// VS Code's `keybindings.json`.
+    VsCodeKeymap,
+    /// A Sublime Text `.sublime-keymap` file.
+    SublimeKeymap,
+    /// A keymap exported from a JetBrains IDE, as XML.
+    JetBrainsKeymap,
+    /// A `.vimrc`, of which options and key mappings are imported.
+    Vimrc,
+}
+
+impl ImportSource {
+    pub const ALL: [Self; 5] = [
+        Self::VsCodeSettings,
+        Self::VsCodeKeymap,
+        Self::SublimeKeymap,
+        Self::JetBrainsKeymap,
+        Self::Vimrc,
+    ];
+
+    /// Guesses what kind of config a file contains from its name and directory, returning `None`
+    /// when it can't be told apart from other files with the same name, like Zed's own
+    /// `settings.json`.
+    pub fn detect(path: &Path) -> Option<Self> {
+        let file_name = path.file_name()?.to_str()?.to_lowercase();
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(str::to_lowercase);
+        let dir_name = path
+            .parent()
+            .and_then(|dir| dir.file_name())
+            .and_then(|dir_name| dir_name.to_str())
+            .map(str::to_lowercase);
+        match (file_name.as_str(), extension.as_deref()) {
+            ("settings.json", _) if matches!(dir_name.as_deref(), Some("user" | ".vscode")) => {
+                Some(Self::VsCodeSettings)
+            }
+            ("keybindings.json", _) => Some(Self::VsCodeKeymap),
+            (_, Some("sublime-keymap")) => Some(Self::SublimeKeymap),
+            (_, Some("xml")) if dir_name.as_deref() == Some("keymaps") => {
+                Some(Self::JetBrainsKeymap)
+            }
+            (".vimrc" | "_vimrc" | "vimrc" | ".exrc" | "init.vim", _) | (_, Some("vim")) => {
+                Some(Self::Vimrc)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::VsCodeSettings => "VS Code settings",
+            Self::VsCodeKeymap => "VS Code key bindings",
+            Self::SublimeKeymap => "Sublime Text key bindings",
+            Self::JetBrainsKeymap => "JetBrains keymap",
+            Self::Vimrc => "Vim config",
+        }
+    }
+
+    /// Converts the content of a config file.
+    pub fn import(&self, content: &str) -> Result<ImportedConfig> {
+        match self {
+            Self::VsCodeSettings => vscode::import_settings(content),
+            Self::VsCodeKeymap => vscode::import_keymap(content),
+            Self::SublimeKeymap => sublime::import_keymap(content),
+            Self::JetBrainsKeymap => jetbrains::import_keymap(content),
+            Self::Vimrc => Ok(vim::import_vimrc(content)),
+        }
+    }
+}
+
+/// The Zed settings and key bindings converted from a config, along with what couldn't be.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ImportedConfig {
+    pub settings: Vec<ImportedSetting>,
+    pub bindings: Vec<ImportedBinding>,
+    pub unmapped: Vec<UnmappedEntry>,
+}
+
+/// A value to set in the user settings.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImportedSetting {
+    pub key_path: Vec<String>,
+    pub value: Value,
+}
+
+/// A binding to add to the user keymap.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImportedBinding {
+    pub context: Option<String>,
+    pub keystrokes: String,
+    /// The action to bind, or `None` to unbind the keystrokes.
+    pub action: Option<ImportedAction>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImportedAction {
+    pub name: String,
+    /// The JSON input of the action.
+    pub input: Option<String>,
+}
+
+/// An entry of the imported config that has no Zed equivalent.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UnmappedEntry {
+    /// The entry, as written in the imported config.
+    pub entry: String,
+    pub reason: String,
+}
+
+impl ImportedConfig {
+    /// Sets a setting, replacing any earlier value of the same setting.
+    fn set(&mut self, key_path: &str, value: Value) {
+        let key_path = key_path.split('.').map(str::to_string).collect::<Vec<_>>();
+        self.settings.retain(|setting| setting.key_path != key_path);
+        self.settings.push(ImportedSetting { key_path, value });
+    }
+
+    fn bind(&mut self, context: Option<&str>, keystrokes: String, action: Option<ImportedAction>) {
+        self.bindings.push(ImportedBinding {
+            context: context.map(str::to_string),
+            keystrokes,
+            action,
+        });
+    }
+
+    fn unmapped(&mut self, entry: impl Into<String>, reason: impl Into<String>) {
+        self.unmapped.push(UnmappedEntry {
+            entry: entry.into(),
+            reason: reason.into(),
+        });
+    }
+
+    /// The imported settings, as the content of a settings file.
+    pub fn settings_json(&self) -> Value {
+        let mut settings = Value::Object(Default::default());
+        for setting in &self.settings {
+            let mut object = &mut settings;
+            for key in &setting.key_path {
+                if !object.is_object() {
+                    *object = Value::Object(Default::default());
+                }
+                object = object
+                    .as_object_mut()
+                    .unwrap()
+                    .entry(key.clone())
+                    .or_insert(Value::Null);
+            }
+            *object = setting.value.clone();
+        }
+        settings
+    }
+
+    /// The imported bindings, as the content of a keymap file.
+    pub fn keymap_json(&self) -> Value {
+        let mut sections = Vec::<(Option<&str>, serde_json::Map<String, Value>)>::new();
+        for binding in &self.bindings {
+            let context = binding.context.as_deref();
+            let ix = match sections.iter().position(|(other, _)| *other == context) {
+                Some(ix) => ix,
+                None => {
+                    sections.push((context, Default::default()));
+                    sections.len() - 1
+                }
+            };
+            sections[ix]
+                .1
+                .insert(binding.keystrokes.clone(), binding.action_value());
+        }
+        Value::Array(
+            sections
+                .into_iter()
+                .map(|(context, bindings)| {
+                    let mut section = serde_json::Map::new();
+                    if let Some(context) = context {
+                        section.insert("context".into(), context.into());
+                    }
+                    section.insert("bindings".into(), Value::Object(bindings));
+                    Value::Object(section)
+                })
+                .collect(),
+        )
+    }
+
+    /// A Markdown summary of the import, listing the entries that weren't imported.
+    pub fn report(&self, source: ImportSource) -> String {
+        let mut report = format!("# Import of {}\n\n", source.name());
+        writeln!(
+            report,
+            "Imported {} settings and {} key bindings.",
+            self.settings.len(),
+            self.bindings.len()
+        )
+        .ok();
+        if self.unmapped.is_empty() {
+            report.push_str("\nEverything was imported.\n");
+        } else {
+            report.push_str("\n## Not imported\n\n");
+            for entry in &self.unmapped {
+                writeln!(report, "- `{}`: {}", entry.entry, entry.reason).ok();
+            }
+        }
+        report
+    }
+}
+
+impl ImportedBinding {
+    /// The action as written in a keymap file.
+    pub fn action_value(&self) -> Value {
+        match &self.action {
+            Some(action) => action.value(),
+            None => Value::Null,
+        }
+    }
+}
+
+impl ImportedAction {
+    fn value(&self) -> Value {
+        let name = Value::String(self.name.clone());
+        match self
+            .input
+            .as_deref()
+            .and_then(|input| serde_json::from_str(input).ok())
+        {
+            Some(input) => Value::Array(vec![name, input]),
+            None => name,
+        }
+    }
+}
+
+/// Parses an action of the mapping tables, written either as a name or as a JSON array of the
+/// name and the action's input.
+fn mapped_action(action: &str) -> ImportedAction {
+    match serde_json::from_str::<(String, Value)>(action) {
+        Ok((name, input)) => ImportedAction {
+            name,
+            input: Some(input.to_string()),
+        },
+        Err(_) => ImportedAction {
+            name: action.to_string(),
+            input: None,
+        },
+    }
+}
+
+/// The context that bindings to an action are added in, so that they apply where the action
+/// is handled.
+fn context_for_action(action: &ImportedAction) -> Option<&'static str> {
+    if action.name.starts_with("editor::") {
+        Some("Editor")
+    } else if action.name.starts_with("terminal::") {
+        Some("Terminal")
+    } else {
+        None
+    }
+}
+
+/// Checks that each space-separated keystroke can be parsed by Zed.
+fn validate_keystrokes(keystrokes: &str) -> Result<(), String> {
+    if keystrokes.is_empty() {
+        return Err("no keystrokes".into());
+    }
+    for keystroke in keystrokes.split(' ') {
+        Keystroke::parse(keystroke)
+            .map_err(|_| format!("`{keystroke}` is not a valid keystroke"))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_detect_import_source() {
+        let detect = |path: &str| ImportSource::detect(Path::new(path));
+        assert_eq!(
+            detect("/home/me/.config/Code/User/settings.json"),
+            Some(ImportSource::VsCodeSettings)
+        );
+        assert_eq!(
+            detect("/home/me/project/.vscode/settings.json"),
+            Some(ImportSource::VsCodeSettings)
+        );
+        assert_eq!(detect("/home/me/.config/zed/settings.json"), None);
+        assert_eq!(detect("keybindings.json"), Some(ImportSource::VsCodeKeymap));
+        assert_eq!(
+            detect("Default (Linux).sublime-keymap"),
+            Some(ImportSource::SublimeKeymap)
+        );
+        assert_eq!(
+            detect("/home/me/.config/JetBrains/IntelliJIdea2024.3/keymaps/My Keymap.xml"),
+            Some(ImportSource::JetBrainsKeymap)
+        );
+        assert_eq!(detect("/home/me/project/pom.xml"), None);
+        assert_eq!(detect("/home/me/.vimrc"), Some(ImportSource::Vimrc));
+        assert_eq!(detect("init.vim"), Some(ImportSource::Vimrc));
+        assert_eq!(detect("notes.txt"), None);
+    }
+
+    #[test]
+    fn test_imported_config_json() {
+        let mut config = ImportedConfig::default();
+        config.set("tab_size", json!(2));
+        config.set("gutter.line_numbers", json!(false));
+        config.set("tab_size", json!(4));
+        config.bind(
+            Some("Editor"),
+            "ctrl-d".into(),
+            Some(mapped_action("editor::DeleteLine")),
+        );
+        config.bind(
+            None,
+            "ctrl-p".into(),
+            Some(mapped_action(r#"["pane::ActivateItem", 0]"#)),
+        );
+        config.bind(Some("Editor"), "ctrl-k".into(), None);
+        config.unmapped("editor.fontLigatures", "no Zed equivalent");
+
+        assert_eq!(
+            config.settings_json(),
+            json!({ "gutter": { "line_numbers": false }, "tab_size": 4 })
+        );
+        assert_eq!(
+            config.keymap_json(),
+            json!([
+                {
+                    "context": "Editor",
+                    "bindings": { "ctrl-d": "editor::DeleteLine", "ctrl-k": null }
+                },
+                { "bindings": { "ctrl-p": ["pane::ActivateItem", 0] } }
+            ])
+        );
+        assert_eq!(
+            config.report(ImportSource::VsCodeSettings),
+            "# Import of VS Code settings\n\n\
+             Imported 2 settings and 3 key bindings.\n\n\
+             ## Not imported\n\n\
+             - `editor.fontLigatures`: no Zed equivalent\n"
+        );
+    }
+}
@@ -0,0 +1,349 @@
+use anyhow::{anyhow, Result};
+use serde_json::json;
+
+use super::{context_for_action, mapped_action, validate_keystrokes, ImportedConfig};
+
+/// JetBrains action ids and the Zed actions they map to.
+const ACTIONS: &[(&str, &str)] = &[
+    ("GotoFile", "file_finder::Toggle"),
+    ("SearchEverywhere", "file_finder::Toggle"),
+    ("GotoAction", "command_palette::Toggle"),
+    ("GotoSymbol", "project_symbols::Toggle"),
+    ("FileStructurePopup", "outline::Toggle"),
+    ("GotoLine", "go_to_line::Toggle"),
+    ("RecentFiles", "tab_switcher::Toggle"),
+    ("Find", "buffer_search::Deploy"),
+    ("Replace", "buffer_search::DeployReplace"),
+    ("FindInPath", "pane::DeploySearch"),
+    ("ShowSettings", "zed::OpenSettings"),
+    ("SaveAll", "workspace::SaveAll"),
+    ("CloseContent", "pane::CloseActiveItem"),
+    ("CloseAllEditors", "pane::CloseAllItems"),
+    ("ReopenClosedTab", "pane::ReopenClosedItem"),
+    ("NextTab", "pane::ActivateNextItem"),
+    ("PreviousTab", "pane::ActivatePrevItem"),
+    ("Back", "pane::GoBack"),
+    ("Forward", "pane::GoForward"),
+    ("SplitVertically", "pane::SplitRight"),
+    ("SplitHorizontally", "pane::SplitDown"),
+    ("ActivateProjectToolWindow", "project_panel::ToggleFocus"),
+    ("ActivateTerminalToolWindow", "terminal_panel::ToggleFocus"),
+    ("HideAllWindows", "workspace::CloseAllDocks"),
+    ("ToggleFullScreen", "zed::ToggleFullScreen"),
+    ("CommentByLineComment", "editor::ToggleComments"),
+    ("ReformatCode", "editor::Format"),
+    ("RenameElement", "editor::Rename"),
+    ("GotoDeclaration", "editor::GoToDefinition"),
+    ("GotoTypeDeclaration", "editor::GoToTypeDefinition"),
+    ("GotoImplementation", "editor::GoToImplementation"),
+    ("FindUsages", "editor::FindAllReferences"),
+    ("ShowIntentionActions", "editor::ToggleCodeActions"),
+    ("QuickJavaDoc", "editor::Hover"),
+    ("ParameterInfo", "editor::ShowSignatureHelp"),
+    ("CodeCompletion", "editor::ShowCompletions"),
+    ("GotoNextError", "editor::GoToDiagnostic"),
+    ("GotoPreviousError", "editor::GoToPrevDiagnostic"),
+    ("VcsShowNextChangeMarker", "editor::GoToHunk"),
+    ("VcsShowPrevChangeMarker", "editor::GoToPrevHunk"),
+    ("EditorDuplicate", "editor::DuplicateSelection"),
+    ("EditorDeleteLine", "editor::DeleteLine"),
+    ("EditorJoinLines", "editor::JoinLines"),
+    ("MoveLineUp", "editor::MoveLineUp"),
+    ("MoveLineDown", "editor::MoveLineDown"),
+    ("EditorSelectWord", "editor::SelectLargerSyntaxNode"),
+    ("EditorUnSelectWord", "editor::SelectSmallerSyntaxNode"),
+    (
+        "SelectNextOccurrence",
+        r#"["editor::SelectNext", { "replace_newest": false }]"#,
+    ),
+    ("SelectAllOccurrences", "editor::SelectAllMatches"),
+    ("EditorCloneCaretAbove", "editor::AddSelectionAbove"),
+    ("EditorCloneCaretBelow", "editor::AddSelectionBelow"),
+    ("EditorStartNewLineBefore", "editor::NewlineAbove"),
+    ("EditorStartNewLine", "editor::NewlineBelow"),
+    ("EditorToggleCase", "editor::ConvertToUpperCase"),
+    ("CollapseRegion", "editor::Fold"),
+    ("ExpandRegion", "editor::UnfoldLines"),
+    ("CollapseAllRegions", "editor::FoldAll"),
+    ("ExpandAllRegions", "editor::UnfoldAll"),
+    ("EditorMatchBrace", "editor::MoveToEnclosingBracket"),
+    ("$Undo", "editor::Undo"),
+    ("$Redo", "editor::Redo"),
+];
+
+/// A tag of an XML document, with its attributes.
+struct Tag<'a> {
+    name: &'a str,
+    attributes: Vec<(&'a str, String)>,
+    /// Whether the tag closes an element, like `</action>` or `<action/>`.
+    closes: bool,
+    /// Whether the tag opens an element, like `<action>` or `<action/>`.
+    opens: bool,
+}
+
+impl Tag<'_> {
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(attribute, _)| *attribute == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+pub(super) fn import_keymap(content: &str) -> Result<ImportedConfig> {
+    let mut config = ImportedConfig::default();
+    config.set("base_keymap", json!("JetBrains"));
+
+    let mut found_keymap = false;
+    let mut action_id = None;
+    let mut action_has_shortcuts = false;
+    for tag in tags(content)? {
+        match tag.name {
+            "keymap" if tag.opens => found_keymap = true,
+            "action" => {
+                if tag.opens {
+                    action_id = tag.attribute("id").map(str::to_string);
+                    action_has_shortcuts = false;
+                }
+                if tag.closes && !action_has_shortcuts {
+                    if let Some(action_id) = action_id.take() {
+                        config.unmapped(
+                            action_id,
+                            "removing the shortcuts of an action isn't supported",
+                        );
+                    }
+                }
+            }
+            "keyboard-shortcut" => {
+                let Some(action_id) = action_id.as_deref() else {
+                    continue;
+                };
+                action_has_shortcuts = true;
+                let keystrokes = [
+                    tag.attribute("first-keystroke"),
+                    tag.attribute("second-keystroke"),
+                ]
+                .into_iter()
+                .flatten()
+                .map(keystroke)
+                .collect::<Vec<_>>()
+                .join(" ");
+                let entry = format!("{action_id}: {keystrokes}");
+                let Some((_, action)) = ACTIONS.iter().find(|(id, _)| *id == action_id) else {
+                    config.unmapped(entry, "no equivalent Zed action");
+                    continue;
+                };
+                if let Err(error) = validate_keystrokes(&keystrokes) {
+                    config.unmapped(entry, error);
+                    continue;
+                }
+                let action = mapped_action(action);
+                config.bind(context_for_action(&action), keystrokes, Some(action));
+            }
+            "mouse-shortcut" => {
+                if let Some(action_id) = action_id.as_deref() {
+                    action_has_shortcuts = true;
+                    config.unmapped(
+                        format!(
+                            "{action_id}: {}",
+                            tag.attribute("keystroke").unwrap_or_default()
+                        ),
+                        "mouse shortcuts aren't supported",
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
+    if !found_keymap {
+        return Err(anyhow!("not a JetBrains keymap: no `<keymap>` element"));
+    }
+    Ok(config)
+}
+
+/// Converts a JetBrains keystroke, like `control shift OPEN_BRACKET`, to a Zed keystroke.
+fn keystroke(keystroke: &str) -> String {
+    keystroke
+        .split_whitespace()
+        .map(|part| match part {
+            "control" | "ctrl" => "ctrl".to_string(),
+            "meta" => "cmd".to_string(),
+            "BACK_SPACE" => "backspace".to_string(),
+            "PAGE_UP" => "pageup".to_string(),
+            "PAGE_DOWN" => "pagedown".to_string(),
+            "OPEN_BRACKET" => "[".to_string(),
+            "CLOSE_BRACKET" => "]".to_string(),
+            "SEMICOLON" => ";".to_string(),
+            "QUOTE" => "'".to_string(),
+            "BACK_QUOTE" => "`".to_string(),
+            "COMMA" => ",".to_string(),
+            "PERIOD" => ".".to_string(),
+            "SLASH" => "/".to_string(),
+            "BACK_SLASH" => "\\".to_string(),
+            "MINUS" => "-".to_string(),
+            "EQUALS" => "=".to_string(),
+            part => part.to_lowercase(),
+        })
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Lists the tags of an XML document, skipping comments, processing instructions and text.
+fn tags(content: &str) -> Result<Vec<Tag<'_>>> {
+    let mut tags = Vec::new();
+    let mut rest = content;
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start + 1..];
+        if let Some(comment) = rest.strip_prefix("!--") {
+            let end = comment
+                .find("-->")
+                .ok_or_else(|| anyhow!("unterminated XML comment"))?;
+            rest = &comment[end + 3..];
+            continue;
+        }
+        let end = tag_end(rest).ok_or_else(|| anyhow!("unterminated XML tag"))?;
+        let tag = &rest[..end];
+        rest = &rest[end + 1..];
+        if tag.starts_with('?') || tag.starts_with('!') {
+            continue;
+        }
+
+        let (tag, closes_before) = match tag.strip_prefix('/') {
+            Some(tag) => (tag, true),
+            None => (tag, false),
+        };
+        let (tag, closes_after) = match tag.strip_suffix('/') {
+            Some(tag) => (tag, true),
+            None => (tag, false),
+        };
+        let name_end = tag.find(|c: char| c.is_whitespace()).unwrap_or(tag.len());
+        tags.push(Tag {
+            name: &tag[..name_end],
+            attributes: attributes(&tag[name_end..])?,
+            closes: closes_before || closes_after,
+            opens: !closes_before,
+        });
+    }
+    Ok(tags)
+}
+
+/// The offset of the `>` ending a tag, skipping any in quoted attribute values.
+fn tag_end(tag: &str) -> Option<usize> {
+    let mut quote = None;
+    for (ix, c) in tag.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(open), c) if c == open => quote = None,
+            (None, '>') => return Some(ix),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn attributes(mut text: &str) -> Result<Vec<(&str, String)>> {
+    let mut attributes = Vec::new();
+    loop {
+        text = text.trim_start();
+        if text.is_empty() {
+            return Ok(attributes);
+        }
+        let (name, rest) = text
+            .split_once('=')
+            .ok_or_else(|| anyhow!("invalid XML attribute `{text}`"))?;
+        let rest = rest.trim_start();
+        let quote = rest
+            .chars()
+            .next()
+            .filter(|quote| matches!(quote, '"' | '\''))
+            .ok_or_else(|| anyhow!("unquoted XML attribute `{name}`"))?;
+        let value_end = rest[1..]
+            .find(quote)
+            .ok_or_else(|| anyhow!("unterminated XML attribute `{name}`"))?;
+        attributes.push((name.trim(), unescape(&rest[1..value_end + 1])));
+        text = &rest[value_end + 2..];
+    }
+}
+
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_import_jetbrains_keymap() {
+        let config = import_keymap(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+            <keymap version="1" name="Custom" parent="$default">
+              <!-- Comments are skipped. -->
+              <action id="GotoFile">
+                <keyboard-shortcut first-keystroke="control shift N" />
+              </action>
+              <action id="EditorDuplicate">
+                <keyboard-shortcut first-keystroke="control D" />
+                <keyboard-shortcut first-keystroke="control K" second-keystroke="control OPEN_BRACKET" />
+              </action>
+              <action id="Vcs.QuickListPopupAction">
+                <keyboard-shortcut first-keystroke="alt BACK_QUOTE" />
+              </action>
+              <action id="GotoDeclaration">
+                <mouse-shortcut keystroke="control button1" />
+              </action>
+              <action id="ReformatCode" />
+            </keymap>"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.keymap_json(),
+            json!([
+                { "bindings": { "ctrl-shift-n": "file_finder::Toggle" } },
+                {
+                    "context": "Editor",
+                    "bindings": {
+                        "ctrl-d": "editor::DuplicateSelection",
+                        "ctrl-k ctrl-[": "editor::DuplicateSelection"
+                    }
+                }
+            ])
+        );
+        assert_eq!(
+            config
+                .unmapped
+                .iter()
+                .map(|entry| (entry.entry.as_str(), entry.reason.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    "Vcs.QuickListPopupAction: alt-`",
+                    "no equivalent Zed action"
+                ),
+                (
+                    "GotoDeclaration: control button1",
+                    "mouse shortcuts aren't supported"
+                ),
+                (
+                    "ReformatCode",
+                    "removing the shortcuts of an action isn't supported"
+                ),
+            ]
+        );
+
+        assert!(import_keymap("<settings></settings>").is_err());
+    }
+
+    #[test]
+    fn test_command_mappings_are_valid() {
+        for (_, action) in ACTIONS {
+            assert!(action.contains("::"), "{action}");
+        }
+    }
+}
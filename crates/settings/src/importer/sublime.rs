@@ -0,0 +1,301 @@
+use anyhow::{Context as _, Result};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::parse_json_with_comments;
+
+use super::{context_for_action, mapped_action, validate_keystrokes, ImportedConfig};
+
+/// Sublime Text commands, the arguments they must be given to match, and the Zed actions they
+/// map to.
+const COMMANDS: &[(&str, Option<&str>, &str)] = &[
+    (
+        "show_overlay",
+        Some(r#"{ "overlay": "command_palette" }"#),
+        "command_palette::Toggle",
+    ),
+    (
+        "show_overlay",
+        Some(r#"{ "overlay": "goto", "show_files": true }"#),
+        "file_finder::Toggle",
+    ),
+    (
+        "show_overlay",
+        Some(r#"{ "overlay": "goto", "text": "@" }"#),
+        "outline::Toggle",
+    ),
+    (
+        "show_overlay",
+        Some(r#"{ "overlay": "goto", "text": ":" }"#),
+        "go_to_line::Toggle",
+    ),
+    ("goto_symbol_in_project", None, "project_symbols::Toggle"),
+    (
+        "show_panel",
+        Some(r#"{ "panel": "find" }"#),
+        "buffer_search::Deploy",
+    ),
+    (
+        "show_panel",
+        Some(r#"{ "panel": "replace" }"#),
+        "buffer_search::DeployReplace",
+    ),
+    (
+        "show_panel",
+        Some(r#"{ "panel": "find_in_files" }"#),
+        "pane::DeploySearch",
+    ),
+    ("new_file", None, "workspace::NewFile"),
+    ("new_window", None, "workspace::NewWindow"),
+    ("save", None, "workspace::Save"),
+    ("save_all", None, "workspace::SaveAll"),
+    ("prompt_save_as", None, "workspace::SaveAs"),
+    ("close", None, "pane::CloseActiveItem"),
+    ("close_all", None, "pane::CloseAllItems"),
+    ("reopen_last_file", None, "pane::ReopenClosedItem"),
+    ("next_view", None, "pane::ActivateNextItem"),
+    ("prev_view", None, "pane::ActivatePrevItem"),
+    ("jump_back", None, "pane::GoBack"),
+    ("jump_forward", None, "pane::GoForward"),
+    ("toggle_side_bar", None, "workspace::ToggleLeftDock"),
+    ("toggle_full_screen", None, "zed::ToggleFullScreen"),
+    ("increase_font_size", None, "zed::IncreaseBufferFontSize"),
+    ("decrease_font_size", None, "zed::DecreaseBufferFontSize"),
+    ("toggle_comment", None, "editor::ToggleComments"),
+    ("duplicate_line", None, "editor::DuplicateLineDown"),
+    ("swap_line_up", None, "editor::MoveLineUp"),
+    ("swap_line_down", None, "editor::MoveLineDown"),
+    (
+        "find_under_expand",
+        None,
+        r#"["editor::SelectNext", { "replace_newest": false }]"#,
+    ),
+    (
+        "find_under_expand_skip",
+        None,
+        r#"["editor::SelectNext", { "replace_newest": true }]"#,
+    ),
+    ("find_all_under", None, "editor::SelectAllMatches"),
+    (
+        "split_selection_into_lines",
+        None,
+        "editor::SplitSelectionIntoLines",
+    ),
+    (
+        "select_lines",
+        Some(r#"{ "forward": false }"#),
+        "editor::AddSelectionAbove",
+    ),
+    (
+        "select_lines",
+        Some(r#"{ "forward": true }"#),
+        "editor::AddSelectionBelow",
+    ),
+    (
+        "expand_selection",
+        Some(r#"{ "to": "line" }"#),
+        "editor::SelectLine",
+    ),
+    (
+        "expand_selection",
+        Some(r#"{ "to": "smart" }"#),
+        "editor::SelectLargerSyntaxNode",
+    ),
+    ("goto_definition", None, "editor::GoToDefinition"),
+    ("goto_reference", None, "editor::FindAllReferences"),
+    (
+        "move_to",
+        Some(r#"{ "to": "brackets" }"#),
+        "editor::MoveToEnclosingBracket",
+    ),
+    ("join_lines", None, "editor::JoinLines"),
+    ("indent", None, "editor::Indent"),
+    ("unindent", None, "editor::Outdent"),
+    ("upper_case", None, "editor::ConvertToUpperCase"),
+    ("lower_case", None, "editor::ConvertToLowerCase"),
+    ("sort_lines", None, "editor::SortLinesCaseSensitive"),
+    ("fold", None, "editor::Fold"),
+    ("unfold", None, "editor::UnfoldLines"),
+    ("unfold_all", None, "editor::UnfoldAll"),
+    ("soft_undo", None, "editor::UndoSelection"),
+    ("soft_redo", None, "editor::RedoSelection"),
+    ("undo", None, "editor::Undo"),
+    ("redo", None, "editor::Redo"),
+    (
+        "run_macro_file",
+        Some(r#"{ "file": "res://Packages/Default/Delete Line.sublime-macro" }"#),
+        "editor::DeleteLine",
+    ),
+];
+
+#[derive(Deserialize)]
+struct SublimeKeybinding {
+    keys: Vec<String>,
+    command: String,
+    #[serde(default)]
+    args: Value,
+    context: Option<Value>,
+}
+
+pub(super) fn import_keymap(content: &str) -> Result<ImportedConfig> {
+    let keybindings: Vec<SublimeKeybinding> =
+        parse_json_with_comments(content).context("invalid Sublime Text keymap")?;
+    let mut config = ImportedConfig::default();
+    config.set("base_keymap", json!("SublimeText"));
+    for keybinding in keybindings {
+        let entry = format!("{}: {}", keybinding.keys.join(", "), keybinding.command);
+        let Some((_, _, action)) = COMMANDS.iter().find(|(command, args, _)| {
+            *command == keybinding.command
+                && args.map_or(true, |args| {
+                    args_match(&serde_json::from_str(args).unwrap(), &keybinding.args)
+                })
+        }) else {
+            config.unmapped(entry, "no equivalent Zed action");
+            continue;
+        };
+        let keystrokes = keybinding
+            .keys
+            .iter()
+            .map(|key| keystroke(key))
+            .collect::<Vec<_>>()
+            .join(" ");
+        if let Err(error) = validate_keystrokes(&keystrokes) {
+            config.unmapped(entry, error);
+            continue;
+        }
+
+        let action = mapped_action(action);
+        let context = context_for_action(&action);
+        if keybinding.context.is_some() {
+            config.unmapped(
+                &entry,
+                format!(
+                    "the context conditions were dropped, the binding applies {}",
+                    context.map_or("everywhere".to_string(), |context| format!(
+                        "in the `{context}` context"
+                    ))
+                ),
+            );
+        }
+        config.bind(context, keystrokes, Some(action));
+    }
+    Ok(config)
+}
+
+/// Whether every argument that a mapping requires is given with the same value.
+fn args_match(required: &Value, args: &Value) -> bool {
+    match required.as_object() {
+        Some(required) => required
+            .iter()
+            .all(|(key, value)| args.get(key) == Some(value)),
+        None => true,
+    }
+}
+
+/// Converts a Sublime Text keystroke, like `ctrl+shift+forward_slash`, to a Zed keystroke.
+fn keystroke(key: &str) -> String {
+    let (modifiers, key) = match key.strip_suffix("++") {
+        Some(modifiers) => (modifiers, "+"),
+        None => key.rsplit_once('+').unwrap_or(("", key)),
+    };
+    let mut keystroke = String::new();
+    for modifier in modifiers.split('+').filter(|modifier| !modifier.is_empty()) {
+        keystroke.push_str(match modifier {
+            "super" if cfg!(target_os = "macos") => "cmd",
+            "primary" if cfg!(target_os = "macos") => "cmd",
+            "primary" => "ctrl",
+            modifier => modifier,
+        });
+        keystroke.push('-');
+    }
+    keystroke.push_str(match key {
+        "forward_slash" => "/",
+        "backquote" => "`",
+        "plus" => "+",
+        "minus" => "-",
+        "equals" => "=",
+        "page_up" => "pageup",
+        "page_down" => "pagedown",
+        key => key,
+    });
+    keystroke
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::importer::ImportedAction;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_import_sublime_keymap() {
+        let config = import_keymap(
+            r##"[
+                // Comments are allowed.
+                { "keys": ["ctrl+shift+p"], "command": "show_overlay", "args": { "overlay": "command_palette" } },
+                { "keys": ["ctrl+k", "ctrl+u"], "command": "upper_case" },
+                { "keys": ["ctrl+d"], "command": "find_under_expand", "context": [{ "key": "selection_empty" }] },
+                { "keys": ["ctrl+shift+o"], "command": "show_overlay", "args": { "overlay": "goto", "text": "#" } },
+                { "keys": ["alt+q"], "command": "wrap_lines" },
+            ]"##,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.settings_json(),
+            json!({ "base_keymap": "SublimeText" })
+        );
+        assert_eq!(
+            config.keymap_json(),
+            json!([
+                { "bindings": { "ctrl-shift-p": "command_palette::Toggle" } },
+                {
+                    "context": "Editor",
+                    "bindings": {
+                        "ctrl-k ctrl-u": "editor::ConvertToUpperCase",
+                        "ctrl-d": ["editor::SelectNext", { "replace_newest": false }]
+                    }
+                }
+            ])
+        );
+        assert_eq!(
+            config.bindings[2].action,
+            Some(ImportedAction {
+                name: "editor::SelectNext".into(),
+                input: Some(r#"{"replace_newest":false}"#.into()),
+            })
+        );
+        assert_eq!(
+            config
+                .unmapped
+                .iter()
+                .map(|entry| entry.entry.as_str())
+                .collect::<Vec<_>>(),
+            vec![
+                "ctrl+d: find_under_expand",
+                "ctrl+shift+o: show_overlay",
+                "alt+q: wrap_lines",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sublime_keystroke() {
+        assert_eq!(keystroke("ctrl+shift+forward_slash"), "ctrl-shift-/");
+        assert_eq!(keystroke("alt+plus"), "alt-+");
+        assert_eq!(keystroke("f12"), "f12");
+    }
+
+    #[test]
+    fn test_command_mappings_are_valid() {
+        for (_, args, action) in COMMANDS {
+            if let Some(args) = args {
+                assert!(
+                    serde_json::from_str::<Value>(args).is_ok_and(|args| args.is_object()),
+                    "{args}"
+                );
+            }
+            assert!(action.contains("::"), "{action}");
+        }
+    }
+}
@@ -0,0 +1,550 @@
+use serde_json::{json, Value};
+
+use super::{validate_keystrokes, ImportedAction, ImportedConfig};
+
+/// Ex commands that are commonly mapped, and the Zed actions they map to.
+const EX_COMMANDS: &[(&[&str], &str)] = &[
+    (&["w", "write"], "workspace::Save"),
+    (&["wa", "wall"], "workspace::SaveAll"),
+    (
+        &["q", "quit", "bd", "bdelete", "close"],
+        "pane::CloseActiveItem",
+    ),
+    (
+        &["bn", "bnext", "tabn", "tabnext"],
+        "pane::ActivateNextItem",
+    ),
+    (
+        &["bp", "bprevious", "bN", "bNext", "tabp", "tabprevious"],
+        "pane::ActivatePrevItem",
+    ),
+    (&["sp", "split"], "pane::SplitDown"),
+    (&["vs", "vsplit"], "pane::SplitRight"),
+    (&["enew"], "workspace::NewFile"),
+];
+
+/// The long names of the options that have a short name.
+const OPTION_ALIASES: &[(&str, &str)] = &[
+    ("cc", "colorcolumn"),
+    ("cul", "cursorline"),
+    ("et", "expandtab"),
+    ("ic", "ignorecase"),
+    ("nu", "number"),
+    ("rnu", "relativenumber"),
+    ("scs", "smartcase"),
+    ("so", "scrolloff"),
+    ("sts", "softtabstop"),
+    ("sw", "shiftwidth"),
+    ("ts", "tabstop"),
+    ("tw", "textwidth"),
+];
+
+const BOOLEAN_OPTIONS: &[&str] = &[
+    "cursorline",
+    "expandtab",
+    "hlsearch",
+    "ignorecase",
+    "incsearch",
+    "list",
+    "number",
+    "relativenumber",
+    "smartcase",
+    "wrap",
+];
+
+pub(super) fn import_vimrc(content: &str) -> ImportedConfig {
+    let mut config = ImportedConfig::default();
+    let mut leader = "\\".to_string();
+    let mut block_end = None;
+    for line in content.lines() {
+        let line = line.trim().trim_start_matches(':').trim_start();
+        if line.is_empty() || line.starts_with('"') {
+            continue;
+        }
+        let (command, args) = line
+            .split_once(char::is_whitespace)
+            .map_or((line, ""), |(command, args)| (command, args.trim()));
+
+        if let Some(end) = block_end {
+            if command == end
+                || (end == "endfunction" && matches!(command, "endf" | "endfu" | "endfun"))
+            {
+                block_end = None;
+            }
+            continue;
+        }
+
+        match command {
+            "set" | "se" | "setlocal" | "setl" => {
+                for option in args
+                    .split_whitespace()
+                    .take_while(|option| !option.starts_with('"'))
+                {
+                    set_option(&mut config, option);
+                }
+            }
+            "let" => match mapleader(args) {
+                Some(value) => leader = value,
+                None => config.unmapped(line, "variables aren't supported"),
+            },
+            "function" | "function!" | "fu" | "fu!" | "fun" | "fun!" => {
+                config.unmapped(line, "functions aren't supported");
+                block_end = Some("endfunction");
+            }
+            "augroup" | "aug" if !args.eq_ignore_ascii_case("end") => {
+                config.unmapped(line, "autocommands aren't supported");
+                block_end = Some("augroup");
+            }
+            "colorscheme" | "colo" => config.unmapped(line, "Vim color schemes aren't imported"),
+            _ => match map_command(command) {
+                Some(map_command) => map(&mut config, line, map_command, args, &leader),
+                None => config.unmapped(line, "unsupported command"),
+            },
+        }
+    }
+    config
+}
+
+fn set_option(config: &mut ImportedConfig, option: &str) {
+    let (name, value) = match option.split_once(['=', ':']) {
+        Some((name, value)) => (name.trim_end_matches(['+', '^']), Some(value)),
+        None => (option.trim_end_matches('!'), None),
+    };
+    let (name, enabled) = match name.strip_prefix("no") {
+        Some(name) if value.is_none() && BOOLEAN_OPTIONS.contains(&long_option_name(name)) => {
+            (long_option_name(name), false)
+        }
+        _ => (long_option_name(name), true),
+    };
+    let number = || value.and_then(|value| value.parse::<u64>().ok());
+
+    let unsupported = |config: &mut ImportedConfig| config.unmapped(option, "unsupported value");
+    match (name, value) {
+        ("number", None) => config.set("gutter.line_numbers", json!(enabled)),
+        ("relativenumber", None) => config.set("relative_line_numbers", json!(enabled)),
+        ("expandtab", None) => config.set("hard_tabs", json!(!enabled)),
+        ("wrap", None) => config.set(
+            "soft_wrap",
+            json!(if enabled { "editor_width" } else { "none" }),
+        ),
+        ("smartcase", None) => config.set("vim.use_smartcase_find", json!(enabled)),
+        ("list", None) => config.set(
+            "show_whitespaces",
+            json!(if enabled { "all" } else { "selection" }),
+        ),
+        ("cursorline", None) => config.set(
+            "current_line_highlight",
+            json!(if enabled { "all" } else { "none" }),
+        ),
+        ("tabstop" | "shiftwidth" | "softtabstop", Some(_)) => match number() {
+            // Zero makes these options fall back to another one.
+            Some(0) => {}
+            Some(tab_size) => config.set("tab_size", json!(tab_size)),
+            None => unsupported(config),
+        },
+        ("textwidth", Some(_)) => match number() {
+            Some(0) => {}
+            Some(width) => config.set("preferred_line_length", json!(width)),
+            None => unsupported(config),
+        },
+        ("scrolloff", Some(_)) => match number() {
+            Some(margin) => config.set("vertical_scroll_margin", json!(margin)),
+            None => unsupported(config),
+        },
+        ("colorcolumn", Some(columns)) => match columns
+            .split(',')
+            .filter(|column| !column.is_empty())
+            .map(|column| column.parse::<u64>().ok())
+            .collect::<Option<Vec<_>>>()
+        {
+            Some(columns) => config.set("wrap_guides", json!(columns)),
+            // Columns relative to `textwidth`, like `+1`.
+            None => unsupported(config),
+        },
+        ("clipboard", Some(clipboard)) => config.set(
+            "vim.use_system_clipboard",
+            json!(if clipboard.contains("unnamed") {
+                "always"
+            } else {
+                "never"
+            }),
+        ),
+        _ => config.unmapped(option, "no Zed equivalent"),
+    }
+}
+
+fn long_option_name(name: &str) -> &str {
+    OPTION_ALIASES
+        .iter()
+        .find(|(alias, _)| *alias == name)
+        .map_or(name, |(_, long_name)| long_name)
+}
+
+/// The leader set by a `let mapleader = ...` command, in key notation.
+fn mapleader(args: &str) -> Option<String> {
+    let (variable, value) = args.split_once('=')?;
+    if !matches!(variable.trim(), "mapleader" | "g:mapleader") {
+        return None;
+    }
+    let value = value.trim();
+    let value = value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .or_else(|| {
+            value
+                .strip_prefix('\'')
+                .and_then(|value| value.strip_suffix('\''))
+        })?;
+    // Double-quoted strings write special keys as `\<Space>`.
+    Some(value.replace("\\<", "<"))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct MapCommand {
+    /// The mode letter the command applies to, or `None` for normal, visual and operator-pending
+    /// modes.
+    mode: Option<char>,
+    unmap: bool,
+}
+
+fn map_command(command: &str) -> Option<MapCommand> {
+    let (command, bang) = match command.strip_suffix('!') {
+        Some(command) => (command, true),
+        None => (command, false),
+    };
+    let (mode, rest) = match command.chars().next()? {
+        mode @ ('n' | 'v' | 'x' | 's' | 'o' | 'i' | 'l' | 'c' | 't') if command != "noremap" => {
+            (Some(mode), &command[1..])
+        }
+        _ => (None, command),
+    };
+    // `map!` applies to insert and command-line modes.
+    let mode = if bang { Some('!') } else { mode };
+    match rest {
+        "map" | "noremap" | "nore" | "no" | "nor" => Some(MapCommand { mode, unmap: false }),
+        "unmap" | "unm" | "un" => Some(MapCommand { mode, unmap: true }),
+        _ => None,
+    }
+}
+
+fn map(config: &mut ImportedConfig, line: &str, map_command: MapCommand, args: &str, leader: &str) {
+    let context = match map_command.mode {
+        None => "VimControl && !menu",
+        Some('n') => "vim_mode == normal",
+        Some('v' | 'x') => "vim_mode == visual",
+        Some('o') => "vim_mode == operator",
+        Some('i') => "vim_mode == insert",
+        Some(_) => return config.unmapped(line, "the mapping's mode isn't supported"),
+    };
+
+    let mut args = args;
+    while let Some(rest) = args.strip_prefix('<') {
+        let Some((argument, rest)) = rest.split_once('>') else {
+            break;
+        };
+        match argument.to_lowercase().as_str() {
+            "silent" | "buffer" | "nowait" | "unique" | "special" | "script" => {
+                args = rest.trim_start()
+            }
+            "expr" => return config.unmapped(line, "expression mappings aren't supported"),
+            _ => break,
+        }
+    }
+    let (lhs, rhs) = args
+        .split_once(char::is_whitespace)
+        .map_or((args, ""), |(lhs, rhs)| (lhs, rhs.trim()));
+
+    let keystrokes = match keys(lhs, leader) {
+        Ok(keys) => keys.join(" "),
+        Err(error) => return config.unmapped(line, error),
+    };
+    if let Err(error) = validate_keystrokes(&keystrokes) {
+        return config.unmapped(line, error);
+    }
+    if map_command.unmap {
+        return config.bind(Some(context), keystrokes, None);
+    }
+
+    match rhs_action(rhs, leader) {
+        Ok(action) => config.bind(Some(context), keystrokes, action),
+        Err(error) => config.unmapped(line, error),
+    }
+}
+
+/// The action that the right-hand side of a mapping translates to, or `None` for `<Nop>`.
+fn rhs_action(rhs: &str, leader: &str) -> Result<Option<ImportedAction>, String> {
+    if rhs.is_empty() {
+        return Err("listing mappings isn't supported".into());
+    }
+    if rhs.eq_ignore_ascii_case("<nop>") {
+        return Ok(None);
+    }
+    let lowercase_rhs = rhs.to_lowercase();
+    if lowercase_rhs.contains("<plug>") || lowercase_rhs.contains("<sid>") {
+        return Err("mappings to plugins and scripts aren't supported".into());
+    }
+
+    if let Some(command) = rhs.strip_prefix(':') {
+        let command = strip_prefix_ignore_case(command, "<c-u>").unwrap_or(command);
+        let command = strip_suffix_ignore_case(command, "<cr>")
+            .ok_or_else(|| "incomplete Ex commands aren't supported".to_string())?
+            .trim();
+        return EX_COMMANDS
+            .iter()
+            .find(|(names, _)| names.contains(&command))
+            .map(|(_, action)| {
+                Some(ImportedAction {
+                    name: action.to_string(),
+                    input: None,
+                })
+            })
+            .ok_or_else(|| format!("the Ex command `:{command}` has no equivalent Zed action"));
+    }
+
+    let keystrokes = keys(rhs, leader)?.join(" ");
+    validate_keystrokes(&keystrokes)?;
+    Ok(Some(ImportedAction {
+        name: "workspace::SendKeystrokes".into(),
+        input: Some(Value::String(keystrokes).to_string()),
+    }))
+}
+
+fn strip_prefix_ignore_case<'a>(text: &'a str, prefix: &str) -> Option<&'a str> {
+    text.get(..prefix.len())
+        .filter(|start| start.eq_ignore_ascii_case(prefix))
+        .map(|_| &text[prefix.len()..])
+}
+
+fn strip_suffix_ignore_case<'a>(text: &'a str, suffix: &str) -> Option<&'a str> {
+    let start = text.len().checked_sub(suffix.len())?;
+    text.get(start..)
+        .filter(|end| end.eq_ignore_ascii_case(suffix))
+        .map(|_| &text[..start])
+}
+
+/// Converts keys in Vim's key notation, like `<C-w>h` or `<leader>f`, to Zed keystrokes.
+fn keys(text: &str, leader: &str) -> Result<Vec<String>, String> {
+    let mut keys = Vec::new();
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        if c == '<' {
+            if let Some((notation, after)) = rest[1..].split_once('>') {
+                if notation.eq_ignore_ascii_case("leader") {
+                    keys.extend(keys_without_leader(leader)?);
+                    rest = after;
+                    continue;
+                }
+                if notation.eq_ignore_ascii_case("localleader") {
+                    return Err("`<LocalLeader>` isn't supported".into());
+                }
+                if let Some(key) = special_key(notation) {
+                    keys.push(key);
+                    rest = after;
+                    continue;
+                }
+            }
+        }
+        keys.push(char_key(c));
+        rest = &rest[c.len_utf8()..];
+    }
+    Ok(keys)
+}
+
+fn keys_without_leader(leader: &str) -> Result<Vec<String>, String> {
+    if leader.to_lowercase().contains("<leader>") {
+        return Err("the leader refers to itself".into());
+    }
+    keys(leader, "")
+}
+
+/// Converts a key written between `<` and `>`, like `C-w` or `CR`.
+fn special_key(notation: &str) -> Option<String> {
+    let mut keystroke = String::new();
+    let mut rest = notation;
+    let mut shift = false;
+    let mut modifiers = Vec::new();
+    while let Some((modifier, key)) = rest.split_once('-').filter(|(_, key)| !key.is_empty()) {
+        match modifier.to_lowercase().as_str() {
+            "c" => modifiers.push("ctrl"),
+            "a" | "m" => modifiers.push("alt"),
+            "d" => modifiers.push("cmd"),
+            "s" => shift = true,
+            _ => return None,
+        }
+        rest = key;
+    }
+    let key = match rest.to_lowercase().as_str() {
+        "cr" | "enter" | "return" => "enter".to_string(),
+        "esc" => "escape".to_string(),
+        "space" => "space".to_string(),
+        "tab" => "tab".to_string(),
+        "bs" => "backspace".to_string(),
+        "del" => "delete".to_string(),
+        "up" | "down" | "left" | "right" | "home" | "end" | "pageup" | "pagedown" | "insert" => {
+            rest.to_lowercase()
+        }
+        "lt" => "<".to_string(),
+        "bar" => "|".to_string(),
+        "bslash" => "\\".to_string(),
+        function_key
+            if function_key.len() > 1
+                && function_key.starts_with('f')
+                && function_key[1..].parse::<u8>().is_ok() =>
+        {
+            function_key.to_string()
+        }
+        _ => {
+            let mut chars = rest.chars();
+            match (chars.next(), chars.next()) {
+                // Vim notation is case-insensitive, `<C-W>` and `<C-w>` are the same key.
+                (Some(c), None) if !modifiers.is_empty() || shift => {
+                    if c.is_ascii_uppercase() && modifiers.is_empty() {
+                        shift = true;
+                    }
+                    c.to_ascii_lowercase().to_string()
+                }
+                _ => return None,
+            }
+        }
+    };
+    if shift {
+        modifiers.push("shift");
+    }
+    for modifier in modifiers {
+        keystroke.push_str(modifier);
+        keystroke.push('-');
+    }
+    keystroke.push_str(&key);
+    Some(keystroke)
+}
+
+fn char_key(c: char) -> String {
+    match c {
+        ' ' => "space".into(),
+        c if c.is_ascii_uppercase() => format!("shift-{}", c.to_ascii_lowercase()),
+        c => c.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_import_vimrc() {
+        let config = import_vimrc(
+            r#"
+            " Options
+            set number relativenumber
+            set tabstop=4 shiftwidth=2 expandtab " trailing comment
+            set nowrap
+            set colorcolumn=80,100
+            set clipboard+=unnamedplus
+            set hlsearch
+            syntax on
+
+            let mapleader = "\<Space>"
+            nnoremap <leader>w :w<CR>
+            nnoremap <silent> <C-h> <C-w>h
+            inoremap jk <Esc>
+            vmap J :m '>+1<CR>gv=gv
+            noremap Y y$
+            nnoremap Q <Nop>
+            nunmap <C-a>
+            nmap <leader>f <Plug>(easymotion-s)
+            cnoremap <C-a> <Home>
+
+            function! Trim()
+              %s/\s\+$//e
+            endfunction
+            "#,
+        );
+
+        assert_eq!(
+            config.settings_json(),
+            json!({
+                "gutter": { "line_numbers": true },
+                "relative_line_numbers": true,
+                "tab_size": 2,
+                "hard_tabs": false,
+                "soft_wrap": "none",
+                "wrap_guides": [80, 100],
+                "vim": { "use_system_clipboard": "always" }
+            })
+        );
+        assert_eq!(
+            config.keymap_json(),
+            json!([
+                {
+                    "context": "vim_mode == normal",
+                    "bindings": {
+                        "space w": "workspace::Save",
+                        "ctrl-h": ["workspace::SendKeystrokes", "ctrl-w h"],
+                        "shift-q": null,
+                        "ctrl-a": null
+                    }
+                },
+                {
+                    "context": "vim_mode == insert",
+                    "bindings": { "j k": ["workspace::SendKeystrokes", "escape"] }
+                },
+                {
+                    "context": "VimControl && !menu",
+                    "bindings": { "shift-y": ["workspace::SendKeystrokes", "y $"] }
+                }
+            ])
+        );
+        assert_eq!(
+            config
+                .unmapped
+                .iter()
+                .map(|entry| (entry.entry.as_str(), entry.reason.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("hlsearch", "no Zed equivalent"),
+                ("syntax on", "unsupported command"),
+                (
+                    "vmap J :m '>+1<CR>gv=gv",
+                    "incomplete Ex commands aren't supported"
+                ),
+                (
+                    "nmap <leader>f <Plug>(easymotion-s)",
+                    "mappings to plugins and scripts aren't supported"
+                ),
+                (
+                    "cnoremap <C-a> <Home>",
+                    "the mapping's mode isn't supported"
+                ),
+                ("function! Trim()", "functions aren't supported"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_vim_keys() {
+        assert_eq!(keys("<C-w>h", "\\").unwrap(), vec!["ctrl-w", "h"]);
+        assert_eq!(keys("<leader>ff", ",").unwrap(), vec![",", "f", "f"]);
+        assert_eq!(keys("<Leader>x", "<Space>").unwrap(), vec!["space", "x"]);
+        assert_eq!(keys("<C-S-Tab>", "\\").unwrap(), vec!["ctrl-shift-tab"]);
+        assert_eq!(keys("<S-x>", "\\").unwrap(), vec!["shift-x"]);
+        assert_eq!(keys("<M-J>", "\\").unwrap(), vec!["alt-j"]);
+        assert_eq!(keys("gT", "\\").unwrap(), vec!["g", "shift-t"]);
+        assert_eq!(keys("<lt>a<F12>", "\\").unwrap(), vec!["<", "a", "f12"]);
+        assert_eq!(keys("<x", "\\").unwrap(), vec!["<", "x"]);
+        assert!(keys("<LocalLeader>x", "\\").is_err());
+    }
+
+    #[test]
+    fn test_map_command() {
+        let command = |mode, unmap| Some(MapCommand { mode, unmap });
+        assert_eq!(map_command("nnoremap"), command(Some('n'), false));
+        assert_eq!(map_command("noremap"), command(None, false));
+        assert_eq!(map_command("map"), command(None, false));
+        assert_eq!(map_command("xmap"), command(Some('x'), false));
+        assert_eq!(map_command("iunmap"), command(Some('i'), true));
+        assert_eq!(map_command("map!"), command(Some('!'), false));
+        assert_eq!(map_command("nnoremenu"), None);
+        assert_eq!(map_command("syntax"), None);
+    }
+}
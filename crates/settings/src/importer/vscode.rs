@@ -0,0 +1,730 @@
+use anyhow::{Context as _, Result};
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+
+use crate::{default_settings, parse_json_with_comments};
+
+use super::{context_for_action, mapped_action, validate_keystrokes, ImportedConfig};
+
+/// VS Code commands and the Zed actions they map to.
+const COMMANDS: &[(&str, &str)] = &[
+    ("workbench.action.quickOpen", "file_finder::Toggle"),
+    ("workbench.action.showCommands", "command_palette::Toggle"),
+    ("workbench.action.files.save", "workspace::Save"),
+    ("workbench.action.files.saveAll", "workspace::SaveAll"),
+    ("workbench.action.files.saveAs", "workspace::SaveAs"),
+    (
+        "workbench.action.files.newUntitledFile",
+        "workspace::NewFile",
+    ),
+    ("workbench.action.newWindow", "workspace::NewWindow"),
+    ("workbench.action.closeWindow", "workspace::CloseWindow"),
+    (
+        "workbench.action.closeActiveEditor",
+        "pane::CloseActiveItem",
+    ),
+    ("workbench.action.closeAllEditors", "pane::CloseAllItems"),
+    (
+        "workbench.action.closeOtherEditors",
+        "pane::CloseInactiveItems",
+    ),
+    (
+        "workbench.action.reopenClosedEditor",
+        "pane::ReopenClosedItem",
+    ),
+    ("workbench.action.nextEditor", "pane::ActivateNextItem"),
+    ("workbench.action.previousEditor", "pane::ActivatePrevItem"),
+    ("workbench.action.splitEditor", "pane::SplitRight"),
+    ("workbench.action.splitEditorDown", "pane::SplitDown"),
+    ("workbench.action.navigateBack", "pane::GoBack"),
+    ("workbench.action.navigateForward", "pane::GoForward"),
+    (
+        "workbench.action.toggleSidebarVisibility",
+        "workspace::ToggleLeftDock",
+    ),
+    (
+        "workbench.action.togglePanel",
+        "workspace::ToggleBottomDock",
+    ),
+    (
+        "workbench.action.toggleAuxiliaryBar",
+        "workspace::ToggleRightDock",
+    ),
+    (
+        "workbench.action.toggleMaximizedPanel",
+        "workspace::ToggleZoom",
+    ),
+    ("workbench.action.toggleFullScreen", "zed::ToggleFullScreen"),
+    (
+        "workbench.action.terminal.toggleTerminal",
+        "terminal_panel::ToggleFocus",
+    ),
+    ("workbench.action.terminal.new", "workspace::NewTerminal"),
+    ("workbench.view.explorer", "project_panel::ToggleFocus"),
+    ("workbench.action.findInFiles", "pane::DeploySearch"),
+    ("workbench.action.gotoSymbol", "outline::Toggle"),
+    ("workbench.action.showAllSymbols", "project_symbols::Toggle"),
+    ("workbench.action.gotoLine", "go_to_line::Toggle"),
+    ("workbench.action.openRecent", "projects::OpenRecent"),
+    ("workbench.action.openSettings", "zed::OpenSettings"),
+    ("workbench.action.openGlobalKeybindings", "zed::OpenKeymap"),
+    ("workbench.action.selectTheme", "theme_selector::Toggle"),
+    (
+        "workbench.action.editor.changeLanguageMode",
+        "language_selector::Toggle",
+    ),
+    ("workbench.action.zoomIn", "zed::IncreaseBufferFontSize"),
+    ("workbench.action.zoomOut", "zed::DecreaseBufferFontSize"),
+    ("workbench.action.zoomReset", "zed::ResetBufferFontSize"),
+    ("workbench.action.tasks.runTask", "task::Spawn"),
+    ("workbench.action.tasks.reRunTask", "task::Rerun"),
+    ("workbench.actions.view.problems", "diagnostics::Deploy"),
+    ("actions.find", "buffer_search::Deploy"),
+    (
+        "editor.action.startFindReplaceAction",
+        "buffer_search::DeployReplace",
+    ),
+    ("editor.action.commentLine", "editor::ToggleComments"),
+    ("editor.action.formatDocument", "editor::Format"),
+    ("editor.action.rename", "editor::Rename"),
+    ("editor.action.revealDefinition", "editor::GoToDefinition"),
+    (
+        "editor.action.revealDefinitionAside",
+        "editor::GoToDefinitionSplit",
+    ),
+    (
+        "editor.action.goToTypeDefinition",
+        "editor::GoToTypeDefinition",
+    ),
+    (
+        "editor.action.goToImplementation",
+        "editor::GoToImplementation",
+    ),
+    ("editor.action.goToReferences", "editor::FindAllReferences"),
+    (
+        "editor.action.referenceSearch.trigger",
+        "editor::FindAllReferences",
+    ),
+    ("editor.action.quickFix", "editor::ToggleCodeActions"),
+    ("editor.action.showHover", "editor::Hover"),
+    ("editor.action.triggerSuggest", "editor::ShowCompletions"),
+    (
+        "editor.action.triggerParameterHints",
+        "editor::ShowSignatureHelp",
+    ),
+    ("editor.action.moveLinesUpAction", "editor::MoveLineUp"),
+    ("editor.action.moveLinesDownAction", "editor::MoveLineDown"),
+    ("editor.action.copyLinesUpAction", "editor::DuplicateLineUp"),
+    (
+        "editor.action.copyLinesDownAction",
+        "editor::DuplicateLineDown",
+    ),
+    ("editor.action.deleteLines", "editor::DeleteLine"),
+    ("editor.action.joinLines", "editor::JoinLines"),
+    ("editor.action.insertLineBefore", "editor::NewlineAbove"),
+    ("editor.action.insertLineAfter", "editor::NewlineBelow"),
+    (
+        "editor.action.insertCursorAbove",
+        "editor::AddSelectionAbove",
+    ),
+    (
+        "editor.action.insertCursorBelow",
+        "editor::AddSelectionBelow",
+    ),
+    (
+        "editor.action.insertCursorAtEndOfEachLineSelected",
+        "editor::SplitSelectionIntoLines",
+    ),
+    (
+        "editor.action.addSelectionToNextFindMatch",
+        r#"["editor::SelectNext", { "replace_newest": false }]"#,
+    ),
+    (
+        "editor.action.moveSelectionToNextFindMatch",
+        r#"["editor::SelectNext", { "replace_newest": true }]"#,
+    ),
+    ("editor.action.selectHighlights", "editor::SelectAllMatches"),
+    (
+        "editor.action.smartSelect.expand",
+        "editor::SelectLargerSyntaxNode",
+    ),
+    (
+        "editor.action.smartSelect.shrink",
+        "editor::SelectSmallerSyntaxNode",
+    ),
+    (
+        "editor.action.jumpToBracket",
+        "editor::MoveToEnclosingBracket",
+    ),
+    ("editor.action.marker.next", "editor::GoToDiagnostic"),
+    ("editor.action.marker.prev", "editor::GoToPrevDiagnostic"),
+    (
+        "editor.action.transformToUppercase",
+        "editor::ConvertToUpperCase",
+    ),
+    (
+        "editor.action.transformToLowercase",
+        "editor::ConvertToLowerCase",
+    ),
+    (
+        "editor.action.sortLinesAscending",
+        "editor::SortLinesCaseSensitive",
+    ),
+    ("editor.action.indentLines", "editor::Indent"),
+    ("editor.action.outdentLines", "editor::Outdent"),
+    ("editor.fold", "editor::Fold"),
+    ("editor.unfold", "editor::UnfoldLines"),
+    ("editor.foldAll", "editor::FoldAll"),
+    ("editor.unfoldAll", "editor::UnfoldAll"),
+    ("editor.toggleFold", "editor::ToggleFold"),
+    ("expandLineSelection", "editor::SelectLine"),
+    ("cursorUndo", "editor::UndoSelection"),
+    ("cursorRedo", "editor::RedoSelection"),
+    ("undo", "editor::Undo"),
+    ("redo", "editor::Redo"),
+];
+
+/// Clauses of `when` conditions that hold wherever the context of the bound action applies.
+const IMPLIED_CONDITIONS: &[&str] = &[
+    "editorTextFocus",
+    "editorFocus",
+    "textInputFocus",
+    "!editorReadonly",
+    "terminalFocus",
+];
+
+/// Language identifiers of VS Code and the names of the corresponding Zed languages.
+const LANGUAGES: &[(&str, &str)] = &[
+    ("c", "C"),
+    ("cpp", "C++"),
+    ("css", "CSS"),
+    ("go", "Go"),
+    ("html", "HTML"),
+    ("javascript", "JavaScript"),
+    ("javascriptreact", "JavaScript"),
+    ("json", "JSON"),
+    ("jsonc", "JSONC"),
+    ("markdown", "Markdown"),
+    ("python", "Python"),
+    ("rust", "Rust"),
+    ("shellscript", "Shell Script"),
+    ("typescript", "TypeScript"),
+    ("typescriptreact", "TSX"),
+    ("yaml", "YAML"),
+];
+
+/// Zed settings that can be overridden per language.
+const LANGUAGE_SETTINGS: &[&str] = &[
+    "tab_size",
+    "hard_tabs",
+    "soft_wrap",
+    "preferred_line_length",
+    "wrap_guides",
+    "format_on_save",
+    "show_whitespaces",
+    "remove_trailing_whitespace_on_save",
+    "ensure_final_newline_on_save",
+    "linked_edits",
+    "use_autoclose",
+    "inlay_hints.enabled",
+    "indent_guides.enabled",
+];
+
+enum Conversion {
+    Settings(Vec<(&'static str, Value)>),
+    UnsupportedValue,
+    NoEquivalent,
+}
+
+pub(super) fn import_settings(content: &str) -> Result<ImportedConfig> {
+    let settings: Map<String, Value> =
+        parse_json_with_comments(content).context("invalid VS Code settings")?;
+    let mut config = ImportedConfig::default();
+    for (key, value) in &settings {
+        if let Some(language_ids) = language_scope(key) {
+            let Some(overrides) = value.as_object() else {
+                config.unmapped(key, "expected an object of settings");
+                continue;
+            };
+            for language_id in language_ids {
+                let Some((_, language)) = LANGUAGES.iter().find(|(id, _)| *id == language_id)
+                else {
+                    config.unmapped(key, format!("unknown language `{language_id}`"));
+                    continue;
+                };
+                for (key, value) in overrides {
+                    match convert_setting(key, value, overrides) {
+                        Conversion::Settings(settings)
+                            if settings
+                                .iter()
+                                .all(|(key_path, _)| LANGUAGE_SETTINGS.contains(key_path)) =>
+                        {
+                            for (key_path, value) in settings {
+                                config.set(&format!("languages.{language}.{key_path}"), value);
+                            }
+                        }
+                        Conversion::Settings(_) | Conversion::NoEquivalent => config.unmapped(
+                            format!("[{language_id}] {key}"),
+                            "no language-specific Zed equivalent",
+                        ),
+                        Conversion::UnsupportedValue => config.unmapped(
+                            format!("[{language_id}] {key}: {value}"),
+                            "unsupported value",
+                        ),
+                    }
+                }
+            }
+            continue;
+        }
+
+        match convert_setting(key, value, &settings) {
+            Conversion::Settings(settings) => {
+                for (key_path, value) in settings {
+                    config.set(key_path, value);
+                }
+            }
+            Conversion::UnsupportedValue => {
+                config.unmapped(format!("{key}: {value}"), "unsupported value")
+            }
+            Conversion::NoEquivalent => config.unmapped(key, "no Zed equivalent"),
+        }
+    }
+    Ok(config)
+}
+
+/// The language identifiers of a language-specific block, like `[javascript][typescript]`.
+fn language_scope(key: &str) -> Option<Vec<&str>> {
+    let ids = key.strip_prefix('[')?.strip_suffix(']')?;
+    Some(ids.split("][").collect())
+}
+
+/// Converts a VS Code setting into Zed settings, given all the settings it's set along with.
+fn convert_setting(key: &str, value: &Value, settings: &Map<String, Value>) -> Conversion {
+    use Conversion::*;
+
+    let one = |key_path, value: Option<Value>| match value {
+        Some(value) => Settings(vec![(key_path, value)]),
+        None => UnsupportedValue,
+    };
+    let number = || value.as_f64().map(|_| value.clone());
+    let boolean = || value.as_bool().map(Value::Bool);
+    let string = || value.as_str().map(|_| value.clone());
+    let enumerated = |pairs: &[(&str, Value)]| {
+        let value = value.as_str()?;
+        pairs
+            .iter()
+            .find(|(name, _)| *name == value)
+            .map(|(_, zed_value)| zed_value.clone())
+    };
+
+    match key {
+        "editor.fontSize" => one("buffer_font_size", number()),
+        "editor.fontFamily" => one(
+            "buffer_font_family",
+            value
+                .as_str()
+                .and_then(|families| families.split(',').next())
+                .map(|family| json!(family.trim().trim_matches(['\'', '"']))),
+        ),
+        "editor.tabSize" => one("tab_size", number()),
+        "editor.insertSpaces" => one("hard_tabs", value.as_bool().map(|spaces| json!(!spaces))),
+        "editor.wordWrap" => one(
+            "soft_wrap",
+            enumerated(&[
+                ("off", json!("none")),
+                ("on", json!("editor_width")),
+                ("wordWrapColumn", json!("preferred_line_length")),
+                ("bounded", json!("bounded")),
+            ]),
+        ),
+        "editor.wordWrapColumn" => one("preferred_line_length", number()),
+        "editor.rulers" => one(
+            "wrap_guides",
+            value.as_array().and_then(|rulers| {
+                rulers
+                    .iter()
+                    .map(|ruler| ruler.get("column").unwrap_or(ruler).as_u64())
+                    .collect::<Option<Vec<_>>>()
+                    .map(|columns| json!(columns))
+            }),
+        ),
+        "editor.formatOnSave" => one(
+            "format_on_save",
+            value
+                .as_bool()
+                .map(|format| json!(if format { "on" } else { "off" })),
+        ),
+        "editor.lineNumbers" => match value.as_str() {
+            Some("on") => Settings(vec![
+                ("gutter.line_numbers", json!(true)),
+                ("relative_line_numbers", json!(false)),
+            ]),
+            Some("relative") => Settings(vec![
+                ("gutter.line_numbers", json!(true)),
+                ("relative_line_numbers", json!(true)),
+            ]),
+            Some("off") => Settings(vec![("gutter.line_numbers", json!(false))]),
+            _ => UnsupportedValue,
+        },
+        "editor.renderWhitespace" => one(
+            "show_whitespaces",
+            enumerated(&[
+                ("none", json!("none")),
+                ("all", json!("all")),
+                ("boundary", json!("boundary")),
+                ("selection", json!("selection")),
+            ]),
+        ),
+        "editor.renderLineHighlight" => one(
+            "current_line_highlight",
+            enumerated(&[
+                ("none", json!("none")),
+                ("gutter", json!("gutter")),
+                ("line", json!("line")),
+                ("all", json!("all")),
+            ]),
+        ),
+        "editor.cursorBlinking" => one(
+            "cursor_blink",
+            value.as_str().map(|blinking| json!(blinking != "solid")),
+        ),
+        "editor.cursorStyle" => one(
+            "cursor_shape",
+            enumerated(&[
+                ("line", json!("bar")),
+                ("line-thin", json!("bar")),
+                ("block", json!("block")),
+                ("block-outline", json!("hollow")),
+                ("underline", json!("underline")),
+                ("underline-thin", json!("underline")),
+            ]),
+        ),
+        "editor.scrollBeyondLastLine" => one(
+            "scroll_beyond_last_line",
+            value
+                .as_bool()
+                .map(|beyond| json!(if beyond { "one_page" } else { "off" })),
+        ),
+        "editor.cursorSurroundingLines" => one("vertical_scroll_margin", number()),
+        "editor.hover.enabled" => one("hover_popover_enabled", boolean()),
+        "editor.inlayHints.enabled" => one(
+            "inlay_hints.enabled",
+            value
+                .as_str()
+                .map(|enabled| json!(enabled != "off"))
+                .or_else(boolean),
+        ),
+        "editor.guides.indentation" => one("indent_guides.enabled", boolean()),
+        "editor.minimap.enabled" => one(
+            "minimap.show",
+            value
+                .as_bool()
+                .map(|enabled| json!(if enabled { "always" } else { "never" })),
+        ),
+        "editor.minimap.side" => one(
+            "minimap.side",
+            enumerated(&[("left", json!("left")), ("right", json!("right"))]),
+        ),
+        "editor.stickyScroll.enabled" => one("sticky_scroll.enabled", boolean()),
+        "editor.autoClosingBrackets" => one(
+            "use_autoclose",
+            value.as_str().map(|autoclose| json!(autoclose != "never")),
+        ),
+        "editor.linkedEditing" => one("linked_edits", boolean()),
+        "editor.multiCursorModifier" => one(
+            "multi_cursor_modifier",
+            enumerated(&[("ctrlCmd", json!("cmd_or_ctrl")), ("alt", json!("alt"))]),
+        ),
+        "files.autoSave" => one(
+            "autosave",
+            match value.as_str() {
+                Some("off") => Some(json!("off")),
+                Some("onFocusChange") => Some(json!("on_focus_change")),
+                Some("onWindowChange") => Some(json!("on_window_change")),
+                Some("afterDelay") => {
+                    let milliseconds = settings
+                        .get("files.autoSaveDelay")
+                        .and_then(Value::as_u64)
+                        .unwrap_or(1000);
+                    Some(json!({ "after_delay": { "milliseconds": milliseconds } }))
+                }
+                _ => None,
+            },
+        ),
+        // Imported along with `files.autoSave`.
+        "files.autoSaveDelay" => Settings(Vec::new()),
+        "files.trimTrailingWhitespace" => one("remove_trailing_whitespace_on_save", boolean()),
+        "files.insertFinalNewline" => one("ensure_final_newline_on_save", boolean()),
+        // Zed's setting replaces its default exclusions, so they're kept alongside the imported ones.
+        "files.exclude" => one(
+            "file_scan_exclusions",
+            value.as_object().map(|patterns| {
+                let mut exclusions = default_file_scan_exclusions();
+                for (pattern, excluded) in patterns {
+                    if excluded.as_bool() == Some(true) && !exclusions.contains(pattern) {
+                        exclusions.push(pattern.clone());
+                    }
+                }
+                json!(exclusions)
+            }),
+        ),
+        "workbench.colorTheme" => one("theme", string()),
+        "workbench.sideBar.location" => one(
+            "project_panel.dock",
+            enumerated(&[("left", json!("left")), ("right", json!("right"))]),
+        ),
+        "explorer.autoReveal" => one("project_panel.auto_reveal_entries", boolean()),
+        "terminal.integrated.fontSize" => one("terminal.font_size", number()),
+        "terminal.integrated.fontFamily" => one("terminal.font_family", string()),
+        "vim.useSystemClipboard" => one(
+            "vim.use_system_clipboard",
+            value
+                .as_bool()
+                .map(|system| json!(if system { "always" } else { "never" })),
+        ),
+        _ => NoEquivalent,
+    }
+}
+
+/// The `file_scan_exclusions` from Zed's default settings.
+fn default_file_scan_exclusions() -> Vec<String> {
+    parse_json_with_comments::<Map<String, Value>>(&default_settings())
+        .ok()
+        .and_then(|settings| {
+            serde_json::from_value(settings.get("file_scan_exclusions")?.clone()).ok()
+        })
+        .unwrap_or_default()
+}
+
+#[derive(Deserialize)]
+struct VsCodeKeybinding {
+    key: String,
+    command: String,
+    when: Option<String>,
+    args: Option<Value>,
+}
+
+pub(super) fn import_keymap(content: &str) -> Result<ImportedConfig> {
+    let keybindings: Vec<VsCodeKeybinding> =
+        parse_json_with_comments(content).context("invalid VS Code key bindings")?;
+    let mut config = ImportedConfig::default();
+    for keybinding in keybindings {
+        let entry = format!("{}: {}", keybinding.key, keybinding.command);
+        let (command, unbind) = match keybinding.command.strip_prefix('-') {
+            Some(command) => (command, true),
+            None => (keybinding.command.as_str(), false),
+        };
+        let Some((_, action)) = COMMANDS.iter().find(|(name, _)| *name == command) else {
+            config.unmapped(entry, "no equivalent Zed action");
+            continue;
+        };
+        let keystrokes = keystrokes(&keybinding.key);
+        if let Err(error) = validate_keystrokes(&keystrokes) {
+            config.unmapped(entry, error);
+            continue;
+        }
+
+        let action = mapped_action(action);
+        let context = context_for_action(&action);
+        if let Some(when) = &keybinding.when {
+            if when
+                .split("&&")
+                .any(|condition| !IMPLIED_CONDITIONS.contains(&condition.trim()))
+            {
+                config.unmapped(
+                    &entry,
+                    format!(
+                        "the condition `{when}` was dropped, the binding applies {}",
+                        context.map_or("everywhere".to_string(), |context| format!(
+                            "in the `{context}` context"
+                        ))
+                    ),
+                );
+            }
+        }
+        if keybinding.args.is_some() && !unbind {
+            config.unmapped(&entry, "the command arguments were dropped");
+        }
+        config.bind(context, keystrokes, (!unbind).then_some(action));
+    }
+    Ok(config)
+}
+
+/// Converts VS Code keystrokes, like `ctrl+k ctrl+shift+s`, to Zed keystrokes.
+fn keystrokes(key: &str) -> String {
+    key.split_whitespace()
+        .map(|chord| {
+            let (modifiers, key) = match chord.strip_suffix("++") {
+                Some(modifiers) => (modifiers, "+"),
+                None => chord.rsplit_once('+').unwrap_or(("", chord)),
+            };
+            let mut keystroke = String::new();
+            for modifier in modifiers.split('+').filter(|modifier| !modifier.is_empty()) {
+                keystroke.push_str(match modifier.to_lowercase().as_str() {
+                    "ctrl" => "ctrl",
+                    "shift" => "shift",
+                    "alt" => "alt",
+                    "cmd" => "cmd",
+                    "meta" | "win" => "super",
+                    _ => modifier,
+                });
+                keystroke.push('-');
+            }
+            keystroke.push_str(&key.to_lowercase());
+            keystroke
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::importer::{ImportedAction, ImportedBinding, ImportedSetting};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_import_vscode_settings() {
+        let config = import_settings(
+            r#"{
+                // Comments are allowed.
+                "editor.fontSize": 13,
+                "editor.fontFamily": "'Fira Code', monospace",
+                "editor.insertSpaces": false,
+                "editor.lineNumbers": "relative",
+                "editor.cursorStyle": "sideways",
+                "files.autoSave": "afterDelay",
+                "files.autoSaveDelay": 500,
+                "files.exclude": { "**/target": true, "**/.git": true, "**/*.log": false },
+                "editor.fontLigatures": true,
+                "[rust][python]": {
+                    "editor.tabSize": 4,
+                    "editor.defaultFormatter": "rust-lang.rust-analyzer"
+                },
+                "[cobol]": { "editor.tabSize": 8 },
+            }"#,
+        )
+        .unwrap();
+
+        let setting = |key_path: &str, value| ImportedSetting {
+            key_path: key_path.split('.').map(str::to_string).collect(),
+            value,
+        };
+        assert_eq!(
+            config.settings,
+            vec![
+                setting("buffer_font_size", json!(13)),
+                setting("buffer_font_family", json!("Fira Code")),
+                setting("hard_tabs", json!(true)),
+                setting("gutter.line_numbers", json!(true)),
+                setting("relative_line_numbers", json!(true)),
+                setting(
+                    "autosave",
+                    json!({ "after_delay": { "milliseconds": 500 } })
+                ),
+                setting("file_scan_exclusions", {
+                    let mut exclusions = default_file_scan_exclusions();
+                    assert!(exclusions.contains(&"**/.git".to_string()));
+                    exclusions.push("**/target".to_string());
+                    json!(exclusions)
+                }),
+                setting("languages.Rust.tab_size", json!(4)),
+                setting("languages.Python.tab_size", json!(4)),
+            ]
+        );
+        assert_eq!(
+            config
+                .unmapped
+                .iter()
+                .map(|entry| entry.entry.as_str())
+                .collect::<Vec<_>>(),
+            vec![
+                "editor.cursorStyle: \"sideways\"",
+                "editor.fontLigatures",
+                "[rust] editor.defaultFormatter",
+                "[python] editor.defaultFormatter",
+                "[cobol]",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_import_vscode_keymap() {
+        let config = import_keymap(
+            r#"[
+                { "key": "ctrl+shift+d", "command": "editor.action.copyLinesDownAction", "when": "editorTextFocus && !editorReadonly" },
+                { "key": "ctrl+k ctrl+x", "command": "workbench.action.closeActiveEditor" },
+                { "key": "ctrl+d", "command": "-editor.action.addSelectionToNextFindMatch", "when": "editorFocus" },
+                { "key": "ctrl+alt+n", "command": "extension.runCode" },
+                { "key": "ctrl+j", "command": "editor.action.joinLines", "when": "editorHasSelection" },
+            ]"#,
+        )
+        .unwrap();
+
+        let action = |name: &str| {
+            Some(ImportedAction {
+                name: name.into(),
+                input: None,
+            })
+        };
+        assert_eq!(
+            config.bindings,
+            vec![
+                ImportedBinding {
+                    context: Some("Editor".into()),
+                    keystrokes: "ctrl-shift-d".into(),
+                    action: action("editor::DuplicateLineDown"),
+                },
+                ImportedBinding {
+                    context: None,
+                    keystrokes: "ctrl-k ctrl-x".into(),
+                    action: action("pane::CloseActiveItem"),
+                },
+                ImportedBinding {
+                    context: Some("Editor".into()),
+                    keystrokes: "ctrl-d".into(),
+                    action: None,
+                },
+                ImportedBinding {
+                    context: Some("Editor".into()),
+                    keystrokes: "ctrl-j".into(),
+                    action: action("editor::JoinLines"),
+                },
+            ]
+        );
+        assert_eq!(
+            config
+                .unmapped
+                .iter()
+                .map(|entry| (entry.entry.as_str(), entry.reason.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("ctrl+alt+n: extension.runCode", "no equivalent Zed action"),
+                (
+                    "ctrl+j: editor.action.joinLines",
+                    "the condition `editorHasSelection` was dropped, the binding applies in the `Editor` context"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_vscode_keystrokes() {
+        assert_eq!(keystrokes("ctrl+shift+p"), "ctrl-shift-p");
+        assert_eq!(keystrokes("ctrl+k ctrl+c"), "ctrl-k ctrl-c");
+        assert_eq!(keystrokes("cmd+="), "cmd-=");
+        assert_eq!(keystrokes("ctrl++"), "ctrl-+");
+        assert_eq!(keystrokes("meta+Enter"), "super-enter");
+        assert_eq!(keystrokes("f5"), "f5");
+    }
+
+    #[test]
+    fn test_command_mappings_are_valid() {
+        for (_, action) in COMMANDS {
+            assert!(action.contains("::"), "{action}");
+            if action.starts_with('[') {
+                assert!(mapped_action(action).input.is_some(), "{action}");
+            }
+        }
+    }
+}
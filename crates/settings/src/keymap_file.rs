@@ -1,11 +1,11 @@
 use std::rc::Rc;
 
 use crate::{settings_store::parse_json_with_comments, SettingsAssets};
-use anyhow::anyhow;
+use anyhow::{anyhow, Context as _};
 use collections::{HashMap, IndexMap};
 use gpui::{
     Action, ActionBuildError, App, InvalidKeystrokeError, KeyBinding, KeyBindingContextPredicate,
-    NoAction, SharedString, KEYSTROKE_PARSE_EXPECTED_MESSAGE,
+    KeyBindingMetaIndex, NoAction, SharedString, KEYSTROKE_PARSE_EXPECTED_MESSAGE,
 };
 use schemars::{
     gen::{SchemaGenerator, SchemaSettings},
@@ -15,6 +15,7 @@ use schemars::{
 use serde::Deserialize;
 use serde_json::Value;
 use std::fmt::Write;
+use std::ops::Range;
 use util::{asset_str, markdown::MarkdownString};
 
 // Note that the doc comments on these are shown by json-language-server when editing the keymap, so
@@ -124,6 +125,79 @@ pub enum KeymapFileLoadResult {
     },
 }
 
+/// The keymap a key binding was loaded from, stored in the binding's [`KeyBindingMetaIndex`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum KeybindSource {
+    User,
+    Default,
+    /// The base keymap selected in the settings, like the JetBrains or Sublime Text keymaps.
+    Base,
+    Vim,
+}
+
+impl KeybindSource {
+    pub fn name(self) -> &'static str {
+        match self {
+            KeybindSource::User => "User",
+            KeybindSource::Default => "Default",
+            KeybindSource::Base => "Base Keymap",
+            KeybindSource::Vim => "Vim",
+        }
+    }
+
+    pub fn meta(self) -> KeyBindingMetaIndex {
+        KeyBindingMetaIndex(self as u32)
+    }
+
+    pub fn from_meta(index: KeyBindingMetaIndex) -> Option<Self> {
+        [Self::User, Self::Default, Self::Base, Self::Vim]
+            .into_iter()
+            .find(|source| source.meta() == index)
+    }
+}
+
+/// A binding in a keymap file, identified by its context and keystrokes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KeybindUpdateTarget<'a> {
+    pub context: Option<&'a str>,
+    pub keystrokes: &'a str,
+    pub action_name: &'a str,
+    /// The JSON input of the action, if any.
+    pub action_input: Option<&'a str>,
+}
+
+impl KeybindUpdateTarget<'_> {
+    fn action_value(&self) -> anyhow::Result<Value> {
+        Ok(match self.action_input {
+            Some(input) => Value::Array(vec![
+                Value::String(self.action_name.to_string()),
+                serde_json::from_str(input).context("invalid action input")?,
+            ]),
+            None => Value::String(self.action_name.to_string()),
+        })
+    }
+}
+
+/// A change to the user keymap, see [`KeymapFile::update_keybinding`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeybindUpdateOperation<'a> {
+    /// Binds keystrokes to an action.
+    Add(KeybindUpdateTarget<'a>),
+    /// Changes the keystrokes of a binding. A binding that isn't in the user keymap is unbound
+    /// there and bound again with the new keystrokes.
+    Replace {
+        source: KeybindSource,
+        target: KeybindUpdateTarget<'a>,
+        new_keystrokes: &'a str,
+    },
+    /// Removes a binding from the user keymap, or unbinds it there when it comes from another
+    /// keymap.
+    Remove {
+        source: KeybindSource,
+        target: KeybindUpdateTarget<'a>,
+    },
+}
+
 impl KeymapFile {
     pub fn parse(content: &str) -> anyhow::Result<Self> {
         parse_json_with_comments::<Self>(content)
@@ -357,7 +431,13 @@ impl KeymapFile {
             },
         };
 
-        match KeyBinding::load(keystrokes, action, context, key_equivalents) {
+        match KeyBinding::load(
+            keystrokes,
+            action,
+            context,
+            key_equivalents,
+            action_input_string.map(SharedString::from),
+        ) {
             Ok(binding) => Ok(binding),
             Err(InvalidKeystrokeError { keystroke }) => Err(format!(
                 "invalid keystroke {}. {}",
@@ -551,6 +631,328 @@ impl KeymapFile {
     pub fn sections(&self) -> impl DoubleEndedIterator<Item = &KeymapSection> {
         self.0.iter()
     }
+
+    /// Applies the change to the text of the user keymap, preserving its comments and formatting.
+    pub fn update_keybinding(
+        operation: KeybindUpdateOperation,
+        keymap_contents: String,
+        tab_size: usize,
+    ) -> anyhow::Result<String> {
+        // Make sure the keymap is valid, so that the edit lands where it's expected.
+        if !keymap_contents.trim().is_empty() {
+            Self::parse(&keymap_contents)?;
+        }
+        match operation {
+            KeybindUpdateOperation::Add(target) => add_binding_in_json_text(
+                keymap_contents,
+                target.context,
+                target.keystrokes,
+                &target.action_value()?,
+                tab_size,
+            ),
+            KeybindUpdateOperation::Replace {
+                source,
+                target,
+                new_keystrokes,
+            } => {
+                if source == KeybindSource::User {
+                    if let Some(binding) = find_binding_in_json_text(&keymap_contents, &target) {
+                        let new_key = serde_json::to_string(new_keystrokes)?;
+                        return Ok(apply_edit(keymap_contents, (binding.key_range, new_key)));
+                    }
+                }
+                let keymap_contents = add_binding_in_json_text(
+                    keymap_contents,
+                    target.context,
+                    new_keystrokes,
+                    &target.action_value()?,
+                    tab_size,
+                )?;
+                add_binding_in_json_text(
+                    keymap_contents,
+                    target.context,
+                    target.keystrokes,
+                    &Value::Null,
+                    tab_size,
+                )
+            }
+            KeybindUpdateOperation::Remove { source, target } => {
+                if source == KeybindSource::User {
+                    if let Some(binding) = find_binding_in_json_text(&keymap_contents, &target) {
+                        let mut keymap_contents = keymap_contents;
+                        for range in binding.removal_ranges.into_iter().rev() {
+                            keymap_contents.replace_range(range, "");
+                        }
+                        return Ok(keymap_contents);
+                    }
+                }
+                add_binding_in_json_text(
+                    keymap_contents,
+                    target.context,
+                    target.keystrokes,
+                    &Value::Null,
+                    tab_size,
+                )
+            }
+        }
+    }
+}
+
+/// A binding in the text of a keymap file.
+struct JsonBinding {
+    key_range: Range<usize>,
+    /// The ranges to remove to delete the binding, in order.
+    removal_ranges: Vec<Range<usize>>,
+}
+
+/// A section of a keymap file, in the syntax tree of its text.
+struct JsonSection<'t> {
+    object: tree_sitter::Node<'t>,
+    context: Option<String>,
+    bindings: Option<tree_sitter::Node<'t>>,
+}
+
+fn parse_json_text(text: &str) -> tree_sitter::Tree {
+    let mut parser = tree_sitter::Parser::new();
+    parser
+        .set_language(&tree_sitter_json::LANGUAGE.into())
+        .unwrap();
+    parser.parse(text, None).unwrap()
+}
+
+/// Returns the `key` and `value` of a `pair` node, with the key unescaped.
+fn json_pair<'t>(
+    pair: tree_sitter::Node<'t>,
+    text: &str,
+) -> Option<(String, tree_sitter::Node<'t>, tree_sitter::Node<'t>)> {
+    let key = pair.child_by_field_name("key")?;
+    let value = pair.child_by_field_name("value")?;
+    let key_text = serde_json::from_str(text.get(key.byte_range())?).ok()?;
+    Some((key_text, key, value))
+}
+
+fn json_sections<'t>(tree: &'t tree_sitter::Tree, text: &str) -> Vec<JsonSection<'t>> {
+    let root = tree.root_node();
+    let mut cursor = root.walk();
+    let Some(array) = root
+        .named_children(&mut cursor)
+        .find(|node| node.kind() == "array")
+    else {
+        return Vec::new();
+    };
+    let mut cursor = array.walk();
+    array
+        .named_children(&mut cursor)
+        .filter(|node| node.kind() == "object")
+        .map(|object| {
+            let mut section = JsonSection {
+                object,
+                context: None,
+                bindings: None,
+            };
+            let mut cursor = object.walk();
+            for pair in object.named_children(&mut cursor) {
+                match json_pair(pair, text) {
+                    Some((key, _, value)) if key == "context" => {
+                        section.context = text
+                            .get(value.byte_range())
+                            .and_then(|value| serde_json::from_str(value).ok());
+                    }
+                    Some((key, _, value)) if key == "bindings" && value.kind() == "object" => {
+                        section.bindings = Some(value);
+                    }
+                    _ => {}
+                }
+            }
+            section
+        })
+        .collect()
+}
+
+fn contexts_match(a: Option<&str>, b: Option<&str>) -> bool {
+    let a = a.map(str::trim).filter(|context| !context.is_empty());
+    let b = b.map(str::trim).filter(|context| !context.is_empty());
+    match (a, b) {
+        (None, None) => true,
+        (Some(a), Some(b)) => {
+            a == b
+                || KeyBindingContextPredicate::parse(a)
+                    .ok()
+                    .zip(KeyBindingContextPredicate::parse(b).ok())
+                    .is_some_and(|(a, b)| a == b)
+        }
+        _ => false,
+    }
+}
+
+fn keystrokes_match(a: &str, b: &str) -> bool {
+    a.split_whitespace().eq(b.split_whitespace())
+}
+
+/// Finds the binding with the target's context and keystrokes that takes effect, which is the
+/// last one in the file.
+fn find_binding_in_json_text(text: &str, target: &KeybindUpdateTarget) -> Option<JsonBinding> {
+    let tree = parse_json_text(text);
+    let sections = json_sections(&tree, text);
+    sections
+        .iter()
+        .rev()
+        .filter(|section| contexts_match(section.context.as_deref(), target.context))
+        .find_map(|section| {
+            let bindings = section.bindings?;
+            let mut cursor = bindings.walk();
+            let pairs = bindings
+                .named_children(&mut cursor)
+                .filter(|node| node.kind() == "pair")
+                .collect::<Vec<_>>();
+            pairs.into_iter().rev().find_map(|pair| {
+                let (keystrokes, key, _) = json_pair(pair, text)?;
+                keystrokes_match(&keystrokes, target.keystrokes).then(|| JsonBinding {
+                    key_range: key.byte_range(),
+                    removal_ranges: removal_ranges(pair, text),
+                })
+            })
+        })
+}
+
+/// The ranges to remove to delete a node from a comma-separated list, along with its separator.
+/// Comments around the node are kept.
+fn removal_ranges(node: tree_sitter::Node, text: &str) -> Vec<Range<usize>> {
+    let line_start = text[..node.start_byte()].rfind('\n').map_or(0, |ix| ix + 1);
+    let is_first_on_line = text[line_start..node.start_byte()].trim().is_empty();
+    if let Some(comma) = node.next_sibling().filter(|next| next.kind() == ",") {
+        // Remove the whole line when the node is the only thing on it.
+        let rest = &text[comma.end_byte()..];
+        let rest_of_line_len = rest.find('\n').map_or(rest.len(), |ix| ix + 1);
+        if is_first_on_line && rest[..rest_of_line_len].trim().is_empty() {
+            vec![line_start..comma.end_byte() + rest_of_line_len]
+        } else {
+            vec![node.start_byte()..comma.end_byte()]
+        }
+    } else {
+        let mut previous = node.prev_sibling();
+        while let Some(comment) = previous.filter(|previous| previous.kind() == "comment") {
+            previous = comment.prev_sibling();
+        }
+        let start = if is_first_on_line {
+            line_start.saturating_sub(1)
+        } else {
+            node.start_byte()
+        };
+        match previous.filter(|previous| previous.kind() == ",") {
+            Some(comma) => vec![comma.byte_range(), start..node.end_byte()],
+            None => vec![start..node.end_byte()],
+        }
+    }
+}
+
+fn line_indent(text: &str, offset: usize) -> &str {
+    let line_start = text[..offset].rfind('\n').map_or(0, |ix| ix + 1);
+    let line = &text[line_start..];
+    &line[..line.len() - line.trim_start_matches([' ', '\t']).len()]
+}
+
+/// Binds the keystrokes to the value in the last section with the given context, adding a new
+/// section at the end of the file if there is none.
+fn add_binding_in_json_text(
+    text: String,
+    context: Option<&str>,
+    keystrokes: &str,
+    value: &Value,
+    tab_size: usize,
+) -> anyhow::Result<String> {
+    let tab = " ".repeat(tab_size);
+    let keystrokes_json = serde_json::to_string(keystrokes)?;
+    let value_json = serde_json::to_string(value)?;
+    let tree = parse_json_text(&text);
+    let sections = json_sections(&tree, &text);
+
+    if let Some(bindings) = sections
+        .iter()
+        .rev()
+        .filter(|section| contexts_match(section.context.as_deref(), context))
+        .find_map(|section| section.bindings)
+    {
+        let mut cursor = bindings.walk();
+        let pairs = bindings
+            .named_children(&mut cursor)
+            .filter(|node| node.kind() == "pair")
+            .collect::<Vec<_>>();
+        let existing = pairs.iter().rev().find_map(|pair| {
+            let (existing_keystrokes, _, value) = json_pair(*pair, &text)?;
+            keystrokes_match(&existing_keystrokes, keystrokes).then(|| value.byte_range())
+        });
+        let edit = if let Some(value_range) = existing {
+            (value_range, value_json)
+        } else if let Some(last_pair) = pairs.last() {
+            let indent = line_indent(&text, last_pair.start_byte());
+            let pair = format!("{keystrokes_json}: {value_json}");
+            match last_pair.next_sibling().filter(|next| next.kind() == ",") {
+                Some(comma) => (
+                    comma.end_byte()..comma.end_byte(),
+                    format!("\n{indent}{pair},"),
+                ),
+                None => (
+                    last_pair.end_byte()..last_pair.end_byte(),
+                    format!(",\n{indent}{pair}"),
+                ),
+            }
+        } else {
+            let indent = line_indent(&text, bindings.start_byte());
+            (
+                bindings.byte_range(),
+                format!("{{\n{indent}{tab}{keystrokes_json}: {value_json}\n{indent}}}"),
+            )
+        };
+        return Ok(apply_edit(text, edit));
+    }
+
+    let mut section = vec!["{".to_string()];
+    if let Some(context) = context {
+        section.push(format!(
+            "{tab}\"context\": {},",
+            serde_json::to_string(context)?
+        ));
+    }
+    section.push(format!("{tab}\"bindings\": {{"));
+    section.push(format!("{tab}{tab}{keystrokes_json}: {value_json}"));
+    section.push(format!("{tab}}}"));
+    section.push("}".to_string());
+
+    if let Some(last_section) = sections.last() {
+        let indent = line_indent(&text, last_section.object.start_byte());
+        let section = section.join(&format!("\n{indent}"));
+        let end = last_section.object.end_byte();
+        let edit = match last_section
+            .object
+            .next_sibling()
+            .filter(|next| next.kind() == ",")
+        {
+            Some(comma) => (
+                comma.end_byte()..comma.end_byte(),
+                format!("\n{indent}{section},"),
+            ),
+            None => (end..end, format!(",\n{indent}{section}")),
+        };
+        return Ok(apply_edit(text, edit));
+    }
+
+    let section = section.join(&format!("\n{tab}"));
+    let root = tree.root_node();
+    let mut cursor = root.walk();
+    let array = root
+        .named_children(&mut cursor)
+        .find(|node| node.kind() == "array");
+    Ok(match array {
+        Some(array) => apply_edit(text, (array.byte_range(), format!("[\n{tab}{section}\n]"))),
+        None if text.trim().is_empty() => format!("[\n{tab}{section}\n]\n"),
+        None => return Err(anyhow!("the keymap file is not a JSON array")),
+    })
+}
+
+fn apply_edit(mut text: String, (range, new_text): (Range<usize>, String)) -> String {
+    text.replace_range(range, &new_text);
+    text
 }
 
 // Double quotes a string and wraps it in backticks for markdown inline code..
@@ -560,7 +962,7 @@ fn inline_code_string(text: &str) -> MarkdownString {
 
 #[cfg(test)]
 mod tests {
-    use crate::KeymapFile;
+    use crate::{KeybindSource, KeybindUpdateOperation, KeybindUpdateTarget, KeymapFile};
 
     #[test]
     fn can_deserialize_keymap_with_trailing_comma() {
@@ -576,4 +978,144 @@ mod tests {
         };
         KeymapFile::parse(json).unwrap();
     }
+
+    #[test]
+    fn test_update_keybinding() {
+        let keymap = indoc::indoc! {r#"
+            [
+              // My bindings
+              {
+                "context": "Editor",
+                "bindings": {
+                  "ctrl-a": "editor::SelectAll", // select everything
+                  "ctrl-b": ["editor::MoveLeft", { "stop_at_soft_wraps": true }]
+                }
+              }
+            ]
+        "#};
+        let update =
+            |operation| KeymapFile::update_keybinding(operation, keymap.to_string(), 2).unwrap();
+        let select_all = KeybindUpdateTarget {
+            context: Some("Editor"),
+            keystrokes: "ctrl-a",
+            action_name: "editor::SelectAll",
+            action_input: None,
+        };
+
+        pretty_assertions::assert_eq!(
+            update(KeybindUpdateOperation::Replace {
+                source: KeybindSource::User,
+                target: select_all,
+                new_keystrokes: "ctrl-shift-a",
+            }),
+            indoc::indoc! {r#"
+                [
+                  // My bindings
+                  {
+                    "context": "Editor",
+                    "bindings": {
+                      "ctrl-shift-a": "editor::SelectAll", // select everything
+                      "ctrl-b": ["editor::MoveLeft", { "stop_at_soft_wraps": true }]
+                    }
+                  }
+                ]
+            "#}
+        );
+
+        pretty_assertions::assert_eq!(
+            update(KeybindUpdateOperation::Remove {
+                source: KeybindSource::User,
+                target: KeybindUpdateTarget {
+                    context: Some("Editor"),
+                    keystrokes: "ctrl-b",
+                    action_name: "editor::MoveLeft",
+                    action_input: Some(r#"{"stop_at_soft_wraps":true}"#),
+                },
+            }),
+            indoc::indoc! {r#"
+                [
+                  // My bindings
+                  {
+                    "context": "Editor",
+                    "bindings": {
+                      "ctrl-a": "editor::SelectAll" // select everything
+                    }
+                  }
+                ]
+            "#}
+        );
+
+        pretty_assertions::assert_eq!(
+            update(KeybindUpdateOperation::Add(KeybindUpdateTarget {
+                context: Some("Editor"),
+                keystrokes: "ctrl-c",
+                action_name: "editor::Copy",
+                action_input: None,
+            })),
+            indoc::indoc! {r#"
+                [
+                  // My bindings
+                  {
+                    "context": "Editor",
+                    "bindings": {
+                      "ctrl-a": "editor::SelectAll", // select everything
+                      "ctrl-b": ["editor::MoveLeft", { "stop_at_soft_wraps": true }],
+                      "ctrl-c": "editor::Copy"
+                    }
+                  }
+                ]
+            "#}
+        );
+
+        // Bindings from other keymaps are unbound in the user keymap, and bound again.
+        pretty_assertions::assert_eq!(
+            update(KeybindUpdateOperation::Replace {
+                source: KeybindSource::Default,
+                target: KeybindUpdateTarget {
+                    context: Some("Workspace"),
+                    keystrokes: "ctrl-p",
+                    action_name: "file_finder::Toggle",
+                    action_input: None,
+                },
+                new_keystrokes: "alt-p",
+            }),
+            indoc::indoc! {r#"
+                [
+                  // My bindings
+                  {
+                    "context": "Editor",
+                    "bindings": {
+                      "ctrl-a": "editor::SelectAll", // select everything
+                      "ctrl-b": ["editor::MoveLeft", { "stop_at_soft_wraps": true }]
+                    }
+                  },
+                  {
+                    "context": "Workspace",
+                    "bindings": {
+                      "alt-p": "file_finder::Toggle",
+                      "ctrl-p": null
+                    }
+                  }
+                ]
+            "#}
+        );
+
+        pretty_assertions::assert_eq!(
+            KeymapFile::update_keybinding(
+                KeybindUpdateOperation::Remove {
+                    source: KeybindSource::Default,
+                    target: KeybindUpdateTarget {
+                        context: None,
+                        keystrokes: "ctrl-q",
+                        action_name: "zed::Quit",
+                        action_input: None,
+                    },
+                },
+                String::new(),
+                2
+            )
+            .unwrap(),
+            "[\n  {\n    \"bindings\": {\n      \"ctrl-q\": null\n    }\n  }\n]\n"
+        );
+    }
 }
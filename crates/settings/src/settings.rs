@@ -1,4 +1,5 @@
 mod editable_setting_control;
+mod importer;
 mod json_schema;
 mod key_equivalents;
 mod keymap_file;
@@ -11,13 +12,19 @@ use std::{borrow::Cow, fmt, str};
 use util::asset_str;
 
 pub use editable_setting_control::*;
+pub use importer::{
+    ImportSource, ImportedAction, ImportedBinding, ImportedConfig, ImportedSetting, UnmappedEntry,
+};
 pub use json_schema::*;
 pub use key_equivalents::*;
-pub use keymap_file::{KeymapFile, KeymapFileLoadResult};
+pub use keymap_file::{
+    KeybindSource, KeybindUpdateOperation, KeybindUpdateTarget, KeymapFile, KeymapFileLoadResult,
+};
 pub use settings_file::*;
 pub use settings_store::{
-    parse_json_with_comments, InvalidSettingsError, LocalSettingsKind, Settings, SettingsLocation,
-    SettingsSources, SettingsStore,
+    parse_json_with_comments, value_range_in_json_text, InvalidSettingsError, LocalSettingsKind,
+    Settings, SettingsFile, SettingsLayerValue, SettingsLocation, SettingsSources, SettingsStore,
+    SettingsValueSource,
 };
 
 #[derive(Copy, Clone, PartialEq, Eq, Debug, Hash, PartialOrd, Ord)]
@@ -1,6 +1,9 @@
 use anyhow::{anyhow, Context as _, Result};
 use collections::{btree_map, hash_map, BTreeMap, HashMap};
-use ec4rs::{ConfigParser, PropertiesSource, Section};
+use ec4rs::{
+    property::{FinalNewline, IndentSize, IndentStyle, TabWidth, TrimTrailingWs},
+    ConfigParser, PropertiesSource, Section,
+};
 use fs::Fs;
 use futures::{channel::mpsc, future::LocalBoxFuture, FutureExt, StreamExt};
 use gpui::{App, AsyncApp, BorrowAppContext, Global, Task, UpdateGlobal};
@@ -203,6 +206,45 @@ impl FromStr for Editorconfig {
     }
 }
 
+/// The settings a value comes from, ordered from the lowest to the highest precedence.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SettingsValueSource {
+    Default,
+    Extension,
+    User,
+    ReleaseChannel,
+    Server,
+    Project,
+    /// An `.editorconfig` file, whose properties override the language settings of the files it
+    /// applies to.
+    Editorconfig,
+}
+
+/// A value set in one of the settings layers, as listed by
+/// [`SettingsStore::value_layers_at_key_path`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct SettingsLayerValue {
+    pub source: SettingsValueSource,
+    /// For project settings and `.editorconfig` files, the directory containing the file,
+    /// relative to the worktree root.
+    pub directory: Option<Arc<Path>>,
+    /// The key path of the value within its file, or the name of the `.editorconfig` property.
+    pub key_path: Vec<String>,
+    /// Whether the value is set under `languages.<language>`, rather than for all languages.
+    pub for_language: bool,
+    pub value: serde_json::Value,
+}
+
+/// A settings file that can be edited at a key path, with [`SettingsStore::update_settings_file_at_key_path`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SettingsFile {
+    User,
+    /// The `.zed/settings.json` file of the project's worktree with the given root.
+    Project {
+        worktree_abs_path: Arc<Path>,
+    },
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum LocalSettingsKind {
     Settings,
@@ -362,6 +404,133 @@ impl SettingsStore {
         &self.raw_user_settings
     }
 
+    /// Get the default settings as a raw JSON value.
+    pub fn raw_default_settings(&self) -> &serde_json::Value {
+        &self.raw_default_settings
+    }
+
+    /// Returns every value set at the key path that applies to the given location, with where
+    /// each is set, ordered from the lowest to the highest precedence. The last value is the one
+    /// in effect.
+    ///
+    /// When a language is given, each file's values under `languages.<language>` are listed right
+    /// after its values for all languages, which they override.
+    pub fn value_layers_at_key_path(
+        &self,
+        key_path: &[&str],
+        location: Option<SettingsLocation>,
+        language: Option<&str>,
+    ) -> Vec<SettingsLayerValue> {
+        let mut files = vec![
+            (
+                SettingsValueSource::Default,
+                None,
+                Some(&self.raw_default_settings),
+            ),
+            (
+                SettingsValueSource::Extension,
+                None,
+                Some(&self.raw_extension_settings),
+            ),
+            (
+                SettingsValueSource::User,
+                None,
+                Some(&self.raw_user_settings),
+            ),
+            (
+                SettingsValueSource::ReleaseChannel,
+                None,
+                Some(&self.raw_user_settings),
+            ),
+            (
+                SettingsValueSource::Server,
+                None,
+                self.raw_server_settings.as_ref(),
+            ),
+        ];
+        if let Some(location) = location {
+            files.extend(
+                self.raw_local_settings
+                    .iter()
+                    .filter(|((worktree_id, directory), _)| {
+                        *worktree_id == location.worktree_id && location.path.starts_with(directory)
+                    })
+                    .map(|((_, directory), settings)| {
+                        (
+                            SettingsValueSource::Project,
+                            Some(directory.clone()),
+                            Some(settings),
+                        )
+                    }),
+            );
+        }
+
+        let mut layers = Vec::new();
+        for (source, directory, settings) in files {
+            let Some(settings) = settings else {
+                continue;
+            };
+            for for_language in [false, true] {
+                let mut file_key_path = Vec::new();
+                // The settings of a release channel are nested in the user settings.
+                if source == SettingsValueSource::ReleaseChannel {
+                    file_key_path.push(release_channel::RELEASE_CHANNEL.dev_name());
+                }
+                if for_language {
+                    let Some(language) = language else {
+                        continue;
+                    };
+                    file_key_path.extend(["languages", language]);
+                }
+                file_key_path.extend(key_path);
+                let value = file_key_path
+                    .iter()
+                    .try_fold(settings, |value, key| value.get(key));
+                if let Some(value) = value.filter(|value| !value.is_null()) {
+                    layers.push(SettingsLayerValue {
+                        source,
+                        directory: directory.clone(),
+                        key_path: file_key_path.iter().map(|key| key.to_string()).collect(),
+                        for_language,
+                        value: value.clone(),
+                    });
+                }
+            }
+        }
+
+        if let (Some(location), [key]) = (location, key_path) {
+            for (directory, _, editorconfig) in
+                self.local_editorconfig_settings(location.worktree_id)
+            {
+                if !location.path.starts_with(&directory) {
+                    continue;
+                }
+                let Some(editorconfig) = editorconfig else {
+                    continue;
+                };
+                if editorconfig.is_root {
+                    layers.retain(|layer| layer.source != SettingsValueSource::Editorconfig);
+                }
+                let mut properties = EditorconfigProperties::new();
+                for section in editorconfig.sections {
+                    section.apply_to(&mut properties, location.path).log_err();
+                }
+                properties.use_fallbacks();
+                if let Some((property, value)) = editorconfig_value(key, &properties) {
+                    layers.push(SettingsLayerValue {
+                        source: SettingsValueSource::Editorconfig,
+                        directory: Some(directory),
+                        key_path: vec![property.to_string()],
+                        for_language: false,
+                        value,
+                    });
+                }
+            }
+        }
+
+        layers
+    }
+
     #[cfg(any(test, feature = "test-support"))]
     pub fn test(cx: &mut App) -> Self {
         let mut this = Self::new(cx);
@@ -439,6 +608,78 @@ impl SettingsStore {
             .ok();
     }
 
+    /// Sets the value at the key path in a settings file, preserving the comments and formatting
+    /// of the rest of the file. A `null` value makes the setting fall back to its inherited value.
+    pub fn update_settings_file_at_key_path(
+        &self,
+        fs: Arc<dyn Fs>,
+        file: SettingsFile,
+        key_path: Vec<String>,
+        new_value: serde_json::Value,
+    ) {
+        self.setting_file_updates_tx
+            .unbounded_send(Box::new(move |cx: AsyncApp| {
+                async move {
+                    let path = match &file {
+                        SettingsFile::User => {
+                            let initial_path = paths::settings_file().as_path();
+                            if fs.is_file(initial_path).await {
+                                fs.canonicalize(initial_path).await.with_context(|| {
+                                    format!(
+                                        "Failed to canonicalize settings path {:?}",
+                                        initial_path
+                                    )
+                                })?
+                            } else {
+                                initial_path.to_path_buf()
+                            }
+                        }
+                        SettingsFile::Project { worktree_abs_path } => {
+                            worktree_abs_path.join(local_settings_file_relative_path())
+                        }
+                    };
+                    let old_text = match file {
+                        SettingsFile::User => Self::load_settings(&fs).await?,
+                        // Only a missing file is empty: writing over a file that failed to load
+                        // would lose its other settings.
+                        SettingsFile::Project { .. } if fs.is_file(&path).await => fs
+                            .load(&path)
+                            .await
+                            .with_context(|| format!("Failed to read settings file {path:?}"))?,
+                        SettingsFile::Project { .. } => String::new(),
+                    };
+                    let key_path = key_path.iter().map(String::as_str).collect::<Vec<_>>();
+                    let new_text = cx.read_global(|store: &SettingsStore, _| {
+                        store.new_text_for_key_path_update(old_text, &key_path, &new_value)
+                    })?;
+                    if let Some(parent) = path.parent() {
+                        fs.create_dir(parent).await?;
+                    }
+                    fs.atomic_write(path.clone(), new_text)
+                        .await
+                        .with_context(|| format!("Failed to write settings to file {:?}", path))?;
+
+                    anyhow::Ok(())
+                }
+                .boxed_local()
+            }))
+            .ok();
+    }
+
+    /// Sets the value at the key path in the text of a JSON file, returning the new text for that JSON file.
+    pub fn new_text_for_key_path_update(
+        &self,
+        old_text: String,
+        key_path: &[&str],
+        new_value: &serde_json::Value,
+    ) -> String {
+        let (range, replacement) =
+            replace_value_in_json_text(&old_text, key_path, self.json_tab_size(), new_value);
+        let mut new_text = old_text;
+        new_text.replace_range(range, &replacement);
+        new_text
+    }
+
     /// Updates the value of a setting in a JSON file, returning the new text
     /// for that JSON file.
     pub fn new_text_for_update<T: Settings>(
@@ -511,7 +752,7 @@ impl SettingsStore {
         ));
     }
 
-    fn json_tab_size(&self) -> usize {
+    pub fn json_tab_size(&self) -> usize {
         const DEFAULT_JSON_TAB_SIZE: usize = 2;
 
         if let Some((setting_type_id, callback)) = &self.tab_size_callback {
@@ -990,6 +1231,37 @@ impl SettingsStore {
     }
 }
 
+/// Returns the `.editorconfig` property that overrides the language setting with the given key,
+/// and the value it sets, mirroring how the language settings are merged with `.editorconfig`
+/// properties.
+fn editorconfig_value(
+    key: &str,
+    properties: &EditorconfigProperties,
+) -> Option<(&'static str, serde_json::Value)> {
+    match key {
+        "tab_size" => {
+            let tab_size = match properties.get::<IndentSize>().ok()? {
+                IndentSize::Value(size) => size,
+                IndentSize::UseTabWidth => match properties.get::<TabWidth>().ok()? {
+                    TabWidth::Value(width) => width,
+                },
+            };
+            Some(("indent_size", tab_size.into()))
+        }
+        "hard_tabs" => {
+            let indent_style = properties.get::<IndentStyle>().ok()?;
+            Some(("indent_style", (indent_style == IndentStyle::Tabs).into()))
+        }
+        "ensure_final_newline_on_save" => match properties.get::<FinalNewline>().ok()? {
+            FinalNewline::Value(value) => Some(("insert_final_newline", value.into())),
+        },
+        "remove_trailing_whitespace_on_save" => match properties.get::<TrimTrailingWs>().ok()? {
+            TrimTrailingWs::Value(value) => Some(("trim_trailing_whitespace", value.into())),
+        },
+        _ => None,
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum InvalidSettingsError {
     LocalSettings { path: PathBuf, message: String },
@@ -1184,12 +1456,22 @@ fn update_value_in_json_text<'a>(
     }
 }
 
-fn replace_value_in_json_text(
+/// Returns the byte range of the value set at the key path in the text of a JSON settings file.
+pub fn value_range_in_json_text(text: &str, key_path: &[&str]) -> Option<Range<usize>> {
+    if key_path.is_empty() {
+        return None;
+    }
+    let (depth, value_range, _) = find_key_path_in_json_text(text, key_path);
+    (depth == key_path.len()).then_some(value_range)
+}
+
+/// Looks for the value at the key path in JSON text. Returns how many keys of the path were
+/// found, the range of the value of the last key found, and the start of the first key within
+/// that value.
+fn find_key_path_in_json_text(
     text: &str,
     key_path: &[&str],
-    tab_size: usize,
-    new_value: &serde_json::Value,
-) -> (Range<usize>, String) {
+) -> (usize, Range<usize>, Option<usize>) {
     static PAIR_QUERY: LazyLock<Query> = LazyLock::new(|| {
         Query::new(
             &tree_sitter_json::LANGUAGE.into(),
@@ -1252,6 +1534,17 @@ fn replace_value_in_json_text(
         }
     }
 
+    (depth, existing_value_range, first_key_start)
+}
+
+fn replace_value_in_json_text(
+    text: &str,
+    key_path: &[&str],
+    tab_size: usize,
+    new_value: &serde_json::Value,
+) -> (Range<usize>, String) {
+    let (depth, existing_value_range, first_key_start) = find_key_path_in_json_text(text, key_path);
+
     // We found the exact key we want, insert the new value
     if depth == key_path.len() {
         let new_val = to_pretty_json(&new_value, tab_size, tab_size * depth);
@@ -1638,6 +1931,195 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    fn test_value_layers_at_key_path(cx: &mut App) {
+        let mut store = SettingsStore::new(cx);
+        store
+            .set_default_settings(
+                r#"{ "tab_size": 4, "languages": { "C": { "tab_size": 1 } } }"#,
+                cx,
+            )
+            .unwrap();
+        store
+            .set_user_settings(
+                r#"{ "tab_size": 2, "languages": { "Rust": { "tab_size": 8 } } }"#,
+                cx,
+            )
+            .unwrap();
+        let worktree_id = WorktreeId::from_usize(1);
+        for (directory, kind, content) in [
+            ("", LocalSettingsKind::Settings, r#"{ "tab_size": 3 }"#),
+            (
+                "crates",
+                LocalSettingsKind::Settings,
+                r#"{ "hard_tabs": true }"#,
+            ),
+            ("docs", LocalSettingsKind::Settings, r#"{ "tab_size": 5 }"#),
+            (
+                "crates",
+                LocalSettingsKind::Editorconfig,
+                "[*.rs]\nindent_size = 6\n",
+            ),
+        ] {
+            store
+                .set_local_settings(
+                    worktree_id,
+                    Path::new(directory).into(),
+                    kind,
+                    Some(content),
+                    cx,
+                )
+                .unwrap();
+        }
+
+        let layers = |key_path: &[&str], path: Option<&str>, language: Option<&str>| {
+            let location = path.map(|path| SettingsLocation {
+                worktree_id,
+                path: Path::new(path),
+            });
+            store
+                .value_layers_at_key_path(key_path, location, language)
+                .into_iter()
+                .map(|layer| {
+                    (
+                        layer.source,
+                        layer
+                            .directory
+                            .map(|directory| directory.to_string_lossy().into_owned()),
+                        layer.key_path.join("."),
+                        layer.value,
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            layers(&["tab_size"], None, None),
+            vec![
+                (
+                    SettingsValueSource::Default,
+                    None,
+                    "tab_size".into(),
+                    4.into()
+                ),
+                (SettingsValueSource::User, None, "tab_size".into(), 2.into()),
+            ]
+        );
+        // A later file's value for all languages overrides the language values of earlier files.
+        assert_eq!(
+            layers(&["tab_size"], None, Some("C")),
+            vec![
+                (
+                    SettingsValueSource::Default,
+                    None,
+                    "tab_size".into(),
+                    4.into()
+                ),
+                (
+                    SettingsValueSource::Default,
+                    None,
+                    "languages.C.tab_size".into(),
+                    1.into()
+                ),
+                (SettingsValueSource::User, None, "tab_size".into(), 2.into()),
+            ]
+        );
+        assert_eq!(
+            layers(&["tab_size"], Some("crates/main.rs"), Some("Rust")),
+            vec![
+                (
+                    SettingsValueSource::Default,
+                    None,
+                    "tab_size".into(),
+                    4.into()
+                ),
+                (SettingsValueSource::User, None, "tab_size".into(), 2.into()),
+                (
+                    SettingsValueSource::User,
+                    None,
+                    "languages.Rust.tab_size".into(),
+                    8.into()
+                ),
+                (
+                    SettingsValueSource::Project,
+                    Some("".into()),
+                    "tab_size".into(),
+                    3.into()
+                ),
+                (
+                    SettingsValueSource::Editorconfig,
+                    Some("crates".into()),
+                    "indent_size".into(),
+                    6.into()
+                ),
+            ]
+        );
+        assert_eq!(
+            layers(&["hard_tabs"], Some("docs/index.md"), None),
+            Vec::new()
+        );
+        assert_eq!(
+            layers(&["hard_tabs"], Some("crates/main.rs"), None),
+            vec![(
+                SettingsValueSource::Project,
+                Some("crates".into()),
+                "hard_tabs".into(),
+                true.into()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_value_range_in_json_text() {
+        let text = r#"{
+            "tab_size": 2,
+            "languages": { "Rust": { "tab_size": 4 } }
+        }"#;
+        let value_at =
+            |key_path: &[&str]| value_range_in_json_text(text, key_path).map(|range| &text[range]);
+        assert_eq!(value_at(&["tab_size"]), Some("2"));
+        assert_eq!(value_at(&["languages", "Rust", "tab_size"]), Some("4"));
+        assert_eq!(
+            value_at(&["languages", "Rust"]),
+            Some(r#"{ "tab_size": 4 }"#)
+        );
+        assert_eq!(value_at(&["languages", "Python", "tab_size"]), None);
+        assert_eq!(value_at(&["hard_tabs"]), None);
+    }
+
+    #[gpui::test]
+    fn test_key_path_update(cx: &mut App) {
+        let store = SettingsStore::new(cx);
+        let old_text = r#"{
+            // The user
+            "user": {
+                "name": "John Doe"
+            }
+        }"#
+        .unindent();
+        pretty_assertions::assert_eq!(
+            store.new_text_for_key_path_update(
+                old_text.clone(),
+                &["user", "name"],
+                &serde_json::json!("Jane Doe")
+            ),
+            r#"{
+                // The user
+                "user": {
+                    "name": "Jane Doe"
+                }
+            }"#
+            .unindent()
+        );
+        pretty_assertions::assert_eq!(
+            store.new_text_for_key_path_update(
+                "".to_string(),
+                &["languages", "Rust", "tab_size"],
+                &serde_json::json!(4)
+            ),
+            "{\n    \"languages\": {\n        \"Rust\": {\n            \"tab_size\": 4\n        }\n    }\n}\n"
+        );
+    }
+
     fn check_settings_update<T: Settings>(
         store: &mut SettingsStore,
         old_json: String,
@@ -0,0 +1,694 @@
+use std::ops::Range;
+use std::rc::Rc;
+
+use collections::HashMap;
+use editor::{Editor, EditorEvent};
+use fs::Fs;
+use gpui::{
+    is_no_action, uniform_list, AnyElement, App, Entity, EventEmitter, FocusHandle, Focusable,
+    KeyBinding, KeyBindingContextPredicate, KeymapVersion, Keystroke, ScrollStrategy, Subscription,
+    UniformListScrollHandle,
+};
+use settings::{
+    KeybindSource, KeybindUpdateOperation, KeybindUpdateTarget, KeymapFile, SettingsStore,
+};
+use ui::{prelude::*, Tooltip};
+use workspace::item::{Item, ItemEvent};
+use workspace::notifications::DetachAndPromptErr;
+use workspace::Workspace;
+
+use crate::settings_page::render_search_input;
+
+/// The most keystrokes a recorded binding can have.
+const MAX_RECORDED_KEYSTROKES: usize = 4;
+
+/// How a binding relates to the other bindings of the same keystrokes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum BindingState {
+    Active,
+    /// A later binding of the same keystrokes in the same context takes precedence.
+    Shadowed {
+        by: usize,
+    },
+    /// The keystrokes are unbound by a later `null` binding in the same or a broader context.
+    Disabled,
+    /// Takes precedence over an earlier binding of the same keystrokes in the same context, to
+    /// another action.
+    Conflicting {
+        with: usize,
+    },
+}
+
+/// Computes the state of each binding in the keymap, given in the order they were added.
+pub(crate) fn binding_states(bindings: &[KeyBinding]) -> Vec<BindingState> {
+    let mut indices_by_keystrokes = HashMap::<&[Keystroke], Vec<usize>>::default();
+    for (ix, binding) in bindings.iter().enumerate() {
+        indices_by_keystrokes
+            .entry(binding.keystrokes())
+            .or_default()
+            .push(ix);
+    }
+
+    let same_context = |a: &KeyBinding, b: &KeyBinding| a.predicate() == b.predicate();
+    bindings
+        .iter()
+        .enumerate()
+        .map(|(ix, binding)| {
+            let indices = &indices_by_keystrokes[binding.keystrokes()];
+            let (earlier, later) = indices.split_at(indices.partition_point(|other| *other <= ix));
+            let earlier = &earlier[..earlier.len().saturating_sub(1)];
+
+            let is_disabled = later.iter().any(|other| {
+                let other = &bindings[*other];
+                is_no_action(other.action())
+                    && match (other.predicate(), binding.predicate()) {
+                        (None, _) => true,
+                        (Some(_), None) => false,
+                        (Some(other), Some(predicate)) => other.is_superset(&predicate),
+                    }
+            });
+            if is_disabled {
+                return BindingState::Disabled;
+            }
+            if let Some(by) = later
+                .iter()
+                .rev()
+                .find(|other| same_context(&bindings[**other], binding))
+            {
+                return BindingState::Shadowed { by: *by };
+            }
+            if let Some(with) = earlier.iter().rev().find(|other| {
+                let other = &bindings[**other];
+                same_context(other, binding)
+                    && !is_no_action(other.action())
+                    && !other.action().partial_eq(binding.action())
+            }) {
+                return BindingState::Conflicting { with: *with };
+            }
+            BindingState::Active
+        })
+        .collect()
+}
+
+/// A binding of the keymap, as listed in the editor.
+#[derive(Clone)]
+struct ProcessedBinding {
+    /// The index of the binding in the keymap.
+    index: usize,
+    keystrokes: SharedString,
+    context: Option<SharedString>,
+    predicate: Option<Rc<KeyBindingContextPredicate>>,
+    action_input: Option<SharedString>,
+    /// The keymap the binding comes from, or `None` for bindings registered in code.
+    source: Option<KeybindSource>,
+    state: BindingState,
+}
+
+impl ProcessedBinding {
+    fn target<'a>(&'a self, action_name: &'a str) -> KeybindUpdateTarget<'a> {
+        KeybindUpdateTarget {
+            context: self.context.as_deref(),
+            keystrokes: &self.keystrokes,
+            action_name,
+            action_input: self.action_input.as_deref(),
+        }
+    }
+}
+
+/// A row of the editor: a binding of an action, or an action without bindings.
+struct KeymapRow {
+    action_name: SharedString,
+    binding: Option<ProcessedBinding>,
+}
+
+struct Recording {
+    row: usize,
+    keystrokes: Vec<Keystroke>,
+    _interceptor: Subscription,
+}
+
+/// Lists every registered action with its bindings, and edits them in the user keymap.
+pub struct KeymapEditor {
+    focus_handle: FocusHandle,
+    search_editor: Entity<Editor>,
+    rows: Vec<KeymapRow>,
+    /// The indices of the rows matching the search query.
+    matches: Vec<usize>,
+    keymap_version: Option<KeymapVersion>,
+    recording: Option<Recording>,
+    scroll_handle: UniformListScrollHandle,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl KeymapEditor {
+    pub fn new(window: &mut Window, cx: &mut Context<Workspace>) -> Entity<Self> {
+        cx.new(|cx| {
+            let search_editor = cx.new(|cx| {
+                let mut editor = Editor::single_line(window, cx);
+                editor.set_placeholder_text("Search actions, keystrokes or contexts...", cx);
+                editor
+            });
+            let _subscriptions =
+                vec![
+                    cx.subscribe(&search_editor, |this, _, event: &EditorEvent, cx| {
+                        if let EditorEvent::Edited { .. } = event {
+                            this.update_matches(cx);
+                        }
+                    }),
+                ];
+            let mut this = Self {
+                focus_handle: cx.focus_handle(),
+                search_editor,
+                rows: Vec::new(),
+                matches: Vec::new(),
+                keymap_version: None,
+                recording: None,
+                scroll_handle: UniformListScrollHandle::new(),
+                _subscriptions,
+            };
+            this.refresh_rows(cx);
+            this
+        })
+    }
+
+    /// Recomputes the rows from the keymap, if it changed since they were last computed.
+    fn refresh_rows(&mut self, cx: &mut Context<Self>) {
+        let keymap = cx.key_bindings();
+        let keymap = keymap.borrow();
+        if self.keymap_version == Some(keymap.version()) {
+            return;
+        }
+        self.keymap_version = Some(keymap.version());
+
+        let bindings = keymap.bindings().cloned().collect::<Vec<_>>();
+        let states = binding_states(&bindings);
+        let mut bindings_by_action = HashMap::<&str, Vec<ProcessedBinding>>::default();
+        for (index, (binding, state)) in bindings.iter().zip(states).enumerate() {
+            if is_no_action(binding.action()) {
+                continue;
+            }
+            let predicate = binding.predicate();
+            bindings_by_action
+                .entry(binding.action().name())
+                .or_default()
+                .push(ProcessedBinding {
+                    index,
+                    keystrokes: keystrokes_text(binding.keystrokes()).into(),
+                    context: predicate
+                        .as_ref()
+                        .map(|predicate| predicate.to_string().into()),
+                    predicate,
+                    action_input: binding.action_input(),
+                    source: binding.meta().and_then(KeybindSource::from_meta),
+                    state,
+                });
+        }
+
+        let mut action_names = cx.all_action_names().to_vec();
+        action_names.sort();
+        self.rows = action_names
+            .into_iter()
+            .filter(|action_name| action_name.as_ref() != "zed::NoAction")
+            .flat_map(|action_name| {
+                let bindings = bindings_by_action
+                    .remove(action_name.as_ref())
+                    .unwrap_or_default();
+                if bindings.is_empty() {
+                    vec![KeymapRow {
+                        action_name,
+                        binding: None,
+                    }]
+                } else {
+                    bindings
+                        .into_iter()
+                        .rev()
+                        .map(|binding| KeymapRow {
+                            action_name: action_name.clone(),
+                            binding: Some(binding),
+                        })
+                        .collect()
+                }
+            })
+            .collect();
+        drop(keymap);
+        self.recording = None;
+        self.update_matches(cx);
+    }
+
+    fn update_matches(&mut self, cx: &mut Context<Self>) {
+        let query = self.search_editor.read(cx).text(cx).to_lowercase();
+        self.matches =
+            self.rows
+                .iter()
+                .enumerate()
+                .filter(|(_, row)| {
+                    query.split_whitespace().all(|word| {
+                        row.action_name.to_lowercase().contains(word)
+                            || row.binding.as_ref().is_some_and(|binding| {
+                                binding.keystrokes.contains(word)
+                                    || binding.context.as_ref().is_some_and(|context| {
+                                        context.to_lowercase().contains(word)
+                                    })
+                            })
+                    })
+                })
+                .map(|(ix, _)| ix)
+                .collect();
+        self.scroll_handle.scroll_to_item(0, ScrollStrategy::Top);
+        cx.notify();
+    }
+
+    fn action_name_of_binding(&self, index: usize) -> Option<SharedString> {
+        self.rows.iter().find_map(|row| {
+            let binding = row.binding.as_ref()?;
+            (binding.index == index).then(|| row.action_name.clone())
+        })
+    }
+
+    /// The actions that the keystrokes are already bound to, in the same context as the binding
+    /// of the row.
+    fn conflicting_actions(&self, row_ix: usize, keystrokes: &str) -> Vec<SharedString> {
+        let Some(row) = self.rows.get(row_ix) else {
+            return Vec::new();
+        };
+        let predicate = row
+            .binding
+            .as_ref()
+            .and_then(|binding| binding.predicate.clone());
+        self.rows
+            .iter()
+            .filter(|other| other.action_name != row.action_name)
+            .filter_map(|other| {
+                let binding = other.binding.as_ref()?;
+                (binding.keystrokes.as_ref() == keystrokes
+                    && binding.predicate == predicate
+                    && !matches!(
+                        binding.state,
+                        BindingState::Shadowed { .. } | BindingState::Disabled
+                    ))
+                .then(|| other.action_name.clone())
+            })
+            .collect()
+    }
+
+    fn start_recording(&mut self, row: usize, window: &mut Window, cx: &mut Context<Self>) {
+        window.focus(&self.focus_handle);
+        let this = cx.weak_entity();
+        let window_handle = window.window_handle();
+        let focus_handle = self.focus_handle.clone();
+        // Only keystrokes typed into the keymap editor are recorded, the other windows and
+        // panes keep working.
+        let interceptor = cx.intercept_keystrokes(move |event, window, cx| {
+            if window.window_handle() != window_handle || !focus_handle.contains_focused(window, cx)
+            {
+                return;
+            }
+            let keystroke = event.keystroke.clone();
+            this.update(cx, |this, cx| {
+                if keystroke.modified() {
+                    this.record_keystroke(keystroke, cx);
+                } else {
+                    match keystroke.key.as_str() {
+                        "escape" => this.cancel_recording(cx),
+                        "enter" => this.save_recording(window, cx),
+                        _ => this.record_keystroke(keystroke, cx),
+                    }
+                }
+            })
+            .ok();
+            cx.stop_propagation();
+        });
+        self.recording = Some(Recording {
+            row,
+            keystrokes: Vec::new(),
+            _interceptor: interceptor,
+        });
+        cx.notify();
+    }
+
+    fn record_keystroke(&mut self, keystroke: Keystroke, cx: &mut Context<Self>) {
+        if let Some(recording) = self.recording.as_mut() {
+            if recording.keystrokes.len() < MAX_RECORDED_KEYSTROKES {
+                recording.keystrokes.push(Keystroke {
+                    key_char: None,
+                    ..keystroke
+                });
+                cx.notify();
+            }
+        }
+    }
+
+    fn cancel_recording(&mut self, cx: &mut Context<Self>) {
+        self.recording = None;
+        cx.notify();
+    }
+
+    fn save_recording(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(recording) = self.recording.take() else {
+            return;
+        };
+        if recording.keystrokes.is_empty() {
+            cx.notify();
+            return;
+        }
+        if let Some(row) = self.rows.get(recording.row) {
+            let new_keystrokes = keystrokes_text(&recording.keystrokes);
+            self.update_keymap_file(
+                row.action_name.clone(),
+                row.binding.clone(),
+                Some(new_keystrokes),
+                window,
+                cx,
+            );
+        }
+        cx.notify();
+    }
+
+    fn remove_binding(&mut self, row: usize, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(row) = self.rows.get(row) {
+            self.update_keymap_file(
+                row.action_name.clone(),
+                row.binding.clone(),
+                None,
+                window,
+                cx,
+            );
+        }
+    }
+
+    /// Writes a change of a binding to the user keymap: binding it to new keystrokes, or removing
+    /// it when there are none. The keymap is reloaded by the keymap file watcher.
+    fn update_keymap_file(
+        &self,
+        action_name: SharedString,
+        binding: Option<ProcessedBinding>,
+        new_keystrokes: Option<String>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let fs = <dyn Fs>::global(cx);
+        let tab_size = cx.global::<SettingsStore>().json_tab_size();
+        cx.spawn_in(window, |_, _| async move {
+            let operation = match (&binding, &new_keystrokes) {
+                (Some(binding), Some(new_keystrokes)) => KeybindUpdateOperation::Replace {
+                    source: binding.source.unwrap_or(KeybindSource::Default),
+                    target: binding.target(&action_name),
+                    new_keystrokes,
+                },
+                (Some(binding), None) => KeybindUpdateOperation::Remove {
+                    source: binding.source.unwrap_or(KeybindSource::Default),
+                    target: binding.target(&action_name),
+                },
+                (None, Some(new_keystrokes)) => KeybindUpdateOperation::Add(KeybindUpdateTarget {
+                    context: None,
+                    keystrokes: new_keystrokes,
+                    action_name: &action_name,
+                    action_input: None,
+                }),
+                (None, None) => return Ok(()),
+            };
+            let keymap_path = paths::keymap_file();
+            // Only a missing keymap is empty: rewriting one that failed to load would lose it.
+            let keymap_contents = if fs.is_file(keymap_path).await {
+                fs.load(keymap_path).await?
+            } else {
+                String::new()
+            };
+            let keymap_contents =
+                KeymapFile::update_keybinding(operation, keymap_contents, tab_size)?;
+            fs.atomic_write(keymap_path.clone(), keymap_contents).await
+        })
+        .detach_and_prompt_err("Failed to update the keymap", window, cx, |_, _, _| None);
+    }
+
+    fn render_items(
+        &mut self,
+        range: Range<usize>,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Vec<AnyElement> {
+        range
+            .filter_map(|ix| {
+                let row_ix = *self.matches.get(ix)?;
+                Some(self.render_row(ix, row_ix, cx))
+            })
+            .collect()
+    }
+
+    fn render_row(&self, ix: usize, row_ix: usize, cx: &mut Context<Self>) -> AnyElement {
+        let row = &self.rows[row_ix];
+        let recording = self
+            .recording
+            .as_ref()
+            .filter(|recording| recording.row == row_ix);
+        let binding = row.binding.as_ref();
+
+        let keystrokes = match (recording, binding) {
+            (Some(recording), _) if recording.keystrokes.is_empty() => {
+                Label::new("Press keystrokes, then enter to save or escape to cancel...")
+                    .color(Color::Accent)
+            }
+            (Some(recording), _) => {
+                Label::new(keystrokes_text(&recording.keystrokes)).color(Color::Accent)
+            }
+            (None, Some(binding)) => Label::new(binding.keystrokes.clone()),
+            (None, None) => Label::new("Unbound").color(Color::Disabled),
+        };
+
+        let status = if let Some(recording) = recording {
+            let conflicts =
+                self.conflicting_actions(row_ix, &keystrokes_text(&recording.keystrokes));
+            (!conflicts.is_empty()).then(|| {
+                (
+                    format!("Conflicts with {}", conflicts.join(", ")),
+                    Color::Warning,
+                )
+            })
+        } else {
+            binding.and_then(|binding| match binding.state {
+                BindingState::Active => None,
+                BindingState::Shadowed { by } => Some((
+                    match self.action_name_of_binding(by) {
+                        Some(action_name) if action_name != row.action_name => {
+                            format!("Shadowed by {action_name}")
+                        }
+                        _ => "Shadowed by a later binding".to_string(),
+                    },
+                    Color::Warning,
+                )),
+                BindingState::Disabled => Some(("Unbound by a later binding".into(), Color::Muted)),
+                BindingState::Conflicting { with } => Some((
+                    match self.action_name_of_binding(with) {
+                        Some(action_name) => format!("Overrides {action_name}"),
+                        None => "Overrides an earlier binding".to_string(),
+                    },
+                    Color::Warning,
+                )),
+            })
+        };
+
+        let buttons = if recording.is_some() {
+            h_flex()
+                .gap_1()
+                .child(
+                    Button::new(("save", ix), "Save")
+                        .style(ButtonStyle::Filled)
+                        .label_size(LabelSize::Small)
+                        .on_click(
+                            cx.listener(|this, _, window, cx| this.save_recording(window, cx)),
+                        ),
+                )
+                .child(
+                    Button::new(("cancel", ix), "Cancel")
+                        .label_size(LabelSize::Small)
+                        .on_click(cx.listener(|this, _, _, cx| this.cancel_recording(cx))),
+                )
+        } else {
+            h_flex()
+                .gap_1()
+                .child(
+                    IconButton::new(("record", ix), IconName::Pencil)
+                        .icon_size(IconSize::Small)
+                        .tooltip(Tooltip::text(if binding.is_some() {
+                            "Change Keystrokes"
+                        } else {
+                            "Add Keystrokes"
+                        }))
+                        .on_click(cx.listener(move |this, _, window, cx| {
+                            this.start_recording(row_ix, window, cx)
+                        })),
+                )
+                .when(binding.is_some(), |this| {
+                    this.child(
+                        IconButton::new(("remove", ix), IconName::Trash)
+                            .icon_size(IconSize::Small)
+                            .tooltip(Tooltip::text("Remove Binding"))
+                            .on_click(cx.listener(move |this, _, window, cx| {
+                                this.remove_binding(row_ix, window, cx)
+                            })),
+                    )
+                })
+        };
+
+        h_flex()
+            .id(("keymap-row", ix))
+            .w_full()
+            .px_3()
+            .py_1()
+            .gap_4()
+            .border_b_1()
+            .border_color(cx.theme().colors().border_variant)
+            .child(
+                div().w(relative(0.3)).child(
+                    Label::new(row.action_name.clone())
+                        .single_line()
+                        .text_ellipsis(),
+                ),
+            )
+            .child(div().w(relative(0.15)).child(keystrokes.single_line()))
+            .child(
+                div().w(relative(0.2)).child(
+                    Label::new(
+                        binding
+                            .and_then(|binding| binding.context.clone())
+                            .unwrap_or_default(),
+                    )
+                    .color(Color::Muted)
+                    .single_line()
+                    .text_ellipsis(),
+                ),
+            )
+            .child(
+                div().w(relative(0.1)).child(
+                    Label::new(
+                        binding
+                            .map(|binding| binding.source.map_or("Built-in", KeybindSource::name))
+                            .unwrap_or_default(),
+                    )
+                    .color(Color::Muted)
+                    .single_line(),
+                ),
+            )
+            .child(
+                div()
+                    .flex_1()
+                    .min_w_0()
+                    .children(status.map(|(status, color)| {
+                        Label::new(status)
+                            .size(LabelSize::Small)
+                            .color(color)
+                            .single_line()
+                            .text_ellipsis()
+                    })),
+            )
+            .child(buttons)
+            .into_any_element()
+    }
+}
+
+fn keystrokes_text(keystrokes: &[Keystroke]) -> String {
+    keystrokes
+        .iter()
+        .map(Keystroke::unparse)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+impl EventEmitter<ItemEvent> for KeymapEditor {}
+
+impl Focusable for KeymapEditor {
+    fn focus_handle(&self, _cx: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Item for KeymapEditor {
+    type Event = ItemEvent;
+
+    fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::Keyboard))
+    }
+
+    fn tab_content_text(&self, _window: &Window, _cx: &App) -> Option<SharedString> {
+        Some("Keymap Editor".into())
+    }
+
+    fn show_toolbar(&self) -> bool {
+        false
+    }
+
+    fn to_item_events(event: &Self::Event, mut f: impl FnMut(ItemEvent)) {
+        f(*event)
+    }
+}
+
+impl Render for KeymapEditor {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        self.refresh_rows(cx);
+
+        v_flex()
+            .key_context("KeymapEditor")
+            .track_focus(&self.focus_handle)
+            .p_4()
+            .size_full()
+            .gap_4()
+            .child(Label::new("Keymap Editor").size(LabelSize::Large))
+            .child(render_search_input(&self.search_editor, cx))
+            .child(if self.matches.is_empty() {
+                div()
+                    .flex_1()
+                    .child(Label::new("No actions found").color(Color::Muted))
+                    .into_any_element()
+            } else {
+                v_flex()
+                    .flex_1()
+                    .elevation_1(cx)
+                    .child(
+                        uniform_list(
+                            cx.entity(),
+                            "keymap-rows",
+                            self.matches.len(),
+                            |this, range, window, cx| this.render_items(range, window, cx),
+                        )
+                        .size_full()
+                        .track_scroll(self.scroll_handle.clone()),
+                    )
+                    .into_any_element()
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{actions, NoAction};
+
+    actions!(keymap_editor_test, [ActionAlpha, ActionBeta]);
+
+    #[test]
+    fn test_binding_states() {
+        let bindings = [
+            KeyBinding::new("ctrl-a", ActionAlpha, Some("Editor")),
+            KeyBinding::new("ctrl-b", ActionAlpha, Some("Editor")),
+            KeyBinding::new("ctrl-a", ActionBeta, Some("Editor")),
+            KeyBinding::new("ctrl-a", ActionBeta, Some("Workspace")),
+            KeyBinding::new("ctrl-b", ActionBeta, Some("Workspace")),
+            KeyBinding::new("ctrl-b", NoAction, None),
+            KeyBinding::new("ctrl-c", ActionAlpha, Some("Editor")),
+            KeyBinding::new("ctrl-c", ActionAlpha, Some("Editor")),
+        ];
+        assert_eq!(
+            binding_states(&bindings),
+            vec![
+                BindingState::Shadowed { by: 2 },
+                BindingState::Disabled,
+                BindingState::Conflicting { with: 0 },
+                BindingState::Active,
+                BindingState::Disabled,
+                BindingState::Active,
+                BindingState::Shadowed { by: 7 },
+                BindingState::Active,
+            ]
+        );
+    }
+}
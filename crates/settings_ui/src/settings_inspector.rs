@@ -0,0 +1,504 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::Context as _;
+use collections::HashSet;
+use editor::{scroll::Autoscroll, Editor};
+use fuzzy::{match_strings, StringMatch, StringMatchCandidate};
+use gpui::{
+    App, Context, DismissEvent, Entity, EventEmitter, Focusable, ParentElement, Render, Styled,
+    Subscription, Task, WeakEntity, Window,
+};
+use picker::{Picker, PickerDelegate};
+use settings::{
+    value_range_in_json_text, SettingsJsonSchemaParams, SettingsLayerValue, SettingsLocation,
+    SettingsStore, SettingsValueSource, WorktreeId,
+};
+use ui::{prelude::*, HighlightedLabel, ListItem, ListItemSpacing};
+use util::ResultExt;
+use workspace::{ModalView, Workspace};
+
+use crate::settings_page::{source_label, value_label};
+use crate::settings_schema::{language_setting_items, setting_items};
+
+/// The file of the buffer whose settings are inspected.
+struct InspectedFile {
+    worktree_id: WorktreeId,
+    /// The path of the file, relative to the worktree root.
+    path: Arc<Path>,
+    /// The absolute path of the worktree root, if the worktree is local.
+    worktree_abs_path: Option<Arc<Path>>,
+}
+
+/// A modal listing every setting, showing for the selected one each value set in the settings
+/// files that apply to the active buffer, from the one in effect to the lowest precedence.
+/// Confirming opens the file setting the value in effect.
+pub struct SettingsInspector {
+    picker: Entity<Picker<SettingsInspectorDelegate>>,
+    _subscription: Subscription,
+}
+
+impl SettingsInspector {
+    pub(crate) fn toggle(
+        workspace: &mut Workspace,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        let buffer = workspace
+            .active_item_as::<Editor>(cx)
+            .and_then(|editor| editor.read(cx).buffer().read(cx).as_singleton());
+        let language = buffer.as_ref().and_then(|buffer| {
+            let language = buffer.read(cx).language()?;
+            Some(language.name().to_string())
+        });
+        let file = buffer.and_then(|buffer| {
+            let file = buffer.read(cx).file()?;
+            let worktree_id = file.worktree_id(cx);
+            let project = workspace.project().read(cx);
+            let worktree_abs_path = project
+                .is_local()
+                .then(|| project.worktree_for_id(worktree_id, cx))
+                .flatten()
+                .map(|worktree| worktree.read(cx).abs_path());
+            Some(InspectedFile {
+                worktree_id,
+                path: file.path().clone(),
+                worktree_abs_path,
+            })
+        });
+
+        let language_names = workspace.app_state().languages.language_names();
+        let font_names = cx.text_system().all_font_names();
+        let schema = cx.global::<SettingsStore>().json_schema(
+            &SettingsJsonSchemaParams {
+                language_names: &language_names,
+                font_names: &font_names,
+            },
+            cx,
+        );
+        let language_key_paths = language_setting_items(&schema)
+            .into_iter()
+            .map(|item| item.key_path)
+            .collect::<HashSet<_>>();
+        let key_paths = setting_items(&schema)
+            .into_iter()
+            .map(|item| {
+                let for_language = language_key_paths.contains(&item.key_path);
+                (item.key_path, for_language)
+            })
+            .collect::<Vec<_>>();
+
+        let delegate = SettingsInspectorDelegate {
+            workspace: workspace.weak_handle(),
+            candidates: key_paths
+                .iter()
+                .enumerate()
+                .map(|(id, (key_path, _))| StringMatchCandidate::new(id, &key_path.join(".")))
+                .collect(),
+            key_paths,
+            file,
+            language,
+            matches: Vec::new(),
+            selected_index: 0,
+        };
+        workspace.toggle_modal(window, cx, |window, cx| {
+            let picker = cx.new(|cx| Picker::uniform_list(delegate, window, cx));
+            let _subscription = cx.subscribe(&picker, |_, _, _, cx| cx.emit(DismissEvent));
+            Self {
+                picker,
+                _subscription,
+            }
+        });
+    }
+}
+
+impl ModalView for SettingsInspector {}
+
+impl EventEmitter<DismissEvent> for SettingsInspector {}
+
+impl Focusable for SettingsInspector {
+    fn focus_handle(&self, cx: &App) -> gpui::FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl Render for SettingsInspector {
+    fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .key_context("SettingsInspector")
+            .w(rems(40.))
+            .child(self.picker.clone())
+    }
+}
+
+pub struct SettingsInspectorDelegate {
+    workspace: WeakEntity<Workspace>,
+    /// The key paths of the settings, and whether each can be overridden per language.
+    key_paths: Vec<(Vec<String>, bool)>,
+    candidates: Vec<StringMatchCandidate>,
+    file: Option<InspectedFile>,
+    /// The language of the buffer, whose overrides apply to it.
+    language: Option<String>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+}
+
+impl SettingsInspectorDelegate {
+    /// The values set for the selected setting, from the lowest to the highest precedence.
+    fn selected_layers(&self, cx: &App) -> Option<Vec<SettingsLayerValue>> {
+        let mat = self.matches.get(self.selected_index)?;
+        let (key_path, for_language) = self.key_paths.get(mat.candidate_id)?;
+        let key_path = key_path.iter().map(String::as_str).collect::<Vec<_>>();
+        let location = self.file.as_ref().map(|file| SettingsLocation {
+            worktree_id: file.worktree_id,
+            path: &file.path,
+        });
+        let language = self.language.as_deref().filter(|_| *for_language);
+        Some(
+            cx.global::<SettingsStore>()
+                .value_layers_at_key_path(&key_path, location, language),
+        )
+    }
+
+    /// The absolute path of the file the layer's value is set in, if it's a local file.
+    fn layer_abs_path(&self, layer: &SettingsLayerValue) -> Option<PathBuf> {
+        match layer.source {
+            SettingsValueSource::User | SettingsValueSource::ReleaseChannel => {
+                Some(paths::settings_file().clone())
+            }
+            SettingsValueSource::Project | SettingsValueSource::Editorconfig => {
+                let worktree_abs_path = self.file.as_ref()?.worktree_abs_path.as_ref()?;
+                Some(worktree_abs_path.join(layer_relative_path(layer)?))
+            }
+            SettingsValueSource::Default
+            | SettingsValueSource::Extension
+            | SettingsValueSource::Server => None,
+        }
+    }
+
+    /// Opens the file the layer's value is set in, selecting the value. Returns whether there
+    /// is such a file.
+    fn open_layer(
+        &self,
+        layer: &SettingsLayerValue,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> bool {
+        let Some(abs_path) = self.layer_abs_path(layer) else {
+            return false;
+        };
+        let Some(open_task) = self
+            .workspace
+            .update(cx, |workspace, cx| {
+                workspace.open_abs_path(abs_path, false, window, cx)
+            })
+            .log_err()
+        else {
+            return false;
+        };
+        let source = layer.source;
+        let key_path = layer.key_path.clone();
+        let inspected_path = self.file.as_ref().map(|file| file.path.clone());
+        cx.spawn_in(window, |_, mut cx| async move {
+            let editor = open_task
+                .await?
+                .downcast::<Editor>()
+                .context("the settings file is not open in an editor")?;
+            editor.update_in(&mut cx, |editor, window, cx| {
+                let text = editor.text(cx);
+                let range = if source == SettingsValueSource::Editorconfig {
+                    inspected_path
+                        .as_deref()
+                        .and_then(|path| editorconfig_property_range(&text, &key_path[0], path))
+                } else {
+                    let key_path = key_path.iter().map(String::as_str).collect::<Vec<_>>();
+                    value_range_in_json_text(&text, &key_path)
+                };
+                if let Some(range) = range {
+                    editor.change_selections(
+                        Some(Autoscroll::center()),
+                        window,
+                        cx,
+                        |selections| selections.select_ranges([range]),
+                    );
+                }
+            })
+        })
+        .detach_and_log_err(cx);
+        true
+    }
+}
+
+impl PickerDelegate for SettingsInspectorDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _window: &mut Window, _: &mut App) -> Arc<str> {
+        "Inspect a setting...".into()
+    }
+
+    fn no_matches_text(&self, _window: &mut Window, _: &mut App) -> SharedString {
+        "No settings found".into()
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(
+        &mut self,
+        ix: usize,
+        _window: &mut Window,
+        _: &mut Context<Picker<Self>>,
+    ) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Task<()> {
+        let background = cx.background_executor().clone();
+        let candidates = self.candidates.clone();
+        cx.spawn_in(window, |this, mut cx| async move {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .map(|candidate| StringMatch {
+                        candidate_id: candidate.id,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    1000,
+                    &Default::default(),
+                    background,
+                )
+                .await
+            };
+
+            this.update(&mut cx, |this, cx| {
+                let delegate = &mut this.delegate;
+                delegate.matches = matches;
+                delegate.selected_index = delegate
+                    .selected_index
+                    .min(delegate.matches.len().saturating_sub(1));
+                cx.notify();
+            })
+            .log_err();
+        })
+    }
+
+    fn confirm(&mut self, _: bool, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let Some(layer) = self.selected_layers(cx).and_then(|mut layers| layers.pop()) else {
+            return;
+        };
+        if self.open_layer(&layer, window, cx) {
+            cx.emit(DismissEvent);
+        }
+    }
+
+    fn dismissed(&mut self, _: &mut Window, cx: &mut Context<Picker<Self>>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _window: &mut Window,
+        _: &mut Context<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let mat = self.matches.get(ix)?;
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .toggle_state(selected)
+                .child(HighlightedLabel::new(
+                    mat.string.clone(),
+                    mat.positions.clone(),
+                )),
+        )
+    }
+
+    fn render_footer(
+        &self,
+        _: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Option<gpui::AnyElement> {
+        let layers = self.selected_layers(cx)?;
+        let file_label = match &self.file {
+            Some(file) => format!("Set for {}", file.path.display()),
+            None => "Set globally".to_string(),
+        };
+        Some(
+            v_flex()
+                .w_full()
+                .max_h(rems(16.))
+                .overflow_hidden()
+                .p_2()
+                .gap_1()
+                .border_t_1()
+                .border_color(cx.theme().colors().border_variant)
+                .child(
+                    Label::new(file_label)
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                )
+                .children({
+                    let in_effect_ix = layers.len().saturating_sub(1);
+                    layers
+                        .into_iter()
+                        .enumerate()
+                        .rev()
+                        .map(move |(ix, layer)| {
+                            self.render_layer(ix, layer, ix == in_effect_ix, cx)
+                        })
+                })
+                .into_any_element(),
+        )
+    }
+}
+
+impl SettingsInspectorDelegate {
+    /// Renders a value of the selected setting, with where it's set.
+    fn render_layer(
+        &self,
+        ix: usize,
+        layer: SettingsLayerValue,
+        in_effect: bool,
+        cx: &mut Context<Picker<Self>>,
+    ) -> impl IntoElement {
+        let language = self.language.as_deref().filter(|_| layer.for_language);
+        let has_file = self.layer_abs_path(&layer).is_some();
+        let key_path = layer.key_path.join(".");
+        let location = match layer_relative_path(&layer) {
+            Some(path) => format!("{} · {key_path}", path.display()),
+            None if has_file => format!("{} · {key_path}", paths::settings_file().display()),
+            None => key_path,
+        };
+        let source = source_label(layer.source, language);
+        let value = value_label(&layer.value);
+        h_flex()
+            .id(("setting-layer", ix))
+            .w_full()
+            .px_1()
+            .gap_2()
+            .justify_between()
+            .rounded_md()
+            .when(has_file, |this| {
+                this.cursor_pointer()
+                    .hover(|style| style.bg(cx.theme().colors().element_hover))
+                    .on_click(cx.listener(move |this, _, window, cx| {
+                        if this.delegate.open_layer(&layer, window, cx) {
+                            cx.emit(DismissEvent);
+                        }
+                    }))
+            })
+            .child(
+                v_flex()
+                    .min_w_0()
+                    .child(
+                        h_flex()
+                            .gap_2()
+                            .child(Label::new(source).color(if in_effect {
+                                Color::Default
+                            } else {
+                                Color::Muted
+                            }))
+                            .when(in_effect, |this| {
+                                this.child(
+                                    Label::new("In effect")
+                                        .size(LabelSize::XSmall)
+                                        .color(Color::Accent),
+                                )
+                            }),
+                    )
+                    .child(
+                        Label::new(location)
+                            .size(LabelSize::XSmall)
+                            .color(Color::Muted)
+                            .single_line()
+                            .text_ellipsis(),
+                    ),
+            )
+            .child(Label::new(value).when(!in_effect, |this| {
+                this.strikethrough(true).color(Color::Muted)
+            }))
+    }
+}
+
+/// The path of the project settings file or `.editorconfig` file the layer's value is set in,
+/// relative to the worktree root.
+fn layer_relative_path(layer: &SettingsLayerValue) -> Option<PathBuf> {
+    let directory = layer.directory.as_ref()?;
+    match layer.source {
+        SettingsValueSource::Project => {
+            Some(directory.join(paths::local_settings_file_relative_path()))
+        }
+        SettingsValueSource::Editorconfig => Some(directory.join(paths::EDITORCONFIG_NAME)),
+        _ => None,
+    }
+}
+
+/// Returns the range of the value on the last line setting the property in the text of an
+/// `.editorconfig` file, among the sections applying to the file at the given path, relative to
+/// the worktree root.
+fn editorconfig_property_range(
+    text: &str,
+    property: &str,
+    path: &Path,
+) -> Option<std::ops::Range<usize>> {
+    let mut line_start = 0;
+    let mut in_applying_section = false;
+    let mut range = None;
+    for line in text.split_inclusive('\n') {
+        let trimmed_line = line.trim();
+        if let Some(pattern) = trimmed_line
+            .strip_prefix('[')
+            .and_then(|line| line.strip_suffix(']'))
+        {
+            in_applying_section = ec4rs::Section::new(pattern).applies_to(path);
+        } else if let Some((key, value)) = line.split_once('=').filter(|_| in_applying_section) {
+            if key.trim().eq_ignore_ascii_case(property) {
+                let value_start = line_start + key.len() + 1;
+                let value_start = value_start + (value.len() - value.trim_start().len());
+                range = Some(value_start..value_start + value.trim().len());
+            }
+        }
+        line_start += line.len();
+    }
+    range
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_editorconfig_property_range() {
+        let text = "root = true\n\n[*]\nindent_size = 4\n\n[*.md]\nINDENT_SIZE=2\n\n\
+                    [{*.rs,*.toml}]\nindent_style = tab\n";
+        let value_at = |property, path: &str| {
+            editorconfig_property_range(text, property, Path::new(path)).map(|range| &text[range])
+        };
+        assert_eq!(value_at("indent_size", "docs/README.md"), Some("2"));
+        assert_eq!(value_at("indent_size", "src/main.rs"), Some("4"));
+        assert_eq!(value_at("indent_style", "src/main.rs"), Some("tab"));
+        assert_eq!(value_at("indent_style", "README.md"), None);
+        // The preamble isn't a section applying to any file.
+        assert_eq!(value_at("root", "README.md"), None);
+    }
+}
@@ -0,0 +1,724 @@
+use std::ops::Range;
+use std::path::Path;
+use std::sync::Arc;
+
+use collections::HashMap;
+use editor::{Editor, EditorElement, EditorEvent, EditorSettingsControls, EditorStyle};
+use fs::Fs;
+use gpui::{
+    uniform_list, AnyElement, App, Entity, EventEmitter, FocusHandle, Focusable, ScrollStrategy,
+    Subscription, TextStyle, UniformListScrollHandle, WeakEntity,
+};
+use serde_json::Value;
+use settings::{
+    Settings, SettingsFile, SettingsJsonSchemaParams, SettingsLocation, SettingsStore,
+    SettingsValueSource, WorktreeId,
+};
+use theme::ThemeSettings;
+use ui::{prelude::*, ContextMenu, DropdownMenu, NumericStepper, Switch, Tooltip};
+use util::ResultExt;
+use workspace::item::{Item, ItemEvent};
+use workspace::Workspace;
+
+use crate::appearance_settings_controls::AppearanceSettingsControls;
+use crate::settings_schema::{language_setting_items, setting_items, SettingItem, SettingKind};
+
+/// The settings file that edits on the page are written to.
+#[derive(Clone, Debug, PartialEq)]
+enum SettingsScope {
+    User,
+    Project {
+        worktree_id: WorktreeId,
+        worktree_abs_path: Arc<Path>,
+        name: SharedString,
+    },
+}
+
+impl SettingsScope {
+    fn label(&self) -> SharedString {
+        match self {
+            Self::User => "User Settings".into(),
+            Self::Project { name, .. } => format!("Project: {name}").into(),
+        }
+    }
+
+    fn file(&self) -> SettingsFile {
+        match self {
+            Self::User => SettingsFile::User,
+            Self::Project {
+                worktree_abs_path, ..
+            } => SettingsFile::Project {
+                worktree_abs_path: worktree_abs_path.clone(),
+            },
+        }
+    }
+
+    fn worktree_id(&self) -> Option<WorktreeId> {
+        match self {
+            Self::User => None,
+            Self::Project { worktree_id, .. } => Some(*worktree_id),
+        }
+    }
+
+    fn source(&self) -> SettingsValueSource {
+        match self {
+            Self::User => SettingsValueSource::User,
+            Self::Project { .. } => SettingsValueSource::Project,
+        }
+    }
+}
+
+/// The effective value of a setting.
+struct EffectiveValue {
+    value: Value,
+    source: SettingsValueSource,
+    /// Whether the value is set for the selected language, rather than for all languages.
+    for_language: bool,
+}
+
+/// A searchable page listing every setting described by the settings JSON schema,
+/// with a control to edit each of them in the user or project settings.
+pub struct SettingsPage {
+    focus_handle: FocusHandle,
+    workspace: WeakEntity<Workspace>,
+    search_editor: Entity<Editor>,
+    items: Arc<[SettingItem]>,
+    language_items: Arc<[SettingItem]>,
+    language_names: Vec<String>,
+    scope: SettingsScope,
+    /// The language whose overrides are edited, under `languages.<language>`.
+    language: Option<String>,
+    /// The indices of the listed items matching the search query.
+    matches: Vec<usize>,
+    /// The editors of string settings, keyed by the key path they edit.
+    string_editors: HashMap<Vec<String>, (Entity<Editor>, Subscription)>,
+    scroll_handle: UniformListScrollHandle,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl SettingsPage {
+    pub fn new(
+        workspace: &Workspace,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) -> Entity<Self> {
+        let language_names = workspace.app_state().languages.language_names();
+        let font_names = cx.text_system().all_font_names();
+        let schema = cx.global::<SettingsStore>().json_schema(
+            &SettingsJsonSchemaParams {
+                language_names: &language_names,
+                font_names: &font_names,
+            },
+            cx,
+        );
+        let items = setting_items(&schema).into();
+        let language_items = language_setting_items(&schema).into();
+        let workspace = workspace.weak_handle();
+
+        cx.new(|cx| {
+            let search_editor = cx.new(|cx| {
+                let mut editor = Editor::single_line(window, cx);
+                editor.set_placeholder_text("Search settings...", cx);
+                editor
+            });
+            let _subscriptions = vec![
+                cx.subscribe(&search_editor, |this, _, event: &EditorEvent, cx| {
+                    if let EditorEvent::Edited { .. } = event {
+                        this.update_matches(cx);
+                    }
+                }),
+                cx.observe_global_in::<SettingsStore>(window, |this, window, cx| {
+                    // Recreate the editors that aren't being edited, to show the new values.
+                    this.string_editors
+                        .retain(|_, (editor, _)| editor.focus_handle(cx).is_focused(window));
+                    cx.notify();
+                }),
+            ];
+            let mut this = Self {
+                focus_handle: cx.focus_handle(),
+                workspace,
+                search_editor,
+                items,
+                language_items,
+                language_names,
+                scope: SettingsScope::User,
+                language: None,
+                matches: Vec::new(),
+                string_editors: HashMap::default(),
+                scroll_handle: UniformListScrollHandle::new(),
+                _subscriptions,
+            };
+            this.update_matches(cx);
+            this
+        })
+    }
+
+    fn listed_items(&self) -> Arc<[SettingItem]> {
+        if self.language.is_some() {
+            self.language_items.clone()
+        } else {
+            self.items.clone()
+        }
+    }
+
+    fn update_matches(&mut self, cx: &mut Context<Self>) {
+        let query = self.search_editor.read(cx).text(cx);
+        self.matches = self
+            .listed_items()
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| item.matches_query(&query))
+            .map(|(ix, _)| ix)
+            .collect();
+        self.scroll_handle.scroll_to_item(0, ScrollStrategy::Top);
+        cx.notify();
+    }
+
+    fn set_scope(&mut self, scope: SettingsScope, cx: &mut Context<Self>) {
+        self.scope = scope;
+        self.string_editors.clear();
+        cx.notify();
+    }
+
+    fn set_language(&mut self, language: Option<String>, cx: &mut Context<Self>) {
+        self.language = language;
+        self.string_editors.clear();
+        self.update_matches(cx);
+    }
+
+    /// The scopes that can be edited: the user settings, and the settings of each local worktree.
+    fn scopes(&self, cx: &App) -> Vec<SettingsScope> {
+        let mut scopes = vec![SettingsScope::User];
+        if let Some(workspace) = self.workspace.upgrade() {
+            let project = workspace.read(cx).project().read(cx);
+            if project.is_local() {
+                scopes.extend(project.visible_worktrees(cx).map(|worktree| {
+                    let worktree = worktree.read(cx);
+                    SettingsScope::Project {
+                        worktree_id: worktree.id(),
+                        worktree_abs_path: worktree.abs_path(),
+                        name: worktree.root_name().to_string().into(),
+                    }
+                }));
+            }
+        }
+        scopes
+    }
+
+    /// The key path the setting is written at in the settings file.
+    fn key_path_to_write(&self, item: &SettingItem) -> Vec<String> {
+        let mut key_path = Vec::new();
+        if let Some(language) = &self.language {
+            key_path.extend(["languages".to_string(), language.clone()]);
+        }
+        key_path.extend(item.key_path.iter().cloned());
+        key_path
+    }
+
+    /// The value in effect at the root of the scope's worktree, or for all projects, resolved
+    /// through the same settings layers as the settings inspector.
+    fn effective_value(&self, item: &SettingItem, cx: &App) -> Option<EffectiveValue> {
+        let key_path = item.key_path.iter().map(String::as_str).collect::<Vec<_>>();
+        let location = self
+            .scope
+            .worktree_id()
+            .map(|worktree_id| SettingsLocation {
+                worktree_id,
+                path: Path::new(""),
+            });
+        let layer = cx
+            .global::<SettingsStore>()
+            .value_layers_at_key_path(&key_path, location, self.language.as_deref())
+            .pop()?;
+        Some(EffectiveValue {
+            value: layer.value,
+            source: layer.source,
+            for_language: layer.for_language,
+        })
+    }
+
+    fn open_settings_file(&self, window: &mut Window, cx: &mut App) {
+        let path = match &self.scope {
+            SettingsScope::User => paths::settings_file().clone(),
+            SettingsScope::Project {
+                worktree_abs_path, ..
+            } => worktree_abs_path.join(paths::local_settings_file_relative_path()),
+        };
+        self.workspace
+            .update(cx, |workspace, cx| {
+                workspace
+                    .open_abs_path(path, false, window, cx)
+                    .detach_and_log_err(cx);
+            })
+            .log_err();
+    }
+
+    fn render_scope_menu(&self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let this = cx.weak_entity();
+        let scopes = self.scopes(cx);
+        let current_scope = self.scope.clone();
+        let menu = ContextMenu::build(window, cx, move |mut menu, _, _| {
+            for scope in scopes {
+                let this = this.clone();
+                menu = menu.toggleable_entry(
+                    scope.label(),
+                    scope == current_scope,
+                    IconPosition::Start,
+                    None,
+                    move |_, cx| {
+                        this.update(cx, |this, cx| this.set_scope(scope.clone(), cx))
+                            .ok();
+                    },
+                );
+            }
+            menu
+        });
+        DropdownMenu::new("settings-scope", self.scope.label(), menu)
+    }
+
+    fn render_language_menu(
+        &self,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let this = cx.weak_entity();
+        let languages = std::iter::once(None)
+            .chain(self.language_names.iter().cloned().map(Some))
+            .collect::<Vec<_>>();
+        let current_language = self.language.clone();
+        let menu = ContextMenu::build(window, cx, move |mut menu, _, _| {
+            for language in languages {
+                let this = this.clone();
+                menu = menu.toggleable_entry(
+                    language.clone().unwrap_or_else(|| "All Languages".into()),
+                    language == current_language,
+                    IconPosition::Start,
+                    None,
+                    move |_, cx| {
+                        this.update(cx, |this, cx| this.set_language(language.clone(), cx))
+                            .ok();
+                    },
+                );
+            }
+            menu
+        });
+        DropdownMenu::new(
+            "settings-language",
+            self.language
+                .clone()
+                .unwrap_or_else(|| "All Languages".into()),
+            menu,
+        )
+    }
+
+    fn render_items(
+        &mut self,
+        range: Range<usize>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Vec<AnyElement> {
+        let items = self.listed_items();
+        range
+            .filter_map(|ix| {
+                let item = items.get(*self.matches.get(ix)?)?;
+                Some(self.render_item(ix, item, window, cx))
+            })
+            .collect()
+    }
+
+    fn render_item(
+        &mut self,
+        ix: usize,
+        item: &SettingItem,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> AnyElement {
+        let effective_value = self.effective_value(item, cx);
+        let source_label = match &effective_value {
+            Some(effective_value) => source_label(
+                effective_value.source,
+                self.language
+                    .as_deref()
+                    .filter(|_| effective_value.for_language),
+            ),
+            None => "Not set".into(),
+        };
+        // Only values set in the edited file itself can be reset from there.
+        let is_set_in_scope = effective_value.as_ref().is_some_and(|effective_value| {
+            effective_value.source == self.scope.source()
+                && effective_value.for_language == self.language.is_some()
+        });
+        let value = effective_value.map_or(Value::Null, |effective_value| effective_value.value);
+        let file = self.scope.file();
+        let key_path = self.key_path_to_write(item);
+        let description = item
+            .description
+            .as_deref()
+            .and_then(|description| description.lines().next())
+            .unwrap_or_default()
+            .to_string();
+
+        h_flex()
+            .id(("setting", ix))
+            .w_full()
+            .px_3()
+            .py_1p5()
+            .gap_4()
+            .justify_between()
+            .border_b_1()
+            .border_color(cx.theme().colors().border_variant)
+            .child(
+                v_flex()
+                    .flex_1()
+                    .min_w_0()
+                    .child(
+                        h_flex().gap_2().child(Label::new(item.title())).child(
+                            Label::new(source_label)
+                                .size(LabelSize::XSmall)
+                                .color(Color::Muted),
+                        ),
+                    )
+                    .child(
+                        Label::new(description)
+                            .size(LabelSize::Small)
+                            .color(Color::Muted)
+                            .single_line()
+                            .text_ellipsis(),
+                    ),
+            )
+            .child(
+                h_flex()
+                    .flex_none()
+                    .gap_1()
+                    .child(self.render_control(
+                        ix,
+                        item,
+                        &value,
+                        file.clone(),
+                        key_path.clone(),
+                        window,
+                        cx,
+                    ))
+                    .child(
+                        IconButton::new(("reset", ix), IconName::RotateCcw)
+                            .icon_size(IconSize::Small)
+                            .disabled(!is_set_in_scope)
+                            .tooltip(Tooltip::text("Reset to Inherited Value"))
+                            .on_click(move |_, _, cx| {
+                                write_setting(file.clone(), key_path.clone(), Value::Null, cx)
+                            }),
+                    ),
+            )
+            .into_any_element()
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn render_control(
+        &mut self,
+        ix: usize,
+        item: &SettingItem,
+        value: &Value,
+        file: SettingsFile,
+        key_path: Vec<String>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> AnyElement {
+        match &item.kind {
+            SettingKind::Boolean => {
+                Switch::new(("toggle", ix), value.as_bool().unwrap_or(false).into())
+                    .on_click(move |state, _, cx| {
+                        write_setting(
+                            file.clone(),
+                            key_path.clone(),
+                            Value::Bool(state.selected()),
+                            cx,
+                        )
+                    })
+                    .into_any_element()
+            }
+            SettingKind::Enum(values) => {
+                let values = values.clone();
+                let current_value = value.clone();
+                let menu = ContextMenu::build(window, cx, move |mut menu, _, _| {
+                    for value in values {
+                        let file = file.clone();
+                        let key_path = key_path.clone();
+                        menu = menu.toggleable_entry(
+                            value_label(&value),
+                            value == current_value,
+                            IconPosition::Start,
+                            None,
+                            move |_, cx| {
+                                write_setting(file.clone(), key_path.clone(), value.clone(), cx)
+                            },
+                        );
+                    }
+                    menu
+                });
+                DropdownMenu::new(("enum", ix), value_label(value), menu).into_any_element()
+            }
+            SettingKind::Number {
+                integer,
+                minimum,
+                maximum,
+            } => {
+                let integer = *integer;
+                let (minimum, maximum) = (*minimum, *maximum);
+                let current_value = value.as_f64().unwrap_or_default();
+                // Fractional settings like line heights and opacities need finer steps.
+                let step = if integer || current_value.abs() >= 2. {
+                    1.
+                } else {
+                    0.1
+                };
+                let stepped_value = move |delta: f64| {
+                    let mut new_value = current_value + delta;
+                    if let Some(minimum) = minimum {
+                        new_value = new_value.max(minimum);
+                    }
+                    if let Some(maximum) = maximum {
+                        new_value = new_value.min(maximum);
+                    }
+                    number_value(new_value, integer)
+                };
+                NumericStepper::new(
+                    ("number", ix),
+                    value_label(value),
+                    {
+                        let file = file.clone();
+                        let key_path = key_path.clone();
+                        move |_, _, cx| {
+                            write_setting(file.clone(), key_path.clone(), stepped_value(-step), cx)
+                        }
+                    },
+                    move |_, _, cx| {
+                        write_setting(file.clone(), key_path.clone(), stepped_value(step), cx)
+                    },
+                )
+                .into_any_element()
+            }
+            SettingKind::String => {
+                let editor = self.string_editor(key_path, value, window, cx);
+                h_flex()
+                    .w(rems(16.))
+                    .px_2()
+                    .py_1()
+                    .border_1()
+                    .border_color(cx.theme().colors().border)
+                    .rounded_md()
+                    .child(editor)
+                    .into_any_element()
+            }
+            SettingKind::Json => Button::new(("edit", ix), "Edit in settings.json")
+                .style(ButtonStyle::Subtle)
+                .label_size(LabelSize::Small)
+                .on_click(cx.listener(|this, _, window, cx| this.open_settings_file(window, cx)))
+                .into_any_element(),
+        }
+    }
+
+    /// Returns the editor of a string setting, which writes the setting when it loses focus.
+    fn string_editor(
+        &mut self,
+        key_path: Vec<String>,
+        value: &Value,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Entity<Editor> {
+        if let Some((editor, _)) = self.string_editors.get(&key_path) {
+            return editor.clone();
+        }
+
+        let initial_text = value.as_str().unwrap_or_default().to_string();
+        let editor = cx.new(|cx| {
+            let mut editor = Editor::single_line(window, cx);
+            editor.set_text(initial_text.clone(), window, cx);
+            editor
+        });
+        let file = self.scope.file();
+        let subscription = cx.subscribe(&editor, {
+            let key_path = key_path.clone();
+            move |_, editor, event: &EditorEvent, cx| {
+                if let EditorEvent::Blurred = event {
+                    let text = editor.read(cx).text(cx);
+                    if text != initial_text {
+                        write_setting(file.clone(), key_path.clone(), Value::String(text), cx);
+                    }
+                }
+            }
+        });
+        self.string_editors
+            .insert(key_path, (editor.clone(), subscription));
+        editor
+    }
+}
+
+fn write_setting(file: SettingsFile, key_path: Vec<String>, value: Value, cx: &App) {
+    cx.global::<SettingsStore>()
+        .update_settings_file_at_key_path(<dyn Fs>::global(cx), file, key_path, value);
+}
+
+pub(crate) fn source_label(source: SettingsValueSource, language: Option<&str>) -> SharedString {
+    let source = match source {
+        SettingsValueSource::Default => "Default",
+        SettingsValueSource::Extension => "Extension",
+        SettingsValueSource::User => "User settings",
+        SettingsValueSource::ReleaseChannel => "Release channel settings",
+        SettingsValueSource::Server => "Server settings",
+        SettingsValueSource::Project => "Project settings",
+        SettingsValueSource::Editorconfig => "EditorConfig",
+    };
+    match language {
+        Some(language) => format!("{source} · {language}").into(),
+        None => source.into(),
+    }
+}
+
+pub(crate) fn value_label(value: &Value) -> SharedString {
+    match value {
+        Value::Null => "Not set".into(),
+        Value::String(value) => value.clone().into(),
+        value => value.to_string().into(),
+    }
+}
+
+fn number_value(value: f64, integer: bool) -> Value {
+    if integer {
+        Value::from(value.round() as i64)
+    } else {
+        // Avoid writing floating point noise like `1.2000000000000002`.
+        Value::from((value * 100.).round() / 100.)
+    }
+}
+
+impl EventEmitter<ItemEvent> for SettingsPage {}
+
+impl Focusable for SettingsPage {
+    fn focus_handle(&self, _cx: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Item for SettingsPage {
+    type Event = ItemEvent;
+
+    fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::Settings))
+    }
+
+    fn tab_content_text(&self, _window: &Window, _cx: &App) -> Option<SharedString> {
+        Some("Settings".into())
+    }
+
+    fn show_toolbar(&self) -> bool {
+        false
+    }
+
+    fn to_item_events(event: &Self::Event, mut f: impl FnMut(ItemEvent)) {
+        f(*event)
+    }
+}
+
+impl Render for SettingsPage {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let show_common_settings = self.search_editor.read(cx).is_empty(cx)
+            && self.scope == SettingsScope::User
+            && self.language.is_none();
+
+        v_flex()
+            .key_context("SettingsPage")
+            .track_focus(&self.focus_handle)
+            .p_4()
+            .size_full()
+            .gap_4()
+            .child(Label::new("Settings").size(LabelSize::Large))
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(render_search_input(&self.search_editor, cx))
+                    .child(self.render_scope_menu(window, cx))
+                    .child(self.render_language_menu(window, cx)),
+            )
+            .when(show_common_settings, |this| {
+                this.child(
+                    h_flex()
+                        .flex_none()
+                        .gap_4()
+                        .items_start()
+                        .child(
+                            v_flex()
+                                .flex_1()
+                                .gap_1()
+                                .child(Label::new("Appearance"))
+                                .child(
+                                    v_flex()
+                                        .elevation_2(cx)
+                                        .child(AppearanceSettingsControls::new()),
+                                ),
+                        )
+                        .child(
+                            v_flex().flex_1().gap_1().child(Label::new("Editor")).child(
+                                v_flex()
+                                    .elevation_2(cx)
+                                    .child(EditorSettingsControls::new()),
+                            ),
+                        ),
+                )
+            })
+            .child(if self.matches.is_empty() {
+                div()
+                    .flex_1()
+                    .child(Label::new("No settings found").color(Color::Muted))
+                    .into_any_element()
+            } else {
+                v_flex()
+                    .flex_1()
+                    .elevation_1(cx)
+                    .child(
+                        uniform_list(
+                            cx.entity(),
+                            "settings",
+                            self.matches.len(),
+                            |this, range, window, cx| this.render_items(range, window, cx),
+                        )
+                        .size_full()
+                        .track_scroll(self.scroll_handle.clone()),
+                    )
+                    .into_any_element()
+            })
+    }
+}
+
+/// Renders a search box for a single-line editor.
+pub(crate) fn render_search_input(editor: &Entity<Editor>, cx: &App) -> impl IntoElement {
+    let settings = ThemeSettings::get_global(cx);
+    let text_style = TextStyle {
+        color: cx.theme().colors().text,
+        font_family: settings.ui_font.family.clone(),
+        font_features: settings.ui_font.features.clone(),
+        font_fallbacks: settings.ui_font.fallbacks.clone(),
+        font_size: rems(0.875).into(),
+        font_weight: settings.ui_font.weight,
+        line_height: relative(1.3),
+        ..Default::default()
+    };
+
+    h_flex()
+        .flex_1()
+        .px_2()
+        .py_1()
+        .gap_2()
+        .border_1()
+        .border_color(cx.theme().colors().border)
+        .rounded_lg()
+        .child(Icon::new(IconName::MagnifyingGlass))
+        .child(EditorElement::new(
+            editor,
+            EditorStyle {
+                background: cx.theme().colors().editor_background,
+                local_player: cx.theme().players().local(),
+                text: text_style,
+                ..Default::default()
+            },
+        ))
+}
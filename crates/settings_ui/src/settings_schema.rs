@@ -0,0 +1,403 @@
+use serde_json::Value;
+
+/// How deep nested objects are flattened into individual settings. Deeper objects are edited as JSON.
+const MAX_DEPTH: usize = 3;
+
+/// Root keys that aren't listed as settings: the release channel overrides repeat the whole
+/// schema, and the language overrides are edited by selecting a language.
+const SKIPPED_ROOT_KEYS: &[&str] = &["dev", "nightly", "preview", "stable", "languages"];
+
+/// A single setting described by the settings JSON schema.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SettingItem {
+    pub key_path: Vec<String>,
+    pub description: Option<String>,
+    pub kind: SettingKind,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SettingKind {
+    Boolean,
+    /// One of a fixed set of values, like the variants of an enum or the available font families.
+    Enum(Vec<Value>),
+    Number {
+        integer: bool,
+        minimum: Option<f64>,
+        maximum: Option<f64>,
+    },
+    String,
+    /// A value that can only be edited in the settings file, like a list or a map.
+    Json,
+}
+
+impl SettingItem {
+    pub fn title(&self) -> String {
+        self.key_path.join(".")
+    }
+
+    /// Whether every word of the query appears in the key path or in the description.
+    pub fn matches_query(&self, query: &str) -> bool {
+        let haystack = format!(
+            "{} {}",
+            self.key_path.join(" ").replace('_', " "),
+            self.description.as_deref().unwrap_or_default()
+        )
+        .to_lowercase();
+        query
+            .to_lowercase()
+            .split_whitespace()
+            .all(|word| haystack.contains(word) || self.title().contains(word))
+    }
+}
+
+/// Lists the settings described by the JSON schema of the settings file.
+pub fn setting_items(schema: &Value) -> Vec<SettingItem> {
+    let mut items = Vec::new();
+    if let Some(properties) = properties(resolve(schema, schema)) {
+        for (key, property) in properties {
+            if !SKIPPED_ROOT_KEYS.contains(&key.as_str()) {
+                collect_items(schema, property, vec![key.clone()], None, &mut items);
+            }
+        }
+    }
+    items.sort_by(|a, b| a.key_path.cmp(&b.key_path));
+    items
+}
+
+/// Lists the settings that can be overridden for a single language, under `languages.<language>`.
+pub fn language_setting_items(schema: &Value) -> Vec<SettingItem> {
+    let Some(languages) = properties(resolve(schema, schema))
+        .and_then(|properties| properties.get("languages"))
+        .map(|languages| resolve(schema, languages))
+    else {
+        return Vec::new();
+    };
+    // Every language shares the same schema, whether languages are listed or arbitrary keys.
+    let language = properties(languages)
+        .and_then(|languages| languages.values().next())
+        .or_else(|| {
+            languages
+                .get("additionalProperties")
+                .filter(|s| s.is_object())
+        });
+    let mut items = Vec::new();
+    if let Some(properties) = language.and_then(|language| properties(resolve(schema, language))) {
+        for (key, property) in properties {
+            collect_items(schema, property, vec![key.clone()], None, &mut items);
+        }
+    }
+    items.sort_by(|a, b| a.key_path.cmp(&b.key_path));
+    items
+}
+
+fn collect_items(
+    root: &Value,
+    schema: &Value,
+    key_path: Vec<String>,
+    description: Option<String>,
+    items: &mut Vec<SettingItem>,
+) {
+    let description = description.or_else(|| description_of(schema));
+    let schema = resolve(root, schema);
+    let description = description.or_else(|| description_of(schema));
+
+    if let Some(properties) = properties(schema).filter(|properties| !properties.is_empty()) {
+        if key_path.len() < MAX_DEPTH && !is_union(schema) {
+            for (key, property) in properties {
+                let mut key_path = key_path.clone();
+                key_path.push(key.clone());
+                collect_items(root, property, key_path, None, items);
+            }
+            return;
+        }
+    }
+
+    if let Some(variants) = union_variants(schema) {
+        let variants = variants
+            .iter()
+            .map(|variant| resolve(root, variant))
+            .filter(|variant| !is_null(variant))
+            .collect::<Vec<_>>();
+        if let [variant] = variants.as_slice() {
+            return collect_items(root, variant, key_path, description, items);
+        }
+        let values = variants
+            .iter()
+            .map(|variant| enum_values(variant))
+            .collect::<Option<Vec<_>>>();
+        let kind = match values {
+            Some(values) if !variants.is_empty() => SettingKind::Enum(values.concat()),
+            _ => SettingKind::Json,
+        };
+        items.push(SettingItem {
+            key_path,
+            description,
+            kind,
+        });
+        return;
+    }
+
+    let kind = if let Some(values) = enum_values(schema) {
+        SettingKind::Enum(values)
+    } else {
+        match non_null_types(schema).as_slice() {
+            ["boolean"] => SettingKind::Boolean,
+            [ty @ ("integer" | "number")] => SettingKind::Number {
+                integer: *ty == "integer",
+                minimum: schema.get("minimum").and_then(Value::as_f64),
+                maximum: schema.get("maximum").and_then(Value::as_f64),
+            },
+            ["string"] => SettingKind::String,
+            _ => SettingKind::Json,
+        }
+    };
+    items.push(SettingItem {
+        key_path,
+        description,
+        kind,
+    });
+}
+
+/// Follows `$ref`s to definitions and single-schema `allOf`s, which schemars uses to attach
+/// descriptions and defaults to references.
+fn resolve<'a>(root: &'a Value, mut schema: &'a Value) -> &'a Value {
+    // Bounded, in case of a cyclic reference.
+    for _ in 0..16 {
+        if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+            match reference
+                .strip_prefix("#/definitions/")
+                .and_then(|name| root.get("definitions")?.get(name))
+            {
+                Some(definition) => schema = definition,
+                None => break,
+            }
+        } else if let Some([single]) = schema
+            .get("allOf")
+            .and_then(Value::as_array)
+            .map(Vec::as_slice)
+        {
+            schema = single;
+        } else {
+            break;
+        }
+    }
+    schema
+}
+
+fn description_of(schema: &Value) -> Option<String> {
+    schema
+        .get("description")
+        .and_then(Value::as_str)
+        .map(|description| description.trim().to_string())
+}
+
+fn properties(schema: &Value) -> Option<&serde_json::Map<String, Value>> {
+    schema.get("properties").and_then(Value::as_object)
+}
+
+fn is_union(schema: &Value) -> bool {
+    union_variants(schema).is_some()
+}
+
+fn union_variants(schema: &Value) -> Option<&Vec<Value>> {
+    schema
+        .get("anyOf")
+        .or_else(|| schema.get("oneOf"))
+        .and_then(Value::as_array)
+}
+
+fn non_null_types(schema: &Value) -> Vec<&str> {
+    match schema.get("type") {
+        Some(Value::String(ty)) => vec![ty.as_str()],
+        Some(Value::Array(types)) => types
+            .iter()
+            .filter_map(Value::as_str)
+            .filter(|ty| *ty != "null")
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn is_null(schema: &Value) -> bool {
+    schema.get("type").and_then(Value::as_str) == Some("null")
+        || enum_values(schema).is_some_and(|values| values.is_empty())
+}
+
+/// The values allowed by an `enum` or `const` schema, without `null`.
+fn enum_values(schema: &Value) -> Option<Vec<Value>> {
+    let values = match (schema.get("enum"), schema.get("const")) {
+        (Some(Value::Array(values)), _) => values.as_slice(),
+        (_, Some(value)) => std::slice::from_ref(value),
+        _ => return None,
+    };
+    Some(
+        values
+            .iter()
+            .filter(|value| !value.is_null())
+            .cloned()
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "autosave": {
+                    "description": "Whether to save files automatically.",
+                    "type": ["boolean", "null"]
+                },
+                "buffer_font_family": {
+                    "$ref": "#/definitions/FontFamilies"
+                },
+                "tab_size": {
+                    "type": ["integer", "null"],
+                    "format": "uint32",
+                    "minimum": 1.0
+                },
+                "cursor_shape": {
+                    "description": "The shape of the cursor.",
+                    "anyOf": [
+                        { "$ref": "#/definitions/CursorShape" },
+                        { "type": "null" }
+                    ]
+                },
+                "terminal": {
+                    "allOf": [{ "$ref": "#/definitions/TerminalSettings" }]
+                },
+                "file_types": {
+                    "type": "object",
+                    "additionalProperties": { "type": "array" }
+                },
+                "languages": { "$ref": "#/definitions/Languages" },
+                "nightly": { "type": "object", "properties": { "autosave": {} } }
+            },
+            "definitions": {
+                "FontFamilies": { "enum": ["Zed Mono", "Zed Sans"] },
+                "CursorShape": {
+                    "oneOf": [
+                        { "description": "A bar.", "type": "string", "enum": ["bar"] },
+                        { "description": "A block.", "type": "string", "enum": ["block"] }
+                    ]
+                },
+                "TerminalSettings": {
+                    "type": "object",
+                    "properties": {
+                        "shell": {
+                            "anyOf": [
+                                { "type": "string" },
+                                { "type": "object" }
+                            ]
+                        },
+                        "line_height": { "type": ["number", "null"], "format": "float" }
+                    }
+                },
+                "Languages": {
+                    "type": "object",
+                    "properties": {
+                        "Rust": { "$ref": "#/definitions/LanguageSettingsContent" },
+                        "Python": { "$ref": "#/definitions/LanguageSettingsContent" }
+                    }
+                },
+                "LanguageSettingsContent": {
+                    "type": "object",
+                    "properties": {
+                        "tab_size": { "type": ["integer", "null"], "minimum": 1.0 },
+                        "formatter": { "type": ["string", "null"] }
+                    }
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn test_setting_items() {
+        let items = setting_items(&schema());
+        let item = |key_path: &[&str], description: Option<&str>, kind| SettingItem {
+            key_path: key_path.iter().map(|key| key.to_string()).collect(),
+            description: description.map(str::to_string),
+            kind,
+        };
+        pretty_assertions::assert_eq!(
+            items,
+            vec![
+                item(
+                    &["autosave"],
+                    Some("Whether to save files automatically."),
+                    SettingKind::Boolean
+                ),
+                item(
+                    &["buffer_font_family"],
+                    None,
+                    SettingKind::Enum(vec![json!("Zed Mono"), json!("Zed Sans")])
+                ),
+                item(
+                    &["cursor_shape"],
+                    Some("The shape of the cursor."),
+                    SettingKind::Enum(vec![json!("bar"), json!("block")])
+                ),
+                item(&["file_types"], None, SettingKind::Json),
+                item(
+                    &["tab_size"],
+                    None,
+                    SettingKind::Number {
+                        integer: true,
+                        minimum: Some(1.0),
+                        maximum: None
+                    }
+                ),
+                item(
+                    &["terminal", "line_height"],
+                    None,
+                    SettingKind::Number {
+                        integer: false,
+                        minimum: None,
+                        maximum: None
+                    }
+                ),
+                item(&["terminal", "shell"], None, SettingKind::Json),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_language_setting_items() {
+        let items = language_setting_items(&schema());
+        assert_eq!(
+            items
+                .iter()
+                .map(|item| (item.title(), item.kind.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("formatter".to_string(), SettingKind::String),
+                (
+                    "tab_size".to_string(),
+                    SettingKind::Number {
+                        integer: true,
+                        minimum: Some(1.0),
+                        maximum: None
+                    }
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_matches_query() {
+        let item = SettingItem {
+            key_path: vec!["terminal".into(), "line_height".into()],
+            description: Some("The line height of the terminal.".into()),
+            kind: SettingKind::Json,
+        };
+        assert!(item.matches_query("terminal"));
+        assert!(item.matches_query("Line Height"));
+        assert!(item.matches_query("terminal.line_height"));
+        assert!(item.matches_query("height terminal"));
+        assert!(!item.matches_query("font"));
+    }
+}
@@ -1,16 +1,19 @@
 mod appearance_settings_controls;
+mod keybindings;
+mod settings_inspector;
+mod settings_page;
+mod settings_schema;
 
 use std::any::TypeId;
 
 use command_palette_hooks::CommandPaletteFilter;
-use editor::EditorSettingsControls;
 use feature_flags::{FeatureFlag, FeatureFlagViewExt};
-use gpui::{actions, App, Entity, EventEmitter, FocusHandle, Focusable};
-use ui::prelude::*;
-use workspace::item::{Item, ItemEvent};
+use gpui::{actions, App};
 use workspace::Workspace;
 
-use crate::appearance_settings_controls::AppearanceSettingsControls;
+pub use crate::keybindings::KeymapEditor;
+pub use crate::settings_inspector::SettingsInspector;
+pub use crate::settings_page::SettingsPage;
 
 pub struct SettingsUiFeatureFlag;
 
@@ -18,7 +21,7 @@ impl FeatureFlag for SettingsUiFeatureFlag {
     const NAME: &'static str = "settings-ui";
 }
 
-actions!(zed, [OpenSettingsEditor]);
+actions!(zed, [OpenSettingsEditor, OpenKeymapEditor, InspectSetting]);
 
 pub fn init(cx: &mut App) {
     cx.observe_new(|workspace: &mut Workspace, window, cx| {
@@ -36,12 +39,34 @@ pub fn init(cx: &mut App) {
             if let Some(existing) = existing {
                 workspace.activate_item(&existing, true, true, window, cx);
             } else {
-                let settings_page = SettingsPage::new(workspace, cx);
+                let settings_page = SettingsPage::new(workspace, window, cx);
                 workspace.add_item_to_active_pane(Box::new(settings_page), None, true, window, cx)
             }
         });
 
-        let settings_ui_actions = [TypeId::of::<OpenSettingsEditor>()];
+        workspace.register_action(|workspace, _: &OpenKeymapEditor, window, cx| {
+            let existing = workspace
+                .active_pane()
+                .read(cx)
+                .items()
+                .find_map(|item| item.downcast::<KeymapEditor>());
+
+            if let Some(existing) = existing {
+                workspace.activate_item(&existing, true, true, window, cx);
+            } else {
+                let keymap_editor = KeymapEditor::new(window, cx);
+                workspace.add_item_to_active_pane(Box::new(keymap_editor), None, true, window, cx)
+            }
+        });
+
+        workspace.register_action(|workspace, _: &InspectSetting, window, cx| {
+            SettingsInspector::toggle(workspace, window, cx);
+        });
+
+        let settings_ui_actions = [
+            TypeId::of::<OpenSettingsEditor>(),
+            TypeId::of::<OpenKeymapEditor>(),
+        ];
 
         CommandPaletteFilter::update_global(cx, |filter, _cx| {
             filter.hide_action_types(&settings_ui_actions);
@@ -65,67 +90,3 @@ pub fn init(cx: &mut App) {
     })
     .detach();
 }
-
-pub struct SettingsPage {
-    focus_handle: FocusHandle,
-}
-
-impl SettingsPage {
-    pub fn new(_workspace: &Workspace, cx: &mut Context<Workspace>) -> Entity<Self> {
-        cx.new(|cx| Self {
-            focus_handle: cx.focus_handle(),
-        })
-    }
-}
-
-impl EventEmitter<ItemEvent> for SettingsPage {}
-
-impl Focusable for SettingsPage {
-    fn focus_handle(&self, _cx: &App) -> FocusHandle {
-        self.focus_handle.clone()
-    }
-}
-
-impl Item for SettingsPage {
-    type Event = ItemEvent;
-
-    fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
-        Some(Icon::new(IconName::Settings))
-    }
-
-    fn tab_content_text(&self, _window: &Window, _cx: &App) -> Option<SharedString> {
-        Some("Settings".into())
-    }
-
-    fn show_toolbar(&self) -> bool {
-        false
-    }
-
-    fn to_item_events(event: &Self::Event, mut f: impl FnMut(ItemEvent)) {
-        f(*event)
-    }
-}
-
-impl Render for SettingsPage {
-    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
-        v_flex()
-            .p_4()
-            .size_full()
-            .gap_4()
-            .child(Label::new("Settings").size(LabelSize::Large))
-            .child(
-                v_flex().gap_1().child(Label::new("Appearance")).child(
-                    v_flex()
-                        .elevation_2(cx)
-                        .child(AppearanceSettingsControls::new()),
-                ),
-            )
-            .child(
-                v_flex().gap_1().child(Label::new("Editor")).child(
-                    v_flex()
-                        .elevation_2(cx)
-                        .child(EditorSettingsControls::new()),
-                ),
-            )
-    }
-}
@@ -1,7 +1,47 @@
-use anyhow::{anyhow, Context as _, Result};
+use anyhow::{anyhow, bail, Context as _, Result};
+use collections::{HashMap, HashSet};
+use regex::Regex;
 use smallvec::SmallVec;
 use std::{collections::BTreeMap, ops::Range};
 
+/// Variables defined by VS Code, which are left empty when they have no value, rather than inserted as placeholders with their names.
+const KNOWN_VARIABLES: &[&str] = &[
+    "TM_SELECTED_TEXT",
+    "TM_CURRENT_LINE",
+    "TM_CURRENT_WORD",
+    "TM_LINE_INDEX",
+    "TM_LINE_NUMBER",
+    "TM_FILENAME",
+    "TM_FILENAME_BASE",
+    "TM_DIRECTORY",
+    "TM_FILEPATH",
+    "RELATIVE_FILEPATH",
+    "CLIPBOARD",
+    "WORKSPACE_NAME",
+    "WORKSPACE_FOLDER",
+    "CURSOR_INDEX",
+    "CURSOR_NUMBER",
+    "CURRENT_YEAR",
+    "CURRENT_YEAR_SHORT",
+    "CURRENT_MONTH",
+    "CURRENT_MONTH_NAME",
+    "CURRENT_MONTH_NAME_SHORT",
+    "CURRENT_DATE",
+    "CURRENT_DAY_NAME",
+    "CURRENT_DAY_NAME_SHORT",
+    "CURRENT_HOUR",
+    "CURRENT_MINUTE",
+    "CURRENT_SECOND",
+    "CURRENT_SECONDS_UNIX",
+    "CURRENT_TIMEZONE_OFFSET",
+    "RANDOM",
+    "RANDOM_HEX",
+    "UUID",
+    "BLOCK_COMMENT_START",
+    "BLOCK_COMMENT_END",
+    "LINE_COMMENT",
+];
+
 #[derive(Clone, Debug, Default, PartialEq)]
 pub struct Snippet {
     pub text: String,
@@ -12,14 +52,107 @@ pub struct Snippet {
 pub struct TabStop {
     pub ranges: SmallVec<[Range<isize>; 2]>,
     pub choices: Option<Vec<String>>,
+    /// Ranges mirroring the text of the tabstop through a transformation, as in `${1/(.*)/${1:/upcase}/}`.
+    pub transformed_mirrors: Vec<TransformedMirror>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TransformedMirror {
+    pub range: Range<isize>,
+    pub transformation: Transformation,
+}
+
+/// A regular expression replacement applied to a variable or a tabstop, as in `${TM_FILENAME/(.*)\..+$/$1/}`.
+#[derive(Clone, Debug)]
+pub struct Transformation {
+    regex: Regex,
+    format: Vec<FormatItem>,
+    global: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum FormatItem {
+    Text(String),
+    Group(usize),
+    Case(usize, CaseModifier),
+    Conditional {
+        group: usize,
+        if_matched: String,
+        otherwise: String,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum CaseModifier {
+    Upcase,
+    Downcase,
+    Capitalize,
+    CamelCase,
+    PascalCase,
 }
 
 impl Snippet {
     pub fn parse(source: &str) -> Result<Self> {
-        let mut text = String::with_capacity(source.len());
-        let mut tabstops = BTreeMap::new();
-        parse_snippet(source, false, &mut text, &mut tabstops)
+        Self::parse_with_variables(source, &HashMap::default())
+    }
+
+    /// Escapes the text so that it's inserted verbatim when used as the source of a snippet.
+    pub fn escape(text: &str) -> String {
+        let mut escaped = String::with_capacity(text.len());
+        for c in text.chars() {
+            if matches!(c, '$' | '\\' | '}') {
+                escaped.push('\\');
+            }
+            escaped.push(c);
+        }
+        escaped
+    }
+
+    /// The names of the variables the snippet refers to, so that only their values need to be
+    /// resolved before parsing it with [`Snippet::parse_with_variables`].
+    pub fn variable_names(source: &str) -> Result<HashSet<String>> {
+        let variables = HashMap::default();
+        let mut parser = Parser::new(&variables, HashMap::default());
+        parser
+            .parse_snippet(source, false)
             .context("failed to parse snippet")?;
+        Ok(parser.variable_names)
+    }
+
+    /// Parses the snippet, substituting its variables, like `$TM_FILENAME`, with the values given.
+    /// Unknown variables are inserted as placeholders with their names.
+    pub fn parse_with_variables(source: &str, variables: &HashMap<String, String>) -> Result<Self> {
+        let mut parser = Parser::new(variables, HashMap::default());
+        parser
+            .parse_snippet(source, false)
+            .context("failed to parse snippet")?;
+        if parser.has_transformed_mirrors {
+            // The text of the transformed mirrors depends on the values of their tabstops,
+            // which may only be defined later in the snippet.
+            let tabstop_values = parser.tabstop_values();
+            parser = Parser::new(variables, tabstop_values);
+            parser
+                .parse_snippet(source, false)
+                .context("failed to parse snippet")?;
+        }
+
+        let Parser {
+            text,
+            mut tabstops,
+            unknown_variables,
+            ..
+        } = parser;
+        let mut next_index = tabstops.keys().last().map_or(1, |index| index + 1);
+        let mut unknown_variable_indices = HashMap::<String, usize>::default();
+        for (name, range) in unknown_variables {
+            let index = *unknown_variable_indices.entry(name).or_insert_with(|| {
+                next_index += 1;
+                next_index - 1
+            });
+            tabstops.entry(index).or_default().ranges.push(range);
+        }
+        // Transformed mirrors of tabstops that are not defined are dropped.
+        tabstops.retain(|_, tabstop| !tabstop.ranges.is_empty());
 
         let len = text.len() as isize;
         let final_tabstop = tabstops.remove(&0);
@@ -30,7 +163,7 @@ impl Snippet {
         } else {
             let end_tabstop = TabStop {
                 ranges: [len..len].into_iter().collect(),
-                choices: None,
+                ..TabStop::default()
             };
 
             if !tabstops.last().map_or(false, |t| *t == end_tabstop) {
@@ -42,95 +175,311 @@ impl Snippet {
     }
 }
 
-fn parse_snippet<'a>(
-    mut source: &'a str,
-    nested: bool,
-    text: &mut String,
-    tabstops: &mut BTreeMap<usize, TabStop>,
-) -> Result<&'a str> {
-    loop {
-        match source.chars().next() {
-            None => return Ok(""),
-            Some('$') => {
-                source = parse_tabstop(&source[1..], text, tabstops)?;
+impl Transformation {
+    /// Replaces the first match of the regular expression in the text, or all of them with the `g` option.
+    pub fn apply(&self, text: &str) -> String {
+        let mut result = String::with_capacity(text.len());
+        let mut last_end = 0;
+        for captures in self.regex.captures_iter(text) {
+            let Some(matched) = captures.get(0) else {
+                continue;
+            };
+            result.push_str(&text[last_end..matched.start()]);
+            for item in &self.format {
+                let group = |group: usize| captures.get(group).map_or("", |group| group.as_str());
+                match item {
+                    FormatItem::Text(text) => result.push_str(text),
+                    FormatItem::Group(group_index) => result.push_str(group(*group_index)),
+                    FormatItem::Case(group_index, modifier) => {
+                        result.push_str(&modifier.apply(group(*group_index)))
+                    }
+                    FormatItem::Conditional {
+                        group: group_index,
+                        if_matched,
+                        otherwise,
+                    } => {
+                        if group(*group_index).is_empty() {
+                            result.push_str(otherwise);
+                        } else {
+                            result.push_str(if_matched);
+                        }
+                    }
+                }
             }
-            Some('\\') => {
-                // As specified in the LSP spec (`Grammar` section),
-                // backslashes can escape some characters:
-                // https://microsoft.github.io/language-server-protocol/specifications/lsp/3.17/specification/#snippet_syntax
-                source = &source[1..];
-                if let Some(c) = source.chars().next() {
-                    if c == '$' || c == '\\' || c == '}' {
-                        text.push(c);
-                        // All escapable characters are 1 byte long:
-                        source = &source[1..];
+            last_end = matched.end();
+            if !self.global {
+                break;
+            }
+        }
+        result.push_str(&text[last_end..]);
+        result
+    }
+}
+
+impl PartialEq for Transformation {
+    fn eq(&self, other: &Self) -> bool {
+        self.regex.as_str() == other.regex.as_str()
+            && self.format == other.format
+            && self.global == other.global
+    }
+}
+
+impl CaseModifier {
+    fn apply(self, text: &str) -> String {
+        fn capitalize(word: &str) -> String {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect())
+                .unwrap_or_default()
+        }
+        let words = || {
+            text.split(|c: char| !c.is_alphanumeric())
+                .filter(|word| !word.is_empty())
+        };
+        match self {
+            Self::Upcase => text.to_uppercase(),
+            Self::Downcase => text.to_lowercase(),
+            Self::Capitalize => capitalize(text),
+            Self::PascalCase => words().map(capitalize).collect(),
+            Self::CamelCase => words()
+                .enumerate()
+                .map(|(ix, word)| {
+                    if ix == 0 {
+                        let mut chars = word.chars();
+                        chars
+                            .next()
+                            .map(|first| first.to_lowercase().chain(chars).collect())
+                            .unwrap_or_default()
                     } else {
-                        text.push('\\');
+                        capitalize(word)
                     }
-                } else {
-                    text.push('\\');
+                })
+                .collect(),
+        }
+    }
+}
+
+struct Parser<'a> {
+    text: String,
+    tabstops: BTreeMap<usize, TabStop>,
+    /// Unknown variables, inserted as placeholders with their names.
+    unknown_variables: Vec<(String, Range<isize>)>,
+    variables: &'a HashMap<String, String>,
+    /// The names of all the variables parsed, known or not.
+    variable_names: HashSet<String>,
+    /// The values of the tabstops to compute their transformed mirrors from.
+    tabstop_values: HashMap<usize, String>,
+    has_transformed_mirrors: bool,
+}
+
+impl<'a> Parser<'a> {
+    fn new(variables: &'a HashMap<String, String>, tabstop_values: HashMap<usize, String>) -> Self {
+        Self {
+            text: String::new(),
+            tabstops: BTreeMap::new(),
+            unknown_variables: Vec::new(),
+            variables,
+            variable_names: HashSet::default(),
+            tabstop_values,
+            has_transformed_mirrors: false,
+        }
+    }
+
+    /// The text of each tabstop, taken from its first non-empty range.
+    fn tabstop_values(&self) -> HashMap<usize, String> {
+        self.tabstops
+            .iter()
+            .map(|(index, tabstop)| {
+                let value = tabstop
+                    .ranges
+                    .iter()
+                    .map(|range| &self.text[range.start as usize..range.end as usize])
+                    .find(|value| !value.is_empty())
+                    .unwrap_or_default();
+                (*index, value.to_string())
+            })
+            .collect()
+    }
+
+    fn parse_snippet<'s>(&mut self, mut source: &'s str, nested: bool) -> Result<&'s str> {
+        loop {
+            match source.chars().next() {
+                None => return Ok(""),
+                Some('$') => {
+                    source = self.parse_tabstop_or_variable(&source[1..])?;
                 }
-            }
-            Some('}') => {
-                if nested {
-                    return Ok(source);
-                } else {
-                    text.push('}');
+                Some('\\') => {
+                    // As specified in the LSP spec (`Grammar` section),
+                    // backslashes can escape some characters:
+                    // https://microsoft.github.io/language-server-protocol/specifications/lsp/3.17/specification/#snippet_syntax
                     source = &source[1..];
+                    if let Some(c) = source.chars().next() {
+                        if c == '$' || c == '\\' || c == '}' {
+                            self.text.push(c);
+                            // All escapable characters are 1 byte long:
+                            source = &source[1..];
+                        } else {
+                            self.text.push('\\');
+                        }
+                    } else {
+                        self.text.push('\\');
+                    }
+                }
+                Some('}') => {
+                    if nested {
+                        return Ok(source);
+                    } else {
+                        self.text.push('}');
+                        source = &source[1..];
+                    }
+                }
+                Some(_) => {
+                    let chunk_end = source.find(['}', '$', '\\']).unwrap_or(source.len());
+                    let (chunk, rest) = source.split_at(chunk_end);
+                    self.text.push_str(chunk);
+                    source = rest;
                 }
-            }
-            Some(_) => {
-                let chunk_end = source.find(['}', '$', '\\']).unwrap_or(source.len());
-                let (chunk, rest) = source.split_at(chunk_end);
-                text.push_str(chunk);
-                source = rest;
             }
         }
     }
-}
 
-fn parse_tabstop<'a>(
-    mut source: &'a str,
-    text: &mut String,
-    tabstops: &mut BTreeMap<usize, TabStop>,
-) -> Result<&'a str> {
-    let tabstop_start = text.len();
-    let tabstop_index;
-    let mut choices = None;
-
-    if source.starts_with('{') {
-        let (index, rest) = parse_int(&source[1..])?;
-        tabstop_index = index;
-        source = rest;
+    fn parse_tabstop_or_variable<'s>(&mut self, source: &'s str) -> Result<&'s str> {
+        let name = source.strip_prefix('{').unwrap_or(source);
+        if name.starts_with(|c: char| c.is_ascii_digit()) {
+            self.parse_tabstop(source)
+        } else if name.starts_with(|c: char| c == '_' || c.is_ascii_alphabetic()) {
+            self.parse_variable(source)
+        } else {
+            // A dollar sign that starts neither a tabstop nor a variable is inserted as is.
+            self.text.push('$');
+            Ok(source)
+        }
+    }
+
+    fn parse_tabstop<'s>(&mut self, mut source: &'s str) -> Result<&'s str> {
+        let tabstop_start = self.text.len();
+        let tabstop_index;
+        let mut choices = None;
+        let mut transformation = None;
+
+        if source.starts_with('{') {
+            let (index, rest) = parse_int(&source[1..])?;
+            tabstop_index = index;
+            source = rest;
+
+            if source.starts_with("|") {
+                (source, choices) = parse_choices(&source[1..], &mut self.text)?;
+            }
+
+            if source.starts_with(':') {
+                source = self.parse_snippet(&source[1..], true)?;
+            } else if source.starts_with('/') {
+                let (parsed_transformation, rest) = parse_transformation(&source[1..])?;
+                transformation = Some(parsed_transformation);
+                source = rest;
+            }
 
-        if source.starts_with("|") {
-            (source, choices) = parse_choices(&source[1..], text)?;
+            if source.starts_with('}') {
+                source = &source[1..];
+            } else {
+                return Err(anyhow!("expected a closing brace"));
+            }
+        } else {
+            let (index, rest) = parse_int(source)?;
+            tabstop_index = index;
+            source = rest;
         }
 
-        if source.starts_with(':') {
-            source = parse_snippet(&source[1..], true, text, tabstops)?;
+        if let Some(transformation) = transformation {
+            self.has_transformed_mirrors = true;
+            if let Some(value) = self.tabstop_values.get(&tabstop_index) {
+                self.text.push_str(&transformation.apply(value));
+            }
+            let range = tabstop_start as isize..self.text.len() as isize;
+            self.tabstops
+                .entry(tabstop_index)
+                .or_default()
+                .transformed_mirrors
+                .push(TransformedMirror {
+                    range,
+                    transformation,
+                });
+            return Ok(source);
         }
 
-        if source.starts_with('}') {
+        let tabstop = self.tabstops.entry(tabstop_index).or_default();
+        if tabstop.choices.is_none() {
+            tabstop.choices = choices;
+        }
+        tabstop
+            .ranges
+            .push(tabstop_start as isize..self.text.len() as isize);
+        Ok(source)
+    }
+
+    fn parse_variable<'s>(&mut self, mut source: &'s str) -> Result<&'s str> {
+        let variable_start = self.text.len();
+        let braced = source.starts_with('{');
+        if braced {
             source = &source[1..];
-        } else {
-            return Err(anyhow!("expected a closing brace"));
         }
-    } else {
-        let (index, rest) = parse_int(source)?;
-        tabstop_index = index;
+        let name_len = source
+            .find(|c: char| c != '_' && !c.is_ascii_alphanumeric())
+            .unwrap_or(source.len());
+        let (name, rest) = source.split_at(name_len);
         source = rest;
-    }
+        self.variable_names.insert(name.to_string());
+
+        let value = self
+            .variables
+            .get(name)
+            .map(String::as_str)
+            .filter(|value| !value.is_empty());
+        let mut has_default = false;
+        let mut transformation = None;
+        if braced {
+            if source.starts_with(':') {
+                has_default = true;
+                source = if value.is_some() {
+                    // The default is not used, only parsed to find where it ends.
+                    Parser::new(self.variables, HashMap::default())
+                        .parse_snippet(&source[1..], true)?
+                } else {
+                    self.parse_snippet(&source[1..], true)?
+                };
+            } else if source.starts_with('/') {
+                let (parsed_transformation, rest) = parse_transformation(&source[1..])?;
+                transformation = Some(parsed_transformation);
+                source = rest;
+            }
 
-    tabstops
-        .entry(tabstop_index)
-        .or_insert_with(|| TabStop {
-            ranges: Default::default(),
-            choices,
-        })
-        .ranges
-        .push(tabstop_start as isize..text.len() as isize);
-    Ok(source)
+            if source.starts_with('}') {
+                source = &source[1..];
+            } else {
+                return Err(anyhow!("expected a closing brace"));
+            }
+        }
+
+        let value = match value {
+            Some(value) => value,
+            None if has_default => return Ok(source),
+            None if KNOWN_VARIABLES.contains(&name) => "",
+            None => {
+                self.text.push_str(name);
+                self.unknown_variables.push((
+                    name.to_string(),
+                    variable_start as isize..self.text.len() as isize,
+                ));
+                return Ok(source);
+            }
+        };
+        match transformation {
+            Some(transformation) => self.text.push_str(&transformation.apply(value)),
+            None => self.text.push_str(value),
+        }
+        Ok(source)
+    }
 }
 
 fn parse_int(source: &str) -> Result<(usize, &str)> {
@@ -199,6 +548,164 @@ fn parse_choices<'a>(
     }
 }
 
+/// Parses the `regex/format/options` part of a transformation, up to its closing brace.
+fn parse_transformation(source: &str) -> Result<(Transformation, &str)> {
+    let mut pattern = String::new();
+    let mut chars = source.char_indices();
+    let format_start = loop {
+        match chars.next() {
+            None => bail!("expected a slash after the transformation's regular expression"),
+            Some((ix, '/')) => break ix + 1,
+            Some((_, '\\')) => match chars.next() {
+                Some((_, '/')) => pattern.push('/'),
+                Some((_, c)) => {
+                    pattern.push('\\');
+                    pattern.push(c);
+                }
+                None => pattern.push('\\'),
+            },
+            Some((_, c)) => pattern.push(c),
+        }
+    };
+    let (format, source) = parse_format(&source[format_start..])?;
+
+    let options_len = source.find('}').unwrap_or(source.len());
+    let (options, source) = source.split_at(options_len);
+    let mut flags = String::new();
+    let mut global = false;
+    for option in options.chars() {
+        match option {
+            'g' => global = true,
+            'i' | 'm' | 's' => flags.push(option),
+            _ => {}
+        }
+    }
+    if !flags.is_empty() {
+        pattern = format!("(?{flags}){pattern}");
+    }
+    let regex = Regex::new(&pattern)
+        .with_context(|| format!("invalid transformation regex `{pattern}`"))?;
+    Ok((
+        Transformation {
+            regex,
+            format,
+            global,
+        },
+        source,
+    ))
+}
+
+/// Parses the format of a transformation, up to and including its closing slash.
+fn parse_format(mut source: &str) -> Result<(Vec<FormatItem>, &str)> {
+    let mut items = Vec::new();
+    loop {
+        let (text, rest) = parse_format_text(source, &['$', '/']);
+        if !text.is_empty() {
+            items.push(FormatItem::Text(text));
+        }
+        source = rest;
+        match source.chars().next() {
+            None => bail!("expected a slash after the transformation's format"),
+            Some('/') => return Ok((items, &source[1..])),
+            Some(_) => {
+                let (item, rest) = parse_format_item(&source[1..])?;
+                items.push(item);
+                source = rest;
+            }
+        }
+    }
+}
+
+/// Parses a format item after its dollar sign: `1`, `{1}`, `{1:/upcase}`, `{1:+if}`, `{1:?if:else}` or `{1:-else}`.
+fn parse_format_item(source: &str) -> Result<(FormatItem, &str)> {
+    let Some(source) = source.strip_prefix('{') else {
+        let (group, rest) = parse_int(source)?;
+        return Ok((FormatItem::Group(group), rest));
+    };
+    let (group, source) = parse_int(source)?;
+    let (item, source) = if let Some(rest) = source.strip_prefix(":/") {
+        let name_len = rest.find('}').unwrap_or(rest.len());
+        let modifier = match &rest[..name_len] {
+            "upcase" => CaseModifier::Upcase,
+            "downcase" => CaseModifier::Downcase,
+            "capitalize" => CaseModifier::Capitalize,
+            "camelcase" => CaseModifier::CamelCase,
+            "pascalcase" => CaseModifier::PascalCase,
+            modifier => bail!("unknown case modifier `{modifier}`"),
+        };
+        (FormatItem::Case(group, modifier), &rest[name_len..])
+    } else if let Some(rest) = source.strip_prefix(":+") {
+        let (if_matched, rest) = parse_format_text(rest, &['}']);
+        let item = FormatItem::Conditional {
+            group,
+            if_matched,
+            otherwise: String::new(),
+        };
+        (item, rest)
+    } else if let Some(rest) = source.strip_prefix(":?") {
+        let (if_matched, rest) = parse_format_text(rest, &[':']);
+        let rest = rest
+            .strip_prefix(':')
+            .ok_or_else(|| anyhow!("expected a colon"))?;
+        let (otherwise, rest) = parse_format_text(rest, &['}']);
+        let item = FormatItem::Conditional {
+            group,
+            if_matched,
+            otherwise,
+        };
+        (item, rest)
+    } else if let Some(rest) = source
+        .strip_prefix(":-")
+        .or_else(|| source.strip_prefix(':'))
+    {
+        let (otherwise, rest) = parse_format_text(rest, &['}']);
+        let item = FormatItem::Conditional {
+            group,
+            if_matched: String::new(),
+            otherwise,
+        };
+        (item, rest)
+    } else {
+        (FormatItem::Group(group), source)
+    };
+    let source = source
+        .strip_prefix('}')
+        .ok_or_else(|| anyhow!("expected a closing brace"))?;
+    Ok((item, source))
+}
+
+/// Parses the text of a format up to one of the terminators given, unescaping the escaped characters.
+fn parse_format_text<'a>(mut source: &'a str, terminators: &[char]) -> (String, &'a str) {
+    let mut text = String::new();
+    loop {
+        match source.chars().next() {
+            None => return (text, source),
+            Some(c) if terminators.contains(&c) => return (text, source),
+            Some('\\') => {
+                source = &source[1..];
+                match source.chars().next() {
+                    Some('n') => text.push('\n'),
+                    Some('t') => text.push('\t'),
+                    Some(c @ ('$' | '/' | '\\' | '}' | ':')) => text.push(c),
+                    Some(c) => {
+                        text.push('\\');
+                        text.push(c);
+                    }
+                    None => {
+                        text.push('\\');
+                        return (text, source);
+                    }
+                }
+                source = &source[source.chars().next().map_or(0, char::len_utf8)..];
+            }
+            Some(c) => {
+                text.push(c);
+                source = &source[c.len_utf8()..];
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -327,6 +834,124 @@ mod tests {
         assert_eq!(tabstops(&snippet), &[vec![4..4], vec![7..7]]);
     }
 
+    #[test]
+    fn test_snippet_with_variables() {
+        let variables = HashMap::from_iter([
+            ("TM_FILENAME_BASE".to_string(), "main".to_string()),
+            ("CURRENT_YEAR".to_string(), "2024".to_string()),
+            ("TM_SELECTED_TEXT".to_string(), String::new()),
+        ]);
+
+        let snippet =
+            Snippet::parse_with_variables("mod $TM_FILENAME_BASE; // ${CURRENT_YEAR}", &variables)
+                .unwrap();
+        assert_eq!(snippet.text, "mod main; // 2024");
+        assert_eq!(tabstops(&snippet), &[vec![17..17]]);
+
+        // Empty and missing known variables use their defaults, or are left empty.
+        let snippet = Snippet::parse_with_variables(
+            "(${TM_SELECTED_TEXT:${1:value}})$CLIPBOARD${TM_FILENAME_BASE:unused}",
+            &variables,
+        )
+        .unwrap();
+        assert_eq!(snippet.text, "(value)main");
+        assert_eq!(tabstops(&snippet), &[vec![1..6], vec![11..11]]);
+
+        // Unknown variables become placeholders with their names.
+        let snippet =
+            Snippet::parse_with_variables("$1 $UNKNOWN ${UNKNOWN} $0", &variables).unwrap();
+        assert_eq!(snippet.text, " UNKNOWN UNKNOWN ");
+        assert_eq!(
+            tabstops(&snippet),
+            &[vec![0..0], vec![1..8, 9..16], vec![17..17]]
+        );
+
+        // Dollar signs that start neither a tabstop nor a variable are kept.
+        let snippet = Snippet::parse("cost: $ 5, ${}").unwrap();
+        assert_eq!(snippet.text, "cost: $ 5, ${}");
+
+        // Only the variables used are resolved, including those in defaults and placeholders.
+        let mut names = Snippet::variable_names(
+            "${1:$TM_FILENAME} ${TM_SELECTED_TEXT:$CLIPBOARD} ${UUID/-//g} $2",
+        )
+        .unwrap()
+        .into_iter()
+        .collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(
+            names,
+            ["CLIPBOARD", "TM_FILENAME", "TM_SELECTED_TEXT", "UUID"]
+        );
+    }
+
+    #[test]
+    fn test_snippet_with_variable_transformations() {
+        let variables = HashMap::from_iter([
+            (
+                "TM_FILENAME".to_string(),
+                "my_component.test.tsx".to_string(),
+            ),
+            ("TM_SELECTED_TEXT".to_string(), "foo bar".to_string()),
+        ]);
+        let snippet = Snippet::parse_with_variables(
+            r"${TM_FILENAME/(.*?)\..+$/${1:/pascalcase}/} ${TM_FILENAME/[._]/-/g} ${TM_SELECTED_TEXT/(\w+)/${1:/upcase}/}",
+            &variables,
+        )
+        .unwrap();
+        assert_eq!(snippet.text, "MyComponent my-component-test-tsx FOO bar");
+
+        let snippet = Snippet::parse_with_variables(
+            r"${TM_SELECTED_TEXT/(foo)|(baz)/${1:?found:missing}${2:+baz}${2:-none}/} ${TM_SELECTED_TEXT/BAR/\/${0:/downcase}\//i}",
+            &variables,
+        )
+        .unwrap();
+        assert_eq!(snippet.text, "foundnone bar foo /bar/");
+
+        assert!(Snippet::parse(r"${TM_FILENAME/(/$1/}").is_err());
+        assert!(Snippet::parse(r"${TM_FILENAME/(.*)/${1:/shout}/}").is_err());
+        assert!(Snippet::parse(r"${TM_FILENAME/(.*)/$1}").is_err());
+    }
+
+    #[test]
+    fn test_snippet_with_transformed_tabstops() {
+        let snippet = Snippet::parse("${1/(.*)/${1:/upcase}/} = ${1:name};$0").unwrap();
+        assert_eq!(snippet.text, "NAME = name;");
+        assert_eq!(tabstops(&snippet), &[vec![7..11], vec![12..12]]);
+        let mirrors = &snippet.tabstops[0].transformed_mirrors;
+        assert_eq!(mirrors.len(), 1);
+        assert_eq!(mirrors[0].range, 0..4);
+        assert_eq!(mirrors[0].transformation.apply("other name"), "OTHER NAME");
+
+        // Mirrors of tabstops that are never defined are dropped.
+        let snippet = Snippet::parse("a${2/(.*)/$1/}b").unwrap();
+        assert_eq!(snippet.text, "ab");
+        assert_eq!(tabstops(&snippet), &[vec![2..2]]);
+    }
+
+    #[test]
+    fn test_escaped_snippet() {
+        let text = r"const price = `${amount}\$` + $1;";
+        let snippet = Snippet::parse(&Snippet::escape(text)).unwrap();
+        assert_eq!(snippet.text, text);
+        assert_eq!(
+            tabstops(&snippet),
+            &[vec![text.len() as isize..text.len() as isize]]
+        );
+    }
+
+    #[test]
+    fn test_case_modifiers() {
+        assert_eq!(CaseModifier::Capitalize.apply("hello world"), "Hello world");
+        assert_eq!(
+            CaseModifier::CamelCase.apply("Hello-big_world"),
+            "helloBigWorld"
+        );
+        assert_eq!(
+            CaseModifier::PascalCase.apply("hello big world"),
+            "HelloBigWorld"
+        );
+    }
+
     fn tabstops(snippet: &Snippet) -> Vec<Vec<Range<isize>>> {
         snippet.tabstops.iter().map(|t| t.ranges.to_vec()).collect()
     }
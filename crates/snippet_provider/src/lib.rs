@@ -8,7 +8,7 @@ use std::{
     time::Duration,
 };
 
-use anyhow::Result;
+use anyhow::{Context as _, Result};
 use collections::{BTreeMap, BTreeSet, HashMap};
 use format::VSSnippetsFile;
 use fs::Fs;
@@ -133,7 +133,7 @@ async fn initial_scan(
 pub struct SnippetProvider {
     fs: Arc<dyn Fs>,
     snippets: HashMap<SnippetKind, BTreeMap<PathBuf, Vec<Arc<Snippet>>>>,
-    watch_tasks: Vec<Task<Result<()>>>,
+    watch_tasks: HashMap<Arc<Path>, Task<Result<()>>>,
 }
 
 // Watches global snippet directory, is created just once and reused across multiple projects
@@ -145,7 +145,7 @@ impl GlobalSnippetWatcher {
         let provider = cx.new(|_cx| SnippetProvider {
             fs,
             snippets: Default::default(),
-            watch_tasks: vec![],
+            watch_tasks: HashMap::default(),
         });
         provider.update(cx, |this, cx| {
             this.watch_directory(&global_snippets_dir, cx)
@@ -165,7 +165,7 @@ impl SnippetProvider {
             }
             let mut this = Self {
                 fs,
-                watch_tasks: Vec::new(),
+                watch_tasks: HashMap::default(),
                 snippets: Default::default(),
             };
 
@@ -178,26 +178,73 @@ impl SnippetProvider {
     }
 
     /// Add directory to be watched for content changes
-    fn watch_directory(&mut self, path: &Path, cx: &Context<Self>) {
+    pub fn watch_directory(&mut self, path: &Path, cx: &Context<Self>) {
         let path: Arc<Path> = Arc::from(path);
+        if self.watch_tasks.contains_key(&path) {
+            return;
+        }
+
+        let task = cx.spawn({
+            let path = path.clone();
+            |this, mut cx| async move {
+                let fs = this.update(&mut cx, |this, _| this.fs.clone())?;
+                'watch: loop {
+                    let exists = fs.is_dir(&path).await;
+                    if !exists {
+                        // Watch the parent directory, e.g. the project's `.zed`, until the directory is created.
+                        let parent = path.parent().context("no parent directory to watch")?;
+                        let parent_exists = fs.is_dir(parent).await;
+                        let (mut events, _) = fs.watch(parent, Duration::from_secs(1)).await;
+                        while events.next().await.is_some() {
+                            if fs.is_dir(&path).await || (!parent_exists && fs.is_dir(parent).await)
+                            {
+                                continue 'watch;
+                            }
+                        }
+                        return Ok(());
+                    }
 
-        self.watch_tasks.push(cx.spawn(|this, mut cx| async move {
-            let fs = this.update(&mut cx, |this, _| this.fs.clone())?;
-            let watched_path = path.clone();
-            let watcher = fs.watch(&watched_path, Duration::from_secs(1));
-            initial_scan(this.clone(), path, cx.clone()).await?;
-
-            let (mut entries, _) = watcher.await;
-            while let Some(entries) = entries.next().await {
-                process_updates(
-                    this.clone(),
-                    entries.into_iter().map(|event| event.path).collect(),
-                    cx.clone(),
-                )
-                .await?;
+                    // Events may come with the canonical path instead, e.g. through symlinks.
+                    let canonical_path = fs.canonicalize(&path).await.ok();
+                    let is_watched_dir = |dir: &Path| {
+                        dir == &*path || canonical_path.as_deref().is_some_and(|path| dir == path)
+                    };
+                    let (mut events, _) = fs.watch(&path, Duration::from_secs(1)).await;
+                    initial_scan(this.clone(), path.clone(), cx.clone()).await?;
+
+                    while let Some(events) = events.next().await {
+                        let paths = events
+                            .into_iter()
+                            .map(|event| event.path)
+                            .collect::<Vec<_>>();
+                        // Start over if the directory itself was removed.
+                        if paths.iter().any(|entry_path| is_watched_dir(entry_path))
+                            && !fs.is_dir(&path).await
+                        {
+                            continue 'watch;
+                        }
+                        let paths = paths
+                            .into_iter()
+                            .filter(|entry_path| entry_path.parent().is_some_and(is_watched_dir))
+                            .collect();
+                        process_updates(this.clone(), paths, cx.clone()).await?;
+                    }
+                    return Ok(());
+                }
             }
-            Ok(())
-        }));
+        });
+        self.watch_tasks.insert(path, task);
+    }
+
+    /// Stops watching the directory, and forgets the snippets loaded from it.
+    pub fn unwatch_directory(&mut self, path: &Path, cx: &mut Context<Self>) {
+        if self.watch_tasks.remove(path).is_none() {
+            return;
+        }
+        for snippets_of_kind in self.snippets.values_mut() {
+            snippets_of_kind.retain(|file_path, _| file_path.parent() != Some(path));
+        }
+        cx.notify();
     }
 
     fn lookup_snippets<'a, const LOOKUP_GLOBALS: bool>(
@@ -234,6 +281,25 @@ impl SnippetProvider {
         user_snippets
     }
 
+    /// Returns all the snippets available, with the language they apply to, or `None` for global snippets.
+    pub fn all_snippets(&self, cx: &App) -> Vec<(SnippetKind, Arc<Snippet>)> {
+        let mut kinds = self.snippets.keys().cloned().collect::<BTreeSet<_>>();
+        if let Some(global_watcher) = cx.try_global::<GlobalSnippetWatcher>() {
+            kinds.extend(global_watcher.0.read(cx).snippets.keys().cloned());
+        }
+        if let Some(registry) = SnippetRegistry::try_global(cx) {
+            kinds.extend(registry.kinds());
+        }
+        kinds
+            .into_iter()
+            .flat_map(|kind| {
+                self.lookup_snippets::<true>(&kind, cx)
+                    .into_iter()
+                    .map(move |snippet| (kind.clone(), snippet))
+            })
+            .collect()
+    }
+
     pub fn snippets_for(&self, language: SnippetKind, cx: &App) -> Vec<Arc<Snippet>> {
         let mut requested_snippets = self.lookup_snippets::<true>(&language, cx);
 
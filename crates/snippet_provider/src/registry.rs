@@ -51,4 +51,8 @@ impl SnippetRegistry {
     pub fn get_snippets(&self, kind: &SnippetKind) -> Vec<Arc<Snippet>> {
         self.snippets.read().get(kind).cloned().unwrap_or_default()
     }
+
+    pub(crate) fn kinds(&self) -> Vec<SnippetKind> {
+        self.snippets.read().keys().cloned().collect()
+    }
 }
@@ -0,0 +1,258 @@
+use collections::HashMap;
+use editor::Editor;
+use fuzzy::{match_strings, StringMatch, StringMatchCandidate};
+use gpui::{
+    App, Context, DismissEvent, Entity, EventEmitter, Focusable, ParentElement, Render, Styled,
+    Subscription, Task, WeakEntity, Window,
+};
+use picker::{Picker, PickerDelegate};
+use snippet::Snippet;
+use std::sync::Arc;
+use ui::{prelude::*, HighlightedLabel, ListItem, ListItemSpacing};
+use util::ResultExt;
+use workspace::ModalView;
+
+/// A snippet available in the project, with the language it applies to, or `None` for global snippets.
+type ScopedSnippet = (Option<String>, Arc<snippet_provider::Snippet>);
+
+/// A modal listing all the snippets, with a preview of the selected snippet's expansion.
+/// Confirming inserts the snippet into the editor the modal was opened from.
+pub struct SnippetBrowser {
+    picker: Entity<Picker<SnippetBrowserDelegate>>,
+    _subscription: Subscription,
+}
+
+impl SnippetBrowser {
+    pub(crate) fn new(
+        snippets: Vec<ScopedSnippet>,
+        variables: HashMap<String, String>,
+        editor: Option<WeakEntity<Editor>>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let candidates = snippets
+            .iter()
+            .enumerate()
+            .map(|(id, (_, snippet))| StringMatchCandidate::new(id, &snippet_label(snippet)))
+            .collect();
+        let delegate = SnippetBrowserDelegate {
+            snippets,
+            candidates,
+            variables,
+            editor,
+            matches: Vec::new(),
+            selected_index: 0,
+        };
+        let picker = cx.new(|cx| Picker::uniform_list(delegate, window, cx));
+        let _subscription = cx.subscribe(&picker, |_, _, _, cx| cx.emit(DismissEvent));
+        Self {
+            picker,
+            _subscription,
+        }
+    }
+}
+
+impl ModalView for SnippetBrowser {}
+
+impl EventEmitter<DismissEvent> for SnippetBrowser {}
+
+impl Focusable for SnippetBrowser {
+    fn focus_handle(&self, cx: &App) -> gpui::FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl Render for SnippetBrowser {
+    fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .key_context("SnippetBrowser")
+            .w(rems(34.))
+            .child(self.picker.clone())
+    }
+}
+
+fn snippet_label(snippet: &snippet_provider::Snippet) -> String {
+    let prefixes = snippet.prefix.join(", ");
+    match &snippet.description {
+        Some(description) => format!("{prefixes}: {description}"),
+        None => prefixes,
+    }
+}
+
+pub struct SnippetBrowserDelegate {
+    snippets: Vec<ScopedSnippet>,
+    candidates: Vec<StringMatchCandidate>,
+    /// The values of the snippet variables in the editor, to preview the snippets with.
+    variables: HashMap<String, String>,
+    editor: Option<WeakEntity<Editor>>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+}
+
+impl SnippetBrowserDelegate {
+    fn selected_snippet(&self) -> Option<&ScopedSnippet> {
+        let mat = self.matches.get(self.selected_index)?;
+        self.snippets.get(mat.candidate_id)
+    }
+}
+
+impl PickerDelegate for SnippetBrowserDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _window: &mut Window, _: &mut App) -> Arc<str> {
+        "Search snippets...".into()
+    }
+
+    fn no_matches_text(&self, _window: &mut Window, _: &mut App) -> SharedString {
+        "No snippets found".into()
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(
+        &mut self,
+        ix: usize,
+        _window: &mut Window,
+        _: &mut Context<Picker<Self>>,
+    ) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Task<()> {
+        let background = cx.background_executor().clone();
+        let candidates = self.candidates.clone();
+        cx.spawn_in(window, |this, mut cx| async move {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .map(|candidate| StringMatch {
+                        candidate_id: candidate.id,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    1000,
+                    &Default::default(),
+                    background,
+                )
+                .await
+            };
+
+            this.update(&mut cx, |this, cx| {
+                let delegate = &mut this.delegate;
+                delegate.matches = matches;
+                delegate.selected_index = delegate
+                    .selected_index
+                    .min(delegate.matches.len().saturating_sub(1));
+                cx.notify();
+            })
+            .log_err();
+        })
+    }
+
+    fn confirm(&mut self, _: bool, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let Some((_, snippet)) = self.selected_snippet() else {
+            return;
+        };
+        if let Some(editor) = self.editor.as_ref().and_then(|editor| editor.upgrade()) {
+            let body = snippet.body.clone();
+            editor.update(cx, |editor, cx| {
+                let Some(variable_names) = Snippet::variable_names(&body).log_err() else {
+                    return;
+                };
+                let variables = editor.snippet_variables(&variable_names, cx);
+                let Some(snippet) = Snippet::parse_with_variables(&body, &variables).log_err()
+                else {
+                    return;
+                };
+                let ranges = editor
+                    .selections
+                    .all::<usize>(cx)
+                    .into_iter()
+                    .map(|selection| selection.range())
+                    .collect::<Vec<_>>();
+                editor
+                    .insert_snippet(&ranges, snippet, window, cx)
+                    .log_err();
+                window.focus(&editor.focus_handle(cx));
+            });
+        }
+        cx.emit(DismissEvent);
+    }
+
+    fn dismissed(&mut self, _: &mut Window, cx: &mut Context<Picker<Self>>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _window: &mut Window,
+        _: &mut Context<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let mat = self.matches.get(ix)?;
+        let (language, _) = self.snippets.get(mat.candidate_id)?;
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .toggle_state(selected)
+                .child(HighlightedLabel::new(
+                    mat.string.clone(),
+                    mat.positions.clone(),
+                ))
+                .end_slot(
+                    Label::new(language.clone().unwrap_or_else(|| "Global".to_string()))
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                ),
+        )
+    }
+
+    fn render_footer(
+        &self,
+        _: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Option<gpui::AnyElement> {
+        let (_, snippet) = self.selected_snippet()?;
+        let preview = match Snippet::parse_with_variables(&snippet.body, &self.variables) {
+            Ok(expansion) => expansion.text,
+            Err(error) => format!("Invalid snippet: {error}"),
+        };
+        Some(
+            div()
+                .w_full()
+                .max_h(rems(12.))
+                .overflow_hidden()
+                .p_2()
+                .border_t_1()
+                .border_color(cx.theme().colors().border_variant)
+                .font_buffer(cx)
+                .text_buffer(cx)
+                .children(preview.lines().map(|line| {
+                    // Keep the height of empty lines.
+                    let line = if line.is_empty() { " " } else { line };
+                    div().whitespace_nowrap().child(line.to_string())
+                }))
+                .into_any_element(),
+        )
+    }
+}
@@ -1,17 +1,37 @@
+mod snippet_browser;
+
+use anyhow::Context as _;
+use editor::{scroll::Autoscroll, Editor};
 use fuzzy::{match_strings, StringMatch, StringMatchCandidate};
 use gpui::{
     actions, App, Context, DismissEvent, Entity, EventEmitter, Focusable, ParentElement, Render,
     Styled, WeakEntity, Window,
 };
-use language::LanguageRegistry;
-use paths::config_dir;
+use language::{LanguageRegistry, Point};
+use paths::{config_dir, snippets_dir};
 use picker::{Picker, PickerDelegate};
-use std::{borrow::Borrow, fs, sync::Arc};
+use snippet::Snippet;
+use snippet_browser::SnippetBrowser;
+use std::{borrow::Borrow, fs, ops::Range, sync::Arc};
 use ui::{prelude::*, HighlightedLabel, ListItem, ListItemSpacing};
 use util::ResultExt;
-use workspace::{notifications::NotifyResultExt, ModalView, Workspace};
+use workspace::{
+    notifications::{DetachAndPromptErr, NotifyResultExt},
+    ModalView, Workspace,
+};
+
+actions!(
+    snippets,
+    [
+        ConfigureSnippets,
+        OpenFolder,
+        CreateSnippetFromSelection,
+        BrowseSnippets
+    ]
+);
 
-actions!(snippets, [ConfigureSnippets, OpenFolder]);
+/// The name given to snippets created from a selection, to be replaced by the user.
+const NEW_SNIPPET_NAME: &str = "New snippet";
 
 pub fn init(cx: &mut App) {
     cx.observe_new(register).detach();
@@ -20,6 +40,8 @@ pub fn init(cx: &mut App) {
 fn register(workspace: &mut Workspace, _window: Option<&mut Window>, _: &mut Context<Workspace>) {
     workspace.register_action(configure_snippets);
     workspace.register_action(open_folder);
+    workspace.register_action(create_snippet_from_selection);
+    workspace.register_action(browse_snippets);
 }
 
 fn configure_snippets(
@@ -46,6 +68,145 @@ fn open_folder(
     cx.open_with_system(config_dir().join("snippets").borrow());
 }
 
+fn browse_snippets(
+    workspace: &mut Workspace,
+    _: &BrowseSnippets,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let snippets = workspace
+        .project()
+        .read(cx)
+        .snippets()
+        .read(cx)
+        .all_snippets(cx);
+    let editor = workspace.active_item_as::<Editor>(cx);
+    let variable_names = snippets
+        .iter()
+        .filter_map(|(_, snippet)| Snippet::variable_names(&snippet.body).ok())
+        .flatten()
+        .collect();
+    let variables = editor
+        .as_ref()
+        .map(|editor| {
+            editor.update(cx, |editor, cx| {
+                editor.snippet_variables(&variable_names, cx)
+            })
+        })
+        .unwrap_or_default();
+    let editor = editor.map(|editor| editor.downgrade());
+
+    workspace.toggle_modal(window, cx, move |window, cx| {
+        SnippetBrowser::new(snippets, variables, editor, window, cx)
+    });
+}
+
+/// Adds the selected text as a new snippet to the snippets file of the selection's language,
+/// and selects the snippet's name for the user to change it.
+fn create_snippet_from_selection(
+    workspace: &mut Workspace,
+    _: &CreateSnippetFromSelection,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let Some(editor) = workspace.active_item_as::<Editor>(cx) else {
+        return;
+    };
+    let (body, scope) = editor.update(cx, |editor, cx| {
+        let snapshot = editor.buffer().read(cx).snapshot(cx);
+        let selection = editor.selections.newest::<Point>(cx);
+        // Include the indentation of the first line, so that it's removed like the other lines'.
+        let line_start = Point::new(selection.start.row, 0);
+        let start = if snapshot
+            .text_for_range(line_start..selection.start)
+            .all(|chunk| chunk.trim().is_empty())
+        {
+            line_start
+        } else {
+            selection.start
+        };
+        let body = snapshot
+            .text_for_range(start..selection.end)
+            .collect::<String>();
+        let scope = snapshot
+            .language_at(selection.start)
+            .map_or_else(|| "snippets".to_string(), |language| language.lsp_id());
+        (body, scope)
+    });
+    if body.trim().is_empty() {
+        return;
+    }
+
+    let path = snippets_dir().join(scope + ".json");
+    if fs::create_dir_all(snippets_dir())
+        .notify_err(workspace, cx)
+        .is_none()
+    {
+        return;
+    }
+    let open_task = workspace.open_abs_path(path.clone(), false, window, cx);
+    cx.spawn_in(window, |_, mut cx| async move {
+        let editor = open_task
+            .await?
+            .downcast::<Editor>()
+            .context("the snippets file is not open in an editor")?;
+        editor.update_in(&mut cx, |editor, window, cx| {
+            let (range, new_text, name_range) = new_snippet_edit(&editor.text(cx), &body)
+                .with_context(|| format!("{path:?} does not contain a JSON object"))?;
+            editor.edit([(range, new_text)], cx);
+            editor.change_selections(Some(Autoscroll::fit()), window, cx, |selections| {
+                selections.select_ranges([name_range])
+            });
+            anyhow::Ok(())
+        })?
+    })
+    .detach_and_prompt_err("Failed to create the snippet", window, cx, |_, _, _| None);
+}
+
+/// Returns the edit appending a snippet with the given body to the text of a snippets file,
+/// and the range of the new snippet's name after the edit, or `None` if the text is not a JSON object.
+fn new_snippet_edit(file_text: &str, body: &str) -> Option<(Range<usize>, String, Range<usize>)> {
+    let body = body.trim_end();
+    let indentation = body
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    let body_lines = body
+        .lines()
+        .map(|line| {
+            let line = line.get(indentation..).unwrap_or(line.trim_start());
+            let line = serde_json::to_string(&Snippet::escape(line)).unwrap_or_default();
+            format!("      {line}")
+        })
+        .collect::<Vec<_>>()
+        .join(",\n");
+    // Snippets are keyed by name, so a snippet with the same name would replace an existing one.
+    let name = std::iter::once(NEW_SNIPPET_NAME.to_string())
+        .chain((2..).map(|ix| format!("{NEW_SNIPPET_NAME} {ix}")))
+        .find(|name| !file_text.contains(&format!("\"{name}\"")))?;
+    let entry = format!(
+        "  \"{name}\": {{\n    \"prefix\": \"\",\n    \"body\": [\n{body_lines}\n    ],\n    \"description\": \"\"\n  }}\n"
+    );
+
+    let (range, new_text) = match file_text.rfind('}') {
+        Some(object_end) => {
+            let content = file_text[..object_end].trim_end();
+            let separator = if content.ends_with(['{', ',']) {
+                ""
+            } else {
+                ","
+            };
+            (content.len()..object_end, format!("{separator}\n{entry}"))
+        }
+        None if file_text.trim().is_empty() => (0..file_text.len(), format!("{{\n{entry}}}\n")),
+        None => return None,
+    };
+    let name_start = range.start + new_text.find(&name)?;
+    Some((range, new_text, name_start..name_start + name.len()))
+}
+
 pub struct ScopeSelector {
     picker: Entity<Picker<ScopeSelectorDelegate>>,
 }
@@ -239,3 +400,62 @@ impl PickerDelegate for ScopeSelectorDelegate {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_snippet_edit() {
+        let apply = |file_text: &str, body: &str| {
+            let (range, new_text, name_range) = new_snippet_edit(file_text, body).unwrap();
+            let mut text = file_text.to_string();
+            text.replace_range(range, &new_text);
+            let name = text[name_range].to_string();
+            (text, name)
+        };
+
+        let (text, name) = apply("", "    if ${ready} {\n        go();\n    }\n");
+        assert_eq!(name, NEW_SNIPPET_NAME);
+        assert_eq!(
+            text,
+            r#"{
+  "New snippet": {
+    "prefix": "",
+    "body": [
+      "if \\${ready\\} {",
+      "    go();",
+      "\\}"
+    ],
+    "description": ""
+  }
+}
+"#
+        );
+
+        let (text, name) = apply("{\n  \"log\": { \"body\": \"log($1)\" }\n}\n", "x");
+        assert_eq!(name, NEW_SNIPPET_NAME);
+        assert_eq!(
+            text,
+            r#"{
+  "log": { "body": "log($1)" },
+  "New snippet": {
+    "prefix": "",
+    "body": [
+      "x"
+    ],
+    "description": ""
+  }
+}
+"#
+        );
+
+        // A second new snippet doesn't replace the first one.
+        let (text, name) = apply(&text, "y");
+        assert_eq!(name, "New snippet 2");
+        let (_, name) = apply(&text, "z");
+        assert_eq!(name, "New snippet 3");
+
+        assert!(new_snippet_edit("[1, 2]", "x").is_none());
+    }
+}
@@ -0,0 +1,615 @@
+//! A reader for Hunspell-format (`.aff` + `.dic`) dictionaries.
+//!
+//! Rather than running the affix rules when a word is checked, every stem is expanded into all of
+//! its affixed forms when the dictionary is loaded, so checking a word is a single lookup.
+//! Compounding and morphological fields are not supported.
+
+use anyhow::{anyhow, Context as _, Result};
+use collections::{HashMap, HashSet};
+
+/// The maximum number of suggestions returned for a misspelled word.
+const MAX_SUGGESTIONS: usize = 5;
+
+type Flag = u32;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum FlagFormat {
+    /// Every character is a flag.
+    #[default]
+    Char,
+    /// Every two characters are a flag.
+    Long,
+    /// Flags are comma-separated numbers.
+    Numeric,
+}
+
+impl FlagFormat {
+    fn parse(self, flags: &str) -> Vec<Flag> {
+        match self {
+            FlagFormat::Char => flags.chars().map(|c| c as Flag).collect(),
+            FlagFormat::Long => {
+                let chars = flags.chars().collect::<Vec<_>>();
+                chars
+                    .chunks(2)
+                    .map(|pair| {
+                        let high = pair[0] as Flag;
+                        let low = pair.get(1).map_or(0, |c| *c as Flag);
+                        (high << 16) | low
+                    })
+                    .collect()
+            }
+            FlagFormat::Numeric => flags
+                .split(',')
+                .filter_map(|flag| flag.trim().parse().ok())
+                .collect(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum ConditionPart {
+    Any,
+    Char(char),
+    Set { chars: Vec<char>, negated: bool },
+}
+
+impl ConditionPart {
+    fn matches(&self, c: char) -> bool {
+        match self {
+            ConditionPart::Any => true,
+            ConditionPart::Char(expected) => c == *expected,
+            ConditionPart::Set { chars, negated } => chars.contains(&c) != *negated,
+        }
+    }
+}
+
+/// The condition a stem has to meet for an affix to apply, e.g. `[^aeiou]y`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct Condition(Vec<ConditionPart>);
+
+impl Condition {
+    fn parse(condition: &str) -> Result<Self> {
+        if condition == "." {
+            return Ok(Self::default());
+        }
+        let mut parts = Vec::new();
+        let mut chars = condition.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '.' => parts.push(ConditionPart::Any),
+                '[' => {
+                    let mut set = Vec::new();
+                    let mut negated = false;
+                    loop {
+                        match chars.next() {
+                            Some(']') => break,
+                            Some('^') if set.is_empty() && !negated => negated = true,
+                            Some(c) => set.push(c),
+                            None => return Err(anyhow!("unterminated set in {condition:?}")),
+                        }
+                    }
+                    parts.push(ConditionPart::Set {
+                        chars: set,
+                        negated,
+                    });
+                }
+                c => parts.push(ConditionPart::Char(c)),
+            }
+        }
+        Ok(Self(parts))
+    }
+
+    fn matches_start(&self, word: &str) -> bool {
+        let mut chars = word.chars();
+        self.0
+            .iter()
+            .all(|part| chars.next().map_or(false, |c| part.matches(c)))
+    }
+
+    fn matches_end(&self, word: &str) -> bool {
+        let mut chars = word.chars().rev();
+        self.0
+            .iter()
+            .rev()
+            .all(|part| chars.next().map_or(false, |c| part.matches(c)))
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Affix {
+    strip: String,
+    add: String,
+    continuation_flags: Vec<Flag>,
+    condition: Condition,
+}
+
+#[derive(Clone, Debug, Default)]
+struct AffixGroup {
+    cross_product: bool,
+    affixes: Vec<Affix>,
+}
+
+impl Affix {
+    fn apply_prefix(&self, word: &str) -> Option<String> {
+        if !self.condition.matches_start(word) {
+            return None;
+        }
+        let rest = word.strip_prefix(self.strip.as_str())?;
+        Some(format!("{}{rest}", self.add))
+    }
+
+    fn apply_suffix(&self, word: &str) -> Option<String> {
+        if !self.condition.matches_end(word) {
+            return None;
+        }
+        let rest = word.strip_suffix(self.strip.as_str())?;
+        Some(format!("{rest}{}", self.add))
+    }
+}
+
+#[derive(Default)]
+struct AffixFile {
+    flag_format: FlagFormat,
+    flag_aliases: Vec<Vec<Flag>>,
+    prefixes: HashMap<Flag, AffixGroup>,
+    suffixes: HashMap<Flag, AffixGroup>,
+    need_affix: Option<Flag>,
+    forbidden_word: Option<Flag>,
+    only_in_compound: Option<Flag>,
+    try_chars: Vec<char>,
+    replacements: Vec<(String, String)>,
+}
+
+impl AffixFile {
+    fn parse(aff: &str) -> Result<Self> {
+        let mut file = Self::default();
+        let mut lines = aff.lines().enumerate();
+        while let Some((ix, line)) = lines.next() {
+            let mut fields = line.split_whitespace();
+            let Some(keyword) = fields.next() else {
+                continue;
+            };
+            let mut argument = || {
+                fields
+                    .next()
+                    .with_context(|| format!("missing argument for {keyword} on line {}", ix + 1))
+            };
+            match keyword {
+                "FLAG" => {
+                    file.flag_format = match argument()? {
+                        "long" => FlagFormat::Long,
+                        "num" => FlagFormat::Numeric,
+                        _ => FlagFormat::Char,
+                    }
+                }
+                "AF" => {
+                    // The first `AF` line holds the number of aliases that follow.
+                    if argument()?.parse::<usize>().is_err() {
+                        let flags = line.split_whitespace().nth(1).unwrap_or_default();
+                        file.flag_aliases.push(file.flag_format.parse(flags));
+                    }
+                }
+                "NEEDAFFIX" | "PSEUDOROOT" => file.need_affix = file.parse_flag(argument()?),
+                "FORBIDDENWORD" => file.forbidden_word = file.parse_flag(argument()?),
+                "ONLYINCOMPOUND" => file.only_in_compound = file.parse_flag(argument()?),
+                "TRY" => file.try_chars = argument()?.chars().collect(),
+                "REP" => {
+                    let from = argument()?;
+                    if let Some(to) = fields.next() {
+                        file.replacements
+                            .push((from.replace('_', " "), to.replace('_', " ")));
+                    }
+                }
+                "PFX" | "SFX" => {
+                    let flag = argument()?;
+                    let cross_product = argument()?;
+                    let count = argument()?
+                        .parse::<usize>()
+                        .with_context(|| format!("invalid affix count on line {}", ix + 1))?;
+                    let Some(flag) = file.parse_flag(flag) else {
+                        continue;
+                    };
+                    let mut group = AffixGroup {
+                        cross_product: cross_product == "Y",
+                        affixes: Vec::with_capacity(count),
+                    };
+                    for _ in 0..count {
+                        let Some((ix, line)) = lines.next() else {
+                            break;
+                        };
+                        group.affixes.push(file.parse_affix(line).with_context(|| {
+                            format!("invalid affix {line:?} on line {}", ix + 1)
+                        })?);
+                    }
+                    let groups = if keyword == "PFX" {
+                        &mut file.prefixes
+                    } else {
+                        &mut file.suffixes
+                    };
+                    groups.insert(flag, group);
+                }
+                _ => {}
+            }
+        }
+        Ok(file)
+    }
+
+    fn parse_flag(&self, flag: &str) -> Option<Flag> {
+        self.flag_format.parse(flag).first().copied()
+    }
+
+    fn parse_flags(&self, flags: &str) -> Vec<Flag> {
+        if !self.flag_aliases.is_empty() {
+            if let Some(alias) = flags
+                .parse::<usize>()
+                .ok()
+                .and_then(|ix| self.flag_aliases.get(ix.checked_sub(1)?))
+            {
+                return alias.clone();
+            }
+        }
+        self.flag_format.parse(flags)
+    }
+
+    fn parse_affix(&self, line: &str) -> Result<Affix> {
+        let mut fields = line.split_whitespace().skip(2);
+        let strip = fields.next().context("missing strip")?;
+        let add = fields.next().context("missing affix")?;
+        let condition = fields.next().unwrap_or(".");
+        let (add, continuation_flags) = match add.split_once('/') {
+            Some((add, flags)) => (add, self.parse_flags(flags)),
+            None => (add, Vec::new()),
+        };
+        let empty_or = |text: &str| {
+            if text == "0" {
+                String::new()
+            } else {
+                text.to_string()
+            }
+        };
+        Ok(Affix {
+            strip: empty_or(strip),
+            add: empty_or(add),
+            continuation_flags,
+            condition: Condition::parse(condition)?,
+        })
+    }
+
+    /// Adds the given stem and all of its affixed forms to `words`.
+    fn expand(&self, stem: &str, flags: &[Flag], words: &mut HashSet<String>) {
+        let has_flag = |flag: Option<Flag>| flag.map_or(false, |flag| flags.contains(&flag));
+        if !has_flag(self.need_affix) && !has_flag(self.only_in_compound) {
+            words.insert(stem.to_string());
+        }
+
+        let mut cross_product_suffixed = Vec::new();
+        for flag in flags {
+            let Some(group) = self.suffixes.get(flag) else {
+                continue;
+            };
+            for suffix in &group.affixes {
+                let Some(word) = suffix.apply_suffix(stem) else {
+                    continue;
+                };
+                // Twofold suffixes, e.g. `-ability` on top of `-able`.
+                for continuation in &suffix.continuation_flags {
+                    if let Some(group) = self.suffixes.get(continuation) {
+                        words.extend(
+                            group
+                                .affixes
+                                .iter()
+                                .filter_map(|suffix| suffix.apply_suffix(&word)),
+                        );
+                    }
+                }
+                if !suffix
+                    .continuation_flags
+                    .contains(&self.need_affix.unwrap_or(Flag::MAX))
+                {
+                    words.insert(word.clone());
+                }
+                if group.cross_product {
+                    cross_product_suffixed.push(word);
+                }
+            }
+        }
+
+        for flag in flags {
+            let Some(group) = self.prefixes.get(flag) else {
+                continue;
+            };
+            for prefix in &group.affixes {
+                words.extend(prefix.apply_prefix(stem));
+                if group.cross_product {
+                    words.extend(
+                        cross_product_suffixed
+                            .iter()
+                            .filter_map(|word| prefix.apply_prefix(word)),
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// A dictionary loaded from a Hunspell `.aff` and `.dic` pair.
+pub struct Dictionary {
+    words: HashSet<String>,
+    try_chars: Vec<char>,
+    replacements: Vec<(String, String)>,
+}
+
+impl Dictionary {
+    pub fn new(aff: &str, dic: &str) -> Result<Self> {
+        let affix_file = AffixFile::parse(aff).context("failed to parse affix file")?;
+        let mut words = HashSet::default();
+        let mut forbidden_words = Vec::new();
+        // The first line of a `.dic` file is the approximate number of words.
+        for line in dic.lines().skip(1) {
+            if line.starts_with(['\t', '#']) {
+                continue;
+            }
+            let Some(entry) = line.split_whitespace().next() else {
+                continue;
+            };
+            let (stem, flags) = split_entry(entry);
+            let flags = flags
+                .map(|flags| affix_file.parse_flags(flags))
+                .unwrap_or_default();
+            if affix_file
+                .forbidden_word
+                .map_or(false, |flag| flags.contains(&flag))
+            {
+                forbidden_words.push(stem);
+                continue;
+            }
+            affix_file.expand(&stem, &flags, &mut words);
+        }
+        for word in forbidden_words {
+            words.remove(&word);
+        }
+
+        let try_chars = if affix_file.try_chars.is_empty() {
+            ('a'..='z').collect()
+        } else {
+            affix_file.try_chars
+        };
+        Ok(Self {
+            words,
+            try_chars,
+            replacements: affix_file.replacements,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    /// Returns whether the word is spelled correctly. Lowercase dictionary words also match
+    /// capitalized and uppercase words, and capitalized ones (names) also match uppercase words.
+    pub fn check(&self, word: &str) -> bool {
+        if self.words.contains(word) {
+            return true;
+        }
+        match Casing::of(word) {
+            Casing::Lower | Casing::Mixed => false,
+            Casing::Title => self.words.contains(&word.to_lowercase()),
+            Casing::Upper => {
+                let lowercase = word.to_lowercase();
+                self.words.contains(&lowercase) || self.words.contains(&capitalize(&lowercase))
+            }
+        }
+    }
+
+    /// Returns the known words that are one edit away from the given word, or that it becomes
+    /// through one of the affix file's common replacements, most likely first.
+    pub fn suggest(&self, word: &str) -> Vec<String> {
+        let casing = Casing::of(word);
+        let lowercase = match casing {
+            Casing::Title | Casing::Upper => word.to_lowercase(),
+            Casing::Lower | Casing::Mixed => word.to_string(),
+        };
+        let mut suggestions = Vec::new();
+        let mut seen = HashSet::default();
+        let mut add = |candidate: String| {
+            if suggestions.len() < MAX_SUGGESTIONS
+                && candidate != lowercase
+                && seen.insert(candidate.clone())
+                && candidate.split(' ').all(|word| self.check(word))
+            {
+                suggestions.push(candidate);
+            }
+        };
+
+        for (from, to) in &self.replacements {
+            for (ix, _) in lowercase.match_indices(from.as_str()) {
+                add(format!(
+                    "{}{to}{}",
+                    &lowercase[..ix],
+                    &lowercase[ix + from.len()..]
+                ));
+            }
+        }
+
+        let chars = lowercase.chars().collect::<Vec<_>>();
+        for ix in 0..chars.len() {
+            // Replacements keep the length of the word, so they come first.
+            for c in &self.try_chars {
+                if *c != chars[ix] {
+                    let mut candidate = chars.clone();
+                    candidate[ix] = *c;
+                    add(candidate.into_iter().collect());
+                }
+            }
+            if ix + 1 < chars.len() {
+                let mut candidate = chars.clone();
+                candidate.swap(ix, ix + 1);
+                add(candidate.into_iter().collect());
+            }
+        }
+        for ix in 0..chars.len() {
+            let mut candidate = chars.clone();
+            candidate.remove(ix);
+            add(candidate.into_iter().collect());
+        }
+        for ix in 0..=chars.len() {
+            for c in &self.try_chars {
+                let mut candidate = chars.clone();
+                candidate.insert(ix, *c);
+                add(candidate.into_iter().collect());
+            }
+        }
+        for ix in 1..chars.len() {
+            let (left, right) = chars.split_at(ix);
+            add(format!(
+                "{} {}",
+                left.iter().collect::<String>(),
+                right.iter().collect::<String>()
+            ));
+        }
+
+        suggestions
+            .into_iter()
+            .map(|suggestion| match casing {
+                Casing::Title => capitalize(&suggestion),
+                Casing::Upper => suggestion.to_uppercase(),
+                Casing::Lower | Casing::Mixed => suggestion,
+            })
+            .collect()
+    }
+}
+
+/// Splits a `.dic` entry into its stem and flags, e.g. `cat/S`. Slashes in the stem are escaped.
+fn split_entry(entry: &str) -> (String, Option<&str>) {
+    let mut stem = String::new();
+    let mut chars = entry.char_indices();
+    while let Some((ix, c)) = chars.next() {
+        match c {
+            '\\' => {
+                if let Some((_, escaped)) = chars.next() {
+                    stem.push(escaped);
+                }
+            }
+            '/' => return (stem, Some(&entry[ix + 1..])),
+            c => stem.push(c),
+        }
+    }
+    (stem, None)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Casing {
+    Lower,
+    Title,
+    Upper,
+    Mixed,
+}
+
+impl Casing {
+    fn of(word: &str) -> Self {
+        let mut letters = word.chars().filter(|c| c.is_alphabetic());
+        let Some(first) = letters.next() else {
+            return Casing::Lower;
+        };
+        let (mut has_lower, mut has_upper) = (false, false);
+        for c in letters {
+            has_lower |= c.is_lowercase();
+            has_upper |= c.is_uppercase();
+        }
+        match (first.is_uppercase(), has_lower, has_upper) {
+            (false, _, false) => Casing::Lower,
+            (true, _, false) => Casing::Title,
+            (true, false, true) => Casing::Upper,
+            _ => Casing::Mixed,
+        }
+    }
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AFF: &str = "
+SET UTF-8
+TRY esianrtolcdugmphbyfvkwzESIANRTOLCDUGMPHBYFVKWZ'
+NEEDAFFIX X
+FORBIDDENWORD !
+
+REP 1
+REP f ph
+
+PFX U Y 1
+PFX U   0     un         .
+
+SFX S Y 4
+SFX S   y     ies        [^aeiou]y
+SFX S   0     s          [aeiou]y
+SFX S   0     es         [sxzh]
+SFX S   0     s          [^sxzhy]
+
+SFX D Y 2
+SFX D   0     d          e
+SFX D   0     ed         [^e]
+
+SFX B Y 1
+SFX B   0     able/S     .
+";
+
+    const DIC: &str = "7
+cat/S
+fly/S
+box/S
+lock/UD
+graph/S
+colour/X
+kidnaped/!
+";
+
+    #[test]
+    fn test_check() {
+        let dictionary = Dictionary::new(AFF, DIC).unwrap();
+        for word in [
+            "cat", "cats", "flies", "boxes", "lock", "locked", "unlock", "unlocked", "Cat", "CATS",
+            "graphs",
+        ] {
+            assert!(dictionary.check(word), "{word:?} should be known");
+        }
+        for word in [
+            "cta", "flys", "boxs", "unlocks", "colour", "kidnaped", "cAT",
+        ] {
+            assert!(!dictionary.check(word), "{word:?} should be unknown");
+        }
+    }
+
+    #[test]
+    fn test_suggest() {
+        let dictionary = Dictionary::new(AFF, DIC).unwrap();
+        assert_eq!(dictionary.suggest("cta"), ["cat"]);
+        assert_eq!(dictionary.suggest("Flys"), ["Fly"]);
+        assert_eq!(dictionary.suggest("grafs"), ["graphs"]);
+        assert_eq!(dictionary.suggest("catbox"), ["cat box"]);
+        assert!(dictionary.suggest("zzzzzz").is_empty());
+    }
+
+    #[test]
+    fn test_conditions() {
+        let condition = Condition::parse("[^aeiou]y").unwrap();
+        assert!(condition.matches_end("fly"));
+        assert!(!condition.matches_end("play"));
+        assert!(!condition.matches_end("y"));
+        assert!(Condition::parse(".").unwrap().matches_end(""));
+        assert!(Condition::parse("[ab").is_err());
+    }
+}
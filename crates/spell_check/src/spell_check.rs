@@ -0,0 +1,742 @@
+//! Offline spell checking of comments, strings and prose, using Hunspell-format dictionaries.
+//!
+//! Which parts of a buffer are checked is decided by the scopes of its languages'
+//! `overrides.scm` queries: in code, only the configured scopes (comments and strings by
+//! default) are checked, while prose languages like Markdown are checked in their entirety,
+//! except for the scopes they mark as `verbatim`, such as code blocks and links.
+
+mod dictionary;
+mod words;
+
+pub use dictionary::Dictionary;
+
+use anyhow::{anyhow, Context as _, Result};
+use collections::HashSet;
+use editor::{CodeActionProvider, Editor, EditorEvent, EditorMode, ExcerptId};
+use fs::Fs;
+use gpui::{
+    App, AppContext as _, Context, Entity, Global, HighlightStyle, Subscription, Task,
+    UnderlineStyle, WeakEntity, Window,
+};
+use language::{Buffer, BufferSnapshot, LanguageServerId, ToOffset as _};
+use project::{CodeAction, ProjectTransaction};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsLocation, SettingsSources, SettingsStore};
+use std::{ops::Range, path::PathBuf, rc::Rc, sync::Arc, time::Duration};
+use text::BufferId;
+use theme::ActiveTheme as _;
+use util::ResultExt as _;
+use words::words;
+
+const REFRESH_DEBOUNCE: Duration = Duration::from_millis(300);
+const CODE_ACTION_PROVIDER_ID: &str = "spell_check";
+/// Prose is checked in its entirety, except for the scopes with this name.
+const VERBATIM_SCOPE: &str = "verbatim";
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct SpellCheckSettings {
+    pub enabled: bool,
+    pub dictionaries: Vec<String>,
+    pub scopes: Vec<String>,
+    pub prose_languages: Vec<String>,
+    pub ignored_words: Vec<String>,
+}
+
+#[derive(Clone, Default, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SpellCheckSettingsContent {
+    /// Whether to underline misspelled words.
+    ///
+    /// Default: false
+    pub enabled: Option<bool>,
+    /// The dictionaries to check words against, as paths of Hunspell `.dic` files with
+    /// the `.aff` files next to them. `~` is expanded to the home directory.
+    ///
+    /// Default: []
+    pub dictionaries: Option<Vec<String>>,
+    /// The scopes of the languages' `overrides.scm` queries in which words are checked.
+    ///
+    /// Default: ["comment", "string"]
+    pub scopes: Option<Vec<String>>,
+    /// The languages whose text is checked in its entirety.
+    ///
+    /// Default: ["Markdown", "Git Commit", "Plain Text"]
+    pub prose_languages: Option<Vec<String>>,
+    /// Words that are never reported as misspelled. The words ignored in the user
+    /// settings and in the project settings are combined.
+    ///
+    /// Default: []
+    pub ignored_words: Option<Vec<String>>,
+}
+
+impl Settings for SpellCheckSettings {
+    const KEY: Option<&'static str> = Some("spell_check");
+
+    type FileContent = SpellCheckSettingsContent;
+
+    fn load(sources: SettingsSources<Self::FileContent>, _: &mut App) -> Result<Self> {
+        let mut settings: Self = sources.json_merge()?;
+        settings.ignored_words = sources
+            .defaults_and_customizations()
+            .flat_map(|content| content.ignored_words.iter().flatten())
+            .cloned()
+            .collect();
+        Ok(settings)
+    }
+}
+
+pub fn init(fs: Arc<dyn Fs>, cx: &mut App) {
+    SpellCheckSettings::register(cx);
+
+    let spell_checker = cx.new(|cx| SpellChecker::new(fs, cx));
+    cx.set_global(GlobalSpellChecker(spell_checker));
+
+    cx.observe_new(|editor: &mut Editor, window, cx: &mut Context<Editor>| {
+        let Some(window) = window else {
+            return;
+        };
+        if editor.mode() != EditorMode::Full || editor.project.is_none() {
+            return;
+        }
+        let editor_handle = cx.entity();
+        let spell_check = cx.new(|cx| EditorSpellCheck::new(&editor_handle, cx));
+        editor.add_code_action_provider(
+            Rc::new(SpellCheckCodeActionProvider { spell_check }),
+            window,
+            cx,
+        );
+    })
+    .detach();
+}
+
+struct GlobalSpellChecker(Entity<SpellChecker>);
+
+impl Global for GlobalSpellChecker {}
+
+/// Holds the dictionaries configured in the settings.
+pub struct SpellChecker {
+    fs: Arc<dyn Fs>,
+    dictionaries: Vec<Arc<Dictionary>>,
+    dictionary_names: Vec<String>,
+    load_dictionaries: Task<()>,
+}
+
+impl SpellChecker {
+    pub fn global(cx: &App) -> Option<Entity<Self>> {
+        cx.try_global::<GlobalSpellChecker>()
+            .map(|spell_checker| spell_checker.0.clone())
+    }
+
+    fn new(fs: Arc<dyn Fs>, cx: &mut Context<Self>) -> Self {
+        cx.observe_global::<SettingsStore>(Self::load_dictionaries)
+            .detach();
+
+        let mut this = Self {
+            fs,
+            dictionaries: Vec::new(),
+            dictionary_names: Vec::new(),
+            load_dictionaries: Task::ready(()),
+        };
+        this.load_dictionaries(cx);
+        this
+    }
+
+    pub fn dictionaries(&self) -> &[Arc<Dictionary>] {
+        &self.dictionaries
+    }
+
+    /// Sets the dictionaries directly, rather than loading the ones from the settings.
+    pub fn set_dictionaries(&mut self, dictionaries: Vec<Arc<Dictionary>>, cx: &mut Context<Self>) {
+        self.dictionaries = dictionaries;
+        cx.notify();
+    }
+
+    fn load_dictionaries(&mut self, cx: &mut Context<Self>) {
+        let names = SpellCheckSettings::get_global(cx).dictionaries.clone();
+        if names == self.dictionary_names {
+            return;
+        }
+        self.dictionary_names = names.clone();
+
+        let fs = self.fs.clone();
+        self.load_dictionaries = cx.spawn(|this, mut cx| async move {
+            let mut dictionaries = Vec::new();
+            for name in names {
+                let dictionary = async {
+                    let (aff, dic) = read_dictionary(&name, fs.as_ref()).await?;
+                    cx.background_executor()
+                        .spawn(async move { Dictionary::new(&aff, &dic) })
+                        .await
+                }
+                .await
+                .with_context(|| format!("failed to load dictionary {name:?}"));
+                if let Some(dictionary) = dictionary.log_err() {
+                    dictionaries.push(Arc::new(dictionary));
+                }
+            }
+            this.update(&mut cx, |this, cx| {
+                this.dictionaries = dictionaries;
+                cx.notify();
+            })
+            .ok();
+        });
+    }
+
+    fn suggest(&self, word: &str) -> Vec<String> {
+        let mut suggestions = Vec::new();
+        for dictionary in &self.dictionaries {
+            for suggestion in dictionary.suggest(word) {
+                if !suggestions.contains(&suggestion) {
+                    suggestions.push(suggestion);
+                }
+            }
+        }
+        suggestions
+    }
+}
+
+fn is_known(
+    dictionaries: &[Arc<Dictionary>],
+    word: &str,
+    in_identifier: bool,
+    ignored_words: &HashSet<String>,
+) -> bool {
+    if ignored_words.contains(word) || ignored_words.contains(&word.to_lowercase()) {
+        return true;
+    }
+    dictionaries.iter().any(|dictionary| {
+        dictionary.check(word)
+            // Parts of identifiers are capitalized by the naming convention, not because
+            // they're names, so `parseJson` is accepted if `JSON` is a word.
+            || (in_identifier
+                && (dictionary.check(&word.to_lowercase())
+                    || dictionary.check(&word.to_uppercase())))
+    })
+}
+
+async fn read_dictionary(path: &str, fs: &dyn Fs) -> Result<(String, String)> {
+    let dic_path = PathBuf::from(shellexpand::tilde(path).as_ref());
+    if dic_path
+        .extension()
+        .map_or(true, |extension| extension != "dic")
+    {
+        return Err(anyhow!("expected the path of a `.dic` file"));
+    }
+    let aff_path = dic_path.with_extension("aff");
+    Ok((fs.load(&aff_path).await?, fs.load(&dic_path).await?))
+}
+
+#[derive(Clone, Debug)]
+struct Misspelling {
+    excerpt_id: ExcerptId,
+    buffer_id: BufferId,
+    range: Range<text::Anchor>,
+    word: String,
+}
+
+/// A part of the multibuffer to spell check.
+struct SpellCheckInput {
+    excerpt_id: ExcerptId,
+    buffer: BufferSnapshot,
+    range: Range<usize>,
+    is_prose: bool,
+    scopes: Vec<String>,
+    ignored_words: HashSet<String>,
+}
+
+/// Keeps the misspellings of an editor up to date and underlines them.
+struct EditorSpellCheck {
+    editor: WeakEntity<Editor>,
+    misspellings: Vec<Misspelling>,
+    refresh_task: Task<Option<()>>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl EditorSpellCheck {
+    fn new(editor: &Entity<Editor>, cx: &mut Context<Self>) -> Self {
+        let mut subscriptions = vec![cx.subscribe(editor, |this, _, event, cx| match event {
+            EditorEvent::Reparsed(_)
+            | EditorEvent::ExcerptsAdded { .. }
+            | EditorEvent::ExcerptsEdited { .. }
+            | EditorEvent::ExcerptsExpanded { .. } => this.refresh(true, cx),
+            EditorEvent::ExcerptsRemoved { .. } => this.refresh(false, cx),
+            _ => {}
+        })];
+        subscriptions.push(cx.observe_global::<SettingsStore>(|this, cx| this.refresh(true, cx)));
+        if let Some(spell_checker) = SpellChecker::global(cx) {
+            subscriptions.push(cx.observe(&spell_checker, |this, _, cx| this.refresh(false, cx)));
+        }
+
+        let mut this = Self {
+            editor: editor.downgrade(),
+            misspellings: Vec::new(),
+            refresh_task: Task::ready(None),
+            _subscriptions: subscriptions,
+        };
+        // The editor is still being created, so it can only be read once this has returned.
+        this.refresh(true, cx);
+        this
+    }
+
+    fn refresh(&mut self, debounce: bool, cx: &mut Context<Self>) {
+        self.refresh_task = cx.spawn(|this, mut cx| async move {
+            if debounce {
+                cx.background_executor().timer(REFRESH_DEBOUNCE).await;
+            }
+            let (spell_checker, inputs) = this
+                .update(&mut cx, |this, cx| {
+                    let spell_checker = SpellChecker::global(cx)?;
+                    Some((spell_checker, this.spell_check_inputs(cx)))
+                })
+                .ok()??;
+            let dictionaries = spell_checker
+                .update(&mut cx, |spell_checker, _| {
+                    spell_checker.dictionaries.clone()
+                })
+                .ok()?;
+            let misspellings = cx
+                .background_executor()
+                .spawn(async move {
+                    inputs
+                        .iter()
+                        .flat_map(|input| find_misspellings(input, &dictionaries))
+                        .collect::<Vec<_>>()
+                })
+                .await;
+            this.update(&mut cx, |this, cx| {
+                this.misspellings = misspellings;
+                this.update_highlights(cx);
+            })
+            .ok()
+        });
+    }
+
+    fn spell_check_inputs(&self, cx: &App) -> Vec<SpellCheckInput> {
+        let Some(editor) = self.editor.upgrade() else {
+            return Vec::new();
+        };
+        let snapshot = editor.read(cx).buffer().read(cx).snapshot(cx);
+        let mut inputs = Vec::new();
+        for (excerpt_id, buffer, range) in snapshot.excerpts() {
+            let location = buffer.file().map(|file| SettingsLocation {
+                worktree_id: file.worktree_id(cx),
+                path: file.path().as_ref(),
+            });
+            let settings = SpellCheckSettings::get(location, cx);
+            if !settings.enabled {
+                continue;
+            }
+            let language_name = buffer
+                .language()
+                .map_or_else(|| "Plain Text".into(), |language| language.name());
+            inputs.push(SpellCheckInput {
+                excerpt_id,
+                buffer: buffer.clone(),
+                range: range.context.to_offset(buffer),
+                is_prose: settings
+                    .prose_languages
+                    .iter()
+                    .any(|name| name.as_str() == language_name.as_ref()),
+                scopes: settings.scopes.clone(),
+                ignored_words: settings.ignored_words.iter().cloned().collect(),
+            });
+        }
+        inputs
+    }
+
+    fn update_highlights(&self, cx: &mut App) {
+        let Some(editor) = self.editor.upgrade() else {
+            return;
+        };
+        let color = cx.theme().status().hint;
+        editor.update(cx, |editor, cx| {
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let ranges = self
+                .misspellings
+                .iter()
+                .filter_map(|misspelling| {
+                    Some(
+                        snapshot
+                            .anchor_in_excerpt(misspelling.excerpt_id, misspelling.range.start)?
+                            ..snapshot
+                                .anchor_in_excerpt(misspelling.excerpt_id, misspelling.range.end)?,
+                    )
+                })
+                .collect::<Vec<_>>();
+            if ranges.is_empty() {
+                editor.clear_highlights::<Self>(cx);
+            } else {
+                editor.highlight_text::<Self>(
+                    ranges,
+                    HighlightStyle {
+                        underline: Some(UnderlineStyle {
+                            thickness: gpui::px(1.),
+                            color: Some(color),
+                            wavy: true,
+                        }),
+                        ..Default::default()
+                    },
+                    cx,
+                );
+            }
+        });
+    }
+}
+
+fn find_misspellings(
+    input: &SpellCheckInput,
+    dictionaries: &[Arc<Dictionary>],
+) -> Vec<Misspelling> {
+    // Without a dictionary, every word would be reported.
+    if dictionaries.is_empty() {
+        return Vec::new();
+    }
+    let buffer = &input.buffer;
+    let mut checked_ranges = Vec::new();
+    let mut verbatim_ranges = Vec::new();
+    if input.is_prose {
+        checked_ranges.push(input.range.clone());
+    }
+    for (range, scope) in buffer.override_ranges(input.range.clone()) {
+        let range = range.start.max(input.range.start)..range.end.min(input.range.end);
+        if scope == VERBATIM_SCOPE {
+            verbatim_ranges.push(range);
+        } else if !input.is_prose && input.scopes.iter().any(|name| name == scope) {
+            checked_ranges.push(range);
+        }
+    }
+
+    let mut misspellings = Vec::new();
+    let mut checked_words = HashSet::default();
+    for range in checked_ranges {
+        let text = buffer.text_for_range(range.clone()).collect::<String>();
+        for word in words(&text) {
+            let word_range = range.start + word.range.start..range.start + word.range.end;
+            let is_verbatim = verbatim_ranges
+                .iter()
+                .any(|verbatim| verbatim.start < word_range.end && word_range.start < verbatim.end);
+            if is_verbatim || !checked_words.insert(word_range.start) {
+                continue;
+            }
+            let word_text = &text[word.range];
+            if !is_known(
+                dictionaries,
+                word_text,
+                word.in_identifier,
+                &input.ignored_words,
+            ) {
+                misspellings.push(Misspelling {
+                    excerpt_id: input.excerpt_id,
+                    buffer_id: buffer.remote_id(),
+                    range: buffer.anchor_before(word_range.start)
+                        ..buffer.anchor_after(word_range.end),
+                    word: word_text.to_string(),
+                });
+            }
+        }
+    }
+    misspellings.sort_by_key(|misspelling| misspelling.range.start.to_offset(buffer));
+    misspellings
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum SpellCheckFix {
+    Replace { text: String },
+    Ignore { word: String },
+}
+
+struct SpellCheckCodeActionProvider {
+    spell_check: Entity<EditorSpellCheck>,
+}
+
+impl CodeActionProvider for SpellCheckCodeActionProvider {
+    fn id(&self) -> Arc<str> {
+        CODE_ACTION_PROVIDER_ID.into()
+    }
+
+    fn code_actions(
+        &self,
+        buffer: &Entity<Buffer>,
+        range: Range<text::Anchor>,
+        _: &mut Window,
+        cx: &mut App,
+    ) -> Task<Result<Vec<CodeAction>>> {
+        let Some(spell_checker) = SpellChecker::global(cx) else {
+            return Task::ready(Ok(Vec::new()));
+        };
+        let spell_checker = spell_checker.read(cx);
+        let snapshot = buffer.read(cx).snapshot();
+        let mut actions = Vec::new();
+        for misspelling in &self.spell_check.read(cx).misspellings {
+            if misspelling.buffer_id != snapshot.remote_id()
+                || misspelling.range.start.cmp(&range.end, &snapshot).is_gt()
+                || misspelling.range.end.cmp(&range.start, &snapshot).is_lt()
+            {
+                continue;
+            }
+            let fixes = spell_checker
+                .suggest(&misspelling.word)
+                .into_iter()
+                .map(|text| {
+                    (
+                        format!("Change to \"{text}\""),
+                        SpellCheckFix::Replace { text },
+                    )
+                })
+                .chain([(
+                    format!("Ignore \"{}\" in this project", misspelling.word),
+                    SpellCheckFix::Ignore {
+                        word: misspelling.word.clone(),
+                    },
+                )]);
+            for (title, fix) in fixes {
+                actions.push(CodeAction {
+                    server_id: LanguageServerId(0),
+                    range: misspelling.range.clone(),
+                    lsp_action: lsp::CodeAction {
+                        title,
+                        kind: Some(lsp::CodeActionKind::QUICKFIX),
+                        data: serde_json::to_value(fix).log_err(),
+                        ..Default::default()
+                    },
+                });
+            }
+        }
+        Task::ready(Ok(actions))
+    }
+
+    fn apply_code_action(
+        &self,
+        buffer: Entity<Buffer>,
+        action: CodeAction,
+        _: ExcerptId,
+        push_to_history: bool,
+        _: &mut Window,
+        cx: &mut App,
+    ) -> Task<Result<ProjectTransaction>> {
+        let fix = action
+            .lsp_action
+            .data
+            .context("spell check action has no data")
+            .and_then(|data| Ok(serde_json::from_value::<SpellCheckFix>(data)?));
+        match fix {
+            Ok(SpellCheckFix::Replace { text }) => {
+                let transaction = buffer.update(cx, |buffer, cx| {
+                    buffer.finalize_last_transaction();
+                    buffer.start_transaction();
+                    buffer.edit([(action.range, text)], None, cx);
+                    buffer.end_transaction(cx);
+                    let transaction = buffer.finalize_last_transaction()?.clone();
+                    if !push_to_history {
+                        buffer.forget_transaction(transaction.id);
+                    }
+                    Some(transaction)
+                });
+                let mut project_transaction = ProjectTransaction::default();
+                if let Some(transaction) = transaction {
+                    project_transaction.0.insert(buffer, transaction);
+                }
+                Task::ready(Ok(project_transaction))
+            }
+            Ok(SpellCheckFix::Ignore { word }) => {
+                let task = ignore_word_in_project(&buffer, word, cx);
+                cx.spawn(|_| async move {
+                    task.await?;
+                    Ok(ProjectTransaction::default())
+                })
+            }
+            Err(error) => Task::ready(Err(error)),
+        }
+    }
+}
+
+/// Adds the word to the `ignored_words` in the settings of the worktree containing the buffer.
+fn ignore_word_in_project(buffer: &Entity<Buffer>, word: String, cx: &mut App) -> Task<Result<()>> {
+    let Some(file) = project::File::from_dyn(buffer.read(cx).file()) else {
+        return Task::ready(Err(anyhow!("the buffer is not part of a project")));
+    };
+    let worktree = file.worktree.read(cx);
+    if !worktree.is_local() {
+        return Task::ready(Err(anyhow!(
+            "ignoring words is only supported in local projects"
+        )));
+    }
+    let settings_path = worktree
+        .abs_path()
+        .join(paths::local_settings_file_relative_path());
+    let Some(fs) = SpellChecker::global(cx).map(|spell_checker| spell_checker.read(cx).fs.clone())
+    else {
+        return Task::ready(Err(anyhow!("spell checking is not initialized")));
+    };
+    cx.spawn(|cx| async move {
+        let old_text = if fs.is_file(&settings_path).await {
+            fs.load(&settings_path).await?
+        } else {
+            settings::initial_project_settings_content().to_string()
+        };
+        let new_text = cx.read_global(|store: &SettingsStore, _| {
+            store.new_text_for_update::<SpellCheckSettings>(old_text, |settings| {
+                let ignored_words = settings.ignored_words.get_or_insert_with(Vec::new);
+                if !ignored_words.contains(&word) {
+                    ignored_words.push(word);
+                }
+            })
+        })?;
+        if let Some(settings_dir) = settings_path.parent() {
+            fs.create_dir(settings_dir).await?;
+        }
+        fs.atomic_write(settings_path, new_text).await
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use editor::test::editor_test_context::EditorTestContext;
+    use gpui::TestAppContext;
+    use indoc::indoc;
+    use language::{Language, LanguageConfig, LanguageMatcher};
+
+    const AFF: &str = "
+SFX S Y 1
+SFX S   0     s          .
+";
+
+    const DIC: &str = "6
+the/S
+word/S
+is/S
+spelled/S
+correctly/S
+parse/S
+";
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            language::init(cx);
+            editor::init(cx);
+            workspace::init_settings(cx);
+            project::Project::init_settings(cx);
+            init(fs::FakeFs::new(cx.background_executor().clone()), cx);
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<SpellCheckSettings>(cx, |settings| {
+                    settings.enabled = Some(true);
+                });
+            });
+            let dictionary = Arc::new(Dictionary::new(AFF, DIC).unwrap());
+            SpellChecker::global(cx)
+                .unwrap()
+                .update(cx, |spell_checker, cx| {
+                    spell_checker.set_dictionaries(vec![dictionary], cx)
+                });
+        });
+    }
+
+    fn rust_lang() -> Language {
+        Language::new(
+            LanguageConfig {
+                name: "Rust".into(),
+                matcher: LanguageMatcher {
+                    path_suffixes: vec!["rs".to_string()],
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            Some(tree_sitter_rust::LANGUAGE.into()),
+        )
+        .with_override_query(
+            r#"
+                (string_literal) @string
+                (line_comment) @comment.inclusive
+            "#,
+        )
+        .unwrap()
+    }
+
+    fn misspelled_words(cx: &mut EditorTestContext) -> Vec<String> {
+        cx.executor().advance_clock(REFRESH_DEBOUNCE);
+        cx.run_until_parked();
+        cx.update_editor(|editor, _, cx| {
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            editor
+                .text_highlights::<EditorSpellCheck>(cx)
+                .map(|(_, ranges)| {
+                    ranges
+                        .iter()
+                        .map(|range| snapshot.text_for_range(range.clone()).collect())
+                        .collect()
+                })
+                .unwrap_or_default()
+        })
+    }
+
+    fn update_settings(cx: &mut EditorTestContext, f: impl FnOnce(&mut SpellCheckSettingsContent)) {
+        cx.update(|_, cx| {
+            cx.update_global::<SettingsStore, _>(|store, cx| {
+                store.update_user_settings::<SpellCheckSettings>(cx, f);
+            });
+        });
+    }
+
+    #[gpui::test]
+    async fn test_spell_check_comments_and_strings(cx: &mut TestAppContext) {
+        init_test(cx);
+        let mut cx = EditorTestContext::new(cx).await;
+        cx.update_buffer(|buffer, cx| buffer.set_language(Some(Arc::new(rust_lang())), cx));
+        cx.set_state(indoc! {r#"
+            // The wrod is speled correctly
+            fn parse_wrods() {
+                let misspeled = "the wordz";
+            }ˇ
+        "#});
+        assert_eq!(misspelled_words(&mut cx), ["wrod", "speled", "wordz"]);
+
+        update_settings(&mut cx, |settings| {
+            settings.ignored_words = Some(vec!["wrod".to_string()]);
+        });
+        assert_eq!(misspelled_words(&mut cx), ["speled", "wordz"]);
+
+        update_settings(&mut cx, |settings| {
+            settings.scopes = Some(vec!["comment".to_string()]);
+        });
+        assert_eq!(misspelled_words(&mut cx), ["speled"]);
+
+        let suggestions =
+            cx.update(|_, cx| SpellChecker::global(cx).unwrap().read(cx).suggest("speled"));
+        assert_eq!(suggestions, ["spelled"]);
+    }
+
+    #[gpui::test]
+    async fn test_spell_check_prose(cx: &mut TestAppContext) {
+        init_test(cx);
+        let mut cx = EditorTestContext::new(cx).await;
+        cx.set_state("The wrod is spelled correctlyˇ\n");
+        assert_eq!(misspelled_words(&mut cx), ["wrod"]);
+
+        update_settings(&mut cx, |settings| {
+            settings.prose_languages = Some(Vec::new());
+        });
+        assert!(misspelled_words(&mut cx).is_empty());
+    }
+
+    #[gpui::test]
+    async fn test_spell_check_without_dictionaries(cx: &mut TestAppContext) {
+        init_test(cx);
+        let mut cx = EditorTestContext::new(cx).await;
+        cx.set_state("The wrod is spelled correctlyˇ\n");
+        assert_eq!(misspelled_words(&mut cx), ["wrod"]);
+
+        cx.update(|_, cx| {
+            SpellChecker::global(cx)
+                .unwrap()
+                .update(cx, |spell_checker, cx| {
+                    spell_checker.set_dictionaries(Vec::new(), cx)
+                });
+        });
+        assert!(misspelled_words(&mut cx).is_empty());
+    }
+}
@@ -0,0 +1,158 @@
+//! Splitting text into the words to spell check.
+
+use std::ops::Range;
+
+/// Words shorter than this are not checked.
+const MIN_WORD_LEN: usize = 3;
+
+/// A word to spell check, as a byte range into the checked text.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Word {
+    pub range: Range<usize>,
+    /// Whether the word is part of an identifier like `parseHttpResponse` or `max_depth`.
+    pub in_identifier: bool,
+}
+
+/// Returns the words in the given text. Identifiers are split into their camelCase and
+/// snake_case parts, and tokens that contain digits, URLs, email addresses, file paths and
+/// all-uppercase acronyms are skipped.
+pub fn words(text: &str) -> Vec<Word> {
+    let mut words = Vec::new();
+    for (chunk_start, chunk) in split_with_offsets(text, char::is_whitespace) {
+        if is_url_or_path(chunk) {
+            continue;
+        }
+        for (token_start, token) in
+            split_with_offsets(chunk, |c| !(c.is_alphanumeric() || c == '_' || c == '\''))
+        {
+            let token = token.trim_matches('\'');
+            if token.is_empty() || token.contains(|c: char| c.is_ascii_digit()) {
+                continue;
+            }
+            let token_start = chunk_start
+                + token_start
+                + chunk[token_start..].find(|c| c != '\'').unwrap_or_default();
+            let parts = identifier_parts(token);
+            let in_identifier = parts.len() > 1;
+            for part in parts {
+                let word = &token[part.clone()];
+                if word.chars().count() < MIN_WORD_LEN || is_acronym(word) {
+                    continue;
+                }
+                words.push(Word {
+                    range: token_start + part.start..token_start + part.end,
+                    in_identifier,
+                });
+            }
+        }
+    }
+    words
+}
+
+/// Splits the text at the characters matching the predicate, returning the non-empty pieces
+/// along with their offsets.
+fn split_with_offsets(
+    text: &str,
+    is_separator: impl Fn(char) -> bool,
+) -> impl Iterator<Item = (usize, &str)> {
+    let mut offset = 0;
+    text.split(move |c: char| is_separator(c))
+        .filter_map(move |piece| {
+            let start = offset;
+            offset += piece.len()
+                + text[start + piece.len()..]
+                    .chars()
+                    .next()
+                    .map_or(0, char::len_utf8);
+            (!piece.is_empty()).then_some((start, piece))
+        })
+}
+
+fn is_url_or_path(chunk: &str) -> bool {
+    chunk.contains("://")
+        || chunk.contains('@')
+        || (chunk.contains(['/', '\\']) && chunk.contains('.'))
+}
+
+fn is_acronym(word: &str) -> bool {
+    word.chars().all(|c| !c.is_lowercase())
+}
+
+/// Splits an identifier into its words, e.g. `parseHTTPResponse` into `parse`, `HTTP` and
+/// `Response`.
+fn identifier_parts(identifier: &str) -> Vec<Range<usize>> {
+    let mut parts = Vec::new();
+    let mut start = None;
+    let chars = identifier.char_indices().collect::<Vec<_>>();
+    for (ix, &(offset, c)) in chars.iter().enumerate() {
+        if c == '_' {
+            if let Some(start) = start.take() {
+                parts.push(start..offset);
+            }
+            continue;
+        }
+        let Some(part_start) = start else {
+            start = Some(offset);
+            continue;
+        };
+        let previous = chars[ix - 1].1;
+        let next = chars.get(ix + 1).map(|(_, c)| *c);
+        let starts_word = c.is_uppercase()
+            && (previous.is_lowercase()
+                || (previous.is_uppercase() && next.map_or(false, char::is_lowercase)));
+        if starts_word {
+            parts.push(part_start..offset);
+            start = Some(offset);
+        }
+    }
+    if let Some(start) = start {
+        parts.push(start..identifier.len());
+    }
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn word_texts(text: &str) -> Vec<(&str, bool)> {
+        words(text)
+            .into_iter()
+            .map(|word| (&text[word.range], word.in_identifier))
+            .collect()
+    }
+
+    #[test]
+    fn test_words() {
+        assert_eq!(
+            word_texts("// Don't parse the 'quoted' text, it's fine."),
+            [
+                ("Don't", false),
+                ("parse", false),
+                ("the", false),
+                ("quoted", false),
+                ("text", false),
+                ("it's", false),
+                ("fine", false),
+            ]
+        );
+        assert_eq!(
+            word_texts("see https://zed.dev or me@example.com in src/main.rs, v2 utf8 HTML"),
+            [("see", false)]
+        );
+    }
+
+    #[test]
+    fn test_identifier_words() {
+        assert_eq!(
+            word_texts("parseHTTPResponse max_depth __init__ SCREAMING_CASE"),
+            [
+                ("parse", true),
+                ("Response", true),
+                ("max", true),
+                ("depth", true),
+                ("init", false),
+            ]
+        );
+    }
+}
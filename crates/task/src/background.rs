@@ -0,0 +1,138 @@
+use anyhow::Context as _;
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Output patterns of a task that keeps running, like a watcher or a dev server,
+/// telling when it starts rebuilding and when it's ready.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct BackgroundTask {
+    /// A regular expression matching the line the task prints when it starts rebuilding.
+    #[serde(default, alias = "beginsPattern")]
+    pub begins_pattern: Option<String>,
+    /// A regular expression matching the line the task prints when it's ready.
+    #[serde(alias = "endsPattern")]
+    pub ends_pattern: String,
+}
+
+/// The compiled patterns of a [`BackgroundTask`].
+#[derive(Clone, Debug)]
+pub struct BackgroundMatcher {
+    begins: Option<Regex>,
+    ends: Regex,
+}
+
+impl BackgroundTask {
+    /// The patterns of `tsc --watch`.
+    pub fn tsc_watch() -> Self {
+        Self {
+            begins_pattern: Some(
+                r"^\s*(?:message TS6032:|\[?\D*.{1,2}[:.].{1,2}[:.].{1,2}\D*(?:├\D*\d{1,2}\D+┤)?(?:\]| -)) (?:Starting compilation in watch mode|File change detected\. Starting incremental compilation)\.\.\."
+                    .to_string(),
+            ),
+            ends_pattern: r"^\s*(?:message TS6042:|\[?\D*.{1,2}[:.].{1,2}[:.].{1,2}\D*(?:├\D*\d{1,2}\D+┤)?(?:\]| -)) (?:Compilation complete\.|Found \d+ errors?\.) Watching for file changes\."
+                .to_string(),
+        }
+    }
+
+    /// Compiles the patterns.
+    pub fn matcher(&self) -> anyhow::Result<BackgroundMatcher> {
+        let compile = |pattern: &str| {
+            Regex::new(pattern)
+                .with_context(|| format!("Invalid background task pattern `{pattern}`"))
+        };
+        Ok(BackgroundMatcher {
+            begins: self.begins_pattern.as_deref().map(compile).transpose()?,
+            ends: compile(&self.ends_pattern)?,
+        })
+    }
+}
+
+impl BackgroundMatcher {
+    /// Returns whether the task is ready after printing the given output, according to the last line
+    /// of the output that matches either pattern, or `None` if no line does.
+    pub fn readiness(&self, output: &str) -> Option<bool> {
+        output.lines().rev().find_map(|line| {
+            if self.ends.is_match(line) {
+                Some(true)
+            } else if self
+                .begins
+                .as_ref()
+                .is_some_and(|begins| begins.is_match(line))
+            {
+                Some(false)
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Returns the part of the output printed since the task last started rebuilding.
+    pub fn latest_build<'a>(&self, output: &'a str) -> &'a str {
+        let Some(begins) = &self.begins else {
+            return output;
+        };
+        let mut start = 0;
+        let mut offset = 0;
+        for line in output.split_inclusive('\n') {
+            offset += line.len();
+            if begins.is_match(line.trim_end_matches(['\r', '\n'])) {
+                start = offset;
+            }
+        }
+        &output[start..]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_background_matcher() {
+        let matcher = BackgroundTask {
+            begins_pattern: Some("^Compiling".to_string()),
+            ends_pattern: r"^Finished|^Listening on port \d+".to_string(),
+        }
+        .matcher()
+        .unwrap();
+        assert_eq!(matcher.readiness("Starting up"), None);
+        assert_eq!(matcher.readiness("Compiling foo\nerror: oops"), Some(false));
+        assert_eq!(
+            matcher.readiness("Compiling foo\nFinished\nListening on port 3000"),
+            Some(true)
+        );
+        assert_eq!(
+            matcher.readiness("Compiling foo\nFinished\nCompiling foo"),
+            Some(false)
+        );
+
+        let output =
+            "Compiling foo\nerror: first\nFinished\nCompiling foo\nerror: second\nFinished";
+        assert_eq!(matcher.latest_build(output), "error: second\nFinished");
+        assert_eq!(matcher.latest_build("Starting up"), "Starting up");
+
+        assert!(BackgroundTask {
+            begins_pattern: None,
+            ends_pattern: "(".to_string(),
+        }
+        .matcher()
+        .is_err());
+    }
+
+    #[test]
+    fn test_tsc_watch_patterns() {
+        let matcher = BackgroundTask::tsc_watch().matcher().unwrap();
+        let output = "[10:00:00 AM] Starting compilation in watch mode...\n\
+            src/index.ts(3,7): error TS2322: Type 'string' is not assignable to type 'number'.\n\
+            [10:00:02 AM] Found 1 error. Watching for file changes.";
+        assert_eq!(matcher.readiness(output), Some(true));
+        assert_eq!(
+            matcher.readiness(
+                "[10:01:00 AM] File change detected. Starting incremental compilation..."
+            ),
+            Some(false)
+        );
+    }
+}
@@ -0,0 +1,415 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail, Context as _, Result};
+use collections::HashMap;
+use futures::{
+    future::{self, join_all, LocalBoxFuture, Shared},
+    Future, FutureExt as _,
+};
+
+use crate::{
+    task_input::input_references, DependsOrder, ResolvedTask, TaskContext, TaskId, TaskInput,
+    TaskTemplate,
+};
+
+impl ResolvedTask {
+    /// Resolves the tasks from the template's `depends_on` with the same [`TaskContext`], recursively.
+    /// Dependencies are looked up by label in `templates`, given along with the id base of their source.
+    ///
+    /// Fails if any dependency cannot be found or resolved, or if the dependencies form a cycle.
+    /// The error is kept in the task too, so that running it with [`run_with_dependencies`] reports it.
+    pub fn resolve_dependencies(
+        &mut self,
+        templates: &[(String, TaskTemplate)],
+        cx: &TaskContext,
+    ) -> Result<()> {
+        let mut dependents = vec![self.original_task.label.clone()];
+        match resolve_dependencies_of(&self.original_task, templates, cx, &mut dependents) {
+            Ok(dependencies) => {
+                self.dependencies = dependencies;
+                self.dependencies_error = None;
+                Ok(())
+            }
+            Err(error) => {
+                self.dependencies = Vec::new();
+                self.dependencies_error = Some(format!("{error:#}"));
+                Err(error)
+            }
+        }
+    }
+
+    /// Returns the inputs referenced by the task and by every task it depends on, once per input id.
+    pub fn referenced_inputs(&self) -> Vec<&TaskInput> {
+        let mut inputs = self.original_task.referenced_inputs();
+        for dependency in &self.dependencies {
+            for input in dependency.referenced_inputs() {
+                if inputs.iter().all(|known| known.id != input.id) {
+                    inputs.push(input);
+                }
+            }
+        }
+        inputs
+    }
+
+    /// Returns the id of an input still referenced in the command or arguments of the task,
+    /// or of a task it depends on, after the resolution: running such a task would pass `${input:id}` literally.
+    pub fn unresolved_input(&self) -> Option<&str> {
+        let resolved_input = self.resolved.as_ref().and_then(|resolved| {
+            std::iter::once(&resolved.command)
+                .chain(&resolved.args)
+                .find_map(|text| input_references(text).next())
+                .map(|(_, id)| id)
+        });
+        resolved_input.or_else(|| {
+            self.dependencies
+                .iter()
+                .find_map(|dependency| dependency.unresolved_input())
+        })
+    }
+}
+
+fn resolve_dependencies_of(
+    template: &TaskTemplate,
+    templates: &[(String, TaskTemplate)],
+    cx: &TaskContext,
+    dependents: &mut Vec<String>,
+) -> Result<Vec<ResolvedTask>> {
+    let mut dependencies = Vec::with_capacity(template.depends_on.len());
+    for label in &template.depends_on {
+        if dependents.contains(label) {
+            dependents.push(label.clone());
+            bail!(
+                "Task dependencies form a cycle: {}",
+                dependents.join(" -> ")
+            );
+        }
+        let (id_base, dependency) = templates
+            .iter()
+            .find(|(_, candidate)| &candidate.label == label)
+            .with_context(|| {
+                format!(
+                    "Task `{}` depends on an unknown task `{label}`",
+                    template.label
+                )
+            })?;
+        let mut resolved = dependency
+            .resolve_task(id_base, cx)
+            .with_context(|| format!("Failed to resolve task `{label}`"))?;
+        dependents.push(label.clone());
+        resolved.dependencies = resolve_dependencies_of(dependency, templates, cx, dependents)?;
+        dependents.pop();
+        dependencies.push(resolved);
+    }
+    Ok(dependencies)
+}
+
+/// Runs the task after the tasks it depends on, using `spawn` to run every single task that has a command,
+/// which reports whether the task succeeded.
+///
+/// A task depended on several times, e.g. by two of the task's dependencies, is only run once.
+/// A failed dependency stops the run before the task that depends on it gets spawned,
+/// and the whole run fails with the first failure found.
+pub fn run_with_dependencies<'a, F, Fut>(
+    task: &'a ResolvedTask,
+    spawn: &'a F,
+) -> LocalBoxFuture<'a, Result<()>>
+where
+    F: Fn(&ResolvedTask) -> Fut,
+    Fut: Future<Output = bool> + 'a,
+{
+    if let Some(error) = &task.dependencies_error {
+        return future::ready(Err(anyhow!("{error}"))).boxed_local();
+    }
+    let run = run_once(task, spawn, &mut HashMap::default());
+    async move { run.await.map_err(|error| anyhow!("{error:#}")) }.boxed_local()
+}
+
+type SharedRun<'a> = Shared<LocalBoxFuture<'a, Result<(), Arc<anyhow::Error>>>>;
+
+/// Returns the run of the task, shared with every other task depending on the same task.
+fn run_once<'a, F, Fut>(
+    task: &'a ResolvedTask,
+    spawn: &'a F,
+    runs: &mut HashMap<TaskId, SharedRun<'a>>,
+) -> SharedRun<'a>
+where
+    F: Fn(&ResolvedTask) -> Fut,
+    Fut: Future<Output = bool> + 'a,
+{
+    if let Some(run) = runs.get(&task.id) {
+        return run.clone();
+    }
+
+    // Futures do nothing until polled, so sequential dependencies still start one after the other.
+    let dependencies = task
+        .dependencies
+        .iter()
+        .map(|dependency| run_once(dependency, spawn, runs))
+        .collect::<Vec<_>>();
+    let run = async move {
+        match task.original_task.depends_order {
+            DependsOrder::Parallel => {
+                join_all(dependencies)
+                    .await
+                    .into_iter()
+                    .collect::<Result<Vec<()>, _>>()?;
+            }
+            DependsOrder::Sequential => {
+                for dependency in dependencies {
+                    dependency.await?;
+                }
+            }
+        }
+
+        if !task.original_task.is_compound() && !spawn(task).await {
+            return Err(Arc::new(anyhow!("Task `{}` failed", task.resolved_label)));
+        }
+        Ok(())
+    }
+    .boxed_local()
+    .shared();
+    runs.insert(task.id.clone(), run.clone());
+    run
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use gpui::TestAppContext;
+
+    use super::*;
+
+    fn task(label: &str, depends_on: &[&str]) -> TaskTemplate {
+        TaskTemplate {
+            label: label.to_string(),
+            command: format!("echo {label}"),
+            depends_on: depends_on.iter().map(|label| label.to_string()).collect(),
+            ..TaskTemplate::default()
+        }
+    }
+
+    fn resolve(label: &str, templates: &[TaskTemplate]) -> Result<ResolvedTask> {
+        let templates = templates
+            .iter()
+            .map(|template| ("test".to_string(), template.clone()))
+            .collect::<Vec<_>>();
+        let (_, template) = templates
+            .iter()
+            .find(|(_, template)| template.label == label)
+            .unwrap();
+        let cx = TaskContext::default();
+        let mut resolved = template.resolve_task("test", &cx).unwrap();
+        resolved.resolve_dependencies(&templates, &cx)?;
+        Ok(resolved)
+    }
+
+    async fn run(task: &ResolvedTask, failing: &[&str]) -> (Result<()>, Vec<String>) {
+        let spawned = RefCell::new(Vec::new());
+        let spawn = |task: &ResolvedTask| {
+            let label = task.resolved_label.clone();
+            spawned.borrow_mut().push(label.clone());
+            let success = !failing.contains(&label.as_str());
+            async move { success }
+        };
+        let result = run_with_dependencies(task, &spawn).await;
+        (result, spawned.into_inner())
+    }
+
+    #[test]
+    fn test_resolve_dependencies() {
+        let templates = [
+            task("build", &["lint", "codegen"]),
+            task("lint", &[]),
+            task("codegen", &["fetch"]),
+            task("fetch", &[]),
+        ];
+        let build = resolve("build", &templates).unwrap();
+        let labels = |task: &ResolvedTask| {
+            task.dependencies()
+                .iter()
+                .map(|dependency| dependency.resolved_label.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(labels(&build), ["lint", "codegen"]);
+        assert_eq!(labels(&build.dependencies()[1]), ["fetch"]);
+
+        let error = resolve("build", &templates[..2]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Task `build` depends on an unknown task `codegen`"
+        );
+    }
+
+    #[test]
+    fn test_dependency_cycles() {
+        let error = resolve("a", &[task("a", &["a"])]).unwrap_err();
+        assert_eq!(error.to_string(), "Task dependencies form a cycle: a -> a");
+
+        let templates = [task("a", &["b"]), task("b", &["c"]), task("c", &["a"])];
+        let error = resolve("a", &templates).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Task dependencies form a cycle: a -> b -> c -> a"
+        );
+
+        // The same task may be depended on several times, as long as it does not depend on itself.
+        let templates = [task("a", &["b", "c"]), task("b", &["c"]), task("c", &[])];
+        assert!(resolve("a", &templates).is_ok());
+    }
+
+    #[test]
+    fn test_dependency_inputs() {
+        let input = |id: &str| TaskInput {
+            id: id.to_string(),
+            description: None,
+            kind: crate::TaskInputKind::PromptString { default: None },
+        };
+        let mut templates = [task("deploy", &["build"]), task("build", &[])];
+        templates[0].command = "./deploy.sh ${input:target}".to_string();
+        templates[0].inputs = vec![input("target"), input("profile")];
+        templates[1].args = vec!["--profile=${input:profile}".to_string()];
+        templates[1].inputs = vec![input("profile"), input("target")];
+
+        let deploy = resolve("deploy", &templates).unwrap();
+        assert_eq!(
+            deploy
+                .referenced_inputs()
+                .into_iter()
+                .map(|input| input.id.as_str())
+                .collect::<Vec<_>>(),
+            ["target", "profile"],
+            "Inputs referenced by dependencies only should be asked for too"
+        );
+        assert_eq!(deploy.unresolved_input(), Some("target"));
+
+        templates[0].command = "./deploy.sh".to_string();
+        let deploy = resolve("deploy", &templates).unwrap();
+        assert_eq!(deploy.unresolved_input(), Some("profile"));
+
+        let templates = templates
+            .iter()
+            .map(|template| ("test".to_string(), template.clone()))
+            .collect::<Vec<_>>();
+        let cx = TaskContext {
+            input_values: HashMap::from_iter([("profile".to_string(), "release".to_string())]),
+            ..TaskContext::default()
+        };
+        let mut deploy = templates[0].1.resolve_task("test", &cx).unwrap();
+        deploy.resolve_dependencies(&templates, &cx).unwrap();
+        assert_eq!(deploy.unresolved_input(), None);
+    }
+
+    #[gpui::test]
+    async fn test_failure_propagation(_: &mut TestAppContext) {
+        let mut templates = [
+            task("all", &["first", "second"]),
+            task("first", &[]),
+            task("second", &[]),
+        ];
+        templates[0].depends_order = DependsOrder::Sequential;
+        let all = resolve("all", &templates).unwrap();
+
+        let (result, spawned) = run(&all, &[]).await;
+        assert!(result.is_ok());
+        assert_eq!(spawned, ["first", "second", "all"]);
+
+        let (result, spawned) = run(&all, &["first"]).await;
+        assert_eq!(result.unwrap_err().to_string(), "Task `first` failed");
+        assert_eq!(
+            spawned,
+            ["first"],
+            "Sequential dependencies should stop at the first failure"
+        );
+
+        let (result, spawned) = run(&all, &["all"]).await;
+        assert_eq!(result.unwrap_err().to_string(), "Task `all` failed");
+        assert_eq!(spawned, ["first", "second", "all"]);
+
+        templates[0].depends_order = DependsOrder::Parallel;
+        let all = resolve("all", &templates).unwrap();
+        let (result, spawned) = run(&all, &["first"]).await;
+        assert_eq!(result.unwrap_err().to_string(), "Task `first` failed");
+        assert_eq!(
+            spawned,
+            ["first", "second"],
+            "Parallel dependencies should all run, but the task depending on them should not"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_unresolved_dependencies(_: &mut TestAppContext) {
+        let templates = [("test".to_string(), task("build", &["codegen"]))];
+        let cx = TaskContext::default();
+        let mut build = templates[0].1.resolve_task("test", &cx).unwrap();
+        assert!(build.resolve_dependencies(&templates, &cx).is_err());
+
+        let (result, spawned) = run(&build, &[]).await;
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Task `build` depends on an unknown task `codegen`"
+        );
+        assert!(
+            spawned.is_empty(),
+            "A task with unresolved dependencies should not run"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_shared_dependencies(_: &mut TestAppContext) {
+        let mut templates = [
+            task("a", &["b", "c"]),
+            task("b", &["d"]),
+            task("c", &["d"]),
+            task("d", &[]),
+        ];
+        let a = resolve("a", &templates).unwrap();
+        let (result, spawned) = run(&a, &[]).await;
+        assert!(result.is_ok());
+        assert_eq!(
+            spawned,
+            ["d", "b", "c", "a"],
+            "A task depended on twice should only run once"
+        );
+
+        let (result, spawned) = run(&a, &["d"]).await;
+        assert_eq!(result.unwrap_err().to_string(), "Task `d` failed");
+        assert_eq!(spawned, ["d"]);
+
+        templates[0].depends_order = DependsOrder::Sequential;
+        let a = resolve("a", &templates).unwrap();
+        let (result, spawned) = run(&a, &[]).await;
+        assert!(result.is_ok());
+        assert_eq!(spawned, ["d", "b", "c", "a"]);
+    }
+
+    #[gpui::test]
+    async fn test_compound_tasks(_: &mut TestAppContext) {
+        let mut compound = task("compound", &["nested", "third"]);
+        compound.command = String::new();
+        let mut nested = task("nested", &["first", "second"]);
+        nested.command = String::new();
+        let templates = [
+            compound,
+            nested,
+            task("first", &[]),
+            task("second", &[]),
+            task("third", &[]),
+        ];
+        assert!(templates[0].is_compound());
+        assert!(!templates[2].is_compound());
+        let compound = resolve("compound", &templates).unwrap();
+
+        let (result, spawned) = run(&compound, &[]).await;
+        assert!(result.is_ok());
+        assert_eq!(
+            spawned,
+            ["first", "second", "third"],
+            "Compound tasks should only spawn the tasks they group"
+        );
+
+        let (result, spawned) = run(&compound, &["second"]).await;
+        assert_eq!(result.unwrap_err().to_string(), "Task `second` failed");
+        assert_eq!(spawned, ["first", "second", "third"]);
+    }
+}
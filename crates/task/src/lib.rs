@@ -1,7 +1,11 @@
 //! Baseline interface of Tasks in Zed: all tasks in Zed are intended to use those for implementing their own logic.
 #![deny(missing_docs)]
 
+mod background;
+mod dependencies;
+mod problem_matcher;
 pub mod static_source;
+mod task_input;
 mod task_template;
 mod vscode_format;
 
@@ -13,7 +17,14 @@ use std::borrow::Cow;
 use std::path::PathBuf;
 use std::str::FromStr;
 
-pub use task_template::{HideStrategy, RevealStrategy, TaskTemplate, TaskTemplates};
+pub use background::{BackgroundMatcher, BackgroundTask};
+pub use dependencies::run_with_dependencies;
+pub use problem_matcher::{
+    CustomProblemMatcher, FileLocation, FileLocationKind, ProblemMatcher, ProblemPattern,
+    ProblemPatterns, ProblemPosition, ProblemSeverity, TaskProblem,
+};
+pub use task_input::{TaskInput, TaskInputKind};
+pub use task_template::{DependsOrder, HideStrategy, RevealStrategy, TaskTemplate, TaskTemplates};
 pub use vscode_format::VsCodeTaskFile;
 pub use zed_actions::RevealTarget;
 
@@ -58,6 +69,10 @@ pub struct SpawnInTerminal {
     pub show_summary: bool,
     /// Whether to show the command line in the task output.
     pub show_command: bool,
+    /// Problem matchers to find problems in the task output with, once the task finishes.
+    pub problem_matchers: Vec<ProblemMatcher>,
+    /// Output patterns telling when the task is ready, for tasks that keep running.
+    pub background: Option<BackgroundTask>,
 }
 
 /// A final form of the [`TaskTemplate`], that got resolved with a particular [`TaskContext`] and now is ready to spawn the actual task.
@@ -75,6 +90,10 @@ pub struct ResolvedTask {
     pub resolved_label: String,
     /// Variables that were substituted during the task template resolution.
     substituted_variables: HashSet<VariableName>,
+    /// Tasks from the template's `depends_on`, resolved with the same context.
+    dependencies: Vec<ResolvedTask>,
+    /// Why the tasks from the template's `depends_on` could not be resolved, reported when the task is run.
+    dependencies_error: Option<String>,
     /// Further actions that need to take place after the resolved task is spawned,
     /// with all task variables resolved.
     pub resolved: Option<SpawnInTerminal>,
@@ -91,6 +110,11 @@ impl ResolvedTask {
         &self.substituted_variables
     }
 
+    /// Tasks to run before this one, empty unless resolved with [`ResolvedTask::resolve_dependencies`].
+    pub fn dependencies(&self) -> &[ResolvedTask] {
+        &self.dependencies
+    }
+
     /// A human-readable label to display in the UI.
     pub fn display_label(&self) -> &str {
         self.resolved
@@ -259,6 +283,8 @@ pub struct TaskContext {
     /// This is the environment one would get when `cd`ing in a terminal
     /// into the project's root directory.
     pub project_env: HashMap<String, String>,
+    /// Values of the task's [`TaskInput`]s, entered by the user when spawning the task, by input ids.
+    pub input_values: HashMap<String, String>,
 }
 
 /// This is a new type representing a 'tag' on a 'runnable symbol', typically a test of main() function, found via treesitter.
@@ -0,0 +1,854 @@
+use std::{
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context as _};
+use regex::{Captures, Regex};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Finds problems, like compiler errors and warnings, in the output of a task.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum ProblemMatcher {
+    /// A built-in matcher, referred to by its name:
+    /// * `$rustc` — the human-readable output of rustc and cargo
+    /// * `$rustc-json` — the JSON output of rustc (`--error-format=json`) and cargo (`--message-format=json`)
+    /// * `$tsc`, `$tsc-watch` — the TypeScript compiler
+    /// * `$gcc` — gcc and clang
+    /// * `$eslint-stylish`, `$eslint-compact` — eslint, with the corresponding formatter
+    BuiltIn(String),
+    /// A matcher that finds problems with regular expressions, in the format of VS Code's `problemMatcher`.
+    Custom(CustomProblemMatcher),
+}
+
+/// A problem matcher that finds problems with regular expressions.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct CustomProblemMatcher {
+    /// Name of a built-in matcher to extend, like `$tsc`: the fields set in this matcher override the built-in ones.
+    #[serde(default)]
+    pub base: Option<String>,
+    /// Source of the diagnostics produced for the problems, `task` by default.
+    #[serde(default)]
+    pub source: Option<String>,
+    /// Severity of the problems that the patterns capture no severity for, `error` by default.
+    #[serde(default)]
+    pub severity: Option<ProblemSeverity>,
+    /// How to resolve the file paths captured by the patterns:
+    /// * `absolute` — the paths are absolute
+    /// * `relative` — the paths are relative to the task's working directory
+    /// * `autoDetect` — the paths are absolute or relative to the task's working directory (default)
+    /// * `["relative", "<dir>"]`, `["autoDetect", "<dir>"]` — same as above, but relative to the given directory
+    #[serde(default, alias = "fileLocation")]
+    pub file_location: Option<FileLocation>,
+    /// The pattern to match each line of the output against,
+    /// or a list of patterns to match against consecutive lines.
+    #[serde(default)]
+    pub pattern: Option<ProblemPatterns>,
+}
+
+/// One or several [`ProblemPattern`]s.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum ProblemPatterns {
+    /// A pattern matching a single line.
+    Single(ProblemPattern),
+    /// Patterns matching consecutive lines, each capturing a part of the problem.
+    Multiple(Vec<ProblemPattern>),
+}
+
+/// A regular expression matching a line of the task output, and the indices of its capture groups
+/// that contain each part of the problem.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ProblemPattern {
+    /// The regular expression to match the line against.
+    pub regexp: String,
+    /// Group of the file path.
+    #[serde(default)]
+    pub file: Option<usize>,
+    /// Group of the location, as `line`, `line,column` or `line,column,end_line,end_column`.
+    #[serde(default)]
+    pub location: Option<usize>,
+    /// Group of the line, starting at 1.
+    #[serde(default)]
+    pub line: Option<usize>,
+    /// Group of the column, starting at 1.
+    #[serde(default)]
+    pub column: Option<usize>,
+    /// Group of the line the problem ends at.
+    #[serde(default, alias = "endLine")]
+    pub end_line: Option<usize>,
+    /// Group of the column the problem ends at.
+    #[serde(default, alias = "endColumn")]
+    pub end_column: Option<usize>,
+    /// Group of the severity, like `error` or `warning`.
+    #[serde(default)]
+    pub severity: Option<usize>,
+    /// Group of the problem's code.
+    #[serde(default)]
+    pub code: Option<usize>,
+    /// Group of the message.
+    #[serde(default)]
+    pub message: Option<usize>,
+    /// Whether the last pattern of the list matches as many consecutive lines as possible,
+    /// producing a problem for each of them.
+    #[serde(default)]
+    pub r#loop: bool,
+}
+
+/// How to resolve the file paths found in the task output.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum FileLocation {
+    /// Paths resolved relative to the task's working directory.
+    Kind(FileLocationKind),
+    /// Paths resolved relative to the given directory.
+    WithBase(FileLocationKind, String),
+}
+
+/// Whether the file paths found in the task output are absolute or relative.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum FileLocationKind {
+    /// The paths are absolute.
+    Absolute,
+    /// The paths are relative.
+    Relative,
+    /// The paths are either absolute or relative.
+    AutoDetect,
+}
+
+/// How severe a problem is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ProblemSeverity {
+    /// An error.
+    Error,
+    /// A warning.
+    Warning,
+    /// An informational message.
+    Info,
+    /// A hint.
+    Hint,
+}
+
+/// A problem found in the output of a task.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TaskProblem {
+    /// The file that the problem is in.
+    pub path: PathBuf,
+    /// Where the problem is in the file. The range is empty if the task only reported where it starts.
+    pub range: Range<ProblemPosition>,
+    /// How severe the problem is.
+    pub severity: ProblemSeverity,
+    /// The message describing the problem.
+    pub message: String,
+    /// The code of the problem, like a compiler error code.
+    pub code: Option<String>,
+    /// Source of the diagnostic to show for the problem.
+    pub source: String,
+}
+
+/// A position in a file, with zero-based rows and columns.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ProblemPosition {
+    /// The zero-based row.
+    pub row: u32,
+    /// The zero-based column, in characters.
+    pub column: u32,
+}
+
+const DEFAULT_SOURCE: &str = "task";
+
+impl ProblemMatcher {
+    /// Finds the problems in the output of a task that ran in the given working directory.
+    pub fn find_problems(
+        &self,
+        output: &str,
+        cwd: Option<&Path>,
+    ) -> anyhow::Result<Vec<TaskProblem>> {
+        let matcher = self.resolve()?;
+        let problems = match &matcher.kind {
+            MatcherKind::RustcJson => find_rustc_json_problems(&matcher, output, cwd),
+            MatcherKind::Patterns(patterns) => {
+                let patterns = patterns
+                    .iter()
+                    .map(CompiledPattern::new)
+                    .collect::<anyhow::Result<Vec<_>>>()?;
+                find_pattern_problems(&matcher, &patterns, output, cwd)
+            }
+        };
+        Ok(problems)
+    }
+
+    /// Returns the directory that relative paths are resolved against, if the matcher sets one.
+    pub(crate) fn base_directory_mut(&mut self) -> Option<&mut String> {
+        match self {
+            Self::BuiltIn(_) => None,
+            Self::Custom(custom) => match custom.file_location.as_mut()? {
+                FileLocation::Kind(_) => None,
+                FileLocation::WithBase(_, base) => Some(base),
+            },
+        }
+    }
+
+    fn resolve(&self) -> anyhow::Result<ResolvedMatcher> {
+        let (base, custom) = match self {
+            Self::BuiltIn(name) => (Some(name.as_str()), None),
+            Self::Custom(custom) => (custom.base.as_deref(), Some(custom)),
+        };
+        let mut matcher = match base {
+            Some(name) => built_in_matcher(name)
+                .with_context(|| format!("Unknown problem matcher `{name}`"))?,
+            None => ResolvedMatcher::new(MatcherKind::Patterns(Vec::new())),
+        };
+
+        if let Some(custom) = custom {
+            if let Some(source) = &custom.source {
+                matcher.source = source.clone();
+            }
+            if let Some(severity) = custom.severity {
+                matcher.severity = severity;
+            }
+            if let Some(file_location) = &custom.file_location {
+                matcher.file_location = file_location.clone();
+            }
+            if let Some(pattern) = &custom.pattern {
+                matcher.kind = MatcherKind::Patterns(pattern.patterns().to_vec());
+            }
+        }
+
+        if matches!(&matcher.kind, MatcherKind::Patterns(patterns) if patterns.is_empty()) {
+            bail!("Problem matcher has no pattern");
+        }
+        Ok(matcher)
+    }
+}
+
+impl ProblemPatterns {
+    /// Returns the patterns, in the order of the lines they match.
+    pub fn patterns(&self) -> &[ProblemPattern] {
+        match self {
+            Self::Single(pattern) => std::slice::from_ref(pattern),
+            Self::Multiple(patterns) => patterns,
+        }
+    }
+}
+
+impl FileLocation {
+    fn resolve(&self, file: &str, cwd: Option<&Path>) -> PathBuf {
+        let (kind, base) = match self {
+            Self::Kind(kind) => (*kind, cwd),
+            Self::WithBase(kind, base) => (*kind, Some(Path::new(base.as_str()))),
+        };
+        match (kind, base) {
+            (FileLocationKind::Absolute, _) | (_, None) => PathBuf::from(file),
+            // Joining an absolute path replaces the base, which is what auto-detection needs.
+            (FileLocationKind::Relative | FileLocationKind::AutoDetect, Some(base)) => {
+                base.join(file)
+            }
+        }
+    }
+}
+
+impl ProblemSeverity {
+    fn from_output(severity: &str) -> Option<Self> {
+        match severity.trim().to_lowercase().as_str() {
+            "error" | "fatal error" | "fatal" | "failure-note" => Some(Self::Error),
+            "warning" | "warn" => Some(Self::Warning),
+            "info" | "information" | "note" => Some(Self::Info),
+            "hint" | "help" => Some(Self::Hint),
+            _ => None,
+        }
+    }
+}
+
+struct ResolvedMatcher {
+    kind: MatcherKind,
+    source: String,
+    severity: ProblemSeverity,
+    file_location: FileLocation,
+}
+
+enum MatcherKind {
+    Patterns(Vec<ProblemPattern>),
+    RustcJson,
+}
+
+impl ResolvedMatcher {
+    fn new(kind: MatcherKind) -> Self {
+        Self {
+            kind,
+            source: DEFAULT_SOURCE.to_string(),
+            severity: ProblemSeverity::Error,
+            file_location: FileLocation::Kind(FileLocationKind::AutoDetect),
+        }
+    }
+}
+
+fn built_in_matcher(name: &str) -> Option<ResolvedMatcher> {
+    let patterns = match name.strip_prefix('$').unwrap_or(name) {
+        "rustc" => vec![
+            ProblemPattern {
+                regexp: r"^(warning|warn|error)(?:\[(\w+)\])?: (.*)$".to_string(),
+                severity: Some(1),
+                code: Some(2),
+                message: Some(3),
+                ..Default::default()
+            },
+            ProblemPattern {
+                regexp: r"^\s+--> (.*?):(\d+):(\d+)$".to_string(),
+                file: Some(1),
+                line: Some(2),
+                column: Some(3),
+                ..Default::default()
+            },
+        ],
+        "rustc-json" => return Some(ResolvedMatcher::new(MatcherKind::RustcJson)),
+        "tsc" | "tsc-watch" => vec![ProblemPattern {
+            regexp: r"^([^\s].*)[\(:](\d+)[,:](\d+)(?:\):\s+|\s+-\s+)(error|warning|info)\s+(TS\d+)\s*:\s*(.*)$"
+                .to_string(),
+            file: Some(1),
+            line: Some(2),
+            column: Some(3),
+            severity: Some(4),
+            code: Some(5),
+            message: Some(6),
+            ..Default::default()
+        }],
+        "gcc" => vec![ProblemPattern {
+            regexp: r"^(.*?):(\d+):(\d*):?\s+(?:fatal\s+)?(warning|error|note):\s+(.*)$"
+                .to_string(),
+            file: Some(1),
+            line: Some(2),
+            column: Some(3),
+            severity: Some(4),
+            message: Some(5),
+            ..Default::default()
+        }],
+        "eslint-stylish" => vec![
+            ProblemPattern {
+                regexp: r"^([^\s].*)$".to_string(),
+                file: Some(1),
+                ..Default::default()
+            },
+            ProblemPattern {
+                regexp: r"^\s+(\d+):(\d+)\s+(error|warning|info)\s+(.*?)(?:\s\s+(\S+))?$"
+                    .to_string(),
+                line: Some(1),
+                column: Some(2),
+                severity: Some(3),
+                message: Some(4),
+                code: Some(5),
+                r#loop: true,
+                ..Default::default()
+            },
+        ],
+        "eslint-compact" => vec![ProblemPattern {
+            regexp: r"^(.+):\sline\s(\d+),\scol\s(\d+),\s(Error|Warning|Info)\s-\s(.+)\s\((.+)\)$"
+                .to_string(),
+            file: Some(1),
+            line: Some(2),
+            column: Some(3),
+            severity: Some(4),
+            message: Some(5),
+            code: Some(6),
+            ..Default::default()
+        }],
+        _ => return None,
+    };
+    Some(ResolvedMatcher::new(MatcherKind::Patterns(patterns)))
+}
+
+struct CompiledPattern<'a> {
+    regex: Regex,
+    pattern: &'a ProblemPattern,
+}
+
+/// The parts of a problem captured so far, by a pattern matching one of its lines.
+#[derive(Clone, Default)]
+struct CapturedProblem<'a> {
+    file: Option<&'a str>,
+    location: Option<&'a str>,
+    line: Option<&'a str>,
+    column: Option<&'a str>,
+    end_line: Option<&'a str>,
+    end_column: Option<&'a str>,
+    severity: Option<&'a str>,
+    code: Option<&'a str>,
+    message: Option<&'a str>,
+}
+
+impl<'a> CompiledPattern<'a> {
+    fn new(pattern: &'a ProblemPattern) -> anyhow::Result<Self> {
+        let regex = Regex::new(&pattern.regexp)
+            .with_context(|| format!("Invalid problem pattern `{}`", pattern.regexp))?;
+        Ok(Self { regex, pattern })
+    }
+
+    fn capture<'o>(&self, captures: &Captures<'o>, problem: &mut CapturedProblem<'o>) {
+        let group = |ix: Option<usize>| {
+            ix.and_then(|ix| captures.get(ix))
+                .map(|capture| capture.as_str())
+                .filter(|capture| !capture.is_empty())
+        };
+        let pattern = self.pattern;
+        for (group_ix, part) in [
+            (pattern.file, &mut problem.file),
+            (pattern.location, &mut problem.location),
+            (pattern.line, &mut problem.line),
+            (pattern.column, &mut problem.column),
+            (pattern.end_line, &mut problem.end_line),
+            (pattern.end_column, &mut problem.end_column),
+            (pattern.severity, &mut problem.severity),
+            (pattern.code, &mut problem.code),
+            (pattern.message, &mut problem.message),
+        ] {
+            if let Some(captured) = group(group_ix) {
+                *part = Some(captured);
+            }
+        }
+    }
+}
+
+fn find_pattern_problems(
+    matcher: &ResolvedMatcher,
+    patterns: &[CompiledPattern],
+    output: &str,
+    cwd: Option<&Path>,
+) -> Vec<TaskProblem> {
+    let Some((last_pattern, leading_patterns)) = patterns.split_last() else {
+        return Vec::new();
+    };
+    let lines = output.lines().collect::<Vec<_>>();
+    let mut problems = Vec::new();
+    let mut ix = 0;
+    'lines: while ix < lines.len() {
+        let mut captured = CapturedProblem::default();
+        for (offset, pattern) in leading_patterns.iter().enumerate() {
+            match lines
+                .get(ix + offset)
+                .and_then(|line| pattern.regex.captures(line))
+            {
+                Some(captures) => pattern.capture(&captures, &mut captured),
+                None => {
+                    ix += 1;
+                    continue 'lines;
+                }
+            }
+        }
+
+        let mut next_ix = ix + leading_patterns.len();
+        while let Some(captures) = lines
+            .get(next_ix)
+            .and_then(|line| last_pattern.regex.captures(line))
+        {
+            let mut captured = captured.clone();
+            last_pattern.capture(&captures, &mut captured);
+            problems.extend(problem_from_captures(matcher, captured, cwd));
+            next_ix += 1;
+            if !last_pattern.pattern.r#loop {
+                break;
+            }
+        }
+        ix = if next_ix > ix + leading_patterns.len() {
+            next_ix
+        } else {
+            ix + 1
+        };
+    }
+    problems
+}
+
+fn problem_from_captures(
+    matcher: &ResolvedMatcher,
+    captured: CapturedProblem,
+    cwd: Option<&Path>,
+) -> Option<TaskProblem> {
+    let file = captured.file?;
+    let message = captured.message?.trim();
+    let mut location = captured
+        .location
+        .map(|location| location.split(',').map(str::trim).collect::<Vec<_>>())
+        .unwrap_or_default()
+        .into_iter();
+    let line = location.next().or(captured.line)?;
+    let column = location.next().or(captured.column);
+    let end_line = location.next().or(captured.end_line);
+    let end_column = location.next().or(captured.end_column);
+
+    let start = position(line, column)?;
+    let end = match end_line.or(end_column) {
+        Some(_) => position(end_line.unwrap_or(line), end_column.or(column))?,
+        None => start,
+    };
+    Some(TaskProblem {
+        path: matcher.file_location.resolve(file, cwd),
+        range: start..end.max(start),
+        severity: captured
+            .severity
+            .and_then(ProblemSeverity::from_output)
+            .unwrap_or(matcher.severity),
+        message: message.to_string(),
+        code: captured.code.map(ToString::to_string),
+        source: matcher.source.clone(),
+    })
+}
+
+/// Converts the one-based line and column from the output into a zero-based position.
+fn position(line: &str, column: Option<&str>) -> Option<ProblemPosition> {
+    let row = line.parse::<u32>().ok()?.saturating_sub(1);
+    let column = column
+        .and_then(|column| column.parse::<u32>().ok())
+        .unwrap_or(1)
+        .saturating_sub(1);
+    Some(ProblemPosition { row, column })
+}
+
+/// A line of `cargo --message-format=json` or `rustc --error-format=json` output.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RustcJsonLine {
+    Cargo { message: RustcDiagnostic },
+    Rustc(RustcDiagnostic),
+}
+
+#[derive(Deserialize)]
+struct RustcDiagnostic {
+    message: String,
+    level: String,
+    code: Option<RustcDiagnosticCode>,
+    #[serde(default)]
+    spans: Vec<RustcSpan>,
+}
+
+#[derive(Deserialize)]
+struct RustcDiagnosticCode {
+    code: String,
+}
+
+#[derive(Deserialize)]
+struct RustcSpan {
+    file_name: String,
+    line_start: u32,
+    line_end: u32,
+    column_start: u32,
+    column_end: u32,
+    is_primary: bool,
+}
+
+fn find_rustc_json_problems(
+    matcher: &ResolvedMatcher,
+    output: &str,
+    cwd: Option<&Path>,
+) -> Vec<TaskProblem> {
+    output
+        .lines()
+        .map(str::trim)
+        .filter(|line| line.starts_with('{'))
+        .filter_map(|line| serde_json_lenient::from_str::<RustcJsonLine>(line).ok())
+        .filter_map(|line| {
+            let diagnostic = match line {
+                RustcJsonLine::Cargo { message } | RustcJsonLine::Rustc(message) => message,
+            };
+            // Diagnostics without a primary span, like "aborting due to 2 previous errors", have no location.
+            let span = diagnostic.spans.iter().find(|span| span.is_primary)?;
+            let start = ProblemPosition {
+                row: span.line_start.saturating_sub(1),
+                column: span.column_start.saturating_sub(1),
+            };
+            let end = ProblemPosition {
+                row: span.line_end.saturating_sub(1),
+                column: span.column_end.saturating_sub(1),
+            };
+            Some(TaskProblem {
+                path: matcher.file_location.resolve(&span.file_name, cwd),
+                range: start..end.max(start),
+                severity: ProblemSeverity::from_output(&diagnostic.level)
+                    .unwrap_or(matcher.severity),
+                message: diagnostic.message,
+                code: diagnostic.code.map(|code| code.code),
+                source: matcher.source.clone(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn problem(
+        path: &str,
+        (row, column): (u32, u32),
+        severity: ProblemSeverity,
+        message: &str,
+        code: Option<&str>,
+    ) -> TaskProblem {
+        let position = ProblemPosition { row, column };
+        TaskProblem {
+            path: PathBuf::from(path),
+            range: position..position,
+            severity,
+            message: message.to_string(),
+            code: code.map(ToString::to_string),
+            source: DEFAULT_SOURCE.to_string(),
+        }
+    }
+
+    fn find_problems(matcher: &str, output: &str) -> Vec<TaskProblem> {
+        ProblemMatcher::BuiltIn(matcher.to_string())
+            .find_problems(output, Some(Path::new("/project")))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_rustc_matcher() {
+        let output = r#"
+   Compiling example v0.1.0 (/project)
+error[E0308]: mismatched types
+ --> src/main.rs:4:18
+  |
+4 |     let x: u32 = "one";
+  |            ---   ^^^^^ expected `u32`, found `&str`
+
+warning: unused variable: `y`
+ --> src/lib.rs:10:9
+error: could not compile `example` (bin "example") due to 1 previous error
+"#;
+        assert_eq!(
+            find_problems("$rustc", output),
+            vec![
+                problem(
+                    "/project/src/main.rs",
+                    (3, 17),
+                    ProblemSeverity::Error,
+                    "mismatched types",
+                    Some("E0308"),
+                ),
+                problem(
+                    "/project/src/lib.rs",
+                    (9, 8),
+                    ProblemSeverity::Warning,
+                    "unused variable: `y`",
+                    None,
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rustc_json_matcher() {
+        let output = r#"{"reason":"compiler-artifact","package_id":"example","target":{"name":"example"},"fresh":true}
+{"reason":"compiler-message","package_id":"example","message":{"$message_type":"diagnostic","message":"unused variable: `y`","code":{"code":"unused_variables","explanation":null},"level":"warning","spans":[{"file_name":"src/lib.rs","byte_start":120,"byte_end":121,"line_start":10,"line_end":10,"column_start":9,"column_end":10,"is_primary":true}],"children":[],"rendered":"warning: unused variable"}}
+{"$message_type":"diagnostic","message":"mismatched types","code":{"code":"E0308","explanation":null},"level":"error","spans":[{"file_name":"/elsewhere/main.rs","byte_start":10,"byte_end":15,"line_start":4,"line_end":4,"column_start":18,"column_end":23,"is_primary":true}],"children":[],"rendered":"error"}
+{"$message_type":"diagnostic","message":"aborting due to 1 previous error","code":null,"level":"error","spans":[],"children":[],"rendered":"error"}
+{"reason":"build-finished","success":false}"#;
+        assert_eq!(
+            find_problems("$rustc-json", output),
+            vec![
+                TaskProblem {
+                    range: ProblemPosition { row: 9, column: 8 }..ProblemPosition {
+                        row: 9,
+                        column: 9
+                    },
+                    ..problem(
+                        "/project/src/lib.rs",
+                        (9, 8),
+                        ProblemSeverity::Warning,
+                        "unused variable: `y`",
+                        Some("unused_variables"),
+                    )
+                },
+                TaskProblem {
+                    range: ProblemPosition { row: 3, column: 17 }..ProblemPosition {
+                        row: 3,
+                        column: 22
+                    },
+                    ..problem(
+                        "/elsewhere/main.rs",
+                        (3, 17),
+                        ProblemSeverity::Error,
+                        "mismatched types",
+                        Some("E0308"),
+                    )
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tsc_matcher() {
+        let output =
+            "src/index.ts(3,7): error TS2322: Type 'string' is not assignable to type 'number'.\n\
+            src/util.ts:12:1 - warning TS6133: 'x' is declared but its value is never read.\n\
+            Found 2 errors.";
+        assert_eq!(
+            find_problems("$tsc", output),
+            vec![
+                problem(
+                    "/project/src/index.ts",
+                    (2, 6),
+                    ProblemSeverity::Error,
+                    "Type 'string' is not assignable to type 'number'.",
+                    Some("TS2322"),
+                ),
+                problem(
+                    "/project/src/util.ts",
+                    (11, 0),
+                    ProblemSeverity::Warning,
+                    "'x' is declared but its value is never read.",
+                    Some("TS6133"),
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_gcc_matcher() {
+        let output = "main.c: In function 'main':\n\
+            main.c:5:12: error: 'y' undeclared (first use in this function)\n\
+            /usr/include/stdio.h:27:1: note: declared here\n\
+            util.c:3: warning: implicit declaration of function 'foo'";
+        assert_eq!(
+            find_problems("$gcc", output),
+            vec![
+                problem(
+                    "/project/main.c",
+                    (4, 11),
+                    ProblemSeverity::Error,
+                    "'y' undeclared (first use in this function)",
+                    None,
+                ),
+                problem(
+                    "/usr/include/stdio.h",
+                    (26, 0),
+                    ProblemSeverity::Info,
+                    "declared here",
+                    None,
+                ),
+                problem(
+                    "/project/util.c",
+                    (2, 0),
+                    ProblemSeverity::Warning,
+                    "implicit declaration of function 'foo'",
+                    None,
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_eslint_matchers() {
+        let output = "/project/src/app.js\n  \
+            1:10  error    'x' is defined but never used  no-unused-vars\n  \
+            4:1   warning  Unexpected console statement   no-console\n\
+            \n\
+            ✖ 2 problems (1 error, 1 warning)";
+        assert_eq!(
+            find_problems("$eslint-stylish", output),
+            vec![
+                problem(
+                    "/project/src/app.js",
+                    (0, 9),
+                    ProblemSeverity::Error,
+                    "'x' is defined but never used",
+                    Some("no-unused-vars"),
+                ),
+                problem(
+                    "/project/src/app.js",
+                    (3, 0),
+                    ProblemSeverity::Warning,
+                    "Unexpected console statement",
+                    Some("no-console"),
+                ),
+            ]
+        );
+
+        let output = "/project/src/app.js: line 1, col 10, Error - 'x' is defined but never used (no-unused-vars)";
+        assert_eq!(
+            find_problems("$eslint-compact", output),
+            vec![problem(
+                "/project/src/app.js",
+                (0, 9),
+                ProblemSeverity::Error,
+                "'x' is defined but never used",
+                Some("no-unused-vars"),
+            )]
+        );
+    }
+
+    #[test]
+    fn test_custom_matchers() {
+        let matcher: ProblemMatcher = serde_json_lenient::from_str(
+            r#"{
+                "owner": "lint",
+                "source": "lint",
+                "severity": "warning",
+                "fileLocation": ["relative", "/project/frontend"],
+                "pattern": [
+                    { "regexp": "^FILE (.*)$", "file": 1 },
+                    { "regexp": "^  (\\d+(?:,\\d+)*) (.*)$", "location": 1, "message": 2, "loop": true }
+                ]
+            }"#,
+        )
+        .unwrap();
+        let output = "FILE a.js\n  1,2,1,5 first\n  3,1,4,2 second\nFILE b.js\nno problems here";
+        assert_eq!(
+            matcher.find_problems(output, None).unwrap(),
+            vec![
+                TaskProblem {
+                    path: PathBuf::from("/project/frontend/a.js"),
+                    range: ProblemPosition { row: 0, column: 1 }..ProblemPosition {
+                        row: 0,
+                        column: 4
+                    },
+                    severity: ProblemSeverity::Warning,
+                    message: "first".to_string(),
+                    code: None,
+                    source: "lint".to_string(),
+                },
+                TaskProblem {
+                    path: PathBuf::from("/project/frontend/a.js"),
+                    range: ProblemPosition { row: 2, column: 0 }..ProblemPosition {
+                        row: 3,
+                        column: 1
+                    },
+                    severity: ProblemSeverity::Warning,
+                    message: "second".to_string(),
+                    code: None,
+                    source: "lint".to_string(),
+                },
+            ]
+        );
+
+        let extended: ProblemMatcher = serde_json_lenient::from_str(
+            r#"{ "base": "$tsc", "fileLocation": ["relative", "/project/editors/code"] }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            extended
+                .find_problems("src/main.ts(1,1): error TS1005: ';' expected.", None)
+                .unwrap(),
+            vec![problem(
+                "/project/editors/code/src/main.ts",
+                (0, 0),
+                ProblemSeverity::Error,
+                "';' expected.",
+                Some("TS1005"),
+            )]
+        );
+
+        let unknown = ProblemMatcher::BuiltIn("$msCompile".to_string());
+        assert_eq!(
+            unknown.find_problems("", None).unwrap_err().to_string(),
+            "Unknown problem matcher `$msCompile`"
+        );
+        let invalid: ProblemMatcher =
+            serde_json_lenient::from_str(r#"{ "pattern": { "regexp": "(", "file": 1 } }"#).unwrap();
+        assert!(invalid.find_problems("", None).is_err());
+    }
+}
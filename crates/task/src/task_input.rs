@@ -0,0 +1,145 @@
+use std::ops::Range;
+
+use collections::HashMap;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+const INPUT_REFERENCE_PREFIX: &str = "${input:";
+
+/// A value the user is asked for when spawning the task, referenced as `${input:id}` in the task's fields.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct TaskInput {
+    /// The name the input is referenced with, as in `${input:id}`.
+    pub id: String,
+    /// A text shown to the user when asking for the value.
+    #[serde(default)]
+    pub description: Option<String>,
+    /// How the value is entered.
+    #[serde(flatten)]
+    pub kind: TaskInputKind,
+}
+
+/// How the value of a [`TaskInput`] is entered.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TaskInputKind {
+    /// The value is typed in.
+    PromptString {
+        /// The value to suggest when no value was entered before.
+        #[serde(default)]
+        default: Option<String>,
+    },
+    /// The value is picked from a list.
+    PickString {
+        /// The values to pick from.
+        options: Vec<String>,
+        /// The value to select when no value was picked before.
+        #[serde(default)]
+        default: Option<String>,
+    },
+    /// The value is picked from the lines printed by a command, run in the task's working directory.
+    Command {
+        /// The command to run.
+        command: String,
+        /// Arguments to the command.
+        #[serde(default)]
+        args: Vec<String>,
+    },
+}
+
+/// Returns the ranges of the `${input:id}` references in the text given, with their input ids.
+pub(crate) fn input_references(text: &str) -> impl Iterator<Item = (Range<usize>, &str)> + '_ {
+    let mut offset = 0;
+    std::iter::from_fn(move || {
+        let start = offset + text[offset..].find(INPUT_REFERENCE_PREFIX)?;
+        let id_start = start + INPUT_REFERENCE_PREFIX.len();
+        let end = id_start + text[id_start..].find('}')? + 1;
+        offset = end;
+        Some((start..end, &text[id_start..end - 1]))
+    })
+}
+
+/// Replaces the `${input:id}` references in the text with the values given, keeping the references
+/// to inputs without a value intact.
+pub(crate) fn substitute_inputs(text: &str, input_values: &HashMap<String, String>) -> String {
+    let mut substituted = String::with_capacity(text.len());
+    let mut last_end = 0;
+    for (range, id) in input_references(text) {
+        if let Some(value) = input_values.get(id) {
+            substituted.push_str(&text[last_end..range.start]);
+            substituted.push_str(value);
+            last_end = range.end;
+        }
+    }
+    substituted.push_str(&text[last_end..]);
+    substituted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_input_references() {
+        let text = "deploy ${input:target} --profile=${input:profile} ${ZED_FILE} ${input:unclosed";
+        assert_eq!(
+            input_references(text)
+                .map(|(range, id)| (&text[range], id))
+                .collect::<Vec<_>>(),
+            vec![
+                ("${input:target}", "target"),
+                ("${input:profile}", "profile")
+            ]
+        );
+
+        let input_values = HashMap::from_iter([
+            ("target".to_string(), "staging".to_string()),
+            ("unused".to_string(), "value".to_string()),
+        ]);
+        assert_eq!(
+            substitute_inputs(text, &input_values),
+            "deploy staging --profile=${input:profile} ${ZED_FILE} ${input:unclosed"
+        );
+    }
+
+    #[test]
+    fn test_deserialize_inputs() {
+        let inputs: Vec<TaskInput> = serde_json_lenient::from_str(
+            r#"[
+                { "id": "name", "type": "prompt_string", "description": "Name", "default": "world" },
+                { "id": "target", "type": "pick_string", "options": ["dev", "prod"] },
+                { "id": "example", "type": "command", "command": "ls", "args": ["examples"] }
+            ]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            inputs,
+            vec![
+                TaskInput {
+                    id: "name".to_string(),
+                    description: Some("Name".to_string()),
+                    kind: TaskInputKind::PromptString {
+                        default: Some("world".to_string())
+                    },
+                },
+                TaskInput {
+                    id: "target".to_string(),
+                    description: None,
+                    kind: TaskInputKind::PickString {
+                        options: vec!["dev".to_string(), "prod".to_string()],
+                        default: None,
+                    },
+                },
+                TaskInput {
+                    id: "example".to_string(),
+                    description: None,
+                    kind: TaskInputKind::Command {
+                        command: "ls".to_string(),
+                        args: vec!["examples".to_string()],
+                    },
+                },
+            ]
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::{borrow::Cow, path::PathBuf};
 use util::serde::default_true;
 
 use anyhow::{bail, Context};
@@ -9,8 +9,9 @@ use sha2::{Digest, Sha256};
 use util::{truncate_and_remove_front, ResultExt};
 
 use crate::{
-    ResolvedTask, RevealTarget, Shell, SpawnInTerminal, TaskContext, TaskId, VariableName,
-    ZED_VARIABLE_NAME_PREFIX,
+    task_input::{input_references, substitute_inputs},
+    BackgroundTask, ProblemMatcher, ResolvedTask, RevealTarget, Shell, SpawnInTerminal,
+    TaskContext, TaskId, TaskInput, VariableName, ZED_VARIABLE_NAME_PREFIX,
 };
 
 /// A template definition of a Zed task to run.
@@ -70,6 +71,48 @@ pub struct TaskTemplate {
     /// Whether to show the command line in the task output.
     #[serde(default = "default_true")]
     pub show_command: bool,
+    /// Labels of the tasks to run before this one. The task is only spawned if all of them succeed.
+    /// A task with dependencies and no command is a compound task, that only groups the tasks it depends on.
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    /// How to run the tasks this one depends on:
+    /// * `parallel` — start all of them at once (default)
+    /// * `sequential` — start them one after another, in the order they are listed in, stopping at the first failure
+    #[serde(default)]
+    pub depends_order: DependsOrder,
+    /// Problem matchers to find problems, like compiler errors, in the task output with.
+    /// The problems are shown as diagnostics once the task finishes, and cleared when it reruns.
+    /// Either the name of a built-in matcher (`$rustc`, `$rustc-json`, `$tsc`, `$gcc`, `$eslint-stylish`, `$eslint-compact`),
+    /// or a custom matcher in the format of VS Code's `problemMatcher`.
+    #[serde(default)]
+    pub problem_matchers: Vec<ProblemMatcher>,
+    /// Makes the task a background task, like a watcher or a dev server, that keeps running and tells
+    /// when it's ready through its output:
+    /// * `ends_pattern` — a regular expression matching the line the task prints when it's ready
+    /// * `begins_pattern` — a regular expression matching the line the task prints when it starts rebuilding
+    /// Tasks depending on a background task start once it's ready, rather than once it exits,
+    /// and its problems are found again each time it's ready.
+    #[serde(default)]
+    pub background: Option<BackgroundTask>,
+    /// Values to ask the user for when spawning the task, referenced as `${input:id}` in the task's
+    /// label, command, args, cwd and env. Each input has an `id`, an optional `description` and a `type`:
+    /// * `prompt_string` — the value is typed in, with an optional `default`
+    /// * `pick_string` — the value is picked from the `options` list, with an optional `default`
+    /// * `command` — the value is picked from the lines printed by the `command` with its `args`
+    /// The last values entered are suggested the next time.
+    #[serde(default)]
+    pub inputs: Vec<TaskInput>,
+}
+
+/// How to run the tasks a task depends on.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DependsOrder {
+    /// Start all of the tasks at once.
+    #[default]
+    Parallel,
+    /// Start the tasks one after another, in the order they are listed in, stopping at the first failure.
+    Sequential,
 }
 
 /// What to do with the terminal pane and tab, after the command was started.
@@ -122,9 +165,10 @@ impl TaskTemplate {
     /// Every [`ResolvedTask`] gets a [`TaskId`], based on the `id_base` (to avoid collision with various task sources),
     /// and hashes of its template and [`TaskContext`], see [`ResolvedTask`] fields' documentation for more details.
     pub fn resolve_task(&self, id_base: &str, cx: &TaskContext) -> Option<ResolvedTask> {
-        if self.label.trim().is_empty() || self.command.trim().is_empty() {
+        if self.label.trim().is_empty() || (self.command.trim().is_empty() && !self.is_compound()) {
             return None;
         }
+        let template = self.with_input_values(&cx.input_values);
 
         let mut variable_names = HashMap::default();
         let mut substituted_variables = HashSet::default();
@@ -141,7 +185,7 @@ impl TaskTemplate {
             })
             .collect::<HashMap<_, _>>();
         let truncated_variables = truncate_variables(&task_variables);
-        let cwd = match self.cwd.as_deref() {
+        let cwd = match template.cwd.as_deref() {
             Some(cwd) => {
                 let substituted_cwd = substitute_all_template_variables_in_str(
                     cwd,
@@ -155,7 +199,7 @@ impl TaskTemplate {
         }
         .or(cx.cwd.clone());
         let full_label = substitute_all_template_variables_in_str(
-            &self.label,
+            &template.label,
             &task_variables,
             &variable_names,
             &mut substituted_variables,
@@ -166,7 +210,7 @@ impl TaskTemplate {
 
         let human_readable_label = if full_label.len() > TRUNCATION_THRESHOLD {
             substitute_all_template_variables_in_str(
-                &self.label,
+                &template.label,
                 &truncated_variables,
                 &variable_names,
                 &mut substituted_variables,
@@ -186,19 +230,32 @@ impl TaskTemplate {
         });
 
         let command = substitute_all_template_variables_in_str(
-            &self.command,
+            &template.command,
             &task_variables,
             &variable_names,
             &mut substituted_variables,
         )?;
         let args_with_substitutions = substitute_all_template_variables_in_vec(
-            &self.args,
+            &template.args,
             &task_variables,
             &variable_names,
             &mut substituted_variables,
         )?;
 
-        let task_hash = to_hex_hash(self)
+        let mut problem_matchers = self.problem_matchers.clone();
+        for base_directory in problem_matchers
+            .iter_mut()
+            .filter_map(ProblemMatcher::base_directory_mut)
+        {
+            *base_directory = substitute_all_template_variables_in_str(
+                base_directory,
+                &task_variables,
+                &variable_names,
+                &mut substituted_variables,
+            )?;
+        }
+
+        let task_hash = to_hex_hash(&template)
             .context("hashing task template")
             .log_err()?;
         let variables_hash = to_hex_hash(&task_variables)
@@ -211,7 +268,7 @@ impl TaskTemplate {
             let mut env = cx.project_env.clone();
 
             // Extend that environment with what's defined in the TaskTemplate
-            env.extend(self.env.clone());
+            env.extend(template.env.clone());
 
             // Then we replace all task variables that could be set in environment variables
             let mut env = substitute_all_template_variables_in_map(
@@ -231,6 +288,8 @@ impl TaskTemplate {
             substituted_variables,
             original_task: self.clone(),
             resolved_label: full_label.clone(),
+            dependencies: Vec::new(),
+            dependencies_error: None,
             resolved: Some(SpawnInTerminal {
                 id,
                 cwd,
@@ -245,7 +304,7 @@ impl TaskTemplate {
                     },
                 ),
                 command,
-                args: self.args.clone(),
+                args: template.args.clone(),
                 env,
                 use_new_terminal: self.use_new_terminal,
                 allow_concurrent_runs: self.allow_concurrent_runs,
@@ -255,9 +314,58 @@ impl TaskTemplate {
                 shell: self.shell.clone(),
                 show_summary: self.show_summary,
                 show_command: self.show_command,
+                problem_matchers,
+                background: self.background.clone(),
             }),
         })
     }
+
+    /// Returns the inputs referenced in the task's fields, in the order they are defined in.
+    pub fn referenced_inputs(&self) -> Vec<&TaskInput> {
+        let referenced_ids = self
+            .referenced_strs()
+            .flat_map(input_references)
+            .map(|(_, id)| id)
+            .collect::<HashSet<_>>();
+        self.inputs
+            .iter()
+            .filter(|input| referenced_ids.contains(input.id.as_str()))
+            .collect()
+    }
+
+    /// Substitutes the `${input:id}` references in the task's fields with the values given.
+    fn with_input_values(&self, input_values: &HashMap<String, String>) -> Cow<'_, Self> {
+        if input_values.is_empty() {
+            return Cow::Borrowed(self);
+        }
+        let substitute = |text: &String| substitute_inputs(text, input_values);
+        Cow::Owned(Self {
+            label: substitute(&self.label),
+            command: substitute(&self.command),
+            args: self.args.iter().map(substitute).collect(),
+            env: self
+                .env
+                .iter()
+                .map(|(key, value)| (substitute(key), substitute(value)))
+                .collect(),
+            cwd: self.cwd.as_ref().map(substitute),
+            ..self.clone()
+        })
+    }
+
+    fn referenced_strs(&self) -> impl Iterator<Item = &str> {
+        [&self.label, &self.command]
+            .into_iter()
+            .chain(&self.args)
+            .chain(self.env.iter().flat_map(|(key, value)| [key, value]))
+            .chain(&self.cwd)
+            .map(String::as_str)
+    }
+
+    /// Whether the task only groups the tasks it depends on, without a command of its own.
+    pub fn is_compound(&self) -> bool {
+        self.command.trim().is_empty() && !self.depends_on.is_empty()
+    }
 }
 
 const MAX_DISPLAY_VARIABLE_LENGTH: usize = 15;
@@ -366,7 +474,7 @@ fn substitute_all_template_variables_in_map(
 mod tests {
     use std::{borrow::Cow, path::Path};
 
-    use crate::{TaskVariables, VariableName};
+    use crate::{TaskInputKind, TaskVariables, VariableName};
 
     use super::*;
 
@@ -431,6 +539,7 @@ mod tests {
             cwd: None,
             task_variables: TaskVariables::default(),
             project_env: HashMap::default(),
+            input_values: HashMap::default(),
         };
         assert_eq!(
             resolved_task(&task_without_cwd, &cx).cwd,
@@ -443,6 +552,7 @@ mod tests {
             cwd: Some(context_cwd.clone()),
             task_variables: TaskVariables::default(),
             project_env: HashMap::default(),
+            input_values: HashMap::default(),
         };
         assert_eq!(
             resolved_task(&task_without_cwd, &cx).cwd,
@@ -459,6 +569,7 @@ mod tests {
             cwd: None,
             task_variables: TaskVariables::default(),
             project_env: HashMap::default(),
+            input_values: HashMap::default(),
         };
         assert_eq!(
             resolved_task(&task_with_cwd, &cx).cwd,
@@ -470,6 +581,7 @@ mod tests {
             cwd: Some(context_cwd.clone()),
             task_variables: TaskVariables::default(),
             project_env: HashMap::default(),
+            input_values: HashMap::default(),
         };
         assert_eq!(
             resolved_task(&task_with_cwd, &cx).cwd,
@@ -546,6 +658,7 @@ mod tests {
                     cwd: None,
                     task_variables: TaskVariables::from_iter(all_variables.clone()),
                     project_env: HashMap::default(),
+                    input_values: HashMap::default(),
                 },
             ).unwrap_or_else(|| panic!("Should successfully resolve task {task_with_all_variables:?} with variables {all_variables:?}"));
 
@@ -634,6 +747,7 @@ mod tests {
                     cwd: None,
                     task_variables: TaskVariables::from_iter(not_all_variables),
                     project_env: HashMap::default(),
+                    input_values: HashMap::default(),
                 },
             );
             assert_eq!(resolved_task_attempt, None, "If any of the Zed task variables is not substituted, the task should not be resolved, but got some resolution without the variable {removed_variable:?} (index {i})");
@@ -687,6 +801,7 @@ mod tests {
                 "test_symbol".to_string(),
             ))),
             project_env: HashMap::default(),
+            input_values: HashMap::default(),
         };
 
         for (i, symbol_dependent_task) in [
@@ -815,6 +930,7 @@ mod tests {
             cwd: None,
             task_variables: TaskVariables::from_iter(all_variables.clone()),
             project_env,
+            input_values: HashMap::default(),
         };
 
         let resolved = template
@@ -831,4 +947,69 @@ mod tests {
             "overwritten"
         );
     }
+
+    #[test]
+    fn test_task_inputs() {
+        let template = TaskTemplate {
+            label: "deploy to ${input:target}".to_string(),
+            command: "./deploy.sh".to_string(),
+            args: vec!["--tag=${input:tag}".to_string()],
+            inputs: vec![
+                TaskInput {
+                    id: "unused".to_string(),
+                    description: None,
+                    kind: TaskInputKind::PromptString { default: None },
+                },
+                TaskInput {
+                    id: "target".to_string(),
+                    description: None,
+                    kind: TaskInputKind::PickString {
+                        options: vec!["staging".to_string(), "production".to_string()],
+                        default: None,
+                    },
+                },
+                TaskInput {
+                    id: "tag".to_string(),
+                    description: Some("Tag to deploy".to_string()),
+                    kind: TaskInputKind::PromptString {
+                        default: Some("latest".to_string()),
+                    },
+                },
+            ],
+            ..TaskTemplate::default()
+        };
+        assert_eq!(
+            template
+                .referenced_inputs()
+                .into_iter()
+                .map(|input| input.id.as_str())
+                .collect::<Vec<_>>(),
+            vec!["target", "tag"]
+        );
+
+        let without_inputs = template
+            .resolve_task(TEST_ID_BASE, &TaskContext::default())
+            .unwrap();
+        assert_eq!(without_inputs.resolved_label, "deploy to ${input:target}");
+
+        let with_inputs = template
+            .resolve_task(
+                TEST_ID_BASE,
+                &TaskContext {
+                    input_values: HashMap::from_iter([
+                        ("target".to_string(), "staging".to_string()),
+                        ("tag".to_string(), "v1.2".to_string()),
+                    ]),
+                    ..TaskContext::default()
+                },
+            )
+            .unwrap();
+        assert_eq!(with_inputs.resolved_label, "deploy to staging");
+        assert_eq!(
+            with_inputs.resolved.as_ref().unwrap().command_label,
+            "./deploy.sh --tag=v1.2"
+        );
+        assert_eq!(with_inputs.original_task(), &template);
+        assert_ne!(with_inputs.id, without_inputs.id);
+    }
 }
@@ -1,9 +1,12 @@
-use anyhow::bail;
+use anyhow::{bail, Context as _};
 use collections::HashMap;
 use serde::Deserialize;
 use util::ResultExt;
 
-use crate::{TaskTemplate, TaskTemplates, VariableName};
+use crate::{
+    BackgroundTask, DependsOrder, ProblemMatcher, TaskInput, TaskInputKind, TaskTemplate,
+    TaskTemplates, VariableName,
+};
 
 #[derive(Clone, Debug, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
@@ -22,6 +25,24 @@ struct VsCodeTaskDefinition {
     #[serde(flatten)]
     other_attributes: HashMap<String, serde_json_lenient::Value>,
     options: Option<TaskOptions>,
+    depends_on: Option<DependsOn>,
+    depends_order: Option<VsCodeDependsOrder>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+enum DependsOn {
+    Label(String),
+    Labels(Vec<String>),
+    /// Dependencies referring to tasks by their type rather than by their label, which we cannot map.
+    Unsupported(serde_json_lenient::Value),
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+enum VsCodeDependsOrder {
+    Parallel,
+    Sequence,
 }
 
 #[derive(Clone, Deserialize, PartialEq, Debug)]
@@ -41,6 +62,65 @@ enum Command {
     },
 }
 
+#[derive(Clone, Deserialize, PartialEq, Debug)]
+#[serde(tag = "type")]
+#[serde(rename_all = "camelCase")]
+enum VsCodeInput {
+    PromptString {
+        id: String,
+        description: Option<String>,
+        default: Option<String>,
+    },
+    PickString {
+        id: String,
+        description: Option<String>,
+        options: Vec<VsCodePickOption>,
+        default: Option<String>,
+    },
+}
+
+#[derive(Clone, Deserialize, PartialEq, Debug)]
+#[serde(untagged)]
+enum VsCodePickOption {
+    Value(String),
+    Labeled { value: String },
+}
+
+impl From<VsCodeInput> for TaskInput {
+    fn from(input: VsCodeInput) -> Self {
+        match input {
+            VsCodeInput::PromptString {
+                id,
+                description,
+                default,
+            } => TaskInput {
+                id,
+                description,
+                kind: TaskInputKind::PromptString { default },
+            },
+            VsCodeInput::PickString {
+                id,
+                description,
+                options,
+                default,
+            } => TaskInput {
+                id,
+                description,
+                kind: TaskInputKind::PickString {
+                    options: options
+                        .into_iter()
+                        .map(|option| match option {
+                            VsCodePickOption::Value(value)
+                            | VsCodePickOption::Labeled { value } => value,
+                        })
+                        .collect(),
+                    default,
+                },
+            },
+        }
+    }
+}
+
 type VsCodeEnvVariable = String;
 type ZedEnvVariable = String;
 
@@ -84,22 +164,41 @@ impl EnvVariableReplacer {
 }
 
 impl VsCodeTaskDefinition {
-    fn into_zed_format(self, replacer: &EnvVariableReplacer) -> anyhow::Result<TaskTemplate> {
-        if self.other_attributes.contains_key("dependsOn") {
-            bail!("Encountered unsupported `dependsOn` key during deserialization");
-        }
-        // `type` might not be set in e.g. tasks that use `dependsOn`; we still want to deserialize the whole object though (hence command is an Option),
-        // as that way we can provide more specific description of why deserialization failed.
-        // E.g. if the command is missing due to `dependsOn` presence, we can check other_attributes first before doing this (and provide nice error message)
-        // catch-all if on value.command presence.
-        let Some(command) = self.command else {
-            bail!("Missing `type` field in task");
+    fn into_zed_format(mut self, replacer: &EnvVariableReplacer) -> anyhow::Result<TaskTemplate> {
+        let is_background = self
+            .other_attributes
+            .remove("isBackground")
+            .and_then(|is_background| is_background.as_bool())
+            .unwrap_or(false);
+        let problem_matcher = self.other_attributes.remove("problemMatcher");
+        let background = problem_matcher
+            .as_ref()
+            .filter(|_| is_background)
+            .and_then(convert_background_patterns);
+        let problem_matchers = problem_matcher
+            .map(|problem_matcher| convert_problem_matchers(problem_matcher, replacer))
+            .unwrap_or_default();
+        let depends_on = match self.depends_on {
+            None => Vec::new(),
+            Some(DependsOn::Label(label)) => vec![label],
+            Some(DependsOn::Labels(labels)) => labels,
+            Some(DependsOn::Unsupported(_)) => {
+                bail!("Encountered unsupported `dependsOn` value during deserialization, only task labels are supported");
+            }
+        };
+        let depends_order = match self.depends_order {
+            None | Some(VsCodeDependsOrder::Parallel) => DependsOrder::Parallel,
+            Some(VsCodeDependsOrder::Sequence) => DependsOrder::Sequential,
         };
 
-        let (command, args) = match command {
-            Command::Npm { script } => ("npm".to_owned(), vec!["run".to_string(), script]),
-            Command::Shell { command, args } => (command, args),
-            Command::Gulp { task } => ("gulp".to_owned(), vec![task]),
+        // `type` might not be set in e.g. tasks that use `dependsOn`; we still want to deserialize the whole object though (hence command is an Option),
+        // as such tasks become compound tasks that only run their dependencies.
+        let (command, args) = match self.command {
+            Some(Command::Npm { script }) => ("npm".to_owned(), vec!["run".to_string(), script]),
+            Some(Command::Shell { command, args }) => (command, args),
+            Some(Command::Gulp { task }) => ("gulp".to_owned(), vec![task]),
+            None if !depends_on.is_empty() => (String::new(), Vec::new()),
+            None => bail!("Missing `type` field in task"),
         };
         // Per VSC docs, only `command`, `args` and `options` support variable substitution.
         let command = replacer.replace(&command);
@@ -108,6 +207,10 @@ impl VsCodeTaskDefinition {
             label: self.label,
             command,
             args,
+            depends_on,
+            depends_order,
+            problem_matchers,
+            background,
             ..Default::default()
         };
         if let Some(options) = self.options {
@@ -118,10 +221,69 @@ impl VsCodeTaskDefinition {
     }
 }
 
+/// Converts a `problemMatcher` value, which is either a single matcher or a list of them.
+/// Matchers that fail to convert are skipped, rather than the whole task.
+fn convert_problem_matchers(
+    problem_matcher: serde_json_lenient::Value,
+    replacer: &EnvVariableReplacer,
+) -> Vec<ProblemMatcher> {
+    let problem_matchers = match problem_matcher {
+        serde_json_lenient::Value::Array(problem_matchers) => problem_matchers,
+        problem_matcher => vec![problem_matcher],
+    };
+    problem_matchers
+        .into_iter()
+        .filter_map(|problem_matcher| {
+            serde_json_lenient::from_value::<ProblemMatcher>(problem_matcher)
+                .context("Encountered unsupported `problemMatcher` value during deserialization")
+                .log_err()
+        })
+        .map(|mut problem_matcher| {
+            if let Some(base_directory) = problem_matcher.base_directory_mut() {
+                *base_directory = replacer.replace(base_directory);
+            }
+            problem_matcher
+        })
+        .collect()
+}
+
+/// Finds the patterns telling when a background task is ready, either in the `background` field
+/// of its problem matchers, or in the ones of the built-in `$tsc-watch` matcher.
+fn convert_background_patterns(
+    problem_matcher: &serde_json_lenient::Value,
+) -> Option<BackgroundTask> {
+    let problem_matchers = match problem_matcher {
+        serde_json_lenient::Value::Array(problem_matchers) => problem_matchers.as_slice(),
+        problem_matcher => std::slice::from_ref(problem_matcher),
+    };
+    problem_matchers.iter().find_map(|problem_matcher| {
+        // Patterns are either regular expressions, or objects with a `regexp` field.
+        let pattern = |key: &str| {
+            let pattern = problem_matcher.get("background")?.get(key)?;
+            pattern
+                .as_str()
+                .or_else(|| pattern.get("regexp")?.as_str())
+                .map(ToString::to_string)
+        };
+        if let Some(ends_pattern) = pattern("endsPattern") {
+            return Some(BackgroundTask {
+                begins_pattern: pattern("beginsPattern"),
+                ends_pattern,
+            });
+        }
+        let name = problem_matcher
+            .as_str()
+            .or_else(|| problem_matcher.get("base")?.as_str())?;
+        (name == "$tsc-watch").then(BackgroundTask::tsc_watch)
+    })
+}
+
 /// [`VsCodeTaskFile`] is a superset of Code's task definition format.
 #[derive(Debug, Deserialize, PartialEq)]
 pub struct VsCodeTaskFile {
     tasks: Vec<VsCodeTaskDefinition>,
+    #[serde(default)]
+    inputs: Vec<serde_json_lenient::Value>,
 }
 
 impl TryFrom<VsCodeTaskFile> for TaskTemplates {
@@ -140,10 +302,26 @@ impl TryFrom<VsCodeTaskFile> for TaskTemplates {
                 VariableName::SelectedText.to_string(),
             ),
         ]));
+        // Inputs running VS Code commands have no equivalent, the tasks referring to them keep the unresolved references.
+        let inputs = value
+            .inputs
+            .into_iter()
+            .filter_map(|input| {
+                serde_json_lenient::from_value::<VsCodeInput>(input)
+                    .context("Encountered unsupported input during deserialization")
+                    .log_err()
+            })
+            .map(TaskInput::from)
+            .collect::<Vec<_>>();
         let templates = value
             .tasks
             .into_iter()
             .filter_map(|vscode_definition| vscode_definition.into_zed_format(&replacer).log_err())
+            .map(|mut template| {
+                template.inputs = inputs.clone();
+                template.inputs = template.referenced_inputs().into_iter().cloned().collect();
+                template
+            })
             .collect();
         Ok(Self(templates))
     }
@@ -154,8 +332,9 @@ mod tests {
     use std::collections::HashMap;
 
     use crate::{
-        vscode_format::{Command, VsCodeTaskDefinition},
-        TaskTemplate, TaskTemplates, VsCodeTaskFile,
+        vscode_format::{Command, DependsOn, VsCodeDependsOrder, VsCodeTaskDefinition},
+        BackgroundTask, CustomProblemMatcher, DependsOrder, FileLocation, FileLocationKind,
+        ProblemMatcher, TaskInput, TaskInputKind, TaskTemplate, TaskTemplates, VsCodeTaskFile,
     };
 
     use super::EnvVariableReplacer;
@@ -212,6 +391,8 @@ mod tests {
                 }),
                 other_attributes: Default::default(),
                 options: None,
+                depends_on: None,
+                depends_order: None,
             },
             VsCodeTaskDefinition {
                 label: "tsc: watch ./src".to_string(),
@@ -226,6 +407,8 @@ mod tests {
                 }),
                 other_attributes: Default::default(),
                 options: None,
+                depends_on: None,
+                depends_order: None,
             },
             VsCodeTaskDefinition {
                 label: "npm: build:compiler".to_string(),
@@ -234,6 +417,8 @@ mod tests {
                 }),
                 other_attributes: Default::default(),
                 options: None,
+                depends_on: None,
+                depends_order: None,
             },
             VsCodeTaskDefinition {
                 label: "npm: build:tests".to_string(),
@@ -242,6 +427,8 @@ mod tests {
                 }),
                 other_attributes: Default::default(),
                 options: None,
+                depends_on: None,
+                depends_order: None,
             },
         ];
 
@@ -257,6 +444,7 @@ mod tests {
                 label: "gulp: tests".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "build:tests:notypecheck".to_string()],
+                problem_matchers: vec![ProblemMatcher::BuiltIn("$tsc".to_string())],
                 ..Default::default()
             },
             TaskTemplate {
@@ -268,18 +456,22 @@ mod tests {
                     "${ZED_WORKTREE_ROOT}/src".to_string(),
                     "--watch".to_string(),
                 ],
+                problem_matchers: vec![ProblemMatcher::BuiltIn("$tsc-watch".to_string())],
+                background: Some(BackgroundTask::tsc_watch()),
                 ..Default::default()
             },
             TaskTemplate {
                 label: "npm: build:compiler".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "build:compiler".to_string()],
+                problem_matchers: vec![ProblemMatcher::BuiltIn("$tsc".to_string())],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "npm: build:tests".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "build:tests:notypecheck".to_string()],
+                problem_matchers: vec![ProblemMatcher::BuiltIn("$tsc".to_string())],
                 ..Default::default()
             },
         ];
@@ -301,6 +493,8 @@ mod tests {
                 }),
                 options: None,
                 other_attributes: Default::default(),
+                depends_on: None,
+                depends_order: None,
             },
             VsCodeTaskDefinition {
                 label: "Build Extension".to_string(),
@@ -309,6 +503,8 @@ mod tests {
                 }),
                 options: None,
                 other_attributes: Default::default(),
+                depends_on: None,
+                depends_order: None,
             },
             VsCodeTaskDefinition {
                 label: "Build Server".to_string(),
@@ -318,6 +514,8 @@ mod tests {
                 }),
                 options: None,
                 other_attributes: Default::default(),
+                depends_on: None,
+                depends_order: None,
             },
             VsCodeTaskDefinition {
                 label: "Build Server (Release)".to_string(),
@@ -327,6 +525,8 @@ mod tests {
                 }),
                 options: None,
                 other_attributes: Default::default(),
+                depends_on: None,
+                depends_order: None,
             },
             VsCodeTaskDefinition {
                 label: "Pretest".to_string(),
@@ -335,18 +535,30 @@ mod tests {
                 }),
                 options: None,
                 other_attributes: Default::default(),
+                depends_on: None,
+                depends_order: None,
             },
             VsCodeTaskDefinition {
                 label: "Build Server and Extension".to_string(),
                 command: None,
                 options: None,
                 other_attributes: Default::default(),
+                depends_on: Some(DependsOn::Labels(vec![
+                    "Build Server".to_string(),
+                    "Build Extension".to_string(),
+                ])),
+                depends_order: None,
             },
             VsCodeTaskDefinition {
                 label: "Build Server (Release) and Extension".to_string(),
                 command: None,
                 options: None,
                 other_attributes: Default::default(),
+                depends_on: Some(DependsOn::Labels(vec![
+                    "Build Server (Release)".to_string(),
+                    "Build Extension".to_string(),
+                ])),
+                depends_order: None,
             },
         ];
         assert_eq!(vscode_definitions.tasks.len(), expected.len());
@@ -360,32 +572,217 @@ mod tests {
                 label: "Build Extension in Background".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "watch".to_string()],
+                background: Some(BackgroundTask::tsc_watch()),
+                problem_matchers: vec![ProblemMatcher::Custom(CustomProblemMatcher {
+                    base: Some("$tsc-watch".to_string()),
+                    file_location: Some(FileLocation::WithBase(
+                        FileLocationKind::Relative,
+                        "${ZED_WORKTREE_ROOT}/editors/code/".to_string(),
+                    )),
+                    ..Default::default()
+                })],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "Build Extension".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "build".to_string()],
+                problem_matchers: vec![ProblemMatcher::Custom(CustomProblemMatcher {
+                    base: Some("$tsc".to_string()),
+                    file_location: Some(FileLocation::WithBase(
+                        FileLocationKind::Relative,
+                        "${ZED_WORKTREE_ROOT}/editors/code/".to_string(),
+                    )),
+                    ..Default::default()
+                })],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "Build Server".to_string(),
                 command: "cargo build --package rust-analyzer".to_string(),
+                problem_matchers: vec![ProblemMatcher::BuiltIn("$rustc".to_string())],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "Build Server (Release)".to_string(),
                 command: "cargo build --release --package rust-analyzer".to_string(),
+                problem_matchers: vec![ProblemMatcher::BuiltIn("$rustc".to_string())],
                 ..Default::default()
             },
             TaskTemplate {
                 label: "Pretest".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "pretest".to_string()],
+                problem_matchers: vec![ProblemMatcher::Custom(CustomProblemMatcher {
+                    base: Some("$tsc".to_string()),
+                    file_location: Some(FileLocation::WithBase(
+                        FileLocationKind::Relative,
+                        "${ZED_WORKTREE_ROOT}/editors/code/".to_string(),
+                    )),
+                    ..Default::default()
+                })],
+                ..Default::default()
+            },
+            TaskTemplate {
+                label: "Build Server and Extension".to_string(),
+                depends_on: vec!["Build Server".to_string(), "Build Extension".to_string()],
+                problem_matchers: vec![ProblemMatcher::BuiltIn("$rustc".to_string())],
+                ..Default::default()
+            },
+            TaskTemplate {
+                label: "Build Server (Release) and Extension".to_string(),
+                depends_on: vec![
+                    "Build Server (Release)".to_string(),
+                    "Build Extension".to_string(),
+                ],
+                problem_matchers: vec![ProblemMatcher::BuiltIn("$rustc".to_string())],
                 ..Default::default()
             },
         ];
         let tasks: TaskTemplates = vscode_definitions.try_into().unwrap();
         assert_eq!(tasks.0, expected);
     }
+
+    #[test]
+    fn can_deserialize_task_dependencies() {
+        const TASKS: &str = r#"{
+            "version": "2.0.0",
+            "tasks": [
+                {
+                    "label": "build",
+                    "type": "shell",
+                    "command": "make",
+                    "dependsOn": "configure"
+                },
+                {
+                    "label": "all",
+                    "dependsOn": ["build", "test"],
+                    "dependsOrder": "sequence"
+                },
+                {
+                    "label": "by type",
+                    "dependsOn": [{ "type": "npm", "script": "build" }]
+                }
+            ]
+        }"#;
+        let vscode_definitions: VsCodeTaskFile = serde_json_lenient::from_str(TASKS).unwrap();
+        assert_eq!(
+            vscode_definitions.tasks[1].depends_order,
+            Some(VsCodeDependsOrder::Sequence)
+        );
+
+        let expected = vec![
+            TaskTemplate {
+                label: "build".to_string(),
+                command: "make".to_string(),
+                depends_on: vec!["configure".to_string()],
+                ..Default::default()
+            },
+            TaskTemplate {
+                label: "all".to_string(),
+                depends_on: vec!["build".to_string(), "test".to_string()],
+                depends_order: DependsOrder::Sequential,
+                ..Default::default()
+            },
+        ];
+        let tasks: TaskTemplates = vscode_definitions.try_into().unwrap();
+        assert_eq!(tasks.0, expected);
+        assert!(tasks.0[1].is_compound());
+    }
+
+    #[test]
+    fn can_deserialize_background_tasks() {
+        const TASKS: &str = r#"{
+            "version": "2.0.0",
+            "tasks": [
+                {
+                    "label": "dev server",
+                    "type": "shell",
+                    "command": "npm run dev",
+                    "isBackground": true,
+                    "problemMatcher": {
+                        "pattern": { "regexp": "^ERROR in (.*)$", "file": 1 },
+                        "background": {
+                            "activeBegins": true,
+                            "beginsPattern": { "regexp": "^Compiling" },
+                            "endsPattern": "^Compiled"
+                        }
+                    }
+                },
+                {
+                    "label": "not a background task",
+                    "type": "shell",
+                    "command": "tsc",
+                    "problemMatcher": "$tsc-watch"
+                }
+            ]
+        }"#;
+        let vscode_definitions: VsCodeTaskFile = serde_json_lenient::from_str(TASKS).unwrap();
+        let tasks: TaskTemplates = vscode_definitions.try_into().unwrap();
+        assert_eq!(
+            tasks.0[0].background,
+            Some(BackgroundTask {
+                begins_pattern: Some("^Compiling".to_string()),
+                ends_pattern: "^Compiled".to_string(),
+            })
+        );
+        assert_eq!(tasks.0[0].problem_matchers.len(), 1);
+        assert_eq!(tasks.0[1].background, None);
+    }
+
+    #[test]
+    fn can_deserialize_inputs() {
+        const TASKS: &str = r#"{
+            "version": "2.0.0",
+            "tasks": [
+                {
+                    "label": "deploy",
+                    "type": "shell",
+                    "command": "./deploy.sh",
+                    "args": ["${input:target}", "${input:component}"]
+                },
+                {
+                    "label": "build",
+                    "type": "shell",
+                    "command": "make"
+                }
+            ],
+            "inputs": [
+                {
+                    "id": "target",
+                    "type": "pickString",
+                    "description": "Where to deploy",
+                    "options": ["staging", { "label": "Production", "value": "production" }],
+                    "default": "staging"
+                },
+                {
+                    "id": "component",
+                    "type": "command",
+                    "command": "extension.pickComponent"
+                },
+                { "id": "unused", "type": "promptString" }
+            ]
+        }"#;
+        let vscode_definitions: VsCodeTaskFile = serde_json_lenient::from_str(TASKS).unwrap();
+        let tasks: TaskTemplates = vscode_definitions.try_into().unwrap();
+        assert_eq!(
+            tasks.0[0].args,
+            vec![
+                "${input:target}".to_string(),
+                "${input:component}".to_string()
+            ]
+        );
+        assert_eq!(
+            tasks.0[0].inputs,
+            vec![TaskInput {
+                id: "target".to_string(),
+                description: Some("Where to deploy".to_string()),
+                kind: TaskInputKind::PickString {
+                    options: vec!["staging".to_string(), "production".to_string()],
+                    default: Some("staging".to_string()),
+                },
+            }]
+        );
+        assert_eq!(tasks.0[1].inputs, Vec::new());
+    }
 }
@@ -0,0 +1,348 @@
+use std::{path::PathBuf, sync::Arc};
+
+use anyhow::Context as _;
+use futures::channel::oneshot;
+use fuzzy::{StringMatch, StringMatchCandidate};
+use gpui::{
+    rems, App, AppContext as _, Context, DismissEvent, Entity, EventEmitter, Focusable,
+    InteractiveElement, ParentElement, Render, SharedString, Styled, Subscription, Task, Window,
+};
+use picker::{highlighted_match_with_paths::HighlightedMatch, Picker, PickerDelegate};
+use project::TaskSourceKind;
+use task::{TaskContext, TaskInput, TaskInputKind, TaskTemplate};
+use ui::{v_flex, Color, IntoElement, ListItem, ListItemSpacing, Toggleable};
+use util::ResultExt;
+use workspace::{notifications::DetachAndPromptErr, tasks::schedule_task, ModalView, Workspace};
+
+/// Asks the user for the values of the inputs referenced by the task and the tasks it depends on,
+/// one modal after another, then schedules the task with them. Dismissing any of the modals cancels the task.
+pub(crate) fn prompt_inputs_and_schedule_task(
+    workspace: &mut Workspace,
+    task_source_kind: TaskSourceKind,
+    task: TaskTemplate,
+    mut task_context: TaskContext,
+    omit_history: bool,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let project = workspace.project().read(cx);
+    let is_local = project.is_local();
+    let task_inventory = project.task_store().read(cx).task_inventory().cloned();
+    let Some(mut resolved_task) = task.resolve_task(&task_source_kind.to_id_base(), &task_context)
+    else {
+        return;
+    };
+    if let Some(task_inventory) = &task_inventory {
+        let worktree = match &task_source_kind {
+            TaskSourceKind::Worktree { id, .. } => Some(*id),
+            _ => None,
+        };
+        // Failing dependencies are reported when scheduling the task.
+        task_inventory
+            .read(cx)
+            .resolve_task_dependencies(&mut resolved_task, worktree, &task_context)
+            .ok();
+    }
+    let inputs = resolved_task
+        .referenced_inputs()
+        .into_iter()
+        .cloned()
+        .collect::<Vec<_>>();
+    if inputs.is_empty() {
+        schedule_task(
+            workspace,
+            task_source_kind,
+            &task,
+            &task_context,
+            omit_history,
+            cx,
+        );
+        return;
+    }
+
+    let task_cwd = resolved_task.resolved.and_then(|resolved| resolved.cwd);
+    cx.spawn_in(window, |workspace, mut cx| async move {
+        for input in inputs {
+            let options = match &input.kind {
+                TaskInputKind::PromptString { .. } => None,
+                TaskInputKind::PickString { options, .. } => Some(options.clone()),
+                TaskInputKind::Command { command, args } => {
+                    anyhow::ensure!(
+                        is_local,
+                        "Task inputs running commands are only supported in local projects"
+                    );
+                    Some(command_output_lines(command, args, task_cwd.clone()).await?)
+                }
+            };
+            let last_value = task_inventory.as_ref().and_then(|task_inventory| {
+                task_inventory
+                    .read_with(&cx, |task_inventory, _| {
+                        task_inventory
+                            .last_input_value(&input.id)
+                            .map(ToOwned::to_owned)
+                    })
+                    .ok()
+                    .flatten()
+            });
+
+            let (value_tx, value_rx) = oneshot::channel();
+            let input_id = input.id.clone();
+            workspace.update_in(&mut cx, |workspace, window, cx| {
+                workspace.toggle_modal(window, cx, |window, cx| {
+                    TaskInputModal::new(input, options, last_value, value_tx, window, cx)
+                })
+            })?;
+            let Ok(value) = value_rx.await else {
+                return Ok(());
+            };
+            task_context.input_values.insert(input_id, value);
+        }
+
+        if let Some(task_inventory) = &task_inventory {
+            task_inventory.update(&mut cx, |task_inventory, _| {
+                task_inventory.input_values_entered(&task_context.input_values)
+            })?;
+        }
+        workspace.update(&mut cx, |workspace, cx| {
+            schedule_task(
+                workspace,
+                task_source_kind,
+                &task,
+                &task_context,
+                omit_history,
+                cx,
+            )
+        })
+    })
+    .detach_and_prompt_err("Failed to spawn the task", window, cx, |_, _, _| None);
+}
+
+/// Runs the command of a task input, and returns the lines it printed.
+async fn command_output_lines(
+    command: &str,
+    args: &[String],
+    cwd: Option<PathBuf>,
+) -> anyhow::Result<Vec<String>> {
+    let mut process = util::command::new_smol_command(command);
+    process.args(args);
+    if let Some(cwd) = cwd {
+        process.current_dir(cwd);
+    }
+    let output = process
+        .output()
+        .await
+        .with_context(|| format!("running task input command `{command}`"))?;
+    anyhow::ensure!(
+        output.status.success(),
+        "Task input command `{command}` failed: {}",
+        String::from_utf8_lossy(&output.stderr).trim()
+    );
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(ToOwned::to_owned)
+        .collect())
+}
+
+/// A modal to type in the value of a task input, or to pick it from a list.
+pub(crate) struct TaskInputModal {
+    picker: Entity<Picker<TaskInputModalDelegate>>,
+    _subscription: Subscription,
+}
+
+impl TaskInputModal {
+    fn new(
+        input: TaskInput,
+        options: Option<Vec<String>>,
+        last_value: Option<String>,
+        value_tx: oneshot::Sender<String>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let default_value = match &input.kind {
+            TaskInputKind::PromptString { default } | TaskInputKind::PickString { default, .. } => {
+                default.clone()
+            }
+            TaskInputKind::Command { .. } => None,
+        };
+        let initial_value = last_value.or(default_value);
+        let is_prompt = options.is_none();
+        let placeholder_text = Arc::from(match &input.description {
+            Some(description) => description.clone(),
+            None if is_prompt => format!("Enter the value of `{}`", input.id),
+            None => format!("Pick the value of `{}`", input.id),
+        });
+        let delegate = TaskInputModalDelegate {
+            options,
+            preferred_option: initial_value.clone().filter(|_| !is_prompt),
+            matches: Vec::new(),
+            selected_index: 0,
+            query: String::new(),
+            value_tx: Some(value_tx),
+            placeholder_text,
+        };
+        let picker = cx.new(|cx| {
+            let picker = Picker::uniform_list(delegate, window, cx);
+            if let Some(initial_value) = initial_value.filter(|_| is_prompt) {
+                picker.set_query(initial_value, window, cx);
+            }
+            picker
+        });
+        let _subscription = cx.subscribe(&picker, |_, _, _, cx| {
+            cx.emit(DismissEvent);
+        });
+        Self {
+            picker,
+            _subscription,
+        }
+    }
+}
+
+impl Render for TaskInputModal {
+    fn render(&mut self, _window: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .key_context("TaskInputModal")
+            .w(rems(34.))
+            .child(self.picker.clone())
+    }
+}
+
+impl EventEmitter<DismissEvent> for TaskInputModal {}
+
+impl Focusable for TaskInputModal {
+    fn focus_handle(&self, cx: &App) -> gpui::FocusHandle {
+        self.picker.read(cx).focus_handle(cx)
+    }
+}
+
+impl ModalView for TaskInputModal {}
+
+struct TaskInputModalDelegate {
+    /// The values to pick from, or `None` when the value is typed in.
+    options: Option<Vec<String>>,
+    /// The option to select initially.
+    preferred_option: Option<String>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+    query: String,
+    value_tx: Option<oneshot::Sender<String>>,
+    placeholder_text: Arc<str>,
+}
+
+impl PickerDelegate for TaskInputModalDelegate {
+    type ListItem = ListItem;
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(
+        &mut self,
+        ix: usize,
+        _window: &mut Window,
+        _cx: &mut Context<Picker<Self>>,
+    ) {
+        self.selected_index = ix;
+    }
+
+    fn placeholder_text(&self, _window: &mut Window, _: &mut App) -> Arc<str> {
+        self.placeholder_text.clone()
+    }
+
+    fn no_matches_text(&self, _window: &mut Window, _: &mut App) -> SharedString {
+        if self.options.is_some() {
+            "No matches".into()
+        } else {
+            "Press enter to confirm".into()
+        }
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Task<()> {
+        let Some(options) = &self.options else {
+            self.query = query;
+            return Task::ready(());
+        };
+        let candidates = options
+            .iter()
+            .enumerate()
+            .map(|(id, option)| StringMatchCandidate::new(id, option))
+            .collect::<Vec<_>>();
+        cx.spawn_in(window, move |picker, mut cx| async move {
+            let matches = fuzzy::match_strings(
+                &candidates,
+                &query,
+                true,
+                1000,
+                &Default::default(),
+                cx.background_executor().clone(),
+            )
+            .await;
+            picker
+                .update(&mut cx, |picker, _| {
+                    let delegate = &mut picker.delegate;
+                    let preferred_option = delegate.preferred_option.take();
+                    delegate.selected_index = preferred_option
+                        .and_then(|preferred_option| {
+                            matches
+                                .iter()
+                                .position(|string_match| string_match.string == preferred_option)
+                        })
+                        .unwrap_or(0);
+                    delegate.matches = matches;
+                    delegate.query = query;
+                })
+                .log_err();
+        })
+    }
+
+    fn confirm(&mut self, _: bool, _window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let value = match &self.options {
+            Some(_) => match self.matches.get(self.selected_index) {
+                Some(string_match) => string_match.string.clone(),
+                None => return,
+            },
+            None => self.query.clone(),
+        };
+        if let Some(value_tx) = self.value_tx.take() {
+            value_tx.send(value).ok();
+        }
+        cx.emit(DismissEvent);
+    }
+
+    fn dismissed(&mut self, _window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let hit = self.matches.get(ix)?;
+        let highlighted_option = HighlightedMatch {
+            text: hit.string.clone(),
+            highlight_positions: hit.positions.clone(),
+            char_count: hit.string.chars().count(),
+            color: Color::Default,
+        };
+        Some(
+            ListItem::new(SharedString::from(format!("task-input-{ix}")))
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .toggle_state(selected)
+                .child(highlighted_option.render(window, cx)),
+        )
+    }
+}
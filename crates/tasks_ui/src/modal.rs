@@ -16,7 +16,10 @@ use ui::{
     KeyBinding, LabelSize, ListItem, ListItemSpacing, RenderOnce, Toggleable, Tooltip,
 };
 use util::ResultExt;
-use workspace::{tasks::schedule_resolved_task, ModalView, Workspace};
+use workspace::{
+    tasks::{schedule_resolved_task, schedule_task_with_inputs},
+    ModalView, Workspace,
+};
 pub use zed_actions::{Rerun, Spawn};
 
 /// A modal used to spawn new tasks.
@@ -285,7 +288,7 @@ impl PickerDelegate for TasksModalDelegate {
     fn confirm(
         &mut self,
         omit_history_entry: bool,
-        _: &mut Window,
+        window: &mut Window,
         cx: &mut Context<picker::Picker<Self>>,
     ) {
         let current_match_index = self.selected_index();
@@ -310,6 +313,33 @@ impl PickerDelegate for TasksModalDelegate {
             }
         }
 
+        // Tasks with inputs get resolved again, once the user enters their values.
+        if !task.referenced_inputs().is_empty() {
+            let mut template = task.original_task().clone();
+            if let Some(TaskOverrides {
+                reveal_target: Some(reveal_target),
+            }) = &self.task_overrides
+            {
+                template.reveal_target = *reveal_target;
+            }
+            let task_context = self.task_context.clone();
+            self.workspace
+                .update(cx, |workspace, cx| {
+                    schedule_task_with_inputs(
+                        workspace,
+                        task_source_kind,
+                        template,
+                        task_context,
+                        omit_history_entry,
+                        window,
+                        cx,
+                    );
+                })
+                .ok();
+            cx.emit(DismissEvent);
+            return;
+        }
+
         self.workspace
             .update(cx, |workspace, cx| {
                 schedule_resolved_task(workspace, task_source_kind, task, omit_history_entry, cx);
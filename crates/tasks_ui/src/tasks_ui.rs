@@ -4,9 +4,12 @@ use gpui::{App, Context, Task as AsyncTask, Window};
 use modal::{TaskOverrides, TasksModal};
 use project::{Location, WorktreeId};
 use task::{RevealTarget, TaskId};
-use workspace::tasks::schedule_task;
-use workspace::{tasks::schedule_resolved_task, Workspace};
+use workspace::{
+    tasks::{register_task_inputs_prompt, schedule_resolved_task, schedule_task_with_inputs},
+    Workspace,
+};
 
+mod inputs;
 mod modal;
 mod settings;
 
@@ -14,6 +17,7 @@ pub use modal::{Rerun, Spawn};
 
 pub fn init(cx: &mut App) {
     settings::TaskSettings::register(cx);
+    register_task_inputs_prompt(inputs::prompt_inputs_and_schedule_task, cx);
     cx.observe_new(
         |workspace: &mut Workspace, _window: Option<&mut Window>, _: &mut Context<Workspace>| {
             workspace
@@ -47,13 +51,14 @@ pub fn init(cx: &mut App) {
                             cx.spawn_in(window, |workspace, mut cx| async move {
                                 let task_context = context_task.await;
                                 workspace
-                                    .update(&mut cx, |workspace, cx| {
-                                        schedule_task(
+                                    .update_in(&mut cx, |workspace, window, cx| {
+                                        schedule_task_with_inputs(
                                             workspace,
                                             task_source_kind,
-                                            &original_task,
-                                            &task_context,
+                                            original_task,
+                                            task_context,
                                             false,
+                                            window,
                                             cx,
                                         )
                                     })
@@ -184,7 +189,7 @@ fn spawn_task_with_name(
         })?;
 
         let did_spawn = workspace
-            .update(&mut cx, |workspace, cx| {
+            .update_in(&mut cx, |workspace, window, cx| {
                 let (task_source_kind, mut target_task) =
                     tasks.into_iter().find(|(_, task)| task.label == name)?;
                 if let Some(overrides) = &overrides {
@@ -192,12 +197,13 @@ fn spawn_task_with_name(
                         target_task.reveal_target = target_override;
                     }
                 }
-                schedule_task(
+                schedule_task_with_inputs(
                     workspace,
                     task_source_kind,
-                    &target_task,
-                    &task_context,
+                    target_task,
+                    task_context,
                     false,
+                    window,
                     cx,
                 );
                 Some(())
@@ -392,6 +398,7 @@ mod tests {
                     (VariableName::Column, "1".into()),
                 ]),
                 project_env: HashMap::default(),
+                input_values: HashMap::default(),
             }
         );
 
@@ -423,6 +430,7 @@ mod tests {
                     (VariableName::Symbol, "this_is_a_rust_file".into()),
                 ]),
                 project_env: HashMap::default(),
+                input_values: HashMap::default(),
             }
         );
 
@@ -448,6 +456,7 @@ mod tests {
                     (VariableName::Symbol, "this_is_a_test".into()),
                 ]),
                 project_env: HashMap::default(),
+                input_values: HashMap::default(),
             }
         );
     }
@@ -0,0 +1,789 @@
+//! Decoding of the Sixel and kitty graphics protocols.
+//!
+//! Like the shell integration sequences, the image sequences are taken out of the PTY output
+//! before it reaches alacritty. Every decoded image is replaced with a blank cell carrying an
+//! internal `OSC 8` hyperlink that identifies the image, followed by the cursor movement the
+//! protocol prescribes. The image then stays anchored to that cell while the grid scrolls,
+//! and disappears from the view once the cell is overwritten or trimmed from the scrollback.
+
+use std::{
+    collections::VecDeque,
+    io::Cursor,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use alacritty_terminal::term::cell::Hyperlink;
+use base64::{
+    alphabet,
+    engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig},
+    Engine as _,
+};
+use gpui::RenderImage;
+use image::{codecs::png::PngDecoder, DynamicImage, Frame, ImageDecoder, Rgba, RgbaImage};
+
+use crate::shell_integration::ShellIntegrationEvent;
+
+const IMAGE_HYPERLINK_PREFIX: &str = "zed-image:";
+/// Images are rejected past this width or height, in pixels.
+const MAX_IMAGE_DIMENSION: u32 = 10_000;
+/// The number of images transmitted with the kitty protocol that are kept around to be placed later.
+const MAX_STORED_KITTY_IMAGES: usize = 32;
+
+const BASE64: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::STANDARD,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+/// A decoded image, anchored to the grid cell that carries its hyperlink.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TerminalImage {
+    pub id: u64,
+    /// The image data, in BGRA format.
+    pub image: Arc<RenderImage>,
+    /// The size the image is displayed at, in pixels of the terminal's cell size.
+    pub width: u32,
+    pub height: u32,
+    /// The number of grid lines the image spans.
+    pub lines: usize,
+}
+
+impl TerminalImage {
+    pub fn memory_size(&self) -> usize {
+        let size = self.image.size(0);
+        size.width.0.max(0) as usize * size.height.0.max(0) as usize * 4
+    }
+}
+
+/// Returns the id of the image, if the hyperlink is an image anchor.
+pub fn image_id(hyperlink: &Hyperlink) -> Option<u64> {
+    hyperlink
+        .uri()
+        .strip_prefix(IMAGE_HYPERLINK_PREFIX)?
+        .parse()
+        .ok()
+}
+
+/// Decodes the image sequences and places the images at the cursor.
+#[derive(Debug)]
+pub(crate) struct GraphicsDecoder {
+    next_image_id: u64,
+    cell_width: u32,
+    cell_height: u32,
+    /// The memory the images of the terminal may take, in bytes.
+    max_image_memory: usize,
+    /// A kitty transmission that is split across several sequences.
+    pending_kitty_command: Option<KittyCommand>,
+    stored_kitty_images: VecDeque<(u32, Arc<RgbaImage>)>,
+    /// The memory taken by the stored kitty images, which the terminal counts toward the
+    /// memory of its images.
+    stored_image_memory: Arc<AtomicUsize>,
+}
+
+impl GraphicsDecoder {
+    pub fn new(max_image_memory: usize, stored_image_memory: Arc<AtomicUsize>) -> Self {
+        Self {
+            next_image_id: 0,
+            cell_width: 1,
+            cell_height: 1,
+            max_image_memory,
+            pending_kitty_command: None,
+            stored_kitty_images: VecDeque::new(),
+            stored_image_memory,
+        }
+    }
+
+    pub fn set_cell_size(&mut self, cell_width: u16, cell_height: u16) {
+        self.cell_width = u32::from(cell_width).max(1);
+        self.cell_height = u32::from(cell_height).max(1);
+    }
+
+    /// Handles the body of a `DCS <params> q <data> ST` sequence.
+    pub fn sixel(
+        &mut self,
+        sequence: &[u8],
+        output: &mut Vec<u8>,
+        events: &mut Vec<ShellIntegrationEvent>,
+    ) {
+        let Some(data_start) = sequence.iter().position(|&byte| byte == b'q') else {
+            return;
+        };
+        let Some(image) = decode_sixel(&sequence[data_start + 1..], self.max_image_memory) else {
+            return;
+        };
+        let (width, height) = image.dimensions();
+        let lines = height.div_ceil(self.cell_height) as usize;
+        self.place_image(image, width, height, output, events);
+        // The cursor ends up below the image, in the column the image starts at.
+        output.resize(output.len() + lines, b'\n');
+    }
+
+    /// Handles the body of an `APC G <control data> ; <payload> ST` sequence.
+    pub fn kitty(
+        &mut self,
+        sequence: &[u8],
+        output: &mut Vec<u8>,
+        events: &mut Vec<ShellIntegrationEvent>,
+    ) {
+        let Some(sequence) = sequence.strip_prefix(b"G") else {
+            return;
+        };
+        let (control, payload) = match sequence.iter().position(|&byte| byte == b';') {
+            Some(ix) => (&sequence[..ix], &sequence[ix + 1..]),
+            None => (sequence, &[][..]),
+        };
+        let chunk = KittyCommand::parse(control);
+
+        let mut command = match self.pending_kitty_command.take() {
+            // Continuation chunks only carry the `m` and `q` keys.
+            Some(mut pending) => {
+                pending.more = chunk.more;
+                pending
+            }
+            None => chunk,
+        };
+        // A single sequence is limited in length, but a transmission can be split into any
+        // number of them, so their payload is limited to the encoded size of the largest image.
+        let max_payload_len = self.max_image_memory.div_ceil(3).saturating_mul(4);
+        if command.payload.len().saturating_add(payload.len()) > max_payload_len {
+            if let Some(reply) = command.reply(Err("ENOMEM:image too large")) {
+                events.push(ShellIntegrationEvent::Reply(reply));
+            }
+            return;
+        }
+        command.payload.extend_from_slice(payload);
+        if command.more {
+            self.pending_kitty_command = Some(command);
+            return;
+        }
+
+        let result = self.run_kitty_command(&command, output, events);
+        if let Some(reply) = command.reply(result) {
+            events.push(ShellIntegrationEvent::Reply(reply));
+        }
+    }
+
+    fn run_kitty_command(
+        &mut self,
+        command: &KittyCommand,
+        output: &mut Vec<u8>,
+        events: &mut Vec<ShellIntegrationEvent>,
+    ) -> Result<(), &'static str> {
+        match command.action {
+            // Query: a transmission that is checked but not stored
+            b'q' => command.decode_image(self.max_image_memory).map(drop),
+            b't' | b'T' => {
+                let image = Arc::new(command.decode_image(self.max_image_memory)?);
+                if command.image_id != 0 {
+                    self.store_kitty_image(command.image_id, image.clone());
+                }
+                if command.action == b'T' {
+                    self.place_kitty_image(command, &image, output, events);
+                }
+                Ok(())
+            }
+            b'p' => {
+                let image = self
+                    .stored_kitty_images
+                    .iter()
+                    .find(|(image_id, _)| *image_id == command.image_id)
+                    .map(|(_, image)| image.clone())
+                    .ok_or("ENOENT:image not found")?;
+                self.place_kitty_image(command, &image, output, events);
+                Ok(())
+            }
+            // Deleting is left to the grid: the images go away with their anchor cells.
+            b'd' => Ok(()),
+            _ => Err("EINVAL:unsupported action"),
+        }
+    }
+
+    /// Keeps the image to be placed later, dropping the oldest stored images past the count
+    /// and memory limits.
+    fn store_kitty_image(&mut self, image_id: u32, image: Arc<RgbaImage>) {
+        self.stored_kitty_images
+            .retain(|(stored_id, _)| *stored_id != image_id);
+        self.stored_kitty_images.push_back((image_id, image));
+        let mut memory = self
+            .stored_kitty_images
+            .iter()
+            .map(|(_, image)| image.len())
+            .sum::<usize>();
+        while self.stored_kitty_images.len() > MAX_STORED_KITTY_IMAGES
+            || memory > self.max_image_memory
+        {
+            let Some((_, image)) = self.stored_kitty_images.pop_front() else {
+                break;
+            };
+            memory -= image.len();
+        }
+        self.stored_image_memory.store(memory, Ordering::Relaxed);
+    }
+
+    fn place_kitty_image(
+        &mut self,
+        command: &KittyCommand,
+        image: &RgbaImage,
+        output: &mut Vec<u8>,
+        events: &mut Vec<ShellIntegrationEvent>,
+    ) {
+        let columns = match command.columns {
+            0 => image.width().div_ceil(self.cell_width),
+            columns => columns,
+        };
+        let lines = match command.lines {
+            0 => image.height().div_ceil(self.cell_height),
+            lines => lines,
+        };
+        let (width, height) = if command.columns == 0 && command.lines == 0 {
+            image.dimensions()
+        } else {
+            (columns * self.cell_width, lines * self.cell_height)
+        };
+        self.place_image(image.clone(), width, height, output, events);
+        if !command.keep_cursor {
+            // The cursor ends up after the image, on its last line.
+            output.resize(output.len() + lines.saturating_sub(1) as usize, b'\n');
+            output.extend_from_slice(format!("\x1b[{columns}C").as_bytes());
+        }
+    }
+
+    /// Writes the anchor cell at the cursor, leaving the cursor where it was.
+    fn place_image(
+        &mut self,
+        mut image: RgbaImage,
+        width: u32,
+        height: u32,
+        output: &mut Vec<u8>,
+        events: &mut Vec<ShellIntegrationEvent>,
+    ) {
+        let id = self.next_image_id;
+        self.next_image_id += 1;
+
+        // Convert from RGBA to BGRA.
+        for pixel in image.chunks_exact_mut(4) {
+            pixel.swap(0, 2);
+        }
+        events.push(ShellIntegrationEvent::ImageReceived(TerminalImage {
+            id,
+            image: Arc::new(RenderImage::new(vec![Frame::new(image)])),
+            width,
+            height,
+            lines: height.div_ceil(self.cell_height) as usize,
+        }));
+        output.extend_from_slice(
+            format!("\x1b]8;;{IMAGE_HYPERLINK_PREFIX}{id}\x07 \x1b]8;;\x07\x08").as_bytes(),
+        );
+    }
+}
+
+#[derive(Debug, Default)]
+struct KittyCommand {
+    action: u8,
+    format: u32,
+    transmission: u8,
+    width: u32,
+    height: u32,
+    image_id: u32,
+    columns: u32,
+    lines: u32,
+    more: bool,
+    quiet: u8,
+    keep_cursor: bool,
+    compressed: bool,
+    payload: Vec<u8>,
+}
+
+impl KittyCommand {
+    fn parse(control: &[u8]) -> Self {
+        let mut command = KittyCommand {
+            action: b't',
+            format: 32,
+            transmission: b'd',
+            ..Default::default()
+        };
+        for pair in control.split(|&byte| byte == b',') {
+            let [key, b'=', value @ ..] = pair else {
+                continue;
+            };
+            let number = || {
+                std::str::from_utf8(value)
+                    .ok()
+                    .and_then(|value| value.parse().ok())
+                    .unwrap_or(0)
+            };
+            match *key {
+                b'a' => command.action = value.first().copied().unwrap_or(b't'),
+                b'f' => command.format = number(),
+                b't' => command.transmission = value.first().copied().unwrap_or(b'd'),
+                b's' => command.width = number(),
+                b'v' => command.height = number(),
+                b'i' => command.image_id = number(),
+                b'c' => command.columns = number(),
+                b'r' => command.lines = number(),
+                b'm' => command.more = value == b"1",
+                b'q' => command.quiet = number() as u8,
+                b'C' => command.keep_cursor = value == b"1",
+                b'o' => command.compressed = true,
+                _ => {}
+            }
+        }
+        command
+    }
+
+    /// Decodes the image, if it takes at most `max_memory` bytes once decoded.
+    fn decode_image(&self, max_memory: usize) -> Result<RgbaImage, &'static str> {
+        if self.compressed {
+            return Err("EINVAL:compression is not supported");
+        }
+        // Reading files and shared memory would let any program writing to the terminal make
+        // Zed read local files, so only the image data sent in the sequences is accepted.
+        if self.transmission != b'd' {
+            return Err("EINVAL:unsupported transmission medium");
+        }
+        let data = BASE64
+            .decode(&self.payload)
+            .map_err(|_| "EINVAL:invalid base64 data")?;
+
+        let image = match self.format {
+            24 | 32 => {
+                check_dimensions(self.width, self.height)?;
+                check_memory(self.width as usize, self.height as usize, max_memory)?;
+                let bytes_per_pixel = if self.format == 24 { 3 } else { 4 };
+                let len = (self.width * self.height * bytes_per_pixel) as usize;
+                if data.len() < len {
+                    return Err("ENODATA:insufficient image data");
+                }
+                let pixels = data[..len]
+                    .chunks_exact(bytes_per_pixel as usize)
+                    .flat_map(|pixel| [pixel[0], pixel[1], pixel[2], *pixel.get(3).unwrap_or(&255)])
+                    .collect();
+                RgbaImage::from_raw(self.width, self.height, pixels)
+                    .ok_or("EINVAL:invalid image size")?
+            }
+            100 => {
+                let decoder =
+                    PngDecoder::new(Cursor::new(&data)).map_err(|_| "EINVAL:invalid PNG data")?;
+                let (width, height) = decoder.dimensions();
+                check_dimensions(width, height)?;
+                check_memory(width as usize, height as usize, max_memory)?;
+                DynamicImage::from_decoder(decoder)
+                    .map_err(|_| "EINVAL:invalid PNG data")?
+                    .into_rgba8()
+            }
+            _ => return Err("EINVAL:unsupported format"),
+        };
+        check_dimensions(image.width(), image.height())?;
+        Ok(image)
+    }
+
+    /// Builds the response to the command, as requested by its `i` and `q` keys.
+    fn reply(&self, result: Result<(), &'static str>) -> Option<Vec<u8>> {
+        if self.image_id == 0 {
+            return None;
+        }
+        let message = match result {
+            Ok(()) if self.quiet == 0 => "OK",
+            Err(error) if self.quiet < 2 => error,
+            _ => return None,
+        };
+        Some(format!("\x1b_Gi={};{message}\x1b\\", self.image_id).into_bytes())
+    }
+}
+
+fn check_dimensions(width: u32, height: u32) -> Result<(), &'static str> {
+    if width == 0 || height == 0 || width > MAX_IMAGE_DIMENSION || height > MAX_IMAGE_DIMENSION {
+        Err("EINVAL:invalid image size")
+    } else {
+        Ok(())
+    }
+}
+
+/// Checks that an image of the given size takes at most `max_memory` bytes once decoded.
+fn check_memory(width: usize, height: usize, max_memory: usize) -> Result<(), &'static str> {
+    if width.saturating_mul(height).saturating_mul(4) > max_memory {
+        Err("ENOMEM:image too large")
+    } else {
+        Ok(())
+    }
+}
+
+/// Decodes the data of a Sixel sequence, that follows the `q` final byte, if the image takes at
+/// most `max_memory` bytes.
+fn decode_sixel(data: &[u8], max_memory: usize) -> Option<RgbaImage> {
+    let mut palette = default_sixel_palette();
+    let mut color = palette[0];
+    let mut rows: Vec<Vec<[u8; 4]>> = Vec::new();
+    let (mut raster_width, mut raster_height) = (0, 0);
+    let (mut x, mut y) = (0usize, 0usize);
+    // The width of the widest row drawn so far.
+    let mut drawn_width = 0;
+
+    let mut ix = 0;
+    while ix < data.len() {
+        let byte = data[ix];
+        ix += 1;
+        match byte {
+            // Raster attributes: `" Pan ; Pad ; Ph ; Pv`
+            b'"' => {
+                let params = parse_sixel_params(data, &mut ix);
+                raster_width = params.get(2).copied().unwrap_or(0) as usize;
+                raster_height = params.get(3).copied().unwrap_or(0) as usize;
+                check_memory(raster_width, raster_height, max_memory).ok()?;
+            }
+            // Color introducer: `# Pc` selects, `# Pc ; Pu ; Px ; Py ; Pz` defines and selects
+            b'#' => {
+                let params = parse_sixel_params(data, &mut ix);
+                let Some(&register) = params.first() else {
+                    continue;
+                };
+                let register = register as usize % palette.len();
+                if let [_, space, a, b, c, ..] = params[..] {
+                    palette[register] = match space {
+                        1 => hls_to_rgb(a, b, c),
+                        _ => [percent_to_u8(a), percent_to_u8(b), percent_to_u8(c), 255],
+                    };
+                }
+                color = palette[register];
+            }
+            // Repeat introducer: `! Pn <sixel>`
+            b'!' => {
+                let count = parse_sixel_params(data, &mut ix)
+                    .first()
+                    .copied()
+                    .unwrap_or(1)
+                    .max(1) as usize;
+                if let Some(&sixel @ 0x3f..=0x7e) = data.get(ix) {
+                    ix += 1;
+                    drawn_width = drawn_width.max(x + count);
+                    check_memory(drawn_width, y + 6, max_memory).ok()?;
+                    draw_sixel(&mut rows, x, y, count, sixel - 0x3f, color)?;
+                    x += count;
+                }
+            }
+            b'$' => x = 0,
+            b'-' => {
+                x = 0;
+                y += 6;
+            }
+            0x3f..=0x7e => {
+                drawn_width = drawn_width.max(x + 1);
+                check_memory(drawn_width, y + 6, max_memory).ok()?;
+                draw_sixel(&mut rows, x, y, 1, byte - 0x3f, color)?;
+                x += 1;
+            }
+            _ => {}
+        }
+    }
+
+    let width = rows
+        .iter()
+        .map(Vec::len)
+        .max()
+        .unwrap_or(0)
+        .max(raster_width);
+    let height = rows.len().max(raster_height);
+    check_dimensions(width as u32, height as u32).ok()?;
+    check_memory(width, height, max_memory).ok()?;
+    let mut image = RgbaImage::new(width as u32, height as u32);
+    for (y, row) in rows.iter().enumerate() {
+        for (x, pixel) in row.iter().enumerate() {
+            image.put_pixel(x as u32, y as u32, Rgba(*pixel));
+        }
+    }
+    Some(image)
+}
+
+fn draw_sixel(
+    rows: &mut Vec<Vec<[u8; 4]>>,
+    x: usize,
+    y: usize,
+    count: usize,
+    bits: u8,
+    color: [u8; 4],
+) -> Option<()> {
+    let max = MAX_IMAGE_DIMENSION as usize;
+    if x + count > max || y + 6 > max {
+        return None;
+    }
+    for bit in 0..6 {
+        if bits & (1 << bit) == 0 {
+            continue;
+        }
+        let y = y + bit;
+        if rows.len() <= y {
+            rows.resize_with(y + 1, Vec::new);
+        }
+        let row = &mut rows[y];
+        if row.len() < x + count {
+            // Pixels that are never drawn stay transparent.
+            row.resize(x + count, [0, 0, 0, 0]);
+        }
+        row[x..x + count].fill(color);
+    }
+    Some(())
+}
+
+fn parse_sixel_params(data: &[u8], ix: &mut usize) -> Vec<u32> {
+    let mut params = Vec::new();
+    let mut current = None::<u32>;
+    while let Some(&byte) = data.get(*ix) {
+        match byte {
+            b'0'..=b'9' => {
+                let digit = u32::from(byte - b'0');
+                current = Some(
+                    current
+                        .unwrap_or(0)
+                        .saturating_mul(10)
+                        .saturating_add(digit),
+                );
+            }
+            b';' => params.push(current.take().unwrap_or(0)),
+            _ => break,
+        }
+        *ix += 1;
+    }
+    if let Some(current) = current {
+        params.push(current);
+    }
+    params
+}
+
+fn percent_to_u8(percent: u32) -> u8 {
+    (percent.min(100) * 255 / 100) as u8
+}
+
+/// Converts a Sixel HLS color, where the hue of blue is 0°, to RGBA.
+fn hls_to_rgb(hue: u32, lightness: u32, saturation: u32) -> [u8; 4] {
+    let hue = ((hue % 360 + 240) % 360) as f32 / 360.;
+    let lightness = lightness.min(100) as f32 / 100.;
+    let saturation = saturation.min(100) as f32 / 100.;
+    let q = if lightness < 0.5 {
+        lightness * (1. + saturation)
+    } else {
+        lightness + saturation - lightness * saturation
+    };
+    let p = 2. * lightness - q;
+    let channel = |t: f32| {
+        let t = t.rem_euclid(1.);
+        let value = if t < 1. / 6. {
+            p + (q - p) * 6. * t
+        } else if t < 1. / 2. {
+            q
+        } else if t < 2. / 3. {
+            p + (q - p) * (2. / 3. - t) * 6.
+        } else {
+            p
+        };
+        (value * 255.).round() as u8
+    };
+    [
+        channel(hue + 1. / 3.),
+        channel(hue),
+        channel(hue - 1. / 3.),
+        255,
+    ]
+}
+
+/// The VT340 default color registers, followed by black.
+fn default_sixel_palette() -> [[u8; 4]; 256] {
+    const VT340_COLORS: [[u32; 3]; 16] = [
+        [0, 0, 0],
+        [20, 20, 80],
+        [80, 13, 13],
+        [20, 80, 20],
+        [80, 20, 80],
+        [20, 80, 80],
+        [80, 80, 20],
+        [53, 53, 53],
+        [26, 26, 26],
+        [33, 33, 60],
+        [60, 26, 26],
+        [33, 60, 33],
+        [60, 33, 60],
+        [33, 60, 60],
+        [60, 60, 33],
+        [80, 80, 80],
+    ];
+    let mut palette = [[0, 0, 0, 255]; 256];
+    for (color, [r, g, b]) in palette.iter_mut().zip(VT340_COLORS) {
+        *color = [percent_to_u8(r), percent_to_u8(g), percent_to_u8(b), 255];
+    }
+    palette
+}
+
+#[cfg(test)]
+mod tests {
+    use base64::Engine as _;
+
+    use super::{decode_sixel, GraphicsDecoder, BASE64};
+    use crate::shell_integration::ShellIntegrationEvent;
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    #[test]
+    fn test_decode_sixel() {
+        // A red 2x6 column, then a green pixel at the top of the next band.
+        let image = decode_sixel(b"\"1;1;3;12#1;2;100;0;0#1!2~-#2;2;0;100;0@", 1024).unwrap();
+        assert_eq!(image.dimensions(), (3, 12));
+        assert_eq!(image.get_pixel(1, 5).0, [255, 0, 0, 255]);
+        assert_eq!(image.get_pixel(0, 6).0, [0, 255, 0, 255]);
+        assert_eq!(image.get_pixel(2, 0).0, [0, 0, 0, 0]);
+        assert_eq!(image.get_pixel(0, 7).0, [0, 0, 0, 0]);
+
+        // Images taking more memory than allowed are rejected before they're allocated.
+        assert!(decode_sixel(b"\"1;1;3;12~", 3 * 12 * 4 - 1).is_none());
+        assert!(decode_sixel(b"!100~", 100 * 6 * 4 - 1).is_none());
+
+        // Out of range HLS components are wrapped or clamped instead of overflowing.
+        let image = decode_sixel(b"#1;1;99999999999;50;100#1~", 1024).unwrap();
+        assert_eq!(image.get_pixel(0, 0).0[3], 255);
+    }
+
+    #[test]
+    fn test_kitty_chunked_transmission() {
+        let mut decoder = GraphicsDecoder::new(1024, Default::default());
+        decoder.set_cell_size(2, 4);
+        let pixels = BASE64.encode([255, 0, 0, 0, 255, 0, 0, 0, 255]);
+        let (first, second) = pixels.split_at(4);
+
+        let mut output = Vec::new();
+        let mut events = Vec::new();
+        decoder.kitty(
+            format!("Ga=T,f=24,s=3,v=1,i=7,m=1;{first}").as_bytes(),
+            &mut output,
+            &mut events,
+        );
+        assert!(output.is_empty() && events.is_empty());
+        decoder.kitty(
+            format!("Gm=0;{second}").as_bytes(),
+            &mut output,
+            &mut events,
+        );
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "\x1b]8;;zed-image:0\x07 \x1b]8;;\x07\x08\x1b[2C"
+        );
+        let [ShellIntegrationEvent::ImageReceived(image), ShellIntegrationEvent::Reply(reply)] =
+            &events[..]
+        else {
+            panic!("unexpected events {events:?}");
+        };
+        assert_eq!((image.width, image.height, image.lines), (3, 1, 1));
+        // Stored as BGRA
+        assert_eq!(image.image.as_bytes(0).unwrap()[..4], [0, 0, 255, 255]);
+        assert_eq!(reply, b"\x1b_Gi=7;OK\x1b\\");
+    }
+
+    #[test]
+    fn test_kitty_oversized_chunked_transmission() {
+        let mut decoder = GraphicsDecoder::new(1024, Default::default());
+        let chunk = "A".repeat(1000);
+        let mut output = Vec::new();
+        let mut events = Vec::new();
+        decoder.kitty(
+            format!("Ga=T,f=32,s=100,v=100,i=9,m=1;{chunk}").as_bytes(),
+            &mut output,
+            &mut events,
+        );
+        for _ in 0..3 {
+            decoder.kitty(format!("Gm=1;{chunk}").as_bytes(), &mut output, &mut events);
+            let pending_len = decoder
+                .pending_kitty_command
+                .as_ref()
+                .map_or(0, |command| command.payload.len());
+            assert!(pending_len <= 1024 / 3 * 4 + 4);
+        }
+        decoder.kitty(format!("Gm=0;{chunk}").as_bytes(), &mut output, &mut events);
+
+        assert!(output.is_empty());
+        assert_eq!(
+            events,
+            vec![ShellIntegrationEvent::Reply(
+                b"\x1b_Gi=9;ENOMEM:image too large\x1b\\".to_vec()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_kitty_errors() {
+        let mut decoder = GraphicsDecoder::new(1024, Default::default());
+        let mut output = Vec::new();
+        let mut events = Vec::new();
+        decoder.kitty(b"Ga=p,i=3", &mut output, &mut events);
+        decoder.kitty(b"Ga=p,i=4,q=2", &mut output, &mut events);
+        decoder.kitty(b"Ga=p", &mut output, &mut events);
+
+        assert!(output.is_empty());
+        assert_eq!(
+            events,
+            vec![ShellIntegrationEvent::Reply(
+                b"\x1b_Gi=3;ENOENT:image not found\x1b\\".to_vec()
+            )]
+        );
+
+        // Files are never read.
+        events.clear();
+        let path = BASE64.encode("/etc/hosts");
+        decoder.kitty(
+            format!("Ga=T,t=f,f=100,i=5;{path}").as_bytes(),
+            &mut output,
+            &mut events,
+        );
+        assert!(output.is_empty());
+        assert_eq!(
+            events,
+            vec![ShellIntegrationEvent::Reply(
+                b"\x1b_Gi=5;EINVAL:unsupported transmission medium\x1b\\".to_vec()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_kitty_stored_image_memory() {
+        let stored_image_memory = Arc::<AtomicUsize>::default();
+        // Room for two 2x2 images.
+        let mut decoder = GraphicsDecoder::new(2 * 2 * 4 * 2, stored_image_memory.clone());
+        let pixels = BASE64.encode([255u8; 2 * 2 * 4]);
+        let mut output = Vec::new();
+        let mut events = Vec::new();
+        for image_id in 1..=3 {
+            decoder.kitty(
+                format!("Ga=t,s=2,v=2,i={image_id},q=2;{pixels}").as_bytes(),
+                &mut output,
+                &mut events,
+            );
+        }
+        assert_eq!(stored_image_memory.load(Ordering::Relaxed), 2 * 2 * 4 * 2);
+
+        // The oldest image was dropped.
+        decoder.kitty(b"Ga=p,i=1", &mut output, &mut events);
+        decoder.kitty(b"Ga=p,i=3,q=1", &mut output, &mut events);
+        let [ShellIntegrationEvent::Reply(reply), ShellIntegrationEvent::ImageReceived(_)] =
+            &events[..]
+        else {
+            panic!("unexpected events {events:?}");
+        };
+        assert_eq!(reply, b"\x1b_Gi=1;ENOENT:image not found\x1b\\");
+
+        // Images larger than the memory limit are rejected.
+        events.clear();
+        let pixels = BASE64.encode([255u8; 3 * 3 * 4]);
+        decoder.kitty(
+            format!("Ga=t,s=3,v=3,i=4;{pixels}").as_bytes(),
+            &mut output,
+            &mut events,
+        );
+        assert_eq!(
+            events,
+            vec![ShellIntegrationEvent::Reply(
+                b"\x1b_Gi=4;ENOMEM:image too large\x1b\\".to_vec()
+            )]
+        );
+    }
+}
@@ -0,0 +1,582 @@
+//! Support for the shell integration escape sequences.
+//!
+//! Shells set up for shell integration (e.g. with the scripts shipped by iTerm2, WezTerm or
+//! VS Code) report where their prompts and commands start and end with `OSC 133` (or VS Code's
+//! `OSC 633`) sequences, and their current working directory with `OSC 7`.
+//!
+//! Alacritty ignores these sequences, so they are intercepted before the PTY output reaches its
+//! parser. The start of every prompt is rewritten into an `OSC 8` hyperlink that spans the prompt
+//! and the command typed after it: this way, the grid keeps track of the command regions for us
+//! while they scroll, reflow and get trimmed from the scrollback.
+//! Everything else is reported as [`ShellIntegrationEvent`]s.
+//!
+//! When enabled, the Sixel and kitty graphics sequences are intercepted the same way, see
+//! [`crate::graphics`].
+
+use std::{
+    io::{self, Read},
+    path::PathBuf,
+    sync::{atomic::AtomicUsize, Arc},
+};
+
+use alacritty_terminal::{
+    event::{Event as AlacTermEvent, EventListener, OnResize, WindowSize},
+    term::cell::Hyperlink,
+    tty::{ChildEvent, EventedPty, EventedReadWrite},
+};
+use futures::channel::mpsc::UnboundedSender;
+use polling::{Event as PollingEvent, PollMode, Poller};
+
+use crate::{
+    graphics::{self, GraphicsDecoder, TerminalImage},
+    ZedListener,
+};
+
+const COMMAND_HYPERLINK_PREFIX: &str = "zed-command:";
+const COMMAND_HYPERLINK_END: &[u8] = b"\x1b]8;;\x07";
+/// Longer sequences can't be shell integration ones, don't bother buffering them.
+const MAX_SEQUENCE_LEN: usize = 4096;
+/// Larger images are passed through to alacritty, which ignores them.
+const MAX_GRAPHICS_SEQUENCE_LEN: usize = 32 * 1024 * 1024;
+/// Upper bound on how much a single read may grow while rewriting the sequences.
+const MAX_EXPANSION_FACTOR: usize = 4;
+const READ_BUFFER_SIZE: usize = 0x10000;
+
+/// A shell integration report that does not need to be anchored in the grid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShellIntegrationEvent {
+    /// The command started after the prompt with the given id has finished.
+    CommandFinished {
+        command_id: u64,
+        exit_code: Option<i32>,
+    },
+    WorkingDirectoryChanged(PathBuf),
+    /// An image was placed in the grid.
+    ImageReceived(TerminalImage),
+    /// A response to a graphics protocol request, to be written back to the PTY.
+    Reply(Vec<u8>),
+}
+
+/// Returns the id of the command, if the hyperlink marks a shell integration command region.
+pub fn command_id(hyperlink: &Hyperlink) -> Option<u64> {
+    hyperlink
+        .uri()
+        .strip_prefix(COMMAND_HYPERLINK_PREFIX)?
+        .parse()
+        .ok()
+}
+
+/// Returns the hyperlink, unless it is one of the internal command region or image markers.
+pub fn user_hyperlink(hyperlink: Option<Hyperlink>) -> Option<Hyperlink> {
+    hyperlink.filter(|hyperlink| {
+        command_id(hyperlink).is_none() && graphics::image_id(hyperlink).is_none()
+    })
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum State {
+    #[default]
+    Ground,
+    Escape,
+    Sequence,
+    SequenceEscape,
+    PassThrough,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum SequenceKind {
+    /// Operating system command, `ESC ]`
+    #[default]
+    Osc,
+    /// Device control string, `ESC P`
+    Dcs,
+    /// Application program command, `ESC _`
+    Apc,
+}
+
+impl SequenceKind {
+    fn introducer(self) -> &'static [u8] {
+        match self {
+            SequenceKind::Osc => b"\x1b]",
+            SequenceKind::Dcs => b"\x1bP",
+            SequenceKind::Apc => b"\x1b_",
+        }
+    }
+}
+
+/// Extracts the shell integration sequences out of the PTY output.
+#[derive(Debug, Default)]
+pub(crate) struct ShellIntegrationFilter {
+    state: State,
+    sequence_kind: SequenceKind,
+    sequence: Vec<u8>,
+    graphics: Option<GraphicsDecoder>,
+    next_command_id: u64,
+    current_command: Option<u64>,
+    command_executed: bool,
+    marking_command: bool,
+}
+
+impl ShellIntegrationFilter {
+    pub fn with_graphics(max_image_memory: usize, stored_image_memory: Arc<AtomicUsize>) -> Self {
+        Self {
+            graphics: Some(GraphicsDecoder::new(max_image_memory, stored_image_memory)),
+            ..Self::default()
+        }
+    }
+
+    pub fn set_cell_size(&mut self, cell_width: u16, cell_height: u16) {
+        if let Some(graphics) = &mut self.graphics {
+            graphics.set_cell_size(cell_width, cell_height);
+        }
+    }
+
+    pub fn process(
+        &mut self,
+        input: &[u8],
+        output: &mut Vec<u8>,
+        events: &mut Vec<ShellIntegrationEvent>,
+    ) {
+        let mut ix = 0;
+        while ix < input.len() {
+            let byte = input[ix];
+            match self.state {
+                State::Ground => {
+                    let end = input[ix..]
+                        .iter()
+                        .position(|&byte| byte == 0x1b)
+                        .map_or(input.len(), |position| ix + position);
+                    output.extend_from_slice(&input[ix..end]);
+                    if end < input.len() {
+                        self.state = State::Escape;
+                    }
+                    ix = end;
+                }
+                State::Escape => match byte {
+                    b']' => self.start_sequence(SequenceKind::Osc),
+                    b'P' if self.graphics.is_some() => self.start_sequence(SequenceKind::Dcs),
+                    b'_' if self.graphics.is_some() => self.start_sequence(SequenceKind::Apc),
+                    0x1b => output.push(0x1b),
+                    _ => {
+                        output.extend_from_slice(&[0x1b, byte]);
+                        self.state = State::Ground;
+                    }
+                },
+                State::Sequence => match byte {
+                    0x07 if self.sequence_kind == SequenceKind::Osc => {
+                        self.finish_sequence(output, events)
+                    }
+                    0x1b => self.state = State::SequenceEscape,
+                    _ => {
+                        self.sequence.push(byte);
+                        if !self.may_be_intercepted_sequence() {
+                            output.extend_from_slice(self.sequence_kind.introducer());
+                            output.extend_from_slice(&self.sequence);
+                            self.state = State::PassThrough;
+                        }
+                    }
+                },
+                State::SequenceEscape => {
+                    if byte == b'\\' {
+                        self.finish_sequence(output, events);
+                    } else {
+                        // An escape aborts the unterminated sequence and starts a new one.
+                        self.state = State::Escape;
+                        continue;
+                    }
+                }
+                State::PassThrough => match byte {
+                    0x1b => self.state = State::Escape,
+                    0x07 | 0x18 | 0x1a => {
+                        output.push(byte);
+                        self.state = State::Ground;
+                    }
+                    _ => output.push(byte),
+                },
+            }
+            ix += 1;
+        }
+    }
+
+    fn start_sequence(&mut self, kind: SequenceKind) {
+        self.sequence.clear();
+        self.sequence_kind = kind;
+        self.state = State::Sequence;
+    }
+
+    fn may_be_intercepted_sequence(&self) -> bool {
+        match self.sequence_kind {
+            SequenceKind::Osc => {
+                self.sequence.len() <= MAX_SEQUENCE_LEN
+                    && [b"133;".as_slice(), b"633;", b"7;"].iter().any(|prefix| {
+                        let len = prefix.len().min(self.sequence.len());
+                        prefix[..len] == self.sequence[..len]
+                    })
+            }
+            // Sixel images are the device control strings with only numeric parameters and a `q` final byte.
+            SequenceKind::Dcs => {
+                self.sequence.len() <= MAX_GRAPHICS_SEQUENCE_LEN
+                    && self
+                        .sequence
+                        .iter()
+                        .find(|byte| !byte.is_ascii_digit() && **byte != b';')
+                        .map_or(true, |byte| *byte == b'q')
+            }
+            SequenceKind::Apc => {
+                self.sequence.len() <= MAX_GRAPHICS_SEQUENCE_LEN && self.sequence[0] == b'G'
+            }
+        }
+    }
+
+    fn finish_sequence(&mut self, output: &mut Vec<u8>, events: &mut Vec<ShellIntegrationEvent>) {
+        self.state = State::Ground;
+        match (self.sequence_kind, &mut self.graphics) {
+            (SequenceKind::Osc, _) => {}
+            (SequenceKind::Dcs, Some(graphics)) => {
+                return graphics.sixel(&self.sequence, output, events);
+            }
+            (SequenceKind::Apc, Some(graphics)) => {
+                return graphics.kitty(&self.sequence, output, events);
+            }
+            (_, None) => return,
+        }
+
+        let sequence = String::from_utf8_lossy(&self.sequence).into_owned();
+        let Some((code, params)) = sequence.split_once(';') else {
+            return;
+        };
+        match code {
+            "133" | "633" => self.handle_command_mark(params, output, events),
+            "7" => {
+                if let Some(path) = path_from_file_uri(params) {
+                    events.push(ShellIntegrationEvent::WorkingDirectoryChanged(path));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_command_mark(
+        &mut self,
+        params: &str,
+        output: &mut Vec<u8>,
+        events: &mut Vec<ShellIntegrationEvent>,
+    ) {
+        let mut params = params.split(';');
+        match params.next() {
+            // Prompt start
+            Some("A") => {
+                self.end_command_marking(output);
+                let command_id = self.next_command_id;
+                self.next_command_id += 1;
+                self.current_command = Some(command_id);
+                self.command_executed = false;
+                self.marking_command = true;
+                output.extend_from_slice(
+                    format!("\x1b]8;;{COMMAND_HYPERLINK_PREFIX}{command_id}\x07").as_bytes(),
+                );
+            }
+            // Command output start
+            Some("C") => {
+                self.end_command_marking(output);
+                self.command_executed = true;
+            }
+            // Command finished
+            Some("D") => {
+                self.end_command_marking(output);
+                let exit_code = params.next().and_then(|code| code.trim().parse().ok());
+                if let Some(command_id) = self.current_command.take() {
+                    if self.command_executed {
+                        events.push(ShellIntegrationEvent::CommandFinished {
+                            command_id,
+                            exit_code,
+                        });
+                    }
+                }
+                self.command_executed = false;
+            }
+            // VS Code's property report
+            Some("P") => {
+                if let Some(cwd) = params.next().and_then(|param| param.strip_prefix("Cwd=")) {
+                    let cwd = path_from_file_uri(cwd).unwrap_or_else(|| PathBuf::from(cwd));
+                    events.push(ShellIntegrationEvent::WorkingDirectoryChanged(cwd));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn end_command_marking(&mut self, output: &mut Vec<u8>) {
+        if self.marking_command {
+            output.extend_from_slice(COMMAND_HYPERLINK_END);
+            self.marking_command = false;
+        }
+    }
+}
+
+fn path_from_file_uri(uri: &str) -> Option<PathBuf> {
+    let host_and_path = uri.strip_prefix("file://")?;
+    let path = percent_decode(&host_and_path[host_and_path.find('/')?..])?;
+    // `file://host/C:/Users` on Windows
+    if cfg!(windows) && path.get(2..3) == Some(":") {
+        return Some(PathBuf::from(&path[1..]));
+    }
+    Some(PathBuf::from(path))
+}
+
+fn percent_decode(text: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut chars = text.bytes();
+    while let Some(byte) = chars.next() {
+        if byte == b'%' {
+            let hex = [chars.next()?, chars.next()?];
+            bytes.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+        } else {
+            bytes.push(byte);
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+/// A PTY that filters the shell integration sequences out of the output of the wrapped one.
+pub(crate) struct ShellIntegrationPty<P> {
+    pty: P,
+    filter: ShellIntegrationFilter,
+    read_buffer: Box<[u8]>,
+    output: Vec<u8>,
+    output_start: usize,
+    events: Vec<ShellIntegrationEvent>,
+    events_tx: UnboundedSender<ShellIntegrationEvent>,
+    listener: ZedListener,
+}
+
+impl<P: EventedPty> ShellIntegrationPty<P> {
+    pub fn new(
+        pty: P,
+        events_tx: UnboundedSender<ShellIntegrationEvent>,
+        listener: ZedListener,
+        max_image_memory: usize,
+        stored_image_memory: Arc<AtomicUsize>,
+    ) -> Self {
+        Self {
+            pty,
+            filter: if max_image_memory > 0 {
+                ShellIntegrationFilter::with_graphics(max_image_memory, stored_image_memory)
+            } else {
+                ShellIntegrationFilter::default()
+            },
+            read_buffer: vec![0; READ_BUFFER_SIZE].into_boxed_slice(),
+            output: Vec::new(),
+            output_start: 0,
+            events: Vec::new(),
+            events_tx,
+            listener,
+        }
+    }
+}
+
+impl<P: EventedPty> Read for ShellIntegrationPty<P> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.output_start == self.output.len() {
+            self.output.clear();
+            self.output_start = 0;
+
+            // Leave enough room for the rewritten sequences, so that no output is held back
+            // after alacritty stops reading.
+            let len = (buf.len() / MAX_EXPANSION_FACTOR).clamp(1, self.read_buffer.len());
+            let read = self.pty.reader().read(&mut self.read_buffer[..len])?;
+            if read == 0 {
+                return Ok(0);
+            }
+            self.filter.process(
+                &self.read_buffer[..read],
+                &mut self.output,
+                &mut self.events,
+            );
+
+            if !self.events.is_empty() {
+                for event in self.events.drain(..) {
+                    self.events_tx.unbounded_send(event).ok();
+                }
+                self.listener.send_event(AlacTermEvent::Wakeup);
+            }
+        }
+
+        let pending = &self.output[self.output_start..];
+        let len = pending.len().min(buf.len());
+        buf[..len].copy_from_slice(&pending[..len]);
+        self.output_start += len;
+        Ok(len)
+    }
+}
+
+impl<P: EventedPty> EventedReadWrite for ShellIntegrationPty<P> {
+    type Reader = Self;
+    type Writer = P::Writer;
+
+    unsafe fn register(
+        &mut self,
+        poller: &Arc<Poller>,
+        interest: PollingEvent,
+        mode: PollMode,
+    ) -> io::Result<()> {
+        unsafe { self.pty.register(poller, interest, mode) }
+    }
+
+    fn reregister(
+        &mut self,
+        poller: &Arc<Poller>,
+        interest: PollingEvent,
+        mode: PollMode,
+    ) -> io::Result<()> {
+        self.pty.reregister(poller, interest, mode)
+    }
+
+    fn deregister(&mut self, poller: &Arc<Poller>) -> io::Result<()> {
+        self.pty.deregister(poller)
+    }
+
+    fn reader(&mut self) -> &mut Self::Reader {
+        self
+    }
+
+    fn writer(&mut self) -> &mut Self::Writer {
+        self.pty.writer()
+    }
+}
+
+impl<P: EventedPty> EventedPty for ShellIntegrationPty<P> {
+    fn next_child_event(&mut self) -> Option<ChildEvent> {
+        self.pty.next_child_event()
+    }
+}
+
+impl<P: OnResize> OnResize for ShellIntegrationPty<P> {
+    fn on_resize(&mut self, window_size: WindowSize) {
+        self.filter
+            .set_cell_size(window_size.cell_width, window_size.cell_height);
+        self.pty.on_resize(window_size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::{ShellIntegrationEvent, ShellIntegrationFilter};
+
+    fn filter(chunks: &[&[u8]]) -> (String, Vec<ShellIntegrationEvent>) {
+        let mut filter = ShellIntegrationFilter::default();
+        let mut output = Vec::new();
+        let mut events = Vec::new();
+        for chunk in chunks {
+            filter.process(chunk, &mut output, &mut events);
+        }
+        (String::from_utf8(output).unwrap(), events)
+    }
+
+    #[test]
+    fn test_other_sequences_pass_through() {
+        let input = b"plain \x1b[1;31mred\x1b[0m \x1b]0;title\x07 \x1b]8;;https://zed.dev\x1b\\link\x1b]8;;\x1b\\";
+        let (output, events) = filter(&[input]);
+        assert_eq!(output.as_bytes(), input);
+        assert!(events.is_empty());
+
+        let (output, _) = filter(&[b"\x1b]13", b"0;title\x07", b"\x1b\x1b[m"]);
+        assert_eq!(output, "\x1b]130;title\x07\x1b\x1b[m");
+    }
+
+    #[test]
+    fn test_command_marks() {
+        let (output, events) = filter(&[
+            b"\x1b]133;D\x07\x1b]133;A\x07$ \x1b]133;B\x07ls\r\n\x1b]133;C\x07",
+            b"file\r\n\x1b]133;D;2\x07",
+            b"\x1b]133;A\x07$ \x1b]133;B\x07\r\n\x1b]133;D;0\x1b\\",
+            b"\x1b]133;A\x07$ ",
+        ]);
+        assert_eq!(
+            output,
+            "\x1b]8;;zed-command:0\x07$ ls\r\n\x1b]8;;\x07file\r\n\
+             \x1b]8;;zed-command:1\x07$ \r\n\x1b]8;;\x07\
+             \x1b]8;;zed-command:2\x07$ "
+        );
+        // Prompts without executed commands are not reported.
+        assert_eq!(
+            events,
+            vec![ShellIntegrationEvent::CommandFinished {
+                command_id: 0,
+                exit_code: Some(2),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_sequences_split_across_reads() {
+        let (output, events) = filter(&[
+            b"\x1b",
+            b"]6",
+            b"33;A\x1b",
+            b"\\$ \x1b]633;C\x07\x1b]633;D;1",
+            b"27\x07",
+        ]);
+        assert_eq!(output, "\x1b]8;;zed-command:0\x07$ \x1b]8;;\x07");
+        assert_eq!(
+            events,
+            vec![ShellIntegrationEvent::CommandFinished {
+                command_id: 0,
+                exit_code: Some(127),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_graphics_sequences() {
+        let mut filter = ShellIntegrationFilter::with_graphics(1024, Default::default());
+        filter.set_cell_size(1, 2);
+        let mut output = Vec::new();
+        let mut events = Vec::new();
+        for chunk in [
+            b"a\x1bPq#0;2;0;0;100~~-~\x1b\\".as_slice(),
+            b"\x1bP$qm\x1b\\\x1b_Ga=q,i=1;AAAA\x1b\\\x1b_Lother\x1b\\b",
+        ] {
+            filter.process(chunk, &mut output, &mut events);
+        }
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "a\x1b]8;;zed-image:0\x07 \x1b]8;;\x07\x08\n\n\n\n\n\n\
+             \x1bP$qm\x1b\\\x1b_Lother\x1b\\b"
+        );
+        let [ShellIntegrationEvent::ImageReceived(image), ShellIntegrationEvent::Reply(reply)] =
+            &events[..]
+        else {
+            panic!("unexpected events {events:?}");
+        };
+        assert_eq!((image.width, image.height, image.lines), (2, 12, 6));
+        assert_eq!(reply, b"\x1b_Gi=1;EINVAL:invalid image size\x1b\\");
+
+        // Without graphics, the sequences are left to alacritty.
+        let input = b"\x1bPq~\x1b\\\x1b_Ga=T;AAAA\x1b\\";
+        let (output, events) = filter(&[input]);
+        assert_eq!(output.as_bytes(), input);
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn test_working_directory() {
+        let (output, events) = filter(&[
+            b"\x1b]7;file://host/home/user/my%20project\x07",
+            b"\x1b]633;P;Cwd=/tmp\x07",
+        ]);
+        assert_eq!(output, "");
+        assert_eq!(
+            events,
+            vec![
+                ShellIntegrationEvent::WorkingDirectoryChanged(PathBuf::from(
+                    "/home/user/my project"
+                )),
+                ShellIntegrationEvent::WorkingDirectoryChanged(PathBuf::from("/tmp")),
+            ]
+        );
+    }
+}
@@ -1,8 +1,10 @@
+pub mod graphics;
 pub mod mappings;
 
 pub use alacritty_terminal;
 
 mod pty_info;
+pub mod shell_integration;
 pub mod terminal_settings;
 
 use alacritty_terminal::{
@@ -13,21 +15,24 @@ use alacritty_terminal::{
     selection::{Selection, SelectionRange, SelectionType},
     sync::FairMutex,
     term::{
-        cell::Cell,
+        cell::{Cell, Flags},
         search::{Match, RegexIter, RegexSearch},
         Config, RenderableCursor, TermMode,
     },
     tty::{self},
     vi_mode::{ViModeCursor, ViMotion},
     vte::ansi::{
-        ClearMode, CursorStyle as AlacCursorStyle, Handler, NamedPrivateMode, PrivateMode,
+        Attr, ClearMode, CursorStyle as AlacCursorStyle, Handler, NamedPrivateMode, PrivateMode,
     },
     Term,
 };
 use anyhow::{bail, Result};
 
 use futures::{
-    channel::mpsc::{unbounded, UnboundedReceiver, UnboundedSender},
+    channel::{
+        mpsc::{unbounded, UnboundedReceiver, UnboundedSender},
+        oneshot,
+    },
     FutureExt,
 };
 
@@ -36,13 +41,15 @@ use mappings::mouse::{
     scroll_report,
 };
 
-use collections::{HashMap, VecDeque};
+use collections::{BTreeMap, HashMap, VecDeque};
 use futures::StreamExt;
+use graphics::TerminalImage;
 use pty_info::PtyProcessInfo;
 use serde::{Deserialize, Serialize};
 use settings::Settings;
+use shell_integration::{user_hyperlink, ShellIntegrationEvent, ShellIntegrationPty};
 use smol::channel::{Receiver, Sender};
-use task::{HideStrategy, Shell, TaskId};
+use task::{BackgroundMatcher, HideStrategy, Shell, TaskId};
 use terminal_settings::{AlternateScroll, CursorShape, TerminalSettings};
 use theme::{ActiveTheme, Theme};
 use util::{paths::home_dir, truncate_and_trailoff};
@@ -52,7 +59,10 @@ use std::{
     fmt::Display,
     ops::{Deref, Index, RangeInclusive},
     path::PathBuf,
-    sync::Arc,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
     time::Duration,
 };
 use thiserror::Error;
@@ -79,6 +89,9 @@ actions!(
         ScrollPageDown,
         ScrollToTop,
         ScrollToBottom,
+        ScrollToPreviousPrompt,
+        ScrollToNextPrompt,
+        CopyLastCommandOutput,
         ToggleViMode,
     ]
 );
@@ -91,6 +104,11 @@ const SCROLL_MULTIPLIER: f32 = 4.;
 #[cfg(not(target_os = "macos"))]
 const SCROLL_MULTIPLIER: f32 = 1.;
 const MAX_SEARCH_LINES: usize = 100;
+/// How many of the last finished shell commands to remember the exit codes for.
+const MAX_FINISHED_COMMANDS: usize = 1000;
+/// The primary device attributes reported by alacritty, and the ones with Sixel support added.
+const DEVICE_ATTRIBUTES: &str = "\x1b[?6c";
+const DEVICE_ATTRIBUTES_WITH_SIXEL: &str = "\x1b[?62;4c";
 const DEBUG_TERMINAL_WIDTH: Pixels = px(500.);
 const DEBUG_TERMINAL_HEIGHT: Pixels = px(30.);
 const DEBUG_CELL_WIDTH: Pixels = px(5.);
@@ -108,6 +126,7 @@ pub enum Event {
     SelectionsChanged,
     NewNavigationTarget(Option<MaybeNavigationTarget>),
     Open(MaybeNavigationTarget),
+    BackgroundTaskStatusChanged,
 }
 
 #[derive(Clone, Debug)]
@@ -136,6 +155,8 @@ enum InternalEvent {
     // FocusNextMatch,
     Scroll(AlacScroll),
     ScrollToAlacPoint(AlacPoint),
+    ScrollToPrompt(AlacDirection),
+    CopyLastCommandOutput,
     SetSelection(Option<(Selection, AlacPoint)>),
     UpdateSelection(Point<Pixels>),
     // Adjusted mouse position, should open
@@ -331,6 +352,7 @@ impl TerminalBuilder {
         cursor_shape: CursorShape,
         alternate_scroll: AlternateScroll,
         max_scroll_history_lines: Option<usize>,
+        restored_scrollback: Option<String>,
         is_ssh_terminal: bool,
         window: AnyWindowHandle,
         completion_tx: Sender<()>,
@@ -416,6 +438,11 @@ impl TerminalBuilder {
             term.unset_private_mode(PrivateMode::Named(NamedPrivateMode::AlternateScroll));
         }
 
+        // Replay before the PTY is spawned, so the new shell's output always comes after it.
+        if let Some(scrollback) = restored_scrollback {
+            replay_scrollback(&mut term, &scrollback);
+        }
+
         let term = Arc::new(FairMutex::new(term));
 
         //Setup the pty...
@@ -435,6 +462,18 @@ impl TerminalBuilder {
         };
 
         let pty_info = PtyProcessInfo::new(&pty);
+        let max_image_memory = TerminalSettings::get_global(cx)
+            .max_image_memory_mb
+            .saturating_mul(1024 * 1024);
+        let stored_image_memory = Arc::<AtomicUsize>::default();
+        let (shell_integration_tx, shell_integration_rx) = unbounded();
+        let pty = ShellIntegrationPty::new(
+            pty,
+            shell_integration_tx,
+            ZedListener(events_tx.clone()),
+            max_image_memory,
+            stored_image_memory.clone(),
+        );
 
         //And connect them together
         let event_loop = EventLoop::new(
@@ -474,6 +513,13 @@ impl TerminalBuilder {
             vi_mode_enabled: false,
             is_ssh_terminal,
             python_venv_directory,
+            shell_integration_rx,
+            finished_commands: BTreeMap::default(),
+            shell_working_directory: None,
+            images: BTreeMap::default(),
+            image_memory: 0,
+            stored_image_memory,
+            max_image_memory,
         };
 
         Ok(TerminalBuilder {
@@ -572,6 +618,30 @@ pub struct TerminalContent {
     pub cursor_char: char,
     pub size: TerminalSize,
     pub last_hovered_word: Option<HoveredWord>,
+    pub command_marks: Vec<CommandMark>,
+    pub images: Vec<ImagePlacement>,
+}
+
+/// An image anchored at a grid cell, which may be above the visible lines.
+#[derive(Clone, Debug)]
+pub struct ImagePlacement {
+    pub point: AlacPoint,
+    pub image: TerminalImage,
+}
+
+/// The first line of a prompt reported by the shell integration, within the visible lines.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommandMark {
+    pub line: Line,
+    pub status: CommandStatus,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CommandStatus {
+    /// The shell is waiting for a command to be entered, or is running it.
+    Running,
+    /// The command has finished, the exit code is absent if the shell did not report it.
+    Finished { exit_code: Option<i32> },
 }
 
 #[derive(Clone)]
@@ -596,6 +666,8 @@ impl Default for TerminalContent {
             cursor_char: Default::default(),
             size: Default::default(),
             last_hovered_word: None,
+            command_marks: Vec::new(),
+            images: Vec::new(),
         }
     }
 }
@@ -633,6 +705,15 @@ pub struct Terminal {
     task: Option<TaskState>,
     vi_mode_enabled: bool,
     is_ssh_terminal: bool,
+    shell_integration_rx: UnboundedReceiver<ShellIntegrationEvent>,
+    finished_commands: BTreeMap<u64, Option<i32>>,
+    shell_working_directory: Option<PathBuf>,
+    /// Images received from the PTY, by id. The oldest ones are dropped first.
+    images: BTreeMap<u64, TerminalImage>,
+    image_memory: usize,
+    /// The memory taken by the kitty images stored to be placed later.
+    stored_image_memory: Arc<AtomicUsize>,
+    max_image_memory: usize,
 }
 
 pub struct TaskState {
@@ -645,6 +726,65 @@ pub struct TaskState {
     pub hide: HideStrategy,
     pub show_summary: bool,
     pub show_command: bool,
+    pub background: Option<BackgroundTaskState>,
+}
+
+impl TaskState {
+    /// Whether the task keeps running in the background and is ready.
+    pub fn is_background_ready(&self) -> bool {
+        self.status == TaskStatus::Running
+            && self
+                .background
+                .as_ref()
+                .is_some_and(|background| background.status == BackgroundTaskStatus::Ready)
+    }
+
+    /// Whether spawning the task again should keep this instance running instead of
+    /// restarting it. A background task never finishes, so a dependent waiting for it to
+    /// rerun would hang, while an explicit rerun is meant to restart it.
+    pub fn keeps_running_when_spawned(&self, spawned_as_dependency: bool) -> bool {
+        spawned_as_dependency && self.status == TaskStatus::Running && self.background.is_some()
+    }
+}
+
+/// Whether a background task is ready, as detected from its output.
+pub struct BackgroundTaskState {
+    matcher: BackgroundMatcher,
+    status: BackgroundTaskStatus,
+    ready_waiters: Vec<oneshot::Sender<()>>,
+    /// Where the output was last scanned up to, to only scan the new output on each wakeup.
+    scanned_history_size: usize,
+    scanned_line: Line,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackgroundTaskStatus {
+    /// The task has not been ready yet.
+    Starting,
+    /// The task is ready.
+    Ready,
+    /// The task was ready, but started rebuilding.
+    Rebuilding,
+}
+
+impl BackgroundTaskState {
+    pub fn new(matcher: BackgroundMatcher) -> Self {
+        Self {
+            matcher,
+            status: BackgroundTaskStatus::Starting,
+            ready_waiters: Vec::new(),
+            scanned_history_size: 0,
+            scanned_line: Line(0),
+        }
+    }
+
+    pub fn status(&self) -> BackgroundTaskStatus {
+        self.status
+    }
+
+    pub fn matcher(&self) -> &BackgroundMatcher {
+        &self.matcher
+    }
 }
 
 /// A status of the current terminal tab's task.
@@ -696,7 +836,13 @@ impl Terminal {
                     },
                 )
             }
-            AlacTermEvent::PtyWrite(out) => self.write_to_pty(out.clone()),
+            AlacTermEvent::PtyWrite(out) => {
+                if self.max_image_memory > 0 && out.as_str() == DEVICE_ATTRIBUTES {
+                    self.write_to_pty(DEVICE_ATTRIBUTES_WITH_SIXEL.to_string())
+                } else {
+                    self.write_to_pty(out.clone())
+                }
+            }
             AlacTermEvent::TextAreaSizeRequest(format) => {
                 self.write_to_pty(format(self.last_content.size.into()))
             }
@@ -714,8 +860,10 @@ impl Terminal {
             }
             AlacTermEvent::Wakeup => {
                 cx.emit(Event::Wakeup);
+                self.update_background_task_status(cx);
 
-                if self.pty_info.has_changed() {
+                let working_directory_changed = self.process_shell_integration_events();
+                if self.pty_info.has_changed() || working_directory_changed {
                     cx.emit(Event::TitleChanged);
                 }
             }
@@ -739,6 +887,44 @@ impl Terminal {
         }
     }
 
+    /// Returns whether the shell reported a new working directory.
+    fn process_shell_integration_events(&mut self) -> bool {
+        let mut working_directory_changed = false;
+        while let Ok(Some(event)) = self.shell_integration_rx.try_next() {
+            match event {
+                ShellIntegrationEvent::CommandFinished {
+                    command_id,
+                    exit_code,
+                } => {
+                    self.finished_commands.insert(command_id, exit_code);
+                    if self.finished_commands.len() > MAX_FINISHED_COMMANDS {
+                        self.finished_commands.pop_first();
+                    }
+                }
+                ShellIntegrationEvent::WorkingDirectoryChanged(working_directory) => {
+                    if self.shell_working_directory.as_ref() != Some(&working_directory) {
+                        self.shell_working_directory = Some(working_directory);
+                        working_directory_changed = true;
+                    }
+                }
+                ShellIntegrationEvent::ImageReceived(image) => {
+                    self.image_memory += image.memory_size();
+                    self.images.insert(image.id, image);
+                    // The images stored to be placed later count toward the same limit.
+                    let stored_image_memory = self.stored_image_memory.load(Ordering::Relaxed);
+                    while self.image_memory + stored_image_memory > self.max_image_memory {
+                        let Some((_, image)) = self.images.pop_first() else {
+                            break;
+                        };
+                        self.image_memory -= image.memory_size();
+                    }
+                }
+                ShellIntegrationEvent::Reply(reply) => self.write_bytes_to_pty(reply),
+            }
+        }
+        working_directory_changed
+    }
+
     pub fn selection_started(&self) -> bool {
         self.selection_phase == SelectionPhase::Selecting
     }
@@ -876,6 +1062,20 @@ impl Terminal {
                 term.scroll_to_point(*point);
                 self.refresh_hovered_word();
             }
+            InternalEvent::ScrollToPrompt(direction) => {
+                if let Some(line) = find_command_start(term, *direction) {
+                    let display_offset = (-line.0).clamp(0, term.history_size() as i32);
+                    term.scroll_display(AlacScroll::Delta(
+                        display_offset - term.grid().display_offset() as i32,
+                    ));
+                    self.refresh_hovered_word();
+                }
+            }
+            InternalEvent::CopyLastCommandOutput => {
+                if let Some(output) = last_command_output(term, &self.finished_commands) {
+                    cx.write_to_clipboard(ClipboardItem::new_string(output))
+                }
+            }
             InternalEvent::ToggleViMode => {
                 self.vi_mode_enabled = !self.vi_mode_enabled;
                 term.toggle_vi_mode();
@@ -893,13 +1093,13 @@ impl Terminal {
                 )
                 .grid_clamp(term, Boundary::Grid);
 
-                let link = term.grid().index(point).hyperlink();
+                let link = user_hyperlink(term.grid().index(point).hyperlink());
                 let found_word = if link.is_some() {
                     let mut min_index = point;
                     loop {
                         let new_min_index = min_index.sub(term, Boundary::Cursor, 1);
                         if new_min_index == min_index
-                            || term.grid().index(new_min_index).hyperlink() != link
+                            || user_hyperlink(term.grid().index(new_min_index).hyperlink()) != link
                         {
                             break;
                         } else {
@@ -911,7 +1111,7 @@ impl Terminal {
                     loop {
                         let new_max_index = max_index.add(term, Boundary::Cursor, 1);
                         if new_max_index == max_index
-                            || term.grid().index(new_max_index).hyperlink() != link
+                            || user_hyperlink(term.grid().index(new_max_index).hyperlink()) != link
                         {
                             break;
                         } else {
@@ -1134,6 +1334,23 @@ impl Terminal {
             .push_back(InternalEvent::Scroll(AlacScroll::Bottom));
     }
 
+    /// Scrolls to the closest prompt above the viewport, reported by the shell integration.
+    pub fn scroll_to_previous_prompt(&mut self) {
+        self.events
+            .push_back(InternalEvent::ScrollToPrompt(AlacDirection::Left));
+    }
+
+    /// Scrolls to the closest prompt below the top of the viewport, reported by the shell integration.
+    pub fn scroll_to_next_prompt(&mut self) {
+        self.events
+            .push_back(InternalEvent::ScrollToPrompt(AlacDirection::Right));
+    }
+
+    /// Copies the output of the last finished command, reported by the shell integration.
+    pub fn copy_last_command_output(&mut self) {
+        self.events.push_back(InternalEvent::CopyLastCommandOutput);
+    }
+
     ///Resize the terminal and the PTY.
     pub fn set_size(&mut self, new_size: TerminalSize) {
         if self.last_content.size != new_size {
@@ -1306,11 +1523,33 @@ impl Terminal {
             self.process_terminal_event(&e, &mut terminal, cx)
         }
 
-        self.last_content = Self::make_content(&terminal, &self.last_content);
+        self.last_content = Self::make_content(
+            &terminal,
+            &self.last_content,
+            &self.finished_commands,
+            &self.images,
+        );
     }
 
-    fn make_content(term: &Term<ZedListener>, last_content: &TerminalContent) -> TerminalContent {
+    fn make_content(
+        term: &Term<ZedListener>,
+        last_content: &TerminalContent,
+        finished_commands: &BTreeMap<u64, Option<i32>>,
+        images: &BTreeMap<u64, TerminalImage>,
+    ) -> TerminalContent {
         let content = term.renderable_content();
+        let viewport_start = Line(-(content.display_offset as i32));
+        let command_marks = (0..term.screen_lines())
+            .map(|line| viewport_start + line)
+            .filter_map(|line| {
+                let command_id = command_start_at(term, line)?;
+                let status = match finished_commands.get(&command_id) {
+                    Some(&exit_code) => CommandStatus::Finished { exit_code },
+                    None => CommandStatus::Running,
+                };
+                Some(CommandMark { line, status })
+            })
+            .collect();
         TerminalContent {
             cells: content
                 .display_iter
@@ -1334,6 +1573,8 @@ impl Terminal {
             cursor_char: term.grid()[content.cursor.point].c,
             size: last_content.size,
             last_hovered_word: last_content.last_hovered_word.clone(),
+            command_marks,
+            images: visible_images(term, viewport_start, images),
         }
     }
 
@@ -1551,7 +1792,9 @@ impl Terminal {
             //Hyperlinks
             if self.selection_phase == SelectionPhase::Ended {
                 let mouse_cell_index = content_index_for_mouse(position, &self.last_content.size);
-                if let Some(link) = self.last_content.cells[mouse_cell_index].hyperlink() {
+                if let Some(link) =
+                    user_hyperlink(self.last_content.cells[mouse_cell_index].hyperlink())
+                {
                     cx.open_url(link.uri());
                 } else if self.secondary_pressed {
                     self.events
@@ -1628,21 +1871,47 @@ impl Terminal {
         }
     }
 
+    /// Searches the whole grid, including the scrollback, for matches of `searcher`.
+    ///
+    /// When `whole_word` is set, matches that are adjacent to word characters are skipped.
     pub fn find_matches(
         &self,
         mut searcher: RegexSearch,
+        whole_word: bool,
         cx: &Context<Self>,
     ) -> Task<Vec<RangeInclusive<AlacPoint>>> {
         let term = self.term.clone();
         cx.background_executor().spawn(async move {
             let term = term.lock();
 
-            all_search_matches(&term, &mut searcher).collect()
+            all_search_matches(&term, &mut searcher)
+                .filter(|search_match| !whole_word || is_whole_word_match(&term, search_match))
+                .collect()
         })
     }
 
+    /// Returns the text of the scrollback and the screen, with soft-wrapped lines joined.
+    ///
+    /// Returns `None` while the alternate screen is active, as its content is not meaningful outside of the running program.
+    pub fn scrollback_text(&self) -> Option<String> {
+        let term = self.term.lock();
+        if term.mode().contains(TermMode::ALT_SCREEN) {
+            return None;
+        }
+
+        let grid = term.grid();
+        let start = AlacPoint::new(grid.topmost_line(), Column(0));
+        let end = AlacPoint::new(grid.bottommost_line(), grid.last_column());
+        let mut text = term.bounds_to_string(start, end);
+        text.truncate(text.trim_end().len());
+        Some(text)
+    }
+
     pub fn working_directory(&self) -> Option<PathBuf> {
-        if self.is_ssh_terminal {
+        if let Some(working_directory) = &self.shell_working_directory {
+            // Reported by the shell itself, so this is correct for the SSH terminals too.
+            Some(working_directory.clone())
+        } else if self.is_ssh_terminal {
             // We can't yet reliably detect the working directory of a shell on the
             // SSH host. Until we can do that, it doesn't make sense to display
             // the working directory on the client and persist that.
@@ -1735,6 +2004,77 @@ impl Terminal {
         Task::ready(())
     }
 
+    /// Waits until the task finishes or, for a background task, until it's ready.
+    pub fn wait_for_ready_task(&mut self, cx: &App) -> Task<()> {
+        if let Some(task) = &mut self.task {
+            if task.is_background_ready() {
+                return Task::ready(());
+            }
+            if let Some(background) = task.background.as_mut() {
+                if task.status == TaskStatus::Running {
+                    let (ready_tx, ready_rx) = oneshot::channel();
+                    background.ready_waiters.push(ready_tx);
+                    // The waiters are dropped when the task finishes, which resolves the receiver too.
+                    return cx.spawn(|_| async move {
+                        ready_rx.await.ok();
+                    });
+                }
+            }
+        }
+        self.wait_for_completed_task(cx)
+    }
+
+    /// Detects whether the background task became ready or started rebuilding,
+    /// by scanning the output printed since the previous wakeup.
+    fn update_background_task_status(&mut self, cx: &mut Context<Self>) {
+        let Some(background) = self
+            .task
+            .as_mut()
+            .filter(|task| task.status == TaskStatus::Running)
+            .and_then(|task| task.background.as_mut())
+        else {
+            return;
+        };
+
+        let term = self.term.lock();
+        if term.mode().contains(TermMode::ALT_SCREEN) {
+            return;
+        }
+        let grid = term.grid();
+        let history_size = grid.history_size();
+        let cursor_line = grid.cursor.point.line;
+        let scrolled_lines = history_size.saturating_sub(background.scanned_history_size) as i32;
+        // Always scan the whole screen, as the lines scrolled out of a full scrollback can't be counted.
+        let start_line = (background.scanned_line - scrolled_lines)
+            .min(Line(0))
+            .max(grid.topmost_line());
+        let output = term.bounds_to_string(
+            AlacPoint::new(start_line, Column(0)),
+            AlacPoint::new(cursor_line, grid.last_column()),
+        );
+        drop(term);
+        background.scanned_history_size = history_size;
+        background.scanned_line = cursor_line;
+
+        let status = match background.matcher.readiness(&output) {
+            None => return,
+            Some(true) => BackgroundTaskStatus::Ready,
+            Some(false) if background.status == BackgroundTaskStatus::Starting => {
+                BackgroundTaskStatus::Starting
+            }
+            Some(false) => BackgroundTaskStatus::Rebuilding,
+        };
+        if status != background.status {
+            background.status = status;
+            if status == BackgroundTaskStatus::Ready {
+                for waiter in background.ready_waiters.drain(..) {
+                    waiter.send(()).ok();
+                }
+            }
+            cx.emit(Event::BackgroundTaskStatusChanged);
+        }
+    }
+
     fn register_task_finished(&mut self, error_code: Option<i32>, cx: &mut Context<'_, Terminal>) {
         self.completion_tx.try_send(()).ok();
         let task = match &mut self.task {
@@ -1749,6 +2089,10 @@ impl Terminal {
         if task.status != TaskStatus::Running {
             return;
         }
+        if let Some(background) = &mut task.background {
+            background.ready_waiters.clear();
+            cx.emit(Event::BackgroundTaskStatusChanged);
+        }
         match error_code {
             Some(error_code) => {
                 task.status.register_task_exit(error_code);
@@ -1885,6 +2229,96 @@ pub fn visible_regex_match_iter<'a, T>(
         .take_while(move |rm| rm.start().line <= viewport_end)
 }
 
+/// Returns the id of the first shell integration command region on the line.
+fn line_command_id<T>(term: &Term<T>, line: Line) -> Option<u64> {
+    term.grid()[line]
+        .into_iter()
+        .find_map(|cell| shell_integration::command_id(&cell.hyperlink()?))
+}
+
+/// Returns the id of the command, if its prompt starts on the line.
+fn command_start_at<T>(term: &Term<T>, line: Line) -> Option<u64> {
+    let command_id = line_command_id(term, line)?;
+    if line > term.topmost_line() && line_command_id(term, line - 1) == Some(command_id) {
+        None
+    } else {
+        Some(command_id)
+    }
+}
+
+/// Finds the images that overlap the viewport, including the ones anchored above it.
+fn visible_images<T>(
+    term: &Term<T>,
+    viewport_start: Line,
+    images: &BTreeMap<u64, TerminalImage>,
+) -> Vec<ImagePlacement> {
+    let Some(max_lines) = images.values().map(|image| image.lines).max() else {
+        return Vec::new();
+    };
+    let first_line = (viewport_start - max_lines).max(term.topmost_line());
+    let last_line = (viewport_start + term.screen_lines() - 1).min(term.bottommost_line());
+    let mut placements = Vec::new();
+    for line in first_line.0..=last_line.0 {
+        let line = Line(line);
+        for column in 0..term.columns() {
+            let point = AlacPoint::new(line, Column(column));
+            let Some(image) = term.grid()[point]
+                .hyperlink()
+                .and_then(|hyperlink| images.get(&graphics::image_id(&hyperlink)?))
+            else {
+                continue;
+            };
+            if line + image.lines > viewport_start {
+                placements.push(ImagePlacement {
+                    point,
+                    image: image.clone(),
+                });
+            }
+        }
+    }
+    placements
+}
+
+/// Finds the first line of the closest prompt before or after the top of the viewport.
+fn find_command_start<T>(term: &Term<T>, direction: AlacDirection) -> Option<Line> {
+    let viewport_start = Line(-(term.grid().display_offset() as i32));
+    let is_command_start = |line: &i32| command_start_at(term, Line(*line)).is_some();
+    let line = match direction {
+        AlacDirection::Left => (term.topmost_line().0..viewport_start.0)
+            .rev()
+            .find(is_command_start),
+        AlacDirection::Right => {
+            (viewport_start.0 + 1..=term.bottommost_line().0).find(is_command_start)
+        }
+    };
+    line.map(Line)
+}
+
+/// Returns the text printed between the last finished command and the next prompt.
+fn last_command_output<T>(
+    term: &Term<T>,
+    finished_commands: &BTreeMap<u64, Option<i32>>,
+) -> Option<String> {
+    let mut output_end = term.bottommost_line();
+    let mut line = term.bottommost_line();
+    while line >= term.topmost_line() {
+        if let Some(command_id) = line_command_id(term, line) {
+            if finished_commands.contains_key(&command_id) {
+                if line >= output_end {
+                    return None;
+                }
+                let start = AlacPoint::new(line + 1, Column(0));
+                let end = AlacPoint::new(output_end, term.last_column());
+                let output = term.bounds_to_string(start, end);
+                return Some(output.trim_end().to_string());
+            }
+            output_end = line - 1;
+        }
+        line -= 1;
+    }
+    None
+}
+
 fn make_selection(range: &RangeInclusive<AlacPoint>) -> Selection {
     let mut selection = Selection::new(SelectionType::Simple, *range.start(), AlacDirection::Left);
     selection.update(*range.end(), AlacDirection::Right);
@@ -1900,6 +2334,59 @@ fn all_search_matches<'a, T>(
     RegexIter::new(start, end, AlacDirection::Right, term, regex)
 }
 
+const RESTORED_SCROLLBACK_SEPARATOR: &str = "──── Restored session ────";
+
+/// Writes the scrollback of a previous session into the grid, dimmed to set it apart from the new output.
+fn replay_scrollback<T: EventListener>(term: &mut Term<T>, scrollback: &str) {
+    term.terminal_attribute(Attr::Dim);
+    for line in scrollback.lines() {
+        for c in line.chars().filter(|c| !c.is_control()) {
+            term.input(c);
+        }
+        term.carriage_return();
+        term.linefeed();
+    }
+    for c in RESTORED_SCROLLBACK_SEPARATOR.chars() {
+        term.input(c);
+    }
+    term.terminal_attribute(Attr::Reset);
+    term.carriage_return();
+    term.linefeed();
+}
+
+/// Checks that the match is not surrounded by word characters, following soft-wrapped lines.
+fn is_whole_word_match<T>(term: &Term<T>, search_match: &Match) -> bool {
+    let grid = term.grid();
+    let is_word_char = |point: AlacPoint| {
+        let c = grid[point].c;
+        c.is_alphanumeric() || c == '_'
+    };
+
+    let start = *search_match.start();
+    let before = if start.column > Column(0) {
+        Some(AlacPoint::new(start.line, start.column - 1))
+    } else if start.line > grid.topmost_line()
+        && grid[start.line - 1][grid.last_column()]
+            .flags
+            .contains(Flags::WRAPLINE)
+    {
+        Some(AlacPoint::new(start.line - 1, grid.last_column()))
+    } else {
+        None
+    };
+
+    let end = *search_match.end();
+    let after = if end.column < grid.last_column() {
+        Some(AlacPoint::new(end.line, end.column + 1))
+    } else if end.line < grid.bottommost_line() && grid[end].flags.contains(Flags::WRAPLINE) {
+        Some(AlacPoint::new(end.line + 1, Column(0)))
+    } else {
+        None
+    };
+
+    !before.is_some_and(is_word_char) && !after.is_some_and(is_word_char)
+}
+
 fn content_index_for_mouse(pos: Point<Pixels>, size: &TerminalSize) -> usize {
     let col = (pos.x / size.cell_width()).round() as usize;
     let clamped_col = min(col, size.columns() - 1);
@@ -1995,14 +2482,27 @@ pub fn rgba_color(r: u8, g: u8, b: u8) -> Hsla {
 #[cfg(test)]
 mod tests {
     use alacritty_terminal::{
-        index::{Column, Line, Point as AlacPoint},
-        term::cell::Cell,
+        event::VoidListener,
+        grid::Scroll,
+        index::{Column, Direction, Line, Point as AlacPoint},
+        term::{
+            cell::{Cell, Flags},
+            search::RegexSearch,
+            Config,
+        },
+        vte::ansi::{Handler, Processor},
+        Term,
     };
-    use gpui::{point, size, Pixels};
+    use collections::BTreeMap;
+    use gpui::{point, px, size, Pixels};
     use rand::{distributions::Alphanumeric, rngs::ThreadRng, thread_rng, Rng};
 
     use crate::{
-        content_index_for_mouse, rgb_for_index, IndexedCell, TerminalContent, TerminalSize,
+        all_search_matches, content_index_for_mouse, find_command_start, is_whole_word_match,
+        last_command_output, replay_scrollback, rgb_for_index,
+        shell_integration::{ShellIntegrationEvent, ShellIntegrationFilter},
+        BackgroundTaskState, BackgroundTaskStatus, IndexedCell, TaskState, TaskStatus,
+        TerminalContent, TerminalSize, RESTORED_SCROLLBACK_SEPARATOR,
     };
 
     #[test]
@@ -2180,4 +2680,236 @@ mod tests {
             vec!["Main.cs:20:5:Error", "desc"],
         );
     }
+
+    fn term_with_lines(lines: &[&str]) -> Term<VoidListener> {
+        let size = TerminalSize::new(px(10.), px(5.), size(px(100.), px(50.)));
+        let mut term = Term::new(Config::default(), &size, VoidListener);
+        for (ix, line) in lines.iter().enumerate() {
+            if ix > 0 {
+                term.carriage_return();
+                term.linefeed();
+            }
+            for c in line.chars() {
+                term.input(c);
+            }
+        }
+        term
+    }
+
+    /// Feeds shell output through the shell integration filter into a terminal of 5 lines,
+    /// returning the terminal along with the exit codes of the finished commands.
+    fn term_with_shell_output(output: &[u8]) -> (Term<VoidListener>, BTreeMap<u64, Option<i32>>) {
+        let mut term = term_with_lines(&[]);
+        let mut filter = ShellIntegrationFilter::default();
+        let mut filtered = Vec::new();
+        let mut events = Vec::new();
+        filter.process(output, &mut filtered, &mut events);
+        let mut processor: Processor = Processor::new();
+        processor.advance(&mut term, &filtered);
+
+        let finished_commands = events
+            .into_iter()
+            .filter_map(|event| match event {
+                ShellIntegrationEvent::CommandFinished {
+                    command_id,
+                    exit_code,
+                } => Some((command_id, exit_code)),
+                _ => None,
+            })
+            .collect();
+        (term, finished_commands)
+    }
+
+    #[test]
+    fn test_find_command_start() {
+        // The first prompt ends up in the scrollback, above the 5 visible lines.
+        let (mut term, _) = term_with_shell_output(
+            b"\x1b]133;A\x07$ \x1b]133;B\x07ls\r\n\x1b]133;C\x07a\r\nb\r\n\x1b]133;D;0\x07\
+              \x1b]633;A\x07$ \x1b]633;B\x07cat\r\n\x1b]633;C\x07c\r\nd\r\ne\r\n\x1b]633;D;1\x07\
+              \x1b]133;A\x07$ ",
+        );
+        assert_eq!(find_command_start(&term, Direction::Left), Some(Line(-3)));
+        assert_eq!(find_command_start(&term, Direction::Right), Some(Line(4)));
+
+        term.scroll_display(Scroll::Delta(2));
+        assert_eq!(find_command_start(&term, Direction::Left), Some(Line(-3)));
+        assert_eq!(find_command_start(&term, Direction::Right), Some(Line(0)));
+
+        term.scroll_display(Scroll::Top);
+        assert_eq!(find_command_start(&term, Direction::Left), None);
+        assert_eq!(find_command_start(&term, Direction::Right), Some(Line(0)));
+
+        // Lines without prompt marks have no commands to scroll to.
+        let term = term_with_lines(&["$ ls", "a", "$ "]);
+        assert_eq!(find_command_start(&term, Direction::Left), None);
+        assert_eq!(find_command_start(&term, Direction::Right), None);
+    }
+
+    #[test]
+    fn test_last_command_output() {
+        let (term, finished_commands) = term_with_shell_output(
+            b"\x1b]133;A\x07$ \x1b]133;B\x07ls\r\n\x1b]133;C\x07a\r\nb\r\n\x1b]133;D;0\x07\
+              \x1b]633;A\x07$ \x1b]633;B\x07cat\r\n\x1b]633;C\x07c\r\nd\r\n\x1b]633;D;1\x07\
+              \x1b]133;A\x07$ ",
+        );
+        assert_eq!(
+            finished_commands,
+            BTreeMap::from_iter([(0, Some(0)), (1, Some(1))])
+        );
+        assert_eq!(
+            last_command_output(&term, &finished_commands).as_deref(),
+            Some("c\nd")
+        );
+
+        // The output of a running command isn't complete yet, the one before it is picked.
+        let (term, finished_commands) = term_with_shell_output(
+            b"\x1b]133;A\x07$ \x1b]133;B\x07ls\r\n\x1b]133;C\x07a\r\n\x1b]133;D;0\x07\
+              \x1b]133;A\x07$ \x1b]133;B\x07sleep\r\n\x1b]133;C\x07zz",
+        );
+        assert_eq!(
+            last_command_output(&term, &finished_commands).as_deref(),
+            Some("a")
+        );
+
+        // A command that printed nothing has no output.
+        let (term, finished_commands) = term_with_shell_output(
+            b"\x1b]133;A\x07$ \x1b]133;B\x07true\r\n\x1b]133;C\x07\x1b]133;D;0\x07\
+              \x1b]133;A\x07$ ",
+        );
+        assert_eq!(last_command_output(&term, &finished_commands), None);
+
+        let (term, finished_commands) = term_with_shell_output(b"$ ls\r\na\r\n$ ");
+        assert!(finished_commands.is_empty());
+        assert_eq!(last_command_output(&term, &finished_commands), None);
+    }
+
+    #[test]
+    fn test_replay_scrollback() {
+        let mut term = term_with_lines(&[]);
+        replay_scrollback(&mut term, "$ echo hi\nhi\n");
+
+        let grid = term.grid();
+        let text = term.bounds_to_string(
+            AlacPoint::new(grid.topmost_line(), Column(0)),
+            AlacPoint::new(grid.bottommost_line(), grid.last_column()),
+        );
+        assert_eq!(
+            text.trim_end(),
+            format!("$ echo hi\nhi\n{RESTORED_SCROLLBACK_SEPARATOR}")
+        );
+        assert!(grid[Line(0)][Column(0)].flags.contains(Flags::DIM));
+        assert_eq!(term.grid().cursor.point.column, Column(0));
+        assert!(!term.grid().cursor.template.flags.contains(Flags::DIM));
+    }
+
+    fn search_starts(
+        term: &Term<VoidListener>,
+        regex: &str,
+        whole_word: bool,
+    ) -> Vec<(i32, usize)> {
+        let mut regex = RegexSearch::new(regex).unwrap();
+        all_search_matches(term, &mut regex)
+            .filter(|search_match| !whole_word || is_whole_word_match(term, search_match))
+            .map(|search_match| (search_match.start().line.0, search_match.start().column.0))
+            .collect()
+    }
+
+    #[test]
+    fn test_search_whole_word() {
+        let term = term_with_lines(&["foo foobar", "barfoo foo_bar foo"]);
+
+        assert_eq!(
+            search_starts(&term, "foo", false),
+            vec![(0, 0), (0, 4), (1, 3), (1, 7), (1, 15)]
+        );
+        assert_eq!(search_starts(&term, "foo", true), vec![(0, 0), (1, 15)]);
+    }
+
+    #[test]
+    fn test_search_whole_word_across_wrapped_lines() {
+        // The terminal is 20 columns wide, so "foobar" is soft-wrapped after "foo".
+        let term = term_with_lines(&["xxxxxxxxxxxxxxxx foobar", "foo"]);
+
+        assert_eq!(search_starts(&term, "foo", false), vec![(0, 17), (2, 0)]);
+        assert_eq!(search_starts(&term, "foo", true), vec![(2, 0)]);
+    }
+
+    #[test]
+    fn test_search_case_flags() {
+        let term = term_with_lines(&["Foo foo"]);
+
+        assert_eq!(search_starts(&term, "(?i)foo", false), vec![(0, 0), (0, 4)]);
+        assert_eq!(search_starts(&term, "(?-i)foo", false), vec![(0, 4)]);
+    }
+
+    #[test]
+    fn test_rerun_background_task_readiness() {
+        let matcher = task::BackgroundTask {
+            begins_pattern: Some("^Rebuilding".to_string()),
+            ends_pattern: "^Watching".to_string(),
+        }
+        .matcher()
+        .unwrap();
+        let mut task = TaskState {
+            id: task::TaskId("watch".to_string()),
+            full_label: "watch".to_string(),
+            label: "watch".to_string(),
+            command_label: "watch".to_string(),
+            status: TaskStatus::Running,
+            completion_rx: smol::channel::unbounded().1,
+            hide: Default::default(),
+            show_summary: true,
+            show_command: true,
+            background: Some(BackgroundTaskState::new(matcher)),
+        };
+        assert!(!task.is_background_ready());
+
+        // A dependency that is already running and ready doesn't need to be waited for when rerunning.
+        task.background.as_mut().unwrap().status = BackgroundTaskStatus::Ready;
+        assert!(task.is_background_ready());
+
+        task.background.as_mut().unwrap().status = BackgroundTaskStatus::Rebuilding;
+        assert!(!task.is_background_ready());
+
+        task.background.as_mut().unwrap().status = BackgroundTaskStatus::Ready;
+        task.status = TaskStatus::Completed { success: true };
+        assert!(!task.is_background_ready());
+
+        task.status = TaskStatus::Running;
+        task.background = None;
+        assert!(!task.is_background_ready());
+    }
+
+    #[test]
+    fn test_respawn_running_background_task() {
+        let matcher = task::BackgroundTask {
+            begins_pattern: None,
+            ends_pattern: "^Watching".to_string(),
+        }
+        .matcher()
+        .unwrap();
+        let mut task = TaskState {
+            id: task::TaskId("watch".to_string()),
+            full_label: "watch".to_string(),
+            label: "watch".to_string(),
+            command_label: "watch".to_string(),
+            status: TaskStatus::Running,
+            completion_rx: smol::channel::unbounded().1,
+            hide: Default::default(),
+            show_summary: true,
+            show_command: true,
+            background: Some(BackgroundTaskState::new(matcher)),
+        };
+
+        // Dependents reuse the running watcher, while rerunning it explicitly restarts it.
+        assert!(task.keeps_running_when_spawned(true));
+        assert!(!task.keeps_running_when_spawned(false));
+
+        task.status = TaskStatus::Completed { success: true };
+        assert!(!task.keeps_running_when_spawned(true));
+
+        task.status = TaskStatus::Running;
+        task.background = None;
+        assert!(!task.keeps_running_when_spawned(true));
+    }
 }
@@ -48,6 +48,8 @@ pub struct TerminalSettings {
     pub max_scroll_history_lines: Option<usize>,
     pub toolbar: Toolbar,
     pub scrollbar: ScrollbarSettings,
+    pub restore_scrollback: bool,
+    pub max_image_memory_mb: usize,
 }
 
 #[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
@@ -223,6 +225,17 @@ pub struct TerminalSettingsContent {
     pub toolbar: Option<ToolbarContent>,
     /// Scrollbar-related settings
     pub scrollbar: Option<ScrollbarSettingsContent>,
+    /// Whether to save the scrollback of terminals when Zed quits and replay it
+    /// when the workspace is restored.
+    ///
+    /// Default: false
+    pub restore_scrollback: Option<bool>,
+    /// The maximum memory, in megabytes, used by the Sixel and kitty graphics protocol
+    /// images of a terminal. The oldest images are dropped first when it is exceeded.
+    /// Set to 0 to disable image support.
+    ///
+    /// Default: 256
+    pub max_image_memory_mb: Option<usize>,
 }
 
 impl settings::Settings for TerminalSettings {
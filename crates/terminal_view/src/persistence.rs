@@ -5,7 +5,9 @@ use futures::{stream::FuturesUnordered, StreamExt as _};
 use gpui::{AppContext as _, AsyncWindowContext, Axis, Entity, Task, WeakEntity};
 use project::{terminals::TerminalKind, Project};
 use serde::{Deserialize, Serialize};
+use settings::Settings as _;
 use std::path::{Path, PathBuf};
+use terminal::terminal_settings::TerminalSettings;
 use ui::{App, Context, Pixels, Window};
 use util::ResultExt as _;
 
@@ -61,7 +63,10 @@ fn serialize_pane(pane: &Entity<Pane>, active: bool, cx: &mut App) -> Serialized
         .items()
         .filter_map(|item| {
             let terminal_view = item.act_as::<TerminalView>(cx)?;
-            if terminal_view.read(cx).terminal().read(cx).task().is_some() {
+            // Task terminals are only worth restoring together with their output.
+            if terminal_view.read(cx).terminal().read(cx).task().is_some()
+                && !TerminalSettings::get_global(cx).restore_scrollback
+            {
                 None
             } else {
                 let id = item.item_id().as_u64();
@@ -403,6 +408,10 @@ define_connection! {
             DROP TABLE terminals;
 
             ALTER TABLE terminals2 RENAME TO terminals;
+        ),
+        sql!(
+            ALTER TABLE terminals ADD COLUMN scrollback TEXT;
+            ALTER TABLE terminals ADD COLUMN task_label TEXT;
         )];
 }
 
@@ -425,8 +434,35 @@ impl TerminalDb {
             workspace_id: WorkspaceId,
             working_directory: PathBuf
         ) -> Result<()> {
-            INSERT OR REPLACE INTO terminals(item_id, workspace_id, working_directory)
+            INSERT INTO terminals(item_id, workspace_id, working_directory)
             VALUES (?, ?, ?)
+            ON CONFLICT(workspace_id, item_id) DO UPDATE SET
+                working_directory = excluded.working_directory
+        }
+    }
+
+    query! {
+        pub async fn save_scrollback(
+            item_id: ItemId,
+            workspace_id: WorkspaceId,
+            working_directory: Option<PathBuf>,
+            scrollback: Option<String>,
+            task_label: Option<String>
+        ) -> Result<()> {
+            INSERT INTO terminals(item_id, workspace_id, working_directory, scrollback, task_label)
+            VALUES (?, ?, ?, ?, ?)
+            ON CONFLICT(workspace_id, item_id) DO UPDATE SET
+                working_directory = COALESCE(excluded.working_directory, working_directory),
+                scrollback = excluded.scrollback,
+                task_label = excluded.task_label
+        }
+    }
+
+    query! {
+        pub fn get_scrollback(item_id: ItemId, workspace_id: WorkspaceId) -> Result<Option<(Option<String>, Option<String>)>> {
+            SELECT scrollback, task_label
+            FROM terminals
+            WHERE item_id = ? AND workspace_id = ?
         }
     }
 
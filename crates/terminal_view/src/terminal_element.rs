@@ -1,11 +1,11 @@
 use editor::{CursorLayout, HighlightedRange, HighlightedRangeLine};
 use gpui::{
     div, fill, point, px, relative, size, AnyElement, App, AvailableSpace, Bounds, ContentMask,
-    Context, DispatchPhase, Element, ElementId, Entity, FocusHandle, Font, FontStyle, FontWeight,
-    GlobalElementId, HighlightStyle, Hitbox, Hsla, InputHandler, InteractiveElement, Interactivity,
-    IntoElement, LayoutId, ModifiersChangedEvent, MouseButton, MouseMoveEvent, Pixels, Point,
-    ShapedLine, StatefulInteractiveElement, StrikethroughStyle, Styled, TextRun, TextStyle,
-    UTF16Selection, UnderlineStyle, WeakEntity, WhiteSpace, Window, WindowTextSystem,
+    Context, Corners, DispatchPhase, Element, ElementId, Entity, FocusHandle, Font, FontStyle,
+    FontWeight, GlobalElementId, HighlightStyle, Hitbox, Hsla, InputHandler, InteractiveElement,
+    Interactivity, IntoElement, LayoutId, ModifiersChangedEvent, MouseButton, MouseMoveEvent,
+    Pixels, Point, ShapedLine, StatefulInteractiveElement, StrikethroughStyle, Styled, TextRun,
+    TextStyle, UTF16Selection, UnderlineStyle, WeakEntity, WhiteSpace, Window, WindowTextSystem,
 };
 use itertools::Itertools;
 use language::CursorShape;
@@ -20,11 +20,14 @@ use terminal::{
             CursorShape as AlacCursorShape, NamedColor,
         },
     },
+    shell_integration::user_hyperlink,
     terminal_settings::TerminalSettings,
-    HoveredWord, IndexedCell, Terminal, TerminalContent, TerminalSize,
+    CommandStatus, HoveredWord, ImagePlacement, IndexedCell, Terminal, TerminalContent,
+    TerminalSize,
 };
 use theme::{ActiveTheme, Theme, ThemeSettings};
 use ui::{ParentElement, Tooltip};
+use util::ResultExt as _;
 use workspace::Workspace;
 
 use std::mem;
@@ -38,6 +41,9 @@ pub struct LayoutState {
     cells: Vec<LayoutCell>,
     rects: Vec<LayoutRect>,
     relative_highlighted_ranges: Vec<(RangeInclusive<AlacPoint>, Hsla)>,
+    /// Finished shell commands, as display lines of their prompts and the color of their gutter marker.
+    command_marks: Vec<(i32, Hsla)>,
+    images: Vec<ImagePlacement>,
     cursor: Option<CursorLayout>,
     background_color: Hsla,
     dimensions: TerminalSize,
@@ -357,7 +363,7 @@ impl TerminalElement {
         }
 
         let underline = (flags.intersects(Flags::ALL_UNDERLINES)
-            || indexed.cell.hyperlink().is_some())
+            || user_hyperlink(indexed.cell.hyperlink()).is_some())
         .then(|| UnderlineStyle {
             color: Some(fg),
             thickness: Pixels::from(1.0),
@@ -708,8 +714,6 @@ impl Element for TerminalElement {
                     TerminalSize::new(line_height, cell_width, size)
                 };
 
-                let search_matches = self.terminal.read(cx).matches.clone();
-
                 let background_color = theme.colors().terminal_background;
 
                 let last_hovered_word = self.terminal.update(cx, |terminal, cx| {
@@ -736,6 +740,7 @@ impl Element for TerminalElement {
                     element
                 });
 
+                let terminal = self.terminal.read(cx);
                 let TerminalContent {
                     cells,
                     mode,
@@ -743,15 +748,39 @@ impl Element for TerminalElement {
                     cursor_char,
                     selection,
                     cursor,
+                    command_marks,
+                    images,
                     ..
-                } = &self.terminal.read(cx).last_content;
+                } = &terminal.last_content;
                 let mode = *mode;
                 let display_offset = *display_offset;
 
+                // Matches cover the whole scrollback, only the visible ones need to be laid out.
+                let viewport_top = -(display_offset as i32);
+                let viewport_bottom = viewport_top + dimensions.num_lines() as i32;
+                let search_matches = terminal.matches.iter().filter(|search_match| {
+                    search_match.end().line.0 >= viewport_top
+                        && search_match.start().line.0 <= viewport_bottom
+                });
+
+                let status_colors = theme.status();
+                let command_marks = command_marks
+                    .iter()
+                    .filter_map(|mark| {
+                        let color = match mark.status {
+                            CommandStatus::Running => return None,
+                            CommandStatus::Finished { exit_code: Some(0) } => status_colors.success,
+                            CommandStatus::Finished { exit_code: Some(_) } => status_colors.error,
+                            CommandStatus::Finished { exit_code: None } => status_colors.hint,
+                        };
+                        Some((mark.line.0 + display_offset as i32, color))
+                    })
+                    .collect();
+
                 // searches, highlights to a single range representations
                 let mut relative_highlighted_ranges = Vec::new();
                 for search_match in search_matches {
-                    relative_highlighted_ranges.push((search_match, match_color))
+                    relative_highlighted_ranges.push((search_match.clone(), match_color))
                 }
                 if let Some(selection) = selection {
                     relative_highlighted_ranges
@@ -862,6 +891,8 @@ impl Element for TerminalElement {
                     dimensions,
                     rects,
                     relative_highlighted_ranges,
+                    command_marks,
+                    images: images.clone(),
                     mode,
                     display_offset,
                     hyperlink_tooltip,
@@ -937,6 +968,18 @@ impl Element for TerminalElement {
                         rect.paint(origin, &layout.dimensions, window);
                     }
 
+                    let line_height = layout.dimensions.line_height;
+                    for (line, color) in &layout.command_marks {
+                        let marker_origin = point(
+                            origin.x - layout.gutter * 0.75,
+                            origin.y + line_height * *line as f32,
+                        );
+                        window.paint_quad(fill(
+                            Bounds::new(marker_origin, size(layout.gutter / 3., line_height)),
+                            *color,
+                        ));
+                    }
+
                     for (relative_highlighted_range, color) in
                         layout.relative_highlighted_ranges.iter()
                     {
@@ -958,6 +1001,30 @@ impl Element for TerminalElement {
                         cell.paint(origin, &layout.dimensions, bounds, window, cx);
                     }
 
+                    for placement in &layout.images {
+                        let image_origin = point(
+                            origin.x
+                                + layout.dimensions.cell_width * placement.point.column.0 as f32,
+                            origin.y
+                                + line_height
+                                    * (placement.point.line.0 + layout.display_offset as i32)
+                                        as f32,
+                        );
+                        let image_size = size(
+                            px(placement.image.width as f32),
+                            px(placement.image.height as f32),
+                        );
+                        window
+                            .paint_image(
+                                Bounds::new(image_origin, image_size),
+                                Corners::default(),
+                                placement.image.image.clone(),
+                                0,
+                                false,
+                            )
+                            .log_err();
+                    }
+
                     if self.cursor_visible {
                         if let Some(mut cursor) = cursor {
                             cursor.paint(origin, window, cx);
@@ -1093,7 +1160,7 @@ pub fn is_blank(cell: &IndexedCell) -> bool {
         return false;
     }
 
-    if cell.hyperlink().is_some() {
+    if user_hyperlink(cell.hyperlink()).is_some() {
         return false;
     }
 
@@ -1,4 +1,4 @@
-use std::{cmp, ops::ControlFlow, path::PathBuf, sync::Arc, time::Duration};
+use std::{cmp, future::Future, ops::ControlFlow, path::PathBuf, sync::Arc, time::Duration};
 
 use crate::{
     default_working_directory,
@@ -10,11 +10,14 @@ use crate::{
 use breadcrumbs::Breadcrumbs;
 use collections::HashMap;
 use db::kvp::KEY_VALUE_STORE;
-use futures::future::join_all;
+use futures::{
+    future::{join_all, Shared},
+    FutureExt as _,
+};
 use gpui::{
     actions, Action, AnyView, App, AsyncApp, AsyncWindowContext, Context, Corner, Entity,
     EventEmitter, ExternalPaths, FocusHandle, Focusable, IntoElement, ParentElement, Pixels,
-    Render, Styled, Task, WeakEntity, Window,
+    Render, Styled, Subscription, Task, WeakEntity, Window,
 };
 use itertools::Itertools;
 use project::{terminals::TerminalKind, Fs, Project, ProjectEntryId};
@@ -23,7 +26,7 @@ use settings::Settings;
 use task::{RevealStrategy, RevealTarget, ShellBuilder, SpawnInTerminal, TaskId};
 use terminal::{
     terminal_settings::{TerminalDockPosition, TerminalSettings},
-    Terminal,
+    TaskStatus, Terminal,
 };
 use ui::{
     prelude::*, ButtonCommon, Clickable, ContextMenu, FluentBuilder, PopoverMenu, Toggleable,
@@ -46,6 +49,8 @@ use zed_actions::assistant::InlineAssist;
 
 const TERMINAL_PANEL_KEY: &str = "TerminalPanel";
 
+type DeferredSpawn = Shared<Task<Result<Entity<Terminal>, Arc<anyhow::Error>>>>;
+
 actions!(terminal_panel, [ToggleFocus]);
 
 pub fn init(cx: &mut App) {
@@ -72,10 +77,13 @@ pub struct TerminalPanel {
     pub(crate) height: Option<Pixels>,
     pending_serialization: Task<Option<()>>,
     pending_terminals_to_add: usize,
-    deferred_tasks: HashMap<TaskId, Task<()>>,
+    /// The spawns waiting for the previous runs of their task to finish, shared by every
+    /// caller spawning the same task in the meantime.
+    deferred_tasks: HashMap<TaskId, DeferredSpawn>,
     assistant_enabled: bool,
     assistant_tab_bar_button: Option<AnyView>,
     active: bool,
+    _subscriptions: Vec<Subscription>,
 }
 
 impl TerminalPanel {
@@ -83,6 +91,16 @@ impl TerminalPanel {
         let project = workspace.project();
         let pane = new_terminal_pane(workspace.weak_handle(), project.clone(), false, window, cx);
         let center = PaneGroup::new(pane.clone());
+        // Split sizes are changed by dragging the pane group handles, which does not emit any
+        // pane events, so flush the layout once more before quitting.
+        let _subscriptions = vec![cx.on_app_quit(|terminal_panel, cx| {
+            let serialization = terminal_panel.write_serialized_panel(cx);
+            async move {
+                if let Some(serialization) = serialization {
+                    serialization.await.log_err();
+                }
+            }
+        })];
         let terminal_panel = Self {
             center,
             active_pane: pane,
@@ -96,6 +114,7 @@ impl TerminalPanel {
             assistant_enabled: false,
             assistant_tab_bar_button: None,
             active: false,
+            _subscriptions,
         };
         terminal_panel.apply_tab_bar_buttons(&terminal_panel.active_pane, cx);
         terminal_panel
@@ -221,15 +240,14 @@ impl TerminalPanel {
         workspace: WeakEntity<Workspace>,
         mut cx: AsyncWindowContext,
     ) -> Result<Entity<Self>> {
+        let database_id = workspace.update(&mut cx, |workspace, _| {
+            workspace.database_id().map(i64::from)
+        })?;
         let serialized_panel = cx
             .background_executor()
-            .spawn(async move { KEY_VALUE_STORE.read_kvp(TERMINAL_PANEL_KEY) })
+            .spawn(async move { read_serialized_panel(database_id) })
             .await
             .log_err()
-            .flatten()
-            .map(|panel| serde_json::from_str::<SerializedTerminalPanel>(&panel))
-            .transpose()
-            .log_err()
             .flatten();
 
         let terminal_panel = workspace
@@ -254,9 +272,32 @@ impl TerminalPanel {
                     cx.subscribe_in(&workspace, window, |terminal_panel, _, e, window, cx| {
                         if let workspace::Event::SpawnTask {
                             action: spawn_in_terminal,
+                            completion_tx,
                         } = e
                         {
-                            terminal_panel.spawn_task(spawn_in_terminal, window, cx);
+                            let spawn = terminal_panel.spawn_task(
+                                spawn_in_terminal,
+                                completion_tx.is_some(),
+                                window,
+                                cx,
+                            );
+                            match completion_tx.clone() {
+                                Some(completion_tx) => cx
+                                    .spawn(|_, mut cx| async move {
+                                        let success = match spawn.await {
+                                            Ok(terminal) => {
+                                                wait_for_task_success(terminal, &mut cx).await
+                                            }
+                                            Err(e) => {
+                                                log::error!("Failed to spawn task: {e:#}");
+                                                false
+                                            }
+                                        };
+                                        completion_tx.unbounded_send(success).ok();
+                                    })
+                                    .detach(),
+                                None => spawn.detach_and_log_err(cx),
+                            }
                         };
                     })
                     .detach();
@@ -331,6 +372,7 @@ impl TerminalPanel {
                     {
                         focus_on_pane.focus_handle(cx).focus(window);
                     }
+                    self.serialize(cx);
                 }
             }
             pane::Event::ZoomIn => {
@@ -367,6 +409,7 @@ impl TerminalPanel {
                 let direction = *direction;
                 self.center.split(&pane, &new_pane, direction).log_err();
                 window.focus(&new_pane.focus_handle(cx));
+                self.serialize(cx);
             }
             pane::Event::Focus => {
                 self.active_pane = pane.clone();
@@ -405,7 +448,13 @@ impl TerminalPanel {
         let window_handle = window.window_handle();
         let terminal = project
             .update(cx, |project, cx| {
-                project.create_terminal_with_venv(kind, python_venv_directory, window_handle, cx)
+                project.create_terminal_with_venv(
+                    kind,
+                    python_venv_directory,
+                    None,
+                    window_handle,
+                    cx,
+                )
             })
             .ok()?;
 
@@ -456,12 +505,21 @@ impl TerminalPanel {
             .detach_and_log_err(cx);
     }
 
-    fn spawn_task(&mut self, task: &SpawnInTerminal, window: &mut Window, cx: &mut Context<Self>) {
-        let Ok(is_local) = self
+    /// Spawns the task, or reruns it in its existing terminal. When `spawned_as_dependency`
+    /// is set, a background task that is still running is kept instead of being restarted.
+    fn spawn_task(
+        &mut self,
+        task: &SpawnInTerminal,
+        spawned_as_dependency: bool,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Entity<Terminal>>> {
+        let is_local = match self
             .workspace
             .update(cx, |workspace, cx| workspace.project().read(cx).is_local())
-        else {
-            return;
+        {
+            Ok(is_local) => is_local,
+            Err(e) => return Task::ready(Err(e)),
         };
 
         let builder = ShellBuilder::new(is_local, &task.shell);
@@ -476,58 +534,78 @@ impl TerminalPanel {
         };
 
         if task.allow_concurrent_runs && task.use_new_terminal {
-            self.spawn_in_new_terminal(task, window, cx)
-                .detach_and_log_err(cx);
-            return;
+            return self.spawn_in_new_terminal(task, window, cx);
         }
 
         let mut terminals_for_task = self.terminals_for_task(&task.full_label, cx);
         let Some(existing) = terminals_for_task.pop() else {
-            self.spawn_in_new_terminal(task, window, cx)
-                .detach_and_log_err(cx);
-            return;
+            return self.spawn_in_new_terminal(task, window, cx);
         };
 
         let (existing_item_index, task_pane, existing_terminal) = existing;
+        let keeps_running = existing_terminal
+            .read(cx)
+            .terminal()
+            .read(cx)
+            .task()
+            .is_some_and(|task| task.keeps_running_when_spawned(spawned_as_dependency));
+        if keeps_running {
+            return Task::ready(Ok(existing_terminal.read(cx).terminal().clone()));
+        }
         if task.allow_concurrent_runs {
-            self.replace_terminal(
+            let replace = self.replace_terminal(
                 task,
                 task_pane,
                 existing_item_index,
                 existing_terminal,
                 window,
                 cx,
-            )
-            .detach();
-            return;
+            );
+            return cx
+                .spawn(|_, _| async move { replace.await.context("replacing the task terminal") });
         }
 
-        self.deferred_tasks.insert(
-            task.id.clone(),
-            cx.spawn_in(window, |terminal_panel, mut cx| async move {
-                wait_for_terminals_tasks(terminals_for_task, &mut cx).await;
-                let task = terminal_panel.update_in(&mut cx, |terminal_panel, window, cx| {
-                    if task.use_new_terminal {
-                        terminal_panel
-                            .spawn_in_new_terminal(task, window, cx)
-                            .detach_and_log_err(cx);
-                        None
-                    } else {
-                        Some(terminal_panel.replace_terminal(
-                            task,
-                            task_pane,
-                            existing_item_index,
-                            existing_terminal,
-                            window,
-                            cx,
-                        ))
-                    }
-                });
-                if let Ok(Some(task)) = task {
-                    task.await;
-                }
-            }),
-        );
+        let pending_spawn = self
+            .deferred_tasks
+            .get(&task.id)
+            .filter(|deferred| deferred.peek().is_none())
+            .cloned();
+        let deferred = match pending_spawn {
+            Some(deferred) => deferred,
+            None => {
+                let task_id = task.id.clone();
+                let deferred = cx
+                    .spawn_in(window, |terminal_panel, mut cx| async move {
+                        wait_for_terminals_tasks(terminals_for_task, &mut cx).await;
+                        let spawn =
+                            terminal_panel.update_in(&mut cx, |terminal_panel, window, cx| {
+                                if task.use_new_terminal {
+                                    terminal_panel.spawn_in_new_terminal(task, window, cx)
+                                } else {
+                                    let replace = terminal_panel.replace_terminal(
+                                        task,
+                                        task_pane,
+                                        existing_item_index,
+                                        existing_terminal,
+                                        window,
+                                        cx,
+                                    );
+                                    cx.spawn(|_, _| async move {
+                                        replace.await.context("replacing the task terminal")
+                                    })
+                                }
+                            });
+                        match spawn {
+                            Ok(spawn) => spawn.await.map_err(Arc::new),
+                            Err(error) => Err(Arc::new(error)),
+                        }
+                    })
+                    .shared();
+                self.deferred_tasks.insert(task_id, deferred.clone());
+                deferred
+            }
+        };
+        cx.spawn(|_, _| async move { deferred.await.map_err(|error| anyhow!("{error:#}")) })
     }
 
     pub fn spawn_in_new_terminal(
@@ -726,45 +804,51 @@ impl TerminalPanel {
     }
 
     fn serialize(&mut self, cx: &mut Context<Self>) {
-        let height = self.height;
-        let width = self.width;
         self.pending_serialization = cx.spawn(|terminal_panel, mut cx| async move {
             cx.background_executor()
                 .timer(Duration::from_millis(50))
                 .await;
             let terminal_panel = terminal_panel.upgrade()?;
-            let items = terminal_panel
+            let serialization = terminal_panel
                 .update(&mut cx, |terminal_panel, cx| {
-                    SerializedItems::WithSplits(serialize_pane_group(
-                        &terminal_panel.center,
-                        &terminal_panel.active_pane,
-                        cx,
-                    ))
+                    terminal_panel.write_serialized_panel(cx)
                 })
-                .ok()?;
+                .ok()??;
             cx.background_executor()
-                .spawn(
-                    async move {
-                        KEY_VALUE_STORE
-                            .write_kvp(
-                                TERMINAL_PANEL_KEY.into(),
-                                serde_json::to_string(&SerializedTerminalPanel {
-                                    items,
-                                    active_item_id: None,
-                                    height,
-                                    width,
-                                })?,
-                            )
-                            .await?;
-                        anyhow::Ok(())
-                    }
-                    .log_err(),
-                )
+                .spawn(serialization.log_err())
                 .await;
             Some(())
         });
     }
 
+    fn write_serialized_panel(
+        &self,
+        cx: &mut App,
+    ) -> Option<impl Future<Output = Result<()>> + 'static> {
+        let database_id = self
+            .workspace
+            .upgrade()?
+            .read(cx)
+            .database_id()
+            .map(i64::from);
+        let serialization_key = serialization_key(database_id);
+        let serialized_panel = SerializedTerminalPanel {
+            items: SerializedItems::WithSplits(serialize_pane_group(
+                &self.center,
+                &self.active_pane,
+                cx,
+            )),
+            active_item_id: None,
+            height: self.height,
+            width: self.width,
+        };
+        Some(async move {
+            KEY_VALUE_STORE
+                .write_kvp(serialization_key, serde_json::to_string(&serialized_panel)?)
+                .await
+        })
+    }
+
     fn replace_terminal(
         &self,
         spawn_task: SpawnInTerminal,
@@ -773,7 +857,7 @@ impl TerminalPanel {
         terminal_to_replace: Entity<TerminalView>,
         window: &mut Window,
         cx: &mut Context<Self>,
-    ) -> Task<Option<()>> {
+    ) -> Task<Option<Entity<Terminal>>> {
         let reveal = spawn_task.reveal;
         let reveal_target = spawn_task.reveal_target;
         let window_handle = window.window_handle();
@@ -796,7 +880,7 @@ impl TerminalPanel {
                 .log_err()?;
             terminal_to_replace
                 .update_in(&mut cx, |terminal_to_replace, window, cx| {
-                    terminal_to_replace.set_terminal(new_terminal, window, cx);
+                    terminal_to_replace.set_terminal(new_terminal.clone(), window, cx);
                 })
                 .ok()?;
 
@@ -872,7 +956,7 @@ impl TerminalPanel {
                 RevealStrategy::Never => {}
             }
 
-            Some(())
+            Some(new_terminal)
         })
     }
 
@@ -1075,6 +1159,45 @@ pub fn new_terminal_pane(
     pane
 }
 
+/// The key the panel layout is stored under: one per workspace, or the key shared by all
+/// workspaces when the workspace isn't stored in the database.
+fn serialization_key(database_id: Option<i64>) -> String {
+    match database_id {
+        Some(database_id) => format!("{TERMINAL_PANEL_KEY}-{database_id}"),
+        None => TERMINAL_PANEL_KEY.to_string(),
+    }
+}
+
+fn read_serialized_panel(database_id: Option<i64>) -> Result<Option<SerializedTerminalPanel>> {
+    let mut serialized_panel = KEY_VALUE_STORE.read_kvp(&serialization_key(database_id))?;
+    if serialized_panel.is_none() && database_id.is_some() {
+        // Fall back to the layout stored before it became per-workspace.
+        serialized_panel = KEY_VALUE_STORE.read_kvp(TERMINAL_PANEL_KEY)?;
+    }
+    serialized_panel
+        .map(|panel| serde_json::from_str::<SerializedTerminalPanel>(&panel))
+        .transpose()
+        .context("deserializing the terminal panel")
+}
+
+/// Waits for the task running in the terminal to finish, and returns whether it exited successfully.
+/// Background tasks never finish, so they succeed once they are ready instead.
+async fn wait_for_task_success(terminal: Entity<Terminal>, cx: &mut AsyncApp) -> bool {
+    let Ok(readiness) = terminal.update(cx, |terminal, cx| terminal.wait_for_ready_task(cx)) else {
+        return false;
+    };
+    readiness.await;
+    terminal
+        .read_with(cx, |terminal, _| {
+            terminal.task().is_some_and(|task| match task.status {
+                TaskStatus::Completed { success } => success,
+                TaskStatus::Running => task.is_background_ready(),
+                TaskStatus::Unknown => false,
+            })
+        })
+        .unwrap_or(false)
+}
+
 async fn wait_for_terminals_tasks(
     terminals_for_task: Vec<(usize, Entity<Pane>, Entity<TerminalView>)>,
     cx: &mut AsyncApp,
@@ -1084,7 +1207,7 @@ async fn wait_for_terminals_tasks(
             .update(cx, |terminal_view, cx| {
                 terminal_view
                     .terminal()
-                    .update(cx, |terminal, cx| terminal.wait_for_completed_task(cx))
+                    .update(cx, |terminal, cx| terminal.wait_for_ready_task(cx))
             })
             .ok()
     });
@@ -1218,6 +1341,7 @@ impl Render for TerminalPanel {
                             .cloned()
                         {
                             terminal_panel.center.swap(&terminal_panel.active_pane, &to);
+                            terminal_panel.serialize(cx);
                             cx.notify();
                         }
                     }),
@@ -1419,3 +1543,74 @@ impl Render for InlineAssistTabBarButton {
             })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persistence::{SerializedAxis, SerializedPane, SerializedPaneGroup};
+    use gpui::Axis;
+
+    fn write_panel(key: String, items: Vec<Vec<u64>>) {
+        let panes = items
+            .into_iter()
+            .map(|children| {
+                SerializedPaneGroup::Pane(SerializedPane {
+                    active: false,
+                    active_item: children.first().copied(),
+                    children,
+                })
+            })
+            .collect::<Vec<_>>();
+        let items = match <[_; 1]>::try_from(panes) {
+            Ok([pane]) => pane,
+            Err(panes) => SerializedPaneGroup::Group {
+                axis: SerializedAxis(Axis::Horizontal),
+                flexes: None,
+                children: panes,
+            },
+        };
+        let panel = SerializedTerminalPanel {
+            items: SerializedItems::WithSplits(items),
+            active_item_id: None,
+            width: None,
+            height: None,
+        };
+        smol::block_on(KEY_VALUE_STORE.write_kvp(key, serde_json::to_string(&panel).unwrap()))
+            .unwrap();
+    }
+
+    fn read_panes(database_id: Option<i64>) -> Option<Vec<Vec<u64>>> {
+        let panel = read_serialized_panel(database_id).unwrap()?;
+        let SerializedItems::WithSplits(items) = panel.items else {
+            panic!("expected a layout with splits");
+        };
+        Some(match items {
+            SerializedPaneGroup::Pane(pane) => vec![pane.children],
+            SerializedPaneGroup::Group { children, .. } => children
+                .into_iter()
+                .map(|child| match child {
+                    SerializedPaneGroup::Pane(pane) => pane.children,
+                    SerializedPaneGroup::Group { .. } => panic!("expected a pane"),
+                })
+                .collect(),
+        })
+    }
+
+    #[test]
+    fn test_serialized_panel_per_workspace() {
+        // Workspaces that were never stored fall back to the shared key, and write to it.
+        assert_eq!(serialization_key(None), TERMINAL_PANEL_KEY);
+        write_panel(serialization_key(None), vec![vec![1]]);
+        assert_eq!(read_panes(None), Some(vec![vec![1]]));
+
+        // Each stored workspace keeps its own split layout.
+        write_panel(serialization_key(Some(7001)), vec![vec![2], vec![3, 4]]);
+        write_panel(serialization_key(Some(7002)), vec![vec![5]]);
+        assert_eq!(read_panes(Some(7001)), Some(vec![vec![2], vec![3, 4]]));
+        assert_eq!(read_panes(Some(7002)), Some(vec![vec![5]]));
+
+        // A workspace without a layout of its own uses the one stored before layouts were
+        // per workspace.
+        assert_eq!(read_panes(Some(7003)), Some(vec![vec![1]]));
+    }
+}
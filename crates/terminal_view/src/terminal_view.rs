@@ -21,9 +21,10 @@ use terminal::{
         term::{search::RegexSearch, TermMode},
     },
     terminal_settings::{self, CursorShape, TerminalBlink, TerminalSettings, WorkingDirectory},
-    Clear, Copy, Event, MaybeNavigationTarget, Paste, ScrollLineDown, ScrollLineUp, ScrollPageDown,
-    ScrollPageUp, ScrollToBottom, ScrollToTop, ShowCharacterPalette, TaskStatus, Terminal,
-    TerminalSize, ToggleViMode,
+    BackgroundTaskStatus, Clear, Copy, CopyLastCommandOutput, Event, MaybeNavigationTarget, Paste,
+    ScrollLineDown, ScrollLineUp, ScrollPageDown, ScrollPageUp, ScrollToBottom, ScrollToNextPrompt,
+    ScrollToPreviousPrompt, ScrollToTop, ShowCharacterPalette, TaskStatus, Terminal, TerminalSize,
+    ToggleViMode,
 };
 use terminal_element::{is_blank, TerminalElement};
 use terminal_panel::TerminalPanel;
@@ -127,6 +128,8 @@ pub struct TerminalView {
     scroll_handle: TerminalScrollHandle,
     show_scrollbar: bool,
     hide_scrollbar_task: Option<Task<()>>,
+    /// The label of the task whose output was restored into this terminal from a previous session.
+    restored_task_label: Option<String>,
     _subscriptions: Vec<Subscription>,
     _terminal_subscriptions: Vec<Subscription>,
 }
@@ -209,10 +212,12 @@ impl TerminalView {
             scroll_handle,
             show_scrollbar: !Self::should_autohide_scrollbar(cx),
             hide_scrollbar_task: None,
+            restored_task_label: None,
             _subscriptions: vec![
                 focus_in,
                 focus_out,
                 cx.observe_global::<SettingsStore>(Self::settings_changed),
+                cx.on_app_quit(Self::save_scrollback),
             ],
             _terminal_subscriptions: terminal_subscriptions,
         }
@@ -278,6 +283,38 @@ impl TerminalView {
         self.context_menu = Some((context_menu, position, subscription));
     }
 
+    /// Saves the terminal output on quit, so that it can be replayed when the workspace is restored.
+    fn save_scrollback(&mut self, cx: &mut Context<Self>) -> Task<()> {
+        let Some(workspace_id) = self.workspace_id else {
+            return Task::ready(());
+        };
+        if !TerminalSettings::get_global(cx).restore_scrollback {
+            return Task::ready(());
+        }
+
+        let terminal = self.terminal.read(cx);
+        let scrollback = terminal.scrollback_text();
+        let task_label = terminal
+            .task()
+            .map(|task| task.label.clone())
+            .or_else(|| self.restored_task_label.clone());
+        // Without a known working directory, the one saved earlier is kept.
+        let working_directory = terminal.working_directory();
+        let item_id = cx.entity_id().as_u64();
+        cx.background_executor().spawn(async move {
+            TERMINAL_DB
+                .save_scrollback(
+                    item_id,
+                    workspace_id,
+                    working_directory,
+                    scrollback,
+                    task_label,
+                )
+                .await
+                .log_err();
+        })
+    }
+
     fn settings_changed(&mut self, cx: &mut Context<Self>) {
         let settings = TerminalSettings::get_global(cx);
         self.show_breadcrumbs = settings.toolbar.breadcrumbs;
@@ -457,6 +494,39 @@ impl TerminalView {
         cx.notify();
     }
 
+    fn scroll_to_previous_prompt(
+        &mut self,
+        _: &ScrollToPreviousPrompt,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.terminal
+            .update(cx, |term, _| term.scroll_to_previous_prompt());
+        cx.notify();
+    }
+
+    fn scroll_to_next_prompt(
+        &mut self,
+        _: &ScrollToNextPrompt,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.terminal
+            .update(cx, |term, _| term.scroll_to_next_prompt());
+        cx.notify();
+    }
+
+    fn copy_last_command_output(
+        &mut self,
+        _: &CopyLastCommandOutput,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.terminal
+            .update(cx, |term, _| term.copy_last_command_output());
+        cx.notify();
+    }
+
     fn toggle_vi_mode(&mut self, _: &ToggleViMode, _: &mut Window, cx: &mut Context<Self>) {
         self.terminal.update(cx, |term, _| term.toggle_vi_mode());
         cx.notify();
@@ -836,7 +906,7 @@ fn subscribe_for_terminal_events(
                 }
             }
 
-            Event::TitleChanged => {
+            Event::TitleChanged | Event::BackgroundTaskStatusChanged => {
                 cx.emit(ItemEvent::UpdateTab);
             }
 
@@ -1078,11 +1148,13 @@ fn regex_to_literal(regex: &str) -> String {
 }
 
 pub fn regex_search_for_query(query: &project::search::SearchQuery) -> Option<RegexSearch> {
-    let query = query.as_str();
-    if query == "." {
+    let pattern = query.as_str();
+    if pattern == "." {
         return None;
     }
-    let searcher = RegexSearch::new(query);
+    // Alacritty picks the case sensitivity from the pattern itself, so override it explicitly.
+    let case_flag = if query.case_sensitive() { "-i" } else { "i" };
+    let searcher = RegexSearch::new(&format!("(?{case_flag}){pattern}"));
     searcher.ok()
 }
 
@@ -1146,6 +1218,9 @@ impl Render for TerminalView {
             .on_action(cx.listener(TerminalView::scroll_page_down))
             .on_action(cx.listener(TerminalView::scroll_to_top))
             .on_action(cx.listener(TerminalView::scroll_to_bottom))
+            .on_action(cx.listener(TerminalView::scroll_to_previous_prompt))
+            .on_action(cx.listener(TerminalView::scroll_to_next_prompt))
+            .on_action(cx.listener(TerminalView::copy_last_command_output))
             .on_action(cx.listener(TerminalView::toggle_vi_mode))
             .on_action(cx.listener(TerminalView::show_character_palette))
             .on_action(cx.listener(TerminalView::select_all))
@@ -1213,7 +1288,10 @@ impl Item for TerminalView {
 
     fn tab_content(&self, params: TabContentParams, _window: &Window, cx: &App) -> AnyElement {
         let terminal = self.terminal().read(cx);
-        let title = terminal.title(true);
+        let title = match &self.restored_task_label {
+            Some(task_label) if terminal.task().is_none() => task_label.clone(),
+            _ => terminal.title(true),
+        };
         let rerun_button = |task_id: task::TaskId| {
             IconButton::new("rerun-icon", IconName::Rerun)
                 .icon_size(IconSize::Small)
@@ -1234,13 +1312,41 @@ impl Item for TerminalView {
                 })
         };
 
+        // The task history does not survive restarts, so restored tasks are spawned again by their label.
+        let respawn_button = |task_label: String| {
+            IconButton::new("rerun-icon", IconName::Rerun)
+                .icon_size(IconSize::Small)
+                .size(ButtonSize::Compact)
+                .icon_color(Color::Default)
+                .shape(ui::IconButtonShape::Square)
+                .tooltip(Tooltip::text("Rerun task"))
+                .on_click(move |_, window, cx| {
+                    window.dispatch_action(
+                        Box::new(zed_actions::Spawn::ByName {
+                            task_name: task_label.clone(),
+                            reveal_target: None,
+                        }),
+                        cx,
+                    );
+                })
+        };
+
         let (icon, icon_color, rerun_button) = match terminal.task() {
             Some(terminal_task) => match &terminal_task.status {
-                TaskStatus::Running => (
-                    IconName::Play,
-                    Color::Disabled,
-                    Some(rerun_button(terminal_task.id.clone())),
-                ),
+                TaskStatus::Running => {
+                    let is_ready = terminal_task.background.as_ref().is_some_and(|background| {
+                        background.status() == BackgroundTaskStatus::Ready
+                    });
+                    (
+                        IconName::Play,
+                        if is_ready {
+                            Color::Success
+                        } else {
+                            Color::Disabled
+                        },
+                        Some(rerun_button(terminal_task.id.clone())),
+                    )
+                }
                 TaskStatus::Unknown => (
                     IconName::Warning,
                     Color::Warning,
@@ -1255,7 +1361,14 @@ impl Item for TerminalView {
                     }
                 }
             },
-            None => (IconName::Terminal, Color::Muted, None),
+            None => match &self.restored_task_label {
+                Some(task_label) => (
+                    IconName::HistoryRerun,
+                    Color::Muted,
+                    Some(respawn_button(task_label.clone())),
+                ),
+                None => (IconName::Terminal, Color::Muted, None),
+            },
         };
 
         h_flex()
@@ -1306,6 +1419,7 @@ impl Item for TerminalView {
                 project.create_terminal_with_venv(
                     TerminalKind::Shell(working_directory),
                     python_venv_directory,
+                    None,
                     window_handle,
                     cx,
                 )
@@ -1372,8 +1486,9 @@ impl Item for TerminalView {
                     .spawn(TERMINAL_DB.update_workspace_id(new_id, old_id, cx.entity_id().as_u64()))
                     .detach();
             }
-            self.workspace_id = workspace.database_id();
         }
+        // Task terminals need the workspace id too, for saving their scrollback on quit.
+        self.workspace_id = workspace.database_id();
     }
 
     fn to_item_events(event: &Self::Event, mut f: impl FnMut(ItemEvent)) {
@@ -1435,6 +1550,20 @@ impl SerializableItem for TerminalView {
     ) -> Task<anyhow::Result<Entity<Self>>> {
         let window_handle = window.window_handle();
         window.spawn(cx, |mut cx| async move {
+            let (scrollback, task_label) = cx
+                .update(|_window, cx| {
+                    if TerminalSettings::get_global(cx).restore_scrollback {
+                        TERMINAL_DB
+                            .get_scrollback(item_id, workspace_id)
+                            .log_err()
+                            .flatten()
+                    } else {
+                        None
+                    }
+                })
+                .ok()
+                .flatten()
+                .unwrap_or_default();
             let cwd = cx
                 .update(|_window, cx| {
                     let from_db = TERMINAL_DB
@@ -1457,19 +1586,26 @@ impl SerializableItem for TerminalView {
 
             let terminal = project
                 .update(&mut cx, |project, cx| {
-                    project.create_terminal(TerminalKind::Shell(cwd), window_handle, cx)
+                    project.create_terminal_with_scrollback(
+                        TerminalKind::Shell(cwd),
+                        scrollback,
+                        window_handle,
+                        cx,
+                    )
                 })?
                 .await?;
             cx.update(|window, cx| {
                 cx.new(|cx| {
-                    TerminalView::new(
+                    let mut terminal_view = TerminalView::new(
                         terminal,
                         workspace,
                         Some(workspace_id),
                         project.downgrade(),
                         window,
                         cx,
-                    )
+                    );
+                    terminal_view.restored_task_label = task_label;
+                    terminal_view
                 })
             })
         })
@@ -1481,8 +1617,8 @@ impl SearchableItem for TerminalView {
 
     fn supported_options(&self) -> SearchOptions {
         SearchOptions {
-            case: false,
-            word: false,
+            case: true,
+            word: true,
             regex: true,
             replacement: false,
             selection: false,
@@ -1557,11 +1693,13 @@ impl SearchableItem for TerminalView {
                 .unwrap()),
             ),
             SearchQuery::Regex { .. } => regex_search_for_query(&query),
+            // Terminal content has no syntax tree to match against.
+            SearchQuery::Structural { .. } => None,
         };
 
         if let Some(s) = searcher {
             self.terminal()
-                .update(cx, |term, cx| term.find_matches(s, cx))
+                .update(cx, |term, cx| term.find_matches(s, query.whole_word(), cx))
         } else {
             Task::ready(vec![])
         }
@@ -1304,7 +1304,10 @@ impl ShellExec {
                         shell,
                         show_summary: false,
                         show_command: false,
+                        problem_matchers: Vec::new(),
+                        background: None,
                     }),
+                    completion_tx: None,
                 });
             });
             return;
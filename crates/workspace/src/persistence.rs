@@ -1,6 +1,9 @@
 pub mod model;
 
-use std::{path::Path, str::FromStr};
+use std::{
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
 use anyhow::{anyhow, bail, Context, Result};
 use client::DevServerProjectId;
@@ -8,7 +11,7 @@ use db::{define_connection, query, sqlez::connection::Connection, sqlez_macros::
 use gpui::{point, size, Axis, Bounds, WindowBounds, WindowId};
 
 use language::{LanguageName, Toolchain};
-use project::WorktreeId;
+use project::{bookmark_store::SerializedBookmark, WorktreeId};
 use remote::ssh_session::SshProjectId;
 use sqlez::{
     bindable::{Bind, Column, StaticColumnCount},
@@ -383,6 +386,18 @@ define_connection! {
     sql!(
         ALTER TABLE toolchains ADD COLUMN raw_json TEXT DEFAULT "{}";
     ),
+    sql!(
+        CREATE TABLE bookmarks (
+            workspace_id INTEGER NOT NULL,
+            path BLOB NOT NULL,
+            row INTEGER NOT NULL,
+            label TEXT,
+            PRIMARY KEY (workspace_id, path, row),
+            FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE
+        ) STRICT;
+    ),
     ];
 }
 
@@ -1152,6 +1167,63 @@ impl WorkspaceDb {
             Ok(())
         }).await
     }
+
+    pub(crate) async fn bookmarks(
+        &self,
+        workspace_id: WorkspaceId,
+    ) -> Result<Vec<SerializedBookmark>> {
+        self.write(move |this| {
+            let mut select = this
+                .select_bound(sql!(
+                    SELECT path, row, label FROM bookmarks WHERE workspace_id = ? ORDER BY path, row
+                ))
+                .context("Preparing selection")?;
+
+            let bookmarks: Vec<(PathBuf, u32, Option<String>)> = select(workspace_id)?;
+
+            Ok(bookmarks
+                .into_iter()
+                .map(|(path, row, label)| SerializedBookmark {
+                    abs_path: path.into(),
+                    row,
+                    label,
+                })
+                .collect())
+        })
+        .await
+    }
+
+    /// Replaces all bookmarks stored for the given workspace.
+    pub(crate) async fn set_bookmarks(
+        &self,
+        workspace_id: WorkspaceId,
+        bookmarks: Vec<SerializedBookmark>,
+    ) -> Result<()> {
+        self.write(move |conn| {
+            conn.with_savepoint("update_bookmarks", || {
+                conn.exec_bound(sql!(
+                    DELETE FROM bookmarks WHERE workspace_id = ?
+                ))?(workspace_id)
+                .context("Clearing old bookmarks")?;
+
+                let mut insert = conn
+                    .exec_bound(sql!(
+                        INSERT OR REPLACE INTO bookmarks(workspace_id, path, row, label) VALUES (?, ?, ?, ?)
+                    ))
+                    .context("Preparing insertion")?;
+                for bookmark in bookmarks {
+                    insert((
+                        workspace_id,
+                        bookmark.abs_path.as_ref(),
+                        bookmark.row,
+                        bookmark.label,
+                    ))?;
+                }
+                Ok(())
+            })
+        })
+        .await
+    }
 }
 
 #[cfg(test)]
@@ -1376,6 +1448,57 @@ mod tests {
         assert_eq!(workspace, round_trip_workspace.unwrap());
     }
 
+    #[gpui::test]
+    async fn test_bookmarks() {
+        env_logger::try_init().ok();
+
+        let db = WorkspaceDb(open_test_db("test_bookmarks").await);
+
+        let workspace = SerializedWorkspace {
+            id: WorkspaceId(1),
+            location: SerializedWorkspaceLocation::Local(
+                LocalPaths::new(["/tmp"]),
+                LocalPathsOrder::new([0]),
+            ),
+            center_group: Default::default(),
+            window_bounds: Default::default(),
+            display: Default::default(),
+            docks: Default::default(),
+            centered_layout: false,
+            session_id: None,
+            window_id: Some(1),
+        };
+        db.save_workspace(workspace.clone()).await;
+
+        let bookmark = |path: &str, row, label: Option<&str>| SerializedBookmark {
+            abs_path: Path::new(path).into(),
+            row,
+            label: label.map(ToString::to_string),
+        };
+        let bookmarks = vec![
+            bookmark("/tmp/a.rs", 3, None),
+            bookmark("/tmp/a.rs", 10, Some("todo")),
+            bookmark("/tmp/b.rs", 0, None),
+        ];
+        db.set_bookmarks(workspace.id, bookmarks.clone())
+            .await
+            .unwrap();
+        assert_eq!(db.bookmarks(workspace.id).await.unwrap(), bookmarks);
+
+        // Saving bookmarks again replaces the previous ones.
+        db.set_bookmarks(workspace.id, vec![bookmark("/tmp/b.rs", 1, None)])
+            .await
+            .unwrap();
+        assert_eq!(
+            db.bookmarks(workspace.id).await.unwrap(),
+            vec![bookmark("/tmp/b.rs", 1, None)]
+        );
+
+        // Bookmarks are removed along with their workspace.
+        db.delete_workspace_by_id(workspace.id).await.unwrap();
+        assert_eq!(db.bookmarks(workspace.id).await.unwrap(), vec![]);
+    }
+
     #[gpui::test]
     async fn test_workspace_assignment() {
         env_logger::try_init().ok();
@@ -1,9 +1,67 @@
-use gpui::Context;
+use anyhow::anyhow;
+use futures::{channel::mpsc, StreamExt as _};
+use gpui::{App, Context, Global, SharedString, Window};
 use project::TaskSourceKind;
 use remote::ConnectionState;
-use task::{ResolvedTask, TaskContext, TaskTemplate};
+use task::{run_with_dependencies, ResolvedTask, TaskContext, TaskTemplate};
 
-use crate::Workspace;
+use crate::{notifications::NotificationId, Toast, Workspace};
+
+/// Asks the user for the values of the inputs referenced by a task and the tasks it depends on,
+/// then schedules the task with them.
+pub type TaskInputsPrompt = fn(
+    &mut Workspace,
+    TaskSourceKind,
+    TaskTemplate,
+    TaskContext,
+    bool,
+    &mut Window,
+    &mut Context<Workspace>,
+);
+
+struct GlobalTaskInputsPrompt(TaskInputsPrompt);
+
+impl Global for GlobalTaskInputsPrompt {}
+
+/// Sets the way [`schedule_task_with_inputs`] asks for the values of task inputs.
+pub fn register_task_inputs_prompt(prompt: TaskInputsPrompt, cx: &mut App) {
+    cx.set_global(GlobalTaskInputsPrompt(prompt));
+}
+
+/// Schedules the task, asking for the values of its inputs first, if it has any.
+/// Every task spawned from a template should go through here, so that no `${input:id}` reaches the terminal.
+pub fn schedule_task_with_inputs(
+    workspace: &mut Workspace,
+    task_source_kind: TaskSourceKind,
+    task: TaskTemplate,
+    task_context: TaskContext,
+    omit_history: bool,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let prompt = cx
+        .try_global::<GlobalTaskInputsPrompt>()
+        .map(|prompt| prompt.0);
+    match prompt {
+        Some(prompt) => prompt(
+            workspace,
+            task_source_kind,
+            task,
+            task_context,
+            omit_history,
+            window,
+            cx,
+        ),
+        None => schedule_task(
+            workspace,
+            task_source_kind,
+            &task,
+            &task_context,
+            omit_history,
+            cx,
+        ),
+    }
+}
 
 pub fn schedule_task(
     workspace: &mut Workspace,
@@ -26,9 +84,31 @@ pub fn schedule_task(
         }
     }
 
-    if let Some(spawn_in_terminal) =
+    if let Some(mut spawn_in_terminal) =
         task_to_resolve.resolve_task(&task_source_kind.to_id_base(), task_cx)
     {
+        let worktree = match &task_source_kind {
+            TaskSourceKind::Worktree { id, .. } => Some(*id),
+            _ => None,
+        };
+        let resolved_dependencies = workspace
+            .project()
+            .read(cx)
+            .task_store()
+            .read(cx)
+            .task_inventory()
+            .map_or(Ok(()), |inventory| {
+                inventory.read(cx).resolve_task_dependencies(
+                    &mut spawn_in_terminal,
+                    worktree,
+                    task_cx,
+                )
+            });
+        if let Err(e) = resolved_dependencies {
+            show_task_error(workspace, &spawn_in_terminal, &e, cx);
+            return;
+        }
+
         schedule_resolved_task(
             workspace,
             task_source_kind,
@@ -46,22 +126,109 @@ pub fn schedule_resolved_task(
     omit_history: bool,
     cx: &mut Context<Workspace>,
 ) {
+    if let Some(input_id) = resolved_task.unresolved_input() {
+        let error = anyhow!(
+            "Task `{}` cannot run without a value for its input `{input_id}`",
+            resolved_task.resolved_label
+        );
+        show_task_error(workspace, &resolved_task, &error, cx);
+        return;
+    }
+
+    if !resolved_task.original_task().depends_on.is_empty() {
+        schedule_task_with_dependencies(
+            workspace,
+            task_source_kind,
+            resolved_task,
+            omit_history,
+            cx,
+        );
+        return;
+    }
+
     if let Some(spawn_in_terminal) = resolved_task.resolved.take() {
         if !omit_history {
             resolved_task.resolved = Some(spawn_in_terminal.clone());
-            workspace.project().update(cx, |project, cx| {
-                if let Some(task_inventory) =
-                    project.task_store().read(cx).task_inventory().cloned()
-                {
-                    task_inventory.update(cx, |inventory, _| {
-                        inventory.task_scheduled(task_source_kind, resolved_task);
-                    })
-                }
-            });
+            record_scheduled_task(workspace, task_source_kind, resolved_task, cx);
         }
 
         cx.emit(crate::Event::SpawnTask {
             action: Box::new(spawn_in_terminal),
+            completion_tx: None,
         });
     }
 }
+
+/// Runs the task after the tasks it depends on, as a single run: the task is only spawned when all of its
+/// dependencies succeed, and the first failure is reported for the whole run.
+fn schedule_task_with_dependencies(
+    workspace: &mut Workspace,
+    task_source_kind: TaskSourceKind,
+    resolved_task: ResolvedTask,
+    omit_history: bool,
+    cx: &mut Context<Workspace>,
+) {
+    if !omit_history {
+        record_scheduled_task(workspace, task_source_kind, resolved_task.clone(), cx);
+    }
+
+    cx.spawn(|workspace, mut cx| async move {
+        let spawn_cx = cx.clone();
+        let spawn = |task: &ResolvedTask| {
+            let (completion_tx, mut completion_rx) = mpsc::unbounded();
+            let spawned = task.resolved.clone().is_some_and(|spawn_in_terminal| {
+                workspace
+                    .update(&mut spawn_cx.clone(), |_, cx| {
+                        cx.emit(crate::Event::SpawnTask {
+                            action: Box::new(spawn_in_terminal),
+                            completion_tx: Some(completion_tx),
+                        })
+                    })
+                    .is_ok()
+            });
+            async move { spawned && completion_rx.next().await.unwrap_or(false) }
+        };
+
+        if let Err(e) = run_with_dependencies(&resolved_task, &spawn).await {
+            workspace
+                .update(&mut cx, |workspace, cx| {
+                    show_task_error(workspace, &resolved_task, &e, cx);
+                })
+                .ok();
+        }
+    })
+    .detach();
+}
+
+fn record_scheduled_task(
+    workspace: &mut Workspace,
+    task_source_kind: TaskSourceKind,
+    resolved_task: ResolvedTask,
+    cx: &mut Context<Workspace>,
+) {
+    workspace.project().update(cx, |project, cx| {
+        if let Some(task_inventory) = project.task_store().read(cx).task_inventory().cloned() {
+            task_inventory.update(cx, |inventory, _| {
+                inventory.task_scheduled(task_source_kind, resolved_task);
+            })
+        }
+    });
+}
+
+fn show_task_error(
+    workspace: &mut Workspace,
+    task: &ResolvedTask,
+    error: &anyhow::Error,
+    cx: &mut Context<Workspace>,
+) {
+    struct TaskError;
+
+    log::error!("Task `{}` did not succeed: {error:#}", task.resolved_label);
+    workspace.show_toast(
+        Toast::new(
+            NotificationId::composite::<TaskError>(SharedString::from(task.id.0.clone())),
+            format!("{error:#}"),
+        ),
+        cx,
+    );
+}
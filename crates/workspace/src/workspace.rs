@@ -60,7 +60,8 @@ use persistence::{
 };
 use postage::stream::Stream;
 use project::{
-    DirectoryLister, Project, ProjectEntryId, ProjectPath, ResolvedPath, Worktree, WorktreeId,
+    bookmark_store::BookmarkStoreEvent, DirectoryLister, Project, ProjectEntryId, ProjectPath,
+    ResolvedPath, Worktree, WorktreeId,
 };
 use remote::{ssh_session::ConnectionIdentifier, SshClientDelegate, SshConnectionOptions};
 use schemars::JsonSchema;
@@ -747,6 +748,8 @@ pub enum Event {
     WorkspaceCreated(WeakEntity<Workspace>),
     SpawnTask {
         action: Box<SpawnInTerminal>,
+        /// Receives whether the task succeeded, once it finishes.
+        completion_tx: Option<UnboundedSender<bool>>,
     },
     OpenBundledFile {
         text: Cow<'static, str>,
@@ -1076,8 +1079,39 @@ impl Workspace {
                     store.workspaces.remove(&window_handle.clone());
                 })
             }),
+            cx.subscribe(
+                project.read(cx).bookmark_store(),
+                |this, bookmark_store, _: &BookmarkStoreEvent, cx| {
+                    let bookmark_store = bookmark_store.read(cx);
+                    // Until the previous bookmarks are restored, persisting would overwrite them.
+                    if bookmark_store.is_restoring() {
+                        return;
+                    }
+                    if let Some(database_id) = this.database_id {
+                        let bookmarks = bookmark_store.serialize(cx);
+                        cx.background_executor()
+                            .spawn(DB.set_bookmarks(database_id, bookmarks))
+                            .detach_and_log_err(cx);
+                    }
+                },
+            ),
         ];
 
+        if let Some(database_id) = workspace_id {
+            let bookmark_store = project.read(cx).bookmark_store().clone();
+            bookmark_store.update(cx, |store, _| store.start_restoring());
+            cx.spawn(|_, mut cx| async move {
+                // The bookmarks are only persisted once restoring is done, even if it failed.
+                let bookmarks = DB
+                    .bookmarks(database_id)
+                    .await
+                    .log_err()
+                    .unwrap_or_default();
+                bookmark_store.update(&mut cx, |store, cx| store.restore(bookmarks, cx))
+            })
+            .detach_and_log_err(cx);
+        }
+
         cx.defer_in(window, |this, window, cx| {
             this.update_window_title(window, cx);
             this.show_initial_notifications(cx);
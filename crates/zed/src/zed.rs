@@ -38,9 +38,10 @@ use release_channel::{AppCommitSha, ReleaseChannel};
 use rope::Rope;
 use search::project_search::ProjectSearchBar;
 use settings::{
-    initial_project_settings_content, initial_tasks_content, update_settings_file,
-    InvalidSettingsError, KeymapFile, KeymapFileLoadResult, Settings, SettingsStore,
-    DEFAULT_KEYMAP_PATH, VIM_KEYMAP_PATH,
+    initial_project_settings_content, initial_tasks_content, update_settings_file, ImportSource,
+    InvalidSettingsError, KeybindSource, KeybindUpdateOperation, KeybindUpdateTarget, KeymapFile,
+    KeymapFileLoadResult, Settings, SettingsFile, SettingsStore, DEFAULT_KEYMAP_PATH,
+    VIM_KEYMAP_PATH,
 };
 use std::any::TypeId;
 use std::path::PathBuf;
@@ -72,6 +73,7 @@ actions!(
         DebugElements,
         Hide,
         HideOthers,
+        ImportConfig,
         Minimize,
         OpenDefaultSettings,
         OpenProjectSettings,
@@ -693,6 +695,7 @@ fn register_actions(
         )
         .register_action(open_project_settings_file)
         .register_action(open_project_tasks_file)
+        .register_action(import_config)
         .register_action(
             move |workspace: &mut Workspace,
                   _: &zed_actions::OpenDefaultKeymap,
@@ -1236,7 +1239,7 @@ fn show_keymap_file_load_error(
 fn reload_keymaps(cx: &mut App, user_key_bindings: Vec<KeyBinding>) {
     cx.clear_key_bindings();
     load_default_keymap(cx);
-    cx.bind_keys(user_key_bindings);
+    cx.bind_keys(with_keybind_source(user_key_bindings, KeybindSource::User));
     cx.set_menus(app_menus());
     cx.set_dock_menu(vec![MenuItem::action("New Window", workspace::NewWindow)]);
 }
@@ -1247,17 +1250,36 @@ pub fn load_default_keymap(cx: &mut App) {
         return;
     }
 
-    cx.bind_keys(KeymapFile::load_asset(DEFAULT_KEYMAP_PATH, cx).unwrap());
+    cx.bind_keys(with_keybind_source(
+        KeymapFile::load_asset(DEFAULT_KEYMAP_PATH, cx).unwrap(),
+        KeybindSource::Default,
+    ));
 
     if let Some(asset_path) = base_keymap.asset_path() {
-        cx.bind_keys(KeymapFile::load_asset(asset_path, cx).unwrap());
+        cx.bind_keys(with_keybind_source(
+            KeymapFile::load_asset(asset_path, cx).unwrap(),
+            KeybindSource::Base,
+        ));
     }
 
     if VimModeSetting::get_global(cx).0 {
-        cx.bind_keys(KeymapFile::load_asset(VIM_KEYMAP_PATH, cx).unwrap());
+        cx.bind_keys(with_keybind_source(
+            KeymapFile::load_asset(VIM_KEYMAP_PATH, cx).unwrap(),
+            KeybindSource::Vim,
+        ));
     }
 }
 
+/// Records the keymap the bindings come from, so that it can be shown in the keymap editor.
+fn with_keybind_source(
+    bindings: Vec<KeyBinding>,
+    source: KeybindSource,
+) -> impl Iterator<Item = KeyBinding> {
+    bindings
+        .into_iter()
+        .map(move |binding| binding.with_meta(source.meta()))
+}
+
 pub fn handle_settings_changed(error: Option<anyhow::Error>, cx: &mut App) {
     struct SettingsParseErrorNotification;
     let id = NotificationId::unique::<SettingsParseErrorNotification>();
@@ -1343,6 +1365,148 @@ fn open_project_tasks_file(
     )
 }
 
+/// Imports the settings or key bindings of another editor into the user settings and keymap,
+/// then opens a report of what couldn't be imported.
+fn import_config(
+    workspace: &mut Workspace,
+    _: &ImportConfig,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let fs = workspace.app_state().fs.clone();
+    let path_prompt = workspace.prompt_for_open_path(
+        PathPromptOptions {
+            files: true,
+            directories: false,
+            multiple: false,
+        },
+        DirectoryLister::Local(fs.clone()),
+        window,
+        cx,
+    );
+    cx.spawn_in(window, |workspace, mut cx| async move {
+        let Some(path) = path_prompt
+            .await
+            .log_err()
+            .flatten()
+            .and_then(|paths| paths.into_iter().next())
+        else {
+            return Ok(());
+        };
+        if path == *paths::settings_file() || path == *paths::keymap_file() {
+            anyhow::bail!("{path:?} is already a Zed config");
+        }
+        let source = match ImportSource::detect(&path) {
+            Some(source) => source,
+            None => {
+                let mut answers = ImportSource::ALL.map(|source| source.name()).to_vec();
+                answers.push("Cancel");
+                let answer = cx
+                    .prompt(
+                        PromptLevel::Info,
+                        "Which editor's config is this?",
+                        Some(&path.to_string_lossy()),
+                        &answers,
+                    )
+                    .await?;
+                match ImportSource::ALL.get(answer) {
+                    Some(source) => *source,
+                    None => return Ok(()),
+                }
+            }
+        };
+        let content = fs.load(&path).await?;
+        let imported = source.import(&content)?;
+
+        let enable_vim_mode = source == ImportSource::Vimrc
+            && !cx.update(|_, cx| VimModeSetting::get_global(cx).0)?
+            && cx
+                .prompt(
+                    PromptLevel::Info,
+                    "Enable Vim mode?",
+                    Some("The imported key mappings only apply in Vim mode."),
+                    &["Enable", "Keep Disabled"],
+                )
+                .await?
+                == 0;
+
+        // The keymap is read before anything is changed, so that the import stops if it can't be
+        // read. Only a missing keymap is empty: rewriting one that failed to load would lose it.
+        let keymap_path = paths::keymap_file();
+        let keymap_exists = fs.is_file(keymap_path).await;
+        let mut keymap_contents = if imported.bindings.is_empty() || !keymap_exists {
+            String::new()
+        } else {
+            fs.load(keymap_path)
+                .await
+                .with_context(|| format!("Failed to read the keymap {keymap_path:?}"))?
+        };
+
+        let tab_size = cx.update(|_, cx| {
+            let store = cx.global::<SettingsStore>();
+            for setting in &imported.settings {
+                store.update_settings_file_at_key_path(
+                    fs.clone(),
+                    SettingsFile::User,
+                    setting.key_path.clone(),
+                    setting.value.clone(),
+                );
+            }
+            if enable_vim_mode {
+                store.update_settings_file_at_key_path(
+                    fs.clone(),
+                    SettingsFile::User,
+                    vec!["vim_mode".into()],
+                    true.into(),
+                );
+            }
+            store.json_tab_size()
+        })?;
+
+        if !imported.bindings.is_empty() {
+            for binding in &imported.bindings {
+                let target = KeybindUpdateTarget {
+                    context: binding.context.as_deref(),
+                    keystrokes: &binding.keystrokes,
+                    action_name: binding
+                        .action
+                        .as_ref()
+                        .map_or("zed::NoAction", |action| action.name.as_str()),
+                    action_input: binding
+                        .action
+                        .as_ref()
+                        .and_then(|action| action.input.as_deref()),
+                };
+                let operation = if binding.action.is_some() {
+                    KeybindUpdateOperation::Add(target)
+                } else {
+                    KeybindUpdateOperation::Remove {
+                        source: KeybindSource::Default,
+                        target,
+                    }
+                };
+                keymap_contents =
+                    KeymapFile::update_keybinding(operation, keymap_contents, tab_size)?;
+            }
+            fs.atomic_write(keymap_path.clone(), keymap_contents)
+                .await?;
+        }
+
+        let report = imported.report(source);
+        workspace.update_in(&mut cx, |workspace, window, cx| {
+            open_bundled_file(
+                workspace,
+                report.into(),
+                "Import Report",
+                "Markdown",
+                window,
+                cx,
+            );
+        })
+    })
+    .detach_and_prompt_err("Failed to import the config", window, cx, |_, _, _| None);
+}
+
 fn open_local_file(
     workspace: &mut Workspace,
     settings_relative_path: &'static Path,